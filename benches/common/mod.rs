@@ -0,0 +1,11 @@
+//! Helpers shared by the bench targets
+
+use std::path::PathBuf;
+
+/// Read a committed fixture IDL from `benches/fixtures`
+pub fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("benches/fixtures")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e))
+}
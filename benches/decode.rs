@@ -0,0 +1,123 @@
+//! Decode engine benchmarks
+//!
+//! Run with `cargo bench --bench decode`. Covers IDL account decompression,
+//! discriminator lookup (indexed vs a linear scan) and Borsh decode
+//! throughput for accounts and instructions of the `amm` fixture.
+
+mod common;
+
+use common::fixture;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use periscope::idl::{decompress_idl_data, parse_idl_json};
+use periscope::{decode_account, decode_instruction};
+use std::io::Write;
+
+/// Borsh-encoded `Pool` account of the fixture with `rewards` reward entries
+fn pool_account(discriminator: &[u8], rewards: usize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&[7; 32]); // authority
+    data.push(254); // bump
+    data.extend_from_slice(&[1; 32]); // mint_a
+    data.extend_from_slice(&[2; 32]); // mint_b
+    data.extend_from_slice(&1_000_000u64.to_le_bytes()); // reserve_a
+    data.extend_from_slice(&2_000_000u64.to_le_bytes()); // reserve_b
+    data.extend_from_slice(&30u16.to_le_bytes()); // fee_bps
+    data.extend_from_slice(&(rewards as u32).to_le_bytes());
+    for i in 0..rewards {
+        data.extend_from_slice(&[3; 32]); // mint
+        data.extend_from_slice(&(i as u128).to_le_bytes()); // emissions_per_second
+        data.extend_from_slice(&(i as u128 * 2).to_le_bytes()); // growth
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes()); // last_update
+    }
+    data.extend_from_slice(&5u64.to_le_bytes()); // params.min_out
+    data.extend_from_slice(&1_700_000_000i64.to_le_bytes()); // params.deadline
+    data.push(1); // params.side
+    for i in 0..8u64 {
+        data.extend_from_slice(&i.to_le_bytes()); // history
+    }
+    data.push(1);
+    data.extend_from_slice(&4u32.to_le_bytes());
+    data.extend_from_slice(b"main"); // label
+    data
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let json = fixture("amm.json");
+    let idl = parse_idl_json(&json).unwrap();
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Bytes(json.len() as u64));
+    group.bench_function("decompress amm IDL account", |b| {
+        b.iter(|| decompress_idl_data(&compressed).unwrap())
+    });
+    group.finish();
+
+    // Last entries are the worst case for a linear scan
+    let last_ix = idl.instructions.last().unwrap().discriminator_bytes();
+    let last_account = idl.accounts.last().unwrap().discriminator_bytes();
+    idl.index();
+    let mut group = c.benchmark_group("lookup");
+    group.bench_function("instruction, linear scan", |b| {
+        b.iter(|| {
+            idl.instructions
+                .iter()
+                .find(|ix| ix.discriminator_bytes() == last_ix)
+                .map(|ix| ix.name.len())
+        })
+    });
+    group.bench_function("instruction, indexed", |b| {
+        b.iter(|| {
+            idl.find_instruction_by_discriminator(&last_ix)
+                .map(|ix| ix.name.len())
+        })
+    });
+    group.bench_function("account, indexed", |b| {
+        b.iter(|| {
+            idl.find_account_by_discriminator(&last_account)
+                .map(|a| a.name.len())
+        })
+    });
+    group.finish();
+
+    let pool_discriminator = idl
+        .accounts
+        .iter()
+        .find(|a| a.name == "Pool")
+        .unwrap()
+        .discriminator_bytes();
+    let small = pool_account(&pool_discriminator, 2);
+    let large = pool_account(&pool_discriminator, 1_000);
+    let mut group = c.benchmark_group("borsh");
+    group.throughput(Throughput::Bytes(small.len() as u64));
+    group.bench_function("decode Pool (2 rewards)", |b| {
+        b.iter(|| decode_account(&idl, &small).unwrap())
+    });
+    group.throughput(Throughput::Bytes(large.len() as u64));
+    group.bench_function("decode Pool (1000 rewards)", |b| {
+        b.iter(|| decode_account(&idl, &large).unwrap())
+    });
+
+    let mut swap = idl
+        .find_instruction("swap_pool")
+        .unwrap()
+        .discriminator_bytes();
+    swap.extend_from_slice(&100u64.to_le_bytes()); // amount
+    swap.extend_from_slice(&95u64.to_le_bytes()); // min_out
+    swap.extend_from_slice(&5u64.to_le_bytes()); // params.min_out
+    swap.extend_from_slice(&1_700_000_000i64.to_le_bytes()); // params.deadline
+    swap.push(0); // params.side
+    group.throughput(Throughput::Bytes(swap.len() as u64));
+    group.bench_function("decode swap_pool args", |b| {
+        b.iter(|| decode_instruction(&idl, &swap).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! IDL parsing benchmarks
+//!
+//! Run with `cargo bench --bench parse`. Covers the committed fixtures in
+//! both formats (legacy parsing includes conversion to the 0.1.0 spec) and a
+//! synthetic ~1 MB IDL for the large-IDL case.
+
+mod common;
+
+use common::fixture;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use periscope::idl::{parse_idl_json, IdlNames};
+use serde_json::{json, Value};
+
+const ITEMS: usize = 2_000;
+
+/// Build an IDL with `ITEMS` instructions and types, each with docs and fields
+fn large_idl() -> String {
+    let instructions: Vec<Value> = (0..ITEMS)
+        .map(|i| {
+            json!({
+                "name": format!("instruction_{i}"),
+                "docs": ["Does something fairly involved with a long explanation attached"],
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [
+                    { "name": "authority", "writable": true, "signer": true },
+                    { "name": "pool", "writable": true },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "data", "type": { "vec": "u8" } }
+                ]
+            })
+        })
+        .collect();
+    let types: Vec<Value> = (0..ITEMS)
+        .map(|i| {
+            json!({
+                "name": format!("Type{i}"),
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "owner", "type": "pubkey" },
+                        { "name": "balances", "type": { "array": ["u64", 16] } },
+                        { "name": "label", "type": { "option": "string" } }
+                    ]
+                }
+            })
+        })
+        .collect();
+
+    json!({
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "large", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": instructions,
+        "types": types,
+    })
+    .to_string()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let amm = fixture("amm.json");
+    let legacy = fixture("amm_legacy.json");
+    let large = large_idl();
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(amm.len() as u64));
+    group.bench_function("fixture amm (0.1.0)", |b| {
+        b.iter(|| parse_idl_json(&amm).unwrap())
+    });
+    group.throughput(Throughput::Bytes(legacy.len() as u64));
+    group.bench_function("fixture amm (legacy + conversion)", |b| {
+        b.iter(|| parse_idl_json(&legacy).unwrap())
+    });
+    group.throughput(Throughput::Bytes(large.len() as u64));
+    group.bench_function("large: value tree (baseline)", |b| {
+        b.iter(|| serde_json::from_str::<Value>(&large).unwrap())
+    });
+    group.bench_function("large: full parse", |b| {
+        b.iter(|| parse_idl_json(&large).unwrap())
+    });
+    group.bench_function("large: names only (zero-copy)", |b| {
+        b.iter(|| {
+            IdlNames::parse(&large)
+                .map(|names| names.instructions.len())
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
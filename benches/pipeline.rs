@@ -0,0 +1,105 @@
+//! Multi-IDL pipeline benchmarks
+//!
+//! Run with `cargo bench --bench pipeline`. Decompresses and parses the IDL
+//! accounts of `PROGRAMS` programs one after another and with the
+//! concurrent (rayon) pipeline; the speedup grows with the number of cores.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use flate2::write::ZlibEncoder;
+use periscope::idl::{
+    decompress_idl_data, idl_account_payload, parse_idl_json, DEFAULT_MAX_IDL_SIZE,
+};
+use periscope::pipeline::{decode_idl_accounts, parse_idls};
+use serde_json::{json, Value};
+use std::io::Write;
+
+const PROGRAMS: usize = 24;
+const ITEMS: usize = 300;
+
+/// IDL JSON of a mid-sized program
+fn program_idl(program: usize) -> String {
+    let instructions: Vec<Value> = (0..ITEMS)
+        .map(|i| {
+            json!({
+                "name": format!("instruction_{i}"),
+                "docs": ["Does something fairly involved with a long explanation attached"],
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [
+                    { "name": "authority", "writable": true, "signer": true },
+                    { "name": "pool", "writable": true }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "data", "type": { "vec": "u8" } }
+                ]
+            })
+        })
+        .collect();
+    let types: Vec<Value> = (0..ITEMS)
+        .map(|i| {
+            json!({
+                "name": format!("Type{i}"),
+                "type": { "kind": "struct", "fields": [
+                    { "name": "owner", "type": "pubkey" },
+                    { "name": "balances", "type": { "array": ["u64", 16] } }
+                ] }
+            })
+        })
+        .collect();
+
+    json!({
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": format!("program_{program}"), "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": instructions,
+        "types": types,
+    })
+    .to_string()
+}
+
+/// IDL account data: discriminator, authority, length, zlib-compressed JSON
+fn idl_account(json: &str) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut data = vec![0u8; 40];
+    data.extend((compressed.len() as u32).to_le_bytes());
+    data.extend(compressed);
+    data
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let jsons: Vec<String> = (0..PROGRAMS).map(program_idl).collect();
+    let accounts: Vec<Vec<u8>> = jsons.iter().map(|json| idl_account(json)).collect();
+    let bytes: usize = jsons.iter().map(String::len).sum();
+
+    let mut group = c.benchmark_group("pipeline");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.bench_function("parse: serial", |b| {
+        b.iter(|| {
+            jsons
+                .iter()
+                .map(|json| parse_idl_json(json).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("parse: concurrent", |b| b.iter(|| parse_idls(&jsons)));
+    group.bench_function("decompress + parse: serial", |b| {
+        b.iter(|| {
+            accounts
+                .iter()
+                .map(|data| {
+                    let json = decompress_idl_data(idl_account_payload(data).unwrap()).unwrap();
+                    parse_idl_json(std::str::from_utf8(&json).unwrap()).unwrap()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("decompress + parse: concurrent", |b| {
+        b.iter(|| decode_idl_accounts(&accounts, DEFAULT_MAX_IDL_SIZE))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_pipeline);
+criterion_main!(benches);
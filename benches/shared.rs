@@ -0,0 +1,103 @@
+//! Concurrent decode benchmarks
+//!
+//! Run with `cargo bench --bench shared`. Decodes a batch of instruction
+//! payloads on one thread and across worker threads sharing a `SharedIdl`,
+//! and compares the cost of handing workers a `SharedIdl` vs a cloned `Idl`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use periscope::{decode_instruction, Idl, SharedIdl};
+use serde_json::{json, Value};
+use std::hint::black_box;
+use std::thread;
+
+const INSTRUCTIONS: usize = 500;
+const PAYLOADS: usize = 200_000;
+
+/// IDL with `INSTRUCTIONS` instructions taking a nested struct argument
+fn idl() -> Idl {
+    let instructions: Vec<Value> = (0..INSTRUCTIONS)
+        .map(|i| {
+            json!({
+                "name": format!("instruction_{i}"),
+                "discriminator": (i as u64).to_le_bytes(),
+                "accounts": [],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "params", "type": { "defined": { "name": "Params" } } }
+                ]
+            })
+        })
+        .collect();
+
+    serde_json::from_value(json!({
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "bench", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": instructions,
+        "types": [{
+            "name": "Params",
+            "type": { "kind": "struct", "fields": [
+                { "name": "min_out", "type": "u64" },
+                { "name": "deadline", "type": "i64" },
+                { "name": "memo", "type": { "option": "string" } }
+            ] }
+        }]
+    }))
+    .unwrap()
+}
+
+fn payloads() -> Vec<Vec<u8>> {
+    (0..PAYLOADS)
+        .map(|i| {
+            let mut data = ((i % INSTRUCTIONS) as u64).to_le_bytes().to_vec();
+            data.extend_from_slice(&(i as u64).to_le_bytes());
+            data.extend_from_slice(&1u64.to_le_bytes());
+            data.extend_from_slice(&2i64.to_le_bytes());
+            data.push(0);
+            data
+        })
+        .collect()
+}
+
+fn bench_shared(c: &mut Criterion) {
+    let shared = SharedIdl::new(idl());
+    let payloads = payloads();
+    let workers = thread::available_parallelism().map_or(4, |n| n.get());
+
+    let mut group = c.benchmark_group("shared");
+    // Each iteration decodes every payload
+    group.sample_size(10);
+    group.bench_function(format!("decode {} payloads, 1 thread", PAYLOADS), |b| {
+        b.iter(|| {
+            for data in &payloads {
+                black_box(decode_instruction(&shared, data).unwrap());
+            }
+        })
+    });
+    group.bench_function(
+        format!("decode {} payloads, {} workers", PAYLOADS, workers),
+        |b| {
+            b.iter(|| {
+                let chunk = payloads.len().div_ceil(workers);
+                thread::scope(|s| {
+                    for batch in payloads.chunks(chunk) {
+                        let idl = shared.clone();
+                        s.spawn(move || {
+                            for data in batch {
+                                black_box(decode_instruction(&idl, data).unwrap());
+                            }
+                        });
+                    }
+                });
+            })
+        },
+    );
+    group.finish();
+
+    let mut group = c.benchmark_group("hand out");
+    group.bench_function("SharedIdl", |b| b.iter(|| black_box(shared.clone())));
+    group.bench_function("cloned Idl", |b| b.iter(|| black_box((*shared).clone())));
+    group.finish();
+}
+
+criterion_group!(benches, bench_shared);
+criterion_main!(benches);
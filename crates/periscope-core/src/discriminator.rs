@@ -0,0 +1,55 @@
+//! Anchor discriminator derivation
+//!
+//! Anchor prefixes accounts, instructions and events with the first 8 bytes
+//! of `sha256("<namespace>:<name>")`. Legacy IDLs don't store them, so they
+//! are recomputed here when needed.
+
+use alloc::format;
+use sha2::{Digest, Sha256};
+
+/// Discriminator length in bytes
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+const ACCOUNT_NAMESPACE: &str = "account";
+const INSTRUCTION_NAMESPACE: &str = "global";
+const EVENT_NAMESPACE: &str = "event";
+
+/// Compute the discriminator of an account type (e.g. `Pool`).
+pub fn account_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    sighash(ACCOUNT_NAMESPACE, name)
+}
+
+/// Compute the discriminator of an instruction (snake_case name, e.g. `swap_exact_in`).
+pub fn instruction_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    sighash(INSTRUCTION_NAMESPACE, name)
+}
+
+/// Compute the discriminator of an event type (e.g. `SwapEvent`).
+pub fn event_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    sighash(EVENT_NAMESPACE, name)
+}
+
+fn sighash(namespace: &str, name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let preimage = format!("{}:{}", namespace, name);
+    let mut out = [0u8; DISCRIMINATOR_LEN];
+    out.copy_from_slice(&Sha256::digest(preimage.as_bytes())[..DISCRIMINATOR_LEN]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_discriminators() {
+        // sha256("global:initialize")[..8], as generated by anchor-lang
+        assert_eq!(
+            instruction_discriminator("initialize"),
+            [175, 175, 109, 31, 13, 152, 155, 237]
+        );
+        assert_eq!(
+            account_discriminator("IdlAccount"),
+            [140, 36, 166, 2, 103, 197, 33, 164]
+        );
+    }
+}
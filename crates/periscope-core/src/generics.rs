@@ -0,0 +1,201 @@
+//! Instantiation of generic type definitions
+//!
+//! A field of type `PodOption<u64>` refers to the definition of
+//! `PodOption<T>`, whose fields mention the parameter `T`. Substituting the
+//! arguments for the parameters gives an ordinary definition with the layout
+//! of the instantiated type, so decoders and encoders never see a generic.
+
+use crate::types::{
+    IdlArrayLen, IdlEnumFields, IdlField, IdlGenericArg, IdlType, IdlTypeComplex, IdlTypeDef,
+    IdlTypeDefGeneric, IdlTypeDefTy,
+};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+impl IdlTypeDef {
+    /// This definition with `args` substituted for its generic parameters
+    ///
+    /// Fails when the number or kinds of the arguments don't match the
+    /// parameters, or a const argument isn't a length.
+    pub fn instantiate(&self, args: &[IdlGenericArg]) -> Result<IdlTypeDef, String> {
+        if args.len() != self.generics.len() {
+            return Err(format!(
+                "{} takes {} generic arguments, got {}",
+                self.name,
+                self.generics.len(),
+                args.len()
+            ));
+        }
+        if args.is_empty() {
+            return Ok(self.clone());
+        }
+        for (param, arg) in self.generics.iter().zip(args) {
+            match (param, arg) {
+                (IdlTypeDefGeneric::Type { .. }, IdlGenericArg::Type { .. }) => {}
+                (IdlTypeDefGeneric::Const { name, .. }, IdlGenericArg::Const { value }) => {
+                    if value.trim().parse::<usize>().is_err() {
+                        return Err(format!(
+                            "{}: const argument {} = '{}' is not a length",
+                            self.name, name, value
+                        ));
+                    }
+                }
+                (param, _) => {
+                    return Err(format!(
+                        "{}: argument for {} has the wrong kind",
+                        self.name,
+                        param.name()
+                    ))
+                }
+            }
+        }
+
+        let subst = Substitution {
+            params: &self.generics,
+            args,
+        };
+        let ty = match &self.ty {
+            IdlTypeDefTy::Struct { fields } => IdlTypeDefTy::Struct {
+                fields: subst.fields(fields),
+            },
+            IdlTypeDefTy::Enum { variants } => IdlTypeDefTy::Enum {
+                variants: variants
+                    .iter()
+                    .map(|variant| {
+                        let mut variant = variant.clone();
+                        variant.fields = variant.fields.map(|fields| match fields {
+                            IdlEnumFields::Named(fields) => {
+                                IdlEnumFields::Named(subst.fields(&fields))
+                            }
+                            IdlEnumFields::Tuple(types) => {
+                                IdlEnumFields::Tuple(types.iter().map(|t| subst.ty(t)).collect())
+                            }
+                        });
+                        variant
+                    })
+                    .collect(),
+            },
+        };
+        Ok(IdlTypeDef {
+            name: self.name.clone(),
+            generics: Vec::new(),
+            serialization: self.serialization.clone(),
+            repr: self.repr.clone(),
+            ty,
+        })
+    }
+}
+
+/// Generic parameters paired with their arguments
+struct Substitution<'a> {
+    params: &'a [IdlTypeDefGeneric],
+    args: &'a [IdlGenericArg],
+}
+
+impl Substitution<'_> {
+    fn arg(&self, name: &str) -> Option<&IdlGenericArg> {
+        let i = self.params.iter().position(|p| p.name() == name)?;
+        self.args.get(i)
+    }
+
+    fn fields(&self, fields: &[IdlField]) -> Vec<IdlField> {
+        fields
+            .iter()
+            .map(|field| IdlField {
+                ty: self.ty(&field.ty),
+                ..field.clone()
+            })
+            .collect()
+    }
+
+    fn ty(&self, ty: &IdlType) -> IdlType {
+        let IdlType::Complex(complex) = ty else {
+            return ty.clone();
+        };
+        IdlType::Complex(match complex {
+            IdlTypeComplex::Vec(inner) => IdlTypeComplex::Vec(Box::new(self.ty(inner))),
+            IdlTypeComplex::Option(inner) => IdlTypeComplex::Option(Box::new(self.ty(inner))),
+            IdlTypeComplex::Array(inner, len) => {
+                IdlTypeComplex::Array(Box::new(self.ty(inner)), self.len(len))
+            }
+            IdlTypeComplex::Defined { name, generics } => IdlTypeComplex::Defined {
+                name: name.clone(),
+                generics: generics
+                    .iter()
+                    .map(|arg| match arg {
+                        IdlGenericArg::Type { ty } => IdlGenericArg::Type { ty: self.ty(ty) },
+                        // An outer const parameter passed on (`Inner<N>`)
+                        IdlGenericArg::Const { value } => match self.arg(value) {
+                            Some(IdlGenericArg::Const { value }) => IdlGenericArg::Const {
+                                value: value.clone(),
+                            },
+                            _ => arg.clone(),
+                        },
+                    })
+                    .collect(),
+            },
+            IdlTypeComplex::Generic(name) => match self.arg(name) {
+                Some(IdlGenericArg::Type { ty }) => return ty.clone(),
+                _ => complex.clone(),
+            },
+        })
+    }
+
+    fn len(&self, len: &IdlArrayLen) -> IdlArrayLen {
+        match len {
+            IdlArrayLen::Generic(name) => match self.arg(name) {
+                Some(IdlGenericArg::Const { value }) => value
+                    .trim()
+                    .parse()
+                    .map(IdlArrayLen::Value)
+                    .unwrap_or_else(|_| len.clone()),
+                _ => len.clone(),
+            },
+            IdlArrayLen::Value(_) => len.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::format_type;
+
+    #[test]
+    fn test_instantiate_substitutes_types_and_lengths() {
+        let def: IdlTypeDef = serde_json::from_str(
+            r#"{
+            "name": "Ring",
+            "generics": [
+                { "kind": "type", "name": "T" },
+                { "kind": "const", "name": "N", "type": "usize" }
+            ],
+            "type": { "kind": "struct", "fields": [
+                { "name": "head", "type": "u8" },
+                { "name": "items", "type": { "array": [{ "generic": "T" }, { "generic": "N" }] } },
+                { "name": "last", "type": { "option": { "generic": "T" } } }
+            ] }
+        }"#,
+        )
+        .unwrap();
+        let args: Vec<IdlGenericArg> = serde_json::from_str(
+            r#"[{ "kind": "type", "type": "u64" }, { "kind": "const", "value": "4" }]"#,
+        )
+        .unwrap();
+
+        let instance = def.instantiate(&args).unwrap();
+        assert!(instance.generics.is_empty());
+        let IdlTypeDefTy::Struct { fields } = &instance.ty else {
+            panic!("not a struct");
+        };
+        let types: Vec<String> = fields.iter().map(|f| format_type(&f.ty)).collect();
+        assert_eq!(types, ["u8", "[u64; 4]", "Option<u64>"]);
+
+        assert!(def.instantiate(&args[..1]).is_err());
+        assert!(def
+            .instantiate(&[args[1].clone(), args[0].clone()])
+            .is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Memory layout rules of IDL types
+//!
+//! Regular Anchor accounts are Borsh-serialized (tightly packed,
+//! length-prefixed collections). `#[account(zero_copy)]` accounts use the
+//! `repr(C)` layout instead, where every field is aligned to its natural
+//! alignment.
+
+use crate::types::IdlSerialization;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::str::FromStr;
+use serde::Serialize;
+
+/// Memory layout used to decode a type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    /// Borsh serialization (tightly packed, length-prefixed collections)
+    Borsh,
+    /// `repr(C)` layout of zero-copy (bytemuck) accounts, with alignment padding
+    ZeroCopy,
+    /// Fixed layout of a native or SPL program account
+    Native,
+}
+
+impl Layout {
+    /// Layout implied by an IDL `serialization` attribute
+    pub fn from_serialization(serialization: &IdlSerialization) -> Self {
+        match serialization {
+            IdlSerialization::Bytemuck | IdlSerialization::BytemuckUnsafe => Layout::ZeroCopy,
+            IdlSerialization::Borsh | IdlSerialization::Custom(_) => Layout::Borsh,
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layout::Borsh => write!(f, "borsh"),
+            Layout::ZeroCopy => write!(f, "zero-copy"),
+            Layout::Native => write!(f, "native"),
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "borsh" => Ok(Layout::Borsh),
+            "zero-copy" | "zero_copy" | "bytemuck" => Ok(Layout::ZeroCopy),
+            other => Err(format!(
+                "unknown layout '{}' (expected borsh or zero-copy)",
+                other
+            )),
+        }
+    }
+}
+
+/// Encoded size of a fixed-size primitive, `None` for `string`, `bytes` and unknown names
+pub fn primitive_size(name: &str) -> Option<usize> {
+    match name {
+        "bool" | "u8" | "i8" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        "u128" | "i128" => Some(16),
+        "u256" | "i256" | "pubkey" | "publicKey" => Some(32),
+        _ => None,
+    }
+}
+
+/// Natural `repr(C)` alignment of a primitive, `None` if it can't appear in a zero-copy layout
+pub fn zero_copy_align(name: &str) -> Option<usize> {
+    match name {
+        "bool" | "u8" | "i8" | "pubkey" | "publicKey" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        "u128" | "i128" | "u256" | "i256" => Some(16),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitive_layout() {
+        assert_eq!(primitive_size("pubkey"), Some(32));
+        assert_eq!(primitive_size("string"), None);
+        assert_eq!(zero_copy_align("pubkey"), Some(1));
+        assert_eq!(zero_copy_align("u128"), Some(16));
+        assert_eq!(zero_copy_align("bytes"), None);
+        assert_eq!("zero_copy".parse::<Layout>(), Ok(Layout::ZeroCopy));
+    }
+}
@@ -0,0 +1,27 @@
+//! Periscope core - Anchor IDL types, discriminators and layout rules
+//!
+//! The parts of Periscope that don't need the standard library: the IDL item
+//! types, Anchor discriminator derivation and the Borsh / zero-copy layout
+//! rules of primitive types. Only `alloc` is required, so the crate can be
+//! used inside on-chain programs and SBF tests:
+//!
+//! ```toml
+//! periscope-core = { version = "0.1", default-features = false }
+//! ```
+//!
+//! The `anchor-periscope` crate re-exports everything here.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod discriminator;
+pub mod generics;
+pub mod layout;
+pub mod types;
+
+pub use discriminator::*;
+pub use layout::*;
+pub use types::*;
@@ -0,0 +1,666 @@
+//! Anchor IDL item types (0.29+ format)
+//!
+//! Everything an IDL is made of except the root document, which lives in the
+//! main crate alongside its lookup indexes.
+//!
+//! Instructions, accounts, type definitions and types implement `Display`
+//! as one plain line in Rust-like notation (`swap(amount: u64) [pool
+//! (writable), user (signer)]`, `struct Pool { fee: u16 }`), stable enough
+//! for logs and snapshot tests.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// IDL metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlMetadata {
+    /// Program name
+    pub name: String,
+
+    /// Program version
+    pub version: String,
+
+    /// IDL spec version
+    pub spec: String,
+
+    /// Program description
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Instruction definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlInstruction {
+    /// Instruction name
+    pub name: String,
+
+    /// Discriminator bytes
+    #[serde(default)]
+    pub discriminator: Vec<u8>,
+
+    /// Accounts required by this instruction
+    pub accounts: Vec<IdlAccountItem>,
+
+    /// Arguments to this instruction
+    pub args: Vec<IdlField>,
+}
+
+/// Account item (can be a single account or nested group)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IdlAccountItem {
+    /// Single account
+    Single(IdlAccount),
+    /// Nested group of accounts
+    Group(IdlAccountGroup),
+}
+
+/// Single account in an instruction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlAccount {
+    /// Account name
+    pub name: String,
+
+    /// Whether this account is writable
+    #[serde(default)]
+    pub writable: bool,
+
+    /// Whether this account must sign
+    #[serde(default)]
+    pub signer: bool,
+
+    /// Whether this account is optional
+    #[serde(default)]
+    pub optional: bool,
+
+    /// Account address (for known accounts like system program)
+    #[serde(default)]
+    pub address: Option<String>,
+
+    /// PDA seeds if this is a PDA
+    #[serde(default)]
+    pub pda: Option<IdlPda>,
+}
+
+/// Group of accounts (nested)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlAccountGroup {
+    /// Group name
+    pub name: String,
+
+    /// Accounts in this group
+    pub accounts: Vec<IdlAccountItem>,
+}
+
+/// PDA definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlPda {
+    /// PDA seeds
+    pub seeds: Vec<IdlSeed>,
+}
+
+/// PDA seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlSeed {
+    /// Constant seed (literal bytes)
+    Const { value: serde_json::Value },
+    /// Account seed (pubkey from another account)
+    Account { path: String },
+    /// Argument seed (value from instruction args)
+    Arg { path: String },
+}
+
+/// Field definition (for args and struct fields)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlField {
+    /// Field name
+    pub name: String,
+
+    /// Field type
+    #[serde(rename = "type")]
+    pub ty: IdlType,
+}
+
+/// Type definition (struct or enum)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlTypeDef {
+    /// Type name
+    pub name: String,
+
+    /// Generic parameters, in declaration order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generics: Vec<IdlTypeDefGeneric>,
+
+    /// Serialization format (bytemuck for zero-copy accounts)
+    #[serde(default)]
+    pub serialization: IdlSerialization,
+
+    /// Memory representation (`#[repr(...)]`), when the program declares one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repr: Option<IdlRepr>,
+
+    /// Type definition
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+/// Serialization format of a type definition
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdlSerialization {
+    /// Borsh (the default)
+    #[default]
+    Borsh,
+    /// bytemuck `Pod` (zero-copy accounts)
+    Bytemuck,
+    /// bytemuck without `Pod` safety checks
+    #[serde(rename = "bytemuckunsafe")]
+    BytemuckUnsafe,
+    /// Program-specific serialization
+    Custom(String),
+}
+
+/// Generic parameter of a type definition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlTypeDefGeneric {
+    /// Type parameter (`T`)
+    Type { name: String },
+    /// Const parameter (`const N: usize`)
+    Const {
+        name: String,
+        #[serde(rename = "type")]
+        ty: String,
+    },
+}
+
+impl IdlTypeDefGeneric {
+    /// Parameter name
+    pub fn name(&self) -> &str {
+        match self {
+            IdlTypeDefGeneric::Type { name } | IdlTypeDefGeneric::Const { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for IdlTypeDefGeneric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlTypeDefGeneric::Type { name } => write!(f, "{}", name),
+            IdlTypeDefGeneric::Const { name, ty } => write!(f, "const {}: {}", name, ty),
+        }
+    }
+}
+
+/// Generic argument of a defined type reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlGenericArg {
+    /// Type argument (`u64` in `PodOption<u64>`)
+    Type {
+        #[serde(rename = "type")]
+        ty: IdlType,
+    },
+    /// Const argument, as a decimal string
+    Const { value: String },
+}
+
+/// Memory representation of a type definition (`#[repr(...)]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlRepr {
+    /// `repr(Rust)`
+    Rust(IdlReprModifier),
+    /// `repr(C)`
+    C(IdlReprModifier),
+    /// `repr(transparent)`
+    Transparent,
+}
+
+/// `packed` and `align(N)` modifiers of a representation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdlReprModifier {
+    /// No padding between fields; the type's alignment is 1
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub packed: bool,
+
+    /// Minimum alignment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub align: Option<usize>,
+}
+
+impl IdlRepr {
+    /// The `packed`/`align` modifiers (none for `transparent`)
+    pub fn modifier(&self) -> Option<&IdlReprModifier> {
+        match self {
+            IdlRepr::Rust(modifier) | IdlRepr::C(modifier) => Some(modifier),
+            IdlRepr::Transparent => None,
+        }
+    }
+}
+
+impl fmt::Display for IdlRepr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, modifier) = match self {
+            IdlRepr::Rust(modifier) => ("Rust", Some(modifier)),
+            IdlRepr::C(modifier) => ("C", Some(modifier)),
+            IdlRepr::Transparent => ("transparent", None),
+        };
+        write!(f, "repr({}", kind)?;
+        if let Some(modifier) = modifier {
+            if modifier.packed {
+                write!(f, ", packed")?;
+            }
+            if let Some(align) = modifier.align {
+                write!(f, ", align({})", align)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+/// Type definition body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlTypeDefTy {
+    /// Struct type
+    Struct { fields: Vec<IdlField> },
+    /// Enum type
+    Enum { variants: Vec<IdlEnumVariant> },
+}
+
+/// Enum variant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlEnumVariant {
+    /// Variant name
+    pub name: String,
+
+    /// Variant fields (if tuple or struct variant)
+    #[serde(default)]
+    pub fields: Option<IdlEnumFields>,
+}
+
+/// Enum variant fields - can be tuple-style (unnamed) or struct-style (named)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IdlEnumFields {
+    /// Tuple variant: fields are just types (e.g., ["u64", "pubkey"])
+    Tuple(Vec<IdlType>),
+    /// Struct variant: fields have names and types (e.g., [{"name": "x", "type": "u64"}])
+    Named(Vec<IdlField>),
+}
+
+/// IDL type (primitives and composites)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IdlType {
+    /// Primitive type as string (u8, u64, bool, pubkey, etc.)
+    Primitive(String),
+
+    /// Complex type
+    Complex(IdlTypeComplex),
+}
+
+/// Complex IDL types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdlTypeComplex {
+    /// Vec<T>
+    Vec(Box<IdlType>),
+    /// Option<T>
+    Option(Box<IdlType>),
+    /// [T; N]
+    Array(Box<IdlType>, IdlArrayLen),
+    /// Reference to a defined type, with its generic arguments
+    Defined {
+        name: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        generics: Vec<IdlGenericArg>,
+    },
+    /// Generic type parameter of the enclosing type definition
+    Generic(String),
+}
+
+/// Length of an array type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdlArrayLen {
+    /// Const generic parameter of the enclosing type definition
+    Generic(String),
+    /// Fixed length
+    #[serde(untagged)]
+    Value(usize),
+}
+
+/// Account reference (root-level accounts array)
+/// Just a discriminator reference - actual type is in `types`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlAccountRef {
+    /// Account type name
+    pub name: String,
+
+    /// Account discriminator bytes
+    #[serde(default)]
+    pub discriminator: Vec<u8>,
+}
+
+/// Event reference (root-level events array)
+/// Just a discriminator reference - actual type is in `types`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlEventRef {
+    /// Event type name
+    pub name: String,
+
+    /// Event discriminator bytes
+    #[serde(default)]
+    pub discriminator: Vec<u8>,
+}
+
+/// Error definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlError {
+    /// Error code
+    pub code: u32,
+
+    /// Error name
+    pub name: String,
+
+    /// Error message
+    #[serde(default)]
+    pub msg: Option<String>,
+}
+
+/// Format IdlType as readable string
+pub fn format_type(ty: &IdlType) -> String {
+    ty.to_string()
+}
+
+/// Write `items` separated by `, `
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// `u64`, `Vec<pubkey>`, `[u8; 32]`, `Option<Pool>`, `PodOption<u64>`
+impl fmt::Display for IdlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlType::Primitive(name) => f.write_str(name),
+            IdlType::Complex(complex) => write!(f, "{}", complex),
+        }
+    }
+}
+
+impl fmt::Display for IdlTypeComplex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlTypeComplex::Vec(inner) => write!(f, "Vec<{}>", inner),
+            IdlTypeComplex::Option(inner) => write!(f, "Option<{}>", inner),
+            IdlTypeComplex::Array(inner, len) => write!(f, "[{}; {}]", inner, len),
+            IdlTypeComplex::Defined { name, generics } if generics.is_empty() => f.write_str(name),
+            IdlTypeComplex::Defined { name, generics } => {
+                write!(f, "{}<", name)?;
+                write_list(f, generics)?;
+                f.write_str(">")
+            }
+            IdlTypeComplex::Generic(name) => f.write_str(name),
+        }
+    }
+}
+
+impl fmt::Display for IdlGenericArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlGenericArg::Type { ty } => write!(f, "{}", ty),
+            IdlGenericArg::Const { value } => f.write_str(value),
+        }
+    }
+}
+
+/// `amount: u64`
+impl fmt::Display for IdlField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.ty)
+    }
+}
+
+/// `Bid`, `Ask(u64)`, `Limit { price: u64 }`
+impl fmt::Display for IdlEnumVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        match &self.fields {
+            None => Ok(()),
+            Some(IdlEnumFields::Tuple(types)) => {
+                f.write_str("(")?;
+                write_list(f, types)?;
+                f.write_str(")")
+            }
+            Some(IdlEnumFields::Named(fields)) => {
+                f.write_str(" { ")?;
+                write_list(f, fields)?;
+                f.write_str(" }")
+            }
+        }
+    }
+}
+
+/// `struct Pool { fee: u16, owner: pubkey }`, `enum Side { Bid, Ask(u64) }`,
+/// with generic parameters after the name; serialization and `repr` are
+/// left out
+impl fmt::Display for IdlTypeDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.ty {
+            IdlTypeDefTy::Struct { .. } => "struct",
+            IdlTypeDefTy::Enum { .. } => "enum",
+        };
+        write!(f, "{} {}", kind, self.name)?;
+        if !self.generics.is_empty() {
+            f.write_str("<")?;
+            write_list(f, &self.generics)?;
+            f.write_str(">")?;
+        }
+        let empty = match &self.ty {
+            IdlTypeDefTy::Struct { fields } => fields.is_empty(),
+            IdlTypeDefTy::Enum { variants } => variants.is_empty(),
+        };
+        if empty {
+            return f.write_str(" {}");
+        }
+        f.write_str(" { ")?;
+        match &self.ty {
+            IdlTypeDefTy::Struct { fields } => write_list(f, fields)?,
+            IdlTypeDefTy::Enum { variants } => write_list(f, variants)?,
+        }
+        f.write_str(" }")
+    }
+}
+
+/// `pool (writable)`, `user (writable, signer)`, `system_program = 111...`;
+/// the flags are `writable`, `signer`, `optional` and `pda`
+impl fmt::Display for IdlAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        let flags = [
+            (self.writable, "writable"),
+            (self.signer, "signer"),
+            (self.optional, "optional"),
+            (self.pda.is_some(), "pda"),
+        ];
+        let mut flags = flags.iter().filter(|(set, _)| *set).map(|(_, flag)| *flag);
+        if let Some(first) = flags.next() {
+            write!(f, " ({}", first)?;
+            for flag in flags {
+                write!(f, ", {}", flag)?;
+            }
+            f.write_str(")")?;
+        }
+        if let Some(address) = &self.address {
+            write!(f, " = {}", address)?;
+        }
+        Ok(())
+    }
+}
+
+/// An account, or a group as `name { accounts }`
+impl fmt::Display for IdlAccountItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlAccountItem::Single(account) => write!(f, "{}", account),
+            IdlAccountItem::Group(group) => {
+                write!(f, "{} {{ ", group.name)?;
+                write_list(f, &group.accounts)?;
+                f.write_str(" }")
+            }
+        }
+    }
+}
+
+/// `swap(amount: u64, min_out: u64) [pool (writable), user (writable, signer)]`;
+/// the account list is left out when there are none
+impl fmt::Display for IdlInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        write_list(f, &self.args)?;
+        f.write_str(")")?;
+        if !self.accounts.is_empty() {
+            f.write_str(" [")?;
+            write_list(f, &self.accounts)?;
+            f.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for IdlArrayLen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdlArrayLen::Generic(name) => write!(f, "{}", name),
+            IdlArrayLen::Value(len) => write!(f, "{}", len),
+        }
+    }
+}
+
+impl IdlArrayLen {
+    /// The length, `None` while it is a generic parameter
+    pub fn value(&self) -> Option<usize> {
+        match self {
+            IdlArrayLen::Value(len) => Some(*len),
+            IdlArrayLen::Generic(_) => None,
+        }
+    }
+}
+
+impl IdlInstruction {
+    /// Discriminator bytes, derived from the snake_case name when the IDL omits them
+    pub fn discriminator_bytes(&self) -> Vec<u8> {
+        if self.discriminator.is_empty() {
+            crate::instruction_discriminator(&to_snake_case(&self.name)).to_vec()
+        } else {
+            self.discriminator.clone()
+        }
+    }
+}
+
+impl IdlAccountRef {
+    /// Discriminator bytes, derived from the name when the IDL omits them (legacy format)
+    pub fn discriminator_bytes(&self) -> Vec<u8> {
+        if self.discriminator.is_empty() {
+            crate::account_discriminator(&self.name).to_vec()
+        } else {
+            self.discriminator.clone()
+        }
+    }
+}
+
+/// `swapExactIn` -> `swap_exact_in` (legacy IDLs store camelCase instruction names)
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::vec;
+
+    #[test]
+    fn test_display_items() {
+        let mut ix: IdlInstruction = serde_json::from_str(
+            r#"{
+            "name": "swap",
+            "accounts": [
+                { "name": "pool", "writable": true },
+                { "name": "user", "writable": true, "signer": true },
+                { "name": "system_program", "address": "11111111111111111111111111111111" }
+            ],
+            "args": [
+                { "name": "amount", "type": "u64" },
+                { "name": "route", "type": { "vec": { "defined": { "name": "Hop" } } } },
+                { "name": "memo", "type": { "option": { "array": ["u8", 32] } } }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let vault = |name: &str, pda| {
+            let account = format!(r#"{{ "name": "{}", "pda": {} }}"#, name, pda);
+            IdlAccountItem::Single(serde_json::from_str(&account).unwrap())
+        };
+        let vaults = IdlAccountGroup {
+            name: "vaults".into(),
+            accounts: vec![vault("a", "null"), vault("b", r#"{ "seeds": [] }"#)],
+        };
+        ix.accounts.insert(2, IdlAccountItem::Group(vaults));
+        assert_eq!(
+            ix.to_string(),
+            "swap(amount: u64, route: Vec<Hop>, memo: Option<[u8; 32]>) \
+             [pool (writable), user (writable, signer), vaults { a, b (pda) }, \
+             system_program = 11111111111111111111111111111111]"
+        );
+
+        let side: IdlTypeDef = serde_json::from_str(
+            r#"{ "name": "Side", "type": { "kind": "enum", "variants": [
+                { "name": "Bid" },
+                { "name": "Ask", "fields": ["u64"] },
+                { "name": "Limit", "fields": [{ "name": "price", "type": "u64" }] }
+            ] } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            side.to_string(),
+            "enum Side { Bid, Ask(u64), Limit { price: u64 } }"
+        );
+
+        let ring: IdlTypeDef = serde_json::from_str(
+            r#"{ "name": "Ring", "generics": [
+                { "kind": "type", "name": "T" }, { "kind": "const", "name": "N", "type": "usize" }
+            ], "type": { "kind": "struct", "fields": [
+                { "name": "items", "type": { "array": [{ "generic": "T" }, { "generic": "N" }] } }
+            ] } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ring.to_string(),
+            "struct Ring<T, const N: usize> { items: [T; N] }"
+        );
+    }
+}
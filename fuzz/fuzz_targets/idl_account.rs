@@ -0,0 +1,17 @@
+//! Raw IDL account data: header parsing, decompression, then JSON parsing
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use periscope::idl::{decompress_idl_data, idl_account_payload, parse_idl_json};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(payload) = idl_account_payload(data) else {
+        return;
+    };
+    let Ok(json) = decompress_idl_data(payload) else {
+        return;
+    };
+    if let Ok(json) = std::str::from_utf8(&json) {
+        let _ = parse_idl_json(json);
+    }
+});
@@ -0,0 +1,17 @@
+//! IDL JSON parsing in both formats, then decoding the input against the result
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use periscope::idl::{parse_idl_json, IdlNames};
+use periscope::{decode_account, decode_instruction};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = IdlNames::parse(json);
+    if let Ok(idl) = parse_idl_json(json) {
+        let _ = decode_account(&idl, data);
+        let _ = decode_instruction(&idl, data);
+    }
+});
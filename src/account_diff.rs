@@ -0,0 +1,98 @@
+//! Field-level diff of decoded account states
+//!
+//! `periscope account-diff` decodes two states of one account with the
+//! owner's IDL and lists the leaf fields whose values differ. A state is a
+//! fixture written by `periscope snapshot` or the live account, so capturing
+//! fixtures periodically answers "what changed in this pool since yesterday".
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// A field whose value differs between two states
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// Field path (`config.fees[2].rate`)
+    pub path: String,
+
+    /// Value before; `null` when the field did not exist
+    pub before: Value,
+
+    /// Value after; `null` when the field no longer exists
+    pub after: Value,
+}
+
+/// Leaf fields that differ between `before` and `after`
+///
+/// Objects are compared key by key and arrays of equal length element by
+/// element; anything else that differs is reported whole.
+pub fn diff_values(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_into(&mut changes, String::new(), before, after);
+    changes
+}
+
+fn diff_into(changes: &mut Vec<FieldChange>, path: String, before: &Value, after: &Value) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                let child = join(&path, key);
+                diff_into(changes, child, value, b.get(key).unwrap_or(&Value::Null));
+            }
+            for (key, value) in b.iter().filter(|(k, _)| !a.contains_key(*k)) {
+                diff_into(changes, join(&path, key), &Value::Null, value);
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                diff_into(changes, format!("{}[{}]", path, i), x, y);
+            }
+        }
+        _ => changes.push(FieldChange {
+            path,
+            before: before.clone(),
+            after: after.clone(),
+        }),
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_values() {
+        let before = json!({
+            "authority": "A",
+            "reserves": [100, 200],
+            "fees": { "rate": 30, "owner": null },
+            "ticks": [1, 2, 3],
+            "side": "Bid"
+        });
+        let after = json!({
+            "authority": "A",
+            "reserves": [100, 250],
+            "fees": { "rate": 25, "owner": null },
+            "ticks": [1, 2],
+            "side": { "Ask": { "limit": 5 } }
+        });
+
+        let changes = diff_values(&before, &after);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["fees.rate", "reserves[1]", "side", "ticks"]);
+        assert_eq!(changes[1].before, json!(200));
+        assert_eq!(changes[1].after, json!(250));
+        assert!(diff_values(&before, &before).is_empty());
+    }
+}
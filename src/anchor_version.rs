@@ -0,0 +1,422 @@
+//! Anchor version and feature detection
+//!
+//! Infers the range of Anchor versions a program was built with from clues
+//! in its IDL (spec, discriminator lengths, account shapes), narrowed to the
+//! exact `anchor-lang` version when the program binary can be read: release
+//! builds keep the crate's source paths (`.../anchor-lang-0.29.0/src/...`) in
+//! their panic locations.
+
+use crate::builtin_programs::BPF_LOADER_UPGRADEABLE_ID;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{fetch_account, Idl, IdlAccount, IdlAccountItem, IdlSerialization, IdlSpec};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+/// ProgramData header: tag, deploy slot, `Option<Pubkey>` upgrade authority
+const PROGRAM_DATA_HEADER_LEN: usize = 45;
+
+/// Anchor release version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(pub u64, pub u64, pub u64);
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Versions the program can have been built with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct VersionRange {
+    /// Oldest possible version (inclusive)
+    pub min: Option<Version>,
+
+    /// First version that is ruled out
+    pub below: Option<Version>,
+}
+
+impl VersionRange {
+    /// `version` and later
+    pub fn since(version: Version) -> Self {
+        VersionRange {
+            min: Some(version),
+            below: None,
+        }
+    }
+
+    /// Versions before `version`
+    pub fn before(version: Version) -> Self {
+        VersionRange {
+            min: None,
+            below: Some(version),
+        }
+    }
+
+    /// Exactly `version`
+    pub fn exact(version: Version) -> Self {
+        VersionRange {
+            min: Some(version),
+            below: Some(Version(version.0, version.1, version.2 + 1)),
+        }
+    }
+
+    /// Versions in both ranges
+    fn intersect(&mut self, other: &VersionRange) {
+        self.min = self.min.max(other.min);
+        self.below = match (self.below, other.below) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.below) {
+            (Some(min), Some(below)) if below == Version(min.0, min.1, min.2 + 1) => {
+                write!(f, "={}", min)
+            }
+            (Some(min), Some(below)) => write!(f, ">={}, <{}", min, below),
+            (Some(min), None) => write!(f, ">={}", min),
+            (None, Some(below)) => write!(f, "<{}", below),
+            (None, None) => write!(f, "any"),
+        }
+    }
+}
+
+/// Something that narrows the version range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Evidence {
+    /// What was observed
+    pub clue: String,
+
+    /// Versions it allows, e.g. `>=0.30.0`
+    pub implies: String,
+}
+
+/// An Anchor feature and whether the program uses it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Feature {
+    /// `events`, `event-cpi`, `zero-copy`, ...
+    pub name: &'static str,
+
+    pub used: bool,
+
+    /// Where it is used (first few items)
+    pub detail: Option<String>,
+}
+
+/// What `anchor-version` found out about a program
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorReport {
+    /// Versions consistent with the evidence
+    pub range: VersionRange,
+
+    /// `anchor-lang` version found in the program binary
+    pub binary_version: Option<Version>,
+
+    pub evidence: Vec<Evidence>,
+
+    pub features: Vec<Feature>,
+
+    /// Client libraries that can read this IDL
+    pub clients: String,
+}
+
+/// Infer the Anchor version and features of a program from its IDL and,
+/// when available, its binary
+///
+/// A version found in the binary is authoritative: the IDL may have been
+/// generated or uploaded by a different Anchor CLI.
+pub fn detect(idl: &Idl, binary: Option<&[u8]>) -> AnchorReport {
+    let mut evidence = Vec::new();
+    let mut note = |clue: String, implies: VersionRange| {
+        evidence.push((clue, implies));
+    };
+
+    let legacy = idl.spec() == IdlSpec::Legacy;
+    if legacy {
+        note(
+            "legacy IDL format".to_string(),
+            VersionRange::before(Version(0, 30, 0)),
+        );
+    } else {
+        note(
+            format!("IDL spec {}", idl.metadata.spec),
+            VersionRange::since(Version(0, 30, 0)),
+        );
+    }
+
+    let features = features(idl);
+    for (feature, since) in [
+        ("custom-discriminators", Version(0, 31, 0)),
+        ("event-cpi", Version(0, 28, 0)),
+        ("optional-accounts", Version(0, 26, 0)),
+    ] {
+        if features.iter().any(|f| f.name == feature && f.used) {
+            note(format!("uses {}", feature), VersionRange::since(since));
+        }
+    }
+
+    let binary_version = binary.and_then(binary_anchor_version);
+    if let Some(version) = binary_version {
+        note(
+            format!("anchor-lang {} in the program binary", version),
+            VersionRange::exact(version),
+        );
+    }
+
+    let range = match binary_version {
+        Some(version) => VersionRange::exact(version),
+        None => evidence
+            .iter()
+            .fold(VersionRange::default(), |mut range, (_, implies)| {
+                range.intersect(implies);
+                range
+            }),
+    };
+    let evidence = evidence
+        .into_iter()
+        .map(|(clue, implies)| Evidence {
+            clue,
+            implies: implies.to_string(),
+        })
+        .collect();
+
+    let clients = if legacy {
+        "@coral-xyz/anchor <0.30 (or convert the IDL with `anchor idl convert`)"
+    } else {
+        "@coral-xyz/anchor >=0.30, anchor-client >=0.30"
+    };
+
+    AnchorReport {
+        range,
+        binary_version,
+        evidence,
+        features,
+        clients: clients.to_string(),
+    }
+}
+
+/// Anchor features the IDL shows signs of
+fn features(idl: &Idl) -> Vec<Feature> {
+    fn feature(name: &'static str, items: Vec<&str>) -> Feature {
+        let detail = match items.len() {
+            0 => None,
+            1..=3 => Some(items.join(", ")),
+            n => Some(format!("{}, ... ({} total)", items[..3].join(", "), n)),
+        };
+        Feature {
+            name,
+            used: !items.is_empty(),
+            detail,
+        }
+    }
+
+    let events = idl.events.iter().map(|e| e.name.as_str()).collect();
+
+    // `#[event_cpi]` adds `event_authority` and `program` to the accounts
+    // (camelCase in legacy IDLs)
+    let event_cpi = idl
+        .instructions
+        .iter()
+        .filter(|ix| {
+            let accounts = flatten_accounts(&ix.accounts);
+            accounts
+                .iter()
+                .any(|a| a.name == "event_authority" || a.name == "eventAuthority")
+                && accounts.iter().any(|a| a.name == "program")
+        })
+        .map(|ix| ix.name.as_str())
+        .collect();
+
+    let zero_copy = idl
+        .types
+        .iter()
+        .filter(|t| {
+            matches!(
+                t.serialization,
+                IdlSerialization::Bytemuck | IdlSerialization::BytemuckUnsafe
+            )
+        })
+        .map(|t| t.name.as_str())
+        .collect();
+
+    let generics = idl
+        .types
+        .iter()
+        .filter(|t| !t.generics.is_empty())
+        .map(|t| t.name.as_str())
+        .collect();
+
+    let custom_discriminators = idl
+        .instructions
+        .iter()
+        .map(|ix| (ix.name.as_str(), ix.discriminator.len()))
+        .chain(
+            idl.accounts
+                .iter()
+                .map(|a| (a.name.as_str(), a.discriminator.len())),
+        )
+        .chain(
+            idl.events
+                .iter()
+                .map(|e| (e.name.as_str(), e.discriminator.len())),
+        )
+        .filter(|(_, len)| *len != 0 && *len != 8)
+        .map(|(name, _)| name)
+        .collect();
+
+    let optional_accounts = idl
+        .instructions
+        .iter()
+        .filter(|ix| flatten_accounts(&ix.accounts).iter().any(|a| a.optional))
+        .map(|ix| ix.name.as_str())
+        .collect();
+
+    let pda_seeds = idl
+        .instructions
+        .iter()
+        .filter(|ix| {
+            flatten_accounts(&ix.accounts)
+                .iter()
+                .any(|a| a.pda.is_some())
+        })
+        .map(|ix| ix.name.as_str())
+        .collect();
+
+    vec![
+        feature("events", events),
+        feature("event-cpi", event_cpi),
+        feature("zero-copy", zero_copy),
+        feature("generics", generics),
+        feature("custom-discriminators", custom_discriminators),
+        feature("optional-accounts", optional_accounts),
+        feature("pda-seeds", pda_seeds),
+    ]
+}
+
+fn flatten_accounts(items: &[IdlAccountItem]) -> Vec<&IdlAccount> {
+    let mut out = Vec::new();
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => out.push(account),
+            IdlAccountItem::Group(group) => out.extend(flatten_accounts(&group.accounts)),
+        }
+    }
+    out
+}
+
+/// `anchor-lang` version referenced by a program binary
+pub fn binary_anchor_version(binary: &[u8]) -> Option<Version> {
+    const MARKER: &[u8] = b"anchor-lang-";
+    binary
+        .windows(MARKER.len())
+        .enumerate()
+        .filter(|(_, window)| *window == MARKER)
+        .find_map(|(i, _)| parse_version(&binary[i + MARKER.len()..]))
+}
+
+/// `X.Y.Z` at the start of `bytes`
+fn parse_version(bytes: &[u8]) -> Option<Version> {
+    let end = bytes
+        .iter()
+        .position(|b| !b.is_ascii_digit() && *b != b'.')
+        .unwrap_or(bytes.len());
+    let text = std::str::from_utf8(&bytes[..end]).ok()?;
+    let mut parts = text.split('.').map(|p| p.parse::<u64>().ok());
+    let version = Version(parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// The executable bytes of a program, without loader headers
+///
+/// `None` when the program or its ProgramData account doesn't exist.
+pub fn fetch_program_binary(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> PeriscopeResult<Option<Vec<u8>>> {
+    let program = match fetch_account(client, program_id) {
+        Ok(account) => account,
+        Err(PeriscopeError::AccountNotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if program.owner != BPF_LOADER_UPGRADEABLE_ID {
+        return Ok(Some(program.data));
+    }
+
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+    match fetch_account(client, &program_data) {
+        Ok(account) => Ok(account
+            .data
+            .get(PROGRAM_DATA_HEADER_LEN..)
+            .map(<[u8]>::to_vec)),
+        Err(PeriscopeError::AccountNotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_from_idl() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "swap",
+                "discriminator": [1],
+                "accounts": [
+                    { "name": "pool", "writable": true },
+                    { "name": "event_authority" },
+                    { "name": "program" }
+                ],
+                "args": []
+            }],
+            "types": [{
+                "name": "Pool",
+                "serialization": "bytemuck",
+                "type": { "kind": "struct", "fields": [] }
+            }]
+        }))
+        .unwrap();
+
+        let report = detect(&idl, None);
+        assert_eq!(report.range.to_string(), ">=0.31.0");
+        assert_eq!(report.evidence.len(), 3);
+        let used: Vec<_> = report
+            .features
+            .iter()
+            .filter(|f| f.used)
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(used, ["event-cpi", "zero-copy", "custom-discriminators"]);
+
+        let binary = b"\0/cargo/registry/src/anchor-lang-0.31.1/src/error.rs\0";
+        let report = detect(&idl, Some(binary));
+        assert_eq!(report.binary_version, Some(Version(0, 31, 1)));
+        assert_eq!(report.range.to_string(), "=0.31.1");
+    }
+
+    #[test]
+    fn test_binary_anchor_version() {
+        assert_eq!(
+            binary_anchor_version(b"xxanchor-lang-syn/anchor-lang-0.29.0/src/lib.rs"),
+            Some(Version(0, 29, 0))
+        );
+        assert_eq!(binary_anchor_version(b"anchor-lang-0.29"), None);
+        assert_eq!(binary_anchor_version(b"no anchor here"), None);
+    }
+}
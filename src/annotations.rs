@@ -0,0 +1,282 @@
+//! User-maintained notes on IDL items
+//!
+//! What an IDL doesn't say ("deprecated, use `swap_v2`", "admin only —
+//! multisig 7xKX…") lives in an annotations file per program, which text
+//! output and generated code show next to the item it is about:
+//!
+//! ```toml
+//! # ~/.config/periscope/annotations/<PROGRAM_ID>.toml
+//! [instructions]
+//! set_fee = { label = "Set protocol fee", note = "admin only — multisig 7xKX…" }
+//! swap = { deprecated = true, note = "use swap_v2" }
+//!
+//! [errors]
+//! Unauthorized = "signer isn't the pool admin"   # a string is a note
+//! ```
+//!
+//! Sections are `instructions`, `accounts`, `types`, `events` and `errors`.
+//! Names match whatever their case (`set_fee` annotates `setFee`). A
+//! project can keep its files in the repository, in the directory named by
+//! `annotations` in `.periscope.toml`; its entries override the user's.
+
+use crate::idl::NameCase;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "cli")]
+use crate::error::{PeriscopeError, PeriscopeResult};
+#[cfg(feature = "cli")]
+use std::path::Path;
+#[cfg(feature = "cli")]
+use std::sync::OnceLock;
+
+/// Annotations directory name, in the config directory
+pub const ANNOTATIONS_DIR: &str = "annotations";
+
+#[cfg(feature = "cli")]
+static ANNOTATIONS: OnceLock<Annotations> = OnceLock::new();
+
+/// Kind of item an annotation is about, one per section of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    Instruction,
+    Account,
+    Type,
+    Event,
+    Error,
+}
+
+/// Notes on one item
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "AnnotationRepr")]
+pub struct Annotation {
+    /// Human name shown next to the IDL name
+    pub label: Option<String>,
+
+    /// Free-form note
+    pub note: Option<String>,
+
+    /// The item is not to be used anymore
+    pub deprecated: bool,
+}
+
+impl Annotation {
+    /// Label and note on one line, `None` when there is neither
+    pub fn summary(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.label, &self.note]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" — "))
+    }
+}
+
+/// A bare string is a note
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnnotationRepr {
+    Note(String),
+    Full {
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        note: Option<String>,
+        #[serde(default)]
+        deprecated: bool,
+    },
+}
+
+impl From<AnnotationRepr> for Annotation {
+    fn from(repr: AnnotationRepr) -> Self {
+        match repr {
+            AnnotationRepr::Note(note) => Annotation {
+                note: Some(note),
+                ..Default::default()
+            },
+            AnnotationRepr::Full {
+                label,
+                note,
+                deprecated,
+            } => Annotation {
+                label,
+                note,
+                deprecated,
+            },
+        }
+    }
+}
+
+/// The annotations of one program
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Annotations {
+    #[serde(default)]
+    pub instructions: BTreeMap<String, Annotation>,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, Annotation>,
+    #[serde(default)]
+    pub types: BTreeMap<String, Annotation>,
+    #[serde(default)]
+    pub events: BTreeMap<String, Annotation>,
+    #[serde(default)]
+    pub errors: BTreeMap<String, Annotation>,
+}
+
+impl Annotations {
+    /// Notes on the item of `kind` named `name`, in any case
+    pub fn get(&self, kind: AnnotationKind, name: &str) -> Option<&Annotation> {
+        let section = self.section(kind);
+        section.get(name).or_else(|| {
+            let name = NameCase::Snake.apply(name);
+            section
+                .iter()
+                .find(|(key, _)| NameCase::Snake.apply(key) == name)
+                .map(|(_, annotation)| annotation)
+        })
+    }
+
+    /// Number of annotated items
+    pub fn len(&self) -> usize {
+        [
+            AnnotationKind::Instruction,
+            AnnotationKind::Account,
+            AnnotationKind::Type,
+            AnnotationKind::Event,
+            AnnotationKind::Error,
+        ]
+        .into_iter()
+        .map(|kind| self.section(kind).len())
+        .sum()
+    }
+
+    /// Whether nothing is annotated
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `other`'s entries, replacing those of the same items
+    pub fn merge(&mut self, other: Annotations) {
+        self.instructions.extend(other.instructions);
+        self.accounts.extend(other.accounts);
+        self.types.extend(other.types);
+        self.events.extend(other.events);
+        self.errors.extend(other.errors);
+    }
+
+    fn section(&self, kind: AnnotationKind) -> &BTreeMap<String, Annotation> {
+        match kind {
+            AnnotationKind::Instruction => &self.instructions,
+            AnnotationKind::Account => &self.accounts,
+            AnnotationKind::Type => &self.types,
+            AnnotationKind::Event => &self.events,
+            AnnotationKind::Error => &self.errors,
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Annotations {
+    /// Parse an annotations file
+    pub fn parse(contents: &str) -> PeriscopeResult<Self> {
+        toml::from_str(contents).map_err(|e| PeriscopeError::ConfigError(e.to_string()))
+    }
+
+    /// Annotations of `program_id` from the user's annotations directory,
+    /// with those in `project_dir` layered on top; empty when there are none
+    pub fn load(program_id: &str, project_dir: Option<&Path>) -> PeriscopeResult<Self> {
+        let user_dir = crate::config::Config::dir_path()?.join(ANNOTATIONS_DIR);
+        let mut annotations = Annotations::default();
+        for dir in std::iter::once(user_dir.as_path()).chain(project_dir) {
+            let path = dir.join(format!("{}.toml", program_id));
+            if !path.is_file() {
+                continue;
+            }
+            let contents = std::fs::read_to_string(&path)?;
+            let file = Self::parse(&contents).map_err(|e| {
+                PeriscopeError::ConfigError(format!("Failed to parse {}: {}", path.display(), e))
+            })?;
+            annotations.merge(file);
+        }
+        Ok(annotations)
+    }
+}
+
+/// Show these annotations in text output for the rest of the process (the
+/// first call wins)
+#[cfg(feature = "cli")]
+pub fn set_annotations(annotations: Annotations) {
+    let _ = ANNOTATIONS.set(annotations);
+}
+
+/// The annotations set for this process (empty when none were)
+#[cfg(feature = "cli")]
+pub fn annotations() -> &'static Annotations {
+    ANNOTATIONS.get_or_init(Annotations::default)
+}
+
+/// Notes on an item in the annotations set for this process
+#[cfg(feature = "cli")]
+pub fn annotation(kind: AnnotationKind, name: &str) -> Option<&'static Annotation> {
+    annotations().get(kind, name)
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_lookup() {
+        let mut annotations = Annotations::parse(
+            r#"
+            [instructions]
+            set_fee = { label = "Set protocol fee", note = "admin only" }
+            swap = { deprecated = true, note = "use swap_v2" }
+
+            [errors]
+            Unauthorized = "signer isn't the pool admin"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(annotations.len(), 3);
+
+        let set_fee = annotations
+            .get(AnnotationKind::Instruction, "setFee")
+            .unwrap();
+        assert_eq!(
+            set_fee.summary().as_deref(),
+            Some("Set protocol fee — admin only")
+        );
+        assert!(
+            annotations
+                .get(AnnotationKind::Instruction, "swap")
+                .unwrap()
+                .deprecated
+        );
+        let unauthorized = annotations
+            .get(AnnotationKind::Error, "Unauthorized")
+            .unwrap();
+        assert_eq!(
+            unauthorized.note.as_deref(),
+            Some("signer isn't the pool admin")
+        );
+        // Sections are kept apart
+        assert!(annotations
+            .get(AnnotationKind::Type, "Unauthorized")
+            .is_none());
+
+        // A project file replaces the user's note on the same item
+        annotations.merge(Annotations::parse(r#"errors.Unauthorized = "multisig only""#).unwrap());
+        assert_eq!(annotations.len(), 3);
+        assert_eq!(
+            annotations
+                .get(AnnotationKind::Error, "Unauthorized")
+                .unwrap()
+                .note
+                .as_deref(),
+            Some("multisig only")
+        );
+
+        assert!(Annotations::parse("[instruction]\nswap = \"x\"").is_err());
+    }
+}
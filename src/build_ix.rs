@@ -0,0 +1,680 @@
+//! Build an instruction's data and account list
+//!
+//! `periscope build-ix <NAME> --interactive` prompts for every argument and
+//! account, validating each value against its IDL type as it is typed.
+//! `--from <FILE>` reads a filled-in `periscope template` instead. Either
+//! way, PDA accounts are derived from their seeds when those only refer to
+//! constants, arguments and other accounts of the instruction. Arguments
+//! come first so that argument seeds are known by the time accounts are.
+
+use crate::encode::{derive_pda, encode_instruction, parse_hex};
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{hex, Idl, IdlAccount, IdlAccountItem, IdlInstruction};
+// Interactive prompting is part of the CLI; `send` only needs built instructions
+#[cfg(feature = "cli")]
+use crate::{
+    encode::parse_primitive,
+    i18n::{tr, trf, Msg},
+    idl::{
+        IdlArrayLen, IdlEnumFields, IdlField, IdlGenericArg, IdlType, IdlTypeComplex, IdlTypeDefTy,
+    },
+    theme::{paint, Element},
+};
+#[cfg(feature = "cli")]
+use inquire::{validator::Validation, Confirm, CustomType, InquireError, Select, Text};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_system_interface::instruction::advance_nonce_account;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Nesting depth of defined types past which prompting gives up
+#[cfg(feature = "cli")]
+const MAX_DEPTH: usize = 16;
+
+/// Size of an initialized nonce account
+const NONCE_ACCOUNT_LEN: usize = 80;
+
+/// Blockhash or durable nonce of an encoded message
+#[derive(Debug, Clone, Default)]
+pub struct MessageOptions {
+    /// Recent blockhash, or the nonce value with `nonce`; zeroed when unset
+    pub blockhash: Option<Hash>,
+
+    /// Durable nonce to advance first, making the message valid until used
+    pub nonce: Option<DurableNonce>,
+}
+
+/// Durable nonce account and the authority allowed to advance it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    /// Nonce account
+    pub account: Pubkey,
+
+    /// Nonce authority, a signer of the message
+    pub authority: Pubkey,
+}
+
+/// State of an initialized nonce account: its authority and current nonce
+///
+/// `None` for uninitialized accounts and data that is not a nonce account.
+pub fn parse_nonce_account(data: &[u8]) -> Option<(Pubkey, Hash)> {
+    if data.len() != NONCE_ACCOUNT_LEN {
+        return None;
+    }
+    // u32 version, u32 state (1 = initialized), authority, durable nonce, fee calculator
+    let state = u32::from_le_bytes(data[4..8].try_into().ok()?);
+    if state != 1 {
+        return None;
+    }
+    let authority = Pubkey::try_from(&data[8..40]).ok()?;
+    let nonce = Hash::new_from_array(data[40..72].try_into().ok()?);
+    Some((authority, nonce))
+}
+
+/// An account of a built instruction
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuiltAccount {
+    /// Account name in the IDL
+    pub name: String,
+
+    /// Address (base58)
+    pub pubkey: String,
+
+    /// Whether the account is writable
+    pub writable: bool,
+
+    /// Whether the account must sign
+    pub signer: bool,
+}
+
+/// An encoded instruction, ready to be put in a transaction
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuiltInstruction {
+    /// Program the instruction is for (base58)
+    pub program_id: String,
+
+    /// Instruction name
+    pub instruction: String,
+
+    /// Accounts in instruction order (groups flattened)
+    pub accounts: Vec<BuiltAccount>,
+
+    /// Instruction data: discriminator followed by the Borsh-encoded args (hex)
+    pub data: String,
+}
+
+impl BuiltInstruction {
+    fn new(idl: &Idl, ix: &IdlInstruction, accounts: Vec<BuiltAccount>, data: &[u8]) -> Self {
+        BuiltInstruction {
+            program_id: idl.address.clone(),
+            instruction: ix.name.clone(),
+            accounts,
+            data: hex(data),
+        }
+    }
+
+    /// The instruction as an SDK [`Instruction`]
+    pub fn to_instruction(&self) -> PeriscopeResult<Instruction> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|a| {
+                let pubkey = parse_pubkey(&a.name, &a.pubkey)?;
+                Ok(if a.writable {
+                    AccountMeta::new(pubkey, a.signer)
+                } else {
+                    AccountMeta::new_readonly(pubkey, a.signer)
+                })
+            })
+            .collect::<PeriscopeResult<Vec<_>>>()?;
+        Ok(Instruction {
+            program_id: parse_pubkey("program", &self.program_id)?,
+            accounts,
+            data: parse_hex(&self.data).ok_or_else(|| PeriscopeError::EncodeError {
+                path: "data".to_string(),
+                reason: format!("'{}' is not valid hex", self.data),
+            })?,
+        })
+    }
+
+    /// Fee payer when none is given: the first signer account
+    pub fn default_payer(&self) -> Option<Pubkey> {
+        self.accounts
+            .iter()
+            .find(|a| a.signer)
+            .and_then(|a| Pubkey::from_str(&a.pubkey).ok())
+    }
+
+    /// Legacy message holding this instruction, paid by `payer`
+    ///
+    /// Without a blockhash it is left zeroed, for multisig UIs and signers to
+    /// set their own. With a durable nonce, `AdvanceNonceAccount` goes first.
+    pub fn to_message(&self, payer: &Pubkey, options: &MessageOptions) -> PeriscopeResult<Message> {
+        let mut instructions = Vec::new();
+        if let Some(nonce) = &options.nonce {
+            instructions.push(advance_nonce_account(&nonce.account, &nonce.authority));
+        }
+        instructions.push(self.to_instruction()?);
+        let blockhash = options.blockhash.unwrap_or_default();
+        Ok(Message::new_with_blockhash(
+            &instructions,
+            Some(payer),
+            &blockhash,
+        ))
+    }
+}
+
+/// Unsigned wire transaction for `message`: zeroed signature slots, then the message
+///
+/// This is what multisig UIs such as Squads import to create a proposal.
+pub fn unsigned_transaction(message: &Message) -> Vec<u8> {
+    let signatures = usize::from(message.header.num_required_signatures);
+    let mut out = Vec::new();
+    // compact-u16 length prefix
+    let mut len = signatures;
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+    out.resize(out.len() + signatures * 64, 0);
+    out.extend(message.serialize());
+    out
+}
+
+/// Build `ix` from a filled-in template (`{ "accounts": {...}, "args": {...} }`)
+///
+/// Accounts may be left empty (`""` or missing) when they have a fixed
+/// address, a derivable PDA, or are optional (the program ID stands in for
+/// an omitted optional account, as Anchor expects).
+pub fn build_from_template(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    filled: &Value,
+) -> PeriscopeResult<BuiltInstruction> {
+    let args = filled.get("args").cloned().unwrap_or(Value::Null);
+    let data = encode_instruction(idl, ix, &args)?;
+
+    let empty = Value::Object(Map::new());
+    let given = filled.get("accounts").unwrap_or(&empty);
+    let mut slots = Vec::new();
+    flatten_accounts(&ix.accounts, Some(given), &mut slots);
+
+    let mut known = HashMap::new();
+    for (account, value) in &slots {
+        let address = match value.and_then(Value::as_str).map(str::trim) {
+            Some(s) if !s.is_empty() => Some(s),
+            _ => account.address.as_deref(),
+        };
+        if let Some(address) = address {
+            known.insert(account.name.clone(), parse_pubkey(&account.name, address)?);
+        }
+    }
+
+    // PDAs may be seeded by other PDAs: derive until nothing changes
+    loop {
+        let mut progress = false;
+        for (account, _) in &slots {
+            if known.contains_key(&account.name) {
+                continue;
+            }
+            let Some(pda) = &account.pda else { continue };
+            if let Some(address) = derive_pda(idl, ix, pda, &known, &args) {
+                known.insert(account.name.clone(), address);
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    let program_id = parse_pubkey("program", &idl.address)?;
+    let accounts = slots
+        .iter()
+        .map(|(account, _)| match known.get(&account.name) {
+            Some(address) => Ok(built_account(account, address)),
+            None if account.optional => Ok(built_account(account, &program_id)),
+            None => Err(PeriscopeError::EncodeError {
+                path: format!("accounts.{}", account.name),
+                reason: if account.pda.is_some() {
+                    "missing address (PDA seeds could not be resolved)".to_string()
+                } else {
+                    "missing address".to_string()
+                },
+            }),
+        })
+        .collect::<PeriscopeResult<Vec<_>>>()?;
+
+    Ok(BuiltInstruction::new(idl, ix, accounts, &data))
+}
+
+/// Build `ix` by prompting for each argument, then each account
+#[cfg(feature = "cli")]
+pub fn build_interactive(idl: &Idl, ix: &IdlInstruction) -> PeriscopeResult<BuiltInstruction> {
+    let prompter = Prompter { idl };
+    let mut args = Map::new();
+    for arg in &ix.args {
+        args.insert(arg.name.clone(), prompter.value(&arg.name, &arg.ty, 0)?);
+    }
+    let args = Value::Object(args);
+    let data = encode_instruction(idl, ix, &args)?;
+
+    let mut slots = Vec::new();
+    flatten_accounts(&ix.accounts, None, &mut slots);
+
+    let program_id = parse_pubkey("program", &idl.address)?;
+    let mut known = HashMap::new();
+    let mut accounts = Vec::new();
+    for (account, _) in &slots {
+        let address = prompter.account(ix, account, &known, &args, &program_id)?;
+        known.insert(account.name.clone(), address);
+        accounts.push(built_account(account, &address));
+    }
+
+    Ok(BuiltInstruction::new(idl, ix, accounts, &data))
+}
+
+/// Accounts in instruction order, paired with their template value
+fn flatten_accounts<'a>(
+    items: &'a [IdlAccountItem],
+    given: Option<&'a Value>,
+    out: &mut Vec<(&'a IdlAccount, Option<&'a Value>)>,
+) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => {
+                out.push((account, given.and_then(|g| g.get(&account.name))))
+            }
+            IdlAccountItem::Group(group) => {
+                let given = given.and_then(|g| g.get(&group.name));
+                flatten_accounts(&group.accounts, given, out);
+            }
+        }
+    }
+}
+
+fn built_account(account: &IdlAccount, address: &Pubkey) -> BuiltAccount {
+    BuiltAccount {
+        name: account.name.clone(),
+        pubkey: address.to_string(),
+        writable: account.writable,
+        signer: account.signer,
+    }
+}
+
+fn parse_pubkey(name: &str, address: &str) -> PeriscopeResult<Pubkey> {
+    Pubkey::from_str(address).map_err(|_| PeriscopeError::EncodeError {
+        path: format!("accounts.{}", name),
+        reason: format!("'{}' is not a valid base58 pubkey", address),
+    })
+}
+
+/// Prompt failures: cancelling aborts the build, anything else is I/O
+#[cfg(feature = "cli")]
+fn prompt_error(e: InquireError) -> PeriscopeError {
+    match e {
+        InquireError::OperationCanceled | InquireError::OperationInterrupted => {
+            PeriscopeError::Usage(tr(Msg::BuildCancelled).to_string())
+        }
+        e => PeriscopeError::IoError(std::io::Error::other(e)),
+    }
+}
+
+#[cfg(feature = "cli")]
+struct Prompter<'a> {
+    idl: &'a Idl,
+}
+
+#[cfg(feature = "cli")]
+impl Prompter<'_> {
+    /// Prompt for an account address, offering a fixed address or derived PDA
+    fn account(
+        &self,
+        ix: &IdlInstruction,
+        account: &IdlAccount,
+        known: &HashMap<String, Pubkey>,
+        args: &Value,
+        program_id: &Pubkey,
+    ) -> PeriscopeResult<Pubkey> {
+        if let Some(address) = &account.address {
+            let address = parse_pubkey(&account.name, address)?;
+            println!(
+                "{} {} {}",
+                paint(&account.name, Element::Field),
+                address,
+                paint(tr(Msg::BuildFixedAddress), Element::Muted)
+            );
+            return Ok(address);
+        }
+
+        let derived = account
+            .pda
+            .as_ref()
+            .and_then(|pda| derive_pda(self.idl, ix, pda, known, args));
+        if let Some(derived) = derived {
+            let accepted = Confirm::new(&trf(Msg::BuildUsePda, &[&account.name, &derived]))
+                .with_default(true)
+                .prompt()
+                .map_err(prompt_error)?;
+            if accepted {
+                return Ok(derived);
+            }
+        }
+
+        let mut notes = vec!["pubkey"];
+        if account.signer {
+            notes.push("signer");
+        }
+        if account.writable {
+            notes.push("writable");
+        }
+        if account.optional {
+            notes.push("optional, empty for None");
+        }
+        let optional = account.optional;
+        let input = Text::new(&format!("{} ({})", account.name, notes.join(", ")))
+            .with_validator(move |s: &str| {
+                let s = s.trim();
+                Ok(
+                    if (optional && s.is_empty()) || Pubkey::from_str(s).is_ok() {
+                        Validation::Valid
+                    } else {
+                        Validation::Invalid(trf(Msg::InvalidAddress, &[&s]).into())
+                    },
+                )
+            })
+            .prompt()
+            .map_err(prompt_error)?;
+        match input.trim() {
+            "" => Ok(*program_id),
+            s => parse_pubkey(&account.name, s),
+        }
+    }
+
+    /// Prompt for a value of `ty`, one primitive at a time
+    fn value(&self, label: &str, ty: &IdlType, depth: usize) -> PeriscopeResult<Value> {
+        match ty {
+            IdlType::Primitive(name) => {
+                let type_name = name.clone();
+                let input = Text::new(&format!("{} ({})", label, name))
+                    .with_validator(move |s: &str| {
+                        Ok(match parse_primitive(&type_name, s) {
+                            Ok(_) => Validation::Valid,
+                            Err(reason) => Validation::Invalid(reason.into()),
+                        })
+                    })
+                    .prompt()
+                    .map_err(prompt_error)?;
+                parse_primitive(name, &input).map_err(|reason| PeriscopeError::EncodeError {
+                    path: label.to_string(),
+                    reason,
+                })
+            }
+            IdlType::Complex(IdlTypeComplex::Option(inner)) => {
+                let set = Confirm::new(&trf(Msg::BuildSetOption, &[&label]))
+                    .with_default(false)
+                    .prompt()
+                    .map_err(prompt_error)?;
+                if set {
+                    self.value(label, inner, depth)
+                } else {
+                    Ok(Value::Null)
+                }
+            }
+            IdlType::Complex(IdlTypeComplex::Vec(inner)) if is_u8(inner) => {
+                self.byte_items(label, None)
+            }
+            IdlType::Complex(IdlTypeComplex::Array(inner, IdlArrayLen::Value(len)))
+                if is_u8(inner) =>
+            {
+                self.byte_items(label, Some(*len))
+            }
+            IdlType::Complex(IdlTypeComplex::Vec(inner)) => {
+                let count = CustomType::<usize>::new(&trf(Msg::BuildItemCount, &[&label]))
+                    .prompt()
+                    .map_err(prompt_error)?;
+                self.items(label, inner, count, depth)
+            }
+            IdlType::Complex(IdlTypeComplex::Array(inner, IdlArrayLen::Value(len))) => {
+                self.items(label, inner, *len, depth)
+            }
+            IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+                self.defined(label, name, generics, depth)
+            }
+            IdlType::Complex(IdlTypeComplex::Array(_, IdlArrayLen::Generic(len))) => {
+                Err(PeriscopeError::EncodeError {
+                    path: label.to_string(),
+                    reason: format!("generic length {} is not bound to a value", len),
+                })
+            }
+            IdlType::Complex(IdlTypeComplex::Generic(name)) => Err(PeriscopeError::EncodeError {
+                path: label.to_string(),
+                reason: format!("generic parameter {} is not bound to a type", name),
+            }),
+        }
+    }
+
+    fn items(
+        &self,
+        label: &str,
+        inner: &IdlType,
+        count: usize,
+        depth: usize,
+    ) -> PeriscopeResult<Value> {
+        (0..count)
+            .map(|i| self.value(&format!("{}[{}]", label, i), inner, depth + 1))
+            .collect::<PeriscopeResult<Vec<_>>>()
+            .map(Value::Array)
+    }
+
+    /// `Vec<u8>` and `[u8; N]` are typed as one hex string
+    fn byte_items(&self, label: &str, len: Option<usize>) -> PeriscopeResult<Value> {
+        let parse = move |s: &str| -> Result<Vec<u8>, String> {
+            let s = s.trim();
+            let bytes = parse_hex(s.strip_prefix("0x").unwrap_or(s))
+                .ok_or_else(|| "expected hex bytes".to_string())?;
+            match len {
+                Some(len) if bytes.len() != len => {
+                    Err(format!("expected {} bytes, got {}", len, bytes.len()))
+                }
+                _ => Ok(bytes),
+            }
+        };
+        let input = Text::new(&format!("{} (hex)", label))
+            .with_validator(move |s: &str| {
+                Ok(match parse(s) {
+                    Ok(_) => Validation::Valid,
+                    Err(reason) => Validation::Invalid(reason.into()),
+                })
+            })
+            .prompt()
+            .map_err(prompt_error)?;
+        let bytes = parse(&input).map_err(|reason| PeriscopeError::EncodeError {
+            path: label.to_string(),
+            reason,
+        })?;
+        Ok(Value::Array(bytes.into_iter().map(Value::from).collect()))
+    }
+
+    fn defined(
+        &self,
+        label: &str,
+        name: &str,
+        generics: &[IdlGenericArg],
+        depth: usize,
+    ) -> PeriscopeResult<Value> {
+        let error = |reason: String| PeriscopeError::EncodeError {
+            path: label.to_string(),
+            reason,
+        };
+        if depth >= MAX_DEPTH {
+            return Err(error(format!("type nesting exceeds {} levels", MAX_DEPTH)));
+        }
+        let type_def = self.idl.instantiate_type(name, generics).map_err(error)?;
+
+        match &type_def.ty {
+            IdlTypeDefTy::Struct { fields } => self.fields(label, fields, depth),
+            IdlTypeDefTy::Enum { variants } => {
+                let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+                let chosen = Select::new(&format!("{} ({})", label, name), names)
+                    .prompt()
+                    .map_err(prompt_error)?;
+                let variant = variants
+                    .iter()
+                    .find(|v| v.name == chosen)
+                    .expect("chosen from the variants");
+                let label = format!("{}.{}", label, variant.name);
+                let fields = match &variant.fields {
+                    None => return Ok(Value::String(variant.name.clone())),
+                    Some(IdlEnumFields::Named(fields)) => self.fields(&label, fields, depth)?,
+                    Some(IdlEnumFields::Tuple(types)) => types
+                        .iter()
+                        .enumerate()
+                        .map(|(i, ty)| self.value(&format!("{}[{}]", label, i), ty, depth + 1))
+                        .collect::<PeriscopeResult<Vec<_>>>()
+                        .map(Value::Array)?,
+                };
+                let mut map = Map::new();
+                map.insert(variant.name.clone(), fields);
+                Ok(Value::Object(map))
+            }
+        }
+    }
+
+    fn fields(&self, label: &str, fields: &[IdlField], depth: usize) -> PeriscopeResult<Value> {
+        let mut map = Map::new();
+        for field in fields {
+            let label = format!("{}.{}", label, field.name);
+            map.insert(
+                field.name.clone(),
+                self.value(&label, &field.ty, depth + 1)?,
+            );
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+#[cfg(feature = "cli")]
+fn is_u8(ty: &IdlType) -> bool {
+    matches!(ty, IdlType::Primitive(name) if name == "u8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_nonce_account() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0u8; NONCE_ACCOUNT_LEN];
+        data[0..4].copy_from_slice(&1u32.to_le_bytes());
+        data[4..8].copy_from_slice(&1u32.to_le_bytes());
+        data[8..40].copy_from_slice(authority.as_ref());
+        data[40..72].copy_from_slice(&[7; 32]);
+        assert_eq!(
+            parse_nonce_account(&data),
+            Some((authority, Hash::new_from_array([7; 32])))
+        );
+
+        data[4..8].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(parse_nonce_account(&data), None);
+        assert_eq!(parse_nonce_account(&data[..40]), None);
+    }
+
+    #[test]
+    fn test_build_from_template_derives_pdas() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "open",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "vault", "writable": true, "pda": { "seeds": [
+                        { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                        { "kind": "account", "path": "user" },
+                        { "kind": "arg", "path": "id" }
+                    ] } },
+                    { "name": "referrer", "optional": true },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [{ "name": "id", "type": "u16" }]
+            }]
+        }))
+        .unwrap();
+        let ix = &idl.instructions[0];
+        let user = Pubkey::new_unique();
+        let filled = json!({
+            "accounts": { "user": user.to_string(), "vault": "" },
+            "args": { "id": 7 }
+        });
+
+        let built = build_from_template(&idl, ix, &filled).unwrap();
+        let program_id = Pubkey::from_str(&idl.address).unwrap();
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"vault", user.as_ref(), &7u16.to_le_bytes()],
+            &program_id,
+        );
+        assert_eq!(built.accounts[1].pubkey, vault.to_string());
+        assert_eq!(built.accounts[2].pubkey, idl.address);
+        assert!(built.accounts[0].signer && built.accounts[0].writable);
+        assert_eq!(built.data, "01020304050607080700");
+
+        let payer = built.default_payer().unwrap();
+        assert_eq!(payer, user);
+        let message = built
+            .to_message(&payer, &MessageOptions::default())
+            .unwrap();
+        let tx = unsigned_transaction(&message);
+        assert_eq!(tx[0], 1);
+        assert!(tx[1..65].iter().all(|b| *b == 0));
+        assert_eq!(tx[65..], message.serialize()[..]);
+
+        let authority = Pubkey::new_unique();
+        let blockhash = Hash::new_from_array([9; 32]);
+        let options = MessageOptions {
+            blockhash: Some(blockhash),
+            nonce: Some(DurableNonce {
+                account: Pubkey::new_unique(),
+                authority,
+            }),
+        };
+        let message = built.to_message(&payer, &options).unwrap();
+        assert_eq!(message.recent_blockhash, blockhash);
+        assert_eq!(message.instructions.len(), 2);
+        assert_eq!(message.instructions[0].data, vec![4, 0, 0, 0]);
+        assert!(message.is_signer(
+            message
+                .account_keys
+                .iter()
+                .position(|k| *k == authority)
+                .unwrap()
+        ));
+
+        // Hand-edited data that isn't hex is an error, never empty data
+        let mut edited = built.clone();
+        edited.data = "0xzz".to_string();
+        assert!(matches!(
+            edited.to_instruction(),
+            Err(PeriscopeError::EncodeError { .. })
+        ));
+
+        let missing = json!({ "accounts": {}, "args": { "id": 7 } });
+        match build_from_template(&idl, ix, &missing).unwrap_err() {
+            PeriscopeError::EncodeError { path, .. } => assert_eq!(path, "accounts.user"),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,624 @@
+//! Decoders for native and SPL program accounts
+//!
+//! SPL Token, Token-2022, Stake and Vote accounts have fixed layouts and no
+//! Anchor IDL, but show up constantly next to Anchor accounts. These decoders
+//! produce the same `DecodedAccount` shape as the IDL decoder.
+
+use crate::decode::{DecodedAccount, FieldSpan, Layout};
+use crate::error::{PeriscopeError, PeriscopeResult};
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+
+/// System program
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
+/// SPL Token program
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// SPL Token-2022 program
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Native stake program
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+
+/// Native vote program
+pub const VOTE_PROGRAM_ID: Pubkey = pubkey!("Vote111111111111111111111111111111111111111");
+
+/// Upgradeable BPF loader (owner of most deployed programs)
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+/// Well-known programs, by address
+pub const KNOWN_PROGRAMS: &[(Pubkey, &str)] = &[
+    (
+        pubkey!("11111111111111111111111111111111"),
+        "System Program",
+    ),
+    (TOKEN_PROGRAM_ID, "SPL Token"),
+    (TOKEN_2022_PROGRAM_ID, "SPL Token-2022"),
+    (
+        pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+        "SPL Associated Token Account",
+    ),
+    (
+        pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+        "SPL Memo",
+    ),
+    (
+        pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
+        "Metaplex Token Metadata",
+    ),
+    (STAKE_PROGRAM_ID, "Stake Program"),
+    (VOTE_PROGRAM_ID, "Vote Program"),
+    (
+        pubkey!("ComputeBudget111111111111111111111111111111"),
+        "Compute Budget",
+    ),
+    (
+        pubkey!("AddressLookupTab1e1111111111111111111111111"),
+        "Address Lookup Table",
+    ),
+    (BPF_LOADER_UPGRADEABLE_ID, "BPF Upgradeable Loader"),
+    (
+        pubkey!("BPFLoader2111111111111111111111111111111111"),
+        "BPF Loader v2",
+    ),
+    (
+        pubkey!("BPFLoader1111111111111111111111111111111111"),
+        "BPF Loader v1",
+    ),
+    (
+        pubkey!("LoaderV411111111111111111111111111111111111"),
+        "Loader v4",
+    ),
+    (
+        pubkey!("NativeLoader1111111111111111111111111111111"),
+        "Native Loader",
+    ),
+    (
+        pubkey!("Sysvar1111111111111111111111111111111111111"),
+        "Sysvar",
+    ),
+];
+
+/// Name of a well-known program, if `program_id` is one
+pub fn known_program_name(program_id: &Pubkey) -> Option<&'static str> {
+    KNOWN_PROGRAMS
+        .iter()
+        .find(|(id, _)| id == program_id)
+        .map(|(_, name)| *name)
+}
+
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const MULTISIG_LEN: usize = 355;
+const MULTISIG_MAX_SIGNERS: usize = 11;
+
+/// Token-2022 account type byte, stored right after the (padded) base layout
+const ACCOUNT_TYPE_OFFSET: usize = TOKEN_ACCOUNT_LEN;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Name of a builtin program, if `program_id` is one
+pub fn builtin_program_name(program_id: &Pubkey) -> Option<&'static str> {
+    match *program_id {
+        TOKEN_PROGRAM_ID => Some("SPL Token"),
+        TOKEN_2022_PROGRAM_ID => Some("SPL Token-2022"),
+        STAKE_PROGRAM_ID => Some("Stake"),
+        VOTE_PROGRAM_ID => Some("Vote"),
+        _ => None,
+    }
+}
+
+/// Decode an account owned by a builtin program.
+///
+/// Returns `None` when `owner` is not a builtin program.
+pub fn decode_builtin_account(
+    owner: &Pubkey,
+    data: &[u8],
+) -> Option<PeriscopeResult<DecodedAccount>> {
+    let decoded = match *owner {
+        TOKEN_PROGRAM_ID => decode_token(data, false),
+        TOKEN_2022_PROGRAM_ID => decode_token(data, true),
+        STAKE_PROGRAM_ID => decode_stake(data),
+        VOTE_PROGRAM_ID => decode_vote(data),
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+fn decode_token(data: &[u8], token_2022: bool) -> PeriscopeResult<DecodedAccount> {
+    match data.len() {
+        MINT_LEN => decode_mint(data),
+        TOKEN_ACCOUNT_LEN => decode_token_account(data),
+        MULTISIG_LEN => decode_multisig(data),
+        len if token_2022 && len > ACCOUNT_TYPE_OFFSET => match data[ACCOUNT_TYPE_OFFSET] {
+            ACCOUNT_TYPE_MINT => decode_mint(data),
+            ACCOUNT_TYPE_ACCOUNT => decode_token_account(data),
+            other => Err(PeriscopeError::DecodeError {
+                path: "account_type".to_string(),
+                offset: ACCOUNT_TYPE_OFFSET,
+                reason: format!("unknown Token-2022 account type {}", other),
+            }),
+        },
+        len => Err(PeriscopeError::DecodeError {
+            path: "<root>".to_string(),
+            offset: 0,
+            reason: format!("{} bytes doesn't match any token account layout", len),
+        }),
+    }
+}
+
+fn decode_mint(data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    let mut r = FieldReader::new("Mint", data);
+    r.coption_pubkey("mint_authority")?;
+    r.u64("supply")?;
+    r.u8("decimals")?;
+    r.bool("is_initialized")?;
+    r.coption_pubkey("freeze_authority")?;
+    if data.len() > MINT_LEN {
+        // Mints are padded to the token account size before the account type byte
+        r.offset = ACCOUNT_TYPE_OFFSET + 1;
+        r.extensions()?;
+    }
+    Ok(r.finish())
+}
+
+fn decode_token_account(data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    let mut r = FieldReader::new("TokenAccount", data);
+    r.pubkey("mint")?;
+    r.pubkey("owner")?;
+    r.u64("amount")?;
+    r.coption_pubkey("delegate")?;
+    let start = r.offset;
+    let state = match r.take(1)?[0] {
+        0 => "Uninitialized",
+        1 => "Initialized",
+        2 => "Frozen",
+        _ => "Unknown",
+    };
+    r.set("state", json!(state), "AccountState", start);
+    r.coption_u64("is_native")?;
+    r.u64("delegated_amount")?;
+    r.coption_pubkey("close_authority")?;
+    if data.len() > TOKEN_ACCOUNT_LEN {
+        r.offset = ACCOUNT_TYPE_OFFSET + 1;
+        r.extensions()?;
+    }
+    Ok(r.finish())
+}
+
+fn decode_multisig(data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    let mut r = FieldReader::new("Multisig", data);
+    r.u8("m")?;
+    r.u8("n")?;
+    r.bool("is_initialized")?;
+    let start = r.offset;
+    let signers: Vec<Value> = (0..MULTISIG_MAX_SIGNERS)
+        .map(|_| r.take(32).map(pubkey_value))
+        .collect::<PeriscopeResult<_>>()?;
+    r.set(
+        "signers",
+        Value::Array(signers),
+        &format!("[pubkey; {}]", MULTISIG_MAX_SIGNERS),
+        start,
+    );
+    Ok(r.finish())
+}
+
+fn decode_stake(data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    let mut r = FieldReader::new("StakeAccount", data);
+    let start = r.offset;
+    let tag = u32::from_le_bytes(r.array()?);
+    let state = match tag {
+        0 => "Uninitialized",
+        1 => "Initialized",
+        2 => "Stake",
+        3 => "RewardsPool",
+        _ => {
+            return Err(r.error_at(start, format!("unknown stake state {}", tag)));
+        }
+    };
+    r.set("state", json!(state), "StakeStateV2", start);
+
+    if tag == 1 || tag == 2 {
+        r.nested("meta", |r| {
+            r.u64("rent_exempt_reserve")?;
+            r.nested("authorized", |r| {
+                r.pubkey("staker")?;
+                r.pubkey("withdrawer")
+            })?;
+            r.nested("lockup", |r| {
+                r.i64("unix_timestamp")?;
+                r.u64("epoch")?;
+                r.pubkey("custodian")
+            })
+        })?;
+    }
+
+    if tag == 2 {
+        r.nested("stake", |r| {
+            r.nested("delegation", |r| {
+                r.pubkey("voter_pubkey")?;
+                r.u64("stake")?;
+                r.u64("activation_epoch")?;
+                r.u64("deactivation_epoch")?;
+                r.f64("warmup_cooldown_rate")
+            })?;
+            r.u64("credits_observed")
+        })?;
+        r.u8("stake_flags")?;
+    }
+
+    Ok(r.finish())
+}
+
+/// Only the fixed header of vote accounts is decoded; votes, credits and
+/// authorized voter history live in variable-length collections after it.
+fn decode_vote(data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    let mut r = FieldReader::new("VoteAccount", data);
+    let start = r.offset;
+    let version = u32::from_le_bytes(r.array()?);
+    let name = match version {
+        0 => "V0_23_5",
+        1 => "V1_14_11",
+        2 => "Current",
+        _ => {
+            return Err(r.error_at(start, format!("unknown vote state version {}", version)));
+        }
+    };
+    r.set("version", json!(name), "VoteStateVersions", start);
+
+    r.pubkey("node_pubkey")?;
+    if version == 0 {
+        r.pubkey("authorized_voter")?;
+    } else {
+        r.pubkey("authorized_withdrawer")?;
+        r.u8("commission")?;
+    }
+
+    Ok(r.finish())
+}
+
+/// Token-2022 extension names, indexed by `ExtensionType` discriminant
+const EXTENSION_NAMES: &[&str] = &[
+    "Uninitialized",
+    "TransferFeeConfig",
+    "TransferFeeAmount",
+    "MintCloseAuthority",
+    "ConfidentialTransferMint",
+    "ConfidentialTransferAccount",
+    "DefaultAccountState",
+    "ImmutableOwner",
+    "MemoTransfer",
+    "NonTransferable",
+    "InterestBearingConfig",
+    "CpiGuard",
+    "PermanentDelegate",
+    "NonTransferableAccount",
+    "TransferHook",
+    "TransferHookAccount",
+    "ConfidentialTransferFeeConfig",
+    "ConfidentialTransferFeeAmount",
+    "MetadataPointer",
+    "TokenMetadata",
+    "GroupPointer",
+    "TokenGroup",
+    "GroupMemberPointer",
+    "TokenGroupMember",
+    "ConfidentialMintBurn",
+    "ScaledUiAmount",
+    "Pausable",
+    "PausableAccount",
+];
+
+/// Sequential reader over a fixed layout that builds a JSON object and field spans
+struct FieldReader<'a> {
+    root: &'static str,
+    data: &'a [u8],
+    offset: usize,
+    path: Vec<String>,
+    fields: Map<String, Value>,
+    spans: Vec<FieldSpan>,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(root: &'static str, data: &'a [u8]) -> Self {
+        Self {
+            root,
+            data,
+            offset: 0,
+            path: Vec::new(),
+            fields: Map::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    fn path_string(&self, name: Option<&str>) -> String {
+        let mut segments = vec![self.root.to_string()];
+        segments.extend(self.path.iter().cloned());
+        segments.extend(name.map(str::to_string));
+        segments.join(".")
+    }
+
+    fn error_at(&self, offset: usize, reason: impl Into<String>) -> PeriscopeError {
+        PeriscopeError::DecodeError {
+            path: self.path_string(None),
+            offset,
+            reason: reason.into(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> PeriscopeResult<&'a [u8]> {
+        let remaining = self.data.len().saturating_sub(self.offset);
+        if len > remaining {
+            return Err(self.error_at(
+                self.offset,
+                format!("needs {} bytes but only {} remain", len, remaining),
+            ));
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> PeriscopeResult<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    /// Insert a field read from `start` up to the current offset
+    fn set(&mut self, name: &str, value: Value, ty: &str, start: usize) {
+        self.spans.push(FieldSpan {
+            path: self.path_string(Some(name)),
+            ty: ty.to_string(),
+            start,
+            end: self.offset,
+        });
+        self.fields.insert(name.to_string(), value);
+    }
+
+    /// Read a nested object under `name`
+    fn nested(
+        &mut self,
+        name: &str,
+        read: impl FnOnce(&mut Self) -> PeriscopeResult<()>,
+    ) -> PeriscopeResult<()> {
+        let outer = std::mem::take(&mut self.fields);
+        self.path.push(name.to_string());
+        let result = read(self);
+        self.path.pop();
+        let inner = std::mem::replace(&mut self.fields, outer);
+        result?;
+        self.fields.insert(name.to_string(), Value::Object(inner));
+        Ok(())
+    }
+
+    fn u8(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let v = self.take(1)?[0];
+        self.set(name, json!(v), "u8", start);
+        Ok(())
+    }
+
+    fn bool(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let v = self.take(1)?[0] != 0;
+        self.set(name, json!(v), "bool", start);
+        Ok(())
+    }
+
+    fn u64(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let v = u64::from_le_bytes(self.array()?);
+        self.set(name, json!(v), "u64", start);
+        Ok(())
+    }
+
+    fn i64(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let v = i64::from_le_bytes(self.array()?);
+        self.set(name, json!(v), "i64", start);
+        Ok(())
+    }
+
+    fn f64(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let v = f64::from_le_bytes(self.array()?);
+        self.set(name, json!(v), "f64", start);
+        Ok(())
+    }
+
+    fn pubkey(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let v = pubkey_value(self.take(32)?);
+        self.set(name, v, "pubkey", start);
+        Ok(())
+    }
+
+    /// `COption<Pubkey>`: u32 tag followed by the (always present) pubkey
+    fn coption_pubkey(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let tag = u32::from_le_bytes(self.array()?);
+        let key = pubkey_value(self.take(32)?);
+        let v = if tag == 0 { Value::Null } else { key };
+        self.set(name, v, "COption<pubkey>", start);
+        Ok(())
+    }
+
+    /// `COption<u64>`: u32 tag followed by the (always present) value
+    fn coption_u64(&mut self, name: &str) -> PeriscopeResult<()> {
+        let start = self.offset;
+        let tag = u32::from_le_bytes(self.array()?);
+        let amount = u64::from_le_bytes(self.array()?);
+        let v = if tag == 0 { Value::Null } else { json!(amount) };
+        self.set(name, v, "COption<u64>", start);
+        Ok(())
+    }
+
+    /// Token-2022 TLV extensions: u16 type, u16 length, value
+    fn extensions(&mut self) -> PeriscopeResult<()> {
+        let mut extensions = Vec::new();
+
+        while self.data.len() - self.offset >= 4 {
+            let start = self.offset;
+            let ext_type = u16::from_le_bytes(self.array()?);
+            let len = u16::from_le_bytes(self.array()?) as usize;
+            if ext_type == 0 && len == 0 {
+                // Zeroed space after the last extension
+                self.offset = start;
+                break;
+            }
+
+            let name = EXTENSION_NAMES
+                .get(ext_type as usize)
+                .copied()
+                .unwrap_or("Unknown");
+            self.path.push(format!("extensions[{}]", extensions.len()));
+            let value = self.take(len)?;
+            self.path.pop();
+
+            let mut map = Map::new();
+            map.insert(name.to_string(), decode_extension(name, value));
+            extensions.push(Value::Object(map));
+
+            self.spans.push(FieldSpan {
+                path: self.path_string(Some(&format!("extensions[{}]", extensions.len() - 1))),
+                ty: name.to_string(),
+                start,
+                end: self.offset,
+            });
+        }
+
+        self.fields
+            .insert("extensions".to_string(), Value::Array(extensions));
+        Ok(())
+    }
+
+    fn finish(self) -> DecodedAccount {
+        DecodedAccount {
+            name: self.root.to_string(),
+            layout: Layout::Native,
+            value: Value::Object(self.fields),
+            consumed: self.offset,
+            data_len: self.data.len(),
+            spans: self.spans,
+        }
+    }
+}
+
+/// Decode the extensions with simple fixed layouts; others report their size
+fn decode_extension(name: &str, data: &[u8]) -> Value {
+    let pubkey_at = |offset: usize| optional_pubkey(data.get(offset..offset + 32));
+
+    match (name, data.len()) {
+        ("MintCloseAuthority", 32) => json!({ "close_authority": pubkey_at(0) }),
+        ("PermanentDelegate", 32) => json!({ "delegate": pubkey_at(0) }),
+        ("MetadataPointer", 64) | ("GroupPointer", 64) => {
+            json!({ "authority": pubkey_at(0), "address": pubkey_at(32) })
+        }
+        ("TransferHook", 64) => json!({ "authority": pubkey_at(0), "program_id": pubkey_at(32) }),
+        ("DefaultAccountState", 1) => json!({ "state": data[0] }),
+        ("ImmutableOwner", 0) | ("NonTransferable", 0) | ("NonTransferableAccount", 0) => {
+            json!({})
+        }
+        ("TokenMetadata", _) => {
+            decode_token_metadata(data).unwrap_or_else(|| json!({ "length": data.len() }))
+        }
+        _ => json!({ "length": data.len() }),
+    }
+}
+
+/// Token metadata extension: update authority, mint, then Borsh strings
+fn decode_token_metadata(data: &[u8]) -> Option<Value> {
+    let mut offset = 64;
+    let mut read_string = || {
+        let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let s = std::str::from_utf8(data.get(offset + 4..offset + 4 + len)?).ok()?;
+        offset += 4 + len;
+        Some(s.to_string())
+    };
+
+    let name = read_string()?;
+    let symbol = read_string()?;
+    let uri = read_string()?;
+
+    Some(json!({
+        "update_authority": optional_pubkey(data.get(0..32)),
+        "mint": optional_pubkey(data.get(32..64)),
+        "name": name,
+        "symbol": symbol,
+        "uri": uri,
+    }))
+}
+
+fn pubkey_value(bytes: &[u8]) -> Value {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(bytes);
+    Value::String(Pubkey::new_from_array(key).to_string())
+}
+
+/// Token-2022 `OptionalNonZeroPubkey`: all zeroes means none
+fn optional_pubkey(bytes: Option<&[u8]>) -> Value {
+    match bytes {
+        Some(bytes) if bytes.iter().any(|b| *b != 0) => pubkey_value(bytes),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_token_account() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&[0; 36]);
+        data.push(1);
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&[0; 36]);
+        assert_eq!(data.len(), TOKEN_ACCOUNT_LEN);
+
+        let decoded = decode_builtin_account(&TOKEN_PROGRAM_ID, &data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decoded.name, "TokenAccount");
+        assert_eq!(decoded.value["mint"], json!(mint.to_string()));
+        assert_eq!(decoded.value["amount"], json!(500));
+        assert_eq!(decoded.value["delegate"], Value::Null);
+        assert_eq!(decoded.value["state"], json!("Initialized"));
+        assert_eq!(decoded.trailing_bytes(), 0);
+    }
+
+    #[test]
+    fn test_decode_token_2022_mint_extensions() {
+        let authority = Pubkey::new_unique();
+
+        let mut data = vec![0u8; MINT_LEN];
+        data[MINT_LEN - 37] = 1; // is_initialized
+        data.resize(ACCOUNT_TYPE_OFFSET, 0);
+        data.push(ACCOUNT_TYPE_MINT);
+        data.extend_from_slice(&3u16.to_le_bytes());
+        data.extend_from_slice(&32u16.to_le_bytes());
+        data.extend_from_slice(authority.as_ref());
+
+        let decoded = decode_builtin_account(&TOKEN_2022_PROGRAM_ID, &data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decoded.name, "Mint");
+        assert_eq!(decoded.value["is_initialized"], json!(true));
+        assert_eq!(
+            decoded.value["extensions"][0]["MintCloseAuthority"]["close_authority"],
+            json!(authority.to_string())
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! IDL bundles
+//!
+//! A bundle (`.psc`) pins the IDLs of several programs in one versioned JSON
+//! file, so an indexer can ship with exactly the IDLs it was built against.
+//! Each IDL is stored as fetched, legacy format included. Commands read a
+//! bundled IDL with `--idl bundle:<path>#<program_id>`; the `#<program_id>`
+//! part can be left out when the bundle holds a single program or the
+//! program ID is given as an argument.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::i18n::{trf, Msg};
+use crate::idl::{read_idl_json_from_file, IdlNames};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// `format` field of every bundle
+pub const BUNDLE_FORMAT: &str = "periscope-bundle";
+
+/// Version of the bundle layout this crate writes and reads
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// `--idl` prefix selecting a bundle
+pub const BUNDLE_PREFIX: &str = "bundle:";
+
+/// A set of program IDLs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    /// Always [`BUNDLE_FORMAT`]
+    pub format: String,
+
+    /// Layout version, see [`BUNDLE_VERSION`]
+    pub version: u32,
+
+    /// Unix time the bundle was created
+    pub created_at: u64,
+
+    pub programs: Vec<BundleEntry>,
+}
+
+/// One program of a bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleEntry {
+    /// Program address
+    pub address: String,
+
+    /// Program name from the IDL
+    pub name: String,
+
+    /// Where the IDL came from: program ID (on-chain), file or URL
+    pub source: String,
+
+    /// RPC URL for on-chain sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+
+    /// The IDL as fetched
+    pub idl: Value,
+}
+
+impl Bundle {
+    /// Empty bundle created at `created_at` (Unix time)
+    pub fn new(created_at: u64) -> Self {
+        Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            created_at,
+            programs: Vec::new(),
+        }
+    }
+
+    /// Add an IDL, replacing any earlier entry for the same program
+    ///
+    /// `address` is required for legacy IDLs without `metadata.address`.
+    pub fn add(
+        &mut self,
+        idl_json: &str,
+        address: Option<&str>,
+        source: &str,
+        rpc_url: Option<&str>,
+    ) -> PeriscopeResult<&BundleEntry> {
+        let idl: Value = serde_json::from_str(idl_json)?;
+        let names = IdlNames::parse(idl_json)?;
+        let address = address
+            .map(str::to_string)
+            .or_else(|| idl["address"].as_str().map(str::to_string))
+            .or_else(|| idl["metadata"]["address"].as_str().map(str::to_string))
+            .ok_or_else(|| PeriscopeError::Usage(trf(Msg::BundleNoAddress, &[&source])))?;
+
+        self.programs.retain(|p| p.address != address);
+        self.programs.push(BundleEntry {
+            address,
+            name: names.name.to_string(),
+            source: source.to_string(),
+            rpc_url: rpc_url.map(str::to_string),
+            idl,
+        });
+        Ok(self.programs.last().expect("just pushed"))
+    }
+
+    /// Read a bundle file
+    pub fn read(path: &Path) -> PeriscopeResult<Self> {
+        let bundle: Bundle = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if bundle.format != BUNDLE_FORMAT || bundle.version > BUNDLE_VERSION {
+            return Err(PeriscopeError::Usage(trf(
+                Msg::BundleUnsupported,
+                &[&path.display(), &bundle.format, &bundle.version],
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// Write the bundle as pretty JSON
+    pub fn write(&self, path: &Path) -> PeriscopeResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// IDL JSON of `program_id`, or of the only program when `None`
+    pub fn idl_json(&self, program_id: Option<&str>) -> PeriscopeResult<String> {
+        let entry = match (program_id, self.programs.as_slice()) {
+            (Some(id), programs) => programs.iter().find(|p| p.address == id),
+            (None, [only]) => Some(only),
+            (None, _) => {
+                let addresses: Vec<&str> =
+                    self.programs.iter().map(|p| p.address.as_str()).collect();
+                return Err(PeriscopeError::Usage(trf(
+                    Msg::BundleProgramRequired,
+                    &[&addresses.join(", ")],
+                )));
+            }
+        };
+        let entry = entry.ok_or_else(|| {
+            PeriscopeError::NotFound(trf(
+                Msg::BundleProgramNotFound,
+                &[&program_id.unwrap_or_default()],
+            ))
+        })?;
+        Ok(serde_json::to_string(&entry.idl)?)
+    }
+}
+
+/// Split `bundle:<path>#<program_id>` into the path and optional program ID
+pub fn parse_bundle_ref(source: &str) -> Option<(&str, Option<&str>)> {
+    let reference = source.strip_prefix(BUNDLE_PREFIX)?;
+    Some(match reference.rsplit_once('#') {
+        Some((path, program_id)) if !program_id.is_empty() => (path, Some(program_id)),
+        Some((path, _)) => (path, None),
+        None => (reference, None),
+    })
+}
+
+/// IDL JSON of a file or a bundle entry (`bundle:<path>#<program_id>`), as
+/// stored: the trust policy isn't applied, see
+/// [`TrustPolicy::check_file`](crate::trust::TrustPolicy::check_file)
+pub fn read_local_idl_json(source: &str) -> PeriscopeResult<String> {
+    match parse_bundle_ref(source) {
+        Some((path, program_id)) => Bundle::read(Path::new(path))?.idl_json(program_id),
+        None => read_idl_json_from_file(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let idl = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": []
+        }"#;
+        let legacy = r#"{ "version": "0.1.0", "name": "vault", "instructions": [] }"#;
+
+        let mut bundle = Bundle::new(1_700_000_000);
+        bundle.add(idl, None, "amm.json", None).unwrap();
+        assert!(bundle.add(legacy, None, "vault.json", None).is_err());
+        bundle
+            .add(
+                legacy,
+                Some("Vote111111111111111111111111111111111111111"),
+                "Vote111111111111111111111111111111111111111",
+                Some("https://api.devnet.solana.com"),
+            )
+            .unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("periscope-bundle-{}.psc", std::process::id()));
+        bundle.write(&path).unwrap();
+        let read = Bundle::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(read, bundle);
+
+        let json = read
+            .idl_json(Some("Vote111111111111111111111111111111111111111"))
+            .unwrap();
+        assert!(json.contains("\"vault\""));
+        assert!(read.idl_json(None).is_err());
+        assert!(read.idl_json(Some("missing")).is_err());
+
+        assert_eq!(
+            parse_bundle_ref("bundle:protocol.psc#1111"),
+            Some(("protocol.psc", Some("1111")))
+        );
+        assert_eq!(
+            parse_bundle_ref("bundle:protocol.psc"),
+            Some(("protocol.psc", None))
+        );
+        assert_eq!(parse_bundle_ref("idl.json"), None);
+    }
+}
@@ -1,15 +1,182 @@
 //! IDL caching layer for Periscope
 //!
-//! Caches fetched IDLs at ~/.config/periscope/cache/
+//! Caches IDLs fetched on-chain at ~/.config/periscope/cache/<cluster>/,
+//! one JSON entry per program with the slot it was fetched at. An entry is
+//! reused only while the IDL account still holds its IDL, checked from a
+//! few bytes of the account, see [`IdlCache::get_checked`]; `--refresh`
+//! bypasses the cache. `periscope cache export` packs every entry into a
+//! zstd-compressed JSON archive (`.json.zst`) that `cache import` unpacks
+//! on another machine, so CI runners start warm instead of hitting rate
+//! limits.
+//!
+//! `clusters.json` next to the entries remembers which cluster each RPC
+//! host serves, as identified by genesis hash.
+//!
+//! Entries are read through memory maps rather than copied into buffers,
+//! and [`IdlCache::handles`] lists them without reading any: a process
+//! that keeps hundreds of cached programs on hand reads and parses each IDL
+//! only when it is first asked for. Entries are replaced by renaming a new
+//! file over the old one, never rewritten in place, so a mapped file never
+//! changes under a reader.
 
-use crate::idl::Idl;
-use std::path::PathBuf;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{
+    fetch_idl_checksum, idl_checksum, parse_idl_json, probe_idls, IdlProbe, SharedIdl,
+};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 /// Cache directory name
 pub const CACHE_DIR: &str = "cache";
 
+/// File mapping RPC hosts to the cluster they serve
+const CLUSTERS_FILE: &str = "clusters.json";
+
+/// `format` field of cache archives
+pub const ARCHIVE_FORMAT: &str = "periscope-cache";
+
+/// Version of the archive layout this crate writes and reads
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Largest decompressed archive accepted by [`IdlCache::import`]
+pub const MAX_ARCHIVE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// A cached IDL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Program ID (base58)
+    pub program_id: String,
+
+    /// Cluster the IDL was fetched from (`mainnet-beta`, `devnet`, or the RPC host)
+    pub cluster: String,
+
+    /// Slot the IDL was fetched at, when the RPC reported it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+
+    /// IDL authority when the IDL was fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+
+    /// Length of the compressed IDL in the account when it was fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed_len: Option<usize>,
+
+    /// Unix timestamp of the fetch
+    pub fetched_at: u64,
+
+    /// Added by `cache import`: the authority is the archive's word until
+    /// the IDL account confirms it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub imported: bool,
+
+    /// The IDL JSON as stored on-chain
+    pub idl_json: String,
+}
+
+impl CacheEntry {
+    /// Whether the IDL account, as probed now, still holds this entry's IDL:
+    /// the compressed IDL has the same length and ends with the checksum of
+    /// this entry's JSON (see [`fetch_idl_checksum`]), and the authority is
+    /// the same when the entry records one. Entries without a recorded
+    /// length never are.
+    pub fn is_current(&self, probe: &IdlProbe, checksum: Option<u32>) -> bool {
+        let same_authority = match &self.authority {
+            Some(authority) => probe
+                .authority
+                .is_some_and(|probed| probed.to_string() == *authority),
+            None => true,
+        };
+        probe.has_idl()
+            && self.compressed_len.is_some()
+            && probe.compressed_len == self.compressed_len
+            && same_authority
+            && checksum == Some(idl_checksum(self.idl_json.as_bytes()))
+    }
+
+    /// Whether this entry was fetched later than `other`: by slot when both
+    /// have one, by time otherwise
+    pub fn is_newer_than(&self, other: &CacheEntry) -> bool {
+        match (self.slot, other.slot) {
+            (Some(a), Some(b)) if a != b => a > b,
+            _ => self.fetched_at > other.fetched_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    format: String,
+    version: u32,
+    entries: Vec<CacheEntry>,
+}
+
+/// Result of [`IdlCache::import`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    /// Entries in the archive
+    pub total: usize,
+
+    /// Entries written; the others were older than the local copy
+    pub imported: usize,
+}
+
+/// A cached entry, as [`IdlCache::get_checked`] found it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedEntry {
+    pub entry: CacheEntry,
+
+    /// The IDL account was read and still holds the entry's IDL; false when
+    /// the RPC node couldn't be reached and the entry is served unchecked
+    pub confirmed: bool,
+}
+
+/// A cached IDL that is read and parsed on first use
+#[derive(Debug)]
+pub struct CachedIdl {
+    /// Program ID (base58), from the entry's file name
+    pub program_id: String,
+
+    /// Entry file
+    pub path: PathBuf,
+
+    idl: OnceLock<SharedIdl>,
+}
+
+impl CachedIdl {
+    /// Read the whole entry
+    pub fn entry(&self) -> PeriscopeResult<CacheEntry> {
+        read_entry(&self.path)
+    }
+
+    /// The parsed IDL; read and parsed on the first call only
+    pub fn idl(&self) -> PeriscopeResult<&SharedIdl> {
+        if let Some(idl) = self.idl.get() {
+            return Ok(idl);
+        }
+        let idl = SharedIdl::new(parse_idl_json(&self.entry()?.idl_json)?);
+        Ok(self.idl.get_or_init(|| idl))
+    }
+
+    /// Whether the IDL has been parsed yet
+    pub fn is_loaded(&self) -> bool {
+        self.idl.get().is_some()
+    }
+}
+
 /// IDL cache manager
-pub struct IdlCache;
+#[derive(Debug, Clone)]
+pub struct IdlCache {
+    dir: PathBuf,
+}
 
 impl IdlCache {
     /// Get the cache directory path (~/.config/periscope/cache/)
@@ -19,32 +186,406 @@ impl IdlCache {
             .map(|p| p.join(CACHE_DIR))
     }
 
-    /// Get cached IDL for a program, if it exists
-    pub fn get(_program_id: &str) -> Option<Idl> {
-        // TODO: Implement cache retrieval
-        // 1. Build path: cache_dir/{program_id}.json
-        // 2. Read and parse if exists
-        // 3. Return None if not cached
-        todo!("Implement cache get")
+    /// The cache in the config directory
+    pub fn open() -> PeriscopeResult<Self> {
+        Self::cache_dir().map(Self::at).ok_or_else(|| {
+            PeriscopeError::CacheError("could not determine config directory".to_string())
+        })
+    }
+
+    /// A cache rooted at `dir`
+    pub fn at(dir: PathBuf) -> Self {
+        IdlCache { dir }
+    }
+
+    /// Get the cached IDL of a program on a cluster, if it exists
+    pub fn get(&self, cluster: &str, program_id: &str) -> Option<CacheEntry> {
+        read_entry(&self.entry_path(cluster, program_id)).ok()
+    }
+
+    /// The cached IDL of a program on a cluster, unless the IDL account has
+    /// changed since. Checking reads the account's header and the last 4
+    /// bytes of its compressed IDL, not the IDL; when that fails, the entry
+    /// is served unconfirmed rather than dropped.
+    pub fn get_checked(
+        &self,
+        client: &RpcClient,
+        cluster: &str,
+        program_id: &Pubkey,
+    ) -> Option<CheckedEntry> {
+        let entry = self.get(cluster, &program_id.to_string())?;
+        let probe = probe_idls(client, std::slice::from_ref(program_id))
+            .map(|mut probes| probes.remove(program_id));
+        let checked = match probe {
+            Ok(Some(probe)) => fetch_idl_checksum(client, &probe)
+                .map(|checksum| entry.is_current(&probe, checksum)),
+            Ok(None) => Ok(false),
+            Err(e) => Err(e),
+        };
+        match checked {
+            Ok(true) => Some(CheckedEntry {
+                entry,
+                confirmed: true,
+            }),
+            Ok(false) => None,
+            Err(_) => Some(CheckedEntry {
+                entry,
+                confirmed: false,
+            }),
+        }
     }
 
-    /// Store IDL in cache
-    pub fn set(_program_id: &str, _idl: &Idl) -> anyhow::Result<()> {
-        // TODO: Implement cache storage
-        // 1. Serialize IDL to JSON
-        // 2. Write to cache_dir/{program_id}.json
-        todo!("Implement cache set")
+    /// Store an entry, replacing any earlier one for the program and cluster
+    pub fn set(&self, entry: &CacheEntry) -> PeriscopeResult<()> {
+        Pubkey::from_str(&entry.program_id).map_err(|_| {
+            PeriscopeError::CacheError(format!("invalid program ID '{}'", entry.program_id))
+        })?;
+        let path = self.entry_path(&entry.cluster, &entry.program_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Readers may have the old file mapped: replace it, don't rewrite it
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, serde_json::to_string(entry)?)?;
+        fs::rename(&partial, &path).inspect_err(|_| {
+            let _ = fs::remove_file(&partial);
+        })?;
+        Ok(())
     }
 
     /// Clear cached IDL for a specific program
-    pub fn clear(_program_id: &str) -> anyhow::Result<()> {
-        // TODO: Implement cache clearing
-        todo!("Implement cache clear")
+    pub fn clear(&self, cluster: &str, program_id: &str) -> PeriscopeResult<()> {
+        match fs::remove_file(self.entry_path(cluster, program_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
     }
 
     /// Clear entire cache
-    pub fn clear_all() -> anyhow::Result<()> {
-        // TODO: Implement full cache clear
-        todo!("Implement cache clear all")
+    pub fn clear_all(&self) -> PeriscopeResult<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every cached entry, by cluster then program ID; unreadable files are skipped
+    pub fn entries(&self) -> PeriscopeResult<Vec<CacheEntry>> {
+        let mut entries: Vec<CacheEntry> = self
+            .entry_paths()?
+            .iter()
+            .filter_map(|path| read_entry(path).ok())
+            .collect();
+        entries.sort_by(|a, b| (&a.cluster, &a.program_id).cmp(&(&b.cluster, &b.program_id)));
+        Ok(entries)
+    }
+
+    /// Every cached entry as a handle that reads and parses its IDL on
+    /// first use; listing them reads no entry
+    pub fn handles(&self) -> PeriscopeResult<Vec<CachedIdl>> {
+        Ok(self
+            .entry_paths()?
+            .into_iter()
+            .filter_map(|path| {
+                let program_id = path.file_stem()?.to_str()?.to_string();
+                Some(CachedIdl {
+                    program_id,
+                    path,
+                    idl: OnceLock::new(),
+                })
+            })
+            .collect())
+    }
+
+    /// Entry files, by cluster directory then file name
+    fn entry_paths(&self) -> PeriscopeResult<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        let clusters = match fs::read_dir(&self.dir) {
+            Ok(clusters) => clusters,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(paths),
+            Err(e) => return Err(e.into()),
+        };
+        for cluster in clusters.flatten().filter(|c| c.path().is_dir()) {
+            for file in fs::read_dir(cluster.path())?.flatten() {
+                let path = file.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    paths.push(path);
+                }
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Write every entry to a zstd-compressed JSON archive (not a tar),
+    /// returning how many
+    pub fn export(&self, path: &Path) -> PeriscopeResult<usize> {
+        let archive = Archive {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            entries: self.entries()?,
+        };
+        let json = serde_json::to_vec(&archive)?;
+        let compressed = zstd::encode_all(json.as_slice(), 0)?;
+        fs::write(path, compressed)?;
+        Ok(archive.entries.len())
+    }
+
+    /// Add the entries of an archive, keeping local entries that are newer.
+    /// Entries are marked [`CacheEntry::imported`]: an archive's IDL
+    /// authorities are whatever its author wrote, so while `idl_authorities`
+    /// is set they're only trusted once the IDL account confirms them.
+    pub fn import(&self, path: &Path) -> PeriscopeResult<ImportSummary> {
+        let mut json = Vec::new();
+        zstd::Decoder::new(fs::File::open(path)?)?
+            .take(MAX_ARCHIVE_SIZE + 1)
+            .read_to_end(&mut json)?;
+        if json.len() as u64 > MAX_ARCHIVE_SIZE {
+            return Err(PeriscopeError::CacheError(format!(
+                "{} is larger than {} MiB decompressed",
+                path.display(),
+                MAX_ARCHIVE_SIZE / (1024 * 1024)
+            )));
+        }
+
+        let archive: Archive = serde_json::from_slice(&json)?;
+        if archive.format != ARCHIVE_FORMAT || archive.version > ARCHIVE_VERSION {
+            return Err(PeriscopeError::CacheError(format!(
+                "{} is not a supported cache archive (format '{}', version {})",
+                path.display(),
+                archive.format,
+                archive.version
+            )));
+        }
+
+        let mut imported = 0;
+        for entry in &archive.entries {
+            let newer_local = self
+                .get(&entry.cluster, &entry.program_id)
+                .is_some_and(|local| !entry.is_newer_than(&local));
+            if !newer_local {
+                self.set(&CacheEntry {
+                    imported: true,
+                    ..entry.clone()
+                })?;
+                imported += 1;
+            }
+        }
+        Ok(ImportSummary {
+            total: archive.entries.len(),
+            imported,
+        })
+    }
+
+    /// Cluster identified earlier for an RPC host, see [`IdlCache::set_cluster`]
+    pub fn cluster_of(&self, host: &str) -> Option<String> {
+        self.clusters().remove(host)
+    }
+
+    /// Remember which cluster an RPC host serves, so it's identified only once
+    pub fn set_cluster(&self, host: &str, cluster: &str) -> PeriscopeResult<()> {
+        let mut clusters = self.clusters();
+        clusters.insert(host.to_string(), cluster.to_string());
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(CLUSTERS_FILE),
+            serde_json::to_string_pretty(&clusters)?,
+        )?;
+        Ok(())
+    }
+
+    fn clusters(&self) -> BTreeMap<String, String> {
+        fs::read_to_string(self.dir.join(CLUSTERS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// cache_dir/<cluster>/<program_id>.json, with the cluster made file-name safe
+    fn entry_path(&self, cluster: &str, program_id: &str) -> PathBuf {
+        let cluster: String = cluster
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(cluster).join(format!(
+            "{}.json",
+            program_id.replace(['/', '\\', '.'], "_")
+        ))
+    }
+}
+
+/// Read a cache entry through a memory map
+fn read_entry(path: &Path) -> PeriscopeResult<CacheEntry> {
+    let file = fs::File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Err(PeriscopeError::CacheError(format!(
+            "{} is empty",
+            path.display()
+        )));
+    }
+    // SAFETY: entries are only ever replaced by rename (see `IdlCache::set`),
+    // so the mapped file is not truncated or rewritten while it is read
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(serde_json::from_slice(&map)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn entry(cluster: &str, slot: u64) -> CacheEntry {
+        CacheEntry {
+            program_id: "Vote111111111111111111111111111111111111111".to_string(),
+            cluster: cluster.to_string(),
+            slot: Some(slot),
+            authority: None,
+            compressed_len: None,
+            fetched_at: 1_700_000_000,
+            imported: false,
+            idl_json: format!(r#"{{"name":"vault","slot":{}}}"#, slot),
+        }
+    }
+
+    #[test]
+    fn test_export_import_keeps_newer() {
+        let root = std::env::temp_dir().join(format!("periscope-cache-{}", std::process::id()));
+        let ci = IdlCache::at(root.join("ci"));
+        let laptop = IdlCache::at(root.join("laptop"));
+
+        ci.set(&entry("mainnet-beta", 100)).unwrap();
+        ci.set(&CacheEntry {
+            authority: Some("11111111111111111111111111111111".to_string()),
+            ..entry("localhost:8899", 5)
+        })
+        .unwrap();
+        // Remembered clusters sit beside the entries without being one
+        ci.set_cluster("rpc.example.com", "devnet").unwrap();
+        assert_eq!(ci.cluster_of("rpc.example.com").as_deref(), Some("devnet"));
+        assert_eq!(ci.cluster_of("other.example.com"), None);
+        laptop.set(&entry("mainnet-beta", 200)).unwrap();
+
+        let archive = root.join("cache.json.zst");
+        assert_eq!(ci.export(&archive).unwrap(), 2);
+        let summary = laptop.import(&archive).unwrap();
+
+        let mainnet = laptop
+            .get(
+                "mainnet-beta",
+                "Vote111111111111111111111111111111111111111",
+            )
+            .unwrap();
+        let local = laptop
+            .get(
+                "localhost:8899",
+                "Vote111111111111111111111111111111111111111",
+            )
+            .unwrap();
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                total: 2,
+                imported: 1
+            }
+        );
+        assert_eq!(mainnet.slot, Some(200));
+        // Kept, but marked as the archive's word
+        assert_eq!(
+            local,
+            CacheEntry {
+                authority: Some("11111111111111111111111111111111".to_string()),
+                imported: true,
+                ..entry("localhost:8899", 5)
+            }
+        );
+    }
+
+    #[test]
+    fn test_imported_entry_loads_without_network() {
+        let root = std::env::temp_dir().join(format!("periscope-offline-{}", std::process::id()));
+        let ci = IdlCache::at(root.join("ci"));
+        let runner = IdlCache::at(root.join("runner"));
+        let cached = CacheEntry {
+            compressed_len: Some(1234),
+            ..entry("devnet", 100)
+        };
+        ci.set(&cached).unwrap();
+        let archive = root.join("cache.json.zst");
+        ci.export(&archive).unwrap();
+        runner.import(&archive).unwrap();
+
+        // Nothing listens on port 1: the account can't be checked
+        let client = RpcClient::new("http://127.0.0.1:1".to_string());
+        let program_id = Pubkey::from_str(&cached.program_id).unwrap();
+        let checked = runner.get_checked(&client, "devnet", &program_id);
+        let missing = runner.get_checked(&client, "mainnet-beta", &program_id);
+        let _ = fs::remove_dir_all(&root);
+
+        let checked = checked.unwrap();
+        assert!(!checked.confirmed);
+        assert!(checked.entry.imported);
+        assert_eq!(checked.entry.idl_json, cached.idl_json);
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn test_entry_is_current_while_the_account_matches() {
+        let authority = Pubkey::new_unique();
+        let probe = |compressed_len: usize, authority: Pubkey| IdlProbe {
+            idl_address: Pubkey::new_unique(),
+            exists: true,
+            authority: Some(authority),
+            compressed_len: Some(compressed_len),
+            account_size: None,
+            slot: 300,
+        };
+        let cached = CacheEntry {
+            authority: Some(authority.to_string()),
+            compressed_len: Some(1234),
+            ..entry("devnet", 100)
+        };
+        let checksum = Some(idl_checksum(cached.idl_json.as_bytes()));
+
+        assert!(cached.is_current(&probe(1234, authority), checksum));
+        // An upgraded IDL, even of the same length, or a new authority
+        assert!(!cached.is_current(&probe(1300, authority), checksum));
+        assert!(!cached.is_current(&probe(1234, authority), Some(7)));
+        assert!(!cached.is_current(&probe(1234, Pubkey::new_unique()), checksum));
+        // Cached before lengths were recorded
+        assert!(!entry("devnet", 100).is_current(&probe(1234, authority), checksum));
+    }
+
+    #[test]
+    fn test_handles_parse_lazily() {
+        let root = std::env::temp_dir().join(format!("periscope-handles-{}", std::process::id()));
+        let cache = IdlCache::at(root.clone());
+        let mut vault = entry("devnet", 1);
+        vault.idl_json = r#"{"address":"Vote111111111111111111111111111111111111111","metadata":{"name":"vault","version":"0.1.0","spec":"0.1.0"},"instructions":[]}"#.to_string();
+        cache.set(&vault).unwrap();
+        cache.set(&vault).unwrap();
+
+        let handles = cache.handles().unwrap();
+        let names: Vec<_> = fs::read_dir(root.join("devnet"))
+            .unwrap()
+            .map(|f| f.unwrap().file_name())
+            .collect();
+        assert_eq!(handles.len(), 1);
+        assert!(!handles[0].is_loaded());
+        assert_eq!(handles[0].program_id, vault.program_id);
+        assert_eq!(handles[0].idl().unwrap().metadata.name, "vault");
+        assert!(handles[0].is_loaded());
+        assert_eq!(handles[0].entry().unwrap(), vault);
+        let _ = fs::remove_dir_all(&root);
+
+        // Replaced, not rewritten: no partial files are left behind
+        assert_eq!(names.len(), 1);
     }
 }
@@ -1,6 +1,15 @@
 //! CLI command definitions using clap
 
-use clap::{Parser, Subcommand};
+use crate::bundle::parse_bundle_ref;
+use crate::config::Config;
+use crate::decode::Layout;
+use crate::error::PeriscopeResult;
+use crate::i18n::Lang;
+use crate::idl::NameCase;
+use crate::page::{Page, DEFAULT_PER_PAGE};
+use crate::schema::SCHEMA_COMMANDS;
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 /// Periscope - Explore and query Anchor program IDLs on-chain
 #[derive(Debug, Parser)]
@@ -16,15 +25,133 @@ pub struct Cli {
     pub refresh: bool,
 
     /// Load IDL from file path or URL instead of fetching from chain
-    /// Accepts: local file path (./idl.json) or URL (https://...)
+    /// Accepts: local file path (./idl.json), URL (https://...) or bundle
+    /// entry (bundle:protocol.psc#<PROGRAM_ID>)
     /// GitHub URLs are auto-converted to raw URLs
     #[arg(short, long, global = true)]
     pub idl: Option<String>,
 
+    /// Read the IDL from a buffer account written by `anchor idl write-buffer`,
+    /// to review it before `anchor idl set-buffer` publishes it
+    #[arg(long, global = true, value_name = "PUBKEY", conflicts_with = "idl")]
+    pub idl_buffer: Option<String>,
+
+    /// Output format: text for humans, json for scripts (errors included)
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Output language: en, es (overrides config and $LANG)
+    #[arg(long, global = true)]
+    pub lang: Option<Lang>,
+
+    /// Make addresses and signatures clickable links to a block explorer
+    /// (OSC 8; base URL from the `explorer_url` config key)
+    #[arg(long, global = true)]
+    pub links: bool,
+
+    /// Spell instruction, argument, account and field names in one case:
+    /// snake, camel, preserve
+    #[arg(long, global = true, default_value_t = NameCase::Preserve)]
+    pub case: NameCase,
+
+    /// Print how long each stage (RPC fetch, decompression, parse, ...) took, on stderr
+    #[arg(long, global = true)]
+    pub timing: bool,
+
+    /// Extra HTTP header for RPC requests, as "Name: value" (repeatable;
+    /// adds to the `rpc_headers` config table)
+    #[arg(long = "rpc-header", global = true, value_name = "HEADER")]
+    pub rpc_headers: Vec<String>,
+
+    /// Refuse to sign or send transactions (also the `read_only` config key)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Layered configuration (user config, then the project's
+    /// `.periscope.toml`), loaded once at startup
+    #[arg(skip)]
+    pub config: Config,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// Output format of command results and errors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored, human-readable text
+    #[default]
+    Text,
+    /// JSON on stdout; errors as JSON on stderr
+    Json,
+}
+
+/// Encodings of a built instruction (`build-ix --format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BuildFormat {
+    /// Base58 unsigned transaction, as Squads imports into a proposal
+    Squads,
+    /// Base64 legacy transaction message
+    Message,
+}
+
+/// Pagination of list commands
+#[derive(Debug, Clone, Copy, Default, Args)]
+pub struct PageArgs {
+    /// Show only page N of the list (from 1)
+    #[arg(long, value_name = "N")]
+    pub page: Option<usize>,
+
+    /// Items per page (default: the `per_page` config key, else 50)
+    #[arg(long, value_name = "M")]
+    pub per_page: Option<usize>,
+
+    /// Show the whole list, even with `per_page` configured
+    #[arg(long, conflicts_with_all = ["page", "per_page"])]
+    pub all: bool,
+}
+
+impl PageArgs {
+    /// Page to show of a list of `total` items, `None` for all of it
+    /// (`configured`: the `per_page` config key)
+    pub fn page(&self, configured: Option<usize>, total: usize) -> PeriscopeResult<Option<Page>> {
+        if self.all || (self.page.is_none() && self.per_page.is_none() && configured.is_none()) {
+            return Ok(None);
+        }
+        let per_page = self.per_page.or(configured).unwrap_or(DEFAULT_PER_PAGE);
+        Page::new(self.page.unwrap_or(1), per_page, total).map(Some)
+    }
+}
+
+/// Groupings of the instruction list (`instructions --group-by`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// First word of the name (`admin` in `admin_set_fee`)
+    Prefix,
+}
+
+/// How `scan --export` writes vectors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum VecStrategyArg {
+    /// One cell, elements separated by `;`
+    #[default]
+    Join,
+    /// One row per element, other columns repeated
+    Explode,
+    /// One cell holding the vector as JSON
+    Json,
+}
+
+/// Test frameworks of `codegen tests`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TestFrameworkArg {
+    /// TypeScript with Anchor's client and mocha
+    #[default]
+    Mocha,
+    /// Rust with LiteSVM
+    Litesvm,
+}
+
 /// Represents where the IDL should be loaded from
 #[derive(Debug, Clone)]
 pub enum IdlSource {
@@ -34,15 +161,28 @@ pub enum IdlSource {
     File(String),
     /// Fetch from URL
     Url(String),
+    /// Load from a bundle file, optionally naming the program
+    Bundle {
+        path: String,
+        program_id: Option<String>,
+    },
+    /// Fetch from an IDL buffer account (`anchor idl write-buffer`)
+    Buffer(String),
 }
 
-/// Convert GitHub blob URLs to raw.githubusercontent.com URLs
-fn normalize_github_url(url: &str) -> String {
-    if url.contains("github.com") && url.contains("/blob/") {
-        url.replace("github.com", "raw.githubusercontent.com")
-            .replace("/blob/", "/")
-    } else {
-        url.to_string()
+impl IdlSource {
+    /// File, URL or bundle source for an `--idl` value
+    pub fn from_path(path: &str) -> Self {
+        if let Some((bundle, program_id)) = parse_bundle_ref(path) {
+            IdlSource::Bundle {
+                path: bundle.to_string(),
+                program_id: program_id.map(str::to_string),
+            }
+        } else if path.starts_with("http://") || path.starts_with("https://") {
+            IdlSource::Url(path.to_string())
+        } else {
+            IdlSource::File(path.to_string())
+        }
     }
 }
 
@@ -58,6 +198,13 @@ pub enum Commands {
     Instructions {
         /// Program ID (base58) - required for on-chain, optional with --idl
         program_id: Option<String>,
+
+        /// Cluster the list, e.g. `admin_*`, `lp_*` with `prefix`
+        #[arg(long, value_enum)]
+        group_by: Option<GroupBy>,
+
+        #[command(flatten)]
+        pages: PageArgs,
     },
 
     /// Show details for a specific instruction
@@ -67,12 +214,445 @@ pub enum Commands {
 
         /// Program ID (base58) - required for on-chain, optional with --idl
         program_id: Option<String>,
+
+        /// Print constant seeds as the byte arrays the IDL stores, instead of
+        /// as text, a pubkey or hex
+        #[arg(long)]
+        raw_seeds: bool,
+    },
+
+    /// Print a fill-in template of an instruction's accounts and args
+    /// (commented TOML, or JSON with --output json)
+    Template {
+        /// Instruction name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+    },
+
+    /// Build an instruction's data and accounts, from prompts or a filled-in template
+    /// (PDA accounts are derived from their seeds where possible)
+    BuildIx {
+        /// Instruction name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Prompt for each argument and account
+        #[arg(long, conflicts_with = "from", required_unless_present = "from")]
+        interactive: bool,
+
+        /// Filled-in `periscope template` to build from (JSON, or TOML if named *.toml)
+        #[arg(long, value_name = "FILE")]
+        from: Option<String>,
+
+        /// Also encode the instruction for pasting elsewhere (squads, message)
+        #[arg(long, value_enum)]
+        format: Option<BuildFormat>,
+
+        /// Fee payer of the encoded message, e.g. the multisig vault (default: first signer)
+        #[arg(long, value_name = "PUBKEY", requires = "format")]
+        payer: Option<String>,
+
+        /// Recent blockhash of the encoded message (default: zeroed, or the nonce value)
+        #[arg(long, value_name = "HASH", requires = "format")]
+        blockhash: Option<String>,
+
+        /// Durable nonce account to advance first, so offline signers have no deadline
+        #[arg(long, value_name = "PUBKEY", requires = "format")]
+        nonce_account: Option<String>,
+
+        /// Authority of the nonce account (default: read from the account)
+        #[arg(long, value_name = "PUBKEY", requires = "nonce_account")]
+        nonce_authority: Option<String>,
+    },
+
+    /// Build an instruction, sign it with a keypair (also the fee payer) and send it
+    /// (asks for confirmation on mainnet unless --yes)
+    #[cfg(feature = "send")]
+    SendIx {
+        /// Instruction name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Prompt for each argument and account
+        #[arg(long, conflicts_with = "from", required_unless_present = "from")]
+        interactive: bool,
+
+        /// Filled-in `periscope template` to build from (JSON, or TOML if named *.toml)
+        #[arg(long, value_name = "FILE")]
+        from: Option<String>,
+
+        /// Keypair file that signs and pays (default: ~/.config/solana/id.json)
+        #[arg(short, long, value_name = "PATH")]
+        keypair: Option<String>,
+
+        /// Send to mainnet without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Simulate an instruction and break down its compute units per program invocation
+    Simulate {
+        /// Instruction name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Prompt for each argument and account
+        #[arg(long, conflicts_with = "from", required_unless_present = "from")]
+        interactive: bool,
+
+        /// Filled-in `periscope template` to build from (JSON, or TOML if named *.toml)
+        #[arg(long, value_name = "FILE")]
+        from: Option<String>,
+
+        /// Fee payer (default: first signer); signatures are not checked
+        #[arg(long, value_name = "PUBKEY")]
+        payer: Option<String>,
+
+        /// Repeat for each value of an argument, e.g. `amount=1,10,100`
+        #[arg(long, value_name = "ARG=V1,V2,...", requires = "from")]
+        sweep: Option<String>,
     },
 
     /// List all error codes defined by the program
     Errors {
         /// Program ID (base58) - required for on-chain, optional with --idl
         program_id: Option<String>,
+
+        #[command(flatten)]
+        pages: PageArgs,
+    },
+
+    /// Decode an account's data using its owner program's IDL
+    /// (SPL Token, Token-2022, Stake and Vote accounts are decoded without one)
+    DecodeAccount {
+        /// Account address (base58)
+        address: String,
+
+        /// Program ID (base58) - defaults to the account owner
+        program_id: Option<String>,
+
+        /// Decode as this account type instead of matching the discriminator
+        #[arg(long = "type")]
+        account_type: Option<String>,
+
+        /// Memory layout: borsh or zero-copy (default: inferred from the IDL)
+        #[arg(long)]
+        layout: Option<Layout>,
+
+        /// Also print a hex dump with bytes colored and labelled by field
+        #[arg(long)]
+        annotate: bool,
+    },
+
+    /// Explain wallet-adapter payloads read from stdin: the JSON array of
+    /// base64 messages or transactions passed to signTransaction
+    Explain,
+
+    /// Show which decoded fields of an account changed between two states
+    /// (fixtures from `periscope snapshot`, or a fixture and the live account)
+    AccountDiff {
+        /// Earlier state: fixture file
+        before: String,
+
+        /// Later state: fixture file (default: the account as it is now)
+        after: Option<String>,
+
+        /// Decode as this account type instead of matching the discriminator
+        #[arg(long = "type")]
+        account_type: Option<String>,
+
+        /// Memory layout: borsh or zero-copy (default: inferred from the IDL)
+        #[arg(long)]
+        layout: Option<Layout>,
+    },
+
+    /// Infer the Anchor version a program was built with and the IDL features
+    /// it uses (reads the program binary when the program ID is given)
+    AnchorVersion {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+    },
+
+    /// Catalogue of the PDAs a program defines: identical seed recipes grouped,
+    /// with the accounts using them and the address when all seeds are constants
+    Pdas {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Print constant seeds as the byte arrays the IDL stores, instead of
+        /// as text, a pubkey or hex
+        #[arg(long)]
+        raw_seeds: bool,
+
+        #[command(flatten)]
+        pages: PageArgs,
+    },
+
+    /// Check the accounts an instruction would be sent with against the IDL:
+    /// existence, owner, account type, signer/writable feasibility and PDA
+    /// addresses (exit 1 if any check fails)
+    Preflight {
+        /// Instruction name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Address of an account, as NAME=PUBKEY (repeatable; fixed and
+        /// derivable PDA accounts may be left out)
+        #[arg(long = "account", value_name = "NAME=PUBKEY")]
+        accounts: Vec<String>,
+    },
+
+    /// Table of accounts by instructions, marking each use as read-only (R),
+    /// writable (W) or signer (S)
+    Matrix {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Only accounts whose name contains this text
+        #[arg(long)]
+        account: Option<String>,
+    },
+
+    /// Every instruction argument and account, event or type field that refers
+    /// to a type (impact of changing it)
+    Uses {
+        /// Type name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        #[command(flatten)]
+        pages: PageArgs,
+    },
+
+    /// Check the IDL for cruft such as types nothing refers to (exit 1 if
+    /// anything is found)
+    Lint {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+    },
+
+    /// Error codes, error names and discriminators shared by several programs
+    /// (ambiguous in log-based monitoring)
+    Overlap {
+        /// Program IDs, IDL files or URLs (at least two programs in all)
+        sources: Vec<String>,
+
+        /// Also every IDL in the local cache, of every cluster
+        #[arg(long)]
+        cache: bool,
+
+        /// Also every program of this bundle file (repeatable)
+        #[arg(long = "bundle", value_name = "FILE")]
+        bundles: Vec<String>,
+
+        /// Only instruction, account and event discriminators, not error
+        /// codes and names (which most Anchor programs share)
+        #[arg(long)]
+        discriminators: bool,
+    },
+
+    /// Search every cached IDL, of every cluster, for a name (case-insensitive
+    /// substring, or a regex with --regex)
+    Grep {
+        /// Text or regex to match against instruction, account, argument,
+        /// type, field, variant, event and error names
+        pattern: String,
+
+        /// Treat the pattern as a (case-insensitive) regular expression
+        #[arg(long)]
+        regex: bool,
+
+        /// Only matches of this kind
+        #[arg(long, value_parser = ["instruction", "account", "arg", "type", "field", "variant", "event", "error"])]
+        kind: Option<String>,
+    },
+
+    /// Pin the IDLs of several programs in one versioned bundle file, read
+    /// back with `--idl bundle:FILE#PROGRAM_ID`
+    Bundle {
+        #[command(subcommand)]
+        action: BundleCommands,
+    },
+
+    /// Sign an IDL with an ed25519 keypair, writing a detached signature file
+    Sign {
+        /// IDL to sign: file, URL or program ID (on-chain)
+        idl: String,
+
+        /// Keypair file to sign with (default: ~/.config/solana/id.json)
+        #[arg(short, long, value_name = "PATH")]
+        keypair: Option<String>,
+
+        /// Signature file to write (default: <IDL file>.sig.json)
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+    },
+
+    /// Check a detached IDL signature before trusting the IDL (exit 5 if invalid)
+    Verify {
+        /// Signed IDL: file, URL or program ID (on-chain)
+        idl: String,
+
+        /// Signature file from `periscope sign`
+        signature: String,
+
+        /// Public key the IDL must be signed by
+        #[arg(long, value_name = "PUBKEY")]
+        signer: String,
+    },
+
+    /// Compare a program's IDL across clusters and list what differs
+    Xcheck {
+        /// Program ID (base58)
+        program_id: String,
+
+        /// Clusters to compare (names or RPC URLs, comma-separated)
+        #[arg(long, value_delimiter = ',', default_value = "mainnet-beta,devnet")]
+        clusters: Vec<String>,
+    },
+
+    /// Write the program, its IDL and all accounts of the given types as
+    /// `solana-test-validator --account-dir` fixtures
+    Snapshot {
+        /// Program ID (base58) - defaults to the IDL's address with --idl
+        program_id: Option<String>,
+
+        /// Account types to capture (repeatable or comma-separated)
+        #[arg(long, value_name = "TYPE", value_delimiter = ',')]
+        accounts_of: Vec<String>,
+
+        /// Directory to write the fixtures to
+        #[arg(long, value_name = "DIR", default_value = "fixtures")]
+        out: String,
+    },
+
+    /// Count and summarize every account of a type: lamports, size
+    /// histogram, and min/max/mean of numeric fields
+    Scan {
+        /// Program ID (base58) - defaults to the IDL's address with --idl
+        program_id: Option<String>,
+
+        /// Account type to scan
+        #[arg(long = "type")]
+        account_type: String,
+
+        /// Numeric fields to summarize, `a.b` for nested ones (repeatable or
+        /// comma-separated)
+        #[arg(long, value_name = "FIELD", value_delimiter = ',')]
+        stats: Vec<String>,
+
+        /// Also write the decoded accounts as CSV (`-` for stdout), nested
+        /// fields as dotted columns
+        #[arg(long, value_name = "FILE")]
+        export: Option<String>,
+
+        /// How exported vectors become cells
+        #[arg(long = "vec", value_enum, default_value_t)]
+        vec_strategy: VecStrategyArg,
+    },
+
+    /// Generate a random value of a defined type, as JSON and Borsh bytes
+    Mock {
+        /// Type name (any type of the IDL's `types`)
+        type_name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Seed of the generator, to reproduce a value (default: random)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// Identify what an address is (program, PDA, token account, wallet, ...)
+    Whois {
+        /// Address (base58)
+        address: String,
+    },
+
+    /// Show how IDL names map to Rust identifiers (keywords, digits, collisions)
+    RustNames {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+    },
+
+    /// Interactive shell with the IDL loaded once (ix, type, search, decode, ...)
+    Repl {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+    },
+
+    /// List recently inspected programs (commands default to the most recent)
+    Recent {
+        /// Forget all recent programs
+        #[arg(long)]
+        clear: bool,
+    },
+
+    /// Check config, RPC reachability, cache directory and clock
+    Doctor,
+
+    /// Check for a newer release and install it (never runs automatically)
+    SelfUpdate {
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+
+        /// Install without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Print the JSON Schema of a command's `--output json` result
+    Schema {
+        /// Command whose output to describe (`error` for the error report)
+        #[arg(value_parser = PossibleValuesParser::new(SCHEMA_COMMANDS))]
+        command: String,
+    },
+
+    /// Map calls of an old IDL's instructions to a new version: renamed
+    /// arguments, added accounts and their defaults
+    Compat {
+        /// Old IDL file
+        old: String,
+
+        /// New IDL file
+        new: String,
+
+        /// Print Rust adapter functions from old to new calls instead
+        #[arg(long)]
+        rust: bool,
+    },
+
+    /// Generate code from the IDL
+    Codegen {
+        #[command(subcommand)]
+        target: CodegenCommands,
+    },
+
+    /// Share cached IDLs between machines as a portable archive
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
+    },
+
+    /// Account data that failed to decode (kept by decode-account), and
+    /// decoding it again once the IDL has caught up
+    Quarantine {
+        #[command(subcommand)]
+        action: QuarantineCommands,
     },
 
     /// Manage Periscope configuration
@@ -82,36 +662,154 @@ pub enum Commands {
     },
 }
 
+#[derive(Debug, Clone, Subcommand)]
+pub enum CodegenCommands {
+    /// Test skeleton with one test per instruction, accounts and args stubbed
+    Tests {
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Test framework to target
+        #[arg(long, value_enum, default_value_t)]
+        framework: TestFrameworkArg,
+
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+    },
+
+    /// Solana Action (Blink) for an instruction: the descriptor wallets
+    /// fetch, args as action parameters, and a TypeScript route handler
+    /// building the transaction
+    Action {
+        /// Instruction name
+        name: String,
+
+        /// Program ID (base58) - required for on-chain, optional with --idl
+        program_id: Option<String>,
+
+        /// Print only the descriptor JSON, not the handler
+        #[arg(long)]
+        descriptor: bool,
+
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum BundleCommands {
+    /// Write a bundle of the given programs' IDLs
+    Create {
+        /// Bundle file to write (e.g. protocol.psc)
+        path: String,
+
+        /// Program IDs (on-chain), IDL files or URLs
+        #[arg(required = true)]
+        sources: Vec<String>,
+    },
+
+    /// List the programs of a bundle
+    List {
+        /// Bundle file
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum QuarantineCommands {
+    /// List quarantined payloads with the error that put them there
+    List,
+
+    /// Decode quarantined payloads again with their program's current IDL
+    /// (`--refresh` to refetch it, `--idl` to try another); those that
+    /// decode are removed
+    Retry {
+        /// Only the payload with this ID
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Only payloads of this program
+        #[arg(long)]
+        program: Option<String>,
+    },
+
+    /// Remove every quarantined payload
+    Clear,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum CacheCommands {
+    /// Write every cached IDL, with its cluster and fetch slot, to an archive
+    Export {
+        /// Archive file to write (zstd-compressed JSON, e.g. cache.json.zst)
+        path: String,
+    },
+
+    /// Add the IDLs of an archive to the cache (newer local entries are kept)
+    Import {
+        /// Archive file from `cache export`
+        path: String,
+    },
+}
+
 #[derive(Debug, Clone, Subcommand)]
 pub enum ConfigCommands {
     /// Show current configuration
     Show,
 
-    /// Set configuration value
+    /// Set a configuration value: `config set <key> <value>`
     Set {
-        /// RPC URL to use
+        /// Key (rpc_url, lang, max_idl_size_mib, per_page, explorer_url, theme.name,
+        /// theme.<element>)
+        #[arg(requires = "value")]
+        key: Option<String>,
+
+        /// New value
+        value: Option<String>,
+
+        /// RPC URL to use (same as `config set rpc_url <URL>`)
         #[arg(long)]
         url: Option<String>,
+
+        /// Output language (en, es)
+        #[arg(long)]
+        lang: Option<Lang>,
     },
+
+    /// Reset a configuration value to its default
+    Unset {
+        /// Key to reset
+        key: String,
+    },
+
+    /// Open the config file in $VISUAL / $EDITOR and validate it afterwards
+    Edit,
 }
 
 impl Cli {
+    /// Whether JSON output was requested
+    pub fn json(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
+
     /// Parse CLI arguments
     pub fn parse_args() -> Self {
         Cli::parse()
     }
 
+    /// Parse CLI arguments, returning clap's error instead of exiting
+    pub fn try_parse_args() -> Result<Self, clap::Error> {
+        Cli::try_parse()
+    }
+
     /// Determine the IDL source based on --idl flag
     pub fn idl_source(&self) -> IdlSource {
-        match &self.idl {
-            None => IdlSource::OnChain,
-            Some(path) => {
-                if path.starts_with("http://") || path.starts_with("https://") {
-                    IdlSource::Url(normalize_github_url(path))
-                } else {
-                    IdlSource::File(path.clone())
-                }
-            }
+        match (&self.idl, &self.idl_buffer) {
+            (Some(path), _) => IdlSource::from_path(path),
+            (None, Some(buffer)) => IdlSource::Buffer(buffer.clone()),
+            (None, None) => IdlSource::OnChain,
         }
     }
 }
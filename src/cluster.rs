@@ -0,0 +1,62 @@
+//! Cluster identification by genesis hash
+//!
+//! The cluster name in an RPC URL (`api.devnet.solana.com`) is a guess;
+//! provider URLs (`rpc.helius.xyz/?api-key=...`) don't carry one at all. The
+//! genesis hash is what actually tells the public clusters apart, so a
+//! program with "no IDL" can be reported as missing *on devnet* rather than
+//! leaving the user to wonder which cluster they were talking to.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::metrics::{timed, Stage};
+use solana_client::rpc_client::RpcClient;
+
+/// Genesis hash of mainnet-beta
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// Genesis hash of devnet
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// Genesis hash of testnet
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+/// Names of the clusters identified by genesis hash, plus `localnet`
+pub const KNOWN_CLUSTERS: &[&str] = &["mainnet-beta", "devnet", "testnet", "localnet"];
+
+/// Public cluster with this genesis hash (base58)
+pub fn cluster_for_genesis_hash(hash: &str) -> Option<&'static str> {
+    match hash {
+        MAINNET_GENESIS_HASH => Some("mainnet-beta"),
+        DEVNET_GENESIS_HASH => Some("devnet"),
+        TESTNET_GENESIS_HASH => Some("testnet"),
+        _ => None,
+    }
+}
+
+/// Public cluster served by `client`, or `None` for any other cluster
+pub fn identify_cluster(client: &RpcClient) -> PeriscopeResult<Option<&'static str>> {
+    let hash = timed(Stage::Rpc("getGenesisHash"), || {
+        client.get_genesis_hash().map_err(PeriscopeError::from)
+    })?;
+    Ok(cluster_for_genesis_hash(&hash.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_for_genesis_hash() {
+        assert_eq!(
+            cluster_for_genesis_hash(DEVNET_GENESIS_HASH),
+            Some("devnet")
+        );
+        assert_eq!(
+            cluster_for_genesis_hash(MAINNET_GENESIS_HASH),
+            Some("mainnet-beta")
+        );
+        assert_eq!(
+            cluster_for_genesis_hash("11111111111111111111111111111111"),
+            None
+        );
+    }
+}
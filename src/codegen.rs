@@ -0,0 +1,816 @@
+//! Test skeleton and Solana Action generation
+//!
+//! `periscope codegen tests` writes one test per instruction with the
+//! accounts and arguments stubbed from the IDL: an Anchor mocha suite in
+//! TypeScript, or a Rust suite running the program in LiteSVM. Stubs use
+//! placeholder values of the right shape (zero, empty, first enum variant);
+//! every placeholder that needs a real value is marked `TODO`. Annotated
+//! instructions get their notes as a comment above their test.
+//!
+//! `periscope codegen action` scaffolds a Solana Action (Blink) for one
+//! instruction: the descriptor wallets fetch with GET, its arguments as
+//! action parameters, and a route handler answering POST with the
+//! transaction, built with Anchor's TypeScript client.
+
+use crate::annotations::{AnnotationKind, Annotations};
+use crate::idl::{
+    capitalize, format_type, rust_ident, split_words, Case, Idl, IdlAccount, IdlAccountItem,
+    IdlEnumFields, IdlField, IdlInstruction, IdlType, IdlTypeComplex, IdlTypeDefTy, RustNames,
+};
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// Nesting depth past which defined types are stubbed with a TODO
+const MAX_DEPTH: usize = 8;
+
+/// Test framework to generate for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestFramework {
+    /// Anchor's TypeScript client with mocha
+    Mocha,
+    /// Rust tests running the program in LiteSVM
+    LiteSvm,
+}
+
+/// Generate a test skeleton for every instruction of `idl`
+pub fn generate_tests(idl: &Idl, framework: TestFramework, annotations: &Annotations) -> String {
+    match framework {
+        TestFramework::Mocha => mocha_tests(idl, annotations),
+        TestFramework::LiteSvm => litesvm_tests(idl, annotations),
+    }
+}
+
+/// Comment lines with the annotation of instruction `name`, if any
+fn annotation_comment(out: &mut String, annotations: &Annotations, name: &str, indent: &str) {
+    let Some(annotation) = annotations.get(AnnotationKind::Instruction, name) else {
+        return;
+    };
+    if annotation.deprecated {
+        let _ = writeln!(out, "{}// DEPRECATED", indent);
+    }
+    if let Some(summary) = annotation.summary() {
+        let _ = writeln!(out, "{}// {}", indent, summary);
+    }
+}
+
+fn mocha_tests(idl: &Idl, annotations: &Annotations) -> String {
+    let mut out = String::new();
+    let name = &idl.metadata.name;
+    let _ = writeln!(
+        out,
+        "// Test skeleton for {} generated by `periscope codegen tests`",
+        name
+    );
+    out.push_str(
+        "import * as anchor from \"@coral-xyz/anchor\";\n\
+         import { Program } from \"@coral-xyz/anchor\";\n\
+         import { Keypair, PublicKey } from \"@solana/web3.js\";\n",
+    );
+    let _ = writeln!(out, "import idl from \"./{}.json\";\n", name);
+    let _ = writeln!(out, "describe(\"{}\", () => {{", name);
+    out.push_str(
+        "  const provider = anchor.AnchorProvider.env();\n\
+         \x20 anchor.setProvider(provider);\n\
+         \x20 const program = new Program(idl as anchor.Idl, provider);\n",
+    );
+
+    for ix in &idl.instructions {
+        out.push('\n');
+        annotation_comment(&mut out, annotations, &ix.name, "  ");
+        let _ = writeln!(out, "  it(\"{}\", async () => {{", ix.name);
+
+        let mut accounts = Vec::new();
+        flatten_accounts(&ix.accounts, &mut accounts);
+        let mut extra_signers = Vec::new();
+        let mut resolved = Vec::new();
+        let mut given = Vec::new();
+        for account in &accounts {
+            let key = ts_ident(&account.name);
+            if account.address.is_some() || account.pda.is_some() {
+                resolved.push(key);
+            } else if account.signer {
+                let _ = writeln!(out, "    const {} = Keypair.generate(); // TODO: fund", key);
+                given.push(format!("{}: {}.publicKey", key, key));
+                extra_signers.push(key);
+            } else {
+                given.push(format!(
+                    "{}: PublicKey.default, // TODO{}",
+                    key,
+                    constraints(account)
+                ));
+            }
+        }
+
+        let args: Vec<String> = ix
+            .args
+            .iter()
+            .map(|arg| ts_value(idl, &arg.ty, 0))
+            .collect();
+        let _ = writeln!(out, "    const tx = await program.methods");
+        let _ = writeln!(out, "      .{}({})", ts_ident(&ix.name), args.join(", "));
+        if !resolved.is_empty() {
+            let _ = writeln!(out, "      // Resolved by Anchor: {}", resolved.join(", "));
+        }
+        let _ = writeln!(out, "      .accounts({{");
+        for entry in &given {
+            let _ = writeln!(out, "        {}", with_comma(entry));
+        }
+        let _ = writeln!(out, "      }})");
+        if !extra_signers.is_empty() {
+            let _ = writeln!(out, "      .signers([{}])", extra_signers.join(", "));
+        }
+        let _ = writeln!(out, "      .rpc();");
+        let _ = writeln!(out, "    console.log(\"signature\", tx);");
+        let _ = writeln!(out, "    // TODO: assert on the resulting state");
+        let _ = writeln!(out, "  }});");
+    }
+    out.push_str("});\n");
+    out
+}
+
+/// Place the comma before a trailing `// comment`
+fn with_comma(entry: &str) -> String {
+    match entry.split_once(", //") {
+        Some((code, comment)) => format!("{}, //{}", code, comment),
+        None => format!("{},", entry),
+    }
+}
+
+fn litesvm_tests(idl: &Idl, annotations: &Annotations) -> String {
+    let names = RustNames::build(idl);
+    let mut out = String::new();
+    let name = &idl.metadata.name;
+    let _ = writeln!(
+        out,
+        "//! Test skeleton for {} generated by `periscope codegen tests`\n",
+        name
+    );
+    out.push_str(
+        "use litesvm::LiteSVM;\n\
+         use solana_sdk::instruction::{AccountMeta, Instruction};\n\
+         use solana_sdk::pubkey::Pubkey;\n\
+         use solana_sdk::signature::{Keypair, Signer};\n\
+         use solana_sdk::transaction::Transaction;\n\
+         use std::str::FromStr;\n\n",
+    );
+    let _ = writeln!(out, "const PROGRAM_ID: &str = \"{}\";\n", idl.address);
+    let _ = writeln!(
+        out,
+        "fn setup() -> (LiteSVM, Pubkey, Keypair) {{\n\
+         \x20   let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();\n\
+         \x20   let mut svm = LiteSVM::new();\n\
+         \x20   // TODO: dump the program with `solana program dump {} {}.so`\n\
+         \x20   svm.add_program_from_file(program_id, \"{}.so\").unwrap();\n\
+         \x20   let payer = Keypair::new();\n\
+         \x20   svm.airdrop(&payer.pubkey(), 10_000_000_000).unwrap();\n\
+         \x20   (svm, program_id, payer)\n\
+         }}",
+        idl.address, name, name
+    );
+
+    for ix in &idl.instructions {
+        let ident = names
+            .get("instructions", &ix.name)
+            .unwrap_or(&ix.name)
+            .trim_start_matches("r#");
+        out.push('\n');
+        annotation_comment(&mut out, annotations, &ix.name, "");
+        let _ = writeln!(out, "#[test]\nfn test_{}() {{", ident);
+        let _ = writeln!(out, "    let (mut svm, program_id, payer) = setup();");
+
+        let mut accounts = Vec::new();
+        flatten_accounts(&ix.accounts, &mut accounts);
+        let scope = format!("accounts {}", ix.name);
+        let mut signers = vec!["&payer".to_string()];
+        let mut metas = Vec::new();
+        let mut payer_used = false;
+        for account in &accounts {
+            let var = names.get(&scope, &account.name).unwrap_or(&account.name);
+            let key = if let Some(address) = &account.address {
+                format!("Pubkey::from_str(\"{}\").unwrap()", address)
+            } else if account.signer && !payer_used {
+                payer_used = true;
+                "payer.pubkey()".to_string()
+            } else if account.signer {
+                let _ = writeln!(out, "    let {} = Keypair::new();", var);
+                signers.push(format!("&{}", var));
+                format!("{}.pubkey()", var)
+            } else if account.pda.is_some() {
+                "Pubkey::new_unique() /* TODO: Pubkey::find_program_address(&[...], &program_id) */"
+                    .to_string()
+            } else {
+                "Pubkey::new_unique() /* TODO */".to_string()
+            };
+            let meta = if account.writable {
+                "new"
+            } else {
+                "new_readonly"
+            };
+            metas.push(format!(
+                "AccountMeta::{}({}, {}), // {}{}",
+                meta,
+                key,
+                account.signer,
+                account.name,
+                constraints(account)
+            ));
+        }
+
+        let discriminator: Vec<String> = ix
+            .discriminator_bytes()
+            .iter()
+            .map(|b| b.to_string())
+            .collect();
+        let _ = writeln!(
+            out,
+            "\n    let {}data = vec![{}];",
+            if ix.args.is_empty() { "" } else { "mut " },
+            discriminator.join(", ")
+        );
+        for arg in &ix.args {
+            match borsh_stub(&arg.ty) {
+                Some(stub) => {
+                    let _ = writeln!(
+                        out,
+                        "    data.extend({}); // {}: {}",
+                        stub,
+                        arg.name,
+                        format_type(&arg.ty)
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "    // TODO: data.extend(borsh::to_vec(&{}).unwrap()); // {}",
+                        arg.name,
+                        format_type(&arg.ty)
+                    );
+                }
+            }
+        }
+
+        let _ = writeln!(out, "    let accounts = vec![");
+        for meta in &metas {
+            let _ = writeln!(out, "        {}", meta);
+        }
+        let _ = writeln!(out, "    ];");
+        let _ = writeln!(
+            out,
+            "    let ix = Instruction {{ program_id, accounts, data }};\n\
+             \x20   let tx = Transaction::new_signed_with_payer(\n\
+             \x20       &[ix],\n\
+             \x20       Some(&payer.pubkey()),\n\
+             \x20       &[{}],\n\
+             \x20       svm.latest_blockhash(),\n\
+             \x20   );\n\
+             \x20   let result = svm.send_transaction(tx);\n\
+             \x20   assert!(result.is_ok(), \"{{:?}}\", result.err());\n\
+             \x20   // TODO: assert on the resulting state\n\
+             }}",
+            signers.join(", ")
+        );
+    }
+    out
+}
+
+fn flatten_accounts<'a>(items: &'a [IdlAccountItem], out: &mut Vec<&'a IdlAccount>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => out.push(account),
+            IdlAccountItem::Group(group) => flatten_accounts(&group.accounts, out),
+        }
+    }
+}
+
+/// `, writable, signer` style note for a comment
+fn constraints(account: &IdlAccount) -> String {
+    let mut notes = String::new();
+    for (set, note) in [
+        (account.writable, "writable"),
+        (account.signer, "signer"),
+        (account.optional, "optional"),
+        (account.pda.is_some(), "PDA"),
+    ] {
+        if set {
+            notes.push_str(if notes.is_empty() { ": " } else { ", " });
+            notes.push_str(note);
+        }
+    }
+    notes
+}
+
+/// Solana Action scaffold of one instruction
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionScaffold {
+    /// Action descriptor (`ActionGetResponse`), as served to GET requests
+    pub descriptor: Value,
+
+    /// TypeScript route handler serving the descriptor and building the
+    /// transaction on POST
+    pub handler: String,
+}
+
+/// How an argument is asked for in an action form
+enum ParamKind {
+    /// Integer; `big` for 64 bits and up, which Anchor's client takes as `BN`
+    Integer {
+        unsigned: bool,
+        big: bool,
+    },
+    Float,
+    Bool,
+    Text,
+    Pubkey,
+    /// Enum without fields, one option per variant
+    Select(Vec<String>),
+    /// Anything else, typed in as JSON
+    Json,
+}
+
+/// Scaffold a Solana Action for instruction `ix` of `idl`; an annotation of
+/// the instruction gives the action's title and description
+pub fn generate_action(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    annotations: &Annotations,
+) -> ActionScaffold {
+    let annotation = annotations.get(AnnotationKind::Instruction, &ix.name);
+    let label = title_case(&ix.name);
+    let title = annotation
+        .and_then(|a| a.label.clone())
+        .unwrap_or_else(|| format!("{}: {}", title_case(&idl.metadata.name), label));
+    let description = annotation
+        .and_then(|a| a.note.clone())
+        .or_else(|| idl.metadata.description.clone())
+        .unwrap_or_else(|| "TODO: describe what this action does".to_string());
+
+    let query: Vec<String> = ix
+        .args
+        .iter()
+        .map(|arg| format!("{0}={{{0}}}", arg.name))
+        .collect();
+    let mut href = format!("/api/actions/{}", ix.name);
+    if !query.is_empty() {
+        href = format!("{}?{}", href, query.join("&"));
+    }
+    let parameters: Vec<Value> = ix
+        .args
+        .iter()
+        .map(|arg| action_parameter(idl, arg))
+        .collect();
+
+    let descriptor = json!({
+        "type": "action",
+        "icon": "https://TODO/icon.png",
+        "title": title,
+        "description": description,
+        "label": label,
+        "links": {
+            "actions": [{
+                "type": "transaction",
+                "label": label,
+                "href": href,
+                "parameters": parameters,
+            }]
+        }
+    });
+    let handler = action_handler(idl, ix, &descriptor);
+    ActionScaffold {
+        descriptor,
+        handler,
+    }
+}
+
+/// `Swap Exact In` for `swap_exact_in` or `swapExactIn`
+fn title_case(name: &str) -> String {
+    let words: Vec<String> = split_words(name).iter().map(|w| capitalize(w)).collect();
+    words.join(" ")
+}
+
+/// What a parameter of type `ty` is entered as; `Option`s are their inner type
+fn param_kind(idl: &Idl, ty: &IdlType) -> ParamKind {
+    match ty {
+        IdlType::Primitive(name) => match name.as_str() {
+            "bool" => ParamKind::Bool,
+            "u8" | "u16" | "u32" => ParamKind::Integer {
+                unsigned: true,
+                big: false,
+            },
+            "i8" | "i16" | "i32" => ParamKind::Integer {
+                unsigned: false,
+                big: false,
+            },
+            "u64" | "u128" | "u256" => ParamKind::Integer {
+                unsigned: true,
+                big: true,
+            },
+            "i64" | "i128" | "i256" => ParamKind::Integer {
+                unsigned: false,
+                big: true,
+            },
+            "f32" | "f64" => ParamKind::Float,
+            "string" => ParamKind::Text,
+            "pubkey" | "publicKey" => ParamKind::Pubkey,
+            _ => ParamKind::Json,
+        },
+        IdlType::Complex(IdlTypeComplex::Option(inner)) => param_kind(idl, inner),
+        IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+            let type_def = idl.instantiate_type(name, generics).ok();
+            match type_def.as_ref().map(|t| &t.ty) {
+                Some(IdlTypeDefTy::Enum { variants })
+                    if variants.iter().all(|v| v.fields.is_none()) =>
+                {
+                    ParamKind::Select(variants.iter().map(|v| v.name.clone()).collect())
+                }
+                _ => ParamKind::Json,
+            }
+        }
+        _ => ParamKind::Json,
+    }
+}
+
+/// Action parameter (`ActionParameter`) asking for `arg`
+fn action_parameter(idl: &Idl, arg: &IdlField) -> Value {
+    let required = !matches!(arg.ty, IdlType::Complex(IdlTypeComplex::Option(_)));
+    let mut parameter = json!({
+        "name": arg.name,
+        "label": title_case(&arg.name),
+        "required": required,
+    });
+    let options = |options: Vec<(String, String)>| -> Value {
+        options
+            .into_iter()
+            .map(|(label, value)| json!({ "label": label, "value": value }))
+            .collect()
+    };
+    let extra = match param_kind(idl, &arg.ty) {
+        ParamKind::Integer { unsigned, .. } => {
+            let mut extra = json!({
+                "type": "number",
+                "pattern": if unsigned { "^[0-9]+$" } else { "^-?[0-9]+$" },
+                "patternDescription": "A whole number",
+            });
+            if unsigned {
+                extra["min"] = json!(0);
+            }
+            extra
+        }
+        ParamKind::Float => json!({ "type": "number" }),
+        ParamKind::Bool => json!({
+            "type": "select",
+            "options": options(vec![
+                ("Yes".to_string(), "true".to_string()),
+                ("No".to_string(), "false".to_string()),
+            ]),
+        }),
+        ParamKind::Text => json!({ "type": "text" }),
+        ParamKind::Pubkey => json!({
+            "type": "text",
+            "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$",
+            "patternDescription": "A Solana address",
+        }),
+        ParamKind::Select(variants) => json!({
+            "type": "select",
+            "options": options(
+                variants
+                    .iter()
+                    .map(|v| (title_case(v), ts_ident(v)))
+                    .collect()
+            ),
+        }),
+        ParamKind::Json => json!({
+            "type": "textarea",
+            "patternDescription": format!("JSON: {}", format_type(&arg.ty)),
+        }),
+    };
+    if let (Some(parameter), Value::Object(extra)) = (parameter.as_object_mut(), extra) {
+        parameter.extend(extra);
+    }
+    parameter
+}
+
+/// TypeScript expression reading `arg` from the query string `params`
+fn ts_param(idl: &Idl, arg: &IdlField) -> String {
+    let raw = format!("params.get(\"{}\")!", arg.name);
+    let value = match param_kind(idl, &arg.ty) {
+        ParamKind::Integer { big: true, .. } => format!("new anchor.BN({})", raw),
+        ParamKind::Integer { big: false, .. } | ParamKind::Float => format!("Number({})", raw),
+        ParamKind::Bool => format!("{} === \"true\"", raw),
+        ParamKind::Text => raw,
+        ParamKind::Pubkey => format!("new PublicKey({})", raw),
+        ParamKind::Select(_) => format!("{{ [{}]: {{}} }}", raw),
+        ParamKind::Json => format!("JSON.parse({})", raw),
+    };
+    match arg.ty {
+        IdlType::Complex(IdlTypeComplex::Option(_)) => {
+            // Blink clients send an optional parameter left blank as ""
+            format!("params.get(\"{}\") ? {} : null", arg.name, value)
+        }
+        _ => value,
+    }
+}
+
+fn action_handler(idl: &Idl, ix: &IdlInstruction, descriptor: &Value) -> String {
+    let mut out = String::new();
+    let name = &idl.metadata.name;
+    let _ = writeln!(
+        out,
+        "// Solana Action for {} of {}, generated by `periscope codegen action`",
+        ix.name, name
+    );
+    out.push_str(
+        "import {\n\
+         \x20 ActionGetResponse,\n\
+         \x20 ActionPostRequest,\n\
+         \x20 ACTIONS_CORS_HEADERS,\n\
+         \x20 createPostResponse,\n\
+         } from \"@solana/actions\";\n\
+         import * as anchor from \"@coral-xyz/anchor\";\n\
+         import { clusterApiUrl, Connection, PublicKey, Transaction } from \"@solana/web3.js\";\n",
+    );
+    let _ = writeln!(out, "import idl from \"./{}.json\";\n", name);
+    out.push_str(
+        "const connection = new Connection(process.env.RPC_URL ?? clusterApiUrl(\"mainnet-beta\"));\n\n",
+    );
+
+    let descriptor = serde_json::to_string_pretty(descriptor).unwrap_or_default();
+    let _ = writeln!(
+        out,
+        "const descriptor: ActionGetResponse = {};\n",
+        descriptor
+    );
+    out.push_str(
+        "export const GET = async () => Response.json(descriptor, { headers: ACTIONS_CORS_HEADERS });\n\n\
+         // Preflight requests of wallets and Blink clients\n\
+         export const OPTIONS = GET;\n\n\
+         export const POST = async (req: Request) => {\n\
+         \x20 const params = new URL(req.url).searchParams;\n\
+         \x20 const body: ActionPostRequest = await req.json();\n\
+         \x20 const account = new PublicKey(body.account);\n\
+         \x20 const program = new anchor.Program(idl as anchor.Idl, { connection });\n\n",
+    );
+
+    let mut accounts = Vec::new();
+    flatten_accounts(&ix.accounts, &mut accounts);
+    let mut resolved = Vec::new();
+    let mut given = Vec::new();
+    let mut wallet_used = false;
+    for account in &accounts {
+        let key = ts_ident(&account.name);
+        if account.address.is_some() || account.pda.is_some() {
+            resolved.push(key);
+        } else if account.signer && !wallet_used {
+            // The wallet of the user clicking the Blink signs and pays
+            wallet_used = true;
+            given.push(format!("{}: account", key));
+        } else if account.signer {
+            given.push(format!(
+                "{}: PublicKey.default, // TODO: must also sign{}",
+                key,
+                constraints(account)
+            ));
+        } else {
+            given.push(format!(
+                "{}: PublicKey.default, // TODO{}",
+                key,
+                constraints(account)
+            ));
+        }
+    }
+
+    let _ = writeln!(out, "  const instruction = await program.methods");
+    if ix.args.is_empty() {
+        let _ = writeln!(out, "    .{}()", ts_ident(&ix.name));
+    } else {
+        let _ = writeln!(out, "    .{}(", ts_ident(&ix.name));
+        for arg in &ix.args {
+            let _ = writeln!(out, "      {},", ts_param(idl, arg));
+        }
+        let _ = writeln!(out, "    )");
+    }
+    if !resolved.is_empty() {
+        let _ = writeln!(out, "    // Resolved by Anchor: {}", resolved.join(", "));
+    }
+    let _ = writeln!(out, "    .accounts({{");
+    for entry in &given {
+        let _ = writeln!(out, "      {}", with_comma(entry));
+    }
+    let _ = writeln!(out, "    }})");
+    let _ = writeln!(out, "    .instruction();\n");
+    out.push_str(
+        "  const transaction = new Transaction().add(instruction);\n\
+         \x20 transaction.feePayer = account;\n\
+         \x20 transaction.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;\n\n\
+         \x20 const payload = await createPostResponse({\n\
+         \x20   fields: { type: \"transaction\", transaction },\n\
+         \x20 });\n\
+         \x20 return Response.json(payload, { headers: ACTIONS_CORS_HEADERS });\n\
+         };\n",
+    );
+    out
+}
+
+/// Identifier as Anchor's TypeScript client spells it (camelCase)
+fn ts_ident(name: &str) -> String {
+    let (ident, _) = rust_ident(name, Case::UpperCamel);
+    let mut chars = ident.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => ident,
+    }
+}
+
+/// Placeholder TypeScript value for `ty`
+fn ts_value(idl: &Idl, ty: &IdlType, depth: usize) -> String {
+    match ty {
+        IdlType::Primitive(name) => match name.as_str() {
+            "bool" => "false".to_string(),
+            "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "f32" | "f64" => "0".to_string(),
+            "u64" | "i64" | "u128" | "i128" | "u256" | "i256" => "new anchor.BN(0)".to_string(),
+            "pubkey" | "publicKey" => "PublicKey.default".to_string(),
+            "bytes" => "Buffer.from([])".to_string(),
+            _ => "\"\"".to_string(),
+        },
+        IdlType::Complex(IdlTypeComplex::Option(_)) => "null".to_string(),
+        IdlType::Complex(IdlTypeComplex::Vec(_)) => "[]".to_string(),
+        IdlType::Complex(IdlTypeComplex::Array(inner, len)) => {
+            format!(
+                "Array.from({{ length: {} }}, () => {})",
+                len,
+                ts_value(idl, inner, depth + 1)
+            )
+        }
+        IdlType::Complex(IdlTypeComplex::Generic(_)) => "null /* TODO */".to_string(),
+        IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+            if depth >= MAX_DEPTH {
+                return "null /* TODO */".to_string();
+            }
+            let type_def = idl.instantiate_type(name, generics).ok();
+            match type_def.as_ref().map(|t| &t.ty) {
+                Some(IdlTypeDefTy::Struct { fields }) => ts_object(idl, fields, depth),
+                Some(IdlTypeDefTy::Enum { variants }) => match variants.first() {
+                    Some(variant) => {
+                        let fields = match &variant.fields {
+                            None => "{}".to_string(),
+                            Some(IdlEnumFields::Named(fields)) => ts_object(idl, fields, depth),
+                            Some(IdlEnumFields::Tuple(types)) => {
+                                let items: Vec<String> = types
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(i, t)| format!("{}: {}", i, ts_value(idl, t, depth + 1)))
+                                    .collect();
+                                format!("{{ {} }}", items.join(", "))
+                            }
+                        };
+                        format!("{{ {}: {} }}", ts_ident(&variant.name), fields)
+                    }
+                    None => "{}".to_string(),
+                },
+                None => "null /* TODO */".to_string(),
+            }
+        }
+    }
+}
+
+fn ts_object(idl: &Idl, fields: &[IdlField], depth: usize) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    let entries: Vec<String> = fields
+        .iter()
+        .map(|f| format!("{}: {}", ts_ident(&f.name), ts_value(idl, &f.ty, depth + 1)))
+        .collect();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+/// Rust expression producing the Borsh bytes of a zero value, for primitives
+fn borsh_stub(ty: &IdlType) -> Option<String> {
+    let IdlType::Primitive(name) = ty else {
+        return None;
+    };
+    Some(match name.as_str() {
+        "bool" => "[0u8]".to_string(),
+        "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "u128" | "i128" => {
+            format!("0{}.to_le_bytes()", name)
+        }
+        "f32" | "f64" => format!("0.0{}.to_le_bytes()", name),
+        "pubkey" | "publicKey" => "Pubkey::default().to_bytes()".to_string(),
+        // Empty string / bytes: a zero u32 length
+        "string" | "bytes" => "0u32.to_le_bytes()".to_string(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::Annotation;
+    use serde_json::json;
+
+    fn test_idl() -> Idl {
+        serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "swap_exact_in",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "pool", "writable": true },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [
+                    { "name": "amount_in", "type": "u64" },
+                    { "name": "side", "type": { "defined": { "name": "Side" } } }
+                ]
+            }],
+            "types": [{ "name": "Side", "type": { "kind": "enum", "variants": [
+                { "name": "Bid" }, { "name": "Ask" }
+            ] } }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mocha_skeleton() {
+        let ts = generate_tests(&test_idl(), TestFramework::Mocha, &Annotations::default());
+        assert!(ts.contains("it(\"swap_exact_in\""));
+        assert!(ts.contains(".swapExactIn(new anchor.BN(0), { bid: {} })"));
+        assert!(ts.contains("const user = Keypair.generate();"));
+        assert!(ts.contains("pool: PublicKey.default, // TODO: writable"));
+        assert!(ts.contains("// Resolved by Anchor: systemProgram"));
+        assert!(ts.contains(".signers([user])"));
+    }
+
+    #[test]
+    fn test_action_scaffold() {
+        let idl = test_idl();
+        let annotations = Annotations {
+            instructions: [(
+                "swap_exact_in".to_string(),
+                Annotation {
+                    label: Some("Swap".to_string()),
+                    note: Some("Sell the input token for the pool's other token".to_string()),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let action = generate_action(&idl, &idl.instructions[0], &annotations);
+
+        let descriptor = &action.descriptor;
+        assert_eq!(descriptor["type"], "action");
+        assert_eq!(descriptor["title"], "Swap");
+        let link = &descriptor["links"]["actions"][0];
+        assert_eq!(
+            link["href"],
+            "/api/actions/swap_exact_in?amount_in={amount_in}&side={side}"
+        );
+        let amount_in = &link["parameters"][0];
+        assert_eq!(
+            (
+                &amount_in["type"],
+                &amount_in["min"],
+                &amount_in["required"]
+            ),
+            (&json!("number"), &json!(0), &json!(true))
+        );
+        let side = &link["parameters"][1];
+        assert_eq!(side["type"], "select");
+        assert_eq!(
+            side["options"][1],
+            json!({ "label": "Ask", "value": "ask" })
+        );
+
+        let ts = &action.handler;
+        assert!(ts.contains("new anchor.BN(params.get(\"amount_in\")!),"));
+        assert!(ts.contains("{ [params.get(\"side\")!]: {} },"));
+        assert!(ts.contains("user: account,"));
+        assert!(ts.contains("pool: PublicKey.default, // TODO: writable"));
+        assert!(ts.contains("// Resolved by Anchor: systemProgram"));
+    }
+
+    #[test]
+    fn test_litesvm_skeleton() {
+        let annotations = Annotations {
+            instructions: [(
+                "swapExactIn".to_string(),
+                Annotation {
+                    note: Some("use swap_v2".to_string()),
+                    deprecated: true,
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let rs = generate_tests(&test_idl(), TestFramework::LiteSvm, &annotations);
+        assert!(rs.contains("// DEPRECATED\n// use swap_v2\n#[test]\nfn test_swap_exact_in()"));
+        assert!(rs.contains("fn test_swap_exact_in()"));
+        assert!(rs.contains("let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];"));
+        assert!(rs.contains("data.extend(0u64.to_le_bytes()); // amount_in: u64"));
+        assert!(rs.contains("AccountMeta::new(payer.pubkey(), true), // user: writable, signer"));
+        assert!(rs.contains("// TODO: data.extend(borsh::to_vec(&side).unwrap()); // Side"));
+    }
+}
@@ -0,0 +1,580 @@
+//! Instruction compatibility between two versions of an IDL
+//!
+//! `periscope compat` pairs each instruction of an old IDL with its
+//! counterpart in a new one and maps their arguments and accounts: kept,
+//! renamed, retyped, added or removed. Instructions are paired by
+//! discriminator (what the program dispatches on), then by an identical
+//! signature for renamed ones; arguments and accounts by name, then by
+//! type. Names are compared as Rust identifiers, so a legacy `amountIn`
+//! matches a new `amount_in`.
+//!
+//! [`rust_adapter`] turns the mapping into Rust functions that convert old
+//! calls to new ones, with a `todo!()` wherever the new interface needs a
+//! value the old call doesn't have.
+
+use crate::idl::{
+    format_type, rust_ident, Case, Idl, IdlAccount, IdlAccountItem, IdlGenericArg, IdlInstruction,
+    IdlType, IdlTypeComplex,
+};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// How an item changed between the two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Change {
+    Unchanged,
+    Renamed,
+    /// Same name, different type (arguments) or constraints (accounts)
+    Retyped,
+    /// Instruction whose arguments or accounts changed
+    Changed,
+    Added,
+    Removed,
+}
+
+/// An argument or account in the old and new versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemMapping {
+    pub old: Option<String>,
+
+    pub new: Option<String>,
+
+    pub change: Change,
+
+    /// Type change, or how to fill in an added item
+    pub note: Option<String>,
+}
+
+/// An instruction in the old and new versions
+#[derive(Debug, Clone, Serialize)]
+pub struct InstructionMapping {
+    pub old: Option<String>,
+
+    pub new: Option<String>,
+
+    pub change: Change,
+
+    pub args: Vec<ItemMapping>,
+
+    pub accounts: Vec<ItemMapping>,
+}
+
+/// Map every instruction of `old` to `new`
+///
+/// Instructions come in the order of the new IDL, followed by removed ones.
+pub fn compare_instructions(old: &Idl, new: &Idl) -> Vec<InstructionMapping> {
+    let mut pairs: Vec<(Option<&IdlInstruction>, Option<&IdlInstruction>)> = Vec::new();
+    let mut unmatched_old: Vec<&IdlInstruction> = old.instructions.iter().collect();
+    let mut unmatched_new = Vec::new();
+
+    for ix in &new.instructions {
+        let discriminator = ix.discriminator_bytes();
+        match unmatched_old
+            .iter()
+            .position(|o| o.discriminator_bytes() == discriminator)
+        {
+            Some(i) => pairs.push((Some(unmatched_old.remove(i)), Some(ix))),
+            None => {
+                pairs.push((None, Some(ix)));
+                unmatched_new.push(pairs.len() - 1);
+            }
+        }
+    }
+
+    // Renamed: same argument types and account count as an unmatched old one
+    for slot in unmatched_new {
+        let ix = pairs[slot].1.expect("new instruction");
+        if let Some(i) = unmatched_old.iter().position(|o| same_signature(o, ix)) {
+            pairs[slot].0 = Some(unmatched_old.remove(i));
+        }
+    }
+    pairs.extend(unmatched_old.into_iter().map(|o| (Some(o), None)));
+
+    pairs
+        .into_iter()
+        .map(|(old, new)| map_instruction(old, new))
+        .collect()
+}
+
+fn same_signature(a: &IdlInstruction, b: &IdlInstruction) -> bool {
+    let types = |ix: &IdlInstruction| -> Vec<String> {
+        ix.args.iter().map(|arg| format_type(&arg.ty)).collect()
+    };
+    types(a) == types(b) && accounts(&a.accounts).len() == accounts(&b.accounts).len()
+}
+
+fn map_instruction(
+    old: Option<&IdlInstruction>,
+    new: Option<&IdlInstruction>,
+) -> InstructionMapping {
+    let (args, accounts) = match (old, new) {
+        (Some(old), Some(new)) => (
+            map_items(
+                &old.args
+                    .iter()
+                    .map(|a| (a.name.as_str(), format_type(&a.ty)))
+                    .collect::<Vec<_>>(),
+                &new.args
+                    .iter()
+                    .map(|a| (a.name.as_str(), format_type(&a.ty)))
+                    .collect::<Vec<_>>(),
+                |name, ty| Some(argument_default(new, name, ty)),
+            ),
+            map_items(
+                &account_keys(&old.accounts),
+                &account_keys(&new.accounts),
+                |name, _| {
+                    accounts(&new.accounts)
+                        .into_iter()
+                        .find(|a| a.name == name)
+                        .map(account_default)
+                },
+            ),
+        ),
+        _ => (Vec::new(), Vec::new()),
+    };
+
+    let change = match (old, new) {
+        (None, _) => Change::Added,
+        (_, None) => Change::Removed,
+        (Some(o), Some(n)) if snake(&o.name) != snake(&n.name) => Change::Renamed,
+        _ if args
+            .iter()
+            .chain(&accounts)
+            .any(|item| item.change != Change::Unchanged) =>
+        {
+            Change::Changed
+        }
+        _ => Change::Unchanged,
+    };
+
+    InstructionMapping {
+        old: old.map(|ix| ix.name.clone()),
+        new: new.map(|ix| ix.name.clone()),
+        change,
+        args,
+        accounts,
+    }
+}
+
+/// Pair items by name, then unmatched ones by key (type); new order first
+fn map_items(
+    old: &[(&str, String)],
+    new: &[(&str, String)],
+    added_note: impl Fn(&str, &str) -> Option<String>,
+) -> Vec<ItemMapping> {
+    let mut unmatched_old: Vec<&(&str, String)> = old.iter().collect();
+    let mut slots: Vec<Option<&(&str, String)>> = Vec::with_capacity(new.len());
+    for (name, _) in new {
+        match unmatched_old
+            .iter()
+            .position(|(o, _)| snake(o) == snake(name))
+        {
+            Some(i) => slots.push(Some(unmatched_old.remove(i))),
+            None => slots.push(None),
+        }
+    }
+    for (slot, (_, key)) in slots.iter_mut().zip(new) {
+        if slot.is_none() {
+            if let Some(i) = unmatched_old.iter().position(|(_, k)| k == key) {
+                *slot = Some(unmatched_old.remove(i));
+            }
+        }
+    }
+
+    let mut items: Vec<ItemMapping> = slots
+        .into_iter()
+        .zip(new)
+        .map(|(old, (name, key))| match old {
+            None => ItemMapping {
+                old: None,
+                new: Some(name.to_string()),
+                change: Change::Added,
+                note: added_note(name, key),
+            },
+            Some((old_name, old_key)) => {
+                let change = if snake(old_name) != snake(name) {
+                    Change::Renamed
+                } else if old_key != key {
+                    Change::Retyped
+                } else {
+                    Change::Unchanged
+                };
+                ItemMapping {
+                    old: Some(old_name.to_string()),
+                    new: Some(name.to_string()),
+                    change,
+                    note: (old_key != key).then(|| format!("{} -> {}", old_key, key)),
+                }
+            }
+        })
+        .collect();
+    items.extend(unmatched_old.into_iter().map(|(name, _)| ItemMapping {
+        old: Some(name.to_string()),
+        new: None,
+        change: Change::Removed,
+        note: None,
+    }));
+    items
+}
+
+fn snake(name: &str) -> String {
+    rust_ident(name, Case::Snake).0
+}
+
+fn accounts(items: &[IdlAccountItem]) -> Vec<&IdlAccount> {
+    let mut out = Vec::new();
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => out.push(account),
+            IdlAccountItem::Group(group) => out.extend(accounts(&group.accounts)),
+        }
+    }
+    out
+}
+
+/// Accounts keyed by their constraints
+fn account_keys(items: &[IdlAccountItem]) -> Vec<(&str, String)> {
+    accounts(items)
+        .into_iter()
+        .map(|a| {
+            let mut key = Vec::new();
+            for (set, constraint) in [
+                (a.writable, "writable"),
+                (a.signer, "signer"),
+                (a.optional, "optional"),
+            ] {
+                if set {
+                    key.push(constraint);
+                }
+            }
+            let key = if key.is_empty() {
+                "read-only".to_string()
+            } else {
+                key.join(", ")
+            };
+            (a.name.as_str(), key)
+        })
+        .collect()
+}
+
+/// How to fill in an argument the old call doesn't have
+fn argument_default(ix: &IdlInstruction, name: &str, ty: &str) -> String {
+    let arg = ix.args.iter().find(|a| a.name == name);
+    match arg.map(|a| &a.ty) {
+        Some(IdlType::Complex(IdlTypeComplex::Option(_))) => format!("{}, default None", ty),
+        Some(IdlType::Complex(IdlTypeComplex::Vec(_))) => format!("{}, default empty", ty),
+        _ => format!("{}, no default", ty),
+    }
+}
+
+/// How to fill in an account the old call doesn't have
+fn account_default(account: &IdlAccount) -> String {
+    if let Some(address) = &account.address {
+        format!("fixed address {}", address)
+    } else if account.pda.is_some() {
+        "PDA, derive from its seeds".to_string()
+    } else if account.optional {
+        "optional, default None".to_string()
+    } else if account.signer {
+        "new signer, no default".to_string()
+    } else {
+        "no default".to_string()
+    }
+}
+
+/// Rust functions converting calls of the old instructions into the new ones
+///
+/// Each changed instruction gets `old::` and `new::` structs for its
+/// accounts and arguments and a function between them. Defined types are
+/// referenced by name and must be in scope.
+pub fn rust_adapter(old: &Idl, new: &Idl, mappings: &[InstructionMapping]) -> String {
+    let mut out = format!(
+        "//! Adapter from {name} {} to {name} {} (generated by periscope)\n\
+         //!\n\
+         //! Every `todo!()` is a value the old call doesn't carry.\n",
+        old.metadata.version,
+        new.metadata.version,
+        name = new.metadata.name,
+    );
+
+    let adapted: Vec<(&InstructionMapping, &IdlInstruction, &IdlInstruction)> = mappings
+        .iter()
+        .filter(|m| matches!(m.change, Change::Renamed | Change::Changed))
+        .filter_map(|m| {
+            let o = old
+                .instructions
+                .iter()
+                .find(|ix| Some(&ix.name) == m.old.as_ref())?;
+            let n = new
+                .instructions
+                .iter()
+                .find(|ix| Some(&ix.name) == m.new.as_ref())?;
+            Some((m, o, n))
+        })
+        .collect();
+
+    for (label, list) in [
+        ("Unchanged", Change::Unchanged),
+        ("Removed", Change::Removed),
+        ("New", Change::Added),
+    ] {
+        let names: Vec<&str> = mappings
+            .iter()
+            .filter(|m| m.change == list)
+            .filter_map(|m| m.new.as_deref().or(m.old.as_deref()))
+            .collect();
+        if !names.is_empty() {
+            let _ = writeln!(out, "//! {}: {}", label, names.join(", "));
+        }
+    }
+
+    for (module, side) in [("old", 0), ("new", 1)] {
+        let _ = writeln!(
+            out,
+            "\npub mod {} {{\n    use solana_sdk::pubkey::Pubkey;\n    #[allow(unused_imports)]\n    use super::*;",
+            module
+        );
+        for (_, o, n) in &adapted {
+            let ix = if side == 0 { o } else { n };
+            out.push_str(&instruction_structs(ix));
+        }
+        out.push_str("}\n");
+    }
+
+    for (mapping, o, n) in adapted {
+        out.push_str(&adapter_fn(mapping, o, n));
+    }
+    out
+}
+
+fn instruction_structs(ix: &IdlInstruction) -> String {
+    let ty = rust_ident(&ix.name, Case::UpperCamel).0;
+    let mut out = format!("\n    pub struct {}Accounts {{\n", ty);
+    for account in accounts(&ix.accounts) {
+        let field_ty = if account.optional {
+            "Option<Pubkey>"
+        } else {
+            "Pubkey"
+        };
+        let _ = writeln!(out, "        pub {}: {},", snake(&account.name), field_ty);
+    }
+    let _ = writeln!(out, "    }}\n\n    pub struct {}Args {{", ty);
+    for arg in &ix.args {
+        let _ = writeln!(
+            out,
+            "        pub {}: {},",
+            snake(&arg.name),
+            rust_type(&arg.ty)
+        );
+    }
+    out.push_str("    }\n");
+    out
+}
+
+fn adapter_fn(mapping: &InstructionMapping, old: &IdlInstruction, new: &IdlInstruction) -> String {
+    let old_ty = rust_ident(&old.name, Case::UpperCamel).0;
+    let new_ty = rust_ident(&new.name, Case::UpperCamel).0;
+    let mut out = format!(
+        "\n/// `{}` -> `{}`\npub fn {}(\n    accounts: old::{}Accounts,\n    args: old::{}Args,\n) -> (new::{}Accounts, new::{}Args) {{\n",
+        old.name,
+        new.name,
+        snake(&old.name),
+        old_ty,
+        old_ty,
+        new_ty,
+        new_ty
+    );
+
+    let new_accounts = accounts(&new.accounts);
+    let _ = writeln!(out, "    let new_accounts = new::{}Accounts {{", new_ty);
+    for item in &mapping.accounts {
+        let Some(name) = &item.new else { continue };
+        let account = new_accounts.iter().find(|a| &a.name == name);
+        let value = match (&item.old, account) {
+            (Some(old), _) => format!("accounts.{}", snake(old)),
+            (None, Some(a)) if a.address.is_some() => format!(
+                "solana_sdk::pubkey!(\"{}\")",
+                a.address.as_deref().unwrap_or_default()
+            ),
+            (None, Some(a)) if a.optional => "None".to_string(),
+            _ => format!(
+                "todo!(\"{}\")",
+                item.note.as_deref().unwrap_or("new account")
+            ),
+        };
+        let _ = writeln!(out, "        {}: {},{}", snake(name), value, comment(item));
+    }
+    out.push_str("    };\n");
+
+    let _ = writeln!(out, "    let new_args = new::{}Args {{", new_ty);
+    for item in &mapping.args {
+        let Some(name) = &item.new else { continue };
+        let arg = new.args.iter().find(|a| &a.name == name);
+        let value = match (&item.old, item.change, arg.map(|a| &a.ty)) {
+            (Some(old), Change::Retyped, _) => {
+                format!("todo!(\"convert args.{}\")", snake(old))
+            }
+            (Some(old), _, _) => format!("args.{}", snake(old)),
+            (None, _, Some(IdlType::Complex(IdlTypeComplex::Option(_)))) => "None".to_string(),
+            (None, _, Some(IdlType::Complex(IdlTypeComplex::Vec(_)))) => "Vec::new()".to_string(),
+            _ => format!(
+                "todo!(\"{}\")",
+                item.note.as_deref().unwrap_or("new argument")
+            ),
+        };
+        let _ = writeln!(out, "        {}: {},{}", snake(name), value, comment(item));
+    }
+    out.push_str("    };\n");
+
+    for item in mapping.args.iter().chain(&mapping.accounts) {
+        if let (Change::Removed, Some(old)) = (item.change, &item.old) {
+            let _ = writeln!(out, "    // Removed: {}", snake(old));
+        }
+    }
+    out.push_str("    (new_accounts, new_args)\n}\n");
+    out
+}
+
+/// Trailing comment describing a change
+fn comment(item: &ItemMapping) -> String {
+    match (item.change, &item.old, &item.note) {
+        (Change::Renamed, Some(old), _) => format!(" // was {}", old),
+        (Change::Retyped, _, Some(note)) => format!(" // {}", note),
+        (Change::Added, _, _) => " // new".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Rust spelling of an IDL type
+fn rust_type(ty: &IdlType) -> String {
+    match ty {
+        IdlType::Primitive(name) => match name.as_str() {
+            "pubkey" | "publicKey" => "Pubkey".to_string(),
+            "string" => "String".to_string(),
+            "bytes" => "Vec<u8>".to_string(),
+            other => other.to_string(),
+        },
+        IdlType::Complex(IdlTypeComplex::Vec(inner)) => format!("Vec<{}>", rust_type(inner)),
+        IdlType::Complex(IdlTypeComplex::Option(inner)) => {
+            format!("Option<{}>", rust_type(inner))
+        }
+        IdlType::Complex(IdlTypeComplex::Array(inner, len)) => {
+            format!("[{}; {}]", rust_type(inner), len)
+        }
+        IdlType::Complex(IdlTypeComplex::Defined { name, generics }) if generics.is_empty() => {
+            rust_ident(name, Case::UpperCamel).0
+        }
+        IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+            let args: Vec<String> = generics
+                .iter()
+                .map(|arg| match arg {
+                    IdlGenericArg::Type { ty } => rust_type(ty),
+                    IdlGenericArg::Const { value } => value.clone(),
+                })
+                .collect();
+            format!(
+                "{}<{}>",
+                rust_ident(name, Case::UpperCamel).0,
+                args.join(", ")
+            )
+        }
+        IdlType::Complex(IdlTypeComplex::Generic(name)) => name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn idl(version: &str, instructions: Value) -> Idl {
+        serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": version, "spec": "0.1.0" },
+            "instructions": instructions
+        }))
+        .unwrap()
+    }
+
+    fn old_and_new() -> (Idl, Idl) {
+        let old = idl(
+            "0.1.0",
+            json!([
+                { "name": "swap", "accounts": [
+                    { "name": "pool", "writable": true },
+                    { "name": "owner", "signer": true }
+                ], "args": [
+                    { "name": "amountIn", "type": "u64" },
+                    { "name": "slippage", "type": "u16" }
+                ] },
+                { "name": "close", "accounts": [], "args": [{ "name": "force", "type": "bool" }] },
+                { "name": "init", "accounts": [], "args": [] }
+            ]),
+        );
+        let new = idl(
+            "0.2.0",
+            json!([
+                { "name": "swap", "accounts": [
+                    { "name": "pool", "writable": true },
+                    { "name": "user", "signer": true },
+                    { "name": "token_program", "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" }
+                ], "args": [
+                    { "name": "amount_in", "type": "u64" },
+                    { "name": "slippage", "type": "u32" },
+                    { "name": "min_out", "type": { "option": "u64" } }
+                ] },
+                { "name": "close_pool", "accounts": [], "args": [{ "name": "force", "type": "bool" }] },
+                { "name": "init", "accounts": [], "args": [] },
+                { "name": "migrate", "accounts": [], "args": [] }
+            ]),
+        );
+        (old, new)
+    }
+
+    #[test]
+    fn test_compare_instructions() {
+        let (old, new) = old_and_new();
+        let mappings = compare_instructions(&old, &new);
+        let changes: Vec<_> = mappings
+            .iter()
+            .map(|m| (m.new.as_deref(), m.change))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (Some("swap"), Change::Changed),
+                (Some("close_pool"), Change::Renamed),
+                (Some("init"), Change::Unchanged),
+                (Some("migrate"), Change::Added),
+            ]
+        );
+
+        let swap = &mappings[0];
+        let args: Vec<_> = swap.args.iter().map(|a| a.change).collect();
+        assert_eq!(args, [Change::Unchanged, Change::Retyped, Change::Added]);
+        assert_eq!(swap.args[1].note.as_deref(), Some("u16 -> u32"));
+        assert_eq!(swap.accounts[1].change, Change::Renamed);
+        assert_eq!(swap.accounts[1].old.as_deref(), Some("owner"));
+        assert_eq!(
+            swap.accounts[2].note.as_deref(),
+            Some("fixed address TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+        );
+    }
+
+    #[test]
+    fn test_rust_adapter() {
+        let (old, new) = old_and_new();
+        let code = rust_adapter(&old, &new, &compare_instructions(&old, &new));
+        assert!(code.contains("//! Unchanged: init"));
+        assert!(code.contains("//! New: migrate"));
+        assert!(code.contains("pub fn swap(\n    accounts: old::SwapAccounts,"));
+        assert!(code.contains("        user: accounts.owner, // was owner\n"));
+        assert!(code.contains("token_program: solana_sdk::pubkey!(\"Tokenkeg"));
+        assert!(code.contains("        amount_in: args.amount_in,\n"));
+        assert!(code.contains("slippage: todo!(\"convert args.slippage\"), // u16 -> u32"));
+        assert!(code.contains("        min_out: None, // new\n"));
+        assert!(code.contains("pub fn close("));
+    }
+}
@@ -1,11 +1,22 @@
 //! Configuration management for Periscope CLI
 //!
-//! Config is stored at ~/.config/periscope/config.toml
+//! Config is stored at ~/.config/periscope/config.toml. A `.periscope.toml`
+//! in the current directory or any parent pins project settings (program ID,
+//! cluster, IDL path, annotations) that take precedence over the user config.
+//!
+//! The user config carries a `version`. Files written by an older Periscope
+//! are upgraded on load by [`migrate`], after backing up the original.
 
 use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::i18n::Lang;
+use crate::idl::{check_rpc_headers, DEFAULT_MAX_IDL_SIZE};
+use crate::links::{ExplorerTemplate, DEFAULT_EXPLORER_URL};
+use crate::theme::{Theme, ThemeConfig};
+use crate::trust::TrustPolicy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Default RPC URL (mainnet-beta)
 pub const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
@@ -16,12 +27,255 @@ const CONFIG_DIR: &str = "periscope";
 /// Config file name
 const CONFIG_FILE: &str = "config.toml";
 
+/// Current config file format version
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Project config file name, looked up from the current directory upwards
+pub const PROJECT_CONFIG_FILE: &str = ".periscope.toml";
+
+/// Keys accepted by `config set` and `config unset`, besides `theme.<element>`
+pub const CONFIG_KEYS: &[&str] = &[
+    "rpc_url",
+    "lang",
+    "max_idl_size_mib",
+    "per_page",
+    "explorer_url",
+    "read_only",
+    "theme.name",
+];
+
+/// Prefix of the `config set` keys that add RPC headers (`rpc_headers.<name>`)
+const RPC_HEADER_PREFIX: &str = "rpc_headers.";
+
+/// RPC URLs of the public clusters, by name
+const CLUSTERS: &[(&str, &str)] = &[
+    ("mainnet-beta", "https://api.mainnet-beta.solana.com"),
+    ("mainnet", "https://api.mainnet-beta.solana.com"),
+    ("devnet", "https://api.devnet.solana.com"),
+    ("testnet", "https://api.testnet.solana.com"),
+    ("localnet", "http://127.0.0.1:8899"),
+    ("localhost", "http://127.0.0.1:8899"),
+];
+
 /// Periscope configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Config file format version (0 for files predating versioning)
+    #[serde(default)]
+    pub version: u32,
+
     /// RPC URL for fetching IDLs
     #[serde(default = "default_rpc_url")]
     pub rpc_url: String,
+
+    /// Output color theme
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_empty")]
+    pub theme: ThemeConfig,
+
+    /// Output language code (e.g. "es"); falls back to $LANG when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+
+    /// Largest decompressed or downloaded IDL accepted, in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_idl_size_mib: Option<usize>,
+
+    /// Page size of list commands; lists are paged by default when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<usize>,
+
+    /// Block explorer that `--links` point at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+
+    /// Explorers, IDL registries and token lists
+    #[serde(default, skip_serializing_if = "Endpoints::is_empty")]
+    pub endpoints: Endpoints,
+
+    /// Extra HTTP headers sent with every RPC request, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rpc_headers: BTreeMap<String, String>,
+
+    /// Refuse to sign or send transactions, whatever the command line says
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+
+    /// Which IDL sources are trusted
+    #[serde(default, skip_serializing_if = "TrustPolicy::is_empty")]
+    pub trust: TrustPolicy,
+
+    /// Default program ID (project config only, never saved to the user config)
+    #[serde(skip)]
+    pub program_id: Option<String>,
+
+    /// Default IDL file path (project config only, never saved to the user config)
+    #[serde(skip)]
+    pub idl: Option<String>,
+
+    /// Directory of the project's annotations files (project config only)
+    #[serde(skip)]
+    pub annotations_dir: Option<PathBuf>,
+
+    /// Project config file that was layered on top, if any
+    #[serde(skip)]
+    pub project_file: Option<PathBuf>,
+
+    /// Keys this version doesn't know, kept so saving doesn't drop them
+    #[serde(flatten, skip_serializing_if = "toml::Table::is_empty")]
+    pub unknown: toml::Table,
+}
+
+/// `[endpoints]` table: services besides the RPC node, which organizations
+/// often run their own of
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Endpoints {
+    /// Block explorers; `--links` use the first, `whois` lists them all
+    /// (default: `explorer_url`, else the Solana Explorer)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explorers: Vec<ExplorerTemplate>,
+
+    /// IDL URL templates (`{program_id}`, `{cluster}`), tried in order for
+    /// programs without an on-chain IDL (default: none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registries: Vec<String>,
+
+    /// Token lists in the Solana token-list or Jupiter format, looked up
+    /// after the bundled list and before the chain (default: none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_lists: Vec<String>,
+}
+
+impl Endpoints {
+    pub fn is_empty(&self) -> bool {
+        self.explorers.is_empty() && self.registries.is_empty() && self.token_lists.is_empty()
+    }
+
+    /// Check every endpoint is an http(s) URL and registries name the program
+    pub fn validate(&self) -> Result<(), String> {
+        for explorer in &self.explorers {
+            explorer.validate()?;
+        }
+        for url in self.registries.iter().chain(&self.token_lists) {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(format!("{} must start with http:// or https://", url));
+            }
+        }
+        if let Some(url) = self
+            .registries
+            .iter()
+            .find(|url| !url.contains("{program_id}"))
+        {
+            return Err(format!(
+                "registry {} has no {{program_id}} placeholder",
+                url
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Project-local settings from `.periscope.toml`; every key is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Program the project works on
+    pub program_id: Option<String>,
+
+    /// Cluster name (`devnet`, `mainnet-beta`, ...); RPC URLs are only
+    /// accepted from config.toml or `--url`
+    pub cluster: Option<String>,
+
+    /// IDL file, relative to the `.periscope.toml` directory
+    pub idl: Option<String>,
+
+    /// Output language
+    pub lang: Option<String>,
+
+    /// Directory of annotations files (`<PROGRAM_ID>.toml`), relative to the
+    /// `.periscope.toml` directory
+    pub annotations: Option<String>,
+
+    /// Always rejected: endpoints are the user's to choose, see
+    /// [`Config::apply_project`]
+    #[serde(default)]
+    pub endpoints: Endpoints,
+}
+
+impl ProjectConfig {
+    /// Find the nearest `.periscope.toml` in `start` or its parents
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Parse a project config file
+    pub fn load(path: &Path) -> PeriscopeResult<Self> {
+        let contents = fs::read_to_string(path).map_err(PeriscopeError::IoError)?;
+        toml::from_str(&contents).map_err(|e| {
+            PeriscopeError::ConfigError(format!("Failed to parse {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// Resolve a cluster name to its RPC URL; URLs are returned unchanged
+pub fn resolve_cluster(cluster: &str) -> PeriscopeResult<String> {
+    if cluster.starts_with("http://") || cluster.starts_with("https://") {
+        return Ok(cluster.to_string());
+    }
+    CLUSTERS
+        .iter()
+        .find(|(name, _)| *name == cluster)
+        .map(|(_, url)| url.to_string())
+        .ok_or_else(|| {
+            PeriscopeError::ConfigError(format!(
+                "Unknown cluster '{}' (use devnet, testnet, mainnet-beta, localnet or an RPC URL)",
+                cluster
+            ))
+        })
+}
+
+/// A value as `config set` echoes it: `rpc_headers.*` values are usually API
+/// keys, so they're masked like in `config show`
+pub fn display_value<'a>(key: &str, value: &'a str) -> &'a str {
+    if key.starts_with(RPC_HEADER_PREFIX) {
+        "********"
+    } else {
+        value
+    }
+}
+
+fn unknown_key(key: &str) -> PeriscopeError {
+    PeriscopeError::Usage(format!(
+        "Unknown config key '{}' (available: {}, theme.<element>, {}<name>)",
+        key,
+        CONFIG_KEYS.join(", "),
+        RPC_HEADER_PREFIX
+    ))
+}
+
+/// Write `contents` to `path`, readable only by the user on Unix: the file
+/// can hold `rpc_headers` API keys
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        // `mode` only applies to new files
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents.as_bytes())
+    }
+    #[cfg(not(unix))]
+    fs::write(path, contents)
 }
 
 fn default_rpc_url() -> String {
@@ -31,11 +285,68 @@ fn default_rpc_url() -> String {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             rpc_url: default_rpc_url(),
+            theme: ThemeConfig::default(),
+            lang: None,
+            max_idl_size_mib: None,
+            per_page: None,
+            explorer_url: None,
+            endpoints: Endpoints::default(),
+            rpc_headers: BTreeMap::new(),
+            read_only: false,
+            trust: TrustPolicy::default(),
+            program_id: None,
+            idl: None,
+            annotations_dir: None,
+            project_file: None,
+            unknown: toml::Table::new(),
         }
     }
 }
 
+/// A migration upgrading a config table by one version
+type Migration = fn(&mut toml::Table);
+
+/// Migrations in order; entry `n` upgrades version `n` to `n + 1`
+const MIGRATIONS: &[Migration] = &[
+    // 0 -> 1: files predating versioning only gain the `version` key
+    |_| {},
+];
+
+/// Upgrade a parsed config file to [`CONFIG_VERSION`]
+///
+/// Returns the version the file had, or an error if it was written by a
+/// newer Periscope.
+pub fn migrate(table: &mut toml::Table) -> PeriscopeResult<u32> {
+    let from = match table.get("version") {
+        None => 0,
+        Some(toml::Value::Integer(v)) => u32::try_from(*v)
+            .map_err(|_| PeriscopeError::ConfigError(format!("Invalid config version {}", v)))?,
+        Some(other) => {
+            return Err(PeriscopeError::ConfigError(format!(
+                "Invalid config version {}",
+                other
+            )))
+        }
+    };
+    if from > CONFIG_VERSION {
+        return Err(PeriscopeError::ConfigError(format!(
+            "Config version {} was written by a newer periscope (this one supports {})",
+            from, CONFIG_VERSION
+        )));
+    }
+
+    for migration in &MIGRATIONS[from as usize..] {
+        migration(table);
+    }
+    table.insert(
+        "version".into(),
+        toml::Value::Integer(CONFIG_VERSION.into()),
+    );
+    Ok(from)
+}
+
 impl Config {
     /// Get the config directory path (~/.config/periscope/)
     pub fn dir_path() -> PeriscopeResult<PathBuf> {
@@ -57,6 +368,10 @@ impl Config {
     }
 
     /// Load config from file, returning defaults if file doesn't exist
+    ///
+    /// Older files are migrated and rewritten, keeping the original as
+    /// `config.toml.v<N>.bak`. If the rewrite fails the migrated config is
+    /// still used and the migration runs again next time.
     pub fn load() -> PeriscopeResult<Self> {
         let path = Self::file_path()?;
 
@@ -70,12 +385,85 @@ impl Config {
             return Ok(Self::default());
         }
 
-        let config: Config = toml::from_str(&contents)
+        let mut table: toml::Table = toml::from_str(&contents)
+            .map_err(|e| PeriscopeError::ConfigError(format!("Failed to parse config: {}", e)))?;
+        let from = migrate(&mut table)?;
+
+        let config: Config = table
+            .try_into()
             .map_err(|e| PeriscopeError::ConfigError(format!("Failed to parse config: {}", e)))?;
 
+        if from < CONFIG_VERSION {
+            let backup = path.with_file_name(format!("{}.v{}.bak", CONFIG_FILE, from));
+            if fs::copy(&path, &backup).is_ok() {
+                let _ = config.save();
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load the user config with the nearest `.periscope.toml` layered on top
+    ///
+    /// Precedence is project > user > defaults.
+    pub fn load_layered() -> PeriscopeResult<Self> {
+        let mut config = Self::load()?;
+
+        let cwd = std::env::current_dir().map_err(PeriscopeError::IoError)?;
+        if let Some(path) = ProjectConfig::find(&cwd) {
+            let project = ProjectConfig::load(&path)?;
+            config.apply_project(project, &path)?;
+        }
+
         Ok(config)
     }
 
+    /// Override settings with a project config read from `path`
+    pub fn apply_project(&mut self, project: ProjectConfig, path: &Path) -> PeriscopeResult<()> {
+        // A cloned repository mustn't choose where IDLs come from, nor the
+        // token lists naming mints or the explorers links point to
+        let endpoints = &project.endpoints;
+        for (key, set) in [
+            ("registries", !endpoints.registries.is_empty()),
+            ("token_lists", !endpoints.token_lists.is_empty()),
+            ("explorers", !endpoints.explorers.is_empty()),
+        ] {
+            if set {
+                return Err(PeriscopeError::ConfigError(format!(
+                    "{}: endpoints.{} can only be set in config.toml",
+                    path.display(),
+                    key
+                )));
+            }
+        }
+        if let Some(cluster) = &project.cluster {
+            // Nor send requests (and the user's rpc_headers) to its own host
+            if cluster.starts_with("http://") || cluster.starts_with("https://") {
+                return Err(PeriscopeError::ConfigError(format!(
+                    "{}: cluster must be devnet, testnet, mainnet-beta or localnet; \
+                     set an RPC URL in config.toml or with --url",
+                    path.display()
+                )));
+            }
+            self.rpc_url = resolve_cluster(cluster)?;
+        }
+        if project.lang.is_some() {
+            self.lang = project.lang;
+        }
+        if project.program_id.is_some() {
+            self.program_id = project.program_id;
+        }
+        let base = path.parent().unwrap_or(Path::new("."));
+        if let Some(idl) = project.idl {
+            self.idl = Some(base.join(idl).to_string_lossy().into_owned());
+        }
+        if let Some(dir) = project.annotations {
+            self.annotations_dir = Some(base.join(dir));
+        }
+        self.project_file = Some(path.to_path_buf());
+        Ok(())
+    }
+
     /// Save config to file, creating directories if needed
     pub fn save(&self) -> PeriscopeResult<()> {
         let dir = Self::dir_path()?;
@@ -89,7 +477,7 @@ impl Config {
             PeriscopeError::ConfigError(format!("Failed to serialize config: {}", e))
         })?;
 
-        fs::write(&path, contents).map_err(PeriscopeError::IoError)?;
+        write_private(&path, &contents).map_err(PeriscopeError::IoError)?;
 
         Ok(())
     }
@@ -102,6 +490,354 @@ impl Config {
             ));
         }
 
+        Theme::from_config(&self.theme)?;
+
+        if let Some(lang) = &self.lang {
+            lang.parse::<Lang>().map_err(PeriscopeError::ConfigError)?;
+        }
+
+        if let Some(url) = &self.explorer_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(PeriscopeError::ConfigError(
+                    "explorer_url must start with http:// or https://".into(),
+                ));
+            }
+        }
+        self.endpoints
+            .validate()
+            .map_err(|e| PeriscopeError::ConfigError(format!("endpoints: {}", e)))?;
+        check_rpc_headers(&self.rpc_headers())
+            .map_err(|e| PeriscopeError::ConfigError(format!("rpc_headers: {}", e)))?;
+
+        if self.max_idl_size_mib == Some(0) {
+            return Err(PeriscopeError::ConfigError(
+                "max_idl_size_mib must be at least 1".into(),
+            ));
+        }
+        if self.per_page == Some(0) {
+            return Err(PeriscopeError::ConfigError(
+                "per_page must be at least 1".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Set a key from its textual value, checking it has the right type
+    ///
+    /// Keys are those of config.toml (`rpc_url`, `lang`, `max_idl_size_mib`,
+    /// `per_page`, `explorer_url`, `theme.name`, `theme.<element>`); `url` is
+    /// accepted for `rpc_url`.
+    /// The config is left unchanged if the value is invalid.
+    pub fn set_key(&mut self, key: &str, value: &str) -> PeriscopeResult<()> {
+        let mut updated = self.clone();
+        updated.apply_key(key, value)?;
+        updated.validate()?;
+        *self = updated;
         Ok(())
     }
+
+    fn apply_key(&mut self, key: &str, value: &str) -> PeriscopeResult<()> {
+        match key {
+            "rpc_url" | "url" => self.rpc_url = value.to_string(),
+            "lang" => self.lang = Some(value.to_string()),
+            "max_idl_size_mib" => {
+                let mib = value.parse().map_err(|_| {
+                    PeriscopeError::ConfigError(format!(
+                        "max_idl_size_mib must be a whole number of MiB, got '{}'",
+                        value
+                    ))
+                })?;
+                self.max_idl_size_mib = Some(mib);
+            }
+            "per_page" => {
+                let per_page = value.parse().map_err(|_| {
+                    PeriscopeError::ConfigError(format!(
+                        "per_page must be a whole number, got '{}'",
+                        value
+                    ))
+                })?;
+                self.per_page = Some(per_page);
+            }
+            "explorer_url" => self.explorer_url = Some(value.to_string()),
+            "read_only" => {
+                self.read_only = value.parse().map_err(|_| {
+                    PeriscopeError::ConfigError(format!(
+                        "read_only must be true or false, got '{}'",
+                        value
+                    ))
+                })?;
+            }
+            "theme.name" => self.theme.name = Some(value.to_string()),
+            _ if key.starts_with(RPC_HEADER_PREFIX) => {
+                let name = &key[RPC_HEADER_PREFIX.len()..];
+                self.rpc_headers.insert(name.to_string(), value.to_string());
+            }
+            _ => match key.strip_prefix("theme.") {
+                Some(element) => {
+                    self.theme
+                        .overrides
+                        .insert(element.to_string(), value.to_string());
+                }
+                None => return Err(unknown_key(key)),
+            },
+        }
+        Ok(())
+    }
+
+    /// Reset a key to its default (keys left over from other versions can be removed too)
+    pub fn unset_key(&mut self, key: &str) -> PeriscopeResult<()> {
+        match key {
+            "rpc_url" | "url" => self.rpc_url = default_rpc_url(),
+            "lang" => self.lang = None,
+            "max_idl_size_mib" => self.max_idl_size_mib = None,
+            "per_page" => self.per_page = None,
+            "explorer_url" => self.explorer_url = None,
+            "read_only" => self.read_only = false,
+            "theme.name" => self.theme.name = None,
+            _ => {
+                let removed = if let Some(name) = key.strip_prefix(RPC_HEADER_PREFIX) {
+                    self.rpc_headers.remove(name).is_some()
+                } else if let Some(element) = key.strip_prefix("theme.") {
+                    self.theme.overrides.remove(element).is_some()
+                } else {
+                    self.unknown.remove(key).is_some()
+                };
+                if !removed {
+                    return Err(unknown_key(key));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Editor for `config edit`: `$VISUAL`, then `$EDITOR`, then a platform default
+    pub fn editor() -> String {
+        std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or_else(|| {
+                if cfg!(windows) {
+                    "notepad".to_string()
+                } else {
+                    "vi".to_string()
+                }
+            })
+    }
+
+    /// Block explorer base URL for `--links`
+    pub fn explorer_url(&self) -> &str {
+        self.explorer_url.as_deref().unwrap_or(DEFAULT_EXPLORER_URL)
+    }
+
+    /// Configured explorers, else the one at [`Config::explorer_url`]
+    pub fn explorers(&self) -> Vec<ExplorerTemplate> {
+        if self.endpoints.explorers.is_empty() {
+            vec![ExplorerTemplate::from_base_url(self.explorer_url())]
+        } else {
+            self.endpoints.explorers.clone()
+        }
+    }
+
+    /// The `rpc_headers` table as (name, value) pairs
+    pub fn rpc_headers(&self) -> Vec<(String, String)> {
+        self.rpc_headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Largest IDL accepted, in bytes
+    pub fn max_idl_size(&self) -> usize {
+        self.max_idl_size_mib
+            .map(|mib| mib.saturating_mul(1024 * 1024))
+            .unwrap_or(DEFAULT_MAX_IDL_SIZE)
+    }
+
+    /// Output language: the `lang` key, then `$LANG`, then English
+    pub fn lang(&self) -> Lang {
+        self.lang
+            .as_deref()
+            .and_then(|code| code.parse().ok())
+            .or_else(|| {
+                std::env::var("LANG")
+                    .ok()
+                    .and_then(|l| Lang::from_locale(&l))
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_project() {
+        let project: ProjectConfig = toml::from_str(
+            r#"
+            program_id = "11111111111111111111111111111111"
+            cluster = "devnet"
+            idl = "target/idl/app.json"
+            annotations = "docs/annotations"
+            "#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config
+            .apply_project(project, Path::new("/work/app/.periscope.toml"))
+            .unwrap();
+
+        assert_eq!(config.rpc_url, "https://api.devnet.solana.com");
+        assert_eq!(config.idl.as_deref(), Some("/work/app/target/idl/app.json"));
+        assert_eq!(
+            config.annotations_dir.as_deref(),
+            Some(Path::new("/work/app/docs/annotations"))
+        );
+        assert!(config.program_id.is_some());
+
+        // Endpoints are only read from config.toml
+        let project: ProjectConfig = toml::from_str(
+            r#"
+            [[endpoints.explorers]]
+            name = "Internal"
+            address = "https://explorer.corp/a/{address}{cluster_query}"
+            tx = "https://explorer.corp/t/{signature}{cluster_query}"
+            "#,
+        )
+        .unwrap();
+        assert!(config
+            .apply_project(project, Path::new(".periscope.toml"))
+            .is_err());
+        assert_eq!(config.explorers()[0].name, "explorer.solana.com");
+        let project: ProjectConfig =
+            toml::from_str(r#"endpoints.token_lists = ["https://evil.example/tokens.json"]"#)
+                .unwrap();
+        assert!(config
+            .apply_project(project, Path::new(".periscope.toml"))
+            .is_err());
+        assert!(config.endpoints.token_lists.is_empty());
+
+        let bad: ProjectConfig = toml::from_str(r#"cluster = "moon""#).unwrap();
+        assert!(Config::default()
+            .apply_project(bad, Path::new(".periscope.toml"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [endpoints]
+            registries = ["https://idls.example.org/{cluster}/{program_id}.json"]
+            token_lists = ["https://example.org/tokens.json"]
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.explorers()[0].name, "explorer.solana.com");
+
+        let project: ProjectConfig =
+            toml::from_str(r#"endpoints.registries = ["https://evil.example/{program_id}"]"#)
+                .unwrap();
+        assert!(config
+            .apply_project(project, Path::new(".periscope.toml"))
+            .is_err());
+        assert_eq!(config.endpoints.registries.len(), 1);
+
+        // Only named clusters, never the project's own RPC host
+        let project: ProjectConfig = toml::from_str(r#"cluster = "devnet""#).unwrap();
+        config
+            .apply_project(project, Path::new(".periscope.toml"))
+            .unwrap();
+        assert_eq!(config.rpc_url, "https://api.devnet.solana.com");
+        let project: ProjectConfig =
+            toml::from_str(r#"cluster = "https://rpc.evil.example""#).unwrap();
+        assert!(config
+            .apply_project(project, Path::new(".periscope.toml"))
+            .is_err());
+        assert_eq!(config.rpc_url, "https://api.devnet.solana.com");
+
+        config.endpoints.registries = vec!["https://idls.example.org/latest.json".into()];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_set_and_unset_keys() {
+        let mut config = Config::default();
+        config.set_key("max_idl_size_mib", "32").unwrap();
+        config.set_key("theme.header", "bold magenta").unwrap();
+        config
+            .set_key("url", "https://api.devnet.solana.com")
+            .unwrap();
+        assert_eq!(config.max_idl_size_mib, Some(32));
+        config.set_key("read_only", "true").unwrap();
+        assert!(config.read_only);
+        assert!(config.set_key("read_only", "yes").is_err());
+        config.set_key("rpc_headers.x-api-key", "secret").unwrap();
+        assert_eq!(
+            config.rpc_headers(),
+            vec![("x-api-key".into(), "secret".into())]
+        );
+        // Echoed masked
+        assert_eq!(display_value("rpc_headers.x-api-key", "secret"), "********");
+        assert_eq!(display_value("per_page", "20"), "20");
+        config.unset_key("rpc_headers.x-api-key").unwrap();
+        assert!(config.rpc_headers.is_empty());
+        assert_eq!(config.rpc_url, "https://api.devnet.solana.com");
+
+        assert!(config.set_key("max_idl_size_mib", "lots").is_err());
+        assert!(config.set_key("per_page", "0").is_err());
+        assert!(config.set_key("lang", "klingon").is_err());
+        assert!(config.set_key("theme.nope", "red").is_err());
+        match config.set_key("timeout", "5") {
+            Err(PeriscopeError::Usage(msg)) => assert!(msg.contains("rpc_headers.<name>")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        config.unset_key("theme.header").unwrap();
+        config.unset_key("rpc_url").unwrap();
+        assert!(config.theme.overrides.is_empty());
+        assert_eq!(config.rpc_url, DEFAULT_RPC_URL);
+        assert!(config.unset_key("theme.header").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("periscope-config-{}", std::process::id()));
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "rpc_url = \"x\"").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "rpc_url = \"x\"");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_config() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            rpc_url = "https://api.devnet.solana.com"
+            future_key = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(migrate(&mut table).unwrap(), 0);
+
+        let config: Config = table.try_into().unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.rpc_url, "https://api.devnet.solana.com");
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert!(saved.contains("future_key = 3"));
+
+        let mut newer: toml::Table = toml::from_str("version = 99").unwrap();
+        assert!(migrate(&mut newer).is_err());
+    }
 }
@@ -0,0 +1,930 @@
+//! Decoding of account data using IDL type layouts
+//!
+//! Two layouts are supported: Borsh (regular Anchor accounts) and the
+//! `repr(C)` layout used by `#[account(zero_copy)]` accounts, where fields are
+//! aligned to their natural alignment instead of being tightly packed.
+//!
+//! Decoding is strict about bounds: whenever the data runs out, a length
+//! prefix is impossible or a tag is invalid, the error names the field path
+//! and byte offset where decoding diverged from the IDL layout.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{
+    format_type, hex, Idl, IdlArrayLen, IdlEnumFields, IdlField, IdlGenericArg, IdlType,
+    IdlTypeComplex, IdlTypeDef, IdlTypeDefTy, DISCRIMINATOR_LEN,
+};
+use crate::metrics::{timed, Stage};
+use periscope_core::zero_copy_align;
+pub use periscope_core::Layout;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey::Pubkey;
+
+/// Maximum nesting depth of defined types, guards against recursive layouts
+const MAX_DEPTH: usize = 64;
+
+/// Longest sequence of zero-sized elements decoded, since data length can't bound it
+const MAX_ZERO_SIZED_ITEMS: usize = 1024;
+
+/// A decoded account
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedAccount {
+    /// Account type name
+    pub name: String,
+
+    /// Layout the account was decoded with
+    pub layout: Layout,
+
+    /// Decoded fields
+    pub value: Value,
+
+    /// Bytes consumed by the layout, including the discriminator
+    pub consumed: usize,
+
+    /// Total length of the account data
+    pub data_len: usize,
+
+    /// Byte ranges of each decoded leaf field, in data order
+    pub spans: Vec<FieldSpan>,
+}
+
+/// Byte range occupied by a decoded field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldSpan {
+    /// Field path (e.g. `Pool.fees[2].bps`)
+    pub path: String,
+
+    /// Type label (e.g. `u16`, `Vec<u8>`, `option tag`)
+    pub ty: String,
+
+    /// Start offset (inclusive)
+    pub start: usize,
+
+    /// End offset (exclusive)
+    pub end: usize,
+}
+
+/// Result of decoding instruction data
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedInstruction {
+    /// Instruction name
+    pub name: String,
+
+    /// Arguments as a JSON object keyed by argument name
+    pub args: Value,
+
+    /// Bytes consumed, including the discriminator
+    pub consumed: usize,
+
+    /// Total instruction data length
+    pub data_len: usize,
+}
+
+/// A decoded event, as logged by Anchor's `emit!`
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedEvent {
+    /// Event name
+    pub name: String,
+
+    /// Event fields
+    pub value: Value,
+}
+
+impl DecodedAccount {
+    /// Bytes left over after the layout was fully decoded (usually reserved space)
+    pub fn trailing_bytes(&self) -> usize {
+        self.data_len.saturating_sub(self.consumed)
+    }
+}
+
+/// Decode account data, identifying the account type from its discriminator.
+pub fn decode_account(idl: &Idl, data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    decode_account_with(idl, data, None, None)
+}
+
+/// Decode account data as the given account type, without checking the discriminator.
+pub fn decode_account_as(idl: &Idl, name: &str, data: &[u8]) -> PeriscopeResult<DecodedAccount> {
+    decode_account_with(idl, data, Some(name), None)
+}
+
+/// Decode account data with explicit overrides.
+///
+/// `account_type` skips discriminator matching; `layout` overrides the layout
+/// inferred from the type's `serialization` attribute (Borsh when absent).
+pub fn decode_account_with(
+    idl: &Idl,
+    data: &[u8],
+    account_type: Option<&str>,
+    layout: Option<Layout>,
+) -> PeriscopeResult<DecodedAccount> {
+    timed(Stage::Decode, || {
+        decode_account_untimed(idl, data, account_type, layout)
+    })
+}
+
+fn decode_account_untimed(
+    idl: &Idl,
+    data: &[u8],
+    account_type: Option<&str>,
+    layout: Option<Layout>,
+) -> PeriscopeResult<DecodedAccount> {
+    let name = match account_type {
+        Some(name) => name.to_string(),
+        None => identify_account(idl, data)?,
+    };
+
+    let layout = layout.unwrap_or_else(|| {
+        idl.find_type(&name)
+            .map(|t| Layout::from_serialization(&t.serialization))
+            .unwrap_or(Layout::Borsh)
+    });
+
+    let mut decoder = Decoder::new(idl, data, layout);
+    decoder.take(DISCRIMINATOR_LEN)?;
+    decoder.spans.push(FieldSpan {
+        path: "discriminator".to_string(),
+        ty: format!("[u8; {}]", DISCRIMINATOR_LEN),
+        start: 0,
+        end: DISCRIMINATOR_LEN,
+    });
+    decoder.base = DISCRIMINATOR_LEN;
+    decoder.path.push(name.clone());
+    let value = decoder.decode_defined(&name, &[])?;
+
+    Ok(DecodedAccount {
+        name,
+        layout,
+        value,
+        consumed: decoder.offset,
+        data_len: data.len(),
+        spans: decoder.spans,
+    })
+}
+
+/// Find the account type whose discriminator prefixes `data`
+fn identify_account(idl: &Idl, data: &[u8]) -> PeriscopeResult<String> {
+    if data.len() < DISCRIMINATOR_LEN {
+        return Err(PeriscopeError::DecodeError {
+            path: "discriminator".to_string(),
+            offset: 0,
+            reason: format!(
+                "account data is {} bytes, shorter than the discriminator",
+                data.len()
+            ),
+        });
+    }
+
+    let discriminator = &data[..DISCRIMINATOR_LEN];
+    let account = idl
+        .find_account_by_discriminator(discriminator)
+        .ok_or_else(|| PeriscopeError::UnknownDiscriminator(hex(discriminator)))?;
+
+    Ok(account.name.clone())
+}
+
+/// Decode instruction data, identifying the instruction from its discriminator.
+pub fn decode_instruction(idl: &Idl, data: &[u8]) -> PeriscopeResult<DecodedInstruction> {
+    timed(Stage::Decode, || decode_instruction_untimed(idl, data))
+}
+
+fn decode_instruction_untimed(idl: &Idl, data: &[u8]) -> PeriscopeResult<DecodedInstruction> {
+    let discriminator = data.get(..DISCRIMINATOR_LEN).unwrap_or(data);
+    let instruction = idl
+        .find_instruction_by_discriminator(discriminator)
+        .ok_or_else(|| PeriscopeError::UnknownDiscriminator(hex(discriminator)))?;
+
+    let mut decoder = Decoder::new(idl, data, Layout::Borsh);
+    decoder.take(DISCRIMINATOR_LEN)?;
+    decoder.path.push(instruction.name.clone());
+    let args = decoder.decode_fields(&instruction.args)?;
+
+    Ok(DecodedInstruction {
+        name: instruction.name.clone(),
+        args,
+        consumed: decoder.offset,
+        data_len: data.len(),
+    })
+}
+
+/// Decode event data, identifying the event from its discriminator.
+pub fn decode_event(idl: &Idl, data: &[u8]) -> PeriscopeResult<DecodedEvent> {
+    let discriminator = data.get(..DISCRIMINATOR_LEN).unwrap_or(data);
+    let event = idl
+        .find_event_by_discriminator(discriminator)
+        .ok_or_else(|| PeriscopeError::UnknownDiscriminator(hex(discriminator)))?;
+
+    let mut decoder = Decoder::new(idl, data, Layout::Borsh);
+    decoder.take(DISCRIMINATOR_LEN)?;
+    decoder.path.push(event.name.clone());
+    let value = decoder.decode_defined(&event.name, &[])?;
+
+    Ok(DecodedEvent {
+        name: event.name.clone(),
+        value,
+    })
+}
+
+/// Decode a single Borsh value of `ty` from the start of `data`.
+///
+/// Returns the value and the number of bytes it occupied.
+pub fn decode_type(idl: &Idl, ty: &IdlType, data: &[u8]) -> PeriscopeResult<(Value, usize)> {
+    let mut decoder = Decoder::new(idl, data, Layout::Borsh);
+    let value = decoder.decode(ty)?;
+    Ok((value, decoder.offset))
+}
+
+/// Cursor over account data that tracks the field path being decoded
+struct Decoder<'a> {
+    idl: &'a Idl,
+    data: &'a [u8],
+    layout: Layout,
+    /// Offset alignment is measured from (the start of the enclosing struct)
+    base: usize,
+    offset: usize,
+    path: Vec<String>,
+    depth: usize,
+    spans: Vec<FieldSpan>,
+    /// Non-zero while inside a leaf that is recorded as a single span
+    collapse: usize,
+    /// Whether the struct being decoded is `repr(packed)` (no padding)
+    packed: bool,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(idl: &'a Idl, data: &'a [u8], layout: Layout) -> Self {
+        Self {
+            idl,
+            data,
+            layout,
+            base: 0,
+            offset: 0,
+            path: Vec::new(),
+            depth: 0,
+            spans: Vec::new(),
+            collapse: 0,
+            packed: false,
+        }
+    }
+
+    /// Record the bytes from `start` to the current offset as a span of the current path
+    fn record(&mut self, start: usize, ty: impl Into<String>) {
+        if self.collapse == 0 {
+            self.spans.push(FieldSpan {
+                path: self.path_string(),
+                ty: ty.into(),
+                start,
+                end: self.offset,
+            });
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    fn error_at(&self, offset: usize, reason: impl Into<String>) -> PeriscopeError {
+        PeriscopeError::DecodeError {
+            path: self.path_string(),
+            offset,
+            reason: reason.into(),
+        }
+    }
+
+    /// Render the path as `Pool.fees[2].amount`
+    fn path_string(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.path {
+            if !out.is_empty() && !segment.starts_with('[') {
+                out.push('.');
+            }
+            out.push_str(segment);
+        }
+        if out.is_empty() {
+            out.push_str("<root>");
+        }
+        out
+    }
+
+    fn take(&mut self, len: usize) -> PeriscopeResult<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(self.error_at(
+                self.offset,
+                format!("needs {} bytes but only {} remain", len, self.remaining()),
+            ));
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> PeriscopeResult<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    /// Read a u32 length prefix and check it against the bytes left
+    fn read_len(&mut self, what: &str) -> PeriscopeResult<usize> {
+        let start = self.offset;
+        let len = u32::from_le_bytes(self.read_array()?) as usize;
+        if len > self.remaining() {
+            return Err(self.error_at(
+                start,
+                format!(
+                    "{} length prefix {} exceeds the {} bytes remaining",
+                    what,
+                    len,
+                    self.remaining()
+                ),
+            ));
+        }
+        Ok(len)
+    }
+
+    fn decode(&mut self, ty: &IdlType) -> PeriscopeResult<Value> {
+        if self.layout == Layout::ZeroCopy && !self.packed {
+            let align = self.align_of(ty, 0)?;
+            self.align_to(align)?;
+        }
+
+        let start = self.offset;
+        let leaf = is_leaf(ty);
+        if leaf {
+            self.collapse += 1;
+        }
+
+        let value = match ty {
+            IdlType::Primitive(name) => self.decode_primitive(name)?,
+            IdlType::Complex(complex) => self.decode_complex(complex)?,
+        };
+
+        if leaf {
+            self.collapse -= 1;
+            self.record(start, format_type(ty));
+        }
+        Ok(value)
+    }
+
+    fn decode_primitive(&mut self, name: &str) -> PeriscopeResult<Value> {
+        let start = self.offset;
+        let value = match name {
+            "bool" => match self.read_array::<1>()?[0] {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                b => return Err(self.error_at(start, format!("invalid bool byte {}", b))),
+            },
+            "u8" => json!(u8::from_le_bytes(self.read_array()?)),
+            "i8" => json!(i8::from_le_bytes(self.read_array()?)),
+            "u16" => json!(u16::from_le_bytes(self.read_array()?)),
+            "i16" => json!(i16::from_le_bytes(self.read_array()?)),
+            "u32" => json!(u32::from_le_bytes(self.read_array()?)),
+            "i32" => json!(i32::from_le_bytes(self.read_array()?)),
+            "u64" => json!(u64::from_le_bytes(self.read_array()?)),
+            "i64" => json!(i64::from_le_bytes(self.read_array()?)),
+            "f32" => json!(f32::from_le_bytes(self.read_array()?)),
+            "f64" => json!(f64::from_le_bytes(self.read_array()?)),
+            // 128-bit integers don't fit JSON numbers losslessly
+            "u128" => Value::String(u128::from_le_bytes(self.read_array()?).to_string()),
+            "i128" => Value::String(i128::from_le_bytes(self.read_array()?).to_string()),
+            "u256" | "i256" => {
+                let mut bytes: [u8; 32] = self.read_array()?;
+                bytes.reverse();
+                Value::String(format!("0x{}", hex(&bytes)))
+            }
+            "pubkey" | "publicKey" => {
+                Value::String(Pubkey::new_from_array(self.read_array()?).to_string())
+            }
+            "string" => {
+                let len = self.read_len("string")?;
+                let bytes_start = self.offset;
+                let bytes = self.take(len)?;
+                let s = std::str::from_utf8(bytes)
+                    .map_err(|_| self.error_at(bytes_start, "string is not valid UTF-8"))?;
+                Value::String(s.to_string())
+            }
+            "bytes" => {
+                let len = self.read_len("bytes")?;
+                Value::Array(self.take(len)?.iter().map(|b| json!(b)).collect())
+            }
+            other => {
+                return Err(self.error_at(start, format!("unsupported primitive type '{}'", other)))
+            }
+        };
+        Ok(value)
+    }
+
+    fn decode_complex(&mut self, ty: &IdlTypeComplex) -> PeriscopeResult<Value> {
+        match ty {
+            IdlTypeComplex::Vec(inner) => {
+                let start = self.offset;
+                let len = self.read_len("vec")?;
+                self.record(start, "vec length");
+                self.decode_sequence(inner, len)
+            }
+            IdlTypeComplex::Array(inner, len) => {
+                let len = self.array_len(len)?;
+                self.decode_sequence(inner, len)
+            }
+            IdlTypeComplex::Option(inner) => {
+                let start = self.offset;
+                let tag = self.read_array::<1>()?[0];
+                self.record(start, "option tag");
+                match tag {
+                    0 => Ok(Value::Null),
+                    1 => self.decode(inner),
+                    tag => Err(self.error_at(start, format!("invalid option tag {}", tag))),
+                }
+            }
+            IdlTypeComplex::Defined { name, generics } => self.decode_defined(name, generics),
+            IdlTypeComplex::Generic(name) => Err(self.error_at(
+                self.offset,
+                format!("generic parameter {} is not bound to a type", name),
+            )),
+        }
+    }
+
+    /// Length of an array, which must not be a generic parameter by now
+    fn array_len(&self, len: &IdlArrayLen) -> PeriscopeResult<usize> {
+        len.value().ok_or_else(|| {
+            self.error_at(
+                self.offset,
+                format!("generic length {} is not bound to a value", len),
+            )
+        })
+    }
+
+    /// Skip padding so the next read is aligned (relative to the enclosing struct)
+    fn align_to(&mut self, align: usize) -> PeriscopeResult<()> {
+        let misalignment = (self.offset - self.base) % align;
+        if misalignment != 0 {
+            self.take(align - misalignment)?;
+        }
+        Ok(())
+    }
+
+    /// Natural `repr(C)` alignment of a type in a zero-copy layout
+    fn align_of(&self, ty: &IdlType, depth: usize) -> PeriscopeResult<usize> {
+        let unsupported = || {
+            self.error_at(
+                self.offset,
+                format!(
+                    "{} is not representable in a zero-copy layout",
+                    format_type(ty)
+                ),
+            )
+        };
+
+        if depth >= MAX_DEPTH {
+            return Err(self.error_at(
+                self.offset,
+                format!("type nesting exceeds {} levels", MAX_DEPTH),
+            ));
+        }
+
+        match ty {
+            IdlType::Primitive(name) => zero_copy_align(name).ok_or_else(unsupported),
+            IdlType::Complex(IdlTypeComplex::Array(inner, _)) => self.align_of(inner, depth + 1),
+            IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+                let type_def = self
+                    .idl
+                    .instantiate_type(name, generics)
+                    .map_err(|reason| self.error_at(self.offset, reason))?;
+                self.type_def_align(&type_def, depth)
+            }
+            IdlType::Complex(_) => Err(unsupported()),
+        }
+    }
+
+    /// Natural `repr(C)` alignment of a (non-generic) type definition
+    fn type_def_align(&self, type_def: &IdlTypeDef, depth: usize) -> PeriscopeResult<usize> {
+        let modifier = type_def.repr.as_ref().and_then(|r| r.modifier());
+        match &type_def.ty {
+            IdlTypeDefTy::Struct { .. } if modifier.is_some_and(|m| m.packed) => Ok(1),
+            IdlTypeDefTy::Struct { fields } => {
+                let mut align = modifier.and_then(|m| m.align).unwrap_or(1);
+                for field in fields {
+                    align = align.max(self.align_of(&field.ty, depth + 1)?);
+                }
+                Ok(align)
+            }
+            IdlTypeDefTy::Enum { .. } => Err(self.error_at(
+                self.offset,
+                format!(
+                    "enum {} is not representable in a zero-copy layout",
+                    type_def.name
+                ),
+            )),
+        }
+    }
+
+    fn decode_sequence(&mut self, inner: &IdlType, len: usize) -> PeriscopeResult<Value> {
+        let mut items = Vec::with_capacity(len.min(self.remaining()));
+        for i in 0..len {
+            let start = self.offset;
+            self.path.push(format!("[{}]", i));
+            items.push(self.decode(inner)?);
+            self.path.pop();
+
+            if i == 0 && self.offset == start && len > MAX_ZERO_SIZED_ITEMS {
+                return Err(self.error_at(
+                    start,
+                    format!(
+                        "sequence of {} zero-sized elements exceeds {}",
+                        len, MAX_ZERO_SIZED_ITEMS
+                    ),
+                ));
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn decode_defined(&mut self, name: &str, generics: &[IdlGenericArg]) -> PeriscopeResult<Value> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error_at(
+                self.offset,
+                format!("type nesting exceeds {} levels", MAX_DEPTH),
+            ));
+        }
+
+        let idl = self.idl;
+        // Generic types are monomorphized here, so their fields are concrete
+        let type_def = idl
+            .instantiate_type(name, generics)
+            .map_err(|reason| self.error_at(self.offset, reason))?;
+
+        self.depth += 1;
+        let value = match &type_def.ty {
+            IdlTypeDefTy::Struct { fields } if self.layout == Layout::ZeroCopy => {
+                // Fields are aligned within the struct; `repr(packed)` drops the padding
+                let outer = (self.base, self.packed);
+                self.base = self.offset;
+                self.packed = type_def
+                    .repr
+                    .as_ref()
+                    .and_then(|r| r.modifier())
+                    .is_some_and(|m| m.packed);
+                let value = self.decode_fields(fields)?;
+
+                // Trailing padding rounds the struct size up to its alignment
+                let align = self.type_def_align(&type_def, self.depth)?;
+                self.align_to(align)?;
+                (self.base, self.packed) = outer;
+                value
+            }
+            IdlTypeDefTy::Struct { fields } => self.decode_fields(fields)?,
+            IdlTypeDefTy::Enum { .. } if self.layout == Layout::ZeroCopy => {
+                return Err(self.error_at(
+                    self.offset,
+                    format!("enum {} is not representable in a zero-copy layout", name),
+                ));
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                let start = self.offset;
+                let tag = self.read_array::<1>()?[0] as usize;
+                self.record(start, "enum variant");
+                let variant = variants.get(tag).ok_or_else(|| {
+                    self.error_at(
+                        start,
+                        format!(
+                            "variant index {} out of range for enum {} ({} variants)",
+                            tag,
+                            name,
+                            variants.len()
+                        ),
+                    )
+                })?;
+
+                self.path.push(variant.name.clone());
+                let fields = match &variant.fields {
+                    None => None,
+                    Some(IdlEnumFields::Named(fields)) => Some(self.decode_fields(fields)?),
+                    Some(IdlEnumFields::Tuple(types)) => {
+                        let mut items = Vec::with_capacity(types.len());
+                        for (i, ty) in types.iter().enumerate() {
+                            self.path.push(format!("[{}]", i));
+                            items.push(self.decode(ty)?);
+                            self.path.pop();
+                        }
+                        Some(Value::Array(items))
+                    }
+                };
+                self.path.pop();
+
+                match fields {
+                    None => Value::String(variant.name.clone()),
+                    Some(fields) => {
+                        let mut map = Map::new();
+                        map.insert(variant.name.clone(), fields);
+                        Value::Object(map)
+                    }
+                }
+            }
+        };
+        self.depth -= 1;
+
+        Ok(value)
+    }
+
+    fn decode_fields(&mut self, fields: &[IdlField]) -> PeriscopeResult<Value> {
+        let mut map = Map::new();
+        for field in fields {
+            self.path.push(field.name.clone());
+            let value = self.decode(&field.ty)?;
+            self.path.pop();
+            map.insert(field.name.clone(), value);
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+/// Primitives, and sequences of primitives, are annotated as a single span
+fn is_leaf(ty: &IdlType) -> bool {
+    match ty {
+        IdlType::Primitive(_) => true,
+        IdlType::Complex(IdlTypeComplex::Vec(inner))
+        | IdlType::Complex(IdlTypeComplex::Array(inner, _)) => {
+            matches!(**inner, IdlType::Primitive(_))
+        }
+        IdlType::Complex(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn test_idl() -> Idl {
+        serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Pool", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+            "types": [
+                {
+                    "name": "Pool",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "amount", "type": "u64" },
+                            { "name": "label", "type": "string" },
+                            { "name": "fees", "type": { "vec": { "defined": { "name": "Fee" } } } }
+                        ]
+                    }
+                },
+                {
+                    "name": "Fee",
+                    "type": { "kind": "struct", "fields": [{ "name": "bps", "type": "u16" }] }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn pool_data(fees: &[u16]) -> Vec<u8> {
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(b"hi");
+        data.extend_from_slice(&(fees.len() as u32).to_le_bytes());
+        for fee in fees {
+            data.extend_from_slice(&fee.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let idl = test_idl();
+        let mut data = pool_data(&[30, 5]);
+        data.extend_from_slice(&[0; 3]);
+
+        let decoded = decode_account(&idl, &data).unwrap();
+        assert_eq!(decoded.name, "Pool");
+        assert_eq!(decoded.value["amount"], json!(42));
+        assert_eq!(decoded.value["label"], json!("hi"));
+        assert_eq!(decoded.value["fees"][1]["bps"], json!(5));
+        assert_eq!(decoded.trailing_bytes(), 3);
+
+        let paths: Vec<(&str, usize, usize)> = decoded
+            .spans
+            .iter()
+            .map(|s| (s.path.as_str(), s.start, s.end))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("discriminator", 0, 8),
+                ("Pool.amount", 8, 16),
+                ("Pool.label", 16, 22),
+                ("Pool.fees", 22, 26),
+                ("Pool.fees[0].bps", 26, 28),
+                ("Pool.fees[1].bps", 28, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_zero_copy_alignment() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Oracle", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }],
+            "types": [{
+                "name": "Oracle",
+                "serialization": "bytemuck",
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "flag", "type": "u8" },
+                        { "name": "price", "type": "u64" },
+                        { "name": "expo", "type": "i32" }
+                    ]
+                }
+            }]
+        }))
+        .unwrap();
+
+        let mut data = vec![0u8; 8];
+        data.push(1);
+        data.extend_from_slice(&[0; 7]);
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&(-6i32).to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+
+        let decoded = decode_account(&idl, &data).unwrap();
+        assert_eq!(decoded.layout, Layout::ZeroCopy);
+        assert_eq!(decoded.value["flag"], json!(1));
+        assert_eq!(decoded.value["price"], json!(1_000));
+        assert_eq!(decoded.value["expo"], json!(-6));
+        assert_eq!(decoded.consumed, data.len());
+    }
+
+    #[test]
+    fn test_decode_zero_copy_packed() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Oracle", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }],
+            "types": [{
+                "name": "Oracle",
+                "serialization": "bytemuckunsafe",
+                "repr": { "kind": "c", "packed": true },
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "flag", "type": "u8" },
+                        { "name": "price", "type": "u64" }
+                    ]
+                }
+            }]
+        }))
+        .unwrap();
+        assert_eq!(
+            idl.types[0].repr.as_ref().unwrap().to_string(),
+            "repr(C, packed)"
+        );
+
+        let mut data = vec![0u8; 8];
+        data.push(1);
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+
+        let decoded = decode_account(&idl, &data).unwrap();
+        assert_eq!(decoded.value["price"], json!(1_000));
+        assert_eq!(decoded.consumed, data.len());
+    }
+
+    #[test]
+    fn test_decode_generic_instance() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Vault", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }],
+            "types": [
+                {
+                    "name": "Vault",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "limit", "type": { "defined": {
+                                "name": "PodOption",
+                                "generics": [{ "kind": "type", "type": "u64" }]
+                            } } },
+                            { "name": "owners", "type": { "defined": {
+                                "name": "Fixed",
+                                "generics": [
+                                    { "kind": "type", "type": "u16" },
+                                    { "kind": "const", "value": "2" }
+                                ]
+                            } } }
+                        ]
+                    }
+                },
+                {
+                    "name": "PodOption",
+                    "generics": [{ "kind": "type", "name": "T" }],
+                    "type": { "kind": "struct", "fields": [
+                        { "name": "is_some", "type": "bool" },
+                        { "name": "value", "type": { "generic": "T" } }
+                    ] }
+                },
+                {
+                    "name": "Fixed",
+                    "generics": [
+                        { "kind": "type", "name": "T" },
+                        { "kind": "const", "name": "N", "type": "usize" }
+                    ],
+                    "type": { "kind": "struct", "fields": [
+                        { "name": "items", "type": { "array": [{ "generic": "T" }, { "generic": "N" }] } }
+                    ] }
+                }
+            ]
+        }))
+        .unwrap();
+
+        let mut data = vec![0u8; 8];
+        data.push(1);
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&7u16.to_le_bytes());
+        data.extend_from_slice(&9u16.to_le_bytes());
+
+        let decoded = decode_account(&idl, &data).unwrap();
+        assert_eq!(decoded.value["limit"]["value"], json!(500));
+        assert_eq!(decoded.value["owners"]["items"], json!([7, 9]));
+        assert_eq!(decoded.consumed, data.len());
+    }
+
+    #[test]
+    fn test_decode_reports_field_and_offset() {
+        let idl = test_idl();
+        let mut data = pool_data(&[30, 5]);
+        data.truncate(data.len() - 1);
+
+        match decode_account(&idl, &data).unwrap_err() {
+            PeriscopeError::DecodeError { path, offset, .. } => {
+                assert_eq!(path, "Pool.fees[1].bps");
+                assert_eq!(offset, 28);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_oversized_length_prefix() {
+        let idl = test_idl();
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        match decode_account(&idl, &data).unwrap_err() {
+            PeriscopeError::DecodeError {
+                path,
+                offset,
+                reason,
+            } => {
+                assert_eq!(path, "Pool.label");
+                assert_eq!(offset, 16);
+                assert!(reason.contains("length prefix"));
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_decode_arbitrary_bytes_never_panics(
+            mut data in prop::collection::vec(any::<u8>(), 0..64),
+            known_discriminator in any::<bool>(),
+        ) {
+            if known_discriminator && data.len() >= 8 {
+                data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+            }
+            let idl = test_idl();
+            let _ = decode_account(&idl, &data);
+            let _ = decode_instruction(&idl, &data);
+        }
+    }
+
+    #[test]
+    fn test_decode_zero_sized_array_is_bounded() {
+        // Elements that read no bytes can't be bounded by the data length
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "types": [{ "name": "Empty", "type": { "kind": "struct", "fields": [] } }]
+        }))
+        .unwrap();
+        let ty = IdlType::Complex(IdlTypeComplex::Array(
+            Box::new(IdlType::Complex(IdlTypeComplex::Defined {
+                name: "Empty".to_string(),
+                generics: Vec::new(),
+            })),
+            IdlArrayLen::Value(usize::MAX),
+        ));
+        assert!(decode_type(&idl, &ty, &[]).is_err());
+    }
+}
@@ -1,29 +1,82 @@
 //! Pretty-print helpers for CLI output
 
-use crate::idl::{Idl, IdlAccount, IdlAccountItem, IdlInstruction, IdlType, IdlTypeComplex};
+use crate::account_diff::FieldChange;
+use crate::anchor_version::AnchorReport;
+use crate::annotations::{annotation, annotations, Annotation, AnnotationKind};
+use crate::build_ix::BuiltInstruction;
+use crate::builtin_programs::known_program_name;
+use crate::bundle::Bundle;
+use crate::cache::CacheEntry;
+use crate::compat::{Change, InstructionMapping};
+use crate::decode::{DecodedAccount, DecodedInstruction, FieldSpan};
+use crate::doctor::{Check, CheckStatus};
+use crate::explain::ExplainedMessage;
+use crate::groups::NameGroup;
+use crate::i18n::{tr, trf, Msg};
+pub use crate::idl::format_type;
+use crate::idl::{
+    hex, Idl, IdlAccount, IdlAccountItem, IdlEnumFields, IdlError, IdlInstruction,
+    IdlSerialization, IdlTypeDef, IdlTypeDefTy, Rename,
+};
+#[cfg(feature = "send")]
+use crate::links::link_tx;
+use crate::links::{link_address, link_if_address, Explorer};
+use crate::lint::Finding;
+use crate::matrix::RoleMatrix;
+use crate::metrics::Metrics;
+use crate::mock::Mock;
+use crate::overlap::Overlap;
+use crate::page::Page;
+use crate::pdas::{seed_label, PdaRecipe};
+use crate::preflight::AccountCheck;
+use crate::quarantine::{QuarantineEntry, RetryOutcome};
+use crate::recent::RecentProgram;
+use crate::scan::ScanStats;
+use crate::search::{ProgramHits, SearchHit};
+#[cfg(feature = "send")]
+use crate::send::SendOutcome;
+use crate::simulate::{Simulation, SweepPoint};
+use crate::snapshot::{Fixture, FixtureKind};
+use crate::theme::{paint, Element};
+use crate::uses::TypeUse;
+use crate::whois::AddressInfo;
+use crate::xcheck::{ClusterIdl, KindChanges, Mismatch};
 use colored::Colorize;
+use serde_json::Value;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::Duration;
 
 /// Print a main header (program name, command title)
 pub fn print_header(title: &str) {
     println!();
-    println!("{}", title.bold().cyan());
-    println!("{}", "─".repeat(50).dimmed());
+    println!("{}", paint(title, Element::Header));
+    println!("{}", paint(&"─".repeat(50), Element::Rule));
 }
 
 /// Print a sub-header (section within output)
 pub fn print_subheader(title: &str) {
     println!();
-    println!("{}", title.bold().white());
+    println!("{}", paint(title, Element::Subheader));
 }
 
 /// Print a key-value pair
 pub fn print_field(key: &str, value: &str) {
-    println!("  {}: {}", key.dimmed(), value);
+    println!(
+        "  {}: {}",
+        paint(key, Element::Label),
+        link_if_address(value)
+    );
 }
 
 /// Print a numbered list item
 pub fn print_numbered_item(num: usize, text: &str) {
-    println!("  {}. {}", format!("{:>2}", num).dimmed(), text);
+    println!(
+        "  {}. {}",
+        paint(&format!("{:>2}", num), Element::Label),
+        text
+    );
 }
 
 /// Print a simple list item with indent
@@ -31,79 +84,217 @@ pub fn print_list_item(text: &str) {
     println!("    {}", text);
 }
 
-/// Display full IDL overview
-pub fn display_idl_overview(idl: &Idl) {
-    print_header(&format!("Program: {}", idl.metadata.name));
+/// Display full IDL overview, with where and when an on-chain IDL was read
+pub fn display_idl_overview(idl: &Idl, origin: Option<&CacheEntry>, now: u64) {
+    print_header(&trf(Msg::ProgramHeader, &[&idl.metadata.name]));
 
-    print_field("Version", &idl.metadata.version);
-    print_field("Address", &idl.address);
-    print_field("Spec", &idl.metadata.spec);
+    print_field(tr(Msg::Version), &idl.metadata.version);
+    print_field(tr(Msg::Address), &idl.address);
+    if let Some(origin) = origin {
+        print_field(tr(Msg::Cluster), &origin.cluster);
+        let age = format_age(now.saturating_sub(origin.fetched_at));
+        match origin.slot {
+            Some(slot) => print_field(tr(Msg::Slot), &trf(Msg::ReadAt, &[&slot, &age])),
+            None => print_field(tr(Msg::Fetched), &age),
+        }
+    }
+    print_field(tr(Msg::Spec), &idl.metadata.spec);
+    print_field(tr(Msg::CanonicalHash), &idl.canonical_hash());
 
     if let Some(desc) = &idl.metadata.description {
-        print_field("Description", desc);
+        print_field(tr(Msg::Description), desc);
+    }
+    if !annotations().is_empty() {
+        print_field(tr(Msg::Annotated), &annotations().len().to_string());
     }
 
-    print_subheader("Summary");
+    print_subheader(tr(Msg::Summary));
     println!(
-        "  {} Instructions, {} Accounts, {} Types, {} Events, {} Errors",
-        format!("{}", idl.instructions.len()).green(),
-        format!("{}", idl.accounts.len()).yellow(),
-        format!("{}", idl.types.len()).blue(),
-        format!("{}", idl.events.len()).magenta(),
-        format!("{}", idl.errors.len()).red(),
+        "  {}",
+        trf(
+            Msg::SummaryCounts,
+            &[
+                &paint(&idl.instructions.len().to_string(), Element::Name),
+                &paint(&idl.accounts.len().to_string(), Element::Field),
+                &paint(&idl.types.len().to_string(), Element::Type),
+                &paint(&idl.events.len().to_string(), Element::Event),
+                &paint(&idl.errors.len().to_string(), Element::Error),
+            ]
+        )
     );
+
+    if !idl.warnings.is_empty() {
+        print_subheader(&trf(Msg::SkippedHeader, &[&idl.warnings.len()]));
+        for warning in &idl.warnings {
+            println!("  {}", paint(&warning.to_string(), Element::Warning));
+        }
+    }
+    println!();
+}
+
+/// Display how an IDL changed since the cached copy it replaced, one line
+/// per item kind (follows [`display_idl_overview`])
+pub fn display_changes_since(previous: &CacheEntry, changes: &[KindChanges], now: u64) {
+    let age = format_age(now.saturating_sub(previous.fetched_at));
+    println!(
+        "{}",
+        paint(&trf(Msg::ChangedSince, &[&age]), Element::Subheader)
+    );
+    for change in changes {
+        let counts: Vec<String> = [
+            (change.added, Msg::ChangesAdded),
+            (change.removed, Msg::ChangesRemoved),
+            (change.changed, Msg::ChangesChanged),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, msg)| trf(*msg, &[count]))
+        .collect();
+        println!(
+            "  {} {}",
+            paint(change.kind, Element::Label),
+            paint(&counts.join(", "), Element::Warning)
+        );
+    }
     println!();
 }
 
 /// Display list of all instructions
-pub fn display_instructions_list(idl: &Idl) {
-    print_header(&format!(
-        "Instructions for {} ({} total)",
-        idl.metadata.name,
-        idl.instructions.len()
-    ));
+pub fn display_instructions_list(program: &str, instructions: &[&str], page: Option<&Page>) {
+    let total = page.map_or(instructions.len(), |p| p.total);
+    print_header(&trf(Msg::InstructionsHeader, &[&program, &total]));
 
-    if idl.instructions.is_empty() {
-        println!("  {}", "(none)".dimmed());
+    if instructions.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
     } else {
-        for (i, ix) in idl.instructions.iter().enumerate() {
-            print_numbered_item(i + 1, &ix.name.green().to_string());
+        let first = page.map_or(0, |p| p.range().start);
+        for (i, name) in instructions.iter().enumerate() {
+            print_numbered_item(first + i + 1, &annotated_name(name));
+        }
+    }
+    display_page_footer(page);
+    println!();
+}
+
+/// An instruction name followed by its annotation, if any
+fn annotated_name(name: &str) -> String {
+    let suffix = annotation(AnnotationKind::Instruction, name).map(annotation_suffix);
+    format!(
+        "{}{}",
+        paint(name, Element::Name),
+        suffix.unwrap_or_default()
+    )
+}
+
+/// `  [deprecated]  label — note`, to follow an annotated item's name
+fn annotation_suffix(annotation: &Annotation) -> String {
+    let mut suffix = String::new();
+    if annotation.deprecated {
+        let tag = format!("[{}]", tr(Msg::Deprecated));
+        suffix.push_str(&format!("  {}", paint(&tag, Element::Warning)));
+    }
+    if let Some(summary) = annotation.summary() {
+        suffix.push_str(&format!("  {}", paint(&summary, Element::Muted)));
+    }
+    suffix
+}
+
+/// The annotation of an item on a line of its own, under its header
+fn display_annotation(annotation: Option<&Annotation>) {
+    if let Some(annotation) = annotation {
+        println!("{}", annotation_suffix(annotation));
+    }
+}
+
+/// Which page of how many was shown, and how to get the next one
+fn display_page_footer(page: Option<&Page>) {
+    let Some(page) = page else {
+        return;
+    };
+    let range = page.range();
+    let mut footer = trf(
+        Msg::PageFooter,
+        &[
+            &page.page,
+            &page.pages,
+            &(range.start + 1).min(range.end),
+            &range.end,
+            &page.total,
+        ],
+    );
+    if page.page < page.pages {
+        footer = format!("{}; {}", footer, trf(Msg::PageNext, &[&(page.page + 1)]));
+    }
+    println!();
+    println!("  {}", paint(&footer, Element::Muted));
+}
+
+/// Display instructions clustered by name prefix, numbered across groups
+pub fn display_instruction_groups(program: &str, groups: &[NameGroup], page: Option<&Page>) {
+    let total = page.map_or_else(|| groups.iter().map(|g| g.names.len()).sum(), |p| p.total);
+    print_header(&trf(Msg::InstructionsHeader, &[&program, &total]));
+    if groups.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+
+    let mut number = page.map_or(0, |p| p.range().start);
+    for group in groups {
+        print_subheader(&format!("{} ({})", group_label(group), group.names.len()));
+        for name in &group.names {
+            number += 1;
+            print_numbered_item(number, &annotated_name(name));
         }
     }
+    display_page_footer(page);
     println!();
 }
 
+/// `admin_*` (`admin*` for camelCase names), or the ungrouped bucket
+pub fn group_label(group: &NameGroup) -> String {
+    match group.prefix {
+        Some(prefix) if group.names[0][prefix.len()..].starts_with('_') => {
+            format!("{}_*", prefix)
+        }
+        Some(prefix) => format!("{}*", prefix),
+        None => tr(Msg::Ungrouped).to_string(),
+    }
+}
+
 /// Display detailed info for a single instruction
-pub fn display_instruction_detail(instruction: &IdlInstruction) {
-    print_header(&format!("Instruction: {}", instruction.name.green()));
+pub fn display_instruction_detail(instruction: &IdlInstruction, raw_seeds: bool) {
+    print_header(&trf(
+        Msg::InstructionHeader,
+        &[&paint(&instruction.name, Element::Name)],
+    ));
+    display_annotation(annotation(AnnotationKind::Instruction, &instruction.name));
 
     if !instruction.discriminator.is_empty() {
         print_field(
-            "Discriminator",
+            tr(Msg::Discriminator),
             &format_discriminator(&instruction.discriminator),
         );
     }
 
-    print_subheader(&format!(
-        "Accounts ({})",
-        count_accounts(&instruction.accounts)
+    print_subheader(&trf(
+        Msg::AccountsCount,
+        &[&count_accounts(&instruction.accounts)],
     ));
     if instruction.accounts.is_empty() {
-        println!("  {}", "(none)".dimmed());
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
     } else {
-        display_account_items(&instruction.accounts, 1, 0);
+        display_account_items(&instruction.accounts, 1, 0, raw_seeds);
     }
 
-    print_subheader(&format!("Arguments ({})", instruction.args.len()));
+    print_subheader(&trf(Msg::ArgumentsCount, &[&instruction.args.len()]));
     if instruction.args.is_empty() {
-        println!("  {}", "(none)".dimmed());
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
     } else {
         for (i, arg) in instruction.args.iter().enumerate() {
             println!(
                 "  {}. {} : {}",
-                format!("{:>2}", i + 1).dimmed(),
-                arg.name.yellow(),
-                format_type(&arg.ty).blue()
+                paint(&format!("{:>2}", i + 1), Element::Label),
+                paint(&arg.name, Element::Field),
+                paint(&format_type(&arg.ty), Element::Type)
             );
         }
     }
@@ -111,7 +302,12 @@ pub fn display_instruction_detail(instruction: &IdlInstruction) {
 }
 
 /// Display account items (handles both Single and Group)
-fn display_account_items(items: &[IdlAccountItem], start_num: usize, indent: usize) -> usize {
+fn display_account_items(
+    items: &[IdlAccountItem],
+    start_num: usize,
+    indent: usize,
+    raw_seeds: bool,
+) -> usize {
     let mut num = start_num;
     let indent_str = "  ".repeat(indent);
 
@@ -119,13 +315,13 @@ fn display_account_items(items: &[IdlAccountItem], start_num: usize, indent: usi
         match item {
             IdlAccountItem::Single(account) => {
                 let constraints = format_account_constraints(account);
-                let extra = format_account_extra(account);
+                let extra = format_account_extra(account, raw_seeds);
 
                 println!(
                     "{}  {}. {} {}{}",
                     indent_str,
-                    format!("{:>2}", num).dimmed(),
-                    account.name.yellow(),
+                    paint(&format!("{:>2}", num), Element::Label),
+                    paint(&account.name, Element::Field),
                     constraints,
                     extra
                 );
@@ -135,10 +331,10 @@ fn display_account_items(items: &[IdlAccountItem], start_num: usize, indent: usi
                 println!(
                     "{}  {} {}",
                     indent_str,
-                    "▸".dimmed(),
-                    group.name.white().bold()
+                    paint("▸", Element::Muted),
+                    paint(&group.name, Element::Subheader)
                 );
-                num = display_account_items(&group.accounts, num, indent + 1);
+                num = display_account_items(&group.accounts, num, indent + 1, raw_seeds);
             }
         }
     }
@@ -162,13 +358,13 @@ fn format_account_constraints(account: &IdlAccount) -> String {
     let mut constraints = Vec::new();
 
     if account.signer {
-        constraints.push("signer".green().to_string());
+        constraints.push(paint("signer", Element::Signer).to_string());
     }
     if account.writable {
-        constraints.push("writable".magenta().to_string());
+        constraints.push(paint("writable", Element::Writable).to_string());
     }
     if account.optional {
-        constraints.push("optional".dimmed().to_string());
+        constraints.push(paint("optional", Element::Optional).to_string());
     }
 
     if constraints.is_empty() {
@@ -178,31 +374,38 @@ fn format_account_constraints(account: &IdlAccount) -> String {
     }
 }
 
-/// Format extra account info (address if present)
-fn format_account_extra(account: &IdlAccount) -> String {
+/// Format extra account info (address or PDA seeds if present)
+fn format_account_extra(account: &IdlAccount, raw_seeds: bool) -> String {
     if let Some(addr) = &account.address {
-        format!(" {}", format!("({})", addr).dimmed())
+        format!(" {}", paint(&format!("({})", addr), Element::Muted))
+    } else if let Some(pda) = &account.pda {
+        let seeds: Vec<String> = pda
+            .seeds
+            .iter()
+            .map(|seed| seed_label(seed, raw_seeds))
+            .collect();
+        format!(
+            " {}",
+            paint(&format!("(pda: {})", seeds.join(", ")), Element::Muted)
+        )
     } else {
         String::new()
     }
 }
 
 /// Display list of all errors
-pub fn display_errors_list(idl: &Idl) {
-    print_header(&format!(
-        "Errors for {} ({} total)",
-        idl.metadata.name,
-        idl.errors.len()
-    ));
+pub fn display_errors_list(program: &str, errors: &[IdlError], page: Option<&Page>) {
+    let total = page.map_or(errors.len(), |p| p.total);
+    print_header(&trf(Msg::ErrorsHeader, &[&program, &total]));
 
-    if idl.errors.is_empty() {
-        println!("  {}", "(none)".dimmed());
+    if errors.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
     } else {
         println!(
             "  {}  {}  {}",
-            format!("{:<6}", "Code").dimmed(),
-            format!("{:<24}", "Name").dimmed(),
-            "Message".dimmed()
+            paint(&format!("{:<6}", tr(Msg::Code)), Element::Label),
+            paint(&format!("{:<24}", tr(Msg::Name)), Element::Label),
+            paint(tr(Msg::Message), Element::Label)
         );
         println!(
             "  {}  {}  {}",
@@ -211,41 +414,1310 @@ pub fn display_errors_list(idl: &Idl) {
             "─".repeat(30)
         );
 
-        for error in &idl.errors {
+        for error in errors {
             let msg = error.msg.as_deref().unwrap_or("-");
+            let suffix = annotation(AnnotationKind::Error, &error.name).map(annotation_suffix);
+            println!(
+                "  {}  {}  {}{}",
+                paint(&format!("{:<6}", error.code), Element::Error),
+                paint(&format!("{:<24}", error.name), Element::Field),
+                paint(msg, Element::Muted),
+                suffix.unwrap_or_default()
+            );
+        }
+    }
+    display_page_footer(page);
+    println!();
+}
+
+/// Display a numbered list of names under a header
+pub fn display_name_list(title: &str, names: &[&str]) {
+    print_header(title);
+
+    if names.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    } else {
+        for (i, name) in names.iter().enumerate() {
+            print_numbered_item(i + 1, &paint(name, Element::Name).to_string());
+        }
+    }
+    println!();
+}
+
+/// Display a type definition: struct fields or enum variants
+pub fn display_type_detail(ty: &IdlTypeDef) {
+    let name = if ty.generics.is_empty() {
+        ty.name.clone()
+    } else {
+        let params: Vec<String> = ty.generics.iter().map(ToString::to_string).collect();
+        format!("{}<{}>", ty.name, params.join(", "))
+    };
+    print_header(&trf(Msg::TypeHeader, &[&paint(&name, Element::Name)]));
+    display_annotation(
+        annotation(AnnotationKind::Type, &ty.name)
+            .or_else(|| annotation(AnnotationKind::Account, &ty.name))
+            .or_else(|| annotation(AnnotationKind::Event, &ty.name)),
+    );
+
+    match &ty.serialization {
+        IdlSerialization::Borsh => {}
+        IdlSerialization::Custom(name) => {
+            print_field(tr(Msg::Layout), &format!("custom ({})", name))
+        }
+        other => print_field(
+            tr(Msg::Layout),
+            &crate::decode::Layout::from_serialization(other).to_string(),
+        ),
+    }
+    if let Some(repr) = &ty.repr {
+        print_field(tr(Msg::Repr), &repr.to_string());
+    }
+
+    match &ty.ty {
+        IdlTypeDefTy::Struct { fields } => {
+            print_subheader(&trf(Msg::FieldsCount, &[&fields.len()]));
+            if fields.is_empty() {
+                println!("  {}", paint(tr(Msg::None), Element::Muted));
+            }
+            for (i, field) in fields.iter().enumerate() {
+                println!(
+                    "  {}. {} : {}",
+                    paint(&format!("{:>2}", i + 1), Element::Label),
+                    paint(&field.name, Element::Field),
+                    paint(&format_type(&field.ty), Element::Type)
+                );
+            }
+        }
+        IdlTypeDefTy::Enum { variants } => {
+            print_subheader(&trf(Msg::VariantsCount, &[&variants.len()]));
+            for (i, variant) in variants.iter().enumerate() {
+                let fields = match &variant.fields {
+                    None => String::new(),
+                    Some(IdlEnumFields::Tuple(types)) => {
+                        let types: Vec<String> = types
+                            .iter()
+                            .map(|t| paint(&format_type(t), Element::Type).to_string())
+                            .collect();
+                        format!("({})", types.join(", "))
+                    }
+                    Some(IdlEnumFields::Named(fields)) => {
+                        let fields: Vec<String> = fields
+                            .iter()
+                            .map(|f| {
+                                format!(
+                                    "{}: {}",
+                                    paint(&f.name, Element::Field),
+                                    paint(&format_type(&f.ty), Element::Type)
+                                )
+                            })
+                            .collect();
+                        format!(" {{ {} }}", fields.join(", "))
+                    }
+                };
+                println!(
+                    "  {}. {}{}",
+                    paint(&format!("{:>2}", i), Element::Label),
+                    paint(&variant.name, Element::Name),
+                    fields
+                );
+            }
+        }
+    }
+    println!();
+}
+
+/// Display search results, one line per hit
+pub fn display_search_results(query: &str, hits: &[SearchHit]) {
+    print_header(&trf(Msg::SearchHeader, &[&query, &hits.len()]));
+
+    if hits.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for hit in hits {
+        print_search_hit("  ", hit);
+    }
+    println!();
+}
+
+/// Display `grep` results: each cached program with matches, then its hits
+pub fn display_grep_results(pattern: &str, searched: usize, programs: &[ProgramHits]) {
+    let total: usize = programs.iter().map(|p| p.hits.len()).sum();
+    print_header(&trf(
+        Msg::GrepHeader,
+        &[&pattern, &total, &programs.len(), &searched],
+    ));
+
+    if programs.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for program in programs {
+        println!(
+            "  {} {} {}",
+            paint(&program.name, Element::Name),
+            paint(&program.program_id, Element::Field),
+            paint(&format!("({})", program.cluster), Element::Muted)
+        );
+        for hit in &program.hits {
+            print_search_hit("    ", hit);
+        }
+    }
+    println!();
+}
+
+fn print_search_hit(indent: &str, hit: &SearchHit) {
+    let detail = if hit.detail.is_empty() {
+        String::new()
+    } else {
+        format!("  {}", paint(&hit.detail, Element::Muted))
+    };
+    println!(
+        "{}{}  {}{}",
+        indent,
+        paint(&format!("{:<11}", hit.kind), Element::Label),
+        paint(&hit.path, Element::Field),
+        detail
+    );
+}
+
+/// Display decoded instruction arguments
+pub fn display_decoded_instruction(decoded: &DecodedInstruction) {
+    print_header(&trf(
+        Msg::InstructionDataHeader,
+        &[&paint(&decoded.name, Element::Name)],
+    ));
+    display_annotation(annotation(AnnotationKind::Instruction, &decoded.name));
+
+    print_field(tr(Msg::Size), &trf(Msg::ByteCount, &[&decoded.data_len]));
+
+    print_subheader(&trf(
+        Msg::ArgumentsCount,
+        &[&decoded.args.as_object().map_or(0, |m| m.len())],
+    ));
+    match &decoded.args {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                display_value_entry(key, value, 1);
+            }
+        }
+        _ => println!("  {}", paint(tr(Msg::None), Element::Muted)),
+    }
+
+    let trailing = decoded.data_len.saturating_sub(decoded.consumed);
+    if trailing > 0 {
+        println!();
+        println!(
+            "  {} {}",
+            paint(tr(Msg::Note), Element::Warning),
+            trf(Msg::TrailingBytes, &[&trailing, &decoded.consumed])
+        );
+    }
+    println!();
+}
+
+/// Display an instruction built by `build-ix`
+pub fn display_built_instruction(built: &BuiltInstruction) {
+    print_header(&trf(
+        Msg::BuiltInstructionHeader,
+        &[&paint(&built.instruction, Element::Name)],
+    ));
+    print_field(tr(Msg::ProgramId), &built.program_id);
+
+    print_subheader(&trf(Msg::AccountsCount, &[&built.accounts.len()]));
+    for (i, account) in built.accounts.iter().enumerate() {
+        let mut flags = Vec::new();
+        if account.signer {
+            flags.push(paint("signer", Element::Signer).to_string());
+        }
+        if account.writable {
+            flags.push(paint("writable", Element::Writable).to_string());
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(", "))
+        };
+        print_numbered_item(
+            i + 1,
+            &format!(
+                "{} {}{}",
+                paint(&account.name, Element::Field),
+                link_address(&account.pubkey, &account.pubkey),
+                flags
+            ),
+        );
+    }
+
+    print_subheader(tr(Msg::InstructionData));
+    println!("  {}", built.data);
+    println!();
+}
+
+/// Display the messages of a wallet payload, instruction by instruction
+pub fn display_explained(messages: &[ExplainedMessage]) {
+    for (i, message) in messages.iter().enumerate() {
+        print_header(&trf(
+            Msg::ExplainHeader,
+            &[&(i + 1), &messages.len(), &message.version],
+        ));
+        print_field(
+            tr(Msg::FeePayer),
+            &link_address(&message.fee_payer, &message.fee_payer),
+        );
+        print_field(tr(Msg::Signers), &message.signers.join(", "));
+        print_field(tr(Msg::RecentBlockhash), &message.recent_blockhash);
+
+        print_subheader(&trf(
+            Msg::ExplainInstructions,
+            &[&message.instructions.len()],
+        ));
+        for (n, ix) in message.instructions.iter().enumerate() {
+            let program = ix.program.as_deref().unwrap_or(&ix.program_id);
+            let title = match &ix.instruction {
+                Some(name) => format!("{} {}", program, paint(name, Element::Name)),
+                None => program.to_string(),
+            };
+            println!();
+            print_numbered_item(n + 1, &title);
+            if let Some(Value::Object(args)) = &ix.args {
+                for (key, value) in args {
+                    display_value_entry(key, value, 3);
+                }
+            }
+            for account in &ix.accounts {
+                let mut flags = Vec::new();
+                if account.signer {
+                    flags.push(paint("signer", Element::Signer).to_string());
+                }
+                if account.writable {
+                    flags.push(paint("writable", Element::Writable).to_string());
+                }
+                let flags = if flags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", flags.join(", "))
+                };
+                let name = account
+                    .name
+                    .as_deref()
+                    .map(|name| format!("{} ", paint(name, Element::Field)))
+                    .unwrap_or_default();
+                println!(
+                    "      {}{}{}",
+                    name,
+                    link_if_address(&account.address),
+                    flags
+                );
+            }
+            if let Some(error) = &ix.error {
+                println!(
+                    "      {} {}",
+                    paint(tr(Msg::Note), Element::Warning),
+                    trf(Msg::ExplainUndecoded, &[error])
+                );
+                if !ix.data.is_empty() {
+                    println!("      {}", paint(&ix.data, Element::Muted));
+                }
+            }
+        }
+        println!();
+    }
+}
+
+/// Display a confirmed `send-ix` transaction with its events and logs
+#[cfg(feature = "send")]
+pub fn display_send_outcome(outcome: &SendOutcome) {
+    print_header(tr(Msg::SentHeader));
+    print_field(tr(Msg::Signature), &link_tx(&outcome.signature));
+
+    print_subheader(&trf(Msg::EventsCount, &[&outcome.events.len()]));
+    if outcome.events.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for event in &outcome.events {
+        display_value_entry(&event.name, &event.value, 1);
+    }
+
+    print_subheader(&trf(Msg::ProgramLogs, &[&outcome.logs.len()]));
+    for line in &outcome.logs {
+        println!("  {}", paint(line, Element::Muted));
+    }
+    println!();
+}
+
+/// Display the fields that changed between two states of an account
+pub fn display_account_diff(
+    address: &str,
+    type_name: &str,
+    before: &str,
+    after: &str,
+    changes: &[FieldChange],
+) {
+    print_header(&trf(
+        Msg::AccountHeader,
+        &[&paint(type_name, Element::Name)],
+    ));
+    print_field(tr(Msg::Address), address);
+    print_field(tr(Msg::Before), before);
+    print_field(tr(Msg::After), after);
+
+    print_subheader(&trf(Msg::ChangedFields, &[&changes.len()]));
+    if changes.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for change in changes {
+        println!(
+            "  {}: {} -> {}",
+            paint(&change.path, Element::Field),
+            paint(&format_value_inline(&change.before), Element::Error),
+            paint(&format_value_inline(&change.after), Element::Success)
+        );
+    }
+    println!();
+}
+
+/// Display the IDL versions per cluster and the items that differ
+pub fn display_xcheck(clusters: &[ClusterIdl], mismatches: &[Mismatch]) {
+    print_header(tr(Msg::XcheckHeader));
+    for cluster in clusters {
+        let status = match (&cluster.version, &cluster.error) {
+            (Some(version), _) => paint(version, Element::Name).to_string(),
+            (None, Some(error)) => paint(error, Element::Error).to_string(),
+            (None, None) => String::new(),
+        };
+        print_field(&cluster.cluster, &status);
+    }
+
+    print_subheader(&trf(Msg::XcheckMismatches, &[&mismatches.len()]));
+    if mismatches.is_empty() {
+        println!("  {}", paint(tr(Msg::XcheckConsistent), Element::Success));
+    }
+    for mismatch in mismatches {
+        let detail = if mismatch.missing_on.is_empty() {
+            paint(tr(Msg::XcheckDiffers), Element::Warning).to_string()
+        } else {
+            let mut detail = trf(Msg::XcheckOnlyOn, &[&mismatch.present_on.join(", ")]);
+            if mismatch.differs {
+                detail = format!("{}; {}", detail, tr(Msg::XcheckDiffers));
+            }
+            paint(&detail, Element::Warning).to_string()
+        };
+        println!(
+            "  {} {} {}",
+            paint(mismatch.kind, Element::Label),
+            paint(&mismatch.name, Element::Field),
+            detail
+        );
+    }
+    println!();
+}
+
+/// Display what several programs define alike, one line per shared key
+pub fn display_overlaps(programs: usize, overlaps: &[Overlap]) {
+    print_header(&trf(Msg::OverlapHeader, &[&programs, &overlaps.len()]));
+    if overlaps.is_empty() {
+        println!("  {}", paint(tr(Msg::OverlapNone), Element::Success));
+    }
+    for overlap in overlaps {
+        let occurrences: Vec<String> = overlap
+            .occurrences
+            .iter()
+            .map(|o| format!("{} {}", paint(&o.program, Element::Name), o.name))
+            .collect();
+        println!(
+            "  {} {}  {}",
+            paint(&format!("{:<11}", overlap.kind), Element::Label),
+            paint(&overlap.key, Element::Warning),
+            occurrences.join(", ")
+        );
+    }
+    println!();
+}
+
+/// Display the programs of a bundle
+pub fn display_bundle(path: &str, bundle: &Bundle, now: u64) {
+    print_header(&trf(
+        Msg::BundleHeader,
+        &[
+            &path,
+            &bundle.programs.len(),
+            &format_age(now.saturating_sub(bundle.created_at)),
+        ],
+    ));
+    for (i, program) in bundle.programs.iter().enumerate() {
+        println!(
+            "  {}. {}  {}  {}",
+            paint(&format!("{:>2}", i + 1), Element::Label),
+            paint(&format!("{:<24}", program.name), Element::Name),
+            link_address(&program.address, &program.address),
+            paint(&program.source, Element::Muted)
+        );
+    }
+    println!();
+}
+
+/// Display the account role matrix, instructions as numbered columns
+pub fn display_role_matrix(program: &str, matrix: &RoleMatrix) {
+    print_header(&trf(
+        Msg::MatrixHeader,
+        &[&program, &matrix.accounts.len(), &matrix.instructions.len()],
+    ));
+    if matrix.accounts.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+        println!();
+        return;
+    }
+
+    let name_width = matrix.accounts.iter().map(|a| a.len()).max().unwrap_or(0);
+    let columns: String = (1..=matrix.instructions.len())
+        .map(|i| format!("{:>4}", i))
+        .collect();
+    println!(
+        "  {}{}",
+        " ".repeat(name_width),
+        paint(&columns, Element::Label)
+    );
+    for (account, cells) in matrix.accounts.iter().zip(&matrix.cells) {
+        let cells: String = cells
+            .iter()
+            .map(|role| {
+                match role {
+                    Some(role) if role.writable => {
+                        paint(&format!("{:>4}", role), Element::Writable)
+                    }
+                    Some(role) if role.signer => paint(&format!("{:>4}", role), Element::Signer),
+                    Some(role) => paint(&format!("{:>4}", role), Element::Muted),
+                    None => paint(&format!("{:>4}", "·"), Element::Muted),
+                }
+                .to_string()
+            })
+            .collect();
+        println!(
+            "  {}{}",
+            paint(
+                &format!("{:<width$}", account, width = name_width),
+                Element::Field
+            ),
+            cells
+        );
+    }
+
+    println!();
+    for (i, ix) in matrix.instructions.iter().enumerate() {
+        println!(
+            "  {} {}",
+            paint(&format!("{:>4}", i + 1), Element::Label),
+            paint(ix, Element::Name)
+        );
+    }
+    println!();
+    println!("  {}", paint(tr(Msg::MatrixLegend), Element::Muted));
+    println!();
+}
+
+/// Display the PDA catalogue, one block per seed recipe
+pub fn display_pdas(program: &str, recipes: &[PdaRecipe], page: Option<&Page>) {
+    let total = page.map_or(recipes.len(), |p| p.total);
+    print_header(&trf(Msg::PdasHeader, &[&program, &total]));
+    if recipes.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+        println!();
+        return;
+    }
+
+    for recipe in recipes {
+        println!();
+        println!("  {}", paint(&recipe.names.join(" / "), Element::Name));
+        print_field(tr(Msg::PdaSeeds), &recipe.seeds.join(", "));
+        if let Some(derived) = &recipe.address {
+            print_field(tr(Msg::Address), &derived.to_string());
+        }
+        print_field(tr(Msg::PdaUsedBy), &recipe.uses.join(", "));
+    }
+    display_page_footer(page);
+    println!();
+}
+
+/// Display where a type is used
+pub fn display_type_uses(name: &str, uses: &[TypeUse], page: Option<&Page>) {
+    let total = page.map_or(uses.len(), |p| p.total);
+    print_header(&trf(
+        Msg::UsesHeader,
+        &[&paint(name, Element::Name), &total],
+    ));
+    if uses.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for used in uses {
+        println!(
+            "  {} {}.{} : {}",
+            paint(&format!("{:<11}", used.kind), Element::Muted),
+            paint(&used.name, Element::Name),
+            paint(&used.field, Element::Field),
+            paint(&used.ty, Element::Type)
+        );
+    }
+    display_page_footer(page);
+    println!();
+}
+
+/// Display lint findings
+pub fn display_lint(program: &str, findings: &[Finding]) {
+    print_header(&trf(Msg::LintHeader, &[&program, &findings.len()]));
+    if findings.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for finding in findings {
+        println!(
+            "  {} {}  {}  {}",
+            paint("!", Element::Warning),
+            paint(&format!("{:<10}", finding.rule), Element::Muted),
+            paint(&finding.name, Element::Name),
+            finding.detail
+        );
+    }
+    println!();
+}
+
+/// Display the inferred Anchor version, the evidence for it and the features used
+pub fn display_anchor_version(program: &str, report: &AnchorReport) {
+    print_header(&trf(Msg::AnchorVersionHeader, &[&program]));
+    print_field(tr(Msg::AnchorVersionRange), &report.range.to_string());
+    let binary = match &report.binary_version {
+        Some(version) => version.to_string(),
+        None => paint(tr(Msg::NotFoundInBinary), Element::Muted).to_string(),
+    };
+    print_field(tr(Msg::AnchorBinaryVersion), &binary);
+    print_field(tr(Msg::ClientLibraries), &report.clients);
+
+    print_subheader(tr(Msg::Evidence));
+    for evidence in &report.evidence {
+        println!(
+            "  {} {}",
+            evidence.clue,
+            paint(&format!("({})", evidence.implies), Element::Muted)
+        );
+    }
+
+    print_subheader(tr(Msg::AnchorFeatures));
+    for feature in &report.features {
+        let mark = if feature.used {
+            paint("✓", Element::Success)
+        } else {
+            paint("-", Element::Muted)
+        };
+        let detail = feature.detail.as_deref().unwrap_or_default();
+        println!(
+            "  {} {}  {}",
+            mark,
+            paint(&format!("{:<21}", feature.name), Element::Field),
+            paint(detail, Element::Muted)
+        );
+    }
+    println!();
+}
+
+/// Display how old instructions map to the new IDL (unchanged ones in one line)
+pub fn display_compat(old: &Idl, new: &Idl, mappings: &[InstructionMapping]) {
+    print_header(&trf(
+        Msg::CompatHeader,
+        &[
+            &new.metadata.name,
+            &old.metadata.version,
+            &new.metadata.version,
+        ],
+    ));
+
+    for mapping in mappings.iter().filter(|m| m.change != Change::Unchanged) {
+        println!(
+            "  {} {}",
+            paint(change_label(mapping.change), change_element(mapping.change)),
+            paint(&item_name(&mapping.old, &mapping.new), Element::Name)
+        );
+        for (kind, items) in [("arg", &mapping.args), ("account", &mapping.accounts)] {
+            for item in items.iter().filter(|i| i.change != Change::Unchanged) {
+                let note = item
+                    .note
+                    .as_deref()
+                    .map(|n| format!(" ({})", n))
+                    .unwrap_or_default();
+                println!(
+                    "      {} {} {}{}",
+                    paint(change_label(item.change), change_element(item.change)),
+                    paint(kind, Element::Label),
+                    paint(&item_name(&item.old, &item.new), Element::Field),
+                    paint(&note, Element::Muted)
+                );
+            }
+        }
+    }
+
+    let unchanged: Vec<&str> = mappings
+        .iter()
+        .filter(|m| m.change == Change::Unchanged)
+        .filter_map(|m| m.new.as_deref())
+        .collect();
+    if !unchanged.is_empty() {
+        println!();
+        println!(
+            "  {}",
+            paint(
+                &trf(Msg::CompatUnchanged, &[&unchanged.join(", ")]),
+                Element::Muted
+            )
+        );
+    }
+    println!();
+}
+
+fn change_label(change: Change) -> &'static str {
+    match change {
+        Change::Unchanged => "unchanged",
+        Change::Renamed => "renamed",
+        Change::Retyped => "retyped",
+        Change::Changed => "changed",
+        Change::Added => "added",
+        Change::Removed => "removed",
+    }
+}
+
+fn change_element(change: Change) -> Element {
+    match change {
+        Change::Added => Element::Success,
+        Change::Removed => Element::Error,
+        Change::Unchanged => Element::Muted,
+        _ => Element::Warning,
+    }
+}
+
+/// `old -> new`, or whichever exists
+fn item_name(old: &Option<String>, new: &Option<String>) -> String {
+    match (old, new) {
+        (Some(old), Some(new)) if old != new => format!("{} -> {}", old, new),
+        (_, Some(name)) | (Some(name), None) => name.clone(),
+        (None, None) => String::new(),
+    }
+}
+
+/// Display the fixtures written by `snapshot` and how to load them
+pub fn display_snapshot(dir: &str, fixtures: &[(FixtureKind, Fixture)]) {
+    print_header(&trf(Msg::SnapshotHeader, &[&fixtures.len(), &dir]));
+    for (i, (kind, fixture)) in fixtures.iter().enumerate() {
+        let kind = match kind {
+            FixtureKind::Program => "program".to_string(),
+            FixtureKind::ProgramData => "program data".to_string(),
+            FixtureKind::Idl => "IDL".to_string(),
+            FixtureKind::Account(name) => name.clone(),
+        };
+        print_numbered_item(
+            i + 1,
+            &format!(
+                "{} {}",
+                paint(&kind, Element::Name),
+                link_address(&fixture.pubkey, &fixture.pubkey)
+            ),
+        );
+    }
+
+    print_subheader(tr(Msg::SnapshotValidatorHint));
+    println!("  solana-test-validator --account-dir {}", dir);
+    println!();
+}
+
+/// Display the summary of a scan: lamports, size histogram, field stats
+pub fn display_scan(stats: &ScanStats) {
+    const BAR_WIDTH: usize = 30;
+
+    print_header(&trf(
+        Msg::ScanHeader,
+        &[&paint(&stats.account_type, Element::Name), &stats.count],
+    ));
+    print_field(
+        tr(Msg::ScanLamports),
+        &format!(
+            "{} ({} SOL)",
+            stats.lamports,
+            stats.lamports as f64 / LAMPORTS_PER_SOL as f64
+        ),
+    );
+
+    print_subheader(tr(Msg::ScanSizes));
+    if stats.sizes.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    let most = stats
+        .sizes
+        .iter()
+        .map(|b| b.count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let labels: Vec<String> = stats
+        .sizes
+        .iter()
+        .map(|b| match b.min == b.max {
+            true => b.min.to_string(),
+            false => format!("{}-{}", b.min, b.max),
+        })
+        .collect();
+    let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    for (bucket, label) in stats.sizes.iter().zip(&labels) {
+        let bar = "█".repeat(bucket.count * BAR_WIDTH / most);
+        println!(
+            "  {:>width$}  {:>8}  {}",
+            label,
+            bucket.count,
+            paint(&bar, Element::Label),
+            width = width
+        );
+    }
+
+    if !stats.fields.is_empty() {
+        print_subheader(tr(Msg::ScanFields));
+        let width = stats
+            .fields
+            .iter()
+            .map(|f| f.field.len())
+            .max()
+            .unwrap_or(0);
+        for field in &stats.fields {
             println!(
-                "  {}  {}  {}",
-                format!("{:<6}", error.code).red(),
-                format!("{:<24}", error.name).yellow(),
-                msg.dimmed()
+                "  {:<width$}  min {}  max {}  mean {:.2}",
+                paint(&field.field, Element::Field),
+                field.min,
+                field.max,
+                field.mean,
+                width = width
             );
         }
     }
+    if stats.undecoded > 0 {
+        println!();
+        println!(
+            "  {} {}",
+            paint(tr(Msg::Note), Element::Warning),
+            trf(Msg::ScanUndecoded, &[&stats.undecoded])
+        );
+    }
     println!();
 }
 
-/// Format IdlType as readable string
-pub fn format_type(ty: &IdlType) -> String {
-    match ty {
-        IdlType::Primitive(s) => s.clone(),
-        IdlType::Complex(complex) => format_complex_type(complex),
+/// Display a generated value: fields, the value as JSON and its Borsh bytes
+pub fn display_mock(type_name: &str, seed: u64, mock: &Mock) {
+    print_header(&trf(Msg::MockHeader, &[&paint(type_name, Element::Name)]));
+    print_field(tr(Msg::Seed), &seed.to_string());
+    print_field(tr(Msg::Size), &trf(Msg::ByteCount, &[&mock.data.len()]));
+
+    print_subheader(tr(Msg::Fields));
+    match &mock.value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                display_value_entry(key, value, 1);
+            }
+        }
+        value => display_value_entry(type_name, value, 1),
     }
+
+    print_subheader("JSON");
+    println!("  {}", mock.value);
+
+    print_subheader(tr(Msg::BorshData));
+    println!("  {}", hex(&mock.data));
+    println!();
 }
 
-/// Format complex types
-fn format_complex_type(ty: &IdlTypeComplex) -> String {
-    match ty {
-        IdlTypeComplex::Vec(inner) => format!("Vec<{}>", format_type(inner)),
-        IdlTypeComplex::Option(inner) => format!("Option<{}>", format_type(inner)),
-        IdlTypeComplex::Array(inner, size) => format!("[{}; {}]", format_type(inner), size),
-        IdlTypeComplex::Defined { name } => name.clone(),
+/// Display a simulation: result, compute units per invocation, events and logs
+pub fn display_simulation(instruction: &str, simulation: &Simulation) {
+    print_header(&trf(
+        Msg::SimulationHeader,
+        &[&paint(instruction, Element::Name)],
+    ));
+    match &simulation.error {
+        None => print_field(tr(Msg::Result), &paint(tr(Msg::Success), Element::Success)),
+        Some(error) => print_field(tr(Msg::Result), &paint(error, Element::Error)),
     }
+    if let Some(units) = simulation.units_consumed {
+        print_field(tr(Msg::ComputeUnits), &units.to_string());
+    }
+
+    if !simulation.frames.is_empty() {
+        print_subheader(tr(Msg::ComputeUnitsByProgram));
+        for frame in &simulation.frames {
+            let name = Pubkey::from_str(&frame.program)
+                .ok()
+                .and_then(|p| known_program_name(&p))
+                .unwrap_or(&frame.program);
+            println!(
+                "{}  {} {:>8}  {}",
+                "  ".repeat(frame.depth.saturating_sub(1)),
+                paint(name, Element::Field),
+                frame.consumed,
+                paint(&trf(Msg::OwnUnits, &[&frame.own]), Element::Muted)
+            );
+        }
+    }
+
+    print_subheader(&trf(Msg::EventsCount, &[&simulation.events.len()]));
+    if simulation.events.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for event in &simulation.events {
+        display_value_entry(&event.name, &event.value, 1);
+    }
+
+    print_subheader(&trf(Msg::ProgramLogs, &[&simulation.logs.len()]));
+    for line in &simulation.logs {
+        println!("  {}", paint(line, Element::Muted));
+    }
+    println!();
+}
+
+/// Display compute units per swept argument value, with a bar chart
+pub fn display_sweep(arg: &str, points: &[SweepPoint]) {
+    const BAR_WIDTH: u64 = 30;
+
+    print_header(&trf(Msg::SweepHeader, &[&paint(arg, Element::Field)]));
+    let max = points
+        .iter()
+        .filter_map(|p| p.units_consumed)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let labels: Vec<String> = points
+        .iter()
+        .map(|p| format_value_inline(&p.value))
+        .collect();
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+    for (point, label) in points.iter().zip(&labels) {
+        let units = point.units_consumed.unwrap_or(0);
+        let bar = "█".repeat((units * BAR_WIDTH / max) as usize);
+        let mut line = format!(
+            "  {:>width$}  {:>8}  {}",
+            label,
+            units,
+            paint(&bar, Element::Label),
+            width = width
+        );
+        if let Some(error) = &point.error {
+            line.push_str(&format!("  {}", paint(error, Element::Error)));
+        }
+        println!("{}", line);
+    }
+    println!();
+}
+
+/// Display recently inspected programs, most recent first
+pub fn display_recent_programs(programs: &[RecentProgram], now: u64) {
+    print_header(&trf(Msg::RecentHeader, &[&programs.len()]));
+
+    if programs.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for (i, program) in programs.iter().enumerate() {
+        println!(
+            "  {}. {}  {}  {}  {}",
+            paint(&format!("{:>2}", i + 1), Element::Label),
+            paint(&format!("{:<24}", program.name), Element::Name),
+            program.program_id,
+            paint(&program.cluster, Element::Type),
+            paint(
+                &format_age(now.saturating_sub(program.last_used)),
+                Element::Muted
+            )
+        );
+    }
+    println!();
+}
+
+/// Display the renaming report of `periscope rust-names`
+pub fn display_renames(program: &str, renames: &[Rename]) {
+    print_header(&trf(
+        Msg::RenamesHeader,
+        &[&paint(program, Element::Name), &renames.len()],
+    ));
+
+    if renames.is_empty() {
+        println!("  {}", paint(tr(Msg::NoRenames), Element::Muted));
+    }
+    for rename in renames {
+        println!(
+            "  {}  {} -> {}  {}",
+            paint(&format!("{:<28}", rename.scope), Element::Label),
+            rename.from,
+            paint(&rename.to, Element::Field),
+            paint(&format!("({})", rename.reason), Element::Muted)
+        );
+    }
+    println!();
+}
+
+/// Format an age in seconds as `42s ago`, `5m ago`, `3h ago` or `2d ago`
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// Display `doctor` results as a checklist
+pub fn display_checks(checks: &[Check]) {
+    print_header(tr(Msg::DoctorHeader));
+
+    for check in checks {
+        let (mark, element) = match check.status {
+            CheckStatus::Pass => ("✓", Element::Success),
+            CheckStatus::Warn => ("!", Element::Warning),
+            CheckStatus::Fail => ("✗", Element::Error),
+        };
+        println!(
+            "  {} {}  {}",
+            paint(mark, element),
+            paint(&format!("{:<10}", check.name), Element::Field),
+            check.detail
+        );
+    }
+    println!();
+}
+
+/// Display quarantined payloads, oldest first
+pub fn display_quarantine(entries: &[QuarantineEntry], now: u64) {
+    print_header(&trf(Msg::QuarantineHeader, &[&entries.len()]));
+    if entries.is_empty() {
+        println!("  {}", paint(tr(Msg::None), Element::Muted));
+    }
+    for entry in entries {
+        println!();
+        println!(
+            "  {}  {}",
+            paint(&entry.id, Element::Name),
+            link_address(&entry.address, &entry.address)
+        );
+        print_field(
+            tr(Msg::ProgramId),
+            &link_address(&entry.program_id, &entry.program_id),
+        );
+        if let Some(account_type) = &entry.account_type {
+            print_field(tr(Msg::QuarantineAccountType), account_type);
+        }
+        print_field(
+            tr(Msg::QuarantineAttempts),
+            &format!(
+                "{} ({})",
+                entry.attempts,
+                format_age(now.saturating_sub(entry.quarantined_at))
+            ),
+        );
+        print_field(tr(Msg::QuarantineError), &entry.error);
+    }
+    println!();
+}
+
+/// Display the outcome of decoding quarantined payloads again
+pub fn display_quarantine_retry(outcomes: &[RetryOutcome]) {
+    for outcome in outcomes {
+        match (&outcome.account, &outcome.error) {
+            (Some(account), _) => println!(
+                "  {} {}  {}  {}",
+                paint("✓", Element::Success),
+                paint(&outcome.id, Element::Name),
+                link_address(&outcome.address, &outcome.address),
+                paint(account, Element::Type)
+            ),
+            (None, error) => println!(
+                "  {} {}  {}  {}",
+                paint("✗", Element::Error),
+                paint(&outcome.id, Element::Name),
+                link_address(&outcome.address, &outcome.address),
+                paint(error.as_deref().unwrap_or("-"), Element::Muted)
+            ),
+        }
+    }
+    let decoded = outcomes.iter().filter(|o| o.account.is_some()).count();
+    println!(
+        "{}",
+        trf(
+            Msg::QuarantineRetried,
+            &[&decoded, &outcomes.len(), &(outcomes.len() - decoded)]
+        )
+    );
+}
+
+/// Display pre-flight checks, grouped by account
+pub fn display_preflight(instruction: &str, checks: &[AccountCheck]) {
+    let mut accounts: Vec<&str> = Vec::new();
+    for check in checks {
+        if !accounts.contains(&check.account.as_str()) {
+            accounts.push(&check.account);
+        }
+    }
+    print_header(&trf(Msg::PreflightHeader, &[&instruction, &accounts.len()]));
+
+    for account in accounts {
+        let mut checks = checks.iter().filter(|c| c.account == account).peekable();
+        let address = checks
+            .peek()
+            .and_then(|c| c.address.as_deref())
+            .map(|a| paint(&link_address(a, a), Element::Muted).to_string())
+            .unwrap_or_default();
+        println!();
+        println!("  {} {}", paint(account, Element::Name), address);
+        for check in checks {
+            let (mark, element) = match check.status {
+                CheckStatus::Pass => ("✓", Element::Success),
+                CheckStatus::Warn => ("!", Element::Warning),
+                CheckStatus::Fail => ("✗", Element::Error),
+            };
+            println!(
+                "    {} {}  {}",
+                paint(mark, element),
+                paint(&format!("{:<8}", check.check), Element::Field),
+                check.detail
+            );
+        }
+    }
+    println!();
+}
+
+/// Display `--timing` results on stderr, so JSON on stdout stays clean
+pub fn display_timing(metrics: &Metrics, total: Duration) {
+    let ms = |d: Duration| format!("{:>10.2} ms", d.as_secs_f64() * 1000.0);
+
+    eprintln!();
+    eprintln!("{}", paint(tr(Msg::TimingHeader), Element::Header));
+    for (stage, m) in metrics.stages() {
+        let runs = if m.count > 1 {
+            paint(&format!("  ×{}", m.count), Element::Muted).to_string()
+        } else {
+            String::new()
+        };
+        eprintln!(
+            "  {} {}{}",
+            paint(&format!("{:<22}", stage.to_string()), Element::Field),
+            ms(m.total),
+            runs
+        );
+    }
+    eprintln!(
+        "  {} {}",
+        paint(&format!("{:<22}", tr(Msg::TimingTotal)), Element::Label),
+        ms(total)
+    );
+}
+
+/// Display a decoded account and its fields
+pub fn display_decoded_account(address: &str, decoded: &DecodedAccount) {
+    print_header(&trf(
+        Msg::AccountHeader,
+        &[&paint(&decoded.name, Element::Name)],
+    ));
+    display_annotation(annotation(AnnotationKind::Account, &decoded.name));
+
+    print_field(tr(Msg::Address), address);
+    print_field(tr(Msg::Size), &trf(Msg::ByteCount, &[&decoded.data_len]));
+    print_field(tr(Msg::Layout), &decoded.layout.to_string());
+
+    print_subheader(tr(Msg::Fields));
+    match &decoded.value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                display_value_entry(key, value, 1);
+            }
+        }
+        _ => println!("  {}", paint(tr(Msg::None), Element::Muted)),
+    }
+
+    if decoded.trailing_bytes() > 0 {
+        println!();
+        println!(
+            "  {} {}",
+            paint(tr(Msg::Note), Element::Warning),
+            trf(
+                Msg::TrailingBytes,
+                &[&decoded.trailing_bytes(), &decoded.consumed]
+            )
+        );
+    }
+    println!();
+}
+
+/// Bytes per row in hex dumps
+const HEX_ROW_LEN: usize = 16;
+
+/// Display a hex dump of account data, coloring each field's bytes and labelling
+/// every row with the fields that start in it
+pub fn display_annotated_hex(data: &[u8], spans: &[FieldSpan]) {
+    print_subheader(tr(Msg::Layout));
+    println!(
+        "  {}  {}  {}",
+        paint(&format!("{:<8}", tr(Msg::Offset)), Element::Label),
+        paint(&format!("{:<47}", tr(Msg::Bytes)), Element::Label),
+        paint(tr(Msg::Fields), Element::Label)
+    );
+
+    for (row, chunk) in data.chunks(HEX_ROW_LEN).enumerate() {
+        let row_start = row * HEX_ROW_LEN;
+
+        let bytes: Vec<String> = chunk
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                let hex = format!("{:02x}", byte);
+                match span_at(spans, row_start + i) {
+                    Some(idx) => paint_span(&hex, idx),
+                    None => paint(&hex, Element::Muted).to_string(),
+                }
+            })
+            .collect();
+
+        // Colored strings break width specifiers, so pad short rows by hand
+        let padding = "   ".repeat(HEX_ROW_LEN - chunk.len());
+
+        let labels: Vec<String> = spans
+            .iter()
+            .enumerate()
+            .filter(|(_, span)| span.start >= row_start && span.start < row_start + HEX_ROW_LEN)
+            .map(|(idx, span)| paint_span(&format!("{}: {}", span.path, span.ty), idx))
+            .collect();
+
+        println!(
+            "  {}  {}{}  {}",
+            paint(&format!("{:08x}", row_start), Element::Label),
+            bytes.join(" "),
+            padding,
+            labels.join(", ")
+        );
+    }
+}
+
+/// Index of the span covering `offset` (spans are sorted and non-overlapping)
+fn span_at(spans: &[FieldSpan], offset: usize) -> Option<usize> {
+    let idx = spans.partition_point(|span| span.start <= offset);
+    (idx > 0 && spans[idx - 1].end > offset).then(|| idx - 1)
+}
+
+/// Elements cycled through to tell neighbouring spans apart
+const SPAN_PALETTE: [Element; 6] = [
+    Element::Header,
+    Element::Name,
+    Element::Field,
+    Element::Event,
+    Element::Type,
+    Element::Error,
+];
+
+/// Color text by span index, cycling through the palette
+fn paint_span(text: &str, idx: usize) -> String {
+    paint(text, SPAN_PALETTE[idx % SPAN_PALETTE.len()]).to_string()
+}
+
+/// Display a decoded value, expanding nested objects and arrays of objects
+fn display_value_entry(key: &str, value: &Value, indent: usize) {
+    let pad = "  ".repeat(indent);
+
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            println!("{}{}:", pad, paint(key, Element::Field));
+            for (k, v) in map {
+                display_value_entry(k, v, indent + 1);
+            }
+        }
+        Value::Array(items) if items.iter().any(|v| v.is_object() || v.is_array()) => {
+            println!("{}{}:", pad, paint(key, Element::Field));
+            for (i, v) in items.iter().enumerate() {
+                display_value_entry(&format!("[{}]", i), v, indent + 1);
+            }
+        }
+        _ => println!(
+            "{}{}: {}",
+            pad,
+            paint(key, Element::Field),
+            format_value_inline(value)
+        ),
+    }
+}
+
+/// Format a scalar (or flat array) decoded value on a single line
+fn format_value_inline(value: &Value) -> String {
+    match value {
+        Value::String(s) => link_if_address(s),
+        Value::Null => paint("null", Element::Muted).to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(format_value_inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(_) => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Display the classification of an address, with its page on each explorer
+pub fn display_address_info(info: &AddressInfo, explorers: &[Explorer]) {
+    let address = info.address.to_string();
+    print_header(&trf(
+        Msg::AddressHeader,
+        &[&link_address(&address, &address)],
+    ));
+
+    print_field(
+        tr(Msg::Kind),
+        &paint(&info.kind.to_string(), Element::Name).to_string(),
+    );
+    print_field(
+        tr(Msg::Curve),
+        if info.on_curve {
+            tr(Msg::OnCurve)
+        } else {
+            tr(Msg::OffCurve)
+        },
+    );
+
+    if let Some(owner) = &info.owner {
+        let owner_str = match known_program_name(owner) {
+            Some(name) => format!(
+                "{} {}",
+                owner,
+                paint(&format!("({})", name), Element::Muted)
+            ),
+            None => owner.to_string(),
+        };
+        print_field(tr(Msg::Owner), &owner_str);
+        print_field(tr(Msg::Size), &trf(Msg::ByteCount, &[&info.data_len]));
+        print_field(
+            tr(Msg::Balance),
+            &format!("{} SOL", info.lamports as f64 / LAMPORTS_PER_SOL as f64),
+        );
+    }
+
+    if let Some(rent_exempt) = info.rent_exempt {
+        print_field(
+            tr(Msg::Rent),
+            &if rent_exempt {
+                paint(tr(Msg::RentExempt), Element::Success).to_string()
+            } else {
+                paint(tr(Msg::RentNotExempt), Element::Error).to_string()
+            },
+        );
+    }
+
+    for (key, value) in &info.details {
+        print_field(key, value);
+    }
+
+    if !explorers.is_empty() {
+        print_subheader(tr(Msg::ExplorersHeader));
+        for explorer in explorers {
+            print_field(explorer.name(), &explorer.address_url(&address));
+        }
+    }
+    println!();
 }
 
 /// Format discriminator bytes as hex
 pub fn format_discriminator(bytes: &[u8]) -> String {
     if bytes.is_empty() {
-        "(none)".to_string()
+        tr(Msg::None).to_string()
     } else {
         let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
         format!("[{}]", hex.join(" "))
@@ -254,23 +1726,27 @@ pub fn format_discriminator(bytes: &[u8]) -> String {
 
 /// Display an error message
 pub fn display_error(msg: &str) {
-    eprintln!("{} {}", "Error:".red().bold(), msg);
+    eprintln!(
+        "{} {}",
+        paint(tr(Msg::ErrorPrefix), Element::Error).bold(),
+        msg
+    );
 }
 
 /// Display instruction not found error with suggestions
 pub fn display_instruction_not_found(name: &str, available: &[&str]) {
-    display_error(&format!("Instruction '{}' not found", name));
+    display_error(&trf(Msg::InstructionNotFound, &[&name]));
 
     if !available.is_empty() {
         eprintln!();
-        eprintln!("{}", "Available instructions:".dimmed());
+        eprintln!("{}", paint(tr(Msg::AvailableInstructions), Element::Label));
         for ix_name in available.iter().take(10) {
-            eprintln!("  - {}", ix_name.green());
+            eprintln!("  - {}", paint(ix_name, Element::Name));
         }
         if available.len() > 10 {
             eprintln!(
                 "  {} more...",
-                format!("(+{})", available.len() - 10).dimmed()
+                paint(&format!("(+{})", available.len() - 10), Element::Muted)
             );
         }
     }
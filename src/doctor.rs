@@ -0,0 +1,190 @@
+//! Self-diagnosis for `periscope doctor`
+//!
+//! Runs independent checks of the local setup (config, RPC endpoint, cache
+//! directory, clock) and reports each as pass, warning or failure. Checks
+//! never abort early: a broken config still lets the RPC check run against
+//! the default endpoint.
+
+use crate::cache::IdlCache;
+use crate::config::Config;
+use crate::idl::{rpc_client_with, RpcOptions, SUPPORTED_IDL_SPECS};
+use crate::recent::now;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// RPC latency above which the check warns
+const SLOW_RPC: Duration = Duration::from_millis(1500);
+
+/// Clock difference from the cluster above which the check warns (seconds)
+const MAX_CLOCK_SKEW: u64 = 60;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// A single diagnostic result
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// What was checked
+    pub name: &'static str,
+
+    /// Outcome
+    pub status: CheckStatus,
+
+    /// Measured value or reason for the outcome
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Run all checks. `rpc_url` overrides the configured endpoint; `rpc` are
+/// the client options for it (headers included), with a shorter timeout.
+pub fn run_checks(rpc_url: Option<&str>, rpc: &RpcOptions) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let config = match Config::load_layered() {
+        Ok(config) => {
+            checks.push(match config.validate() {
+                Ok(()) => Check::new("config", CheckStatus::Pass, config_source(&config)),
+                Err(e) => Check::new("config", CheckStatus::Fail, e.to_string()),
+            });
+            config
+        }
+        Err(e) => {
+            checks.push(Check::new("config", CheckStatus::Fail, e.to_string()));
+            Config::default()
+        }
+    };
+
+    let rpc_url = rpc_url.unwrap_or(&config.rpc_url);
+    let options = RpcOptions {
+        timeout: Duration::from_secs(10),
+        ..rpc.clone()
+    };
+    match rpc_client_with(rpc_url, &options) {
+        Ok(client) => {
+            checks.push(check_rpc(&client, rpc_url));
+            checks.push(check_clock(&client));
+        }
+        Err(e) => checks.push(Check::new(
+            "rpc",
+            CheckStatus::Fail,
+            format!("{}: {}", rpc_url, e),
+        )),
+    }
+    checks.push(check_cache_dir());
+    checks.push(Check::new(
+        "idl spec",
+        CheckStatus::Pass,
+        format!(
+            "periscope {} parses specs: {}",
+            env!("CARGO_PKG_VERSION"),
+            SUPPORTED_IDL_SPECS.join(", ")
+        ),
+    ));
+
+    checks
+}
+
+fn config_source(config: &Config) -> String {
+    let user = Config::file_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    match &config.project_file {
+        Some(project) => format!("{} + {}", user, project.display()),
+        None if Config::exists() => user,
+        None => "defaults (no config file)".to_string(),
+    }
+}
+
+fn check_rpc(client: &RpcClient, rpc_url: &str) -> Check {
+    let start = Instant::now();
+    match client.get_version() {
+        Ok(version) => {
+            let elapsed = start.elapsed();
+            let detail = format!(
+                "{} (solana-core {}) in {} ms",
+                rpc_url,
+                version.solana_core,
+                elapsed.as_millis()
+            );
+            let status = if elapsed > SLOW_RPC {
+                CheckStatus::Warn
+            } else {
+                CheckStatus::Pass
+            };
+            Check::new("rpc", status, detail)
+        }
+        Err(e) => Check::new("rpc", CheckStatus::Fail, format!("{}: {}", rpc_url, e)),
+    }
+}
+
+/// Compare the local clock with the block time of the latest slot
+fn check_clock(client: &RpcClient) -> Check {
+    let block_time = match client.get_slot() {
+        Ok(slot) => client.get_block_time(slot),
+        Err(e) => Err(e),
+    };
+
+    match block_time {
+        Ok(block_time) => {
+            let local = now() as i64;
+            let skew = local - block_time;
+            let detail = format!("local clock is {}s off the cluster", skew);
+            if skew.unsigned_abs() > MAX_CLOCK_SKEW {
+                Check::new("clock", CheckStatus::Warn, detail)
+            } else {
+                Check::new("clock", CheckStatus::Pass, detail)
+            }
+        }
+        Err(e) => Check::new(
+            "clock",
+            CheckStatus::Warn,
+            format!("could not read cluster time: {}", e),
+        ),
+    }
+}
+
+/// The cache directory must be creatable and writable
+fn check_cache_dir() -> Check {
+    let Some(dir) = IdlCache::cache_dir() else {
+        return Check::new(
+            "cache dir",
+            CheckStatus::Fail,
+            "could not determine config directory",
+        );
+    };
+
+    let probe = dir.join(".doctor-probe");
+    let result = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe));
+
+    match result {
+        Ok(()) => Check::new(
+            "cache dir",
+            CheckStatus::Pass,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => Check::new(
+            "cache dir",
+            CheckStatus::Fail,
+            format!("{}: {}", dir.display(), e),
+        ),
+    }
+}
@@ -0,0 +1,553 @@
+//! Borsh encoding of instruction arguments
+//!
+//! The inverse of [`decode`](crate::decode): values use the representation
+//! decoding produces (and `periscope template` prints), so decoded output can
+//! be edited and encoded again. Integers also accept decimal strings, and
+//! `bytes` accept a `0x` hex string.
+//!
+//! Invalid values fail with the path of the argument (`swap.fees[0].bps`)
+//! and what is wrong with it: out of range, negative for an unsigned type,
+//! not a pubkey, an unknown enum variant, the wrong array length, or null
+//! outside an `Option`.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{
+    format_type, Idl, IdlEnumFields, IdlField, IdlGenericArg, IdlInstruction, IdlPda, IdlSeed,
+    IdlType, IdlTypeComplex, IdlTypeDefTy,
+};
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum nesting depth of defined types, guards against recursive layouts
+const MAX_DEPTH: usize = 64;
+
+/// Encode instruction data: discriminator followed by the Borsh-encoded args
+///
+/// `args` is an object keyed by argument name; every argument is required
+/// (`null` for an `Option` that is None).
+pub fn encode_instruction(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    args: &Value,
+) -> PeriscopeResult<Vec<u8>> {
+    let mut encoder = Encoder::new(idl);
+    encoder.out.extend(ix.discriminator_bytes());
+    encoder.path.push(ix.name.clone());
+    encoder.encode_fields(&ix.args, args)?;
+    Ok(encoder.out)
+}
+
+/// Borsh-encode a single value of `ty`
+pub fn encode_type(idl: &Idl, ty: &IdlType, value: &Value) -> PeriscopeResult<Vec<u8>> {
+    let mut encoder = Encoder::new(idl);
+    encoder.encode(ty, value)?;
+    Ok(encoder.out)
+}
+
+struct Encoder<'a> {
+    idl: &'a Idl,
+    out: Vec<u8>,
+    path: Vec<String>,
+    depth: usize,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(idl: &'a Idl) -> Self {
+        Encoder {
+            idl,
+            out: Vec::new(),
+            path: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> PeriscopeError {
+        PeriscopeError::EncodeError {
+            path: self.path.join(".").replace(".[", "["),
+            reason: reason.into(),
+        }
+    }
+
+    fn mismatch(&self, ty: &IdlType, value: &Value) -> PeriscopeError {
+        if value.is_null() {
+            return self.error(format!(
+                "expected {}, got null (only an Option may be null)",
+                format_type(ty)
+            ));
+        }
+        self.error(format!("expected {}, got {}", format_type(ty), value))
+    }
+
+    fn encode(&mut self, ty: &IdlType, value: &Value) -> PeriscopeResult<()> {
+        match ty {
+            IdlType::Primitive(name) => self.encode_primitive(ty, name, value),
+            IdlType::Complex(IdlTypeComplex::Option(inner)) => match value {
+                Value::Null => {
+                    self.out.push(0);
+                    Ok(())
+                }
+                value => {
+                    self.out.push(1);
+                    self.encode(inner, value)
+                }
+            },
+            IdlType::Complex(IdlTypeComplex::Vec(inner)) => {
+                let items = value.as_array().ok_or_else(|| self.mismatch(ty, value))?;
+                let len = u32::try_from(items.len())
+                    .map_err(|_| self.error("vec longer than u32::MAX"))?;
+                self.out.extend(len.to_le_bytes());
+                self.encode_items(inner, items)
+            }
+            IdlType::Complex(IdlTypeComplex::Array(inner, len)) => {
+                let len = len.value().ok_or_else(|| {
+                    self.error(format!("generic length {} is not bound to a value", len))
+                })?;
+                let items = value.as_array().ok_or_else(|| self.mismatch(ty, value))?;
+                if items.len() != len {
+                    return Err(self.error(format!("expected {} items, got {}", len, items.len())));
+                }
+                self.encode_items(inner, items)
+            }
+            IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+                self.encode_defined(name, generics, value)
+            }
+            IdlType::Complex(IdlTypeComplex::Generic(name)) => {
+                Err(self.error(format!("generic parameter {} is not bound to a type", name)))
+            }
+        }
+    }
+
+    fn encode_items(&mut self, inner: &IdlType, items: &[Value]) -> PeriscopeResult<()> {
+        for (i, item) in items.iter().enumerate() {
+            self.path.push(format!("[{}]", i));
+            self.encode(inner, item)?;
+            self.path.pop();
+        }
+        Ok(())
+    }
+
+    fn encode_primitive(&mut self, ty: &IdlType, name: &str, value: &Value) -> PeriscopeResult<()> {
+        match primitive_bytes(name, value) {
+            Ok(bytes) => {
+                self.out.extend(bytes);
+                Ok(())
+            }
+            Err(None) => Err(self.mismatch(ty, value)),
+            Err(Some(reason)) => Err(self.error(reason)),
+        }
+    }
+
+    fn encode_defined(
+        &mut self,
+        name: &str,
+        generics: &[IdlGenericArg],
+        value: &Value,
+    ) -> PeriscopeResult<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(format!("type nesting exceeds {} levels", MAX_DEPTH)));
+        }
+        let idl = self.idl;
+        let type_def = idl
+            .instantiate_type(name, generics)
+            .map_err(|reason| self.error(reason))?;
+
+        self.depth += 1;
+        match &type_def.ty {
+            IdlTypeDefTy::Struct { fields } => self.encode_fields(fields, value)?,
+            IdlTypeDefTy::Enum { variants } => {
+                // "Variant" for unit variants, { "Variant": fields } otherwise
+                let (variant_name, fields_value) = match value {
+                    Value::String(s) => (s.as_str(), &Value::Null),
+                    Value::Object(map) if map.len() == 1 => {
+                        let (k, v) = map.iter().next().expect("one entry");
+                        (k.as_str(), v)
+                    }
+                    _ => {
+                        return Err(self.error(format!(
+                        "expected a variant of {} (\"Variant\" or {{\"Variant\": ...}}), got {}",
+                        name, value
+                    )))
+                    }
+                };
+                let (index, variant) = variants
+                    .iter()
+                    .enumerate()
+                    .find(|(_, v)| v.name == variant_name)
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+                        self.error(format!(
+                            "unknown variant '{}' of {} (expected one of: {})",
+                            variant_name,
+                            name,
+                            names.join(", ")
+                        ))
+                    })?;
+                self.out.push(index as u8);
+
+                self.path.push(variant.name.clone());
+                match &variant.fields {
+                    None => {}
+                    Some(IdlEnumFields::Named(fields)) => {
+                        self.encode_fields(fields, fields_value)?
+                    }
+                    Some(IdlEnumFields::Tuple(types)) => {
+                        let items = fields_value.as_array().ok_or_else(|| {
+                            self.error(format!("expected {} tuple fields", types.len()))
+                        })?;
+                        if items.len() != types.len() {
+                            return Err(self.error(format!(
+                                "expected {} tuple fields, got {}",
+                                types.len(),
+                                items.len()
+                            )));
+                        }
+                        for (i, (ty, item)) in types.iter().zip(items).enumerate() {
+                            self.path.push(format!("[{}]", i));
+                            self.encode(ty, item)?;
+                            self.path.pop();
+                        }
+                    }
+                }
+                self.path.pop();
+            }
+        }
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn encode_fields(&mut self, fields: &[IdlField], value: &Value) -> PeriscopeResult<()> {
+        let empty = Map::new();
+        let map = match value {
+            Value::Object(map) => map,
+            Value::Null if fields.is_empty() => &empty,
+            other => return Err(self.error(format!("expected an object, got {}", other))),
+        };
+        for field in fields {
+            self.path.push(field.name.clone());
+            let value = match map.get(&field.name) {
+                Some(value) => value,
+                // Only an Option may be left out
+                None if matches!(field.ty, IdlType::Complex(IdlTypeComplex::Option(_))) => {
+                    &Value::Null
+                }
+                None => return Err(self.error("missing value")),
+            };
+            self.encode(&field.ty, value)?;
+            self.path.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Borsh bytes of a primitive value
+///
+/// `Err(None)` when the value has the wrong shape for the type, `Err(Some)`
+/// with a reason for more specific problems.
+fn primitive_bytes(name: &str, value: &Value) -> Result<Vec<u8>, Option<String>> {
+    /// `Err(Some)` for integers out of the type's range, so the message
+    /// says why rather than just "expected u8"
+    fn int<T: FromStr + fmt::Display>(
+        name: &str,
+        value: &Value,
+        min: T,
+        max: T,
+    ) -> Result<T, Option<String>> {
+        let text = match value {
+            Value::Number(n) => n.to_string(),
+            Value::String(s) => s.trim().to_string(),
+            _ => return Err(None),
+        };
+        if let Ok(int) = text.parse() {
+            return Ok(int);
+        }
+        let digits = text.strip_prefix('-').unwrap_or(&text);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(None);
+        }
+        Err(Some(if text.starts_with('-') && name.starts_with('u') {
+            format!("{} is negative, but {} is unsigned", text, name)
+        } else {
+            format!("{} is out of range for {} ({}..={})", text, name, min, max)
+        }))
+    }
+    macro_rules! int {
+        ($ty:ty) => {
+            int::<$ty>(name, value, <$ty>::MIN, <$ty>::MAX)?
+                .to_le_bytes()
+                .to_vec()
+        };
+    }
+
+    let bytes = match name {
+        "bool" => vec![value.as_bool().ok_or(None)? as u8],
+        "u8" => int!(u8),
+        "i8" => int!(i8),
+        "u16" => int!(u16),
+        "i16" => int!(i16),
+        "u32" => int!(u32),
+        "i32" => int!(i32),
+        "u64" => int!(u64),
+        "i64" => int!(i64),
+        "u128" => int!(u128),
+        "i128" => int!(i128),
+        "f32" => (value.as_f64().ok_or(None)? as f32).to_le_bytes().to_vec(),
+        "f64" => value.as_f64().ok_or(None)?.to_le_bytes().to_vec(),
+        "u256" | "i256" => {
+            // `0x`-prefixed big-endian hex, as decoding prints it
+            let hex = value
+                .as_str()
+                .and_then(|s| s.strip_prefix("0x"))
+                .ok_or(None)?;
+            let mut bytes = parse_hex(hex)
+                .filter(|b| b.len() <= 32)
+                .ok_or_else(|| Some("expected up to 32 bytes of 0x-prefixed hex".to_string()))?;
+            bytes.reverse();
+            bytes.resize(32, 0);
+            bytes
+        }
+        "pubkey" | "publicKey" => {
+            let s = value.as_str().ok_or(None)?;
+            Pubkey::from_str(s.trim())
+                .map_err(|_| Some(format!("'{}' is not a valid base58 pubkey", s)))?
+                .to_bytes()
+                .to_vec()
+        }
+        "string" => {
+            let s = value.as_str().ok_or(None)?;
+            let mut bytes = (s.len() as u32).to_le_bytes().to_vec();
+            bytes.extend(s.as_bytes());
+            bytes
+        }
+        "bytes" => {
+            let raw: Vec<u8> = match value {
+                Value::String(s) => s.strip_prefix("0x").and_then(parse_hex),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                    .collect(),
+                _ => None,
+            }
+            .ok_or(None)?;
+            let mut bytes = (raw.len() as u32).to_le_bytes().to_vec();
+            bytes.extend(raw);
+            bytes
+        }
+        other => return Err(Some(format!("unsupported primitive type '{}'", other))),
+    };
+    Ok(bytes)
+}
+
+/// Derive the address of a PDA account from its IDL seeds
+///
+/// `accounts` holds the addresses chosen so far, by account name. Returns
+/// `None` while a seed is unresolvable: an account or argument not known
+/// yet, or a field of another account's data (that would need a fetch).
+pub fn derive_pda(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    pda: &IdlPda,
+    accounts: &HashMap<String, Pubkey>,
+    args: &Value,
+) -> Option<Pubkey> {
+    let program_id = Pubkey::from_str(&idl.address).ok()?;
+    let seeds = pda
+        .seeds
+        .iter()
+        .map(|seed| seed_bytes(idl, ix, seed, accounts, args))
+        .collect::<Option<Vec<Vec<u8>>>>()?;
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::try_find_program_address(&seeds, &program_id).map(|(address, _)| address)
+}
+
+/// Bytes of one PDA seed, as the program's `seeds = [...]` would produce them
+fn seed_bytes(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    seed: &IdlSeed,
+    accounts: &HashMap<String, Pubkey>,
+    args: &Value,
+) -> Option<Vec<u8>> {
+    match seed {
+        IdlSeed::Const { value } => value
+            .as_array()?
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect(),
+        IdlSeed::Account { path } => accounts.get(path).map(|key| key.to_bytes().to_vec()),
+        IdlSeed::Arg { path } => {
+            // `params.owner` is a field of a struct argument
+            let mut parts = path.split('.');
+            let name = parts.next()?;
+            let mut ty = ix.args.iter().find(|a| a.name == name)?.ty.clone();
+            let mut value = args.get(name)?;
+            for part in parts {
+                let IdlType::Complex(IdlTypeComplex::Defined { name, generics }) = &ty else {
+                    return None;
+                };
+                let type_def = idl.instantiate_type(name, generics).ok()?;
+                let IdlTypeDefTy::Struct { fields } = &type_def.ty else {
+                    return None;
+                };
+                ty = fields.iter().find(|f| f.name == part)?.ty.clone();
+                value = value.get(part)?;
+            }
+            let bytes = encode_type(idl, &ty, value).ok()?;
+            // Strings and byte vectors are seeded by content, without the length prefix
+            match ty {
+                IdlType::Primitive(name) if name == "string" || name == "bytes" => {
+                    Some(bytes[4..].to_vec())
+                }
+                _ => Some(bytes),
+            }
+        }
+    }
+}
+
+/// Turn typed-in text into the value of a primitive, checking it encodes
+///
+/// Used by interactive prompts; numbers that fit JSON stay numbers.
+pub fn parse_primitive(name: &str, input: &str) -> Result<Value, String> {
+    let input = input.trim();
+    let value = match name {
+        "bool" => match input {
+            "true" | "yes" | "y" | "1" => Value::Bool(true),
+            "false" | "no" | "n" | "0" => Value::Bool(false),
+            _ => return Err("expected true or false".to_string()),
+        },
+        "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "f32" | "f64" => {
+            serde_json::from_str::<serde_json::Number>(input)
+                .map(Value::Number)
+                .unwrap_or_else(|_| Value::String(input.to_string()))
+        }
+        _ => Value::String(input.to_string()),
+    };
+    match primitive_bytes(name, &value) {
+        Ok(_) => Ok(value),
+        Err(None) => Err(format!("not a valid {}", name)),
+        Err(Some(reason)) => Err(reason),
+    }
+}
+
+/// Parse a hex string (an odd length gets a leading zero)
+pub(crate) fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = if !hex.len().is_multiple_of(2) {
+        format!("0{}", hex)
+    } else {
+        hex.to_string()
+    };
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::decode_instruction;
+    use serde_json::json;
+
+    fn test_idl() -> Idl {
+        serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "configure",
+                "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                "accounts": [],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "big", "type": "u128" },
+                    { "name": "limit", "type": { "option": "i32" } },
+                    { "name": "owner", "type": "pubkey" },
+                    { "name": "label", "type": "string" },
+                    { "name": "fees", "type": { "vec": { "defined": { "name": "Fee" } } } },
+                    { "name": "side", "type": { "defined": { "name": "Side" } } }
+                ]
+            }],
+            "types": [
+                { "name": "Fee", "type": { "kind": "struct", "fields": [{ "name": "bps", "type": "u16" }] } },
+                { "name": "Side", "type": { "kind": "enum", "variants": [
+                    { "name": "Bid" },
+                    { "name": "Ask", "fields": [{ "name": "price", "type": "u64" }] }
+                ] } }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encode_roundtrips_through_decode() {
+        let idl = test_idl();
+        let args = json!({
+            "amount": 42,
+            "big": "340282366920938463463374607431768211455",
+            "limit": -7,
+            "owner": "11111111111111111111111111111111",
+            "label": "hi",
+            "fees": [{ "bps": 30 }, { "bps": 5 }],
+            "side": { "Ask": { "price": 9 } }
+        });
+
+        let data = encode_instruction(&idl, &idl.instructions[0], &args).unwrap();
+        let decoded = decode_instruction(&idl, &data).unwrap();
+        assert_eq!(decoded.args, args);
+        assert_eq!(decoded.consumed, data.len());
+    }
+
+    #[test]
+    fn test_encode_reports_path() {
+        let idl = test_idl();
+        let mut args = json!({
+            "amount": 1, "big": "0", "owner": "11111111111111111111111111111111",
+            "label": "", "fees": [{ "bps": 70000 }], "side": "Bid"
+        });
+        let err = encode_instruction(&idl, &idl.instructions[0], &args).unwrap_err();
+        match err {
+            PeriscopeError::EncodeError { path, .. } => assert_eq!(path, "configure.fees[0].bps"),
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        args["fees"] = json!([]);
+        args["side"] = json!("Hold");
+        assert!(encode_instruction(&idl, &idl.instructions[0], &args).is_err());
+    }
+
+    #[test]
+    fn test_encode_explains_invalid_values() {
+        let idl = test_idl();
+        let valid = json!({
+            "amount": 1, "big": "0", "limit": null, "owner": "11111111111111111111111111111111",
+            "label": "", "fees": [], "side": "Bid"
+        });
+        let reason = |field: &str, value: Value| {
+            let mut args = valid.clone();
+            args[field] = value;
+            match encode_instruction(&idl, &idl.instructions[0], &args).unwrap_err() {
+                PeriscopeError::EncodeError { reason, .. } => reason,
+                other => panic!("unexpected error {:?}", other),
+            }
+        };
+
+        assert_eq!(
+            reason("amount", json!(-1)),
+            "-1 is negative, but u64 is unsigned"
+        );
+        assert_eq!(
+            reason("fees", json!([{ "bps": 70000 }])),
+            "70000 is out of range for u16 (0..=65535)"
+        );
+        assert_eq!(
+            reason("owner", json!("not-a-key")),
+            "'not-a-key' is not a valid base58 pubkey"
+        );
+        assert_eq!(
+            reason("label", Value::Null),
+            "expected string, got null (only an Option may be null)"
+        );
+        assert_eq!(reason("amount", json!(1.5)), "expected u64, got 1.5");
+    }
+}
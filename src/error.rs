@@ -1,5 +1,6 @@
 //! Error types for Periscope
 
+use serde::Serialize;
 use thiserror::Error;
 
 /// Main error type for Periscope operations
@@ -11,11 +12,18 @@ pub enum PeriscopeError {
     #[error("Failed to decompress IDL data: {0}")]
     DecompressionError(String),
 
+    #[error("IDL exceeds the {limit}-byte size limit")]
+    IdlTooLarge { limit: usize },
+
+    #[error("Invalid IDL: {0}")]
+    InvalidIdl(String),
+
     #[error("Failed to parse IDL JSON: {0}")]
     ParseError(#[from] serde_json::Error),
 
+    #[cfg(feature = "fetch-rpc")]
     #[error("RPC error: {0}")]
-    RpcError(#[from] solana_client::client_error::ClientError),
+    RpcError(Box<solana_client::client_error::ClientError>),
 
     #[error("Invalid program ID: {0}")]
     InvalidProgramId(String),
@@ -34,7 +42,207 @@ pub enum PeriscopeError {
 
     #[error("HTTP error {status}: {url}")]
     HttpError { status: u16, url: String },
+
+    #[error("{url} returned {content_type} instead of JSON; did you mean the raw file URL?")]
+    NotJson { url: String, content_type: String },
+
+    #[error("Account {0} not found")]
+    AccountNotFound(String),
+
+    #[error("No account type in the IDL matches discriminator {0}")]
+    UnknownDiscriminator(String),
+
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("IDL signature verification failed: {0}")]
+    SignatureInvalid(String),
+
+    #[error("Rejected by trust policy: {0}")]
+    PolicyViolation(String),
+
+    #[error("Read-only mode: {0}")]
+    ReadOnly(String),
+
+    #[error("Failed to decode {path} at offset {offset}: {reason}")]
+    DecodeError {
+        path: String,
+        offset: usize,
+        reason: String,
+    },
+
+    #[error("Invalid value for {path}: {reason}")]
+    EncodeError { path: String, reason: String },
+
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    Usage(String),
+}
+
+impl PeriscopeError {
+    /// Stable machine-readable code, e.g. `PSC-IDL-404`.
+    ///
+    /// Codes never change meaning once published; new failure classes get new codes.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PeriscopeError::IdlNotFound(_) => "PSC-IDL-404",
+            PeriscopeError::DecompressionError(_) => "PSC-IDL-415",
+            PeriscopeError::IdlTooLarge { .. } => "PSC-IDL-413",
+            PeriscopeError::InvalidIdl(_) => "PSC-IDL-422",
+            PeriscopeError::ParseError(_) => "PSC-IDL-400",
+            #[cfg(feature = "fetch-rpc")]
+            PeriscopeError::RpcError(_) => "PSC-RPC-503",
+            PeriscopeError::InvalidProgramId(_) => "PSC-ARG-400",
+            PeriscopeError::ConfigError(_) => "PSC-CFG-400",
+            PeriscopeError::CacheError(_) => "PSC-CACHE-500",
+            PeriscopeError::IoError(e) if e.kind() == std::io::ErrorKind::NotFound => "PSC-IO-404",
+            PeriscopeError::IoError(_) => "PSC-IO-500",
+            PeriscopeError::NetworkError(_) => "PSC-NET-503",
+            PeriscopeError::HttpError { status: 404, .. } => "PSC-HTTP-404",
+            PeriscopeError::HttpError { status: 429, .. } => "PSC-HTTP-429",
+            PeriscopeError::HttpError { status, .. } if *status >= 500 => "PSC-HTTP-5XX",
+            PeriscopeError::HttpError { .. } => "PSC-HTTP-4XX",
+            PeriscopeError::NotJson { .. } => "PSC-HTTP-415",
+            PeriscopeError::AccountNotFound(_) => "PSC-ACC-404",
+            PeriscopeError::UnknownDiscriminator(_) => "PSC-DEC-404",
+            PeriscopeError::ChecksumMismatch { .. } => "PSC-UPD-409",
+            PeriscopeError::SignatureInvalid(_) => "PSC-SIG-401",
+            PeriscopeError::PolicyViolation(_) => "PSC-TRUST-403",
+            PeriscopeError::ReadOnly(_) => "PSC-RO-403",
+            PeriscopeError::DecodeError { .. } => "PSC-DEC-422",
+            PeriscopeError::EncodeError { .. } => "PSC-ENC-400",
+            PeriscopeError::TransactionFailed(_) => "PSC-TX-422",
+            PeriscopeError::NotFound(_) => "PSC-CLI-404",
+            PeriscopeError::Usage(_) => "PSC-CLI-400",
+        }
+    }
+
+    /// Suggestion for fixing the error, when there is a useful one
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            PeriscopeError::IdlNotFound(_) => {
+                Some("The program has not published an Anchor IDL; load one with --idl <file|url>")
+            }
+            #[cfg(feature = "fetch-rpc")]
+            PeriscopeError::RpcError(_) => {
+                Some("Check the RPC endpoint with `periscope doctor` or retry later")
+            }
+            PeriscopeError::NetworkError(_) => {
+                Some("Check the RPC endpoint with `periscope doctor` or retry later")
+            }
+            PeriscopeError::HttpError { status: 429, .. } => {
+                Some("Rate limited; retry later or use a dedicated RPC/HTTP endpoint")
+            }
+            PeriscopeError::ConfigError(_) => {
+                Some("Inspect the effective configuration with `periscope config show`")
+            }
+            PeriscopeError::UnknownDiscriminator(_) => {
+                Some("Pass --type <Account> to decode as a specific account type")
+            }
+            PeriscopeError::DecodeError { .. } => {
+                Some("The IDL may not match the deployed program; try --layout or a newer IDL")
+            }
+            PeriscopeError::ParseError(_) => Some("Check that the file is an Anchor IDL JSON"),
+            PeriscopeError::EncodeError { .. } => {
+                Some("Print the expected shape of each value with `periscope template <NAME>`")
+            }
+            PeriscopeError::NotJson { .. } => Some(
+                "Link to the raw file (e.g. raw.githubusercontent.com), not the page showing it",
+            ),
+            PeriscopeError::SignatureInvalid(_) => {
+                Some("Don't use this IDL until its publisher confirms the file and signature")
+            }
+            PeriscopeError::PolicyViolation(_) => {
+                Some("Review the [trust] section of the config file, or use a trusted source")
+            }
+            PeriscopeError::ReadOnly(_) => {
+                Some("Drop --read-only and the read_only config key to send transactions")
+            }
+            PeriscopeError::IdlTooLarge { .. } => {
+                Some("If the IDL is legitimately this large, raise max_idl_size_mib in the config")
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether retrying the same operation may succeed (transient failure)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(feature = "fetch-rpc")]
+            PeriscopeError::RpcError(_) => true,
+            PeriscopeError::NetworkError(_) => true,
+            PeriscopeError::HttpError { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Machine-readable description of this error
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            code: self.code(),
+            message: self.to_string(),
+            hint: self.hint(),
+            retryable: self.is_retryable(),
+        }
+    }
+}
+
+/// Error as printed by `--output json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    /// Stable error code (see [`PeriscopeError::code`])
+    pub code: &'static str,
+
+    /// Human-readable message
+    pub message: String,
+
+    /// Suggested fix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+
+    /// Whether the failure is transient
+    pub retryable: bool,
+}
+
+#[cfg(feature = "fetch-rpc")]
+impl From<solana_client::client_error::ClientError> for PeriscopeError {
+    fn from(e: solana_client::client_error::ClientError) -> Self {
+        PeriscopeError::RpcError(Box::new(e))
+    }
 }
 
 /// Result type alias for Periscope operations
 pub type PeriscopeResult<T> = Result<T, PeriscopeError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let err = PeriscopeError::IdlNotFound("x".into());
+        assert_eq!(err.code(), "PSC-IDL-404");
+        assert!(!err.is_retryable());
+        assert!(err.hint().is_some());
+
+        let err = PeriscopeError::HttpError {
+            status: 502,
+            url: "https://example.com".into(),
+        };
+        assert_eq!(err.code(), "PSC-HTTP-5XX");
+        assert!(err.is_retryable());
+
+        let json = serde_json::to_value(PeriscopeError::Usage("bad".into()).report()).unwrap();
+        assert_eq!(json["code"], "PSC-CLI-400");
+        assert!(json.get("hint").is_none());
+    }
+}
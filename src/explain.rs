@@ -0,0 +1,515 @@
+//! Explain the transactions a wallet is asked to sign
+//!
+//! Wallet adapters hand `signTransaction` / `signAllTransactions` an array
+//! of base64 payloads. `periscope explain` reads that JSON on stdin and
+//! spells out each message: fee payer, signers, and every instruction
+//! decoded with its program's IDL, so a transaction preview can be checked
+//! against what Periscope sees. Payloads may be bare messages (legacy or
+//! v0) or whole wire transactions; their signatures are skipped.
+
+use crate::builtin_programs::{known_program_name, SYSTEM_PROGRAM_ID};
+use crate::decode::decode_instruction;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{hex, Idl, IdlAccount, IdlAccountItem};
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// Compute budget program
+const COMPUTE_BUDGET_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Size of an address lookup table's metadata, before its addresses
+pub const LOOKUP_TABLE_META_LEN: usize = 56;
+
+/// A message as laid out on the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireMessage {
+    /// `None` for legacy messages, `Some(0)` for v0
+    pub version: Option<u8>,
+    pub num_required_signatures: u8,
+    pub num_readonly_signed: u8,
+    pub num_readonly_unsigned: u8,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: Hash,
+    pub instructions: Vec<WireInstruction>,
+    pub lookups: Vec<WireLookup>,
+}
+
+/// A compiled instruction: indexes into the message's accounts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireInstruction {
+    pub program_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// Accounts a v0 message loads from an address lookup table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireLookup {
+    pub table: Pubkey,
+    pub writable: Vec<u8>,
+    pub readonly: Vec<u8>,
+}
+
+/// An account of an explained instruction
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedAccount {
+    /// Account name in the IDL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Address (base58), or `<table>[index]` when its lookup table wasn't loaded
+    pub address: String,
+    pub signer: bool,
+    pub writable: bool,
+}
+
+/// One instruction of an explained message
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedInstruction {
+    pub program_id: String,
+
+    /// IDL or well-known name of the program
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+
+    /// Instruction name, when it decoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<Value>,
+    pub accounts: Vec<ExplainedAccount>,
+
+    /// Instruction data (hex)
+    pub data: String,
+
+    /// Why it couldn't be decoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One explained message
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedMessage {
+    /// `legacy` or `v0`
+    pub version: String,
+    pub fee_payer: String,
+    pub signers: Vec<String>,
+    pub recent_blockhash: String,
+    pub instructions: Vec<ExplainedInstruction>,
+}
+
+/// Payloads of the JSON wallets pass to `signTransaction`: an array of
+/// base64 strings, or a single one
+pub fn read_payloads(json: &str) -> PeriscopeResult<Vec<Vec<u8>>> {
+    let value: Value = serde_json::from_str(json).map_err(|e| {
+        PeriscopeError::Usage(format!("expected a JSON array of base64 payloads: {}", e))
+    })?;
+    let strings: Vec<&Value> = match &value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    strings
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let text = item.as_str().ok_or_else(|| {
+                PeriscopeError::Usage(format!("payload {} is not a base64 string", i + 1))
+            })?;
+            base64::engine::general_purpose::STANDARD
+                .decode(text.trim())
+                .map_err(|e| PeriscopeError::Usage(format!("payload {}: {}", i + 1, e)))
+        })
+        .collect()
+}
+
+/// Parse a message, or the message of a wire transaction
+pub fn parse_payload(bytes: &[u8]) -> PeriscopeResult<WireMessage> {
+    if let Some(message) = parse_message(bytes) {
+        return Ok(message);
+    }
+    let mut reader = Reader { bytes, pos: 0 };
+    let signatures = reader.compact_len();
+    signatures
+        .and_then(|n| reader.take(n * 64))
+        .and_then(|_| parse_message(&bytes[reader.pos..]))
+        .ok_or_else(|| {
+            PeriscopeError::Usage("payload is neither a message nor a transaction".into())
+        })
+}
+
+/// Parse a legacy or v0 message; `None` unless all of `bytes` is one
+pub fn parse_message(bytes: &[u8]) -> Option<WireMessage> {
+    let mut r = Reader { bytes, pos: 0 };
+    let version = match *bytes.first()? {
+        prefix if prefix & 0x80 != 0 => {
+            if prefix & 0x7f != 0 {
+                return None;
+            }
+            r.pos += 1;
+            Some(0)
+        }
+        _ => None,
+    };
+    let header = r.take(3)?;
+    let (num_required_signatures, num_readonly_signed, num_readonly_unsigned) =
+        (header[0], header[1], header[2]);
+
+    let keys = r.compact_len()?;
+    let account_keys = (0..keys).map(|_| r.pubkey()).collect::<Option<Vec<_>>>()?;
+    let recent_blockhash = Hash::new_from_array(r.take(32)?.try_into().ok()?);
+
+    let count = r.compact_len()?;
+    let instructions = (0..count)
+        .map(|_| {
+            let program_index = r.take(1)?[0];
+            let accounts = r.compact_bytes()?;
+            let data = r.compact_bytes()?;
+            Some(WireInstruction {
+                program_index,
+                accounts,
+                data,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let lookups = match version {
+        None => Vec::new(),
+        Some(_) => {
+            let count = r.compact_len()?;
+            (0..count)
+                .map(|_| {
+                    Some(WireLookup {
+                        table: r.pubkey()?,
+                        writable: r.compact_bytes()?,
+                        readonly: r.compact_bytes()?,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?
+        }
+    };
+
+    let valid = r.pos == bytes.len()
+        && num_required_signatures > 0
+        && usize::from(num_required_signatures) <= account_keys.len()
+        && instructions
+            .iter()
+            .all(|ix| usize::from(ix.program_index) < account_keys.len());
+    valid.then_some(WireMessage {
+        version,
+        num_required_signatures,
+        num_readonly_signed,
+        num_readonly_unsigned,
+        account_keys,
+        recent_blockhash,
+        instructions,
+        lookups,
+    })
+}
+
+/// Addresses stored in an address lookup table account
+pub fn lookup_table_addresses(data: &[u8]) -> Vec<Pubkey> {
+    data.get(LOOKUP_TABLE_META_LEN..)
+        .unwrap_or_default()
+        .chunks_exact(32)
+        .map(|chunk| Pubkey::new_from_array(chunk.try_into().expect("32-byte chunk")))
+        .collect()
+}
+
+impl WireMessage {
+    /// Every program an instruction of the message calls
+    pub fn program_ids(&self) -> Vec<Pubkey> {
+        let mut ids: Vec<Pubkey> = Vec::new();
+        for ix in &self.instructions {
+            let id = self.account_keys[usize::from(ix.program_index)];
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Accounts of the message in index order: static keys, then lookup
+    /// table writable and readonly accounts, resolved through `tables`
+    fn accounts(&self, tables: &HashMap<Pubkey, Vec<Pubkey>>) -> Vec<ExplainedAccount> {
+        let signers = usize::from(self.num_required_signatures);
+        let writable_signers = signers.saturating_sub(usize::from(self.num_readonly_signed));
+        let writable_unsigned = self
+            .account_keys
+            .len()
+            .saturating_sub(usize::from(self.num_readonly_unsigned));
+        let mut accounts: Vec<ExplainedAccount> = self
+            .account_keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| ExplainedAccount {
+                name: None,
+                address: key.to_string(),
+                signer: i < signers,
+                writable: if i < signers {
+                    i < writable_signers
+                } else {
+                    i < writable_unsigned
+                },
+            })
+            .collect();
+
+        let loaded = |lookup: &WireLookup, index: u8, writable: bool| {
+            let address = tables
+                .get(&lookup.table)
+                .and_then(|addresses| addresses.get(usize::from(index)))
+                .map(Pubkey::to_string)
+                .unwrap_or_else(|| format!("{}[{}]", lookup.table, index));
+            ExplainedAccount {
+                name: None,
+                address,
+                signer: false,
+                writable,
+            }
+        };
+        for lookup in &self.lookups {
+            accounts.extend(lookup.writable.iter().map(|i| loaded(lookup, *i, true)));
+        }
+        for lookup in &self.lookups {
+            accounts.extend(lookup.readonly.iter().map(|i| loaded(lookup, *i, false)));
+        }
+        accounts
+    }
+}
+
+/// Explain a message, decoding instructions with the IDL `idls` returns for
+/// their program and resolving lookup table accounts through `tables`
+pub fn explain<'a>(
+    message: &WireMessage,
+    idls: impl Fn(&Pubkey) -> Option<&'a Idl>,
+    tables: &HashMap<Pubkey, Vec<Pubkey>>,
+) -> ExplainedMessage {
+    let accounts = message.accounts(tables);
+    let instructions = message
+        .instructions
+        .iter()
+        .map(|ix| {
+            let program_id = message.account_keys[usize::from(ix.program_index)];
+            let mut explained = ExplainedInstruction {
+                program_id: program_id.to_string(),
+                program: known_program_name(&program_id).map(str::to_string),
+                instruction: None,
+                args: None,
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|i| {
+                        accounts
+                            .get(usize::from(*i))
+                            .cloned()
+                            .unwrap_or(ExplainedAccount {
+                                name: None,
+                                address: format!("#{}", i),
+                                signer: false,
+                                writable: false,
+                            })
+                    })
+                    .collect(),
+                data: hex(&ix.data),
+                error: None,
+            };
+
+            if let Some((name, args)) = builtin_instruction(&program_id, &ix.data) {
+                explained.instruction = Some(name.to_string());
+                explained.args = args;
+            } else if let Some(idl) = idls(&program_id) {
+                explained.program = Some(idl.metadata.name.clone());
+                match decode_instruction(idl, &ix.data) {
+                    Ok(decoded) => {
+                        if let Some(def) = idl.find_instruction(&decoded.name) {
+                            let mut names = Vec::new();
+                            flatten(&def.accounts, &mut names);
+                            for (account, name) in explained.accounts.iter_mut().zip(names) {
+                                account.name = Some(name.name.clone());
+                            }
+                        }
+                        explained.instruction = Some(decoded.name);
+                        explained.args = Some(decoded.args);
+                    }
+                    Err(e) => explained.error = Some(e.to_string()),
+                }
+            } else if explained.program.is_none() {
+                explained.error = Some(format!("no IDL for {}", program_id));
+            }
+            explained
+        })
+        .collect();
+
+    ExplainedMessage {
+        version: match message.version {
+            None => "legacy".to_string(),
+            Some(v) => format!("v{}", v),
+        },
+        fee_payer: accounts[0].address.clone(),
+        signers: accounts
+            .iter()
+            .filter(|a| a.signer)
+            .map(|a| a.address.clone())
+            .collect(),
+        recent_blockhash: message.recent_blockhash.to_string(),
+        instructions,
+    }
+}
+
+/// Name and arguments of the System and Compute Budget instructions a
+/// wallet sees most: transfers and priority fees
+fn builtin_instruction(program_id: &Pubkey, data: &[u8]) -> Option<(&'static str, Option<Value>)> {
+    let u32_at = |at: usize| Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let u64_at = |at: usize| Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?));
+    match *program_id {
+        SYSTEM_PROGRAM_ID => match u32_at(0)? {
+            0 => Some(("CreateAccount", None)),
+            1 => Some(("Assign", None)),
+            2 => Some(("Transfer", Some(json!({ "lamports": u64_at(4)? })))),
+            3 => Some(("CreateAccountWithSeed", None)),
+            4 => Some(("AdvanceNonceAccount", None)),
+            8 => Some(("Allocate", None)),
+            _ => None,
+        },
+        COMPUTE_BUDGET_ID => match *data.first()? {
+            1 => Some(("RequestHeapFrame", Some(json!({ "bytes": u32_at(1)? })))),
+            2 => Some(("SetComputeUnitLimit", Some(json!({ "units": u32_at(1)? })))),
+            3 => Some((
+                "SetComputeUnitPrice",
+                Some(json!({ "micro_lamports": u64_at(1)? })),
+            )),
+            4 => Some((
+                "SetLoadedAccountsDataSizeLimit",
+                Some(json!({ "bytes": u32_at(1)? })),
+            )),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn flatten<'a>(items: &'a [IdlAccountItem], out: &mut Vec<&'a IdlAccount>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => out.push(account),
+            IdlAccountItem::Group(group) => flatten(&group.accounts, out),
+        }
+    }
+}
+
+/// Cursor over wire bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(slice)
+    }
+
+    /// compact-u16 length prefix
+    fn compact_len(&mut self) -> Option<usize> {
+        let mut len = 0usize;
+        for shift in [0, 7, 14] {
+            let byte = self.take(1)?[0];
+            len |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(len);
+            }
+        }
+        None
+    }
+
+    fn compact_bytes(&mut self) -> Option<Vec<u8>> {
+        let len = self.compact_len()?;
+        self.take(len).map(<[u8]>::to_vec)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        Some(Pubkey::new_from_array(self.take(32)?.try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_ix::unsigned_transaction;
+    use crate::idl::parse_idl_json;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::Message;
+
+    #[test]
+    fn test_explain_wallet_payload() {
+        let idl = parse_idl_json(
+            r#"{
+            "address": "11111111111111111111111111111112",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "swap", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "pool", "writable": true }
+                ], "args": [{ "name": "amount", "type": "u64" }] }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let program_id = Pubkey::from_str_const("11111111111111111111111111111112");
+        let payer = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+
+        let mut swap = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        swap.extend(5u64.to_le_bytes());
+        let mut limit = vec![2];
+        limit.extend(200_000u32.to_le_bytes());
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(COMPUTE_BUDGET_ID, &limit, vec![]),
+                Instruction::new_with_bytes(
+                    program_id,
+                    &swap,
+                    vec![AccountMeta::new(payer, true), AccountMeta::new(pool, false)],
+                ),
+            ],
+            Some(&payer),
+        );
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(message.serialize());
+        let payloads = read_payloads(&format!(r#"["{}"]"#, encoded)).unwrap();
+        let parsed = parse_payload(&payloads[0]).unwrap();
+        assert_eq!(
+            parse_payload(&unsigned_transaction(&message)).unwrap(),
+            parsed
+        );
+
+        let explained = explain(
+            &parsed,
+            |id| (*id == program_id).then_some(&idl),
+            &HashMap::new(),
+        );
+        assert_eq!(explained.version, "legacy");
+        assert_eq!(explained.fee_payer, payer.to_string());
+        assert_eq!(explained.signers, [payer.to_string()]);
+
+        let budget = &explained.instructions[0];
+        assert_eq!(budget.instruction.as_deref(), Some("SetComputeUnitLimit"));
+        assert_eq!(budget.args, Some(json!({ "units": 200000 })));
+
+        let swap = &explained.instructions[1];
+        assert_eq!(swap.program.as_deref(), Some("amm"));
+        assert_eq!(swap.instruction.as_deref(), Some("swap"));
+        assert_eq!(swap.args, Some(json!({ "amount": 5 })));
+        assert_eq!(swap.accounts[1].name.as_deref(), Some("pool"));
+        assert_eq!(swap.accounts[1].address, pool.to_string());
+        assert!(swap.accounts[1].writable && !swap.accounts[1].signer);
+
+        assert!(parse_payload(&[0xff, 1, 2]).is_err());
+    }
+}
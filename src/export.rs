@@ -0,0 +1,208 @@
+//! Tabular export of decoded values
+//!
+//! Spreadsheets want one flat row per record. [`flatten`] turns a decoded
+//! value into columns named by dotted paths (`config.fee_bps`,
+//! `side.Limit.price`), with a [`VecStrategy`] for vectors and arrays.
+//! [`Table`] collects the rows of many records under one header; it is the
+//! layer every output format writes from. CSV is the only format written so
+//! far (it imports into Google Sheets and Excel as is); SQLite and Parquet
+//! writers would go on top of [`Table`] too.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Separator of vector elements joined into one cell
+pub const JOIN_SEPARATOR: &str = ";";
+
+/// How vectors and arrays become cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VecStrategy {
+    /// One cell, elements separated by [`JOIN_SEPARATOR`]
+    #[default]
+    Join,
+    /// One row per element, the other columns repeated
+    Explode,
+    /// One cell holding the vector as JSON
+    Json,
+}
+
+/// A flat record: column name to cell
+pub type Row = BTreeMap<String, String>;
+
+/// Flatten `value` into rows (more than one only when exploding vectors)
+pub fn flatten(value: &Value, strategy: VecStrategy) -> Vec<Row> {
+    let mut rows = vec![Row::new()];
+    flatten_into(value, "", strategy, &mut rows);
+    rows
+}
+
+fn flatten_into(value: &Value, path: &str, strategy: VecStrategy, rows: &mut Vec<Row>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten_into(value, &join_path(path, key), strategy, rows);
+            }
+        }
+        Value::Array(items) => match strategy {
+            VecStrategy::Join => set(rows, path, join_cells(items)),
+            VecStrategy::Json => set(rows, path, value.to_string()),
+            VecStrategy::Explode if items.is_empty() => set(rows, path, String::new()),
+            VecStrategy::Explode => {
+                let mut exploded = Vec::with_capacity(rows.len() * items.len());
+                for item in items {
+                    let mut copies = rows.clone();
+                    flatten_into(item, path, strategy, &mut copies);
+                    exploded.extend(copies);
+                }
+                *rows = exploded;
+            }
+        },
+        other => set(rows, path, cell(other)),
+    }
+}
+
+fn set(rows: &mut [Row], path: &str, cell: String) {
+    let column = if path.is_empty() { "value" } else { path };
+    for row in rows {
+        row.insert(column.to_string(), cell.clone());
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Vector elements in one cell: scalars as is, nested values as JSON
+fn join_cells(items: &[Value]) -> String {
+    items
+        .iter()
+        .map(|item| match item {
+            Value::Object(_) | Value::Array(_) => item.to_string(),
+            other => cell(other),
+        })
+        .collect::<Vec<_>>()
+        .join(JOIN_SEPARATOR)
+}
+
+/// A scalar as a cell: strings unquoted, null empty
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Object(_) => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Rows of many records under one header
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    /// Column names, in order of first appearance
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Empty table with these leading columns
+    pub fn with_columns(columns: &[&str]) -> Self {
+        Table {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a record: `leading` cells for the leading columns, then `value`
+    /// flattened with `strategy`. Columns new to the table are appended and
+    /// left empty in earlier rows.
+    pub fn push(&mut self, leading: &[String], value: &Value, strategy: VecStrategy) {
+        for row in flatten(value, strategy) {
+            for column in row.keys() {
+                if !self.columns.contains(column) {
+                    self.columns.push(column.clone());
+                }
+            }
+            let mut cells = leading.to_vec();
+            cells.extend(
+                self.columns[leading.len()..]
+                    .iter()
+                    .map(|column| row.get(column).cloned().unwrap_or_default()),
+            );
+            self.rows.push(cells);
+        }
+    }
+
+    /// Write as CSV (RFC 4180: cells quoted when they hold a comma, quote
+    /// or line break)
+    pub fn write_csv(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "{}", csv_line(&self.columns))?;
+        for row in &self.rows {
+            let mut cells = row.clone();
+            cells.resize(self.columns.len(), String::new());
+            writeln!(out, "{}", csv_line(&cells))?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_line(cells: &[String]) -> String {
+    cells
+        .iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_strategies() {
+        let pool = json!({
+            "fee": 30,
+            "config": { "admin": "Adm1n", "paused": false },
+            "side": { "Limit": { "price": 5 } },
+            "tiers": [1, 2],
+            "memo": null
+        });
+
+        let rows = flatten(&pool, VecStrategy::Join);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["config.admin"], "Adm1n");
+        assert_eq!(rows[0]["side.Limit.price"], "5");
+        assert_eq!(rows[0]["tiers"], "1;2");
+        assert_eq!(rows[0]["memo"], "");
+        assert_eq!(flatten(&pool, VecStrategy::Json)[0]["tiers"], "[1,2]");
+
+        let rows = flatten(&pool, VecStrategy::Explode);
+        let tiers: Vec<&str> = rows.iter().map(|r| r["tiers"].as_str()).collect();
+        assert_eq!(tiers, ["1", "2"]);
+        assert!(rows.iter().all(|r| r["fee"] == "30"));
+
+        let mut table = Table::with_columns(&["address"]);
+        table.push(&["A".into()], &json!({ "fee": 1 }), VecStrategy::Join);
+        table.push(
+            &["B".into()],
+            &json!({ "fee": 2, "name": "x, \"y\"" }),
+            VecStrategy::Join,
+        );
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "address,fee,name\nA,1,\nB,2,\"x, \"\"y\"\"\"\n"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Instruction groups by name prefix
+//!
+//! Large programs namespace their instructions (`admin_set_fee`,
+//! `lp_deposit`, `perp_open`). Grouping by the first word of the name turns
+//! a flat list of 150 instructions into a dozen groups; prefixes used by a
+//! single instruction go into one ungrouped bucket at the end.
+
+use serde::Serialize;
+use std::ops::Range;
+
+/// Instructions sharing a prefix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NameGroup<'a> {
+    /// First word of the names, `None` for the ungrouped bucket
+    pub prefix: Option<&'a str>,
+
+    /// Names in the group, in their original order
+    pub names: Vec<&'a str>,
+}
+
+/// First word of a snake_case or camelCase name (`admin` for `admin_set_fee`
+/// and `adminSetFee`), `None` for a single-word name
+pub fn name_prefix(name: &str) -> Option<&str> {
+    let end = name
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '_' || c.is_uppercase())
+        .map(|(i, _)| i)?;
+    Some(&name[..end])
+}
+
+/// Group `names` by prefix, groups in order of first appearance and
+/// singletons last
+pub fn group_by_prefix<'a>(names: &[&'a str]) -> Vec<NameGroup<'a>> {
+    let mut groups: Vec<NameGroup<'a>> = Vec::new();
+    for name in names {
+        let prefix = name_prefix(name);
+        match groups
+            .iter_mut()
+            .find(|g| prefix.is_some() && g.prefix == prefix)
+        {
+            Some(group) => group.names.push(name),
+            None => groups.push(NameGroup {
+                prefix,
+                names: vec![name],
+            }),
+        }
+    }
+
+    let mut other = NameGroup {
+        prefix: None,
+        names: Vec::new(),
+    };
+    groups.retain(|group| {
+        if group.names.len() > 1 {
+            return true;
+        }
+        other.names.extend(&group.names);
+        false
+    });
+    if !other.names.is_empty() {
+        groups.push(other);
+    }
+    groups
+}
+
+/// The part of `groups` on a page: names at positions `range` when the
+/// groups are read in order, empty groups dropped
+pub fn page_groups<'a>(groups: &[NameGroup<'a>], range: Range<usize>) -> Vec<NameGroup<'a>> {
+    let mut position = 0;
+    let mut paged = Vec::new();
+    for group in groups {
+        let names: Vec<&str> = group
+            .names
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| range.contains(&(position + i)))
+            .map(|(_, name)| *name)
+            .collect();
+        position += group.names.len();
+        if !names.is_empty() {
+            paged.push(NameGroup {
+                prefix: group.prefix,
+                names,
+            });
+        }
+    }
+    paged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_prefix() {
+        assert_eq!(name_prefix("admin_set_fee"), Some("admin"));
+        assert_eq!(name_prefix("adminSetFee"), Some("admin"));
+        assert_eq!(name_prefix("initialize"), None);
+
+        let groups = group_by_prefix(&[
+            "admin_pause",
+            "lp_deposit",
+            "initialize",
+            "admin_set_fee",
+            "lp_withdraw",
+            "swap_exact_in",
+        ]);
+        let paged: Vec<Vec<&str>> = page_groups(&groups, 1..3)
+            .into_iter()
+            .map(|g| g.names)
+            .collect();
+        assert_eq!(paged, [vec!["admin_set_fee"], vec!["lp_deposit"]]);
+
+        let groups: Vec<(Option<&str>, Vec<&str>)> =
+            groups.into_iter().map(|g| (g.prefix, g.names)).collect();
+        assert_eq!(
+            groups,
+            [
+                (Some("admin"), vec!["admin_pause", "admin_set_fee"]),
+                (Some("lp"), vec!["lp_deposit", "lp_withdraw"]),
+                (None, vec!["initialize", "swap_exact_in"]),
+            ]
+        );
+    }
+}
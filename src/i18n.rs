@@ -0,0 +1,928 @@
+//! Message catalog for user-facing CLI text
+//!
+//! Headers, labels, hints and CLI error messages are looked up by [`Msg`]
+//! key in the active language. The language comes from `--lang`, then the
+//! `lang` config key, then `$LANG`, falling back to English.
+//!
+//! Adding a locale means adding a [`Lang`] variant and a column to
+//! [`catalog`]; missing translations are a compile error, not a runtime one.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Active language, set once at startup
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Supported output languages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    /// English
+    #[default]
+    En,
+    /// Spanish
+    Es,
+}
+
+impl Lang {
+    /// Language codes accepted by `--lang`
+    pub const CODES: &'static [&'static str] = &["en", "es"];
+
+    /// Detect the language from a locale string like `es_AR.UTF-8`
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale.split(['_', '-', '.']).next()?;
+        code.to_ascii_lowercase().parse().ok()
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Lang::En),
+            "es" => Ok(Lang::Es),
+            other => Err(format!(
+                "unsupported language '{}' (available: {})",
+                other,
+                Lang::CODES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::En => write!(f, "en"),
+            Lang::Es => write!(f, "es"),
+        }
+    }
+}
+
+/// Message keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // Headers and labels
+    ProgramHeader,
+    Version,
+    Address,
+    Spec,
+    CanonicalHash,
+    Cluster,
+    Slot,
+    ReadAt,
+    Fetched,
+    Description,
+    Annotated,
+    Deprecated,
+    Summary,
+    SummaryCounts,
+    Ungrouped,
+    InstructionsHeader,
+    InstructionHeader,
+    Discriminator,
+    AccountsCount,
+    ArgumentsCount,
+    ErrorsHeader,
+    Code,
+    Name,
+    Message,
+    None,
+    AccountHeader,
+    Size,
+    ByteCount,
+    Layout,
+    Repr,
+    Fields,
+    Note,
+    TrailingBytes,
+    Offset,
+    Bytes,
+    AddressHeader,
+    Kind,
+    Curve,
+    OnCurve,
+    OffCurve,
+    Owner,
+    Balance,
+    Rent,
+    RentExempt,
+    RentNotExempt,
+    TypeHeader,
+    TypeNotFound,
+    FieldsCount,
+    VariantsCount,
+    TypesCount,
+    AccountTypesCount,
+    EventsCount,
+    SearchHeader,
+    GrepHeader,
+    InstructionDataHeader,
+    RecentHeader,
+    RenamesHeader,
+    NoRenames,
+    SkippedHeader,
+    SkippedNote,
+    ChangedSince,
+    ChangesAdded,
+    ChangesRemoved,
+    ChangesChanged,
+    UpToDate,
+    UpdateAvailable,
+    UpdateNoAsset,
+    UpdateNoChecksum,
+    UpdateConfirm,
+    UpdateInstalled,
+    DoctorHeader,
+    TimingHeader,
+    TimingTotal,
+    DoctorFailed,
+    UsingRecent,
+    ReadFromRegistry,
+    TokenListSkipped,
+    RecentCleared,
+
+    // Errors and hints
+    ErrorPrefix,
+    InstructionNotFound,
+    AvailableInstructions,
+    ProgramIdRequired,
+    InvalidProgramId,
+    InvalidAddress,
+    InvalidPattern,
+
+    // Config command
+    ConfigHeader,
+    ConfigFile,
+    ConfigFileExists,
+    ProjectFile,
+    ProgramId,
+    Yes,
+    NoUsingDefaults,
+    RpcUrl,
+    Theme,
+    Language,
+    SavedConfig,
+    NothingToSet,
+    ConfigSetUsage,
+    ConfigUnset,
+    EditorFailed,
+
+    // REPL
+    ReplLoaded,
+    ReplUnknownCommand,
+    ReplUsage,
+    ReplInvalidHex,
+    ReplUndecodable,
+    ReplCommands,
+    ReplHistory,
+    HelpInfo,
+    HelpInstructions,
+    HelpGroups,
+    HelpInstruction,
+    HelpTypes,
+    HelpType,
+    HelpAccounts,
+    HelpEvents,
+    HelpErrors,
+    HelpSearch,
+    HelpDecode,
+    HelpHistory,
+    HelpExit,
+
+    // build-ix
+    BuiltInstructionHeader,
+    InstructionData,
+    BuildUsePda,
+    BuildFixedAddress,
+    BuildSetOption,
+    BuildItemCount,
+    BuildCancelled,
+    BuildNeedsTerminal,
+    BuildPayerRequired,
+    InvalidBlockhash,
+    NotNonceAccount,
+
+    // send-ix
+    KeypairUnreadable,
+    ReadOnlySend,
+    SendExtraSigner,
+    SendMainnetConfirm,
+    SendMainnetNeedsYes,
+    SendConfirming,
+    SendNotConfirmed,
+    SentHeader,
+    Signature,
+    ProgramLogs,
+
+    // simulate
+    SimulationHeader,
+    Result,
+    Success,
+    ComputeUnits,
+    ComputeUnitsByProgram,
+    OwnUnits,
+    SweepHeader,
+    InvalidSweep,
+
+    // mock
+    MockHeader,
+    Seed,
+    BorshData,
+
+    // links
+    Explorer,
+    ExplorersHeader,
+    Registries,
+    TokenLists,
+
+    // rpc headers
+    RpcHeaders,
+    InvalidRpcHeader,
+
+    // trust
+    TrustPolicy,
+    TrustSummary,
+
+    // anchor-version
+    AnchorVersionHeader,
+    AnchorVersionRange,
+    AnchorBinaryVersion,
+    NotFoundInBinary,
+    Evidence,
+    AnchorFeatures,
+    ClientLibraries,
+
+    // xcheck
+    XcheckHeader,
+    XcheckMismatches,
+    XcheckConsistent,
+    XcheckOnlyOn,
+    XcheckDiffers,
+    XcheckFailed,
+
+    // matrix
+    MatrixHeader,
+    MatrixLegend,
+
+    // pdas
+    PdasHeader,
+    PdaSeeds,
+    PdaUsedBy,
+
+    // pagination
+    PageFooter,
+    PageNext,
+
+    // quarantine
+    Quarantined,
+    QuarantineHeader,
+    QuarantineAccountType,
+    QuarantineAttempts,
+    QuarantineError,
+    QuarantineRetried,
+    QuarantineCleared,
+    QuarantineNotFound,
+
+    // scan
+    ScanHeader,
+    ScanLamports,
+    ScanSizes,
+    ScanFields,
+    ScanUndecoded,
+    ScanFieldNotNumeric,
+    ScanExported,
+
+    // explain
+    ExplainHeader,
+    FeePayer,
+    Signers,
+    RecentBlockhash,
+    ExplainInstructions,
+    ExplainUndecoded,
+
+    // uses
+    UsesHeader,
+
+    // lint
+    LintHeader,
+    LintFailed,
+
+    // preflight
+    PreflightHeader,
+    PreflightFailed,
+    InvalidAccountArg,
+
+    // overlap
+    OverlapHeader,
+    OverlapNone,
+    OverlapFailed,
+    OverlapTooFew,
+
+    // cache
+    CacheExported,
+    CacheImported,
+
+    // sign / verify
+    IdlSigned,
+    IdlSignatureValid,
+
+    // bundle
+    BundleHeader,
+    BundleNoAddress,
+    BundleUnsupported,
+    BundleProgramRequired,
+    BundleProgramNotFound,
+
+    // compat
+    CompatHeader,
+    CompatUnchanged,
+
+    // account-diff
+    Before,
+    After,
+    LiveAccount,
+    ChangedFields,
+    DiffDifferentAccounts,
+
+    // snapshot
+    AccountTypeNotFound,
+    SnapshotHeader,
+    SnapshotValidatorHint,
+
+    // test-util
+    GoldenMismatch,
+    GoldenUnreadable,
+}
+
+/// Translations, one column per `Lang` variant (`{0}`, `{1}`... are arguments)
+fn catalog(msg: Msg) -> [&'static str; 2] {
+    match msg {
+        Msg::ProgramHeader => ["Program: {0}", "Programa: {0}"],
+        Msg::Version => ["Version", "Versión"],
+        Msg::Address => ["Address", "Dirección"],
+        Msg::Spec => ["Spec", "Especificación"],
+        Msg::CanonicalHash => ["Canonical hash", "Hash canónico"],
+        Msg::Cluster => ["Cluster", "Clúster"],
+        Msg::Slot => ["Slot", "Slot"],
+        Msg::ReadAt => ["{0} (read {1})", "{0} (leído {1})"],
+        Msg::Fetched => ["Fetched", "Obtenido"],
+        Msg::Description => ["Description", "Descripción"],
+        Msg::Annotated => ["Annotations", "Anotaciones"],
+        Msg::Deprecated => ["deprecated", "obsoleto"],
+        Msg::Summary => ["Summary", "Resumen"],
+        Msg::SummaryCounts => [
+            "{0} Instructions, {1} Accounts, {2} Types, {3} Events, {4} Errors",
+            "{0} Instrucciones, {1} Cuentas, {2} Tipos, {3} Eventos, {4} Errores",
+        ],
+        Msg::InstructionsHeader => [
+            "Instructions for {0} ({1} total)",
+            "Instrucciones de {0} ({1} en total)",
+        ],
+        Msg::InstructionHeader => ["Instruction: {0}", "Instrucción: {0}"],
+        Msg::Discriminator => ["Discriminator", "Discriminador"],
+        Msg::AccountsCount => ["Accounts ({0})", "Cuentas ({0})"],
+        Msg::ArgumentsCount => ["Arguments ({0})", "Argumentos ({0})"],
+        Msg::ErrorsHeader => ["Errors for {0} ({1} total)", "Errores de {0} ({1} en total)"],
+        Msg::Code => ["Code", "Código"],
+        Msg::Name => ["Name", "Nombre"],
+        Msg::Message => ["Message", "Mensaje"],
+        Msg::None => ["(none)", "(ninguno)"],
+        Msg::Ungrouped => ["ungrouped", "sin grupo"],
+        Msg::AccountHeader => ["Account: {0}", "Cuenta: {0}"],
+        Msg::Size => ["Size", "Tamaño"],
+        Msg::ByteCount => ["{0} bytes", "{0} bytes"],
+        Msg::Layout => ["Layout", "Formato"],
+        Msg::Repr => ["Representation", "Representación"],
+        Msg::Fields => ["Fields", "Campos"],
+        Msg::Note => ["Note:", "Nota:"],
+        Msg::TrailingBytes => [
+            "{0} trailing bytes after offset {1} were not consumed by the IDL layout",
+            "{0} bytes finales a partir del offset {1} no forman parte del formato del IDL",
+        ],
+        Msg::Offset => ["Offset", "Offset"],
+        Msg::Bytes => ["Bytes", "Bytes"],
+        Msg::AddressHeader => ["Address: {0}", "Dirección: {0}"],
+        Msg::Kind => ["Kind", "Tipo"],
+        Msg::Curve => ["Curve", "Curva"],
+        Msg::OnCurve => ["on curve (keypair address)", "en la curva (dirección de keypair)"],
+        Msg::OffCurve => ["off curve (PDA)", "fuera de la curva (PDA)"],
+        Msg::Owner => ["Owner", "Propietario"],
+        Msg::Balance => ["Balance", "Saldo"],
+        Msg::Rent => ["Rent", "Renta"],
+        Msg::RentExempt => ["exempt", "exenta"],
+        Msg::RentNotExempt => ["not exempt", "no exenta"],
+        Msg::TypeHeader => ["Type: {0}", "Tipo: {0}"],
+        Msg::TypeNotFound => ["Type '{0}' not found", "No se encontró el tipo '{0}'"],
+        Msg::FieldsCount => ["Fields ({0})", "Campos ({0})"],
+        Msg::VariantsCount => ["Variants ({0})", "Variantes ({0})"],
+        Msg::TypesCount => ["Types ({0})", "Tipos ({0})"],
+        Msg::AccountTypesCount => ["Account types ({0})", "Tipos de cuenta ({0})"],
+        Msg::EventsCount => ["Events ({0})", "Eventos ({0})"],
+        Msg::SearchHeader => ["Matches for '{0}' ({1})", "Coincidencias de '{0}' ({1})"],
+        Msg::GrepHeader => [
+            "Matches for '{0}' in cached IDLs ({1} in {2} of {3} programs)",
+            "Coincidencias de '{0}' en IDLs en caché ({1} en {2} de {3} programas)",
+        ],
+        Msg::DoctorHeader => ["Periscope doctor", "Diagnóstico de Periscope"],
+        Msg::TimingHeader => ["Timing", "Tiempos"],
+        Msg::TimingTotal => ["total", "total"],
+        Msg::DoctorFailed => ["{0} check(s) failed", "{0} comprobación(es) fallaron"],
+        Msg::UpToDate => ["periscope {0} is up to date", "periscope {0} está actualizado"],
+        Msg::UpdateAvailable => [
+            "periscope {0} is available (installed: {1})",
+            "periscope {0} está disponible (instalado: {1})",
+        ],
+        Msg::UpdateNoAsset => [
+            "No prebuilt binary for {0}; update with `cargo install {1}`",
+            "No hay binario para {0}; actualiza con `cargo install {1}`",
+        ],
+        Msg::UpdateNoChecksum => [
+            "Release has no checksum for {0}, refusing to install it",
+            "La versión no publica checksum para {0}, no se instalará",
+        ],
+        Msg::UpdateConfirm => [
+            "Replace {0} with version {1}?",
+            "¿Reemplazar {0} por la versión {1}?",
+        ],
+        Msg::UpdateInstalled => [
+            "Updated to {0} (checksum verified)",
+            "Actualizado a {0} (checksum verificado)",
+        ],
+        Msg::SkippedHeader => [
+            "Skipped (unsupported or malformed, {0})",
+            "Omitidos (no soportados o mal formados, {0})",
+        ],
+        Msg::SkippedNote => [
+            "{0} IDL item(s) could not be parsed and were skipped; `inspect` lists them",
+            "{0} elemento(s) del IDL no se pudieron leer y se omitieron; `inspect` los muestra",
+        ],
+        Msg::ChangedSince => [
+            "Changed since last viewed ({0})",
+            "Cambios desde la última consulta ({0})",
+        ],
+        Msg::ChangesAdded => ["{0} added", "{0} añadidos"],
+        Msg::ChangesRemoved => ["{0} removed", "{0} eliminados"],
+        Msg::ChangesChanged => ["{0} changed", "{0} modificados"],
+        Msg::RecentHeader => ["Recent programs ({0})", "Programas recientes ({0})"],
+        Msg::RenamesHeader => ["Rust names for {0} ({1} renamed)", "Nombres Rust de {0} ({1} renombrados)"],
+        Msg::NoRenames => [
+            "Every name maps to a Rust identifier by case conversion alone",
+            "Todos los nombres se convierten en identificadores Rust con solo ajustar mayúsculas",
+        ],
+        Msg::UsingRecent => [
+            "Using most recent program {0} ({1})",
+            "Usando el programa más reciente {0} ({1})",
+        ],
+        Msg::ReadFromRegistry => [
+            "No on-chain IDL for {0}; read from registry {1}",
+            "Sin IDL on-chain para {0}; leído del registro {1}",
+        ],
+        Msg::TokenListSkipped => [
+            "Token list {0} skipped: {1}",
+            "Lista de tokens {0} omitida: {1}",
+        ],
+        Msg::RecentCleared => ["Cleared recent programs", "Programas recientes borrados"],
+        Msg::InstructionDataHeader => ["Instruction data: {0}", "Datos de instrucción: {0}"],
+
+        Msg::ErrorPrefix => ["Error:", "Error:"],
+        Msg::InstructionNotFound => [
+            "Instruction '{0}' not found",
+            "No se encontró la instrucción '{0}'",
+        ],
+        Msg::AvailableInstructions => ["Available instructions:", "Instrucciones disponibles:"],
+        Msg::ProgramIdRequired => [
+            "Program ID is required when fetching on-chain. Use --idl to load from file/URL instead.",
+            "Se requiere el ID del programa para leer on-chain. Usa --idl para cargar desde un archivo o URL.",
+        ],
+        Msg::InvalidProgramId => ["Invalid program ID: {0}", "ID de programa inválido: {0}"],
+        Msg::InvalidAddress => ["Invalid address: {0}", "Dirección inválida: {0}"],
+        Msg::InvalidPattern => ["Invalid pattern: {0}", "Patrón inválido: {0}"],
+
+        Msg::ConfigHeader => ["Periscope Configuration:", "Configuración de Periscope:"],
+        Msg::ConfigFile => ["Config file", "Archivo de configuración"],
+        Msg::ConfigFileExists => ["File exists", "Archivo existe"],
+        Msg::ProjectFile => ["Project file", "Archivo de proyecto"],
+        Msg::ProgramId => ["Program ID", "ID de programa"],
+        Msg::Yes => ["yes", "sí"],
+        Msg::NoUsingDefaults => ["no (using defaults)", "no (usando valores por defecto)"],
+        Msg::RpcUrl => ["RPC URL", "URL de RPC"],
+        Msg::Theme => ["Theme", "Tema"],
+        Msg::Language => ["Language", "Idioma"],
+        Msg::SavedConfig => ["Saved config to {0}", "Configuración guardada en {0}"],
+        Msg::NothingToSet => ["No value provided to set.", "No se indicó ningún valor."],
+        Msg::ConfigSetUsage => [
+            "Usage: periscope config set <KEY> <VALUE>  (or --url <RPC_URL>, --lang <LANG>)",
+            "Uso: periscope config set <CLAVE> <VALOR>  (o --url <RPC_URL>, --lang <IDIOMA>)",
+        ],
+        Msg::ConfigUnset => ["reset to default", "restablecido al valor por defecto"],
+        Msg::EditorFailed => [
+            "Editor '{0}' exited with an error; config left as is",
+            "El editor '{0}' terminó con un error; la configuración no se modificó",
+        ],
+
+        Msg::ReplLoaded => [
+            "{0} v{1} loaded. Type 'help' for commands, 'exit' to quit.",
+            "{0} v{1} cargado. Escribe 'help' para ver los comandos, 'exit' para salir.",
+        ],
+        Msg::ReplUnknownCommand => [
+            "Unknown command '{0}'. Type 'help' for commands.",
+            "Comando desconocido '{0}'. Escribe 'help' para ver los comandos.",
+        ],
+        Msg::ReplUsage => ["Usage: {0}", "Uso: {0}"],
+        Msg::ReplInvalidHex => ["Invalid hex data: {0}", "Datos hex inválidos: {0}"],
+        Msg::ReplUndecodable => [
+            "No instruction or account matches discriminator {0}",
+            "Ninguna instrucción o cuenta coincide con el discriminador {0}",
+        ],
+        Msg::ReplCommands => ["Commands", "Comandos"],
+        Msg::ReplHistory => ["History", "Historial"],
+        Msg::HelpInfo => ["Program overview", "Resumen del programa"],
+        Msg::HelpInstructions => [
+            "List instructions (one prefix group with an argument)",
+            "Listar instrucciones (un grupo de prefijo con argumento)",
+        ],
+        Msg::HelpGroups => [
+            "Instruction groups by name prefix, collapsed",
+            "Grupos de instrucciones por prefijo, contraídos",
+        ],
+        Msg::HelpInstruction => ["Show an instruction", "Mostrar una instrucción"],
+        Msg::HelpTypes => ["List types", "Listar tipos"],
+        Msg::HelpType => ["Show a type definition", "Mostrar la definición de un tipo"],
+        Msg::HelpAccounts => ["List account types", "Listar tipos de cuenta"],
+        Msg::HelpEvents => ["List events", "Listar eventos"],
+        Msg::HelpErrors => ["List error codes", "Listar códigos de error"],
+        Msg::HelpSearch => ["Search all names", "Buscar en todos los nombres"],
+        Msg::HelpDecode => [
+            "Decode instruction or account data",
+            "Decodificar datos de instrucción o cuenta",
+        ],
+        Msg::HelpHistory => ["Show command history", "Mostrar el historial"],
+        Msg::HelpExit => ["Leave the REPL", "Salir del REPL"],
+
+        Msg::BuiltInstructionHeader => ["Built instruction: {0}", "Instrucción construida: {0}"],
+        Msg::InstructionData => ["Data", "Datos"],
+        Msg::BuildUsePda => [
+            "{0}: use derived PDA {1}?",
+            "{0}: ¿usar la PDA derivada {1}?",
+        ],
+        Msg::BuildFixedAddress => ["(fixed address)", "(dirección fija)"],
+        Msg::BuildSetOption => ["Set {0}?", "¿Indicar {0}?"],
+        Msg::BuildItemCount => ["{0}: number of items", "{0}: cantidad de elementos"],
+        Msg::BuildCancelled => [
+            "Cancelled; no instruction was built",
+            "Cancelado; no se construyó ninguna instrucción",
+        ],
+        Msg::BuildPayerRequired => [
+            "No signer account to pay for the message; pass --payer <PUBKEY>",
+            "Ninguna cuenta firmante puede pagar el mensaje; indica --payer <PUBKEY>",
+        ],
+        Msg::InvalidBlockhash => ["Invalid blockhash: {0}", "Blockhash inválido: {0}"],
+        Msg::NotNonceAccount => [
+            "{0} is not an initialized nonce account",
+            "{0} no es una cuenta nonce inicializada",
+        ],
+        Msg::KeypairUnreadable => [
+            "Could not read keypair {0}: {1}",
+            "No se pudo leer el keypair {0}: {1}",
+        ],
+        Msg::ReadOnlySend => [
+            "sending transactions is disabled",
+            "el envío de transacciones está desactivado",
+        ],
+        Msg::SendExtraSigner => [
+            "Account {0} ({1}) must sign too; only the --keypair signer is supported",
+            "La cuenta {0} ({1}) también debe firmar; solo se admite el firmante de --keypair",
+        ],
+        Msg::SendMainnetConfirm => [
+            "Send {0} to mainnet ({1})?",
+            "¿Enviar {0} a mainnet ({1})?",
+        ],
+        Msg::SendMainnetNeedsYes => [
+            "Refusing to send to mainnet without confirmation; pass --yes",
+            "No se envía a mainnet sin confirmación; indica --yes",
+        ],
+        Msg::SendConfirming => ["Confirming {0}...", "Confirmando {0}..."],
+        Msg::SendNotConfirmed => [
+            "{0} not confirmed after {1}s; it may still land, check it later",
+            "{0} sin confirmar tras {1}s; aún puede confirmarse, revísalo más tarde",
+        ],
+        Msg::SentHeader => ["Transaction confirmed", "Transacción confirmada"],
+        Msg::Signature => ["Signature", "Firma"],
+        Msg::ProgramLogs => ["Logs ({0})", "Logs ({0})"],
+        Msg::SimulationHeader => ["Simulation: {0}", "Simulación: {0}"],
+        Msg::Result => ["Result", "Resultado"],
+        Msg::Success => ["success", "éxito"],
+        Msg::ComputeUnits => ["Compute units", "Unidades de cómputo"],
+        Msg::ComputeUnitsByProgram => [
+            "Compute units by invocation",
+            "Unidades de cómputo por invocación",
+        ],
+        Msg::OwnUnits => ["own {0}", "propias {0}"],
+        Msg::SweepHeader => [
+            "Compute units by {0}",
+            "Unidades de cómputo según {0}",
+        ],
+        Msg::Explorer => ["Explorer", "Explorador"],
+        Msg::ExplorersHeader => ["Explorers", "Exploradores"],
+        Msg::Registries => ["IDL registries", "Registros de IDL"],
+        Msg::TokenLists => ["Token lists", "Listas de tokens"],
+        Msg::TrustPolicy => ["Trust policy", "Política de confianza"],
+        Msg::RpcHeaders => ["RPC headers", "Encabezados RPC"],
+        Msg::InvalidRpcHeader => [
+            "Invalid RPC header '{0}', expected \"Name: value\"",
+            "Encabezado RPC no válido '{0}', se esperaba \"Nombre: valor\"",
+        ],
+        Msg::TrustSummary => [
+            "{0} IDL authorities, {1} signers, {2} pinned URLs",
+            "{0} autoridades de IDL, {1} firmantes, {2} URL fijadas",
+        ],
+        Msg::AnchorVersionHeader => ["Anchor version: {0}", "Versión de Anchor: {0}"],
+        Msg::AnchorVersionRange => ["Built with", "Compilado con"],
+        Msg::AnchorBinaryVersion => ["Program binary", "Binario del programa"],
+        Msg::NotFoundInBinary => [
+            "not read (no program ID, or no anchor-lang version in it)",
+            "no leído (sin ID de programa, o sin versión de anchor-lang)",
+        ],
+        Msg::Evidence => ["Evidence", "Indicios"],
+        Msg::AnchorFeatures => ["Features", "Funcionalidades"],
+        Msg::ClientLibraries => ["Client libraries", "Bibliotecas cliente"],
+        Msg::XcheckHeader => ["IDL by cluster", "IDL por clúster"],
+        Msg::XcheckMismatches => ["Differences ({0})", "Diferencias ({0})"],
+        Msg::XcheckConsistent => [
+            "The IDL is the same on every cluster",
+            "El IDL es igual en todos los clústeres",
+        ],
+        Msg::XcheckOnlyOn => ["only on {0}", "solo en {0}"],
+        Msg::XcheckDiffers => ["defined differently", "definido de otra forma"],
+        Msg::XcheckFailed => [
+            "{0} IDL items differ across clusters",
+            "{0} elementos del IDL difieren entre clústeres",
+        ],
+        Msg::MatrixHeader => [
+            "Account roles: {0} ({1} accounts, {2} instructions)",
+            "Roles de cuentas: {0} ({1} cuentas, {2} instrucciones)",
+        ],
+        Msg::MatrixLegend => [
+            "R read-only  W writable  S signer  ? optional",
+            "R solo lectura  W escritura  S firmante  ? opcional",
+        ],
+        Msg::PdasHeader => [
+            "PDAs: {0} ({1} seed recipes)",
+            "PDA: {0} ({1} recetas de semillas)",
+        ],
+        Msg::PdaSeeds => ["Seeds", "Semillas"],
+        Msg::PdaUsedBy => ["Used by", "Usada por"],
+        Msg::PageFooter => [
+            "Page {0} of {1}, items {2}-{3} of {4}",
+            "Página {0} de {1}, elementos {2}-{3} de {4}",
+        ],
+        Msg::PageNext => ["--page {0} for more", "--page {0} para ver más"],
+        Msg::Quarantined => [
+            "kept the account data in quarantine ({0}); `periscope quarantine retry` decodes it again",
+            "datos de la cuenta guardados en cuarentena ({0}); `periscope quarantine retry` los decodifica de nuevo",
+        ],
+        Msg::QuarantineHeader => ["Quarantine ({0} payloads)", "Cuarentena ({0} datos)"],
+        Msg::QuarantineAccountType => ["Account type", "Tipo de cuenta"],
+        Msg::QuarantineAttempts => ["Attempts", "Intentos"],
+        Msg::QuarantineError => ["Error", "Error"],
+        Msg::QuarantineRetried => [
+            "Decoded {0} of {1}; {2} still quarantined",
+            "Decodificados {0} de {1}; {2} siguen en cuarentena",
+        ],
+        Msg::QuarantineCleared => [
+            "Removed {0} quarantined payload(s)",
+            "Eliminados {0} dato(s) en cuarentena",
+        ],
+        Msg::QuarantineNotFound => [
+            "No quarantined payload with ID {0}",
+            "Ningún dato en cuarentena con ID {0}",
+        ],
+        Msg::ScanHeader => ["{0} accounts ({1})", "Cuentas {0} ({1})"],
+        Msg::ScanLamports => ["Lamports", "Lamports"],
+        Msg::ScanSizes => ["Sizes", "Tamaños"],
+        Msg::ScanFields => ["Fields", "Campos"],
+        Msg::ScanUndecoded => [
+            "{0} account(s) failed to decode and are left out of the field stats",
+            "{0} cuenta(s) no se pudieron decodificar y quedan fuera de las estadísticas",
+        ],
+        Msg::ScanFieldNotNumeric => [
+            "{0} is not a numeric field of {1}",
+            "{0} no es un campo numérico de {1}",
+        ],
+        Msg::ScanExported => ["Wrote {0} rows to {1}", "Escritas {0} filas en {1}"],
+        Msg::ExplainHeader => ["Message {0} of {1} ({2})", "Mensaje {0} de {1} ({2})"],
+        Msg::FeePayer => ["Fee payer", "Pagador de comisiones"],
+        Msg::Signers => ["Signers", "Firmantes"],
+        Msg::RecentBlockhash => ["Recent blockhash", "Blockhash reciente"],
+        Msg::ExplainInstructions => ["Instructions ({0})", "Instrucciones ({0})"],
+        Msg::ExplainUndecoded => ["not decoded: {0}", "no decodificada: {0}"],
+        Msg::UsesHeader => ["Uses of {0} ({1})", "Usos de {0} ({1})"],
+        Msg::LintHeader => ["Lint: {0} ({1} findings)", "Lint: {0} ({1} hallazgos)"],
+        Msg::LintFailed => ["{0} lint finding(s)", "{0} hallazgo(s) de lint"],
+        Msg::PreflightHeader => [
+            "Pre-flight: {0} ({1} accounts)",
+            "Verificación previa: {0} ({1} cuentas)",
+        ],
+        Msg::PreflightFailed => [
+            "{0} pre-flight check(s) failed",
+            "{0} verificación(es) previa(s) fallaron",
+        ],
+        Msg::InvalidAccountArg => [
+            "Invalid --account '{0}'; expected NAME=PUBKEY",
+            "--account '{0}' inválido; se espera NOMBRE=PUBKEY",
+        ],
+        Msg::OverlapHeader => [
+            "Overlaps across {0} programs ({1})",
+            "Coincidencias entre {0} programas ({1})",
+        ],
+        Msg::OverlapNone => [
+            "No error code, error name or discriminator is shared",
+            "Ningún código de error, nombre de error ni discriminador se repite",
+        ],
+        Msg::OverlapFailed => [
+            "{0} codes, names or discriminators are shared across programs",
+            "{0} códigos, nombres o discriminadores se repiten entre programas",
+        ],
+        Msg::OverlapTooFew => [
+            "Need at least two programs to compare, got {0}",
+            "Se necesitan al menos dos programas para comparar, hay {0}",
+        ],
+        Msg::CacheExported => [
+            "Exported {0} cached IDLs to {1}",
+            "{0} IDL en caché exportados a {1}",
+        ],
+        Msg::CacheImported => [
+            "Imported {0} of {1} cached IDLs from {2} (skipped: not newer than the local copy)",
+            "Importados {0} de {1} IDL en caché desde {2} (omitidos: no más recientes que la copia local)",
+        ],
+        Msg::IdlSigned => [
+            "Signed {0} with {1}, wrote {2}",
+            "Firmado {0} con {1}, escrito {2}",
+        ],
+        Msg::IdlSignatureValid => [
+            "Valid signature: {0} signed by {1}",
+            "Firma válida: {0} firmado por {1}",
+        ],
+        Msg::BundleHeader => [
+            "Bundle {0}: {1} programs, created {2}",
+            "Paquete {0}: {1} programas, creado {2}",
+        ],
+        Msg::BundleNoAddress => [
+            "{0} has no program address; give the program ID instead",
+            "{0} no tiene dirección de programa; usa el ID del programa",
+        ],
+        Msg::BundleUnsupported => [
+            "{0} is not a supported bundle (format '{1}', version {2})",
+            "{0} no es un paquete compatible (formato '{1}', versión {2})",
+        ],
+        Msg::BundleProgramRequired => [
+            "The bundle holds several programs, pick one with bundle:FILE#PROGRAM_ID ({0})",
+            "El paquete contiene varios programas, elige uno con bundle:FICHERO#ID_PROGRAMA ({0})",
+        ],
+        Msg::BundleProgramNotFound => [
+            "Program {0} is not in the bundle",
+            "El programa {0} no está en el paquete",
+        ],
+        Msg::CompatHeader => [
+            "Instruction changes: {0} {1} -> {2}",
+            "Cambios de instrucciones: {0} {1} -> {2}",
+        ],
+        Msg::CompatUnchanged => ["Unchanged: {0}", "Sin cambios: {0}"],
+        Msg::Before => ["Before", "Antes"],
+        Msg::After => ["After", "Después"],
+        Msg::LiveAccount => ["live account", "cuenta actual"],
+        Msg::ChangedFields => ["Changed fields ({0})", "Campos modificados ({0})"],
+        Msg::DiffDifferentAccounts => [
+            "The fixtures are of different accounts: {0} and {1}",
+            "Las capturas son de cuentas distintas: {0} y {1}",
+        ],
+        Msg::AccountTypeNotFound => [
+            "Account type '{0}' not found",
+            "No se encontró el tipo de cuenta '{0}'",
+        ],
+        Msg::SnapshotHeader => [
+            "Wrote {0} account fixtures to {1}",
+            "Se escribieron {0} cuentas de prueba en {1}",
+        ],
+        Msg::SnapshotValidatorHint => ["Start a local validator with", "Inicia un validador local con"],
+        Msg::GoldenMismatch => [
+            "IDL does not match {0} (run with {1}=1 to accept it):",
+            "El IDL no coincide con {0} (ejecuta con {1}=1 para aceptarlo):",
+        ],
+        Msg::GoldenUnreadable => [
+            "Cannot read golden IDL {0}: {1} (run with {2}=1 to create it)",
+            "No se puede leer el IDL de referencia {0}: {1} (ejecuta con {2}=1 para crearlo)",
+        ],
+        Msg::MockHeader => ["Mock value: {0}", "Valor de prueba: {0}"],
+        Msg::Seed => ["Seed", "Semilla"],
+        Msg::BorshData => ["Borsh data", "Datos Borsh"],
+        Msg::InvalidSweep => [
+            "Invalid --sweep '{0}'; expected ARG=V1,V2,... (e.g. amount=1,10,100)",
+            "--sweep '{0}' inválido; se espera ARG=V1,V2,... (p. ej. amount=1,10,100)",
+        ],
+        Msg::BuildNeedsTerminal => [
+            "--interactive needs a terminal; use --from <FILE> with a filled-in `periscope template`",
+            "--interactive necesita una terminal; usa --from <ARCHIVO> con un `periscope template` completado",
+        ],
+    }
+}
+
+/// Install the output language. Only the first call has an effect.
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+/// The active language
+pub fn lang() -> Lang {
+    *LANG.get_or_init(Lang::default)
+}
+
+/// Look up a message in the active language
+pub fn tr(msg: Msg) -> &'static str {
+    catalog(msg)[lang() as usize]
+}
+
+/// Look up a message and substitute `{0}`, `{1}`, ... with `args`
+pub fn trf(msg: Msg, args: &[&dyn fmt::Display]) -> String {
+    let mut out = tr(msg).to_string();
+    for (i, arg) in args.iter().enumerate() {
+        out = out.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lang_from_locale() {
+        assert_eq!(Lang::from_locale("es_AR.UTF-8"), Some(Lang::Es));
+        assert_eq!(Lang::from_locale("en-US"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C"), None);
+    }
+
+    #[test]
+    fn test_catalog_placeholders_match() {
+        let count = |s: &str| (0..5).filter(|i| s.contains(&format!("{{{}}}", i))).count();
+        for msg in [
+            Msg::SummaryCounts,
+            Msg::InstructionsHeader,
+            Msg::TrailingBytes,
+            Msg::InstructionNotFound,
+            Msg::SavedConfig,
+            Msg::EditorFailed,
+            Msg::RenamesHeader,
+            Msg::BuildUsePda,
+            Msg::SendExtraSigner,
+            Msg::SendNotConfirmed,
+            Msg::SnapshotHeader,
+            Msg::DiffDifferentAccounts,
+            Msg::XcheckOnlyOn,
+            Msg::CompatHeader,
+            Msg::MatrixHeader,
+            Msg::PdasHeader,
+            Msg::Quarantined,
+            Msg::QuarantineHeader,
+            Msg::QuarantineRetried,
+            Msg::QuarantineCleared,
+            Msg::QuarantineNotFound,
+            Msg::ScanHeader,
+            Msg::ScanUndecoded,
+            Msg::ScanFieldNotNumeric,
+            Msg::ScanExported,
+            Msg::ExplainHeader,
+            Msg::ExplainInstructions,
+            Msg::ExplainUndecoded,
+            Msg::PageFooter,
+            Msg::PageNext,
+            Msg::UsesHeader,
+            Msg::LintHeader,
+            Msg::LintFailed,
+            Msg::PreflightHeader,
+            Msg::PreflightFailed,
+            Msg::InvalidAccountArg,
+            Msg::OverlapHeader,
+            Msg::OverlapTooFew,
+            Msg::BundleHeader,
+            Msg::IdlSigned,
+            Msg::InvalidRpcHeader,
+            Msg::ReadAt,
+            Msg::ChangedSince,
+            Msg::GrepHeader,
+            Msg::InvalidPattern,
+            Msg::ReadFromRegistry,
+            Msg::TokenListSkipped,
+            Msg::TrustSummary,
+            Msg::CacheImported,
+            Msg::BundleUnsupported,
+            Msg::GoldenMismatch,
+            Msg::GoldenUnreadable,
+        ] {
+            let [en, es] = catalog(msg);
+            assert_eq!(count(en), count(es), "{:?}", msg);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Conversion from the `anchor-lang-idl` crate
+//!
+//! Projects that already parse IDLs with Anchor's own crate hand the result
+//! to Periscope directly instead of serializing it back to JSON. Items this
+//! crate can't represent (type aliases, tuple structs, kinds added to the
+//! spec later) are skipped and listed in [`Idl::warnings`], as lenient
+//! parsing does for JSON.
+
+use anchor_lang_idl::types as anchor;
+
+use super::types::{
+    Idl, IdlAccount, IdlAccountGroup, IdlAccountItem, IdlAccountRef, IdlArrayLen, IdlEnumFields,
+    IdlEnumVariant, IdlError, IdlEventRef, IdlField, IdlGenericArg, IdlInstruction, IdlMetadata,
+    IdlPda, IdlRepr, IdlReprModifier, IdlSeed, IdlSerialization, IdlType, IdlTypeComplex,
+    IdlTypeDef, IdlTypeDefGeneric, IdlTypeDefTy,
+};
+use super::ParseWarning;
+
+type Converted<T> = Result<T, String>;
+
+impl From<anchor::Idl> for Idl {
+    fn from(idl: anchor::Idl) -> Self {
+        let mut warnings = Vec::new();
+        let instructions = convert_section(
+            "instructions",
+            idl.instructions,
+            |ix| ix.name.clone(),
+            instruction,
+            &mut warnings,
+        );
+        let types = convert_section(
+            "types",
+            idl.types,
+            |def| def.name.clone(),
+            type_def,
+            &mut warnings,
+        );
+
+        Idl {
+            address: idl.address,
+            metadata: IdlMetadata {
+                name: idl.metadata.name,
+                version: idl.metadata.version,
+                spec: idl.metadata.spec,
+                description: idl.metadata.description,
+            },
+            instructions,
+            accounts: idl
+                .accounts
+                .into_iter()
+                .map(|a| IdlAccountRef {
+                    name: a.name,
+                    discriminator: a.discriminator,
+                })
+                .collect(),
+            types,
+            events: idl
+                .events
+                .into_iter()
+                .map(|e| IdlEventRef {
+                    name: e.name,
+                    discriminator: e.discriminator,
+                })
+                .collect(),
+            errors: idl
+                .errors
+                .into_iter()
+                .map(|e| IdlError {
+                    code: e.code,
+                    name: e.name,
+                    msg: e.msg,
+                })
+                .collect(),
+            warnings,
+            index: Default::default(),
+        }
+    }
+}
+
+/// Convert each item of a section, collecting failures as warnings
+fn convert_section<A, T>(
+    section: &str,
+    items: Vec<A>,
+    name: impl Fn(&A) -> String,
+    convert: impl Fn(A) -> Converted<T>,
+    warnings: &mut Vec<ParseWarning>,
+) -> Vec<T> {
+    let mut converted = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let item_name = name(&item);
+        match convert(item) {
+            Ok(item) => converted.push(item),
+            Err(reason) => warnings.push(ParseWarning {
+                section: section.to_string(),
+                index,
+                name: Some(item_name),
+                reason,
+            }),
+        }
+    }
+    converted
+}
+
+fn instruction(ix: anchor::IdlInstruction) -> Converted<IdlInstruction> {
+    Ok(IdlInstruction {
+        name: ix.name,
+        discriminator: ix.discriminator,
+        accounts: ix.accounts.into_iter().map(account_item).collect(),
+        args: fields(ix.args)?,
+    })
+}
+
+fn account_item(item: anchor::IdlInstructionAccountItem) -> IdlAccountItem {
+    match item {
+        anchor::IdlInstructionAccountItem::Single(account) => IdlAccountItem::Single(IdlAccount {
+            name: account.name,
+            writable: account.writable,
+            signer: account.signer,
+            optional: account.optional,
+            address: account.address,
+            pda: account.pda.map(|pda| IdlPda {
+                seeds: pda.seeds.into_iter().map(seed).collect(),
+            }),
+        }),
+        anchor::IdlInstructionAccountItem::Composite(group) => {
+            IdlAccountItem::Group(IdlAccountGroup {
+                name: group.name,
+                accounts: group.accounts.into_iter().map(account_item).collect(),
+            })
+        }
+    }
+}
+
+fn seed(seed: anchor::IdlSeed) -> IdlSeed {
+    match seed {
+        anchor::IdlSeed::Const(seed) => IdlSeed::Const {
+            value: seed.value.into(),
+        },
+        anchor::IdlSeed::Account(seed) => IdlSeed::Account { path: seed.path },
+        anchor::IdlSeed::Arg(seed) => IdlSeed::Arg { path: seed.path },
+    }
+}
+
+fn type_def(def: anchor::IdlTypeDef) -> Converted<IdlTypeDef> {
+    let ty = match def.ty {
+        anchor::IdlTypeDefTy::Struct { fields: None } => IdlTypeDefTy::Struct { fields: vec![] },
+        anchor::IdlTypeDefTy::Struct {
+            fields: Some(anchor::IdlDefinedFields::Named(named)),
+        } => IdlTypeDefTy::Struct {
+            fields: fields(named)?,
+        },
+        anchor::IdlTypeDefTy::Struct {
+            fields: Some(anchor::IdlDefinedFields::Tuple(_)),
+        } => return Err("tuple structs are not supported".to_string()),
+        anchor::IdlTypeDefTy::Enum { variants } => IdlTypeDefTy::Enum {
+            variants: variants
+                .into_iter()
+                .map(variant)
+                .collect::<Converted<_>>()?,
+        },
+        anchor::IdlTypeDefTy::Type { .. } => {
+            return Err("type aliases are not supported".to_string())
+        }
+    };
+    Ok(IdlTypeDef {
+        name: def.name,
+        generics: def
+            .generics
+            .into_iter()
+            .map(|generic| match generic {
+                anchor::IdlTypeDefGeneric::Type { name } => IdlTypeDefGeneric::Type { name },
+                anchor::IdlTypeDefGeneric::Const { name, ty } => {
+                    IdlTypeDefGeneric::Const { name, ty }
+                }
+            })
+            .collect(),
+        serialization: serialization(def.serialization)?,
+        repr: def.repr.map(repr).transpose()?,
+        ty,
+    })
+}
+
+fn serialization(serialization: anchor::IdlSerialization) -> Converted<IdlSerialization> {
+    match serialization {
+        anchor::IdlSerialization::Borsh => Ok(IdlSerialization::Borsh),
+        anchor::IdlSerialization::Bytemuck => Ok(IdlSerialization::Bytemuck),
+        anchor::IdlSerialization::BytemuckUnsafe => Ok(IdlSerialization::BytemuckUnsafe),
+        anchor::IdlSerialization::Custom(name) => Ok(IdlSerialization::Custom(name)),
+        other => Err(format!("unsupported serialization {:?}", other)),
+    }
+}
+
+fn repr(repr: anchor::IdlRepr) -> Converted<IdlRepr> {
+    let modifier = |modifier: anchor::IdlReprModifier| IdlReprModifier {
+        packed: modifier.packed,
+        align: modifier.align,
+    };
+    match repr {
+        anchor::IdlRepr::Rust(m) => Ok(IdlRepr::Rust(modifier(m))),
+        anchor::IdlRepr::C(m) => Ok(IdlRepr::C(modifier(m))),
+        anchor::IdlRepr::Transparent => Ok(IdlRepr::Transparent),
+        other => Err(format!("unsupported representation {:?}", other)),
+    }
+}
+
+fn variant(variant: anchor::IdlEnumVariant) -> Converted<IdlEnumVariant> {
+    Ok(IdlEnumVariant {
+        name: variant.name,
+        fields: match variant.fields {
+            None => None,
+            Some(anchor::IdlDefinedFields::Named(named)) => {
+                Some(IdlEnumFields::Named(fields(named)?))
+            }
+            Some(anchor::IdlDefinedFields::Tuple(types)) => Some(IdlEnumFields::Tuple(
+                types.into_iter().map(idl_type).collect::<Converted<_>>()?,
+            )),
+        },
+    })
+}
+
+fn fields(fields: Vec<anchor::IdlField>) -> Converted<Vec<IdlField>> {
+    fields
+        .into_iter()
+        .map(|field| {
+            Ok(IdlField {
+                name: field.name,
+                ty: idl_type(field.ty)?,
+            })
+        })
+        .collect()
+}
+
+fn idl_type(ty: anchor::IdlType) -> Converted<IdlType> {
+    let primitive = |name: &str| Ok(IdlType::Primitive(name.to_string()));
+    let complex = |complex| Ok(IdlType::Complex(complex));
+    match ty {
+        anchor::IdlType::Bool => primitive("bool"),
+        anchor::IdlType::U8 => primitive("u8"),
+        anchor::IdlType::I8 => primitive("i8"),
+        anchor::IdlType::U16 => primitive("u16"),
+        anchor::IdlType::I16 => primitive("i16"),
+        anchor::IdlType::U32 => primitive("u32"),
+        anchor::IdlType::I32 => primitive("i32"),
+        anchor::IdlType::F32 => primitive("f32"),
+        anchor::IdlType::U64 => primitive("u64"),
+        anchor::IdlType::I64 => primitive("i64"),
+        anchor::IdlType::F64 => primitive("f64"),
+        anchor::IdlType::U128 => primitive("u128"),
+        anchor::IdlType::I128 => primitive("i128"),
+        anchor::IdlType::U256 => primitive("u256"),
+        anchor::IdlType::I256 => primitive("i256"),
+        anchor::IdlType::Bytes => primitive("bytes"),
+        anchor::IdlType::String => primitive("string"),
+        anchor::IdlType::Pubkey => primitive("pubkey"),
+        anchor::IdlType::Option(inner) => {
+            complex(IdlTypeComplex::Option(Box::new(idl_type(*inner)?)))
+        }
+        anchor::IdlType::Vec(inner) => complex(IdlTypeComplex::Vec(Box::new(idl_type(*inner)?))),
+        anchor::IdlType::Array(inner, len) => complex(IdlTypeComplex::Array(
+            Box::new(idl_type(*inner)?),
+            match len {
+                anchor::IdlArrayLen::Generic(name) => IdlArrayLen::Generic(name),
+                anchor::IdlArrayLen::Value(len) => IdlArrayLen::Value(len),
+            },
+        )),
+        anchor::IdlType::Defined { name, generics } => complex(IdlTypeComplex::Defined {
+            name,
+            generics: generics
+                .into_iter()
+                .map(|arg| match arg {
+                    anchor::IdlGenericArg::Type { ty: arg } => {
+                        Ok(IdlGenericArg::Type { ty: idl_type(arg)? })
+                    }
+                    anchor::IdlGenericArg::Const { value } => Ok(IdlGenericArg::Const { value }),
+                })
+                .collect::<Converted<_>>()?,
+        }),
+        anchor::IdlType::Generic(name) => complex(IdlTypeComplex::Generic(name)),
+        other => Err(format!("unsupported type {:?}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_anchor_idl() {
+        let json = json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "swap",
+                "discriminator": [248, 198, 158, 145, 225, 117, 135, 200],
+                "accounts": [
+                    { "name": "pool", "writable": true,
+                      "pda": { "seeds": [{ "kind": "const", "value": [112, 111, 111, 108] }] } },
+                    { "name": "user", "signer": true }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "route", "type": { "vec": { "defined": { "name": "Hop" } } } }
+                ]
+            }],
+            "accounts": [{ "name": "Pool", "discriminator": [241, 154, 109, 4, 17, 177, 109, 188] }],
+            "types": [
+                { "name": "Pool", "type": { "kind": "struct", "fields": [
+                    { "name": "reserves", "type": { "array": ["u64", 2] } }
+                ] } },
+                { "name": "Hop", "type": { "kind": "enum", "variants": [
+                    { "name": "Direct" },
+                    { "name": "Via", "fields": ["pubkey"] }
+                ] } },
+                { "name": "Price", "type": { "kind": "type", "alias": "u64" } }
+            ],
+            "errors": [{ "code": 6000, "name": "SlippageExceeded" }]
+        });
+        let anchor_idl: anchor::Idl = serde_json::from_value(json.clone()).unwrap();
+        let mut idl = Idl::from(anchor_idl);
+
+        let swap = idl.find_instruction("swap").unwrap();
+        assert_eq!(
+            swap.to_string(),
+            "swap(amount: u64, route: Vec<Hop>) [pool (writable, pda), user (signer)]"
+        );
+        assert_eq!(
+            idl.find_type("Hop").unwrap().to_string(),
+            "enum Hop { Direct, Via(pubkey) }"
+        );
+        assert_eq!(idl.find_error(6000).unwrap().name, "SlippageExceeded");
+
+        // Same items as parsing the JSON, the alias skipped with a warning
+        let mut parsed = parse_idl_json(&json.to_string()).unwrap();
+        assert_eq!(idl.warnings.len(), 1);
+        assert_eq!(idl.warnings[0].section, "types");
+        assert_eq!(idl.warnings[0].name.as_deref(), Some("Price"));
+        assert_eq!(idl.warnings[0].index, parsed.warnings[0].index);
+        idl.warnings.clear();
+        parsed.warnings.clear();
+        assert_eq!(
+            serde_json::to_value(&idl).unwrap(),
+            serde_json::to_value(&parsed).unwrap()
+        );
+    }
+}
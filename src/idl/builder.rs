@@ -0,0 +1,547 @@
+//! Building IDLs in code
+//!
+//! Test fixtures and IDL generators for non-Anchor programs construct an
+//! [`Idl`] with [`IdlBuilder`] instead of writing JSON by hand. Instruction,
+//! account and event discriminators are derived from the names the way
+//! Anchor does, unless set explicitly, and [`IdlBuilder::build`] rejects
+//! documents the rest of the crate couldn't use: duplicate names, codes or
+//! discriminators, and references to types that aren't defined.
+//!
+//! ```ignore
+//! use periscope::idl::{IdlBuilder, IdlType, InstructionBuilder, TypeDefBuilder};
+//!
+//! let u64_ty = IdlType::Primitive("u64".into());
+//! let idl = IdlBuilder::new("amm", "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4")
+//!     .instruction(
+//!         InstructionBuilder::new("swap")
+//!             .writable("pool")
+//!             .signer("user")
+//!             .arg("amount", u64_ty.clone()),
+//!     )
+//!     .account(TypeDefBuilder::new("Pool").field("reserve", u64_ty))
+//!     .error(6000, "SlippageExceeded", "Slippage tolerance exceeded")
+//!     .build()?;
+//! ```
+
+use std::collections::HashSet;
+
+use solana_sdk::pubkey::Pubkey;
+
+use super::spec::IdlSpec;
+use super::types::{
+    Idl, IdlAccount, IdlAccountItem, IdlAccountRef, IdlEnumFields, IdlEnumVariant, IdlError,
+    IdlEventRef, IdlField, IdlInstruction, IdlMetadata, IdlRepr, IdlSerialization, IdlType,
+    IdlTypeDef, IdlTypeDefGeneric, IdlTypeDefTy,
+};
+use super::{event_discriminator, hex};
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::uses::{fields, references};
+
+/// Builder of a whole [`Idl`]
+#[derive(Debug, Clone)]
+pub struct IdlBuilder {
+    address: String,
+    metadata: IdlMetadata,
+    instructions: Vec<InstructionBuilder>,
+    accounts: Vec<TypeDefBuilder>,
+    events: Vec<TypeDefBuilder>,
+    types: Vec<TypeDefBuilder>,
+    errors: Vec<IdlError>,
+}
+
+impl IdlBuilder {
+    /// Program `name` deployed at `address` (base58), version `0.1.0`
+    pub fn new(name: impl Into<String>, address: impl Into<String>) -> Self {
+        IdlBuilder {
+            address: address.into(),
+            metadata: IdlMetadata {
+                name: name.into(),
+                version: "0.1.0".to_string(),
+                spec: IdlSpec::LATEST.to_string(),
+                description: None,
+            },
+            instructions: Vec::new(),
+            accounts: Vec::new(),
+            events: Vec::new(),
+            types: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Program version
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.metadata.version = version.into();
+        self
+    }
+
+    /// Program description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.metadata.description = Some(description.into());
+        self
+    }
+
+    /// Add an instruction
+    pub fn instruction(mut self, instruction: InstructionBuilder) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Add an account type: its definition goes in `types`, its
+    /// discriminator in `accounts`
+    pub fn account(mut self, def: TypeDefBuilder) -> Self {
+        self.accounts.push(def);
+        self
+    }
+
+    /// Add an event type: its definition goes in `types`, its discriminator
+    /// in `events`
+    pub fn event(mut self, def: TypeDefBuilder) -> Self {
+        self.events.push(def);
+        self
+    }
+
+    /// Add a type that is neither an account nor an event
+    pub fn type_def(mut self, def: TypeDefBuilder) -> Self {
+        self.types.push(def);
+        self
+    }
+
+    /// Add an error code
+    pub fn error(mut self, code: u32, name: impl Into<String>, msg: impl Into<String>) -> Self {
+        self.errors.push(IdlError {
+            code,
+            name: name.into(),
+            msg: Some(msg.into()),
+        });
+        self
+    }
+
+    /// The IDL, or [`PeriscopeError::InvalidIdl`] naming the first problem
+    ///
+    /// Type definitions are listed accounts first, then events, then the
+    /// other types, each in the order they were added.
+    pub fn build(self) -> PeriscopeResult<Idl> {
+        if self.address.parse::<Pubkey>().is_err() {
+            return Err(invalid(format!(
+                "program address {} is not a base58 public key",
+                self.address
+            )));
+        }
+
+        let mut accounts = Vec::with_capacity(self.accounts.len());
+        let mut events = Vec::with_capacity(self.events.len());
+        let mut types = Vec::new();
+        for builder in self.accounts {
+            let discriminator = builder.discriminator.clone();
+            let def = builder.build()?;
+            let mut account = IdlAccountRef {
+                name: def.name.clone(),
+                discriminator: discriminator.unwrap_or_default(),
+            };
+            account.discriminator = account.discriminator_bytes();
+            accounts.push(account);
+            types.push(def);
+        }
+        for builder in self.events {
+            let discriminator = builder.discriminator.clone();
+            let def = builder.build()?;
+            events.push(IdlEventRef {
+                discriminator: discriminator
+                    .unwrap_or_else(|| event_discriminator(&def.name).to_vec()),
+                name: def.name.clone(),
+            });
+            types.push(def);
+        }
+        for builder in self.types {
+            types.push(builder.build()?);
+        }
+        let instructions: Vec<IdlInstruction> = self
+            .instructions
+            .into_iter()
+            .map(InstructionBuilder::build)
+            .collect();
+
+        unique("instruction", instructions.iter().map(|ix| ix.name.clone()))?;
+        unique(
+            "instruction discriminator",
+            instructions.iter().map(|ix| hex(&ix.discriminator)),
+        )?;
+        unique("type", types.iter().map(|def| def.name.clone()))?;
+        unique(
+            "account discriminator",
+            accounts.iter().map(|account| hex(&account.discriminator)),
+        )?;
+        unique(
+            "event discriminator",
+            events.iter().map(|event| hex(&event.discriminator)),
+        )?;
+        unique("error code", self.errors.iter().map(|e| e.code.to_string()))?;
+
+        let defined: HashSet<&str> = types.iter().map(|def| def.name.as_str()).collect();
+        let mut mentioned = Vec::new();
+        for ix in &instructions {
+            for arg in &ix.args {
+                references(&arg.ty, &mut mentioned);
+            }
+        }
+        for def in &types {
+            for (_, ty) in fields(def) {
+                references(ty, &mut mentioned);
+            }
+        }
+        if let Some(name) = mentioned.iter().find(|name| !defined.contains(*name)) {
+            return Err(invalid(format!("type {} is used but not defined", name)));
+        }
+
+        Ok(Idl {
+            address: self.address,
+            metadata: self.metadata,
+            instructions,
+            accounts,
+            types,
+            events,
+            errors: self.errors,
+            warnings: Vec::new(),
+            index: Default::default(),
+        })
+    }
+}
+
+/// Builder of an [`IdlInstruction`]
+#[derive(Debug, Clone)]
+pub struct InstructionBuilder {
+    name: String,
+    discriminator: Option<Vec<u8>>,
+    accounts: Vec<IdlAccountItem>,
+    args: Vec<IdlField>,
+}
+
+impl InstructionBuilder {
+    /// Instruction `name` (snake_case, as in Anchor 0.30+ IDLs)
+    pub fn new(name: impl Into<String>) -> Self {
+        InstructionBuilder {
+            name: name.into(),
+            discriminator: None,
+            accounts: Vec::new(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Use these discriminator bytes instead of Anchor's `global:<name>` hash
+    pub fn discriminator(mut self, discriminator: impl Into<Vec<u8>>) -> Self {
+        self.discriminator = Some(discriminator.into());
+        self
+    }
+
+    /// Add a read-only account
+    pub fn account(self, name: impl Into<String>) -> Self {
+        self.push_account(name.into(), false, false)
+    }
+
+    /// Add a writable account
+    pub fn writable(self, name: impl Into<String>) -> Self {
+        self.push_account(name.into(), true, false)
+    }
+
+    /// Add a read-only signer
+    pub fn signer(self, name: impl Into<String>) -> Self {
+        self.push_account(name.into(), false, true)
+    }
+
+    /// Add a writable signer (e.g. the fee payer)
+    pub fn writable_signer(self, name: impl Into<String>) -> Self {
+        self.push_account(name.into(), true, true)
+    }
+
+    /// Add a read-only account with a fixed address (e.g. the system program)
+    pub fn fixed(mut self, name: impl Into<String>, address: impl Into<String>) -> Self {
+        self.accounts.push(IdlAccountItem::Single(IdlAccount {
+            address: Some(address.into()),
+            ..account(name.into(), false, false)
+        }));
+        self
+    }
+
+    /// Add an account item as is: optional and PDA accounts, nested groups
+    pub fn account_item(mut self, item: IdlAccountItem) -> Self {
+        self.accounts.push(item);
+        self
+    }
+
+    /// Add an argument
+    pub fn arg(mut self, name: impl Into<String>, ty: IdlType) -> Self {
+        self.args.push(IdlField {
+            name: name.into(),
+            ty,
+        });
+        self
+    }
+
+    /// The instruction, with its discriminator filled in
+    pub fn build(self) -> IdlInstruction {
+        let mut ix = IdlInstruction {
+            name: self.name,
+            discriminator: self.discriminator.unwrap_or_default(),
+            accounts: self.accounts,
+            args: self.args,
+        };
+        ix.discriminator = ix.discriminator_bytes();
+        ix
+    }
+
+    fn push_account(mut self, name: String, writable: bool, signer: bool) -> Self {
+        self.accounts
+            .push(IdlAccountItem::Single(account(name, writable, signer)));
+        self
+    }
+}
+
+fn account(name: String, writable: bool, signer: bool) -> IdlAccount {
+    IdlAccount {
+        name,
+        writable,
+        signer,
+        optional: false,
+        address: None,
+        pda: None,
+    }
+}
+
+/// Builder of an [`IdlTypeDef`]: a struct once a field is added, an enum
+/// once a variant is added
+#[derive(Debug, Clone)]
+pub struct TypeDefBuilder {
+    name: String,
+    discriminator: Option<Vec<u8>>,
+    generics: Vec<IdlTypeDefGeneric>,
+    serialization: IdlSerialization,
+    repr: Option<IdlRepr>,
+    fields: Vec<IdlField>,
+    variants: Vec<IdlEnumVariant>,
+}
+
+impl TypeDefBuilder {
+    /// Type `name` (PascalCase); a struct without fields until something is added
+    pub fn new(name: impl Into<String>) -> Self {
+        TypeDefBuilder {
+            name: name.into(),
+            discriminator: None,
+            generics: Vec::new(),
+            serialization: IdlSerialization::default(),
+            repr: None,
+            fields: Vec::new(),
+            variants: Vec::new(),
+        }
+    }
+
+    /// Use these discriminator bytes instead of Anchor's when the type is
+    /// added as an account or event
+    pub fn discriminator(mut self, discriminator: impl Into<Vec<u8>>) -> Self {
+        self.discriminator = Some(discriminator.into());
+        self
+    }
+
+    /// Add a generic parameter
+    pub fn generic(mut self, generic: IdlTypeDefGeneric) -> Self {
+        self.generics.push(generic);
+        self
+    }
+
+    /// Serialization format (bytemuck for zero-copy accounts)
+    pub fn serialization(mut self, serialization: IdlSerialization) -> Self {
+        self.serialization = serialization;
+        self
+    }
+
+    /// Memory representation (`#[repr(...)]`)
+    pub fn repr(mut self, repr: IdlRepr) -> Self {
+        self.repr = Some(repr);
+        self
+    }
+
+    /// Add a struct field
+    pub fn field(mut self, name: impl Into<String>, ty: IdlType) -> Self {
+        self.fields.push(IdlField {
+            name: name.into(),
+            ty,
+        });
+        self
+    }
+
+    /// Add an enum variant without fields
+    pub fn variant(self, name: impl Into<String>) -> Self {
+        self.push_variant(name.into(), None)
+    }
+
+    /// Add a tuple enum variant (`Ask(u64)`)
+    pub fn tuple_variant(self, name: impl Into<String>, types: Vec<IdlType>) -> Self {
+        self.push_variant(name.into(), Some(IdlEnumFields::Tuple(types)))
+    }
+
+    /// Add a struct enum variant (`Limit { price: u64 }`)
+    pub fn struct_variant(self, name: impl Into<String>, fields: Vec<IdlField>) -> Self {
+        self.push_variant(name.into(), Some(IdlEnumFields::Named(fields)))
+    }
+
+    /// The type definition; fails if both fields and variants were added
+    pub fn build(self) -> PeriscopeResult<IdlTypeDef> {
+        let ty = match (self.fields.is_empty(), self.variants.is_empty()) {
+            (_, true) => IdlTypeDefTy::Struct {
+                fields: self.fields,
+            },
+            (true, false) => IdlTypeDefTy::Enum {
+                variants: self.variants,
+            },
+            (false, false) => {
+                return Err(invalid(format!(
+                    "type {} has both struct fields and enum variants",
+                    self.name
+                )))
+            }
+        };
+        Ok(IdlTypeDef {
+            name: self.name,
+            generics: self.generics,
+            serialization: self.serialization,
+            repr: self.repr,
+            ty,
+        })
+    }
+
+    fn push_variant(mut self, name: String, fields: Option<IdlEnumFields>) -> Self {
+        self.variants.push(IdlEnumVariant { name, fields });
+        self
+    }
+}
+
+fn invalid(reason: String) -> PeriscopeError {
+    PeriscopeError::InvalidIdl(reason)
+}
+
+/// Fail on the first key seen twice
+fn unique(what: &str, keys: impl Iterator<Item = String>) -> PeriscopeResult<()> {
+    let mut seen = HashSet::new();
+    for key in keys {
+        if !seen.insert(key.clone()) {
+            return Err(invalid(format!("duplicate {} {}", what, key)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::{instruction_discriminator, parse_idl_json, IdlTypeComplex};
+
+    const ADDRESS: &str = "11111111111111111111111111111111";
+
+    fn prim(name: &str) -> IdlType {
+        IdlType::Primitive(name.to_string())
+    }
+
+    fn defined(name: &str) -> IdlType {
+        IdlType::Complex(IdlTypeComplex::Defined {
+            name: name.to_string(),
+            generics: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_build_idl() {
+        let idl = IdlBuilder::new("amm", ADDRESS)
+            .version("1.2.0")
+            .instruction(
+                InstructionBuilder::new("swap")
+                    .writable("pool")
+                    .writable_signer("user")
+                    .fixed("system_program", ADDRESS)
+                    .arg("amount", prim("u64"))
+                    .arg("side", defined("Side")),
+            )
+            .instruction(InstructionBuilder::new("legacy_op").discriminator(vec![7]))
+            .account(TypeDefBuilder::new("Pool").field("reserve", prim("u64")))
+            .event(TypeDefBuilder::new("Swapped").field("amount", prim("u64")))
+            .type_def(
+                TypeDefBuilder::new("Side")
+                    .variant("Bid")
+                    .tuple_variant("Ask", vec![prim("u64")]),
+            )
+            .error(6000, "SlippageExceeded", "Slippage tolerance exceeded")
+            .build()
+            .unwrap();
+
+        let swap = idl.find_instruction("swap").unwrap();
+        assert_eq!(swap.discriminator, instruction_discriminator("swap"));
+        assert_eq!(
+            swap.to_string(),
+            "swap(amount: u64, side: Side) [pool (writable), user (writable, signer), \
+             system_program = 11111111111111111111111111111111]"
+        );
+        assert_eq!(
+            idl.find_instruction("legacy_op").unwrap().discriminator,
+            [7]
+        );
+        assert_eq!(
+            idl.accounts[0].discriminator,
+            crate::idl::account_discriminator("Pool")
+        );
+        assert_eq!(idl.events[0].discriminator, event_discriminator("Swapped"));
+        let names: Vec<&str> = idl.types.iter().map(|def| def.name.as_str()).collect();
+        assert_eq!(names, ["Pool", "Swapped", "Side"]);
+        assert_eq!(
+            idl.find_type("Side").unwrap().to_string(),
+            "enum Side { Bid, Ask(u64) }"
+        );
+
+        // What the builder produces survives a JSON round trip
+        let json = serde_json::to_string(&idl).unwrap();
+        let parsed = parse_idl_json(&json).unwrap();
+        assert_eq!(parsed.metadata.version, "1.2.0");
+        assert_eq!(parsed.instructions.len(), 2);
+        assert_eq!(parsed.find_error(6000).unwrap().name, "SlippageExceeded");
+    }
+
+    #[test]
+    fn test_build_rejects_invalid() {
+        let reason = |builder: IdlBuilder| match builder.build() {
+            Err(PeriscopeError::InvalidIdl(reason)) => reason,
+            other => panic!("expected InvalidIdl, got {:?}", other.map(|_| ())),
+        };
+
+        assert_eq!(
+            reason(
+                IdlBuilder::new("amm", ADDRESS)
+                    .instruction(InstructionBuilder::new("swap"))
+                    .instruction(InstructionBuilder::new("swap"))
+            ),
+            "duplicate instruction swap"
+        );
+        assert_eq!(
+            reason(
+                IdlBuilder::new("amm", ADDRESS)
+                    .account(TypeDefBuilder::new("Pool").field("side", defined("Side")))
+            ),
+            "type Side is used but not defined"
+        );
+        assert_eq!(
+            reason(
+                IdlBuilder::new("amm", ADDRESS).type_def(
+                    TypeDefBuilder::new("Side")
+                        .variant("Bid")
+                        .field("x", prim("u8"))
+                )
+            ),
+            "type Side has both struct fields and enum variants"
+        );
+        assert_eq!(
+            reason(
+                IdlBuilder::new("amm", ADDRESS)
+                    .error(6000, "A", "a")
+                    .error(6000, "B", "b")
+            ),
+            "duplicate error code 6000"
+        );
+        assert!(reason(IdlBuilder::new("amm", "not-a-key")).contains("not a base58"));
+    }
+}
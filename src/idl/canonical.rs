@@ -0,0 +1,131 @@
+//! Canonical form and hash of an IDL
+//!
+//! The same IDL can be published with keys in any order, pretty-printed or
+//! minified, with defaults spelled out or left out. [`Idl::canonical_json`]
+//! serializes the parsed IDL with object keys sorted and no whitespace, so
+//! [`Idl::canonical_hash`] is equal for IDLs that parse to the same thing.
+//! Parse warnings are not part of the canonical form.
+//!
+//! The canonical form follows Periscope's IDL model, so it changes when the
+//! model does. Such a change must bump [`CANONICAL_VERSION`], which is
+//! hashed along with the JSON: hashes pinned or signed under one version
+//! then fail visibly under the next instead of silently matching another
+//! document. The locked fixture hash in the tests catches a forgotten bump.
+
+use crate::idl::{hex, Idl};
+use serde_json::Value;
+use solana_sdk::hash::hash;
+
+/// Version of the canonical form, bumped whenever [`Idl::canonical_json`]
+/// of some IDL changes
+pub const CANONICAL_VERSION: u32 = 1;
+
+impl Idl {
+    /// The IDL as compact JSON with object keys sorted at every level
+    pub fn canonical_json(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("IDL serializes to JSON");
+        if let Value::Object(object) = &mut value {
+            object.remove("warnings");
+        }
+        let mut out = String::new();
+        write_canonical(&value, &mut out);
+        out
+    }
+
+    /// SHA-256 of [`CANONICAL_VERSION`] and [`Idl::canonical_json`], as
+    /// lowercase hex
+    pub fn canonical_hash(&self) -> String {
+        let payload = format!(
+            "periscope-idl-canonical:v{}:{}",
+            CANONICAL_VERSION,
+            self.canonical_json()
+        );
+        hex(&hash(payload.as_bytes()).to_bytes())
+    }
+}
+
+/// Sorted keys don't depend on serde_json's `preserve_order` feature being off
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::idl::parse_idl_json;
+
+    #[test]
+    fn test_canonical_hash_ignores_layout() {
+        let pretty = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{ "name": "deposit", "accounts": [], "args": [
+                { "name": "amount", "type": "u64" }
+            ] }]
+        }"#;
+        let reordered = r#"{"instructions":[{"args":[{"type":"u64","name":"amount"}],"accounts":[],"name":"deposit"}],"metadata":{"spec":"0.1.0","version":"0.1.0","name":"vault"},"address":"11111111111111111111111111111111","errors":[]}"#;
+        let changed = pretty.replace("u64", "u32");
+
+        let hash = parse_idl_json(pretty).unwrap().canonical_hash();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(parse_idl_json(reordered).unwrap().canonical_hash(), hash);
+        assert_ne!(parse_idl_json(&changed).unwrap().canonical_hash(), hash);
+
+        let canonical = parse_idl_json(pretty).unwrap().canonical_json();
+        assert!(canonical.starts_with(r#"{"accounts":[],"address":"#));
+        assert!(!canonical.contains(' '));
+    }
+
+    /// Fails when the canonical form changes: bump CANONICAL_VERSION, then
+    /// update the hash
+    #[test]
+    fn test_canonical_hash_is_locked() {
+        let fixture = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [242, 35, 198, 137, 82, 225, 242, 182],
+                "accounts": [{ "name": "vault", "writable": true }, { "name": "owner", "signer": true }],
+                "args": [{ "name": "amount", "type": "u64" }, { "name": "memo", "type": { "option": "string" } }]
+            }],
+            "accounts": [{ "name": "Vault", "discriminator": [211, 8, 232, 43, 2, 152, 117, 119] }],
+            "types": [{ "name": "Vault", "type": { "kind": "struct", "fields": [
+                { "name": "owner", "type": "pubkey" },
+                { "name": "balances", "type": { "array": ["u64", 4] } }
+            ] } }],
+            "events": [],
+            "errors": [{ "code": 6000, "name": "Unauthorized", "msg": "Not the owner" }]
+        }"#;
+        assert_eq!(super::CANONICAL_VERSION, 1);
+        assert_eq!(
+            parse_idl_json(fixture).unwrap().canonical_hash(),
+            "1e7cf1bf868e6c029a5d434162ab82280f37891f57d80de0bfa951e7d9d45367"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Name casing policy
+//!
+//! IDLs mix conventions: legacy ones spell names in camelCase (`amountIn`),
+//! 0.30+ ones in snake_case (`amount_in`), and merged or hand-edited ones
+//! both. [`Idl::apply_case`] rewrites instruction, argument, account and
+//! field names to one convention so displays, JSON output and generated code
+//! agree. Type, event and error names are UpperCamel everywhere and are left
+//! alone.
+
+use crate::idl::rust_names::{capitalize, split_words};
+use crate::idl::{Idl, IdlAccountItem, IdlEnumFields, IdlField, IdlSeed, IdlTypeDefTy};
+use std::fmt;
+use std::str::FromStr;
+
+/// How names are spelled in output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameCase {
+    /// `amount_in`
+    Snake,
+    /// `amountIn`
+    Camel,
+    /// As written in the IDL
+    #[default]
+    Preserve,
+}
+
+impl NameCase {
+    /// Values accepted by `--case`
+    pub const NAMES: &'static [&'static str] = &["snake", "camel", "preserve"];
+
+    /// `name` in this case; leading underscores (`_reserved`) are kept
+    pub fn apply(self, name: &str) -> String {
+        if self == NameCase::Preserve {
+            return name.to_string();
+        }
+        let trimmed = name.trim_start_matches('_');
+        let prefix = &name[..name.len() - trimmed.len()];
+        let words = split_words(trimmed);
+        if words.is_empty() {
+            return name.to_string();
+        }
+        let body = if self == NameCase::Snake {
+            words.join("_")
+        } else {
+            let mut body = words[0].clone();
+            for word in &words[1..] {
+                body.push_str(&capitalize(word));
+            }
+            body
+        };
+        format!("{}{}", prefix, body)
+    }
+}
+
+impl fmt::Display for NameCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NameCase::Snake => "snake",
+            NameCase::Camel => "camel",
+            NameCase::Preserve => "preserve",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for NameCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "snake" => Ok(NameCase::Snake),
+            "camel" => Ok(NameCase::Camel),
+            "preserve" => Ok(NameCase::Preserve),
+            other => Err(format!(
+                "unknown case '{}' (available: {})",
+                other,
+                NameCase::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl Idl {
+    /// Rename instructions, arguments, instruction accounts, PDA seed paths
+    /// and type fields to `case`
+    ///
+    /// Instruction discriminators derived from names (legacy IDLs) are
+    /// pinned first, so encoding and decoding are unaffected.
+    pub fn apply_case(&mut self, case: NameCase) {
+        if case == NameCase::Preserve {
+            return;
+        }
+
+        for ix in &mut self.instructions {
+            ix.discriminator = ix.discriminator_bytes();
+            ix.name = case.apply(&ix.name);
+            rename_fields(&mut ix.args, case);
+            rename_accounts(&mut ix.accounts, case);
+        }
+
+        for ty in &mut self.types {
+            match &mut ty.ty {
+                IdlTypeDefTy::Struct { fields } => rename_fields(fields, case),
+                IdlTypeDefTy::Enum { variants } => {
+                    for variant in variants {
+                        if let Some(IdlEnumFields::Named(fields)) = &mut variant.fields {
+                            rename_fields(fields, case);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.index = Default::default();
+    }
+}
+
+fn rename_fields(fields: &mut [IdlField], case: NameCase) {
+    for field in fields {
+        field.name = case.apply(&field.name);
+    }
+}
+
+fn rename_accounts(items: &mut [IdlAccountItem], case: NameCase) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => {
+                account.name = case.apply(&account.name);
+                for seed in account.pda.iter_mut().flat_map(|pda| &mut pda.seeds) {
+                    if let IdlSeed::Account { path } | IdlSeed::Arg { path } = seed {
+                        *path = path
+                            .split('.')
+                            .map(|segment| case.apply(segment))
+                            .collect::<Vec<_>>()
+                            .join(".");
+                    }
+                }
+            }
+            IdlAccountItem::Group(group) => {
+                group.name = case.apply(&group.name);
+                rename_accounts(&mut group.accounts, case);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_case() {
+        assert_eq!(NameCase::Snake.apply("amountIn"), "amount_in");
+        assert_eq!(NameCase::Camel.apply("amount_in"), "amountIn");
+        assert_eq!(NameCase::Camel.apply("_reserved_v2"), "_reservedV2");
+        assert_eq!(NameCase::Preserve.apply("amountIn"), "amountIn");
+
+        let mut idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "legacy" },
+            "instructions": [{
+                "name": "swapExactIn",
+                "accounts": [{ "name": "userAta", "pda": { "seeds": [
+                    { "kind": "account", "path": "poolState.tokenMint" }
+                ] } }],
+                "args": [{ "name": "amountIn", "type": "u64" }]
+            }],
+            "types": [{ "name": "PoolState", "type": { "kind": "struct", "fields": [
+                { "name": "tokenMint", "type": "pubkey" }
+            ] } }]
+        }))
+        .unwrap();
+        let discriminator = idl.instructions[0].discriminator_bytes();
+
+        idl.apply_case(NameCase::Snake);
+        let ix = &idl.instructions[0];
+        assert_eq!(ix.name, "swap_exact_in");
+        assert_eq!(ix.discriminator, discriminator);
+        assert_eq!(ix.args[0].name, "amount_in");
+        let IdlAccountItem::Single(account) = &ix.accounts[0] else {
+            panic!("single account")
+        };
+        assert_eq!(account.name, "user_ata");
+        assert!(matches!(
+            &account.pda.as_ref().unwrap().seeds[0],
+            IdlSeed::Account { path } if path == "pool_state.token_mint"
+        ));
+        assert!(idl.find_instruction("swap_exact_in").is_some());
+        let IdlTypeDefTy::Struct { fields } = &idl.types[0].ty else {
+            panic!("struct")
+        };
+        assert_eq!(fields[0].name, "token_mint");
+    }
+}
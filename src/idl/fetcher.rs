@@ -1,26 +1,34 @@
 //! IDL fetching from multiple sources: on-chain, file, or URL
 
-use crate::cli::IdlSource;
 use crate::error::{PeriscopeError, PeriscopeResult};
-use crate::idl::legacy::LegacyIdl;
-use crate::idl::Idl;
+use crate::idl::{Idl, IdlSpec};
+use crate::metrics::{timed, Stage};
+#[cfg(feature = "cli")]
+use crate::{
+    bundle::Bundle,
+    cli::IdlSource,
+    idl::{fetch_idl_buffer, fetch_idl_from_chain, fetch_idl_from_url, rpc_client},
+};
 use flate2::read::{DeflateDecoder, ZlibDecoder};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::io::Read;
 use std::path::Path;
-use std::time::Duration;
 
 pub const IDL_SEED: &str = "anchor:idl";
 
-const HTTP_TIMEOUT_SECS: u64 = 30;
 const DISCRIMINATOR_SIZE: usize = 8;
 const AUTHORITY_SIZE: usize = 32;
 const DATA_LEN_SIZE: usize = 4;
 const DATA_LEN_OFFSET: usize = DISCRIMINATOR_SIZE + AUTHORITY_SIZE;
-const HEADER_SIZE: usize = DATA_LEN_OFFSET + DATA_LEN_SIZE;
+/// Bytes before the compressed IDL: discriminator, authority, `data_len`
+pub(crate) const HEADER_SIZE: usize = DATA_LEN_OFFSET + DATA_LEN_SIZE;
+
+/// Default cap on a decompressed or downloaded IDL (guards against
+/// decompression bombs); functions taking a `limit` accept another
+pub const DEFAULT_MAX_IDL_SIZE: usize = 8 * 1024 * 1024;
 
 /// Load IDL from the specified source.
+#[cfg(feature = "cli")]
 pub async fn load_idl(
     source: IdlSource,
     program_id: &Pubkey,
@@ -30,30 +38,50 @@ pub async fn load_idl(
         IdlSource::OnChain => fetch_idl_from_chain(program_id, rpc_url),
         IdlSource::File(path) => load_idl_from_file(&path),
         IdlSource::Url(url) => fetch_idl_from_url(&url).await,
+        IdlSource::Bundle {
+            path,
+            program_id: entry,
+        } => {
+            let entry = entry.unwrap_or_else(|| program_id.to_string());
+            parse_idl_json(&Bundle::read(Path::new(&path))?.idl_json(Some(&entry))?)
+        }
+        IdlSource::Buffer(buffer) => {
+            let buffer = buffer
+                .parse::<Pubkey>()
+                .map_err(|_| PeriscopeError::InvalidProgramId(buffer.clone()))?;
+            let buffer = fetch_idl_buffer(&rpc_client(rpc_url), &buffer, DEFAULT_MAX_IDL_SIZE)?;
+            parse_idl_json(&buffer.json)
+        }
     }
 }
 
-/// Fetch IDL from on-chain IDL account.
-pub fn fetch_idl_from_chain(program_id: &Pubkey, rpc_url: &str) -> PeriscopeResult<Idl> {
-    let client = RpcClient::new(rpc_url.to_string());
-    fetch_idl_with_client(&client, program_id)
-}
-
-/// Fetch IDL using an existing RPC client.
-pub fn fetch_idl_with_client(client: &RpcClient, program_id: &Pubkey) -> PeriscopeResult<Idl> {
-    let idl_address = get_idl_address(program_id)?;
-
-    let account = client.get_account(&idl_address).map_err(|e| {
-        let error_str = e.to_string();
-        if error_str.contains("AccountNotFound") || error_str.contains("could not find account") {
-            PeriscopeError::IdlNotFound(program_id.to_string())
-        } else {
-            PeriscopeError::RpcError(e)
-        }
-    })?;
+/// Extract the compressed IDL from raw IDL account data.
+///
+/// Layout: 8-byte discriminator, 32-byte authority, u32 `data_len`, then
+/// `data_len` bytes of compressed JSON. The data is untrusted, so every
+/// length is checked against what is actually there.
+pub fn idl_account_payload(data: &[u8]) -> PeriscopeResult<&[u8]> {
+    let data_len = idl_account_data_len(data)?;
+    if data_len == 0 {
+        return Err(PeriscopeError::DecompressionError(
+            "IDL compressed data is empty".to_string(),
+        ));
+    }
 
-    let data = account.data;
+    data.get(HEADER_SIZE..)
+        .and_then(|payload| payload.get(..data_len))
+        .ok_or_else(|| {
+            PeriscopeError::DecompressionError(format!(
+                "Compressed data truncated: expected {} bytes, got {}",
+                data_len,
+                data.len() - HEADER_SIZE
+            ))
+        })
+}
 
+/// Length of the compressed IDL, from the header of raw IDL account data
+/// (the rest of the data need not be there)
+pub fn idl_account_data_len(data: &[u8]) -> PeriscopeResult<usize> {
     if data.len() < HEADER_SIZE {
         return Err(PeriscopeError::DecompressionError(
             "Account data too small for IDL header".to_string(),
@@ -63,33 +91,25 @@ pub fn fetch_idl_with_client(client: &RpcClient, program_id: &Pubkey) -> Perisco
     let data_len_bytes: [u8; 4] = data[DATA_LEN_OFFSET..DATA_LEN_OFFSET + DATA_LEN_SIZE]
         .try_into()
         .map_err(|_| PeriscopeError::DecompressionError("Failed to read data_len".to_string()))?;
-    let data_len = u32::from_le_bytes(data_len_bytes) as usize;
-
-    if data_len == 0 {
-        return Err(PeriscopeError::DecompressionError(
-            "IDL compressed data is empty".to_string(),
-        ));
-    }
-
-    if data.len() < HEADER_SIZE + data_len {
-        return Err(PeriscopeError::DecompressionError(format!(
-            "Compressed data truncated: expected {} bytes, got {}",
-            data_len,
-            data.len() - HEADER_SIZE
-        )));
-    }
-
-    let compressed = &data[HEADER_SIZE..HEADER_SIZE + data_len];
-    let json_bytes = decompress_idl_data(compressed)?;
-
-    let json_str = std::str::from_utf8(&json_bytes)
-        .map_err(|_| PeriscopeError::DecompressionError("Invalid UTF-8".to_string()))?;
+    Ok(u32::from_le_bytes(data_len_bytes) as usize)
+}
 
-    parse_idl_json(json_str)
+/// The authority allowed to update an IDL, from raw IDL account data
+pub fn idl_account_authority(data: &[u8]) -> PeriscopeResult<Pubkey> {
+    data.get(DISCRIMINATOR_SIZE..DATA_LEN_OFFSET)
+        .and_then(|bytes| Pubkey::try_from(bytes).ok())
+        .ok_or_else(|| {
+            PeriscopeError::DecompressionError("Account data too small for IDL header".to_string())
+        })
 }
 
 /// Load IDL from a local JSON file.
 pub fn load_idl_from_file(path: &str) -> PeriscopeResult<Idl> {
+    parse_idl_json(&read_idl_json_from_file(path)?)
+}
+
+/// Read IDL JSON from a local file without parsing it.
+pub fn read_idl_json_from_file(path: &str) -> PeriscopeResult<String> {
     let path = Path::new(path);
 
     if !path.exists() {
@@ -99,109 +119,78 @@ pub fn load_idl_from_file(path: &str) -> PeriscopeResult<Idl> {
         )));
     }
 
-    let contents = std::fs::read_to_string(path)?;
-    parse_idl_json(&contents)
+    Ok(timed(Stage::ReadFile, || std::fs::read_to_string(path))?)
 }
 
-/// Fetch IDL from a remote URL.
-pub async fn fetch_idl_from_url(url: &str) -> PeriscopeResult<Idl> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
-        .build()
-        .map_err(|e| {
-            PeriscopeError::NetworkError(format!("Failed to create HTTP client: {}", e))
-        })?;
-
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| PeriscopeError::NetworkError(format!("HTTP request failed: {}", e)))?;
-
-    if !response.status().is_success() {
-        return Err(PeriscopeError::HttpError {
-            status: response.status().as_u16(),
-            url: url.to_string(),
-        });
-    }
-
-    let body = response.text().await.map_err(|e| {
-        PeriscopeError::NetworkError(format!("Failed to read response body: {}", e))
-    })?;
-
-    parse_idl_json(&body)
-}
-
-/// Parse IDL JSON, auto-detecting format (new 0.1.0 spec vs legacy).
-fn parse_idl_json(json_str: &str) -> PeriscopeResult<Idl> {
-    let value: serde_json::Value = serde_json::from_str(json_str)?;
-
-    let is_new_format = value.get("address").map(|v| v.is_string()).unwrap_or(false);
-
-    if is_new_format {
-        return serde_json::from_str(json_str).map_err(PeriscopeError::ParseError);
-    }
-
-    if value.get("name").is_some() {
-        if let Ok(legacy) = serde_json::from_str::<LegacyIdl>(json_str) {
-            return Ok(legacy.into());
-        }
-    }
-
-    if let Ok(idl) = serde_json::from_str::<Idl>(json_str) {
-        return Ok(idl);
-    }
-
-    if let Ok(legacy) = serde_json::from_str::<LegacyIdl>(json_str) {
-        return Ok(legacy.into());
-    }
-
-    Err(PeriscopeError::ParseError(
-        serde_json::from_str::<Idl>(json_str).unwrap_err(),
-    ))
+/// Parse IDL JSON, auto-detecting its spec (new 0.1.0 spec vs legacy).
+///
+/// New-format IDLs that fail strict parsing are parsed leniently. See
+/// [`IdlSpec`] for how the spec is detected.
+pub fn parse_idl_json(json_str: &str) -> PeriscopeResult<Idl> {
+    timed(Stage::Parse, || IdlSpec::detect(json_str)?.parse(json_str))
 }
 
 /// Derive the IDL account address for a program.
 pub fn get_idl_address(program_id: &Pubkey) -> PeriscopeResult<Pubkey> {
-    let (program_signer, _bump) = Pubkey::find_program_address(&[], program_id);
+    timed(Stage::DeriveAddress, || {
+        let (program_signer, _bump) = Pubkey::find_program_address(&[], program_id);
 
-    let idl_address = Pubkey::create_with_seed(&program_signer, IDL_SEED, program_id)
-        .map_err(|e| PeriscopeError::InvalidProgramId(e.to_string()))?;
+        Pubkey::create_with_seed(&program_signer, IDL_SEED, program_id)
+            .map_err(|e| PeriscopeError::InvalidProgramId(e.to_string()))
+    })
+}
 
-    Ok(idl_address)
+/// Decompress the payload of an IDL account (zlib, falling back to raw deflate).
+///
+/// Output is capped at [`DEFAULT_MAX_IDL_SIZE`].
+pub fn decompress_idl_data(compressed: &[u8]) -> PeriscopeResult<Vec<u8>> {
+    decompress_idl_data_with_limit(compressed, DEFAULT_MAX_IDL_SIZE)
 }
 
-fn decompress_idl_data(compressed: &[u8]) -> PeriscopeResult<Vec<u8>> {
-    if let Ok(bytes) = decompress_zlib(compressed) {
-        return Ok(bytes);
-    }
+/// Decompress an IDL account payload, failing with `IdlTooLarge` past `limit` bytes.
+pub fn decompress_idl_data_with_limit(compressed: &[u8], limit: usize) -> PeriscopeResult<Vec<u8>> {
+    let bytes = timed(Stage::Decompress, || {
+        read_bounded(ZlibDecoder::new(compressed), limit)
+            .or_else(|_| read_bounded(DeflateDecoder::new(compressed), limit))
+    })
+    .map_err(|_| PeriscopeError::DecompressionError("Failed to decompress IDL data".to_string()))?;
 
-    if let Ok(bytes) = decompress_deflate(compressed) {
-        return Ok(bytes);
+    if bytes.len() > limit {
+        return Err(PeriscopeError::IdlTooLarge { limit });
     }
-
-    Err(PeriscopeError::DecompressionError(
-        "Failed to decompress IDL data".to_string(),
-    ))
+    Ok(bytes)
 }
 
-fn decompress_zlib(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    let mut decoder = ZlibDecoder::new(data);
-    let mut bytes = Vec::new();
-    decoder.read_to_end(&mut bytes)?;
-    Ok(bytes)
+/// Adler-32 of an uncompressed IDL: the checksum a zlib-compressed IDL
+/// ends with, so a cached IDL can be compared with the one on-chain by
+/// reading those 4 bytes only
+pub fn idl_checksum(json: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` could overflow
+    for chunk in json.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
 }
 
-fn decompress_deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    let mut decoder = DeflateDecoder::new(data);
+/// Read at most one byte past `limit`, enough to detect oversized output
+fn read_bounded(reader: impl Read, limit: usize) -> std::io::Result<Vec<u8>> {
     let mut bytes = Vec::new();
-    decoder.read_to_end(&mut bytes)?;
+    reader.take(limit as u64 + 1).read_to_end(&mut bytes)?;
     Ok(bytes)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::idl::IdlNames;
+    use proptest::prelude::*;
 
     #[test]
     fn test_get_idl_address() {
@@ -225,6 +214,21 @@ mod tests {
         assert_eq!(addr1, addr2);
     }
 
+    #[test]
+    fn test_idl_checksum_is_the_zlib_trailer() {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        let json = br#"{"address":"11111111111111111111111111111111"}"#.repeat(500);
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&json).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let trailer: [u8; 4] = compressed[compressed.len() - 4..].try_into().unwrap();
+
+        assert_eq!(idl_checksum(&json), u32::from_be_bytes(trailer));
+        assert_eq!(idl_checksum(b""), 1);
+    }
+
     #[test]
     fn test_header_constants() {
         assert_eq!(DISCRIMINATOR_SIZE, 8);
@@ -233,4 +237,150 @@ mod tests {
         assert_eq!(DATA_LEN_OFFSET, 40);
         assert_eq!(HEADER_SIZE, 44);
     }
+
+    #[test]
+    fn test_idl_account_payload_bounds() {
+        let mut data = vec![0u8; HEADER_SIZE];
+        assert!(idl_account_payload(&data[..HEADER_SIZE - 1]).is_err());
+        assert!(idl_account_payload(&data).is_err());
+
+        // data_len far beyond the account must not be trusted
+        data[DATA_LEN_OFFSET..HEADER_SIZE].copy_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(b"abc");
+        assert!(idl_account_payload(&data).is_err());
+
+        data[DATA_LEN_OFFSET..HEADER_SIZE].copy_from_slice(&2u32.to_le_bytes());
+        assert_eq!(idl_account_payload(&data).unwrap(), b"ab");
+    }
+
+    #[test]
+    fn test_decompress_rejects_bombs() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![b' '; 1024 * 1024]).unwrap();
+        let bomb = encoder.finish().unwrap();
+        assert!(bomb.len() < 4 * 1024);
+
+        assert!(matches!(
+            decompress_idl_data_with_limit(&bomb, 64 * 1024),
+            Err(PeriscopeError::IdlTooLarge { limit: 65536 })
+        ));
+        assert_eq!(
+            decompress_idl_data_with_limit(&bomb, 1024 * 1024)
+                .unwrap()
+                .len(),
+            1024 * 1024
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_untrusted_input_never_panics(
+            mut data in prop::collection::vec(any::<u8>(), 0..128),
+            data_len in prop::option::of(0..256u32),
+        ) {
+            if let Some(data_len) = data_len.filter(|_| data.len() >= HEADER_SIZE) {
+                data[DATA_LEN_OFFSET..HEADER_SIZE].copy_from_slice(&data_len.to_le_bytes());
+            }
+            if let Ok(payload) = idl_account_payload(&data) {
+                let _ = decompress_idl_data(payload);
+            }
+            let _ = decompress_idl_data(&data);
+        }
+
+        #[test]
+        fn test_parse_idl_json_round_trips(
+            json in arbitrary_idl().prop_map(|idl| idl.to_string()),
+            cut in any::<prop::sample::Index>(),
+        ) {
+            let idl = parse_idl_json(&json).unwrap_or_else(|e| panic!("{}: {}", e, json));
+            prop_assert!(idl.warnings.is_empty(), "{}", json);
+
+            let names = IdlNames::parse(&json).unwrap();
+            prop_assert_eq!(names.instructions.len(), idl.instructions.len());
+            prop_assert_eq!(names.name, idl.metadata.name.as_str());
+
+            let reserialized = serde_json::to_string(&idl).unwrap();
+            let again = parse_idl_json(&reserialized).unwrap();
+            prop_assert_eq!(serde_json::to_string(&again).unwrap(), reserialized);
+
+            // Corrupted input may fail, but must not panic
+            let _ = parse_idl_json(json.get(..cut.index(json.len())).unwrap_or_default());
+        }
+    }
+
+    /// IDL JSON with awkward names (escapes, unicode) and nested types
+    fn arbitrary_idl() -> impl Strategy<Value = serde_json::Value> {
+        use serde_json::json;
+
+        fn name() -> impl Strategy<Value = String> {
+            const CHARS: &[char] = &['a', 'Z', '_', '0', '"', '\\', '\n', 'é', '🦀', ' '];
+            prop::collection::vec(prop::sample::select(CHARS), 1..8)
+                .prop_map(|chars| chars.into_iter().collect())
+        }
+        fn ty() -> impl Strategy<Value = serde_json::Value> {
+            const PRIMITIVES: &[&str] = &["u8", "i64", "u128", "bool", "string", "pubkey", "bytes"];
+            prop::sample::select(PRIMITIVES)
+                .prop_map(|p| json!(p))
+                .prop_recursive(3, 16, 1, |inner| {
+                    prop_oneof![
+                        inner.clone().prop_map(|ty| json!({ "vec": ty })),
+                        inner.clone().prop_map(|ty| json!({ "option": ty })),
+                        (inner, 0..64usize).prop_map(|(ty, len)| json!({ "array": [ty, len] })),
+                        name().prop_map(|name| json!({ "defined": { "name": name } })),
+                    ]
+                })
+        }
+        fn fields() -> impl Strategy<Value = Vec<serde_json::Value>> {
+            prop::collection::vec(
+                (name(), ty()).prop_map(|(name, ty)| json!({ "name": name, "type": ty })),
+                0..4,
+            )
+        }
+
+        let instruction = (name(), any::<[u8; 8]>(), name(), any::<bool>(), fields()).prop_map(
+            |(name, discriminator, account, writable, args)| {
+                json!({
+                    "name": name,
+                    "discriminator": discriminator,
+                    "accounts": [{ "name": account, "writable": writable }],
+                    "args": args,
+                })
+            },
+        );
+        let variant =
+            (name(), fields()).prop_map(|(name, fields)| json!({ "name": name, "fields": fields }));
+        let kind = prop_oneof![
+            fields().prop_map(|fields| json!({ "kind": "struct", "fields": fields })),
+            prop::collection::vec(variant, 1..4)
+                .prop_map(|variants| json!({ "kind": "enum", "variants": variants })),
+        ];
+        let ty_def = (name(), kind).prop_map(|(name, kind)| json!({ "name": name, "type": kind }));
+        let errors = prop::collection::vec((name(), name()), 0..3).prop_map(|errors| {
+            errors
+                .into_iter()
+                .enumerate()
+                .map(|(i, (name, msg))| json!({ "code": 6000 + i, "name": name, "msg": msg }))
+                .collect::<Vec<_>>()
+        });
+
+        (
+            name(),
+            prop::collection::vec(instruction, 0..5),
+            prop::collection::vec(ty_def, 0..5),
+            errors,
+        )
+            .prop_map(|(name, instructions, types, errors)| {
+                json!({
+                    "address": "11111111111111111111111111111111",
+                    "metadata": { "name": name, "version": "0.1.0", "spec": "0.1.0" },
+                    "instructions": instructions,
+                    "types": types,
+                    "errors": errors,
+                })
+            })
+    }
 }
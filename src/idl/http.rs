@@ -0,0 +1,157 @@
+//! IDL fetching over HTTP (`fetch-http` feature)
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{normalize_idl_url, parse_idl_json, Idl, DEFAULT_MAX_IDL_SIZE};
+use crate::metrics::{record, Stage};
+use std::time::{Duration, Instant};
+
+/// User-Agent sent with HTTP requests; some CDNs reject requests without one
+pub const USER_AGENT: &str = concat!("periscope/", env!("CARGO_PKG_VERSION"));
+
+const HTTP_TIMEOUT_SECS: u64 = 30;
+const MAX_REDIRECTS: usize = 5;
+
+/// Fetch IDL from a remote URL.
+pub async fn fetch_idl_from_url(url: &str) -> PeriscopeResult<Idl> {
+    parse_idl_json(&fetch_idl_json_from_url(url, DEFAULT_MAX_IDL_SIZE).await?)
+}
+
+/// Fetch IDL JSON from a remote URL without parsing it.
+///
+/// Code-hosting page URLs are rewritten to raw file URLs (see
+/// [`normalize_idl_url`]), also when a redirect lands on such a page. The
+/// body is streamed and capped at `limit` bytes; HTML pages are rejected
+/// from the `Content-Type` before the body is downloaded. gzip and brotli
+/// responses are decompressed transparently (the size cap applies to the
+/// decompressed body).
+pub async fn fetch_idl_json_from_url(url: &str, limit: usize) -> PeriscopeResult<String> {
+    let started = Instant::now();
+    let result = download(url, limit).await;
+    record(Stage::Http, started.elapsed());
+    result
+}
+
+async fn download(url: &str, limit: usize) -> PeriscopeResult<String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+        .user_agent(USER_AGENT)
+        .gzip(true)
+        .brotli(true)
+        .build()
+        .map_err(|e| {
+            PeriscopeError::NetworkError(format!("Failed to create HTTP client: {}", e))
+        })?;
+
+    let url = normalize_idl_url(url);
+    let mut response = send(&client, &url).await?;
+    let mut content_type = content_type_of(&response);
+
+    // A short link may redirect to a page view of the file
+    if is_html(&content_type) {
+        let landed = response.url().to_string();
+        let raw = normalize_idl_url(&landed);
+        if raw != landed {
+            response = send(&client, &raw).await?;
+            content_type = content_type_of(&response);
+        }
+    }
+
+    if is_html(&content_type) {
+        return Err(PeriscopeError::NotJson {
+            url: url.to_string(),
+            content_type,
+        });
+    }
+
+    if response.content_length().unwrap_or(0) > limit as u64 {
+        return Err(PeriscopeError::IdlTooLarge { limit });
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| PeriscopeError::NetworkError(format!("Failed to read response body: {}", e)))?
+    {
+        if body.len() + chunk.len() > limit {
+            return Err(PeriscopeError::IdlTooLarge { limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    check_json_body(&url, &content_type, &body)?;
+    String::from_utf8(body).map_err(|_| PeriscopeError::NotJson {
+        url: url.to_string(),
+        content_type,
+    })
+}
+
+async fn send(client: &reqwest::Client, url: &str) -> PeriscopeResult<reqwest::Response> {
+    let response = client.get(url).send().await.map_err(|e| {
+        if e.is_redirect() {
+            PeriscopeError::NetworkError(format!(
+                "Too many redirects (more than {}) fetching {}",
+                MAX_REDIRECTS, url
+            ))
+        } else {
+            PeriscopeError::NetworkError(format!("HTTP request failed: {}", e))
+        }
+    })?;
+
+    if !response.status().is_success() {
+        return Err(PeriscopeError::HttpError {
+            status: response.status().as_u16(),
+            url: url.to_string(),
+        });
+    }
+    Ok(response)
+}
+
+fn content_type_of(response: &reqwest::Response) -> String {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn is_html(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    mime.eq_ignore_ascii_case("text/html") || mime.eq_ignore_ascii_case("application/xhtml+xml")
+}
+
+/// Reject bodies that can't be an IDL (HTML served as text/plain, error pages)
+fn check_json_body(url: &str, content_type: &str, body: &[u8]) -> PeriscopeResult<()> {
+    let first = body.iter().find(|b| !b.is_ascii_whitespace());
+    match first {
+        Some(b'{') => Ok(()),
+        _ => Err(PeriscopeError::NotJson {
+            url: url.to_string(),
+            content_type: if content_type.is_empty() {
+                "a non-JSON body".to_string()
+            } else {
+                content_type.to_string()
+            },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_body_checks() {
+        let url = "https://example.com/idl.json";
+        assert!(is_html("text/html; charset=utf-8"));
+        assert!(!is_html("text/plain; charset=utf-8"));
+        assert!(check_json_body(url, "text/plain", b"\n  {\"address\": 1}").is_ok());
+        assert!(matches!(
+            check_json_body(url, "text/plain", b"<!DOCTYPE html><html>"),
+            Err(PeriscopeError::NotJson { .. })
+        ));
+        assert!(check_json_body(url, "", b"").is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Lookup indexes over an IDL
+//!
+//! Decoding resolves a defined type for every nested struct field and an
+//! account, instruction or event by discriminator for every payload; with
+//! hundreds of types a linear scan per lookup dominates decode time.
+//! [`IdlIndex`] maps names, discriminators and codes to positions in the
+//! IDL's vectors.
+
+use crate::idl::{
+    Idl, IdlAccountRef, IdlError, IdlEventRef, IdlGenericArg, IdlInstruction, IdlTypeDef,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Positions of IDL items keyed by name, discriminator or code
+///
+/// When several items share a key the first one wins, as with a linear scan.
+#[derive(Debug, Clone, Default)]
+pub struct IdlIndex {
+    /// Lowercased instruction name -> position in `instructions`
+    instructions: HashMap<String, usize>,
+    instruction_discriminators: HashMap<Vec<u8>, usize>,
+    types: HashMap<String, usize>,
+    /// Lowercased type name -> position in `types`
+    type_names: HashMap<String, usize>,
+    account_discriminators: HashMap<Vec<u8>, usize>,
+    event_discriminators: HashMap<Vec<u8>, usize>,
+    errors: HashMap<u32, usize>,
+}
+
+impl IdlIndex {
+    /// Build the indexes for `idl`
+    pub fn build(idl: &Idl) -> Self {
+        let mut index = IdlIndex::default();
+
+        for (i, ix) in idl.instructions.iter().enumerate() {
+            index
+                .instructions
+                .entry(ix.name.to_lowercase())
+                .or_insert(i);
+            index
+                .instruction_discriminators
+                .entry(ix.discriminator_bytes())
+                .or_insert(i);
+        }
+        for (i, ty) in idl.types.iter().enumerate() {
+            index.types.entry(ty.name.clone()).or_insert(i);
+            index.type_names.entry(ty.name.to_lowercase()).or_insert(i);
+        }
+        for (i, account) in idl.accounts.iter().enumerate() {
+            index
+                .account_discriminators
+                .entry(account.discriminator_bytes())
+                .or_insert(i);
+        }
+        for (i, event) in idl.events.iter().enumerate() {
+            index
+                .event_discriminators
+                .entry(event.discriminator.clone())
+                .or_insert(i);
+        }
+        for (i, error) in idl.errors.iter().enumerate() {
+            index.errors.entry(error.code).or_insert(i);
+        }
+
+        index
+    }
+
+    /// Position of the instruction named `name` (case-insensitive)
+    pub fn instruction(&self, name: &str) -> Option<usize> {
+        self.instructions.get(&name.to_lowercase()).copied()
+    }
+
+    /// Position of the instruction with this discriminator
+    pub fn instruction_by_discriminator(&self, discriminator: &[u8]) -> Option<usize> {
+        self.instruction_discriminators.get(discriminator).copied()
+    }
+
+    /// Position of the type named `name`
+    pub fn type_def(&self, name: &str) -> Option<usize> {
+        self.types.get(name).copied()
+    }
+
+    /// Position of the type named `name`, ignoring case
+    pub fn type_def_ignore_case(&self, name: &str) -> Option<usize> {
+        self.type_names.get(&name.to_lowercase()).copied()
+    }
+
+    /// Position of the account type with this discriminator
+    pub fn account_by_discriminator(&self, discriminator: &[u8]) -> Option<usize> {
+        self.account_discriminators.get(discriminator).copied()
+    }
+
+    /// Position of the event with this discriminator
+    pub fn event_by_discriminator(&self, discriminator: &[u8]) -> Option<usize> {
+        self.event_discriminators.get(discriminator).copied()
+    }
+
+    /// Position of the error with this code
+    pub fn error(&self, code: u32) -> Option<usize> {
+        self.errors.get(&code).copied()
+    }
+}
+
+impl Idl {
+    /// Lookup indexes, built on first use and cached
+    ///
+    /// The cache is not invalidated: build the IDL completely before the
+    /// first lookup.
+    pub fn index(&self) -> &IdlIndex {
+        self.index.get_or_init(|| IdlIndex::build(self))
+    }
+
+    /// Find an instruction by name (case-insensitive)
+    pub fn find_instruction(&self, name: &str) -> Option<&IdlInstruction> {
+        self.index()
+            .instruction(name)
+            .map(|i| &self.instructions[i])
+    }
+
+    /// Find the instruction with this discriminator
+    pub fn find_instruction_by_discriminator(
+        &self,
+        discriminator: &[u8],
+    ) -> Option<&IdlInstruction> {
+        self.index()
+            .instruction_by_discriminator(discriminator)
+            .map(|i| &self.instructions[i])
+    }
+
+    /// Find a custom type definition by name
+    pub fn find_type(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.index().type_def(name).map(|i| &self.types[i])
+    }
+
+    /// Find a custom type definition by name, ignoring case
+    pub fn find_type_ignore_case(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.index()
+            .type_def_ignore_case(name)
+            .map(|i| &self.types[i])
+    }
+
+    /// Find a type definition and instantiate it with the generic arguments
+    /// of a reference to it (borrowed when it isn't generic)
+    pub fn instantiate_type(
+        &self,
+        name: &str,
+        generics: &[IdlGenericArg],
+    ) -> Result<Cow<'_, IdlTypeDef>, String> {
+        let type_def = self
+            .find_type(name)
+            .ok_or_else(|| format!("type '{}' is not defined in the IDL", name))?;
+        if generics.is_empty() && type_def.generics.is_empty() {
+            return Ok(Cow::Borrowed(type_def));
+        }
+        type_def.instantiate(generics).map(Cow::Owned)
+    }
+
+    /// Find the account type with this discriminator
+    pub fn find_account_by_discriminator(&self, discriminator: &[u8]) -> Option<&IdlAccountRef> {
+        self.index()
+            .account_by_discriminator(discriminator)
+            .map(|i| &self.accounts[i])
+    }
+
+    /// Find the event with this discriminator
+    pub fn find_event_by_discriminator(&self, discriminator: &[u8]) -> Option<&IdlEventRef> {
+        self.index()
+            .event_by_discriminator(discriminator)
+            .map(|i| &self.events[i])
+    }
+
+    /// Find an error by code
+    pub fn find_error(&self, code: u32) -> Option<&IdlError> {
+        self.index().error(code).map(|i| &self.errors[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_index_lookups() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "init", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8], "accounts": [], "args": [] },
+                { "name": "Init", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9], "accounts": [], "args": [] }
+            ],
+            "accounts": [{ "name": "Pool", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1] }],
+            "events": [{ "name": "Swapped", "discriminator": [3, 3, 3, 3, 3, 3, 3, 3] }],
+            "types": [{ "name": "Pool", "type": { "kind": "struct", "fields": [] } }],
+            "errors": [{ "code": 6000, "name": "Oops" }]
+        }))
+        .unwrap();
+
+        assert_eq!(idl.find_instruction("INIT").unwrap().discriminator[0], 1);
+        assert_eq!(
+            idl.find_instruction_by_discriminator(&[9; 8]).unwrap().name,
+            "Init"
+        );
+        assert_eq!(idl.find_type("Pool").unwrap().name, "Pool");
+        assert!(idl.find_type("pool").is_none());
+        assert_eq!(idl.find_type_ignore_case("pool").unwrap().name, "Pool");
+        assert_eq!(
+            idl.find_account_by_discriminator(&[8, 7, 6, 5, 4, 3, 2, 1])
+                .unwrap()
+                .name,
+            "Pool"
+        );
+        assert_eq!(
+            idl.find_event_by_discriminator(&[3; 8]).unwrap().name,
+            "Swapped"
+        );
+        assert!(idl.find_event_by_discriminator(&[4; 8]).is_none());
+        assert_eq!(idl.find_error(6000).unwrap().name, "Oops");
+        assert!(idl.find_error(6001).is_none());
+    }
+}
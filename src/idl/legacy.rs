@@ -5,10 +5,11 @@
 
 use serde::Deserialize;
 
+use super::spec::IdlSpec;
 use super::types::{
-    Idl, IdlAccount, IdlAccountItem, IdlAccountRef, IdlEnumFields, IdlEnumVariant, IdlError,
-    IdlEventRef, IdlField, IdlInstruction, IdlMetadata, IdlType, IdlTypeComplex, IdlTypeDef,
-    IdlTypeDefTy,
+    Idl, IdlAccount, IdlAccountItem, IdlAccountRef, IdlArrayLen, IdlEnumFields, IdlEnumVariant,
+    IdlError, IdlEventRef, IdlField, IdlInstruction, IdlMetadata, IdlSerialization, IdlType,
+    IdlTypeComplex, IdlTypeDef, IdlTypeDefTy,
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -186,7 +187,7 @@ impl From<LegacyIdl> for Idl {
             metadata: IdlMetadata {
                 name: legacy.name,
                 version: legacy.version,
-                spec: "legacy".to_string(),
+                spec: IdlSpec::Legacy.to_string(),
                 description: None,
             },
             instructions: legacy.instructions.into_iter().map(Into::into).collect(),
@@ -213,6 +214,8 @@ impl From<LegacyIdl> for Idl {
                 })
                 .collect(),
             errors: legacy.errors,
+            warnings: Vec::new(),
+            index: Default::default(),
         }
     }
 }
@@ -248,7 +251,10 @@ impl From<LegacyInstructionAccount> for IdlAccount {
 impl From<LegacyTypeDef> for IdlTypeDef {
     fn from(legacy: LegacyTypeDef) -> Self {
         IdlTypeDef {
+            generics: Vec::new(),
             name: legacy.name,
+            serialization: IdlSerialization::default(),
+            repr: None,
             ty: legacy.ty.into(),
         }
     }
@@ -308,9 +314,12 @@ impl From<LegacyTypeComplex> for IdlTypeComplex {
             LegacyTypeComplex::Vec(inner) => IdlTypeComplex::Vec(Box::new((*inner).into())),
             LegacyTypeComplex::Option(inner) => IdlTypeComplex::Option(Box::new((*inner).into())),
             LegacyTypeComplex::Array(inner, size) => {
-                IdlTypeComplex::Array(Box::new((*inner).into()), size)
+                IdlTypeComplex::Array(Box::new((*inner).into()), IdlArrayLen::Value(size))
             }
-            LegacyTypeComplex::Defined(name) => IdlTypeComplex::Defined { name },
+            LegacyTypeComplex::Defined(name) => IdlTypeComplex::Defined {
+                name,
+                generics: Vec::new(),
+            },
         }
     }
 }
@@ -0,0 +1,167 @@
+//! Lenient IDL parsing
+//!
+//! A single unsupported construct (a new type kind, a generic, a malformed
+//! seed) shouldn't make a 300-type IDL unusable. Lenient parsing parses each
+//! instruction, account, type, event and error on its own, keeps the ones
+//! that parse and reports the rest as [`ParseWarning`]s.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{Idl, IdlMetadata};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// An IDL item that was skipped during lenient parsing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// IDL section (`instructions`, `types`, ...)
+    pub section: String,
+
+    /// Index of the item within the section
+    pub index: usize,
+
+    /// Item name, when it could be read
+    pub name: Option<String>,
+
+    /// Why the item was skipped
+    pub reason: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(
+                f,
+                "skipped {}[{}] '{}': {}",
+                self.section, self.index, name, self.reason
+            ),
+            None => write!(
+                f,
+                "skipped {}[{}]: {}",
+                self.section, self.index, self.reason
+            ),
+        }
+    }
+}
+
+impl Idl {
+    /// Parse an IDL (0.1.0 spec), skipping items that fail to parse.
+    ///
+    /// Only the program address and metadata are required; everything else is
+    /// parsed item by item.
+    pub fn parse_lenient(json: &str) -> PeriscopeResult<(Idl, Vec<ParseWarning>)> {
+        let value: Value = serde_json::from_str(json)?;
+        Self::from_value_lenient(value)
+    }
+
+    /// Lenient parse of an already decoded JSON value
+    pub fn from_value_lenient(mut value: Value) -> PeriscopeResult<(Idl, Vec<ParseWarning>)> {
+        let mut warnings = Vec::new();
+
+        let address = match value.get("address").and_then(Value::as_str) {
+            Some(address) => address.to_string(),
+            None => {
+                return Err(PeriscopeError::ParseError(serde::de::Error::missing_field(
+                    "address",
+                )))
+            }
+        };
+        let metadata: IdlMetadata = serde_json::from_value(
+            value
+                .get_mut("metadata")
+                .map(Value::take)
+                .unwrap_or_default(),
+        )?;
+
+        let mut idl = Idl {
+            address,
+            metadata,
+            instructions: parse_section(&mut value, "instructions", &mut warnings),
+            accounts: parse_section(&mut value, "accounts", &mut warnings),
+            types: parse_section(&mut value, "types", &mut warnings),
+            events: parse_section(&mut value, "events", &mut warnings),
+            errors: parse_section(&mut value, "errors", &mut warnings),
+            warnings: Vec::new(),
+            index: Default::default(),
+        };
+        idl.warnings = warnings.clone();
+
+        Ok((idl, warnings))
+    }
+}
+
+/// Parse each element of `value[section]`, collecting failures as warnings
+fn parse_section<T: DeserializeOwned>(
+    value: &mut Value,
+    section: &str,
+    warnings: &mut Vec<ParseWarning>,
+) -> Vec<T> {
+    let items = match value.get_mut(section).map(Value::take) {
+        Some(Value::Array(items)) => items,
+        None | Some(Value::Null) => return Vec::new(),
+        Some(other) => {
+            warnings.push(ParseWarning {
+                section: section.to_string(),
+                index: 0,
+                name: None,
+                reason: format!("expected an array, found {}", json_kind(&other)),
+            });
+            return Vec::new();
+        }
+    };
+
+    let mut parsed = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let name = item.get("name").and_then(Value::as_str).map(str::to_string);
+        match serde_json::from_value(item) {
+            Ok(item) => parsed.push(item),
+            Err(e) => warnings.push(ParseWarning {
+                section: section.to_string(),
+                index,
+                name,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    parsed
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lenient_skips_bad_items() {
+        let json = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "types": [
+                { "name": "Good", "type": { "kind": "struct", "fields": [] } },
+                { "name": "Weird", "type": { "kind": "type", "alias": "u64" } }
+            ]
+        }"#;
+
+        let (idl, warnings) = Idl::parse_lenient(json).unwrap();
+        assert_eq!(idl.types.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].section, "types");
+        assert_eq!(warnings[0].index, 1);
+        assert_eq!(warnings[0].name.as_deref(), Some("Weird"));
+        assert_eq!(idl.warnings, warnings);
+
+        assert!(Idl::parse_lenient(r#"{ "metadata": {} }"#).is_err());
+    }
+}
@@ -3,9 +3,47 @@
 //! This module handles fetching Anchor IDLs from on-chain
 //! and provides types for working with them.
 
+#[cfg(feature = "anchor-idl")]
+mod anchor;
+mod builder;
+mod canonical;
+mod casing;
 mod fetcher;
+#[cfg(feature = "fetch-http")]
+mod http;
+mod index;
 mod legacy;
+mod lenient;
+mod names;
+#[cfg(feature = "fetch-rpc")]
+mod rpc;
+mod rust_names;
+mod shared;
+mod spec;
 mod types;
+#[cfg(feature = "fetch-http")]
+mod url;
 
+pub use builder::*;
+pub use canonical::CANONICAL_VERSION;
+pub use casing::*;
 pub use fetcher::*;
+#[cfg(feature = "fetch-http")]
+pub use http::*;
+pub use index::*;
+pub use lenient::*;
+pub use names::*;
+pub use periscope_core::discriminator::*;
+#[cfg(feature = "fetch-rpc")]
+pub use rpc::*;
+pub use rust_names::*;
+pub use shared::*;
+pub use spec::*;
 pub use types::*;
+#[cfg(feature = "fetch-http")]
+pub use url::*;
+
+/// Bytes as lowercase hex, e.g. a discriminator in messages and reports
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,381 @@
+//! Zero-copy name index of an IDL
+//!
+//! Listing commands only need names, yet a full [`Idl`](crate::idl::Idl)
+//! parse allocates every field, type and doc string of a multi-megabyte IDL.
+//! [`IdlNames`] borrows names straight from the JSON text and skips
+//! everything else without building it. [`IdlNames::parse_instructions`]
+//! goes further for the `instructions` listing: it scans the raw bytes and
+//! stops as soon as it has the instruction names, leaving the accounts and
+//! types that make up most of a large IDL unread.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// Names of the items in an IDL, borrowed from the JSON text
+///
+/// Works for both the 0.1.0 spec and legacy IDLs. Names containing JSON
+/// escapes are the only ones that allocate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdlNames<'a> {
+    /// Program name
+    pub name: Cow<'a, str>,
+
+    /// Program address (base58), empty when a legacy IDL has none
+    pub address: Cow<'a, str>,
+
+    /// Instruction names, in IDL order
+    pub instructions: Vec<Cow<'a, str>>,
+
+    /// Account type names
+    pub accounts: Vec<Cow<'a, str>>,
+
+    /// Type names
+    pub types: Vec<Cow<'a, str>>,
+
+    /// Event names
+    pub events: Vec<Cow<'a, str>>,
+
+    /// Error codes and names
+    pub errors: Vec<(u32, Cow<'a, str>)>,
+}
+
+#[derive(Deserialize)]
+struct RawNames<'a> {
+    /// Legacy IDLs keep the program name at the top level
+    #[serde(borrow, default)]
+    name: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    address: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    metadata: Option<RawMetadata<'a>>,
+    #[serde(borrow, default)]
+    instructions: Vec<Named<'a>>,
+    #[serde(borrow, default)]
+    accounts: Vec<Named<'a>>,
+    #[serde(borrow, default)]
+    types: Vec<Named<'a>>,
+    #[serde(borrow, default)]
+    events: Vec<Named<'a>>,
+    #[serde(borrow, default)]
+    errors: Vec<NamedError<'a>>,
+}
+
+#[derive(Deserialize)]
+struct RawMetadata<'a> {
+    #[serde(borrow, default)]
+    name: Option<Cow<'a, str>>,
+    /// Legacy IDLs may keep the program address here
+    #[serde(borrow, default)]
+    address: Option<Cow<'a, str>>,
+}
+
+/// Any item with a `name`; unknown keys are skipped without being built
+#[derive(Deserialize)]
+struct Named<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+}
+
+#[derive(Deserialize)]
+struct NamedError<'a> {
+    code: u32,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+}
+
+impl<'a> IdlNames<'a> {
+    /// Read the item names of an IDL without parsing the rest of it
+    pub fn parse(json: &'a str) -> PeriscopeResult<Self> {
+        let raw: RawNames<'a> = serde_json::from_str(json)?;
+
+        let (name, metadata_address) = match raw.metadata {
+            Some(metadata) => (metadata.name, metadata.address),
+            None => (None, None),
+        };
+        let name = name.or(raw.name).unwrap_or_default();
+        let address = raw.address.or(metadata_address).unwrap_or_default();
+        let names = |items: Vec<Named<'a>>| items.into_iter().map(|item| item.name).collect();
+
+        Ok(IdlNames {
+            name,
+            address,
+            instructions: names(raw.instructions),
+            accounts: names(raw.accounts),
+            types: names(raw.types),
+            events: names(raw.events),
+            errors: raw.errors.into_iter().map(|e| (e.code, e.name)).collect(),
+        })
+    }
+}
+
+impl<'a> IdlNames<'a> {
+    /// Read only the program name and instruction names, scanning the JSON
+    /// no further than needed; the other name lists are left empty
+    pub fn parse_instructions(json: &'a str) -> PeriscopeResult<Self> {
+        let mut scanner = Scanner { json, pos: 0 };
+        let mut legacy_name = None;
+        let mut metadata_name = None;
+        let mut address = None;
+        let mut instructions = None;
+
+        scanner.object(|s, key| {
+            match key.as_ref() {
+                "instructions" => {
+                    let mut names = Vec::new();
+                    s.array(|s| {
+                        let mut name = None;
+                        s.object(|s, key| {
+                            match key.as_ref() {
+                                "name" => name = Some(s.string()?),
+                                _ => s.skip_value()?,
+                            }
+                            Ok(false)
+                        })?;
+                        names.push(name.ok_or_else(|| s.error("instruction without a name"))?);
+                        Ok(())
+                    })?;
+                    instructions = Some(names);
+                }
+                "metadata" => {
+                    s.object(|s, key| {
+                        match key.as_ref() {
+                            "name" => metadata_name = Some(s.string()?),
+                            "address" if address.is_none() => address = Some(s.string()?),
+                            _ => s.skip_value()?,
+                        }
+                        Ok(false)
+                    })?;
+                }
+                "name" => legacy_name = Some(s.string()?),
+                "address" => address = Some(s.string()?),
+                _ => s.skip_value()?,
+            }
+            // Both specs put the program name (and address) first, so this
+            // rarely reads on
+            Ok(instructions.is_some() && (metadata_name.is_some() || legacy_name.is_some()))
+        })?;
+
+        Ok(IdlNames {
+            name: metadata_name.or(legacy_name).unwrap_or_default(),
+            address: address.unwrap_or_default(),
+            instructions: instructions.unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Just enough of a JSON tokenizer to walk objects and arrays and skip the
+/// values that aren't wanted. Stopping early means the text after that
+/// point is never checked.
+struct Scanner<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// The next non-whitespace byte, without consuming it
+    fn peek(&mut self) -> Option<u8> {
+        let bytes = self.json.as_bytes();
+        while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> PeriscopeResult<()> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    /// Walk an object, calling `entry` with each key while positioned at
+    /// its value, which `entry` must consume. Stops early when `entry`
+    /// returns true; returns whether it did.
+    fn object(
+        &mut self,
+        mut entry: impl FnMut(&mut Self, Cow<'a, str>) -> PeriscopeResult<bool>,
+    ) -> PeriscopeResult<bool> {
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(false);
+        }
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            if entry(self, key)? {
+                return Ok(true);
+            }
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(false);
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    /// Walk an array, calling `item` positioned at each element, which
+    /// `item` must consume
+    fn array(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> PeriscopeResult<()>,
+    ) -> PeriscopeResult<()> {
+        self.expect(b'[')?;
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            item(self)?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    /// A string, borrowed unless it has escapes
+    fn string(&mut self) -> PeriscopeResult<Cow<'a, str>> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error("expected a string"));
+        }
+        let start = self.pos;
+        let bytes = self.json.as_bytes();
+        let mut escaped = false;
+        self.pos += 1;
+        loop {
+            match bytes.get(self.pos) {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    escaped = true;
+                    self.pos += 2;
+                }
+                Some(_) => self.pos += 1,
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+        self.pos += 1;
+        let literal = &self.json[start..self.pos];
+        if escaped {
+            Ok(Cow::Owned(serde_json::from_str(literal)?))
+        } else {
+            Ok(Cow::Borrowed(&literal[1..literal.len() - 1]))
+        }
+    }
+
+    /// Step over a value of any kind without building it
+    fn skip_value(&mut self) -> PeriscopeResult<()> {
+        match self.peek() {
+            Some(b'"') => self.string().map(|_| ()),
+            Some(b'{' | b'[') => {
+                let bytes = self.json.as_bytes();
+                let mut depth = 0usize;
+                loop {
+                    match bytes.get(self.pos) {
+                        Some(b'"') => {
+                            self.string()?;
+                            continue;
+                        }
+                        Some(b'{' | b'[') => depth += 1,
+                        Some(b'}' | b']') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.pos += 1;
+                                return Ok(());
+                            }
+                        }
+                        Some(_) => {}
+                        None => return Err(self.error("unterminated value")),
+                    }
+                    self.pos += 1;
+                }
+            }
+            Some(_) => {
+                let bytes = self.json.as_bytes();
+                let start = self.pos;
+                while self.pos < bytes.len()
+                    && !matches!(bytes[self.pos], b',' | b'}' | b']')
+                    && !bytes[self.pos].is_ascii_whitespace()
+                {
+                    self.pos += 1;
+                }
+                serde_json::from_str::<serde::de::IgnoredAny>(&self.json[start..self.pos])?;
+                Ok(())
+            }
+            None => Err(self.error("unexpected end of JSON")),
+        }
+    }
+
+    fn error(&self, reason: &str) -> PeriscopeError {
+        PeriscopeError::ParseError(serde::de::Error::custom(format!(
+            "{} at byte {}",
+            reason, self.pos
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_names_borrows() {
+        let json = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "demo", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "init", "accounts": [], "args": [{ "name": "x", "type": "u64" }] },
+                { "name": "say_\"hi\"", "accounts": [], "args": [] }
+            ],
+            "types": [{ "name": "Pool", "type": { "kind": "weird" } }],
+            "errors": [{ "code": 6000, "name": "Oops", "msg": "oops" }]
+        }"#;
+
+        let names = IdlNames::parse(json).unwrap();
+        assert_eq!(names.name, "demo");
+        assert_eq!(names.address, "11111111111111111111111111111111");
+        assert_eq!(names.instructions, vec!["init", "say_\"hi\""]);
+        assert!(matches!(names.instructions[0], Cow::Borrowed(_)));
+        assert!(matches!(names.instructions[1], Cow::Owned(_)));
+        assert_eq!(names.types, vec!["Pool"]);
+        assert_eq!(names.errors, vec![(6000, Cow::Borrowed("Oops"))]);
+
+        let legacy = r#"{ "version": "0.1.0", "name": "old", "instructions": [] }"#;
+        assert_eq!(IdlNames::parse(legacy).unwrap().name, "old");
+    }
+
+    #[test]
+    fn test_parse_instructions_stops_early() {
+        let json = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "demo", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "args": [{ "name": "x", "type": { "vec": "u8" } }], "name": "init", "docs": ["a ] \" }"] },
+                { "name": "say_\"hi\"", "accounts": [], "discriminator": [1, 2] }
+            ],
+            "types": [ this part is never read"#;
+
+        let names = IdlNames::parse_instructions(json).unwrap();
+        assert_eq!(names.name, "demo");
+        assert_eq!(names.address, "11111111111111111111111111111111");
+        assert_eq!(names.instructions, vec!["init", "say_\"hi\""]);
+        assert!(matches!(names.instructions[0], Cow::Borrowed(_)));
+        assert!(names.types.is_empty());
+
+        let legacy = r#"{ "version": "0.1.0", "instructions": [], "name": "old" }"#;
+        let names = IdlNames::parse_instructions(legacy).unwrap();
+        assert_eq!((names.name.as_ref(), names.instructions.len()), ("old", 0));
+        assert!(IdlNames::parse_instructions(r#"{ "instructions": [{ "name": 1 }] }"#).is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! IDL and account fetching over Solana RPC (`fetch-rpc` feature)
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{
+    decompress_idl_data_with_limit, get_idl_address, idl_account_authority, idl_account_data_len,
+    idl_account_payload, parse_idl_json, Idl, DEFAULT_MAX_IDL_SIZE, HEADER_SIZE,
+};
+use crate::metrics::{record, Stage};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{json, Value};
+use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+use solana_client::rpc_request::RpcRequest;
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Timeout of [`rpc_client`], the same as `RpcClient::new`'s
+const RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How the client of [`rpc_client_with`] connects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcOptions {
+    /// Extra headers (name, value) sent with every request, for providers
+    /// that authenticate by header rather than a key in the URL
+    pub headers: Vec<(String, String)>,
+
+    /// Request timeout
+    pub timeout: Duration,
+}
+
+impl Default for RpcOptions {
+    fn default() -> Self {
+        RpcOptions {
+            headers: Vec::new(),
+            timeout: RPC_TIMEOUT,
+        }
+    }
+}
+
+impl RpcOptions {
+    /// Options for a client of `rpc_url`, carrying `headers` configured for
+    /// the `endpoint` RPC URL only when `rpc_url` is on the same scheme, host
+    /// and port: a provider's credentials never go to another host
+    pub fn for_endpoint(rpc_url: &str, endpoint: &str, headers: Vec<(String, String)>) -> Self {
+        let same_origin = match (reqwest::Url::parse(rpc_url), reqwest::Url::parse(endpoint)) {
+            (Ok(url), Ok(endpoint)) => url.origin() == endpoint.origin(),
+            _ => false,
+        };
+        RpcOptions {
+            headers: if same_origin { headers } else { Vec::new() },
+            ..Default::default()
+        }
+    }
+}
+
+/// Check that every header is a valid HTTP header name and value
+pub fn check_rpc_headers(headers: &[(String, String)]) -> PeriscopeResult<()> {
+    header_map(headers).map(|_| ())
+}
+
+fn header_map(headers: &[(String, String)]) -> PeriscopeResult<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let invalid = || PeriscopeError::Usage(format!("Invalid RPC header '{}'", name));
+        let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid())?;
+        let mut value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+        value.set_sensitive(true);
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Split a `Name: value` header
+pub fn parse_rpc_header(header: &str) -> Option<(String, String)> {
+    let (name, value) = header.split_once(':')?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| (name.to_string(), value.trim().to_string()))
+}
+
+/// RPC client for `rpc_url`, without extra headers
+pub fn rpc_client(rpc_url: &str) -> RpcClient {
+    RpcClient::new_with_timeout(rpc_url.to_string(), RPC_TIMEOUT)
+}
+
+/// RPC client for `rpc_url` with `options`
+pub fn rpc_client_with(rpc_url: &str, options: &RpcOptions) -> PeriscopeResult<RpcClient> {
+    if options.headers.is_empty() {
+        return Ok(RpcClient::new_with_timeout(
+            rpc_url.to_string(),
+            options.timeout,
+        ));
+    }
+    let mut headers = HttpSender::default_headers();
+    headers.extend(header_map(&options.headers)?);
+    let http = reqwest::Client::builder()
+        .default_headers(headers)
+        .timeout(options.timeout)
+        .pool_idle_timeout(options.timeout)
+        .build()
+        .map_err(|e| PeriscopeError::NetworkError(format!("Failed to build RPC client: {}", e)))?;
+    Ok(RpcClient::new_sender(
+        HttpSender::new_with_client(rpc_url, http),
+        RpcClientConfig::with_commitment(CommitmentConfig::default()),
+    ))
+}
+
+/// Fetch IDL from on-chain IDL account.
+pub fn fetch_idl_from_chain(program_id: &Pubkey, rpc_url: &str) -> PeriscopeResult<Idl> {
+    let client = rpc_client(rpc_url);
+    fetch_idl_with_client(&client, program_id)
+}
+
+/// Fetch IDL using an existing RPC client.
+pub fn fetch_idl_with_client(client: &RpcClient, program_id: &Pubkey) -> PeriscopeResult<Idl> {
+    parse_idl_json(&fetch_idl_json_with_client(client, program_id)?)
+}
+
+/// Fetch the decompressed IDL JSON from a program's on-chain IDL account.
+pub fn fetch_idl_json_with_client(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> PeriscopeResult<String> {
+    fetch_on_chain_idl(client, program_id, DEFAULT_MAX_IDL_SIZE).map(|idl| idl.json)
+}
+
+/// An IDL read from a program's IDL account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChainIdl {
+    /// Decompressed IDL JSON
+    pub json: String,
+
+    /// IDL authority
+    pub authority: Pubkey,
+
+    /// Length of the compressed IDL in the account, as [`IdlProbe`] reports it
+    pub compressed_len: usize,
+
+    /// Slot the account was read at
+    pub slot: u64,
+}
+
+/// Fetch the decompressed IDL JSON of a program, with its IDL authority and
+/// the slot it was read at. IDLs larger than `limit` bytes decompressed fail
+/// with `IdlTooLarge`.
+pub fn fetch_on_chain_idl(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    limit: usize,
+) -> PeriscopeResult<OnChainIdl> {
+    let idl_address = get_idl_address(program_id)?;
+
+    let (account, slot) = fetch_account_with_slot(client, &idl_address).map_err(|e| match e {
+        PeriscopeError::AccountNotFound(_) => PeriscopeError::IdlNotFound(program_id.to_string()),
+        e => e,
+    })?;
+    on_chain_idl(&account.data, slot, limit)
+}
+
+/// Fetch the IDL staged in a buffer account by `anchor idl write-buffer`,
+/// before `anchor idl set-buffer` publishes it. Buffers have the IDL
+/// account layout; their authority is the IDL authority to be.
+pub fn fetch_idl_buffer(
+    client: &RpcClient,
+    buffer: &Pubkey,
+    limit: usize,
+) -> PeriscopeResult<OnChainIdl> {
+    let (account, slot) = fetch_account_with_slot(client, buffer)?;
+    on_chain_idl(&account.data, slot, limit)
+}
+
+fn on_chain_idl(data: &[u8], slot: u64, limit: usize) -> PeriscopeResult<OnChainIdl> {
+    let authority = idl_account_authority(data)?;
+    let payload = idl_account_payload(data)?;
+    let compressed_len = payload.len();
+    let json_bytes = decompress_idl_data_with_limit(payload, limit)?;
+
+    let json = String::from_utf8(json_bytes)
+        .map_err(|_| PeriscopeError::DecompressionError("Invalid UTF-8".to_string()))?;
+    Ok(OnChainIdl {
+        json,
+        authority,
+        compressed_len,
+        slot,
+    })
+}
+
+/// Fetch any account, mapping a missing account to `AccountNotFound`.
+pub fn fetch_account(client: &RpcClient, address: &Pubkey) -> PeriscopeResult<Account> {
+    fetch_account_with_slot(client, address).map(|(account, _)| account)
+}
+
+/// Fetch any account and the slot it was read at, mapping a missing account
+/// to `AccountNotFound`.
+pub fn fetch_account_with_slot(
+    client: &RpcClient,
+    address: &Pubkey,
+) -> PeriscopeResult<(Account, u64)> {
+    let started = Instant::now();
+    let result = client.get_account_with_commitment(address, client.commitment());
+    record(Stage::Rpc("getAccountInfo"), started.elapsed());
+
+    let response = result?;
+    match response.value {
+        Some(account) => Ok((account, response.context.slot)),
+        None => Err(PeriscopeError::AccountNotFound(address.to_string())),
+    }
+}
+
+/// Most accounts a `getMultipleAccounts` request may ask for
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Fetch accounts in batches, `None` for those that don't exist.
+pub fn fetch_accounts(
+    client: &RpcClient,
+    addresses: &[Pubkey],
+) -> PeriscopeResult<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let started = Instant::now();
+        let result = client.get_multiple_accounts(chunk);
+        record(Stage::Rpc("getMultipleAccounts"), started.elapsed());
+        accounts.extend(result?);
+    }
+    Ok(accounts)
+}
+
+/// What a program's IDL account holds, read without downloading the IDL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlProbe {
+    /// IDL account address
+    pub idl_address: Pubkey,
+
+    /// Whether the IDL account exists
+    pub exists: bool,
+
+    /// IDL authority, when the account has an IDL header
+    pub authority: Option<Pubkey>,
+
+    /// Length of the compressed IDL, when the account has an IDL header
+    pub compressed_len: Option<usize>,
+
+    /// Allocated size of the account, when the RPC node reports it
+    pub account_size: Option<usize>,
+
+    /// Slot the account was read at
+    pub slot: u64,
+}
+
+impl IdlProbe {
+    /// Whether the account holds a (non-empty) IDL
+    pub fn has_idl(&self) -> bool {
+        self.compressed_len.is_some_and(|len| len > 0)
+    }
+}
+
+/// Whether a program has an on-chain IDL, without downloading it
+pub fn has_idl(client: &RpcClient, program_id: &Pubkey) -> PeriscopeResult<bool> {
+    let probes = probe_idls(client, std::slice::from_ref(program_id))?;
+    Ok(probes.values().any(IdlProbe::has_idl))
+}
+
+/// Probe the IDL accounts of many programs: existence, authority and size,
+/// read from the account headers only, in batched `getMultipleAccounts`
+/// requests
+pub fn probe_idls(
+    client: &RpcClient,
+    program_ids: &[Pubkey],
+) -> PeriscopeResult<HashMap<Pubkey, IdlProbe>> {
+    let mut probes = HashMap::with_capacity(program_ids.len());
+    for chunk in program_ids.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let addresses = chunk
+            .iter()
+            .map(get_idl_address)
+            .collect::<PeriscopeResult<Vec<_>>>()?;
+        let started = Instant::now();
+        let result: Result<Value, _> = client.send(
+            RpcRequest::GetMultipleAccounts,
+            json!([addresses.iter().map(Pubkey::to_string).collect::<Vec<_>>(), {
+                "encoding": "base58",
+                "commitment": client.commitment().commitment,
+                "dataSlice": { "offset": 0, "length": HEADER_SIZE }
+            }]),
+        );
+        record(Stage::Rpc("getMultipleAccounts"), started.elapsed());
+
+        let response = result?;
+        let slot = response["context"]["slot"].as_u64().unwrap_or_default();
+        let accounts = response["value"]
+            .as_array()
+            .filter(|accounts| accounts.len() == chunk.len())
+            .ok_or_else(|| {
+                PeriscopeError::NetworkError("unexpected getMultipleAccounts response".to_string())
+            })?;
+        for ((program_id, idl_address), account) in chunk.iter().zip(addresses).zip(accounts) {
+            probes.insert(*program_id, idl_probe(idl_address, account, slot));
+        }
+    }
+    Ok(probes)
+}
+
+/// Checksum of the IDL a probed account holds (see [`idl_checksum`]), read
+/// from the last 4 bytes of its compressed IDL; `None` when it holds none
+///
+/// [`idl_checksum`]: crate::idl::idl_checksum
+pub fn fetch_idl_checksum(client: &RpcClient, probe: &IdlProbe) -> PeriscopeResult<Option<u32>> {
+    let Some(len) = probe.compressed_len.filter(|&len| len >= 4) else {
+        return Ok(None);
+    };
+    let started = Instant::now();
+    let result: Result<Value, _> = client.send(
+        RpcRequest::GetAccountInfo,
+        json!([probe.idl_address.to_string(), {
+            "encoding": "base58",
+            "commitment": client.commitment().commitment,
+            "dataSlice": { "offset": HEADER_SIZE + len - 4, "length": 4 }
+        }]),
+    );
+    record(Stage::Rpc("getAccountInfo"), started.elapsed());
+
+    let response = result?;
+    Ok(response["value"]["data"][0]
+        .as_str()
+        .and_then(|data| solana_sdk::bs58::decode(data).into_vec().ok())
+        .and_then(|trailer| <[u8; 4]>::try_from(trailer).ok())
+        .map(u32::from_be_bytes))
+}
+
+/// A probe from one `getMultipleAccounts` entry (`null` when the account
+/// doesn't exist) holding the first [`HEADER_SIZE`] bytes of data
+fn idl_probe(idl_address: Pubkey, account: &Value, slot: u64) -> IdlProbe {
+    let header = account["data"][0]
+        .as_str()
+        .and_then(|data| solana_sdk::bs58::decode(data).into_vec().ok())
+        .unwrap_or_default();
+    IdlProbe {
+        idl_address,
+        exists: !account.is_null(),
+        authority: idl_account_authority(&header).ok(),
+        compressed_len: idl_account_data_len(&header).ok(),
+        account_size: account["space"].as_u64().map(|size| size as usize),
+        slot,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_headers() {
+        assert_eq!(
+            parse_rpc_header("x-api-key: abc:def "),
+            Some(("x-api-key".to_string(), "abc:def".to_string()))
+        );
+        assert_eq!(parse_rpc_header("no-colon"), None);
+        assert_eq!(parse_rpc_header(": value"), None);
+
+        let bad = [("bad name".to_string(), "v".to_string())];
+        assert!(matches!(
+            check_rpc_headers(&bad),
+            Err(PeriscopeError::Usage(_))
+        ));
+        let bad = [("x-key".to_string(), "line\nbreak".to_string())];
+        assert!(check_rpc_headers(&bad).is_err());
+        let good = [("Authorization".to_string(), "Bearer abc".to_string())];
+        assert!(check_rpc_headers(&good).is_ok());
+    }
+
+    /// Lowercased request head of the one request a client of `options_for`
+    /// (given the listener's URL) sends
+    fn request_head(options_for: impl FnOnce(&str) -> RpcOptions) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf).unwrap() {
+                    0 => break,
+                    n => head.extend(&buf[..n]),
+                }
+            }
+            let _ = stream.write_all(
+                b"HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            );
+            String::from_utf8_lossy(&head).to_lowercase()
+        });
+        let options = RpcOptions {
+            timeout: Duration::from_secs(5),
+            ..options_for(&url)
+        };
+        let _ = rpc_client_with(&url, &options).unwrap().get_slot();
+        server.join().unwrap()
+    }
+
+    #[test]
+    fn test_headers_stay_with_their_endpoint() {
+        let auth = || vec![("Authorization".to_string(), "Bearer secret".to_string())];
+
+        let other_host = request_head(|url| {
+            let options = RpcOptions::for_endpoint(url, "https://rpc.provider.example", auth());
+            assert!(options.headers.is_empty());
+            options
+        });
+        assert!(!other_host.contains("authorization"), "{}", other_host);
+
+        let endpoint = request_head(|url| RpcOptions::for_endpoint(url, url, auth()));
+        assert!(
+            endpoint.contains("authorization: bearer secret"),
+            "{}",
+            endpoint
+        );
+
+        // Same host on another port is another endpoint
+        assert!(RpcOptions::for_endpoint(
+            "https://rpc.provider.example:8443",
+            "https://rpc.provider.example",
+            auth()
+        )
+        .headers
+        .is_empty());
+    }
+
+    #[test]
+    fn test_buffer_layout() {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        let json = r#"{"address":"11111111111111111111111111111111"}"#;
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0u8; 8];
+        data.extend(authority.to_bytes());
+        data.extend((compressed.len() as u32).to_le_bytes());
+        data.extend(&compressed);
+
+        let idl = on_chain_idl(&data, 42, DEFAULT_MAX_IDL_SIZE).unwrap();
+        assert_eq!(idl.json, json);
+        assert_eq!(idl.authority, authority);
+        assert_eq!(idl.compressed_len, compressed.len());
+        assert_eq!(idl.slot, 42);
+        assert!(on_chain_idl(&data[..20], 42, DEFAULT_MAX_IDL_SIZE).is_err());
+        assert!(matches!(
+            on_chain_idl(&data, 42, json.len() - 1),
+            Err(PeriscopeError::IdlTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_idl_probe() {
+        let authority = Pubkey::new_unique();
+        let mut header = vec![0u8; 8];
+        header.extend(authority.to_bytes());
+        header.extend(1234u32.to_le_bytes());
+        let account = json!({
+            "data": [solana_sdk::bs58::encode(&header).into_string(), "base58"],
+            "space": 2000
+        });
+
+        let address = Pubkey::new_unique();
+        let probe = idl_probe(address, &account, 7);
+        assert!(probe.exists && probe.has_idl());
+        assert_eq!(probe.authority, Some(authority));
+        assert_eq!(
+            (probe.compressed_len, probe.account_size, probe.slot),
+            (Some(1234), Some(2000), 7)
+        );
+
+        let missing = idl_probe(address, &Value::Null, 7);
+        assert!(!missing.exists && !missing.has_idl());
+        assert_eq!(missing.authority, None);
+    }
+}
@@ -0,0 +1,373 @@
+//! Idiomatic Rust identifiers for IDL names
+//!
+//! IDL names come from many sources: camelCase legacy IDLs, snake_case 0.30
+//! IDLs, hand-written JSON. Reusing them verbatim in generated Rust breaks on
+//! keywords (`type`), leading digits (`2x_fee`) and names that collide once
+//! case is normalized (`swapV2` and `swap_v2`). [`RustNames`] maps every name
+//! to a valid identifier and reports each non-trivial rename.
+
+use crate::idl::{Idl, IdlAccountItem, IdlEnumFields, IdlTypeDefTy};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// Rust keywords, strict and reserved (2021 edition plus `gen`)
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that can't be written as raw identifiers
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Identifier style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// `UpperCamelCase`: types, enum variants
+    UpperCamel,
+    /// `snake_case`: functions, fields, modules
+    Snake,
+}
+
+/// Why a name was changed beyond case conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RenameReason {
+    /// The name is a Rust keyword
+    Keyword,
+    /// The name starts with a digit
+    LeadingDigit,
+    /// Characters not allowed in identifiers were dropped
+    InvalidChars,
+    /// Another name in the same scope maps to the same identifier
+    Duplicate,
+}
+
+impl fmt::Display for RenameReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameReason::Keyword => write!(f, "keyword"),
+            RenameReason::LeadingDigit => write!(f, "leading digit"),
+            RenameReason::InvalidChars => write!(f, "invalid characters"),
+            RenameReason::Duplicate => write!(f, "duplicate"),
+        }
+    }
+}
+
+/// A name that changed beyond case conversion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rename {
+    /// Where the name lives (`types`, `type Pool`, `instruction swap`, ...)
+    pub scope: String,
+
+    /// Name in the IDL
+    pub from: String,
+
+    /// Rust identifier
+    pub to: String,
+
+    /// Why it was changed
+    pub reason: RenameReason,
+}
+
+/// Rust identifiers for every name in an IDL
+///
+/// Scopes are `types`, `instructions`, `errors`, `type <IdlName>` (fields or
+/// variants), `instruction <IdlName>` (args) and `accounts <IdlName>`
+/// (instruction accounts). Names are assigned in IDL order, so the result is
+/// deterministic: the first of two colliding names keeps the plain form.
+#[derive(Debug, Clone, Default)]
+pub struct RustNames {
+    names: BTreeMap<(String, String), String>,
+
+    /// Non-trivial renames, in IDL order
+    pub renames: Vec<Rename>,
+}
+
+impl RustNames {
+    /// Assign identifiers to every name in `idl`
+    pub fn build(idl: &Idl) -> Self {
+        let mut names = RustNames::default();
+
+        names.scope(
+            "types",
+            Case::UpperCamel,
+            idl.types.iter().map(|t| t.name.as_str()),
+        );
+        names.scope(
+            "instructions",
+            Case::Snake,
+            idl.instructions.iter().map(|ix| ix.name.as_str()),
+        );
+        names.scope(
+            "errors",
+            Case::UpperCamel,
+            idl.errors.iter().map(|e| e.name.as_str()),
+        );
+
+        for ty in &idl.types {
+            let scope = format!("type {}", ty.name);
+            match &ty.ty {
+                IdlTypeDefTy::Struct { fields } => {
+                    names.scope(&scope, Case::Snake, fields.iter().map(|f| f.name.as_str()));
+                }
+                IdlTypeDefTy::Enum { variants } => {
+                    names.scope(
+                        &scope,
+                        Case::UpperCamel,
+                        variants.iter().map(|v| v.name.as_str()),
+                    );
+                    for variant in variants {
+                        if let Some(IdlEnumFields::Named(fields)) = &variant.fields {
+                            names.scope(
+                                &format!("{}::{}", scope, variant.name),
+                                Case::Snake,
+                                fields.iter().map(|f| f.name.as_str()),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        for ix in &idl.instructions {
+            names.scope(
+                &format!("instruction {}", ix.name),
+                Case::Snake,
+                ix.args.iter().map(|a| a.name.as_str()),
+            );
+            let mut accounts = Vec::new();
+            flatten_accounts(&ix.accounts, &mut accounts);
+            names.scope(&format!("accounts {}", ix.name), Case::Snake, accounts);
+        }
+
+        names
+    }
+
+    /// Rust identifier of `name` in `scope`
+    pub fn get(&self, scope: &str, name: &str) -> Option<&str> {
+        self.names
+            .get(&(scope.to_string(), name.to_string()))
+            .map(|s| s.as_str())
+    }
+
+    fn scope<'a>(&mut self, scope: &str, case: Case, names: impl IntoIterator<Item = &'a str>) {
+        let mut taken = HashSet::new();
+        for name in names {
+            let key = (scope.to_string(), name.to_string());
+            if self.names.contains_key(&key) {
+                // Exact duplicates in the IDL share one identifier
+                continue;
+            }
+
+            let (mut ident, mut reason) = rust_ident(name, case);
+            if !taken.insert(ident.clone()) {
+                let base = ident.trim_start_matches("r#").to_string();
+                ident = (2..)
+                    .map(|n| match case {
+                        Case::UpperCamel => format!("{}{}", base, n),
+                        Case::Snake => format!("{}_{}", base, n),
+                    })
+                    .find(|candidate| !taken.contains(candidate))
+                    .unwrap_or(base);
+                taken.insert(ident.clone());
+                reason = Some(RenameReason::Duplicate);
+            }
+
+            if let Some(reason) = reason {
+                self.renames.push(Rename {
+                    scope: scope.to_string(),
+                    from: name.to_string(),
+                    to: ident.clone(),
+                    reason,
+                });
+            }
+            self.names.insert(key, ident);
+        }
+    }
+}
+
+fn flatten_accounts<'a>(items: &'a [IdlAccountItem], out: &mut Vec<&'a str>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => out.push(&account.name),
+            IdlAccountItem::Group(group) => flatten_accounts(&group.accounts, out),
+        }
+    }
+}
+
+/// Convert a name to a valid identifier in `case`
+///
+/// Returns the identifier and, if it is more than a case conversion of the
+/// name, the reason. Keywords become raw identifiers (`r#type`) where Rust
+/// allows it and get a trailing `_` otherwise; leading digits get a `_`
+/// (snake) or `N` (camel) prefix.
+pub fn rust_ident(name: &str, case: Case) -> (String, Option<RenameReason>) {
+    let mut reason = None;
+    if name
+        .chars()
+        .any(|c| !c.is_ascii_alphanumeric() && c != '_' && c != '-' && c != ' ')
+    {
+        reason = Some(RenameReason::InvalidChars);
+    }
+
+    let words = split_words(name);
+    let mut ident = match case {
+        Case::Snake => words.join("_"),
+        Case::UpperCamel => words.iter().map(|w| capitalize(w)).collect(),
+    };
+
+    if ident.is_empty() {
+        ident = match case {
+            Case::Snake => "unnamed".to_string(),
+            Case::UpperCamel => "Unnamed".to_string(),
+        };
+        reason = Some(RenameReason::InvalidChars);
+    } else if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident = match case {
+            Case::Snake => format!("_{}", ident),
+            Case::UpperCamel => format!("N{}", ident),
+        };
+        reason = Some(RenameReason::LeadingDigit);
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        ident = if NON_RAW_KEYWORDS.contains(&ident.as_str()) {
+            format!("{}_", ident)
+        } else {
+            format!("r#{}", ident)
+        };
+        reason = Some(RenameReason::Keyword);
+    }
+
+    (ident, reason)
+}
+
+/// Lowercased words of a snake, camel, kebab or acronym-laden name
+///
+/// Digits stay attached to the word before them (`swapV2` -> `swap`, `v2`).
+pub(crate) fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        if c.is_ascii_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            // fooBar, v2Pool | HTTPServer (the S starts a word)
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c.to_ascii_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+pub(crate) fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rust_ident() {
+        let cases = [
+            ("swapExactIn", Case::Snake, "swap_exact_in", None),
+            ("swap_exact_in", Case::UpperCamel, "SwapExactIn", None),
+            ("HTTPServer", Case::UpperCamel, "HttpServer", None),
+            ("poolV2", Case::Snake, "pool_v2", None),
+            ("type", Case::Snake, "r#type", Some(RenameReason::Keyword)),
+            ("self", Case::Snake, "self_", Some(RenameReason::Keyword)),
+            (
+                "self",
+                Case::UpperCamel,
+                "Self_",
+                Some(RenameReason::Keyword),
+            ),
+            (
+                "2x_fee",
+                Case::Snake,
+                "_2x_fee",
+                Some(RenameReason::LeadingDigit),
+            ),
+            (
+                "2x_fee",
+                Case::UpperCamel,
+                "N2xFee",
+                Some(RenameReason::LeadingDigit),
+            ),
+            ("fee%", Case::Snake, "fee", Some(RenameReason::InvalidChars)),
+            (
+                "€",
+                Case::UpperCamel,
+                "Unnamed",
+                Some(RenameReason::InvalidChars),
+            ),
+        ];
+        for (name, case, ident, reason) in cases {
+            assert_eq!(
+                rust_ident(name, case),
+                (ident.to_string(), reason),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_rust_names_dedupes_in_order() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "swapV2", "accounts": [], "args": [{ "name": "type", "type": "u8" }] },
+                { "name": "swap_v2", "accounts": [], "args": [] }
+            ],
+            "types": [{ "name": "pool", "type": { "kind": "struct", "fields": [
+                { "name": "feeBps", "type": "u16" },
+                { "name": "fee_bps", "type": "u16" }
+            ] } }]
+        }))
+        .unwrap();
+
+        let names = RustNames::build(&idl);
+        assert_eq!(names.get("instructions", "swapV2"), Some("swap_v2"));
+        assert_eq!(names.get("instructions", "swap_v2"), Some("swap_v2_2"));
+        assert_eq!(names.get("instruction swapV2", "type"), Some("r#type"));
+        assert_eq!(names.get("types", "pool"), Some("Pool"));
+        assert_eq!(names.get("type pool", "fee_bps"), Some("fee_bps_2"));
+
+        let reasons: Vec<_> = names.renames.iter().map(|r| r.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                RenameReason::Duplicate,
+                RenameReason::Duplicate,
+                RenameReason::Keyword
+            ]
+        );
+    }
+}
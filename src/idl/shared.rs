@@ -0,0 +1,102 @@
+//! Cheaply clonable IDL handle for concurrent decoders
+//!
+//! Indexers decode transaction streams on many worker threads against the
+//! same IDL. [`SharedIdl`] builds the lookup indexes once, up front, and hands
+//! out reference-counted clones, so workers never copy the IDL or race to
+//! build the index on their first lookup.
+
+use crate::idl::Idl;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Reference-counted IDL with its lookup indexes prebuilt
+///
+/// Cloning only bumps a reference count. Dereferences to [`Idl`], so it can
+/// be passed anywhere an `&Idl` is expected:
+///
+/// ```ignore
+/// let shared = SharedIdl::new(idl);
+/// let worker = shared.clone();
+/// std::thread::spawn(move || decode_instruction(&worker, &data));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedIdl(Arc<Idl>);
+
+impl SharedIdl {
+    /// Wrap an IDL, building its lookup indexes
+    pub fn new(idl: Idl) -> Self {
+        idl.index();
+        SharedIdl(Arc::new(idl))
+    }
+
+    /// Number of handles to this IDL
+    pub fn handle_count(this: &Self) -> usize {
+        Arc::strong_count(&this.0)
+    }
+}
+
+impl From<Idl> for SharedIdl {
+    fn from(idl: Idl) -> Self {
+        SharedIdl::new(idl)
+    }
+}
+
+impl Deref for SharedIdl {
+    type Target = Idl;
+
+    fn deref(&self) -> &Idl {
+        &self.0
+    }
+}
+
+impl AsRef<Idl> for SharedIdl {
+    fn as_ref(&self) -> &Idl {
+        &self.0
+    }
+}
+
+// Part of the public contract: workers must be able to share and move handles
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedIdl>();
+    assert_send_sync::<Idl>();
+};
+
+#[cfg(all(test, feature = "decode"))]
+mod tests {
+    use super::*;
+    use crate::decode::decode_instruction;
+    use serde_json::json;
+    use std::thread;
+
+    #[test]
+    fn test_shared_idl_across_threads() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "deposit",
+                "discriminator": [1, 1, 1, 1, 1, 1, 1, 1],
+                "accounts": [],
+                "args": [{ "name": "amount", "type": "u64" }]
+            }]
+        }))
+        .unwrap();
+        let shared = SharedIdl::new(idl);
+
+        let workers: Vec<_> = (0..4u64)
+            .map(|n| {
+                let idl = shared.clone();
+                thread::spawn(move || {
+                    let mut data = vec![1; 8];
+                    data.extend_from_slice(&n.to_le_bytes());
+                    decode_instruction(&idl, &data).unwrap().args["amount"].clone()
+                })
+            })
+            .collect();
+
+        let amounts: Vec<_> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(amounts, vec![json!(0), json!(1), json!(2), json!(3)]);
+        assert_eq!(SharedIdl::handle_count(&shared), 1);
+    }
+}
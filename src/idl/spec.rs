@@ -0,0 +1,156 @@
+//! IDL spec versions and parser dispatch
+//!
+//! Every IDL format Anchor has published is an [`IdlSpec`]. [`IdlSpec::detect`]
+//! reads which one a JSON document claims without parsing the rest of it, and
+//! [`IdlSpec::parse`] hands the document to that spec's parser. A new Anchor
+//! spec gets a variant and a parser here; until then its IDLs are read by the
+//! parser of the newest spec this crate knows, as they always have been.
+
+use crate::error::PeriscopeResult;
+use crate::idl::legacy::LegacyIdl;
+use crate::idl::Idl;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::fmt;
+
+/// IDL spec version of a document
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IdlSpec {
+    /// Pre-0.30 Anchor format: camelCase names, no addresses or discriminators
+    Legacy,
+    /// Anchor 0.30+ format (`"spec": "0.1.0"`)
+    V0_1_0,
+    /// A spec version without a parser of its own
+    Unknown(String),
+}
+
+impl IdlSpec {
+    /// Spec of the newest format this crate has a parser for
+    pub const LATEST: IdlSpec = IdlSpec::V0_1_0;
+
+    /// Read the spec an IDL document is written in
+    ///
+    /// Documents with a program `address` are in the new format and name
+    /// their spec in `metadata.spec`; documents with only a root `name` are
+    /// legacy. Fails on input that can't be an IDL (not JSON, or a scalar).
+    pub fn detect(json: &str) -> PeriscopeResult<IdlSpec> {
+        let probe: SpecProbe = serde_json::from_str(json)?;
+        if probe.address.is_none() && probe.name.is_some() {
+            return Ok(IdlSpec::Legacy);
+        }
+        Ok(probe
+            .metadata
+            .and_then(|metadata| metadata.spec)
+            .map_or(IdlSpec::LATEST, |spec| IdlSpec::from(spec.as_str())))
+    }
+
+    /// Parse `json` as an IDL of this spec
+    ///
+    /// New-format IDLs that fail strict parsing are parsed leniently; skipped
+    /// items are listed in [`Idl::warnings`].
+    pub fn parse(&self, json: &str) -> PeriscopeResult<Idl> {
+        match self {
+            IdlSpec::Legacy => Ok(serde_json::from_str::<LegacyIdl>(json)?.into()),
+            // Newer specs are read as the latest one until they get a parser
+            IdlSpec::V0_1_0 | IdlSpec::Unknown(_) => match serde_json::from_str(json) {
+                Ok(idl) => Ok(idl),
+                Err(_) => Idl::parse_lenient(json).map(|(idl, _)| idl),
+            },
+        }
+    }
+
+    /// The spec as written in IDL metadata (`0.1.0`, `legacy`)
+    pub fn as_str(&self) -> &str {
+        match self {
+            IdlSpec::Legacy => "legacy",
+            IdlSpec::V0_1_0 => "0.1.0",
+            IdlSpec::Unknown(spec) => spec,
+        }
+    }
+
+    /// Whether this crate has a parser written for this spec
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, IdlSpec::Unknown(_))
+    }
+}
+
+impl From<&str> for IdlSpec {
+    fn from(spec: &str) -> Self {
+        match spec {
+            "legacy" => IdlSpec::Legacy,
+            "0.1.0" => IdlSpec::V0_1_0,
+            other => IdlSpec::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for IdlSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Idl {
+    /// Spec version this IDL was written in
+    pub fn spec(&self) -> IdlSpec {
+        IdlSpec::from(self.metadata.spec.as_str())
+    }
+}
+
+/// Top-level keys that tell IDL formats and specs apart
+///
+/// Deserializing into this skips the rest of the document without building
+/// it, unlike a `serde_json::Value` probe which allocates the whole tree.
+#[derive(Deserialize)]
+struct SpecProbe {
+    #[serde(default)]
+    address: Option<IgnoredAny>,
+    #[serde(default)]
+    name: Option<IgnoredAny>,
+    #[serde(default)]
+    metadata: Option<MetadataProbe>,
+}
+
+#[derive(Deserialize)]
+struct MetadataProbe {
+    #[serde(default)]
+    spec: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::SUPPORTED_IDL_SPECS;
+
+    #[test]
+    fn test_detect_and_dispatch() {
+        let v0 = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": []
+        }"#;
+        assert_eq!(IdlSpec::detect(v0).unwrap(), IdlSpec::V0_1_0);
+        assert_eq!(IdlSpec::V0_1_0.parse(v0).unwrap().spec(), IdlSpec::V0_1_0);
+
+        let legacy = r#"{ "name": "amm", "version": "0.1.0", "instructions": [] }"#;
+        assert_eq!(IdlSpec::detect(legacy).unwrap(), IdlSpec::Legacy);
+        assert_eq!(
+            IdlSpec::Legacy.parse(legacy).unwrap().spec(),
+            IdlSpec::Legacy
+        );
+
+        // A future spec is reported as such and still read with the latest parser
+        let future = v0.replace(r#""spec": "0.1.0""#, r#""spec": "0.2.0""#);
+        let spec = IdlSpec::detect(&future).unwrap();
+        assert_eq!(spec, IdlSpec::Unknown("0.2.0".to_string()));
+        assert!(!spec.is_supported());
+        assert_eq!(spec.parse(&future).unwrap().metadata.name, "amm");
+
+        assert!(IdlSpec::detect("42").is_err());
+        for spec in SUPPORTED_IDL_SPECS {
+            let parsed = IdlSpec::from(*spec);
+            assert!(parsed.is_supported());
+            assert_eq!(parsed.to_string(), *spec);
+        }
+    }
+}
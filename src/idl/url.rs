@@ -0,0 +1,119 @@
+//! IDL URL normalization
+//!
+//! People paste the URL of the page showing a file, not the file itself.
+//! [`normalize_idl_url`] rewrites the common code-hosting page URLs to their
+//! raw-content equivalents; anything it doesn't recognize is left alone.
+
+use reqwest::Url;
+
+/// Rewrite a code-hosting page URL to the URL of the raw file
+///
+/// Handles:
+/// - GitHub `blob/` and `raw/` URLs, including `?raw=true`
+/// - GitHub gists (`gist.github.com/<user>/<id>`)
+/// - GitLab `-/blob/` URLs
+/// - Bitbucket `src/` URLs
+///
+/// Line anchors (`#L10`) and display-only query strings are dropped.
+pub fn normalize_idl_url(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(host) = parsed.host_str() else {
+        return url.to_string();
+    };
+    let segments: Vec<&str> = parsed
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    match (host, segments.as_slice()) {
+        // github.com/<owner>/<repo>/(blob|raw)/<ref>/<path..>
+        ("github.com" | "www.github.com", [owner, repo, "blob" | "raw", rest @ ..])
+            if !rest.is_empty() =>
+        {
+            format!(
+                "https://raw.githubusercontent.com/{}/{}/{}",
+                owner,
+                repo,
+                rest.join("/")
+            )
+        }
+        // gist.github.com/<user>/<id> (the raw endpoint serves the first file)
+        ("gist.github.com", [user, id]) => {
+            format!("https://gist.github.com/{}/{}/raw", user, id)
+        }
+        // gitlab.com/<group..>/<project>/-/blob/<ref>/<path..>
+        ("gitlab.com" | "www.gitlab.com", segments) => {
+            match segments.windows(2).position(|w| w == ["-", "blob"]) {
+                Some(i) => format!(
+                    "https://gitlab.com/{}/-/raw/{}",
+                    segments[..i].join("/"),
+                    segments[i + 2..].join("/")
+                ),
+                None => url.to_string(),
+            }
+        }
+        // bitbucket.org/<owner>/<repo>/src/<ref>/<path..>
+        ("bitbucket.org" | "www.bitbucket.org", [owner, repo, "src", rest @ ..])
+            if !rest.is_empty() =>
+        {
+            format!(
+                "https://bitbucket.org/{}/{}/raw/{}",
+                owner,
+                repo,
+                rest.join("/")
+            )
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_idl_url() {
+        let cases = [
+            (
+                "https://github.com/o/r/blob/main/target/idl/p.json",
+                "https://raw.githubusercontent.com/o/r/main/target/idl/p.json",
+            ),
+            (
+                "https://github.com/o/r/blob/v1.0/idl.json?raw=true#L12",
+                "https://raw.githubusercontent.com/o/r/v1.0/idl.json",
+            ),
+            (
+                "https://github.com/o/r/raw/main/idl.json",
+                "https://raw.githubusercontent.com/o/r/main/idl.json",
+            ),
+            (
+                "https://gist.github.com/alice/0123abcd",
+                "https://gist.github.com/alice/0123abcd/raw",
+            ),
+            (
+                "https://gitlab.com/group/sub/proj/-/blob/main/idl/p.json?ref_type=heads",
+                "https://gitlab.com/group/sub/proj/-/raw/main/idl/p.json",
+            ),
+            (
+                "https://bitbucket.org/o/r/src/main/idl.json",
+                "https://bitbucket.org/o/r/raw/main/idl.json",
+            ),
+            // Already raw or unknown: unchanged
+            (
+                "https://raw.githubusercontent.com/o/r/main/idl.json",
+                "https://raw.githubusercontent.com/o/r/main/idl.json",
+            ),
+            (
+                "https://example.com/idl.json?v=2",
+                "https://example.com/idl.json?v=2",
+            ),
+            ("https://github.com/o/r", "https://github.com/o/r"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(normalize_idl_url(input), expected, "{}", input);
+        }
+    }
+}
@@ -34,21 +34,150 @@
 //! // From URL
 //! let idl = fetch_idl_from_url("https://raw.githubusercontent.com/...").await?;
 //! ```
+//!
+//! # Many Programs
+//!
+//! An indexer decoding several programs keeps their IDLs in a
+//! [`ProgramRegistry`] and looks them up by program ID:
+//!
+//! ```ignore
+//! use periscope::{decode_instruction, ProgramRegistry};
+//!
+//! let mut registry = ProgramRegistry::new();
+//! registry.load_file("./target/idl/amm.json")?;
+//! registry.fetch(&client, &jupiter)?;
+//!
+//! if let Some(idl) = registry.get(&program_id) {
+//!     let decoded = decode_instruction(idl, &data)?;
+//! }
+//! ```
 
+//!
+//! # Cargo features
+//!
+//! IDL types, parsing, lookups and search are always available. Everything
+//! else is optional so embedders only pull in what they use:
+//!
+//! | Feature | Enables | Pulls in |
+//! |---------|---------|----------|
+//! | `decode` | account / instruction decoding, built-in programs | - |
+//! | `codegen` | [`codegen`] (tests, clients, Solana Actions) and [`compat`] (IDL compatibility reports) | - |
+//! | `parallel` | [`pipeline`] works on a thread pool instead of serially | rayon |
+//! | `fetch-http` | `fetch_idl_from_url` | reqwest |
+//! | `fetch-rpc` | `fetch_idl_from_chain`, `probe_idls`, `rpc_client`, `whois` and `tokens` (with `decode`) | solana-client, reqwest |
+//! | `cli` (default) | the `periscope` binary and its modules | all of the above, clap, colored, tokio, ... |
+//! | `send` (default) | [`send`]: signing and broadcasting transactions (`send-ix`) | `fetch-rpc`, `decode`, base64 |
+//! | `test-util` | `assert_idl_matches!`: golden-file IDL assertions for tests | - |
+//! | `anchor-idl` | `From<anchor_lang_idl::types::Idl>` for [`Idl`] | anchor-lang-idl |
+//!
+//! A service that only parses IDL JSON can use
+//! `default-features = false`. One that must never broadcast can build with
+//! `default-features = false, features = ["cli"]`: no code that signs or
+//! sends a transaction is compiled in. One that only sends needs
+//! `default-features = false, features = ["send"]`, without the CLI. Builds with `send` can still
+//! refuse it at runtime: [`send::SendOptions::new`] takes whether sending is
+//! read-only, which the CLI sets from `--read-only` and the `read_only`
+//! config key. On-chain code can depend on the `no_std`
+//! `periscope-core` crate directly; its types are re-exported from [`idl`].
+
+#[cfg(any(feature = "cli", feature = "test-util"))]
+pub mod account_diff;
+#[cfg(feature = "cli")]
+pub mod anchor_version;
+pub mod annotations;
+#[cfg(any(feature = "cli", feature = "send"))]
+pub mod build_ix;
+#[cfg(feature = "decode")]
+pub mod builtin_programs;
+pub mod bundle;
+#[cfg(feature = "cli")]
 pub mod cache;
+#[cfg(feature = "cli")]
 pub mod cli;
+#[cfg(feature = "fetch-rpc")]
+pub mod cluster;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "codegen")]
+pub mod compat;
+#[cfg(feature = "cli")]
 pub mod config;
+#[cfg(feature = "decode")]
+pub mod decode;
+#[cfg(feature = "cli")]
 pub mod display;
+#[cfg(feature = "cli")]
+pub mod doctor;
+#[cfg(feature = "decode")]
+pub mod encode;
 pub mod error;
+#[cfg(feature = "cli")]
+pub mod explain;
+pub mod export;
+pub mod groups;
+pub mod i18n;
 pub mod idl;
+#[cfg(feature = "cli")]
+pub mod links;
+pub mod lint;
+pub mod matrix;
+pub mod metrics;
+#[cfg(feature = "decode")]
+pub mod mock;
+pub mod overlap;
+pub mod page;
+pub mod pdas;
+pub mod pipeline;
+#[cfg(feature = "cli")]
+pub mod preflight;
+#[cfg(feature = "cli")]
+pub mod quarantine;
+#[cfg(feature = "cli")]
+pub mod recent;
+pub mod registry;
+#[cfg(feature = "cli")]
+pub mod repl;
+#[cfg(feature = "cli")]
+pub mod scan;
+#[cfg(feature = "cli")]
+pub mod schema;
+pub mod search;
+#[cfg(feature = "send")]
+pub mod send;
+pub mod signing;
+#[cfg(any(feature = "cli", feature = "send"))]
+pub mod simulate;
+#[cfg(feature = "cli")]
+pub mod snapshot;
+pub mod template;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "cli")]
+pub mod theme;
+#[cfg(all(feature = "fetch-rpc", feature = "decode"))]
+pub mod tokens;
+pub mod trust;
+#[cfg(feature = "cli")]
+pub mod update;
+pub mod uses;
+#[cfg(all(feature = "fetch-rpc", feature = "decode"))]
+pub mod whois;
+#[cfg(feature = "cli")]
+pub mod xcheck;
 
 // Public re-exports for library users
+#[cfg(feature = "decode")]
+pub use decode::{
+    decode_account, decode_account_as, decode_account_with, decode_event, decode_instruction,
+    decode_type, DecodedAccount, DecodedEvent, DecodedInstruction, FieldSpan, Layout,
+};
 pub use error::{PeriscopeError, PeriscopeResult};
+#[cfg(feature = "fetch-http")]
+pub use idl::fetch_idl_from_url;
+#[cfg(feature = "fetch-rpc")]
+pub use idl::{fetch_idl_from_chain, fetch_idl_with_client, has_idl, probe_idls, IdlProbe};
 pub use idl::{
     // Fetching functions
-    fetch_idl_from_chain,
-    fetch_idl_from_url,
-    fetch_idl_with_client,
     get_idl_address,
     load_idl_from_file,
     // Types
@@ -56,11 +185,20 @@ pub use idl::{
     IdlAccount,
     IdlAccountItem,
     IdlAccountRef,
+    IdlBuilder,
     IdlError as IdlErrorDef,
     IdlEventRef,
     IdlField,
     IdlInstruction,
     IdlMetadata,
+    IdlSpec,
     IdlType,
     IdlTypeDef,
+    InstructionBuilder,
+    SharedIdl,
+    TypeDefBuilder,
 };
+pub use metrics::Metrics;
+pub use registry::ProgramRegistry;
+#[cfg(all(feature = "fetch-rpc", feature = "decode"))]
+pub use tokens::{fetch_token, resolve_token, TokenInfo};
@@ -0,0 +1,209 @@
+//! Explorer hyperlinks in terminal output
+//!
+//! With `--links`, addresses and signatures in text output are wrapped in
+//! OSC 8 escape sequences pointing at a block explorer, so terminals that
+//! support them make them clickable. Links use the first explorer of the
+//! `endpoints.explorers` config list (else the `explorer_url` key); the
+//! cluster is taken from the RPC URL.
+
+use crate::recent::cluster_name;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Explorer used when `explorer_url` is not configured
+pub const DEFAULT_EXPLORER_URL: &str = "https://explorer.solana.com";
+
+static EXPLORER: OnceLock<Explorer> = OnceLock::new();
+
+/// URL templates of a block explorer's pages, as configured
+///
+/// `{address}` and `{signature}` are replaced by the item shown, and
+/// `{cluster_query}` by `?cluster=...` for clusters other than mainnet-beta.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExplorerTemplate {
+    /// Name shown next to its links
+    pub name: String,
+
+    /// Page of an account or program
+    pub address: String,
+
+    /// Page of a transaction
+    pub tx: String,
+}
+
+impl ExplorerTemplate {
+    /// Templates of an explorer laid out like explorer.solana.com under `base_url`
+    pub fn from_base_url(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        ExplorerTemplate {
+            name: base_url
+                .split_once("://")
+                .map_or(base_url, |(_, host)| host)
+                .to_string(),
+            address: format!("{}/address/{{address}}{{cluster_query}}", base_url),
+            tx: format!("{}/tx/{{signature}}{{cluster_query}}", base_url),
+        }
+    }
+
+    /// Check that both templates are http(s) URLs naming their item
+    pub fn validate(&self) -> Result<(), String> {
+        for (template, placeholder) in [(&self.address, "{address}"), (&self.tx, "{signature}")] {
+            if !template.starts_with("http://") && !template.starts_with("https://") {
+                return Err(format!(
+                    "explorer {}: {} must start with http:// or https://",
+                    self.name, template
+                ));
+            }
+            if !template.contains(placeholder) {
+                return Err(format!(
+                    "explorer {}: {} has no {} placeholder",
+                    self.name, template, placeholder
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Block explorer that links point at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explorer {
+    template: ExplorerTemplate,
+
+    /// `?cluster=...` for clusters other than mainnet-beta
+    query: String,
+}
+
+impl Explorer {
+    /// Explorer at `base_url` showing the cluster served by `rpc_url`
+    pub fn new(base_url: &str, rpc_url: &str) -> Self {
+        Self::from_template(ExplorerTemplate::from_base_url(base_url), rpc_url)
+    }
+
+    /// Explorer with the URL templates `template`, showing the cluster
+    /// served by `rpc_url`
+    ///
+    /// RPC hosts that aren't a public cluster or localhost are assumed to be
+    /// mainnet-beta (private mainnet RPC providers).
+    pub fn from_template(template: ExplorerTemplate, rpc_url: &str) -> Self {
+        let query = match cluster_name(rpc_url).as_str() {
+            cluster @ ("devnet" | "testnet") => format!("?cluster={}", cluster),
+            "localnet" => format!("?cluster=custom&customUrl={}", rpc_url),
+            _ => String::new(),
+        };
+        Explorer { template, query }
+    }
+
+    /// Name of the explorer
+    pub fn name(&self) -> &str {
+        &self.template.name
+    }
+
+    /// Page of an account or program
+    pub fn address_url(&self, address: &str) -> String {
+        self.template
+            .address
+            .replace("{address}", address)
+            .replace("{cluster_query}", &self.query)
+    }
+
+    /// Page of a transaction
+    pub fn tx_url(&self, signature: &str) -> String {
+        self.template
+            .tx
+            .replace("{signature}", signature)
+            .replace("{cluster_query}", &self.query)
+    }
+}
+
+/// Turn on links for the rest of the process
+pub fn set_links(explorer: Explorer) {
+    let _ = EXPLORER.set(explorer);
+}
+
+/// `text` as an OSC 8 hyperlink to `url`
+pub fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// `text` linked to the explorer page of `address`, when links are on
+pub fn link_address(text: &str, address: &str) -> String {
+    match EXPLORER.get() {
+        Some(explorer) => hyperlink(&explorer.address_url(address), text),
+        None => text.to_string(),
+    }
+}
+
+/// `signature` linked to its explorer page, when links are on
+pub fn link_tx(signature: &str) -> String {
+    match EXPLORER.get() {
+        Some(explorer) => hyperlink(&explorer.tx_url(signature), signature),
+        None => signature.to_string(),
+    }
+}
+
+/// `value` linked to its explorer page if it is an address, unchanged otherwise
+pub fn link_if_address(value: &str) -> String {
+    if EXPLORER.get().is_some()
+        && (32..=44).contains(&value.len())
+        && Pubkey::from_str(value).is_ok()
+    {
+        link_address(value, value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explorer_urls() {
+        let address = "11111111111111111111111111111111";
+        let mainnet = Explorer::new(DEFAULT_EXPLORER_URL, "https://api.mainnet-beta.solana.com");
+        assert_eq!(
+            mainnet.address_url(address),
+            format!("https://explorer.solana.com/address/{}", address)
+        );
+
+        let devnet = Explorer::new("https://solscan.io/", "https://api.devnet.solana.com");
+        assert_eq!(
+            devnet.tx_url("sig"),
+            "https://solscan.io/tx/sig?cluster=devnet"
+        );
+
+        let local = Explorer::new(DEFAULT_EXPLORER_URL, "http://127.0.0.1:8899");
+        assert!(local
+            .address_url(address)
+            .ends_with("?cluster=custom&customUrl=http://127.0.0.1:8899"));
+
+        let template = ExplorerTemplate {
+            name: "SolanaFM".into(),
+            address: "https://solana.fm/address/{address}{cluster_query}".into(),
+            tx: "https://solana.fm/tx/{signature}{cluster_query}".into(),
+        };
+        assert!(template.validate().is_ok());
+        let fm = Explorer::from_template(template, "https://api.devnet.solana.com");
+        assert_eq!(fm.name(), "SolanaFM");
+        assert_eq!(
+            fm.address_url(address),
+            format!("https://solana.fm/address/{}?cluster=devnet", address)
+        );
+        assert_eq!(
+            ExplorerTemplate::from_base_url(DEFAULT_EXPLORER_URL).name,
+            "explorer.solana.com"
+        );
+        let mut bad = ExplorerTemplate::from_base_url("https://x.io");
+        bad.tx = "https://x.io/tx".into();
+        assert!(bad.validate().is_err());
+
+        assert_eq!(
+            hyperlink("https://x", "label"),
+            "\x1b]8;;https://x\x1b\\label\x1b]8;;\x1b\\"
+        );
+    }
+}
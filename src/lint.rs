@@ -0,0 +1,136 @@
+//! IDL lint
+//!
+//! Checks an IDL for problems that don't stop it from parsing but cost its
+//! users: definitions nothing refers to, which codegen still turns into
+//! types. Each finding names the rule that produced it.
+
+use crate::idl::Idl;
+use crate::uses::{fields, references};
+use serde::Serialize;
+
+/// One problem found in an IDL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Rule that produced it (`dead-type`)
+    pub rule: &'static str,
+
+    /// Definition it concerns
+    pub name: String,
+
+    /// What's wrong
+    pub detail: String,
+}
+
+/// Every finding of every rule, in rule order
+pub fn lint(idl: &Idl) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    dead_types(idl, &mut findings);
+    findings
+}
+
+/// Types no instruction, account or event reaches, directly or through
+/// other types
+fn dead_types(idl: &Idl, findings: &mut Vec<Finding>) {
+    let mut live: Vec<&str> = idl
+        .accounts
+        .iter()
+        .map(|a| a.name.as_str())
+        .chain(idl.events.iter().map(|e| e.name.as_str()))
+        .collect();
+    for ix in &idl.instructions {
+        for arg in &ix.args {
+            references(&arg.ty, &mut live);
+        }
+    }
+    let mut next = 0;
+    while next < live.len() {
+        if let Some(def) = idl.find_type(live[next]) {
+            let mut mentioned = Vec::new();
+            for (_, ty) in fields(def) {
+                references(ty, &mut mentioned);
+            }
+            for name in mentioned {
+                if !live.contains(&name) {
+                    live.push(name);
+                }
+            }
+        }
+        next += 1;
+    }
+
+    for def in &idl.types {
+        if live.contains(&def.name.as_str()) {
+            continue;
+        }
+        let users: Vec<&str> = idl
+            .types
+            .iter()
+            .filter(|other| {
+                fields(other).iter().any(|(_, ty)| {
+                    let mut mentioned = Vec::new();
+                    references(ty, &mut mentioned);
+                    mentioned.contains(&def.name.as_str())
+                })
+            })
+            .map(|other| other.name.as_str())
+            .collect();
+        let detail = if users.is_empty() {
+            "never referenced".to_string()
+        } else {
+            format!("only referenced by unused types ({})", users.join(", "))
+        };
+        findings.push(Finding {
+            rule: "dead-type",
+            name: def.name.clone(),
+            detail,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+
+    #[test]
+    fn test_dead_types() {
+        let idl = parse_idl_json(
+            r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "swap", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [], "args": [
+                    { "name": "side", "type": { "defined": { "name": "Side" } } }
+                ] }
+            ],
+            "accounts": [{ "name": "Pool", "discriminator": [1,1,1,1,1,1,1,1] }],
+            "types": [
+                { "name": "Side", "type": { "kind": "enum", "variants": [{ "name": "Buy" }] } },
+                { "name": "Pool", "type": { "kind": "struct", "fields": [
+                    { "name": "fees", "type": { "vec": { "defined": { "name": "Fee" } } } }
+                ] } },
+                { "name": "Fee", "type": { "kind": "struct", "fields": [{ "name": "bps", "type": "u16" }] } },
+                { "name": "OldConfig", "type": { "kind": "struct", "fields": [
+                    { "name": "limit", "type": { "defined": { "name": "Limit" } } }
+                ] } },
+                { "name": "Limit", "type": { "kind": "struct", "fields": [{ "name": "max", "type": "u64" }] } }
+            ]
+        }"#,
+        )
+        .unwrap();
+
+        let findings = lint(&idl);
+        let dead: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|f| (f.name.as_str(), f.detail.as_str()))
+            .collect();
+        assert_eq!(
+            dead,
+            [
+                ("OldConfig", "never referenced"),
+                ("Limit", "only referenced by unused types (OldConfig)"),
+            ]
+        );
+        assert!(findings.iter().all(|f| f.rule == "dead-type"));
+    }
+}
@@ -1,157 +1,2422 @@
 use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::json;
+use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{IsTerminal, Read};
+use std::path::Path;
 use std::str::FromStr;
+use std::time::Instant;
 
-use periscope::cli::{Cli, Commands, ConfigCommands, IdlSource};
-use periscope::config::Config;
+use periscope::account_diff::{diff_values, FieldChange};
+use periscope::anchor_version::{detect, fetch_program_binary};
+use periscope::annotations::{annotations, set_annotations, Annotations};
+use periscope::build_ix::{
+    build_from_template, build_interactive, parse_nonce_account, unsigned_transaction,
+    BuiltInstruction, DurableNonce, MessageOptions,
+};
+use periscope::builtin_programs::{decode_builtin_account, known_program_name};
+use periscope::bundle::{read_local_idl_json, Bundle, BUNDLE_PREFIX};
+use periscope::cache::{CacheEntry, IdlCache};
+use periscope::cli::{
+    BuildFormat, BundleCommands, CacheCommands, Cli, CodegenCommands, Commands, ConfigCommands,
+    GroupBy, IdlSource, PageArgs, QuarantineCommands, TestFrameworkArg, VecStrategyArg,
+};
+use periscope::cluster::{identify_cluster, KNOWN_CLUSTERS};
+use periscope::codegen::{generate_action, generate_tests, TestFramework};
+use periscope::compat::{compare_instructions, rust_adapter};
+use periscope::config::{display_value, Config};
+use periscope::decode::{decode_account_with, Layout};
+#[cfg(feature = "send")]
+use periscope::display::display_send_outcome;
 use periscope::display::{
-    display_error, display_errors_list, display_idl_overview, display_instruction_detail,
-    display_instruction_not_found, display_instructions_list,
+    display_account_diff, display_address_info, display_anchor_version, display_annotated_hex,
+    display_built_instruction, display_bundle, display_changes_since, display_checks,
+    display_compat, display_decoded_account, display_error, display_errors_list, display_explained,
+    display_grep_results, display_idl_overview, display_instruction_detail,
+    display_instruction_groups, display_instruction_not_found, display_instructions_list,
+    display_lint, display_mock, display_overlaps, display_pdas, display_preflight,
+    display_quarantine, display_quarantine_retry, display_recent_programs, display_renames,
+    display_role_matrix, display_scan, display_simulation, display_snapshot, display_sweep,
+    display_timing, display_type_uses, display_xcheck,
 };
-use periscope::idl::{fetch_idl_from_chain, fetch_idl_from_url, load_idl_from_file, Idl};
+use periscope::doctor::{run_checks, CheckStatus};
+use periscope::error::PeriscopeError;
+use periscope::explain::{explain, lookup_table_addresses, parse_payload, read_payloads};
+use periscope::export::VecStrategy;
+use periscope::groups::{group_by_prefix, page_groups};
+use periscope::i18n::{set_lang, tr, trf, Msg};
+use periscope::idl::{
+    fetch_account, fetch_accounts, fetch_idl_buffer, fetch_idl_json_from_url, fetch_on_chain_idl,
+    hex, load_idl_from_file, parse_idl_json, parse_rpc_header, read_idl_json_from_file,
+    rpc_client_with, Idl, IdlNames, IdlType, IdlTypeComplex, RpcOptions, RustNames,
+};
+use periscope::links::{set_links, Explorer};
+use periscope::lint::lint;
+use periscope::matrix::RoleMatrix;
+use periscope::metrics;
+use periscope::mock::mock_type;
+use periscope::overlap::find_overlaps;
+use periscope::page::Page;
+use periscope::pdas::pda_recipes;
+use periscope::pipeline::{map_concurrent, parse_idls};
+use periscope::preflight::preflight;
+use periscope::quarantine::{Quarantine, QuarantineEntry, RetryOutcome};
+use periscope::recent::{cluster_name, now, RecentPrograms};
+use periscope::registry::ProgramRegistry;
+use periscope::repl::Repl;
+use periscope::scan::{scan, scan_stats, scan_table};
+use periscope::schema::{output_schema, versioned};
+use periscope::search::{search_idl_with, ProgramHits};
+#[cfg(feature = "send")]
+use periscope::send::{is_mainnet, send_instruction, SendOptions};
+use periscope::signing::{sign_idl, IdlSignature};
+use periscope::simulate::{parse_sweep, set_arg, simulate_instruction, SweepPoint};
+use periscope::snapshot::{read_fixture, snapshot, write_fixtures, Fixture};
+use periscope::template::Template;
+use periscope::theme::{paint, set_theme, Element, Theme};
+use periscope::tokens::{parse_token_list, set_token_lists};
+use periscope::trust::signature_path;
+use periscope::update::{check_latest, install_asset, CURRENT_VERSION};
+use periscope::uses::type_uses;
+use periscope::whois::classify_address;
+use periscope::xcheck::{change_counts, compare, fetch_clusters};
+use regex::RegexBuilder;
+use solana_client::rpc_client::RpcClient;
+
+/// Process exit codes, by failure class
+///
+/// These are part of the CLI contract: scripts rely on them to tell a
+/// transient RPC failure from a program that never published an IDL.
+mod exit_code {
+    /// Any failure not covered below
+    pub const FAILURE: i32 = 1;
+    /// IDL, account, file or named item does not exist
+    pub const NOT_FOUND: i32 = 2;
+    /// RPC or network failure, usually transient
+    pub const NETWORK: i32 = 3;
+    /// IDL or account data could not be parsed or decoded
+    pub const PARSE: i32 = 4;
+    /// Integrity check failed (checksum mismatch, invalid IDL signature, trust policy)
+    pub const INTEGRITY: i32 = 5;
+    /// Invalid arguments (sysexits EX_USAGE)
+    pub const USAGE: i32 = 64;
+    /// Local I/O failure (sysexits EX_IOERR)
+    pub const IO: i32 = 74;
+    /// Invalid configuration (sysexits EX_CONFIG)
+    pub const CONFIG: i32 = 78;
+}
+
+/// Exit code for a command error
+fn exit_code_for(e: &anyhow::Error) -> i32 {
+    let Some(err) = e.downcast_ref::<PeriscopeError>() else {
+        return exit_code::FAILURE;
+    };
+
+    match err {
+        PeriscopeError::IdlNotFound(_)
+        | PeriscopeError::AccountNotFound(_)
+        | PeriscopeError::NotFound(_)
+        | PeriscopeError::HttpError { status: 404, .. } => exit_code::NOT_FOUND,
+        PeriscopeError::IoError(io) if io.kind() == std::io::ErrorKind::NotFound => {
+            exit_code::NOT_FOUND
+        }
+        PeriscopeError::RpcError(_)
+        | PeriscopeError::NetworkError(_)
+        | PeriscopeError::HttpError { .. } => exit_code::NETWORK,
+        PeriscopeError::ParseError(_)
+        | PeriscopeError::InvalidIdl(_)
+        | PeriscopeError::DecompressionError(_)
+        | PeriscopeError::IdlTooLarge { .. }
+        | PeriscopeError::NotJson { .. }
+        | PeriscopeError::UnknownDiscriminator(_)
+        | PeriscopeError::DecodeError { .. } => exit_code::PARSE,
+        PeriscopeError::ChecksumMismatch { .. }
+        | PeriscopeError::SignatureInvalid(_)
+        | PeriscopeError::PolicyViolation(_) => exit_code::INTEGRITY,
+        PeriscopeError::Usage(_)
+        | PeriscopeError::ReadOnly(_)
+        | PeriscopeError::InvalidProgramId(_)
+        | PeriscopeError::EncodeError { .. } => exit_code::USAGE,
+        PeriscopeError::IoError(_) | PeriscopeError::CacheError(_) => exit_code::IO,
+        PeriscopeError::ConfigError(_) => exit_code::CONFIG,
+        PeriscopeError::TransactionFailed(_) => exit_code::FAILURE,
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse_args();
+    let mut cli = match Cli::try_parse_args() {
+        Ok(cli) => cli,
+        // --help and --version are "errors" printed to stdout
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => {
+            let _ = e.print();
+            std::process::exit(exit_code::USAGE);
+        }
+    };
 
+    cli.config = match Config::load_layered() {
+        Ok(config) => config,
+        // `doctor` reports a broken config and `config edit` repairs it
+        Err(_) if matches!(cli.command, Commands::Doctor | Commands::Config { .. }) => {
+            Config::default()
+        }
+        Err(e) => exit_with_error(&e.into(), cli.json()),
+    };
+    set_theme(Theme::from_config(&cli.config.theme).unwrap_or_default());
+    set_lang(cli.lang.unwrap_or_else(|| cli.config.lang()));
+    if cli.links && !cli.json() {
+        let explorer = cli.config.explorers().remove(0);
+        set_links(Explorer::from_template(explorer, &get_rpc_url(&cli)));
+    }
+
+    let json = cli.json();
+    let timing = cli.timing;
+    let started = Instant::now();
     let result = run(cli).await;
+    if timing {
+        display_timing(&metrics::snapshot(), started.elapsed());
+    }
 
     if let Err(e) = &result {
-        display_error(&e.to_string());
-        std::process::exit(1);
+        exit_with_error(e, json);
     }
 
     Ok(())
 }
 
+/// Report `e` on stderr, as JSON for `--output json`, and exit with its code
+fn exit_with_error(e: &anyhow::Error, json: bool) -> ! {
+    if json {
+        let report = versioned(error_report(e), "error");
+        eprintln!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        display_error(&e.to_string());
+    }
+    std::process::exit(exit_code_for(e));
+}
+
 async fn run(cli: Cli) -> Result<()> {
     match &cli.command {
         Commands::Inspect { program_id } => cmd_inspect(&cli, program_id.as_deref()).await,
-        Commands::Instructions { program_id } => {
-            cmd_instructions(&cli, program_id.as_deref()).await
+        Commands::Instructions {
+            program_id,
+            group_by,
+            pages,
+        } => cmd_instructions(&cli, program_id.as_deref(), *group_by, pages).await,
+        Commands::Instruction {
+            name,
+            program_id,
+            raw_seeds,
+        } => cmd_instruction(&cli, program_id.as_deref(), name, *raw_seeds).await,
+        Commands::Template { name, program_id } => {
+            cmd_template(&cli, program_id.as_deref(), name).await
+        }
+        Commands::BuildIx {
+            name,
+            program_id,
+            interactive,
+            from,
+            format,
+            payer,
+            blockhash,
+            nonce_account,
+            nonce_authority,
+        } => {
+            let output = BuildOutput {
+                format: *format,
+                payer: payer.as_deref(),
+                blockhash: blockhash.as_deref(),
+                nonce_account: nonce_account.as_deref(),
+                nonce_authority: nonce_authority.as_deref(),
+            };
+            cmd_build_ix(
+                &cli,
+                program_id.as_deref(),
+                name,
+                *interactive,
+                from.as_deref(),
+                output,
+            )
+            .await
+        }
+        #[cfg(feature = "send")]
+        Commands::SendIx {
+            name,
+            program_id,
+            interactive,
+            from,
+            keypair,
+            yes,
+        } => {
+            cmd_send_ix(
+                &cli,
+                program_id.as_deref(),
+                name,
+                *interactive,
+                from.as_deref(),
+                keypair.as_deref(),
+                *yes,
+            )
+            .await
         }
-        Commands::Instruction { name, program_id } => {
-            cmd_instruction(&cli, program_id.as_deref(), name).await
+        Commands::Simulate {
+            name,
+            program_id,
+            interactive,
+            from,
+            payer,
+            sweep,
+        } => {
+            cmd_simulate(
+                &cli,
+                program_id.as_deref(),
+                name,
+                *interactive,
+                from.as_deref(),
+                payer.as_deref(),
+                sweep.as_deref(),
+            )
+            .await
         }
-        Commands::Errors { program_id } => cmd_errors(&cli, program_id.as_deref()).await,
+        Commands::Errors { program_id, pages } => {
+            cmd_errors(&cli, program_id.as_deref(), pages).await
+        }
+        Commands::DecodeAccount {
+            address,
+            program_id,
+            account_type,
+            layout,
+            annotate,
+        } => {
+            cmd_decode_account(
+                &cli,
+                address,
+                program_id.as_deref(),
+                account_type.as_deref(),
+                *layout,
+                *annotate,
+            )
+            .await
+        }
+        Commands::Explain => cmd_explain(&cli).await,
+        Commands::AccountDiff {
+            before,
+            after,
+            account_type,
+            layout,
+        } => {
+            cmd_account_diff(
+                &cli,
+                before,
+                after.as_deref(),
+                account_type.as_deref(),
+                *layout,
+            )
+            .await
+        }
+        Commands::AnchorVersion { program_id } => {
+            cmd_anchor_version(&cli, program_id.as_deref()).await
+        }
+        Commands::Matrix {
+            program_id,
+            account,
+        } => cmd_matrix(&cli, program_id.as_deref(), account.as_deref()).await,
+        Commands::Uses {
+            name,
+            program_id,
+            pages,
+        } => cmd_uses(&cli, program_id.as_deref(), name, pages).await,
+        Commands::Lint { program_id } => cmd_lint(&cli, program_id.as_deref()).await,
+        Commands::Pdas {
+            program_id,
+            raw_seeds,
+            pages,
+        } => cmd_pdas(&cli, program_id.as_deref(), *raw_seeds, pages).await,
+        Commands::Preflight {
+            name,
+            program_id,
+            accounts,
+        } => cmd_preflight(&cli, program_id.as_deref(), name, accounts).await,
+        Commands::Overlap {
+            sources,
+            cache,
+            bundles,
+            discriminators,
+        } => cmd_overlap(&cli, sources, *cache, bundles, *discriminators).await,
+        Commands::Grep {
+            pattern,
+            regex,
+            kind,
+        } => cmd_grep(&cli, pattern, *regex, kind.as_deref()),
+        Commands::Bundle { action } => cmd_bundle(&cli, action).await,
+        Commands::Sign { idl, keypair, out } => {
+            cmd_sign(&cli, idl, keypair.as_deref(), out.as_deref()).await
+        }
+        Commands::Verify {
+            idl,
+            signature,
+            signer,
+        } => cmd_verify(&cli, idl, signature, signer).await,
+        Commands::Xcheck {
+            program_id,
+            clusters,
+        } => cmd_xcheck(&cli, program_id, clusters),
+        Commands::Snapshot {
+            program_id,
+            accounts_of,
+            out,
+        } => cmd_snapshot(&cli, program_id.as_deref(), accounts_of, out).await,
+        Commands::Scan {
+            program_id,
+            account_type,
+            stats,
+            export,
+            vec_strategy,
+        } => {
+            cmd_scan(
+                &cli,
+                program_id.as_deref(),
+                account_type,
+                stats,
+                export.as_deref(),
+                *vec_strategy,
+            )
+            .await
+        }
+        Commands::Mock {
+            type_name,
+            program_id,
+            seed,
+        } => cmd_mock(&cli, program_id.as_deref(), type_name, *seed).await,
+        Commands::Whois { address } => cmd_whois(&cli, address).await,
+        Commands::RustNames { program_id } => cmd_rust_names(&cli, program_id.as_deref()).await,
+        Commands::Repl { program_id } => cmd_repl(&cli, program_id.as_deref()).await,
+        Commands::Recent { clear } => cmd_recent(&cli, *clear),
+        Commands::Doctor => cmd_doctor(&cli),
+        Commands::SelfUpdate { check, yes } => cmd_self_update(*check, *yes).await,
+        Commands::Schema { command } => cmd_schema(command),
+        Commands::Compat { old, new, rust } => cmd_compat(&cli, old, new, *rust),
+        Commands::Codegen { target } => cmd_codegen(&cli, target).await,
+        Commands::Cache { action } => cmd_cache(&cli, action),
+        Commands::Quarantine { action } => cmd_quarantine(&cli, action).await,
         Commands::Config { action } => cmd_config(action.clone()),
     }
 }
 
+/// Machine-readable form of a command error
+fn error_report(e: &anyhow::Error) -> serde_json::Value {
+    match e.downcast_ref::<PeriscopeError>() {
+        Some(err) => json!(err.report()),
+        None => json!({
+            "code": "PSC-ERR-500",
+            "message": e.to_string(),
+            "retryable": false,
+        }),
+    }
+}
+
+/// Print a command result as pretty, versioned JSON (lists are wrapped under `key`)
+fn print_json<T: Serialize + ?Sized>(value: &T, key: &str) -> Result<()> {
+    let value = versioned(serde_json::to_value(value)?, key);
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// Print a list as JSON, with the page shown when it was paged
+fn print_json_page<T: Serialize>(items: &[T], key: &str, page: Option<Page>) -> Result<()> {
+    let mut value = versioned(serde_json::to_value(items)?, key);
+    if let Some(page) = page {
+        value["page"] = serde_json::to_value(page)?;
+    }
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
 /// Handle `inspect` command
 async fn cmd_inspect(cli: &Cli, program_id: Option<&str>) -> Result<()> {
-    let idl = fetch_idl(cli, program_id).await?;
-    display_idl_overview(&idl);
+    let (json, origin, replaced) = load_idl_with_origin(cli, program_id).await?;
+    let mut idl = parse_idl_json(&json)?;
+    idl.apply_case(cli.case);
+    load_annotations(cli, &idl.address)?;
+
+    // What changed since the cached copy this fetch replaced; an old entry
+    // that no longer parses just goes without a summary
+    let changes = replaced.and_then(|previous| {
+        let mut old = parse_idl_json(&previous.idl_json).ok()?;
+        old.apply_case(cli.case);
+        let changes = change_counts(&old, &idl);
+        (!changes.is_empty()).then_some((previous, changes))
+    });
+
+    if cli.json() {
+        let mut value = serde_json::to_value(&idl)?;
+        value["canonical_hash"] = json!(idl.canonical_hash());
+        if let Some(origin) = &origin {
+            value["cluster"] = json!(origin.cluster);
+            value["slot"] = json!(origin.slot);
+            value["fetched_at"] = json!(origin.fetched_at);
+        }
+        if let Some((previous, changes)) = &changes {
+            value["changes_since_cached"] = json!({
+                "slot": previous.slot,
+                "fetched_at": previous.fetched_at,
+                "kinds": changes,
+            });
+        }
+        return print_json(&value, "idl");
+    }
+    display_idl_overview(&idl, origin.as_ref(), now());
+    if let Some((previous, changes)) = &changes {
+        display_changes_since(previous, changes, now());
+    }
     Ok(())
 }
 
 /// Handle `instructions` command
-async fn cmd_instructions(cli: &Cli, program_id: Option<&str>) -> Result<()> {
+async fn cmd_instructions(
+    cli: &Cli,
+    program_id: Option<&str>,
+    group_by: Option<GroupBy>,
+    pages: &PageArgs,
+) -> Result<()> {
+    // The text listing only needs names, which are read without a full
+    // parse, and without reading past the instructions
+    if !cli.json() {
+        let json = load_idl_json(cli, program_id).await?;
+        let names = IdlNames::parse_instructions(&json)?;
+        load_annotations(cli, &names.address)?;
+        let instructions: Vec<String> = names
+            .instructions
+            .iter()
+            .map(|n| cli.case.apply(n))
+            .collect();
+        let instructions: Vec<&str> = instructions.iter().map(String::as_str).collect();
+        let page = pages.page(cli.config.per_page, instructions.len())?;
+        match group_by {
+            Some(GroupBy::Prefix) => {
+                let mut groups = group_by_prefix(&instructions);
+                if let Some(page) = &page {
+                    groups = page_groups(&groups, page.range());
+                }
+                display_instruction_groups(&names.name, &groups, page.as_ref())
+            }
+            None => {
+                let shown = page.map_or(&instructions[..], |p| p.slice(&instructions));
+                display_instructions_list(&names.name, shown, page.as_ref())
+            }
+        }
+        return Ok(());
+    }
+
     let idl = fetch_idl(cli, program_id).await?;
-    display_instructions_list(&idl);
-    Ok(())
+    let names: Vec<&str> = idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
+    let groups = match group_by {
+        Some(GroupBy::Prefix) => group_by_prefix(&names),
+        None => Vec::new(),
+    };
+    let page = pages.page(cli.config.per_page, idl.instructions.len())?;
+    let shown = page.map_or(&idl.instructions[..], |p| p.slice(&idl.instructions));
+    let instructions: Vec<_> = shown
+        .iter()
+        .map(|ix| {
+            let mut item = json!({ "name": ix.name, "discriminator": ix.discriminator_bytes() });
+            if let Some(group) = groups.iter().find(|g| g.names.contains(&ix.name.as_str())) {
+                item["group"] = json!(group.prefix);
+            }
+            item
+        })
+        .collect();
+    print_json_page(&instructions, "instructions", page)
 }
 
 /// Handle `instruction` command
-async fn cmd_instruction(cli: &Cli, program_id: Option<&str>, name: &str) -> Result<()> {
+async fn cmd_instruction(
+    cli: &Cli,
+    program_id: Option<&str>,
+    name: &str,
+    raw_seeds: bool,
+) -> Result<()> {
     let idl = fetch_idl(cli, program_id).await?;
 
-    let instruction = idl
-        .instructions
-        .iter()
-        .find(|ix| ix.name.eq_ignore_ascii_case(name));
-
-    match instruction {
+    match idl.find_instruction(&cli.case.apply(name)) {
+        Some(ix) if cli.json() => print_json(ix, "instruction"),
         Some(ix) => {
-            display_instruction_detail(ix);
+            display_instruction_detail(ix, raw_seeds);
             Ok(())
         }
         None => {
+            if !cli.json() {
+                let available: Vec<&str> =
+                    idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
+                display_instruction_not_found(name, &available);
+            }
+            Err(PeriscopeError::NotFound(trf(Msg::InstructionNotFound, &[&name])).into())
+        }
+    }
+}
+
+/// Handle `template` command
+async fn cmd_template(cli: &Cli, program_id: Option<&str>, name: &str) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let Some(ix) = idl.find_instruction(&cli.case.apply(name)) else {
+        if !cli.json() {
             let available: Vec<&str> = idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
             display_instruction_not_found(name, &available);
-            Err(anyhow!("Instruction '{}' not found", name))
         }
+        return Err(PeriscopeError::NotFound(trf(Msg::InstructionNotFound, &[&name])).into());
+    };
+
+    let template = Template::new(&idl, ix);
+    if cli.json() {
+        return print_json(&template.to_json(), "template");
     }
+    print!("{}", template.to_toml());
+    Ok(())
+}
+
+/// Handle `compat` command
+fn cmd_compat(cli: &Cli, old: &str, new: &str, rust: bool) -> Result<()> {
+    let mut old_idl = load_idl_from_file(old)?;
+    let mut new_idl = load_idl_from_file(new)?;
+    old_idl.apply_case(cli.case);
+    new_idl.apply_case(cli.case);
+    let mappings = compare_instructions(&old_idl, &new_idl);
+    let code = rust.then(|| rust_adapter(&old_idl, &new_idl, &mappings));
+
+    if cli.json() {
+        return print_json(
+            &json!({
+                "old_version": old_idl.metadata.version,
+                "new_version": new_idl.metadata.version,
+                "instructions": mappings,
+                "code": code,
+            }),
+            "compat",
+        );
+    }
+    match code {
+        Some(code) => print!("{}", code),
+        None => display_compat(&old_idl, &new_idl, &mappings),
+    }
+    Ok(())
+}
+
+/// Handle `codegen` command
+async fn cmd_codegen(cli: &Cli, target: &CodegenCommands) -> Result<()> {
+    let (program_id, out) = match target {
+        CodegenCommands::Tests {
+            program_id, out, ..
+        }
+        | CodegenCommands::Action {
+            program_id, out, ..
+        } => (program_id.as_deref(), out),
+    };
+    let idl = fetch_idl(cli, program_id).await?;
+
+    let (code, value) = match target {
+        CodegenCommands::Tests { framework, .. } => {
+            let framework = match framework {
+                TestFrameworkArg::Mocha => TestFramework::Mocha,
+                TestFrameworkArg::Litesvm => TestFramework::LiteSvm,
+            };
+            let code = generate_tests(&idl, framework, annotations());
+            let value = json!({
+                "framework": match framework {
+                    TestFramework::Mocha => "mocha",
+                    TestFramework::LiteSvm => "litesvm",
+                },
+                "path": out,
+                "code": code,
+            });
+            (code, value)
+        }
+        CodegenCommands::Action {
+            name, descriptor, ..
+        } => {
+            let Some(ix) = idl.find_instruction(&cli.case.apply(name)) else {
+                if !cli.json() {
+                    let available: Vec<&str> =
+                        idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
+                    display_instruction_not_found(name, &available);
+                }
+                return Err(
+                    PeriscopeError::NotFound(trf(Msg::InstructionNotFound, &[&name])).into(),
+                );
+            };
+            let action = generate_action(&idl, ix, annotations());
+            let code = if *descriptor {
+                format!("{}\n", serde_json::to_string_pretty(&action.descriptor)?)
+            } else {
+                action.handler
+            };
+            let value = json!({
+                "instruction": ix.name,
+                "descriptor": action.descriptor,
+                "path": out,
+                "code": code,
+            });
+            (code, value)
+        }
+    };
+
+    if let Some(path) = out {
+        std::fs::write(path, &code).map_err(PeriscopeError::IoError)?;
+    }
+    if cli.json() {
+        return print_json(&value, "codegen");
+    }
+    if out.is_none() {
+        print!("{}", code);
+    }
+    Ok(())
+}
+
+/// How `build-ix` encodes its result besides the account list
+struct BuildOutput<'a> {
+    format: Option<BuildFormat>,
+    payer: Option<&'a str>,
+    blockhash: Option<&'a str>,
+    nonce_account: Option<&'a str>,
+    nonce_authority: Option<&'a str>,
+}
+
+/// Handle `build-ix` command
+async fn cmd_build_ix(
+    cli: &Cli,
+    program_id: Option<&str>,
+    name: &str,
+    interactive: bool,
+    from: Option<&str>,
+    output: BuildOutput<'_>,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let built = build_instruction(cli, &idl, name, interactive, from)?;
+
+    let encoded = match output.format {
+        Some(format) => Some(encode_built(cli, &built, format, &output)?),
+        None => None,
+    };
+    if cli.json() {
+        let mut value = serde_json::to_value(&built)?;
+        if let Some(encoded) = encoded {
+            value["encoded"] = encoded;
+        }
+        return print_json(&value, "instruction");
+    }
+    match encoded {
+        // Only the encoded string, so it can be piped or pasted as is
+        Some(encoded) => println!("{}", encoded["data"].as_str().unwrap_or_default()),
+        None => display_built_instruction(&built),
+    }
+    Ok(())
+}
+
+/// Build instruction `name` from prompts or a filled-in template file
+fn build_instruction(
+    cli: &Cli,
+    idl: &Idl,
+    name: &str,
+    interactive: bool,
+    from: Option<&str>,
+) -> Result<BuiltInstruction> {
+    if interactive && !std::io::stdin().is_terminal() {
+        return Err(PeriscopeError::Usage(tr(Msg::BuildNeedsTerminal).to_string()).into());
+    }
+    let Some(ix) = idl.find_instruction(&cli.case.apply(name)) else {
+        if !cli.json() {
+            let available: Vec<&str> = idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
+            display_instruction_not_found(name, &available);
+        }
+        return Err(PeriscopeError::NotFound(trf(Msg::InstructionNotFound, &[&name])).into());
+    };
+
+    let built = match from {
+        Some(path) => build_from_template(idl, ix, &read_filled_template(path)?)?,
+        None => build_interactive(idl, ix)?,
+    };
+    Ok(built)
+}
+
+/// Read a filled-in template: JSON, or TOML when the file is named *.toml
+fn read_filled_template(path: &str) -> Result<serde_json::Value> {
+    let contents = std::fs::read_to_string(path).map_err(PeriscopeError::IoError)?;
+    let filled = if path.ends_with(".toml") {
+        toml::from_str::<toml::Table>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|t| serde_json::to_value(t).map_err(|e| e.to_string()))
+    } else {
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    }
+    .map_err(|reason| PeriscopeError::EncodeError {
+        path: path.to_string(),
+        reason,
+    })?;
+    Ok(filled)
+}
+
+/// Handle `simulate` command
+async fn cmd_simulate(
+    cli: &Cli,
+    program_id: Option<&str>,
+    name: &str,
+    interactive: bool,
+    from: Option<&str>,
+    payer: Option<&str>,
+    sweep: Option<&str>,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let built = build_instruction(cli, &idl, name, interactive, from)?;
+    let payer = match payer {
+        Some(payer) => Pubkey::from_str(payer)
+            .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&payer])))?,
+        None => built
+            .default_payer()
+            .ok_or_else(|| PeriscopeError::Usage(tr(Msg::BuildPayerRequired).to_string()))?,
+    };
+    let client = rpc_client(cli, &get_rpc_url(cli))?;
+
+    let (Some(spec), Some(path)) = (sweep, from) else {
+        let simulation = simulate_instruction(&client, &idl, &built, &payer)?;
+        if cli.json() {
+            return print_json(&simulation, "simulation");
+        }
+        display_simulation(&built.instruction, &simulation);
+        return Ok(());
+    };
+
+    let (arg, values) =
+        parse_sweep(spec).ok_or_else(|| PeriscopeError::Usage(trf(Msg::InvalidSweep, &[&spec])))?;
+    let ix = idl
+        .find_instruction(&cli.case.apply(name))
+        .ok_or_else(|| PeriscopeError::NotFound(trf(Msg::InstructionNotFound, &[&name])))?;
+    let mut filled = read_filled_template(path)?;
+    let mut points = Vec::new();
+    for value in values {
+        set_arg(&mut filled, &arg, value.clone());
+        let built = build_from_template(&idl, ix, &filled)?;
+        let simulation = simulate_instruction(&client, &idl, &built, &payer)?;
+        points.push(SweepPoint {
+            value,
+            units_consumed: simulation.units_consumed,
+            error: simulation.error,
+        });
+    }
+    if cli.json() {
+        return print_json(&json!({ "arg": arg, "points": points }), "sweep");
+    }
+    display_sweep(&arg, &points);
+    Ok(())
+}
+
+/// Handle `send-ix` command
+#[cfg(feature = "send")]
+async fn cmd_send_ix(
+    cli: &Cli,
+    program_id: Option<&str>,
+    name: &str,
+    interactive: bool,
+    from: Option<&str>,
+    keypair: Option<&str>,
+    yes: bool,
+) -> Result<()> {
+    let options = SendOptions::new(cli.read_only || cli.config.read_only);
+    options.check()?;
+    let idl = fetch_idl(cli, program_id).await?;
+    let built = build_instruction(cli, &idl, name, interactive, from)?;
+
+    let signer = load_keypair(keypair)?;
+
+    let rpc_url = get_rpc_url(cli);
+    let client = rpc_client(cli, &rpc_url)?;
+    if !yes && is_mainnet(&client)? {
+        if !std::io::stdin().is_terminal() {
+            return Err(PeriscopeError::Usage(tr(Msg::SendMainnetNeedsYes).to_string()).into());
+        }
+        let confirmed = inquire::Confirm::new(&trf(
+            Msg::SendMainnetConfirm,
+            &[&built.instruction, &rpc_url],
+        ))
+        .with_default(false)
+        .prompt()
+        .unwrap_or(false);
+        if !confirmed {
+            return Err(PeriscopeError::Usage(tr(Msg::BuildCancelled).to_string()).into());
+        }
+    }
+
+    let outcome = send_instruction(&client, &idl, &built, &signer, &options)?;
+    if cli.json() {
+        return print_json(&outcome, "transaction");
+    }
+    display_send_outcome(&outcome);
+    Ok(())
+}
+
+/// Encode a built instruction as a multisig-ready transaction or message
+fn encode_built(
+    cli: &Cli,
+    built: &BuiltInstruction,
+    format: BuildFormat,
+    output: &BuildOutput<'_>,
+) -> Result<serde_json::Value> {
+    use base64::Engine;
+
+    let pubkey = |s: &str| {
+        Pubkey::from_str(s).map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&s])))
+    };
+    let payer = match output.payer {
+        Some(payer) => pubkey(payer)?,
+        None => built
+            .default_payer()
+            .ok_or_else(|| PeriscopeError::Usage(tr(Msg::BuildPayerRequired).to_string()))?,
+    };
+
+    let mut options = MessageOptions {
+        blockhash: match output.blockhash {
+            Some(hash) => Some(
+                Hash::from_str(hash)
+                    .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidBlockhash, &[&hash])))?,
+            ),
+            None => None,
+        },
+        nonce: None,
+    };
+    if let Some(account) = output.nonce_account {
+        let account = pubkey(account)?;
+        let authority = output.nonce_authority.map(pubkey).transpose()?;
+        // The nonce value is the blockhash; read it (and the authority) unless both are given
+        let stored = if options.blockhash.is_none() || authority.is_none() {
+            let client = rpc_client(cli, &get_rpc_url(cli))?;
+            let data = fetch_account(&client, &account)?.data;
+            Some(
+                parse_nonce_account(&data)
+                    .ok_or_else(|| PeriscopeError::Usage(trf(Msg::NotNonceAccount, &[&account])))?,
+            )
+        } else {
+            None
+        };
+        options.blockhash = options.blockhash.or(stored.map(|(_, nonce)| nonce));
+        options.nonce = Some(DurableNonce {
+            account,
+            authority: authority
+                .or(stored.map(|(authority, _)| authority))
+                .expect("authority given or read from the account"),
+        });
+    }
+
+    let message = built.to_message(&payer, &options)?;
+    let (format, data) = match format {
+        BuildFormat::Squads => (
+            "squads",
+            solana_sdk::bs58::encode(unsigned_transaction(&message)).into_string(),
+        ),
+        BuildFormat::Message => (
+            "message",
+            base64::engine::general_purpose::STANDARD.encode(message.serialize()),
+        ),
+    };
+    Ok(json!({
+        "format": format,
+        "payer": payer.to_string(),
+        "blockhash": message.recent_blockhash.to_string(),
+        "nonce_account": options.nonce.map(|n| n.account.to_string()),
+        "data": data,
+    }))
 }
 
 /// Handle `errors` command
-async fn cmd_errors(cli: &Cli, program_id: Option<&str>) -> Result<()> {
+async fn cmd_errors(cli: &Cli, program_id: Option<&str>, pages: &PageArgs) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let page = pages.page(cli.config.per_page, idl.errors.len())?;
+    let errors = page.map_or(&idl.errors[..], |p| p.slice(&idl.errors));
+    if cli.json() {
+        return print_json_page(errors, "errors", page);
+    }
+    display_errors_list(&idl.metadata.name, errors, page.as_ref());
+    Ok(())
+}
+
+/// Handle `decode-account` command
+async fn cmd_decode_account(
+    cli: &Cli,
+    address: &str,
+    program_id: Option<&str>,
+    account_type: Option<&str>,
+    layout: Option<Layout>,
+    annotate: bool,
+) -> Result<()> {
+    let pubkey = Pubkey::from_str(address)
+        .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&address])))?;
+
+    let client = rpc_client(cli, &get_rpc_url(cli))?;
+    let account = fetch_account(&client, &pubkey)?;
+
+    let builtin = if cli.idl.is_none()
+        && cli.idl_buffer.is_none()
+        && program_id.is_none()
+        && account_type.is_none()
+    {
+        decode_builtin_account(&account.owner, &account.data)
+    } else {
+        None
+    };
+
+    let decoded = match builtin {
+        Some(decoded) => decoded?,
+        None => {
+            let owner = account.owner.to_string();
+            let program_id = program_id.unwrap_or(&owner);
+            let idl = fetch_idl(cli, Some(program_id)).await?;
+            match decode_account_with(&idl, &account.data, account_type, layout) {
+                Ok(decoded) => decoded,
+                Err(
+                    e @ (PeriscopeError::UnknownDiscriminator(_)
+                    | PeriscopeError::DecodeError { .. }),
+                ) => {
+                    let entry = QuarantineEntry::new(
+                        program_id,
+                        address,
+                        account_type,
+                        &account.data,
+                        &e,
+                        now(),
+                    );
+                    if let Ok(path) = Quarantine::open().and_then(|q| q.add(entry)) {
+                        if !cli.json() {
+                            eprintln!(
+                                "{} {}",
+                                paint(tr(Msg::Note), Element::Warning),
+                                trf(Msg::Quarantined, &[&path.display()])
+                            );
+                        }
+                    }
+                    return Err(e.into());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    };
+
+    if cli.json() {
+        let mut value = json!(decoded);
+        value["address"] = json!(address);
+        return print_json(&value, "account");
+    }
+
+    display_decoded_account(address, &decoded);
+    if annotate {
+        display_annotated_hex(&account.data, &decoded.spans);
+        println!();
+    }
+    Ok(())
+}
+
+/// Handle `explain` command
+async fn cmd_explain(cli: &Cli) -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let messages = read_payloads(&input)?
+        .iter()
+        .map(|bytes| parse_payload(bytes))
+        .collect::<Result<Vec<_>, PeriscopeError>>()?;
+
+    // With --idl, that IDL is the only one; otherwise each program's own
+    let mut registry = ProgramRegistry::new();
+    let explicit = cli.idl.is_some() || cli.idl_buffer.is_some();
+    if explicit {
+        let idl = fetch_idl(cli, None).await?;
+        let program_id = Pubkey::from_str(&idl.address)
+            .map_err(|_| PeriscopeError::InvalidProgramId(idl.address.clone()))?;
+        registry.insert(program_id, idl);
+    }
+
+    let client = rpc_client(cli, &get_rpc_url(cli))?;
+    let mut tried = HashSet::new();
+    let mut tables = HashMap::new();
+    for message in &messages {
+        for program_id in message.program_ids() {
+            if explicit || known_program_name(&program_id).is_some() || !tried.insert(program_id) {
+                continue;
+            }
+            if let Ok(idl) = fetch_idl(cli, Some(&program_id.to_string())).await {
+                registry.insert(program_id, idl);
+            }
+        }
+        for lookup in &message.lookups {
+            if let Entry::Vacant(slot) = tables.entry(lookup.table) {
+                if let Ok(account) = fetch_account(&client, &lookup.table) {
+                    slot.insert(lookup_table_addresses(&account.data));
+                }
+            }
+        }
+    }
+
+    let explained: Vec<_> = messages
+        .iter()
+        .map(|message| explain(message, |id| registry.get(id).map(AsRef::as_ref), &tables))
+        .collect();
+    if cli.json() {
+        return print_json(&explained, "messages");
+    }
+    display_explained(&explained);
+    Ok(())
+}
+
+/// Handle `account-diff` command
+async fn cmd_account_diff(
+    cli: &Cli,
+    before: &str,
+    after: Option<&str>,
+    account_type: Option<&str>,
+    layout: Option<Layout>,
+) -> Result<()> {
+    let before_fixture = read_fixture(Path::new(before))?;
+    let address = before_fixture.address()?;
+    let (after_source, after_fixture) = match after {
+        Some(path) => {
+            let fixture = read_fixture(Path::new(path))?;
+            if fixture.pubkey != before_fixture.pubkey {
+                return Err(PeriscopeError::Usage(trf(
+                    Msg::DiffDifferentAccounts,
+                    &[&before_fixture.pubkey, &fixture.pubkey],
+                ))
+                .into());
+            }
+            (path.to_string(), fixture)
+        }
+        None => {
+            let client = rpc_client(cli, &get_rpc_url(cli))?;
+            let account = fetch_account(&client, &address)?;
+            (
+                tr(Msg::LiveAccount).to_string(),
+                Fixture::new(&address, &account),
+            )
+        }
+    };
+
+    let owner = after_fixture.owner()?.to_string();
+    let idl = fetch_idl(cli, Some(&owner)).await?;
+    let old = decode_account_with(&idl, &before_fixture.data()?, account_type, layout)?;
+    let new = decode_account_with(&idl, &after_fixture.data()?, account_type, layout)?;
+
+    let mut changes = Vec::new();
+    if old.name != new.name {
+        changes.push(FieldChange {
+            path: "(type)".to_string(),
+            before: json!(old.name),
+            after: json!(new.name),
+        });
+    }
+    changes.extend(diff_values(&old.value, &new.value));
+
+    if cli.json() {
+        return print_json(
+            &json!({
+                "address": address.to_string(),
+                "type": new.name,
+                "before": before,
+                "after": after_source,
+                "changes": changes,
+            }),
+            "account-diff",
+        );
+    }
+    display_account_diff(
+        &address.to_string(),
+        &new.name,
+        before,
+        &after_source,
+        &changes,
+    );
+    Ok(())
+}
+
+/// Handle `anchor-version` command
+async fn cmd_anchor_version(cli: &Cli, program_id: Option<&str>) -> Result<()> {
     let idl = fetch_idl(cli, program_id).await?;
-    display_errors_list(&idl);
+
+    // Only an explicitly given program is looked up on chain
+    let binary = match program_id {
+        Some(program_id) => {
+            let pubkey = Pubkey::from_str(program_id)
+                .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidProgramId, &[&program_id])))?;
+            let client = rpc_client(cli, &get_rpc_url(cli))?;
+            fetch_program_binary(&client, &pubkey).ok().flatten()
+        }
+        None => None,
+    };
+    let report = detect(&idl, binary.as_deref());
+
+    if cli.json() {
+        return print_json(&report, "anchor-version");
+    }
+    display_anchor_version(&idl.metadata.name, &report);
+    Ok(())
+}
+
+/// Handle `matrix` command
+async fn cmd_matrix(cli: &Cli, program_id: Option<&str>, account: Option<&str>) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let mut matrix = RoleMatrix::build(&idl);
+    if let Some(filter) = account {
+        matrix = matrix.filter_accounts(filter);
+    }
+
+    if cli.json() {
+        return print_json(&matrix, "matrix");
+    }
+    display_role_matrix(&idl.metadata.name, &matrix);
+    Ok(())
+}
+
+/// Handle `pdas` command
+async fn cmd_pdas(
+    cli: &Cli,
+    program_id: Option<&str>,
+    raw_seeds: bool,
+    pages: &PageArgs,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let recipes = pda_recipes(&idl, raw_seeds);
+    let page = pages.page(cli.config.per_page, recipes.len())?;
+    let recipes = page.map_or(&recipes[..], |p| p.slice(&recipes));
+    if cli.json() {
+        return print_json_page(recipes, "pdas", page);
+    }
+    display_pdas(&idl.metadata.name, recipes, page.as_ref());
+    Ok(())
+}
+
+/// Handle `uses` command
+async fn cmd_uses(
+    cli: &Cli,
+    program_id: Option<&str>,
+    type_name: &str,
+    pages: &PageArgs,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    if idl.find_type(type_name).is_none() {
+        return Err(PeriscopeError::NotFound(trf(Msg::TypeNotFound, &[&type_name])).into());
+    }
+    let uses = type_uses(&idl, type_name);
+    let page = pages.page(cli.config.per_page, uses.len())?;
+    let uses = page.map_or(&uses[..], |p| p.slice(&uses));
+    if cli.json() {
+        return print_json_page(uses, "uses", page);
+    }
+    display_type_uses(type_name, uses, page.as_ref());
+    Ok(())
+}
+
+/// Handle `lint` command
+async fn cmd_lint(cli: &Cli, program_id: Option<&str>) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let findings = lint(&idl);
+    if cli.json() {
+        print_json(&findings, "findings")?;
+    } else {
+        display_lint(&idl.metadata.name, &findings);
+    }
+    if !findings.is_empty() {
+        return Err(anyhow!(trf(Msg::LintFailed, &[&findings.len()])));
+    }
+    Ok(())
+}
+
+/// Handle `grep` command
+fn cmd_grep(cli: &Cli, pattern: &str, regex: bool, kind: Option<&str>) -> Result<()> {
+    let matcher: Box<dyn Fn(&str) -> bool> = if regex {
+        let re = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| PeriscopeError::Usage(trf(Msg::InvalidPattern, &[&e])))?;
+        Box::new(move |name| re.is_match(name))
+    } else {
+        let needle = pattern.to_lowercase();
+        Box::new(move |name| name.to_lowercase().contains(&needle))
+    };
+
+    let entries = IdlCache::open()?.entries()?;
+    let jsons: Vec<&str> = entries.iter().map(|e| e.idl_json.as_str()).collect();
+    let mut programs = Vec::new();
+    for (parsed, entry) in parse_idls(&jsons).into_iter().zip(&entries) {
+        // Cache entries that no longer parse are left to `periscope cache`
+        let Ok(mut idl) = parsed else { continue };
+        idl.apply_case(cli.case);
+        let mut hits = search_idl_with(&idl, &matcher);
+        if let Some(kind) = kind {
+            hits.retain(|hit| hit.kind.to_string() == kind);
+        }
+        if !hits.is_empty() {
+            programs.push(ProgramHits {
+                program_id: entry.program_id.clone(),
+                cluster: entry.cluster.clone(),
+                name: idl.metadata.name.clone(),
+                hits,
+            });
+        }
+    }
+    programs.sort_by(|a, b| (&a.name, &a.cluster).cmp(&(&b.name, &b.cluster)));
+
+    if cli.json() {
+        let programs: Vec<_> = programs
+            .iter()
+            .map(|p| {
+                let hits: Vec<_> = p
+                    .hits
+                    .iter()
+                    .map(|h| json!({ "kind": h.kind.to_string(), "path": h.path, "detail": h.detail }))
+                    .collect();
+                json!({
+                    "program_id": p.program_id,
+                    "cluster": p.cluster,
+                    "name": p.name,
+                    "hits": hits,
+                })
+            })
+            .collect();
+        return print_json(
+            &json!({ "pattern": pattern, "searched": entries.len(), "programs": programs }),
+            "grep",
+        );
+    }
+    display_grep_results(pattern, entries.len(), &programs);
+    Ok(())
+}
+
+/// Handle `preflight` command
+async fn cmd_preflight(
+    cli: &Cli,
+    program_id: Option<&str>,
+    name: &str,
+    accounts: &[String],
+) -> Result<()> {
+    let mut given = HashMap::new();
+    for spec in accounts {
+        let (account, address) = spec
+            .split_once('=')
+            .and_then(|(account, address)| {
+                Some((account.trim(), Pubkey::from_str(address.trim()).ok()?))
+            })
+            .ok_or_else(|| PeriscopeError::Usage(trf(Msg::InvalidAccountArg, &[&spec])))?;
+        given.insert(cli.case.apply(account), address);
+    }
+
+    let idl = fetch_idl(cli, program_id).await?;
+    let Some(ix) = idl.find_instruction(&cli.case.apply(name)) else {
+        if !cli.json() {
+            let available: Vec<&str> = idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
+            display_instruction_not_found(name, &available);
+        }
+        return Err(PeriscopeError::NotFound(trf(Msg::InstructionNotFound, &[&name])).into());
+    };
+
+    let client = rpc_client(cli, &get_rpc_url(cli))?;
+    let checks = preflight(&idl, ix, &given, |addresses| {
+        fetch_accounts(&client, addresses)
+    })?;
+    if cli.json() {
+        print_json(&checks, "checks")?;
+    } else {
+        display_preflight(&ix.name, &checks);
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(anyhow!(trf(Msg::PreflightFailed, &[&failed])));
+    }
+    Ok(())
+}
+
+/// Handle `overlap` command
+async fn cmd_overlap(
+    cli: &Cli,
+    sources: &[String],
+    cache: bool,
+    bundles: &[String],
+    discriminators: bool,
+) -> Result<()> {
+    let mut idls = Vec::with_capacity(sources.len());
+    for source in sources {
+        idls.push(load_idl_source(cli, source).await?);
+    }
+    // Bundled and cached IDLs (JSON, address) are parsed together, concurrently
+    let trust = &cli.config.trust;
+    let mut bundled = Vec::new();
+    for path in bundles {
+        let bundle = Bundle::read(Path::new(path))?;
+        for entry in &bundle.programs {
+            let json = bundle.idl_json(Some(&entry.address))?;
+            let source = format!("{}{}#{}", BUNDLE_PREFIX, path, entry.address);
+            trust.check_file(&source, &json)?;
+            bundled.push((json, entry.address.clone()));
+        }
+    }
+    let mut cached = Vec::new();
+    if cache {
+        for entry in IdlCache::open()?.entries()? {
+            cached.push((entry.idl_json, entry.program_id));
+        }
+    }
+    let stored: Vec<&(String, String)> = bundled.iter().chain(&cached).collect();
+    let jsons: Vec<&str> = stored.iter().map(|(json, _)| json.as_str()).collect();
+    for (i, (parsed, (_, address))) in parse_idls(&jsons).into_iter().zip(stored).enumerate() {
+        let mut idl = match parsed {
+            Ok(idl) => idl,
+            // Cache entries that no longer parse are left to `periscope cache`
+            Err(_) if i >= bundled.len() => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if idl.address.is_empty() {
+            idl.address = address.clone();
+        }
+        idls.push(idl);
+    }
+
+    // The same program cached for several clusters counts once
+    let mut seen = HashSet::new();
+    idls.retain(|idl| seen.insert(idl.address.clone()));
+    if idls.len() < 2 {
+        return Err(PeriscopeError::Usage(trf(Msg::OverlapTooFew, &[&idls.len()])).into());
+    }
+
+    let mut overlaps = find_overlaps(&idls.iter().collect::<Vec<_>>());
+    if discriminators {
+        overlaps.retain(|o| !o.kind.starts_with("error-"));
+    }
+
+    if cli.json() {
+        let programs: Vec<_> = idls
+            .iter()
+            .map(|idl| json!({ "name": idl.metadata.name, "address": idl.address }))
+            .collect();
+        print_json(
+            &json!({ "programs": programs, "overlaps": overlaps }),
+            "overlap",
+        )?;
+    } else {
+        display_overlaps(idls.len(), &overlaps);
+    }
+
+    if !overlaps.is_empty() {
+        return Err(anyhow!(trf(Msg::OverlapFailed, &[&overlaps.len()])));
+    }
+    Ok(())
+}
+
+/// Handle `bundle` command
+async fn cmd_bundle(cli: &Cli, action: &BundleCommands) -> Result<()> {
+    let path = match action {
+        BundleCommands::Create { path, sources } => {
+            // On-chain IDLs are fetched and decompressed concurrently up front
+            let rpc_url = get_rpc_url(cli);
+            let program_ids: Vec<Pubkey> = sources
+                .iter()
+                .filter_map(|source| Pubkey::from_str(source).ok())
+                .collect();
+            let mut fetched = map_concurrent(&program_ids, |program_id| {
+                fetch_idl_cached(cli, &rpc_url, program_id)
+            })
+            .into_iter();
+
+            let mut bundle = Bundle::new(now());
+            for source in sources {
+                let on_chain = Pubkey::from_str(source).is_ok();
+                let json = if on_chain {
+                    fetched.next().expect("one fetch per program ID")?.idl_json
+                } else {
+                    load_idl_source_json(cli, source).await?
+                };
+                bundle.add(
+                    &json,
+                    on_chain.then_some(source.as_str()),
+                    source,
+                    on_chain.then_some(rpc_url.as_str()),
+                )?;
+            }
+            bundle.write(Path::new(path))?;
+            path
+        }
+        BundleCommands::List { path } => path,
+    };
+
+    let bundle = Bundle::read(Path::new(path))?;
+    if cli.json() {
+        let programs: Vec<_> = bundle
+            .programs
+            .iter()
+            .map(|p| json!({ "address": p.address, "name": p.name, "source": p.source }))
+            .collect();
+        return print_json(
+            &json!({
+                "path": path,
+                "version": bundle.version,
+                "created_at": bundle.created_at,
+                "programs": programs,
+            }),
+            "bundle",
+        );
+    }
+    display_bundle(path, &bundle, now());
+    Ok(())
+}
+
+/// Handle `sign` command
+async fn cmd_sign(cli: &Cli, source: &str, keypair: Option<&str>, out: Option<&str>) -> Result<()> {
+    // Signed as published: --case would change the canonical hash
+    let idl = parse_idl_json(&load_signing_source_json(cli, source).await?)?;
+    let keypair = load_keypair(keypair)?;
+    let signature = sign_idl(&idl, &keypair, now());
+
+    // By default where the trust policy looks for it
+    let out = match (out, IdlSource::from_path(source)) {
+        (Some(out), _) => out.to_string(),
+        (None, IdlSource::Url(_)) => format!("{}.sig.json", idl.address),
+        (None, _) if Pubkey::from_str(source).is_ok() => format!("{}.sig.json", idl.address),
+        (None, _) => signature_path(source, &idl.address),
+    };
+    std::fs::write(&out, serde_json::to_string_pretty(&signature)?)?;
+
+    if cli.json() {
+        return print_json(&signature, "sign");
+    }
+    println!(
+        "{}",
+        trf(
+            Msg::IdlSigned,
+            &[&idl.metadata.name, &signature.signer, &out]
+        )
+    );
+    Ok(())
+}
+
+/// Handle `verify` command
+async fn cmd_verify(cli: &Cli, source: &str, signature: &str, signer: &str) -> Result<()> {
+    let signer = Pubkey::from_str(signer)
+        .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&signer])))?;
+    let signature: IdlSignature = serde_json::from_str(&std::fs::read_to_string(signature)?)?;
+    let idl = parse_idl_json(&load_signing_source_json(cli, source).await?)?;
+    signature.verify(&idl, &signer)?;
+
+    if cli.json() {
+        return print_json(
+            &json!({
+                "valid": true,
+                "signer": signature.signer,
+                "program": signature.program,
+                "canonical_version": signature.canonical_version,
+                "canonical_hash": signature.canonical_hash,
+                "signed_at": signature.signed_at,
+            }),
+            "verify",
+        );
+    }
+    println!(
+        "{} {}",
+        paint("✓", Element::Success),
+        trf(
+            Msg::IdlSignatureValid,
+            &[&idl.metadata.name, &signature.signer]
+        )
+    );
+    Ok(())
+}
+
+/// Handle `xcheck` command
+fn cmd_xcheck(cli: &Cli, program_id: &str, clusters: &[String]) -> Result<()> {
+    let pubkey = Pubkey::from_str(program_id)
+        .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidProgramId, &[&program_id])))?;
+    let fetched = fetch_clusters(&pubkey, clusters, cli.config.max_idl_size())?;
+    let idls: Vec<(&str, &Idl)> = fetched
+        .iter()
+        .filter_map(|c| c.idl.as_ref().map(|idl| (c.cluster.as_str(), idl)))
+        .collect();
+    let mismatches = compare(&idls);
+
+    if cli.json() {
+        print_json(
+            &json!({ "clusters": fetched, "mismatches": mismatches }),
+            "xcheck",
+        )?;
+    } else {
+        display_xcheck(&fetched, &mismatches);
+    }
+
+    if !mismatches.is_empty() {
+        return Err(anyhow!(trf(Msg::XcheckFailed, &[&mismatches.len()])));
+    }
+    Ok(())
+}
+
+/// Handle `snapshot` command
+async fn cmd_snapshot(
+    cli: &Cli,
+    program_id: Option<&str>,
+    accounts_of: &[String],
+    out: &str,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let program_id_str = program_id.unwrap_or(&idl.address);
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidProgramId, &[&program_id_str])))?;
+
+    let client = rpc_client(cli, &get_rpc_url(cli))?;
+    let fixtures = snapshot(&client, &idl, &program_id, accounts_of)?;
+    let paths = write_fixtures(Path::new(out), &fixtures)?;
+
+    if cli.json() {
+        let fixtures: Vec<_> = fixtures
+            .iter()
+            .zip(&paths)
+            .map(|((kind, fixture), path)| {
+                let mut entry = json!(kind);
+                entry["pubkey"] = json!(fixture.pubkey);
+                entry["path"] = json!(path);
+                entry
+            })
+            .collect();
+        return print_json(&json!({ "dir": out, "fixtures": fixtures }), "snapshot");
+    }
+    display_snapshot(out, &fixtures);
+    Ok(())
+}
+
+/// Handle `scan` command
+async fn cmd_scan(
+    cli: &Cli,
+    program_id: Option<&str>,
+    account_type: &str,
+    fields: &[String],
+    export: Option<&str>,
+    vec_strategy: VecStrategyArg,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let program_id_str = program_id.unwrap_or(&idl.address);
+    let program_id = Pubkey::from_str(program_id_str)
+        .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidProgramId, &[&program_id_str])))?;
+
+    let client = rpc_client(cli, &get_rpc_url(cli))?;
+    let accounts = scan(&client, &idl, &program_id, account_type)?;
+    let stats = scan_stats(&idl, account_type, &accounts, fields)?;
+
+    if let Some(path) = export {
+        let strategy = match vec_strategy {
+            VecStrategyArg::Join => VecStrategy::Join,
+            VecStrategyArg::Explode => VecStrategy::Explode,
+            VecStrategyArg::Json => VecStrategy::Json,
+        };
+        let table = scan_table(&idl, account_type, &accounts, strategy)?;
+        if path == "-" {
+            table.write_csv(&mut std::io::stdout().lock())?;
+            return Ok(());
+        }
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        table.write_csv(&mut file)?;
+        if !cli.json() {
+            eprintln!("{}", trf(Msg::ScanExported, &[&table.rows.len(), &path]));
+        }
+    }
+
+    if cli.json() {
+        return print_json(&stats, "scan");
+    }
+    display_scan(&stats);
+    Ok(())
+}
+
+/// Handle `mock` command
+async fn cmd_mock(
+    cli: &Cli,
+    program_id: Option<&str>,
+    type_name: &str,
+    seed: Option<u64>,
+) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    if idl.find_type(type_name).is_none() {
+        return Err(PeriscopeError::NotFound(trf(Msg::TypeNotFound, &[&type_name])).into());
+    }
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64)
+    });
+    let ty = IdlType::Complex(IdlTypeComplex::Defined {
+        name: type_name.to_string(),
+        generics: Vec::new(),
+    });
+    let mock = mock_type(&idl, &ty, seed)?;
+
+    if cli.json() {
+        return print_json(
+            &json!({ "type": type_name, "seed": seed, "value": mock.value, "data": hex(&mock.data) }),
+            "mock",
+        );
+    }
+    display_mock(type_name, seed, &mock);
+    Ok(())
+}
+
+/// Handle `whois` command
+async fn cmd_whois(cli: &Cli, address: &str) -> Result<()> {
+    let pubkey = Pubkey::from_str(address)
+        .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&address])))?;
+
+    let config = &cli.config;
+    let mut tokens = Vec::new();
+    for url in &config.endpoints.token_lists {
+        // An unreachable list only costs the names it would have given
+        match fetch_idl_json_from_url(url, config.max_idl_size())
+            .await
+            .and_then(|json| parse_token_list(&json))
+        {
+            Ok(list) => tokens.extend(list),
+            Err(_) if cli.json() => {}
+            Err(e) => eprintln!(
+                "{}",
+                paint(&trf(Msg::TokenListSkipped, &[url, &e]), Element::Muted)
+            ),
+        }
+    }
+    set_token_lists(tokens);
+
+    let rpc_url = get_rpc_url(cli);
+    let client = rpc_client(cli, &rpc_url)?;
+    let info = classify_address(&client, &pubkey, config.max_idl_size())?;
+    let explorers: Vec<Explorer> = config
+        .explorers()
+        .into_iter()
+        .map(|template| Explorer::from_template(template, &rpc_url))
+        .collect();
+
+    if cli.json() {
+        let mut value = info.to_json();
+        let urls: serde_json::Map<String, serde_json::Value> = explorers
+            .iter()
+            .map(|e| (e.name().to_string(), json!(e.address_url(address))))
+            .collect();
+        value["explorers"] = urls.into();
+        return print_json(&value, "address");
+    }
+    display_address_info(&info, &explorers);
+    Ok(())
+}
+
+/// Handle `rust-names` command
+async fn cmd_rust_names(cli: &Cli, program_id: Option<&str>) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    let names = RustNames::build(&idl);
+    if cli.json() {
+        return print_json(&names.renames, "renames");
+    }
+    display_renames(&idl.metadata.name, &names.renames);
+    Ok(())
+}
+
+/// Handle `repl` command
+async fn cmd_repl(cli: &Cli, program_id: Option<&str>) -> Result<()> {
+    let idl = fetch_idl(cli, program_id).await?;
+    Repl::new(idl).run()?;
+    Ok(())
+}
+
+/// Handle `recent` command
+fn cmd_recent(cli: &Cli, clear: bool) -> Result<()> {
+    let mut recent = RecentPrograms::load()?;
+    if clear {
+        recent.clear();
+        recent.save()?;
+        println!("{}", tr(Msg::RecentCleared));
+    } else if cli.json() {
+        print_json(&recent.programs, "programs")?;
+    } else {
+        display_recent_programs(&recent.programs, now());
+    }
+    Ok(())
+}
+
+/// Handle `doctor` command
+fn cmd_doctor(cli: &Cli) -> Result<()> {
+    let checks = run_checks(cli.url.as_deref(), &rpc_options(cli, &get_rpc_url(cli))?);
+    if cli.json() {
+        print_json(&checks, "checks")?;
+    } else {
+        display_checks(&checks);
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(anyhow!(trf(Msg::DoctorFailed, &[&failed])));
+    }
+    Ok(())
+}
+
+/// Handle `self-update` command
+async fn cmd_self_update(check: bool, yes: bool) -> Result<()> {
+    let release = check_latest().await?;
+    if !release.is_newer() {
+        println!("{}", trf(Msg::UpToDate, &[&CURRENT_VERSION]));
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        trf(Msg::UpdateAvailable, &[&release.version, &CURRENT_VERSION])
+    );
+    if check {
+        return Ok(());
+    }
+
+    let Some((binary, checksum)) = release.platform_asset() else {
+        println!(
+            "{}",
+            trf(
+                Msg::UpdateNoAsset,
+                &[&std::env::consts::OS, &env!("CARGO_PKG_NAME")]
+            )
+        );
+        return Ok(());
+    };
+    let checksum = checksum.ok_or_else(|| anyhow!(trf(Msg::UpdateNoChecksum, &[&binary.name])))?;
+
+    let target = std::env::current_exe()?;
+    if !yes {
+        let confirmed = inquire::Confirm::new(&trf(
+            Msg::UpdateConfirm,
+            &[&target.display(), &release.version],
+        ))
+        .with_default(false)
+        .prompt()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    install_asset(binary, checksum, &target).await?;
+    println!("{}", trf(Msg::UpdateInstalled, &[&release.version]));
+    Ok(())
+}
+
+/// Handle `schema` command
+fn cmd_schema(command: &str) -> Result<()> {
+    let schema = output_schema(command)
+        .ok_or_else(|| PeriscopeError::Usage(format!("no output schema for '{}'", command)))?;
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+/// Handle `cache` command
+fn cmd_cache(cli: &Cli, action: &CacheCommands) -> Result<()> {
+    let cache = IdlCache::open()?;
+    match action {
+        CacheCommands::Export { path } => {
+            let entries = cache.export(Path::new(path))?;
+            if cli.json() {
+                return print_json(&json!({ "path": path, "entries": entries }), "cache");
+            }
+            println!("{}", trf(Msg::CacheExported, &[&entries, &path]));
+        }
+        CacheCommands::Import { path } => {
+            let summary = cache.import(Path::new(path))?;
+            if cli.json() {
+                return print_json(
+                    &json!({ "path": path, "entries": summary.total, "imported": summary.imported }),
+                    "cache",
+                );
+            }
+            println!(
+                "{}",
+                trf(
+                    Msg::CacheImported,
+                    &[&summary.imported, &summary.total, &path]
+                )
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Handle `quarantine` command
+async fn cmd_quarantine(cli: &Cli, action: &QuarantineCommands) -> Result<()> {
+    let quarantine = Quarantine::open()?;
+    match action {
+        QuarantineCommands::List => {
+            let entries = quarantine.entries()?;
+            if cli.json() {
+                return print_json(&entries, "entries");
+            }
+            display_quarantine(&entries, now());
+        }
+        QuarantineCommands::Retry { id, program } => {
+            let entries: Vec<QuarantineEntry> = quarantine
+                .entries()?
+                .into_iter()
+                .filter(|e| id.as_ref().is_none_or(|id| e.id == *id))
+                .filter(|e| program.as_ref().is_none_or(|p| e.program_id == *p))
+                .collect();
+            if let (Some(id), true) = (id, entries.is_empty()) {
+                return Err(PeriscopeError::NotFound(trf(Msg::QuarantineNotFound, &[&id])).into());
+            }
+
+            let mut registry = ProgramRegistry::new();
+            let mut outcomes = Vec::new();
+            for mut entry in entries {
+                let program_id = Pubkey::from_str(&entry.program_id)
+                    .map_err(|_| PeriscopeError::InvalidProgramId(entry.program_id.clone()))?;
+                if registry.get(&program_id).is_none() {
+                    let idl = fetch_idl(cli, Some(&entry.program_id)).await?;
+                    registry.insert(program_id, idl);
+                }
+                let idl = registry.get(&program_id).expect("registered above");
+                let decoded = entry.bytes().and_then(|data| {
+                    decode_account_with(idl, &data, entry.account_type.as_deref(), None)
+                });
+                let outcome = match decoded {
+                    Ok(decoded) => {
+                        quarantine.remove(&entry.id)?;
+                        RetryOutcome {
+                            id: entry.id,
+                            address: entry.address,
+                            account: Some(decoded.name),
+                            value: Some(decoded.value),
+                            error: None,
+                        }
+                    }
+                    Err(e) => {
+                        entry.error = e.to_string();
+                        entry.attempts += 1;
+                        quarantine.put(&entry)?;
+                        RetryOutcome {
+                            id: entry.id,
+                            address: entry.address,
+                            account: None,
+                            value: None,
+                            error: Some(entry.error),
+                        }
+                    }
+                };
+                outcomes.push(outcome);
+            }
+            if cli.json() {
+                return print_json(&outcomes, "outcomes");
+            }
+            display_quarantine_retry(&outcomes);
+        }
+        QuarantineCommands::Clear => {
+            let entries = quarantine.entries()?;
+            for entry in &entries {
+                quarantine.remove(&entry.id)?;
+            }
+            if cli.json() {
+                return print_json(&json!({ "removed": entries.len() }), "quarantine");
+            }
+            println!("{}", trf(Msg::QuarantineCleared, &[&entries.len()]));
+        }
+    }
     Ok(())
 }
 
 fn cmd_config(action: ConfigCommands) -> Result<()> {
     match action {
         ConfigCommands::Show => {
-            let config = Config::load()?;
+            let config = Config::load_layered()?;
             let config_path = Config::file_path()?;
             let exists = Config::exists();
 
             println!();
-            println!("Periscope Configuration:");
-            println!("  Config file: {}", config_path.display());
+            println!("{}", tr(Msg::ConfigHeader));
+            println!("  {}: {}", tr(Msg::ConfigFile), config_path.display());
             println!(
-                "  File exists: {}",
-                if exists { "yes" } else { "no (using defaults)" }
+                "  {}: {}",
+                tr(Msg::ConfigFileExists),
+                if exists {
+                    tr(Msg::Yes)
+                } else {
+                    tr(Msg::NoUsingDefaults)
+                }
             );
+            if let Some(project_file) = &config.project_file {
+                println!("  {}: {}", tr(Msg::ProjectFile), project_file.display());
+            }
             println!();
-            println!("  RPC URL: {}", config.rpc_url);
+            println!("  {}: {}", tr(Msg::RpcUrl), config.rpc_url);
+            if let Some(program_id) = &config.program_id {
+                println!("  {}: {}", tr(Msg::ProgramId), program_id);
+            }
+            if let Some(idl) = &config.idl {
+                println!("  IDL: {}", idl);
+            }
+            println!(
+                "  {}: {}",
+                tr(Msg::Theme),
+                config.theme.name.as_deref().unwrap_or("default")
+            );
+            println!("  {}: {}", tr(Msg::Language), config.lang());
+            let explorers: Vec<String> = config.explorers().into_iter().map(|e| e.name).collect();
+            println!("  {}: {}", tr(Msg::Explorer), explorers.join(", "));
+            if !config.endpoints.registries.is_empty() {
+                println!(
+                    "  {}: {}",
+                    tr(Msg::Registries),
+                    config.endpoints.registries.join(", ")
+                );
+            }
+            if !config.endpoints.token_lists.is_empty() {
+                println!(
+                    "  {}: {}",
+                    tr(Msg::TokenLists),
+                    config.endpoints.token_lists.join(", ")
+                );
+            }
+            if !config.rpc_headers.is_empty() {
+                // Names only: the values are usually API keys
+                let names: Vec<&str> = config.rpc_headers.keys().map(String::as_str).collect();
+                println!("  {}: {}", tr(Msg::RpcHeaders), names.join(", "));
+            }
+            if !config.trust.is_empty() {
+                let trust = &config.trust;
+                println!(
+                    "  {}: {}",
+                    tr(Msg::TrustPolicy),
+                    trf(
+                        Msg::TrustSummary,
+                        &[
+                            &trust.idl_authorities.len(),
+                            &trust.signers.len(),
+                            &trust.pinned_urls.len()
+                        ]
+                    )
+                );
+            }
             println!();
             Ok(())
         }
-        ConfigCommands::Set { url } => {
+        ConfigCommands::Set {
+            key,
+            value,
+            url,
+            lang,
+        } => {
+            let mut pairs: Vec<(String, String)> = key.into_iter().zip(value).collect();
             if let Some(url) = url {
-                let mut config = Config::load()?;
-                config.rpc_url = url.clone();
-                config.validate()?;
-                config.save()?;
-
-                let config_path = Config::file_path()?;
-                println!("Saved RPC URL to {}", config_path.display());
-                println!("  rpc_url = \"{}\"", url);
-            } else {
-                println!("No value provided to set.");
-                println!("Usage: periscope config set --url <RPC_URL>");
+                pairs.push(("rpc_url".into(), url));
+            }
+            if let Some(lang) = lang {
+                pairs.push(("lang".into(), lang.to_string()));
+            }
+            if pairs.is_empty() {
+                println!("{}", tr(Msg::NothingToSet));
+                println!("{}", tr(Msg::ConfigSetUsage));
+                return Ok(());
+            }
+
+            let mut config = Config::load()?;
+            for (key, value) in &pairs {
+                config.set_key(key, value)?;
+            }
+            config.save()?;
+
+            let config_path = Config::file_path()?;
+            println!("{}", trf(Msg::SavedConfig, &[&config_path.display()]));
+            for (key, value) in pairs {
+                println!("  {} = \"{}\"", key, display_value(&key, &value));
+            }
+            Ok(())
+        }
+        ConfigCommands::Unset { key } => {
+            let mut config = Config::load()?;
+            config.unset_key(&key)?;
+            config.save()?;
+
+            let config_path = Config::file_path()?;
+            println!("{}", trf(Msg::SavedConfig, &[&config_path.display()]));
+            println!("  {} {}", key, tr(Msg::ConfigUnset));
+            Ok(())
+        }
+        ConfigCommands::Edit => {
+            let path = Config::file_path()?;
+            if !path.exists() {
+                Config::default().save()?;
             }
+
+            let editor = Config::editor();
+            let mut words = editor.split_whitespace();
+            let program = words.next().unwrap_or("vi");
+            let status = std::process::Command::new(program)
+                .args(words)
+                .arg(&path)
+                .status()
+                .map_err(|e| {
+                    PeriscopeError::ConfigError(format!("Failed to run editor '{}': {}", editor, e))
+                })?;
+            if !status.success() {
+                return Err(anyhow!(trf(Msg::EditorFailed, &[&editor])));
+            }
+
+            // Report mistakes now rather than on the next command
+            Config::load()?.validate()?;
+            println!("{}", trf(Msg::SavedConfig, &[&path.display()]));
             Ok(())
         }
     }
 }
 
 async fn fetch_idl(cli: &Cli, program_id: Option<&str>) -> Result<Idl> {
-    let source = cli.idl_source();
+    let mut idl = parse_idl_json(&load_idl_json(cli, program_id).await?)?;
+    idl.apply_case(cli.case);
+    load_annotations(cli, &idl.address)?;
+
+    let inspecting = matches!(cli.command, Commands::Inspect { .. });
+    if !idl.warnings.is_empty() && !inspecting && !cli.json() {
+        eprintln!(
+            "{} {}",
+            paint(tr(Msg::Note), Element::Warning),
+            trf(Msg::SkippedNote, &[&idl.warnings.len()])
+        );
+    }
+    Ok(idl)
+}
+
+/// Show the user's and the project's annotations of `program_id` in text
+/// output; the first program a command loads is the one annotated
+fn load_annotations(cli: &Cli, program_id: &str) -> Result<()> {
+    if program_id.is_empty() {
+        return Ok(());
+    }
+    set_annotations(Annotations::load(
+        program_id,
+        cli.config.annotations_dir.as_deref(),
+    )?);
+    Ok(())
+}
+
+/// Where a command's IDL comes from: without an explicit program, the
+/// project's IDL file, then its program ID
+fn idl_source(cli: &Cli, program_id: Option<&str>) -> IdlSource {
+    match (&cli.idl, &cli.idl_buffer, program_id, &cli.config.idl) {
+        (None, None, None, Some(path)) => IdlSource::from_path(path),
+        _ => cli.idl_source(),
+    }
+}
+
+/// Load the raw IDL JSON for a command, resolving the source from flags and config
+async fn load_idl_json(cli: &Cli, program_id: Option<&str>) -> Result<String> {
+    Ok(load_idl_with_origin(cli, program_id).await?.0)
+}
+
+/// [`load_idl_json`], plus the cache entry (without its JSON) saying where
+/// and at which slot an on-chain IDL was read, and the cached entry a fresh
+/// fetch replaced when its IDL was different
+async fn load_idl_with_origin(
+    cli: &Cli,
+    program_id: Option<&str>,
+) -> Result<(String, Option<CacheEntry>, Option<CacheEntry>)> {
+    let config = &cli.config;
+
+    let source = idl_source(cli, program_id);
+    let program_id = program_id.or(config.program_id.as_deref());
 
     match source {
         IdlSource::File(path) => {
-            let idl = load_idl_from_file(&path)?;
-            Ok(idl)
+            let json = read_idl_json_from_file(&path)?;
+            cli.config.trust.check_file(&path, &json)?;
+            Ok((json, None, None))
         }
         IdlSource::Url(url) => {
-            let idl = fetch_idl_from_url(&url).await?;
-            Ok(idl)
+            let json = fetch_idl_json_from_url(&url, config.max_idl_size()).await?;
+            cli.config.trust.check_url(&url, &json)?;
+            Ok((json, None, None))
+        }
+        IdlSource::Bundle {
+            path,
+            program_id: entry,
+        } => {
+            let entry = entry.as_deref().or(program_id);
+            let json = Bundle::read(Path::new(&path))?.idl_json(entry)?;
+            let source = match entry {
+                Some(entry) => format!("{}{}#{}", BUNDLE_PREFIX, path, entry),
+                None => format!("{}{}", BUNDLE_PREFIX, path),
+            };
+            cli.config.trust.check_file(&source, &json)?;
+            Ok((json, None, None))
+        }
+        IdlSource::Buffer(buffer) => {
+            let pubkey = Pubkey::from_str(&buffer)
+                .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&buffer])))?;
+            let rpc_url = get_rpc_url(cli);
+            let client = rpc_client(cli, &rpc_url)?;
+            let fetched = fetch_idl_buffer(&client, &pubkey, config.max_idl_size())?;
+            let authority = fetched.authority.to_string();
+            cli.config.trust.check_on_chain(&buffer, Some(&authority))?;
+            // Buffers are short-lived, so they're never cached
+            let origin = CacheEntry {
+                cluster: cluster_of(&rpc_url, &client, IdlCache::open().ok().as_ref()),
+                program_id: buffer,
+                slot: Some(fetched.slot),
+                authority: Some(authority),
+                compressed_len: Some(fetched.compressed_len),
+                fetched_at: now(),
+                imported: false,
+                idl_json: String::new(),
+            };
+            Ok((fetched.json, Some(origin), None))
         }
         IdlSource::OnChain => {
-            let program_id_str = program_id.ok_or_else(|| {
-                anyhow!("Program ID is required when fetching on-chain. Use --idl to load from file/URL instead.")
-            })?;
+            let mut recent = RecentPrograms::load().unwrap_or_default();
 
-            let pubkey = Pubkey::from_str(program_id_str)
-                .map_err(|_| anyhow!("Invalid program ID: {}", program_id_str))?;
+            let program_id_str = match program_id {
+                Some(id) => id.to_string(),
+                None => {
+                    let latest = recent.latest().ok_or_else(|| {
+                        PeriscopeError::Usage(tr(Msg::ProgramIdRequired).to_string())
+                    })?;
+                    eprintln!(
+                        "{}",
+                        paint(
+                            &trf(Msg::UsingRecent, &[&latest.name, &latest.program_id]),
+                            Element::Muted
+                        )
+                    );
+                    latest.program_id.clone()
+                }
+            };
+
+            let pubkey = Pubkey::from_str(&program_id_str).map_err(|_| {
+                PeriscopeError::Usage(trf(Msg::InvalidProgramId, &[&program_id_str]))
+            })?;
 
             let rpc_url = get_rpc_url(cli);
-            let idl = fetch_idl_from_chain(&pubkey, &rpc_url)?;
-            Ok(idl)
+            let (json, origin, replaced) = fetch_idl_or_registry(cli, &rpc_url, &pubkey).await?;
+
+            // Remembering the program is a convenience, never fail the command over it
+            if let Ok(names) = IdlNames::parse(&json) {
+                recent.touch(&program_id_str, &names.name, &rpc_url);
+                let _ = recent.save();
+            }
+
+            Ok((json, origin, replaced))
         }
     }
 }
 
+/// Load an IDL named on the command line: a program ID (on-chain), a file,
+/// a URL or a bundle entry
+async fn load_idl_source(cli: &Cli, source: &str) -> Result<Idl> {
+    let mut idl = parse_idl_json(&load_idl_source_json(cli, source).await?)?;
+    idl.apply_case(cli.case);
+    Ok(idl)
+}
+
+/// Raw IDL JSON of a source, see [`load_idl_source`]
+async fn load_idl_source_json(cli: &Cli, source: &str) -> Result<String> {
+    if let Ok(pubkey) = Pubkey::from_str(source) {
+        return Ok(fetch_idl_or_registry(cli, &get_rpc_url(cli), &pubkey)
+            .await?
+            .0);
+    }
+
+    if let IdlSource::Url(url) = IdlSource::from_path(source) {
+        let json = fetch_idl_json_from_url(&url, cli.config.max_idl_size()).await?;
+        cli.config.trust.check_url(&url, &json)?;
+        return Ok(json);
+    }
+    let json = read_local_idl_json(source)?;
+    cli.config.trust.check_file(source, &json)?;
+    Ok(json)
+}
+
+/// IDL JSON for `sign` and `verify`: files and bundle entries are read
+/// without the trust policy's signature check, which would reject the very
+/// IDLs being signed
+async fn load_signing_source_json(cli: &Cli, source: &str) -> Result<String> {
+    match IdlSource::from_path(source) {
+        IdlSource::Url(_) => load_idl_source_json(cli, source).await,
+        _ if Pubkey::from_str(source).is_ok() => load_idl_source_json(cli, source).await,
+        _ => Ok(read_local_idl_json(source)?),
+    }
+}
+
+/// On-chain IDL JSON with the cache entry it came from and the one it
+/// replaced (see [`fetch_idl_replacing`]); programs without an on-chain IDL
+/// are looked up in the configured IDL registries, which aren't cached
+async fn fetch_idl_or_registry(
+    cli: &Cli,
+    rpc_url: &str,
+    program_id: &Pubkey,
+) -> Result<(String, Option<CacheEntry>, Option<CacheEntry>)> {
+    let not_found = match fetch_idl_replacing(cli, rpc_url, program_id) {
+        Ok((mut entry, replaced)) => {
+            let json = std::mem::take(&mut entry.idl_json);
+            return Ok((json, Some(entry), replaced));
+        }
+        Err(e) if matches!(e.downcast_ref(), Some(PeriscopeError::IdlNotFound(_))) => e,
+        Err(e) => return Err(e),
+    };
+
+    let config = &cli.config;
+    let program_id = program_id.to_string();
+    let cluster = cluster_name(rpc_url);
+    for template in &config.endpoints.registries {
+        let url = template
+            .replace("{program_id}", &program_id)
+            .replace("{cluster}", &cluster);
+        // Registries only have some programs; the next one may have this one
+        let Ok(json) = fetch_idl_json_from_url(&url, config.max_idl_size()).await else {
+            continue;
+        };
+        cli.config.trust.check_registry(&url, &json, &program_id)?;
+        if !cli.json() {
+            eprintln!(
+                "{}",
+                paint(
+                    &trf(Msg::ReadFromRegistry, &[&program_id, &url]),
+                    Element::Muted
+                )
+            );
+        }
+        return Ok((json, None, None));
+    }
+    Err(not_found)
+}
+
+/// On-chain IDL, from the cache unless `--refresh`; fresh fetches are
+/// cached. Both are checked against the trust policy.
+fn fetch_idl_cached(cli: &Cli, rpc_url: &str, program_id: &Pubkey) -> Result<CacheEntry> {
+    Ok(fetch_idl_replacing(cli, rpc_url, program_id)?.0)
+}
+
+/// [`fetch_idl_cached`], plus the cache entry a fresh fetch replaced when
+/// the IDL changed since it was cached
+fn fetch_idl_replacing(
+    cli: &Cli,
+    rpc_url: &str,
+    program_id: &Pubkey,
+) -> Result<(CacheEntry, Option<CacheEntry>)> {
+    let trust = &cli.config.trust;
+    let cache = IdlCache::open().ok();
+    let client = rpc_client(cli, rpc_url)?;
+    let cluster = cluster_of(rpc_url, &client, cache.as_ref());
+    if !cli.refresh {
+        // Entries the IDL account no longer holds are fetched again, and so
+        // are those whose authority is unknown (cached before authorities
+        // were recorded) or only an archive's word while it is restricted
+        let cached = cache
+            .as_ref()
+            .and_then(|c| c.get_checked(&client, &cluster, program_id))
+            .filter(|checked| {
+                let known = checked.entry.authority.is_some()
+                    && (checked.confirmed || !checked.entry.imported);
+                known || trust.idl_authorities.is_empty()
+            })
+            .map(|checked| checked.entry);
+        if let Some(entry) = cached {
+            trust.check_on_chain(&entry.program_id, entry.authority.as_deref())?;
+            return Ok((entry, None));
+        }
+    }
+
+    let fetched = fetch_on_chain_idl(&client, program_id, cli.config.max_idl_size()).map_err(
+        |e| match e {
+            // Name the cluster: the IDL is often just published on another one
+            PeriscopeError::IdlNotFound(id) => {
+                PeriscopeError::IdlNotFound(format!("{} ({})", id, cluster))
+            }
+            e => e,
+        },
+    )?;
+    let authority = fetched.authority.to_string();
+    trust.check_on_chain(&program_id.to_string(), Some(&authority))?;
+    let entry = CacheEntry {
+        program_id: program_id.to_string(),
+        cluster,
+        slot: Some(fetched.slot),
+        authority: Some(authority),
+        compressed_len: Some(fetched.compressed_len),
+        fetched_at: now(),
+        imported: false,
+        idl_json: fetched.json,
+    };
+    let mut replaced = None;
+    if let Some(cache) = cache {
+        replaced = cache
+            .get(&entry.cluster, &entry.program_id)
+            .filter(|previous| previous.idl_json != entry.idl_json);
+        // Caching is an optimization, never fail the command over it
+        let _ = cache.set(&entry);
+    }
+    Ok((entry, replaced))
+}
+
+/// Cluster an RPC URL serves: named in the URL, else identified by genesis
+/// hash (remembered per host in the cache), else the URL's host
+fn cluster_of(rpc_url: &str, client: &RpcClient, cache: Option<&IdlCache>) -> String {
+    let host = cluster_name(rpc_url);
+    if KNOWN_CLUSTERS.contains(&host.as_str()) {
+        return host;
+    }
+    if let Some(cluster) = cache.and_then(|c| c.cluster_of(&host)) {
+        return cluster;
+    }
+    match identify_cluster(client) {
+        Ok(Some(cluster)) => {
+            if let Some(cache) = cache {
+                let _ = cache.set_cluster(&host, cluster);
+            }
+            cluster.to_string()
+        }
+        _ => host,
+    }
+}
+
+/// Read a keypair file, by default the Solana CLI's ~/.config/solana/id.json
+fn load_keypair(keypair: Option<&str>) -> Result<Keypair> {
+    let keypair_path = match keypair {
+        Some(path) => std::path::PathBuf::from(path),
+        None => dirs::home_dir()
+            .unwrap_or_default()
+            .join(".config/solana/id.json"),
+    };
+    Ok(read_keypair_file(&keypair_path).map_err(|e| {
+        PeriscopeError::Usage(trf(Msg::KeypairUnreadable, &[&keypair_path.display(), &e]))
+    })?)
+}
+
+/// RPC client for `rpc_url`, see [`rpc_options`]
+fn rpc_client(cli: &Cli, rpc_url: &str) -> Result<RpcClient> {
+    Ok(rpc_client_with(rpc_url, &rpc_options(cli, rpc_url)?)?)
+}
+
+/// Client options for `rpc_url`: the `rpc_headers` config table only goes to
+/// the user's configured `rpc_url` (same host and port), so a `--url`
+/// override or another cluster never gets the provider's credentials;
+/// `--rpc-header` flags go to whatever the command talks to
+fn rpc_options(cli: &Cli, rpc_url: &str) -> Result<RpcOptions> {
+    let config = Config::load().unwrap_or_default();
+    let mut options = RpcOptions::for_endpoint(rpc_url, &config.rpc_url, config.rpc_headers());
+    for header in &cli.rpc_headers {
+        let parsed = parse_rpc_header(header)
+            .ok_or_else(|| PeriscopeError::Usage(trf(Msg::InvalidRpcHeader, &[header])))?;
+        options.headers.push(parsed);
+    }
+    Ok(options)
+}
+
 fn get_rpc_url(cli: &Cli) -> String {
     match &cli.url {
         Some(url) => url.clone(),
-        None => {
-            let config = Config::load().unwrap_or_default();
-            config.rpc_url
-        }
+        None => cli.config.rpc_url.clone(),
     }
 }
@@ -0,0 +1,215 @@
+//! Account role matrix
+//!
+//! Which instructions take which accounts, and how: read-only, writable,
+//! signer. Accounts are matched by name across instructions (nested groups
+//! as `group.account`), so `periscope matrix` can show at a glance every
+//! instruction able to mutate, say, the treasury.
+
+use crate::idl::{Idl, IdlAccount, IdlAccountItem};
+use serde::Serialize;
+use std::fmt;
+
+/// How an instruction uses an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Role {
+    pub writable: bool,
+    pub signer: bool,
+    pub optional: bool,
+}
+
+impl Role {
+    fn of(account: &IdlAccount) -> Self {
+        Role {
+            writable: account.writable,
+            signer: account.signer,
+            optional: account.optional,
+        }
+    }
+}
+
+/// `R`, `W`, `S` or `WS`, with `?` for optional accounts
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = match (self.writable, self.signer) {
+            (true, true) => "WS",
+            (true, false) => "W",
+            (false, true) => "S",
+            (false, false) => "R",
+        }
+        .to_string();
+        if self.optional {
+            s.push('?');
+        }
+        f.pad(&s)
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accounts (rows) by instructions (columns)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleMatrix {
+    /// Instruction names, in IDL order
+    pub instructions: Vec<String>,
+
+    /// Account names, in order of first use
+    pub accounts: Vec<String>,
+
+    /// `cells[account][instruction]`
+    pub cells: Vec<Vec<Option<Role>>>,
+}
+
+impl RoleMatrix {
+    /// Matrix of every instruction account of `idl`
+    pub fn build(idl: &Idl) -> Self {
+        let instructions: Vec<String> = idl.instructions.iter().map(|ix| ix.name.clone()).collect();
+        let mut accounts: Vec<String> = Vec::new();
+        let mut cells: Vec<Vec<Option<Role>>> = Vec::new();
+
+        for (column, ix) in idl.instructions.iter().enumerate() {
+            let mut used = Vec::new();
+            flatten(&ix.accounts, "", &mut used);
+            for (name, role) in used {
+                let row = match accounts.iter().position(|a| *a == name) {
+                    Some(row) => row,
+                    None => {
+                        accounts.push(name);
+                        cells.push(vec![None; instructions.len()]);
+                        accounts.len() - 1
+                    }
+                };
+                cells[row][column] = Some(role);
+            }
+        }
+
+        RoleMatrix {
+            instructions,
+            accounts,
+            cells,
+        }
+    }
+
+    /// Only the accounts whose name contains `filter` (case-insensitive)
+    pub fn filter_accounts(mut self, filter: &str) -> Self {
+        let filter = filter.to_lowercase();
+        let keep: Vec<bool> = self
+            .accounts
+            .iter()
+            .map(|a| a.to_lowercase().contains(&filter))
+            .collect();
+        let mut rows = keep.iter();
+        self.accounts.retain(|_| *rows.next().unwrap_or(&false));
+        let mut rows = keep.iter();
+        self.cells.retain(|_| *rows.next().unwrap_or(&false));
+        self
+    }
+
+    /// Instructions that can write to `account`
+    pub fn writers(&self, account: &str) -> Vec<&str> {
+        let Some(row) = self.accounts.iter().position(|a| a == account) else {
+            return Vec::new();
+        };
+        self.cells[row]
+            .iter()
+            .zip(&self.instructions)
+            .filter(|(role, _)| role.is_some_and(|r| r.writable))
+            .map(|(_, ix)| ix.as_str())
+            .collect()
+    }
+}
+
+/// Rows as `{ "name": ..., "roles": { instruction: role } }`
+impl Serialize for RoleMatrix {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(Serialize)]
+        struct Row<'a> {
+            name: &'a str,
+            roles: std::collections::BTreeMap<&'a str, Role>,
+        }
+
+        let rows: Vec<Row> = self
+            .accounts
+            .iter()
+            .zip(&self.cells)
+            .map(|(name, cells)| Row {
+                name,
+                roles: self
+                    .instructions
+                    .iter()
+                    .zip(cells)
+                    .filter_map(|(ix, role)| role.map(|r| (ix.as_str(), r)))
+                    .collect(),
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("RoleMatrix", 2)?;
+        state.serialize_field("instructions", &self.instructions)?;
+        state.serialize_field("accounts", &rows)?;
+        state.end()
+    }
+}
+
+fn flatten(items: &[IdlAccountItem], prefix: &str, out: &mut Vec<(String, Role)>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => {
+                out.push((format!("{}{}", prefix, account.name), Role::of(account)))
+            }
+            IdlAccountItem::Group(group) => {
+                flatten(&group.accounts, &format!("{}{}.", prefix, group.name), out)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_role_matrix() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "deposit", "accounts": [
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "treasury", "writable": true }
+                ], "args": [] },
+                { "name": "report", "accounts": [
+                    { "name": "treasury" },
+                    { "name": "authority", "signer": true, "optional": true }
+                ], "args": [] }
+            ]
+        }))
+        .unwrap();
+
+        let matrix = RoleMatrix::build(&idl);
+        assert_eq!(matrix.accounts, ["user", "treasury", "authority"]);
+        let labels: Vec<Vec<String>> = matrix
+            .cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|r| r.map_or("-".to_string(), |r| r.to_string()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(labels, [["WS", "-"], ["W", "R"], ["-", "S?"]]);
+        assert_eq!(matrix.writers("treasury"), ["deposit"]);
+
+        let treasury = matrix.clone().filter_accounts("TREAS");
+        assert_eq!(treasury.accounts, ["treasury"]);
+        assert_eq!(
+            serde_json::to_value(&treasury).unwrap()["accounts"][0]["roles"],
+            json!({ "deposit": "W", "report": "R" })
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Timing of IDL loading stages
+//!
+//! Fetching an IDL goes through address derivation, RPC or HTTP fetch,
+//! decompression and parsing. Each stage is timed into a process-wide
+//! [`Metrics`] collector; `--timing` prints it, and embedders can read
+//! [`snapshot`] to export counts and durations (e.g. as Prometheus
+//! summaries).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics::new());
+
+/// A timed stage of loading or decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    /// Deriving the IDL account address from the program ID
+    DeriveAddress,
+    /// An RPC call, by method name
+    Rpc(&'static str),
+    /// Downloading an IDL from a URL
+    Http,
+    /// Reading an IDL file from disk
+    ReadFile,
+    /// Decompressing IDL account data
+    Decompress,
+    /// Parsing IDL JSON
+    Parse,
+    /// Decoding account or instruction data
+    Decode,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::DeriveAddress => write!(f, "derive address"),
+            Stage::Rpc(method) => write!(f, "rpc {}", method),
+            Stage::Http => write!(f, "http fetch"),
+            Stage::ReadFile => write!(f, "read file"),
+            Stage::Decompress => write!(f, "decompress"),
+            Stage::Parse => write!(f, "parse"),
+            Stage::Decode => write!(f, "decode"),
+        }
+    }
+}
+
+/// How often a stage ran and for how long in total
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageMetrics {
+    /// Number of times the stage ran
+    pub count: u64,
+
+    /// Time spent across all runs
+    pub total: Duration,
+}
+
+/// Per-stage counts and durations
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metrics {
+    stages: BTreeMap<Stage, StageMetrics>,
+}
+
+impl Metrics {
+    /// Empty metrics
+    pub const fn new() -> Self {
+        Metrics {
+            stages: BTreeMap::new(),
+        }
+    }
+
+    /// Add one run of `stage` taking `elapsed`
+    pub fn record(&mut self, stage: Stage, elapsed: Duration) {
+        let entry = self.stages.entry(stage).or_default();
+        entry.count += 1;
+        entry.total += elapsed;
+    }
+
+    /// Metrics of one stage, if it ran
+    pub fn get(&self, stage: Stage) -> Option<StageMetrics> {
+        self.stages.get(&stage).copied()
+    }
+
+    /// Stages that ran, in pipeline order
+    pub fn stages(&self) -> impl Iterator<Item = (Stage, StageMetrics)> + '_ {
+        self.stages.iter().map(|(stage, m)| (*stage, *m))
+    }
+
+    /// True when nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+/// Record one run of `stage` in the process-wide metrics
+pub fn record(stage: Stage, elapsed: Duration) {
+    METRICS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .record(stage, elapsed);
+}
+
+/// Run `f`, recording its duration under `stage`
+pub fn timed<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record(stage, started.elapsed());
+    result
+}
+
+/// Copy of the process-wide metrics
+pub fn snapshot() -> Metrics {
+    METRICS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Clear the process-wide metrics
+pub fn reset() {
+    *METRICS.lock().unwrap_or_else(|e| e.into_inner()) = Metrics::new();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_accumulate() {
+        let mut metrics = Metrics::new();
+        metrics.record(Stage::Parse, Duration::from_millis(3));
+        metrics.record(Stage::Rpc("getAccountInfo"), Duration::from_millis(100));
+        metrics.record(Stage::Parse, Duration::from_millis(2));
+
+        assert_eq!(
+            metrics.get(Stage::Parse),
+            Some(StageMetrics {
+                count: 2,
+                total: Duration::from_millis(5)
+            })
+        );
+        let order: Vec<String> = metrics.stages().map(|(s, _)| s.to_string()).collect();
+        assert_eq!(order, vec!["rpc getAccountInfo", "parse"]);
+    }
+}
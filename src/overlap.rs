@@ -0,0 +1,249 @@
+//! Error code and discriminator overlaps across programs
+//!
+//! Protocols made of several programs are usually monitored by parsing
+//! logs, where `custom program error: 0x1770` or an instruction
+//! discriminator means nothing without knowing which program emitted it.
+//! [`find_overlaps`] lists the error codes, error names and instruction,
+//! account and event discriminators that more than one program of a set
+//! defines.
+//!
+//! Indexers keyed on the discriminator alone mix up colliding items; a
+//! [`DiscriminatorIndex`] over the same programs resolves a discriminator
+//! with the program ID of the instruction or account it came from.
+
+use crate::idl::{event_discriminator, hex, Idl};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// An item of one program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Occurrence {
+    /// Program name
+    pub program: String,
+
+    /// Program address
+    pub address: String,
+
+    /// Item name
+    pub name: String,
+}
+
+/// A code, name or discriminator defined by several programs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Overlap {
+    /// `error-code`, `error-name`, `instruction`, `account` or `event`
+    pub kind: &'static str,
+
+    /// The shared code, name or discriminator (hex)
+    pub key: String,
+
+    pub occurrences: Vec<Occurrence>,
+}
+
+/// Everything at least two of `idls` define, by kind then key
+pub fn find_overlaps(idls: &[&Idl]) -> Vec<Overlap> {
+    let mut keyed: BTreeMap<(usize, &'static str, String), Vec<Occurrence>> = BTreeMap::new();
+    for idl in idls {
+        let mut add = |order: usize, kind: &'static str, key: String, name: &str| {
+            keyed
+                .entry((order, kind, key))
+                .or_default()
+                .push(Occurrence {
+                    program: idl.metadata.name.clone(),
+                    address: idl.address.clone(),
+                    name: name.to_string(),
+                });
+        };
+        for error in &idl.errors {
+            add(0, "error-code", error.code.to_string(), &error.name);
+            add(1, "error-name", error.name.clone(), &error.name);
+        }
+        for (kind, discriminator, name) in discriminators(idl) {
+            let order = match kind {
+                "instruction" => 2,
+                "account" => 3,
+                _ => 4,
+            };
+            add(order, kind, hex(&discriminator), name);
+        }
+    }
+
+    keyed
+        .into_iter()
+        .filter(|(_, occurrences)| {
+            occurrences
+                .iter()
+                .any(|o| o.address != occurrences[0].address)
+        })
+        .map(|((_, kind, key), occurrences)| Overlap {
+            kind,
+            key,
+            occurrences,
+        })
+        .collect()
+}
+
+/// An instruction, account or event of one program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiscriminatorEntry {
+    /// `instruction`, `account` or `event`
+    pub kind: &'static str,
+
+    #[serde(flatten)]
+    pub occurrence: Occurrence,
+}
+
+/// Instruction, account and event discriminators of many programs
+///
+/// ```ignore
+/// let index = DiscriminatorIndex::new(&idls);
+/// if index.is_ambiguous(&ix.data) {
+///     // only the program ID tells the candidates apart
+/// }
+/// let entry = index.lookup(&program_id, "instruction", &ix.data);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DiscriminatorIndex {
+    entries: HashMap<Vec<u8>, Vec<DiscriminatorEntry>>,
+    lengths: BTreeSet<usize>,
+}
+
+impl DiscriminatorIndex {
+    /// Index the discriminators of `idls`
+    pub fn new(idls: &[&Idl]) -> Self {
+        let mut index = DiscriminatorIndex::default();
+        for idl in idls {
+            for (kind, discriminator, name) in discriminators(idl) {
+                index.lengths.insert(discriminator.len());
+                index
+                    .entries
+                    .entry(discriminator)
+                    .or_default()
+                    .push(DiscriminatorEntry {
+                        kind,
+                        occurrence: Occurrence {
+                            program: idl.metadata.name.clone(),
+                            address: idl.address.clone(),
+                            name: name.to_string(),
+                        },
+                    });
+            }
+        }
+        index
+    }
+
+    /// Every item, of any program, whose discriminator starts `data`
+    pub fn candidates(&self, data: &[u8]) -> Vec<&DiscriminatorEntry> {
+        self.lengths
+            .iter()
+            .filter_map(|len| self.entries.get(data.get(..*len)?))
+            .flatten()
+            .collect()
+    }
+
+    /// Whether the discriminator starting `data` belongs to more than one
+    /// program
+    pub fn is_ambiguous(&self, data: &[u8]) -> bool {
+        let candidates = self.candidates(data);
+        candidates
+            .iter()
+            .any(|c| c.occurrence.address != candidates[0].occurrence.address)
+    }
+
+    /// The `kind` item of program `program_id` whose discriminator starts
+    /// `data`
+    pub fn lookup(&self, program_id: &str, kind: &str, data: &[u8]) -> Option<&DiscriminatorEntry> {
+        self.candidates(data)
+            .into_iter()
+            .find(|c| c.kind == kind && c.occurrence.address == program_id)
+    }
+}
+
+/// Kind, discriminator and name of every instruction, account and event
+fn discriminators(idl: &Idl) -> Vec<(&'static str, Vec<u8>, &str)> {
+    let instructions = idl
+        .instructions
+        .iter()
+        .map(|ix| ("instruction", ix.discriminator_bytes(), ix.name.as_str()));
+    let accounts = idl.accounts.iter().map(|account| {
+        (
+            "account",
+            account.discriminator_bytes(),
+            account.name.as_str(),
+        )
+    });
+    let events = idl.events.iter().map(|event| {
+        let discriminator = if event.discriminator.is_empty() {
+            event_discriminator(&event.name).to_vec()
+        } else {
+            event.discriminator.clone()
+        };
+        ("event", discriminator, event.name.as_str())
+    });
+    instructions.chain(accounts).chain(events).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn idl(name: &str, address: &str, instructions: &[&str], errors: serde_json::Value) -> Idl {
+        let instructions: Vec<_> = instructions
+            .iter()
+            .map(|ix| json!({ "name": ix, "accounts": [], "args": [] }))
+            .collect();
+        serde_json::from_value(json!({
+            "address": address,
+            "metadata": { "name": name, "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": instructions,
+            "errors": errors
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_overlaps() {
+        let amm = idl(
+            "amm",
+            "11111111111111111111111111111111",
+            &["initialize", "swap"],
+            json!([{ "code": 6000, "name": "Slippage" }, { "code": 6001, "name": "Paused" }]),
+        );
+        let vault = idl(
+            "vault",
+            "Vote111111111111111111111111111111111111111",
+            &["initialize", "deposit"],
+            json!([{ "code": 6000, "name": "Paused" }]),
+        );
+
+        let overlaps = find_overlaps(&[&amm, &vault]);
+        let keys: Vec<_> = overlaps.iter().map(|o| (o.kind, o.key.as_str())).collect();
+        assert_eq!(
+            keys,
+            [
+                ("error-code", "6000"),
+                ("error-name", "Paused"),
+                (
+                    "instruction",
+                    hex(&amm.instructions[0].discriminator_bytes()).as_str()
+                ),
+            ]
+        );
+        assert_eq!(overlaps[0].occurrences[1].program, "vault");
+        assert_eq!(overlaps[2].occurrences[0].name, "initialize");
+
+        // A program never overlaps with itself
+        assert!(find_overlaps(&[&amm, &amm]).is_empty());
+
+        let index = DiscriminatorIndex::new(&[&amm, &vault]);
+        let mut data = amm.instructions[0].discriminator_bytes();
+        data.extend([1, 2, 3]);
+        assert!(index.is_ambiguous(&data));
+        assert_eq!(index.candidates(&data).len(), 2);
+        let found = index.lookup(&vault.address, "instruction", &data).unwrap();
+        assert_eq!(found.occurrence.program, "vault");
+        assert!(index.lookup(&vault.address, "account", &data).is_none());
+        assert!(!index.is_ambiguous(&amm.instructions[1].discriminator_bytes()));
+    }
+}
@@ -0,0 +1,82 @@
+//! Pagination of list output
+//!
+//! `--page N --per-page M` cuts a listing to one page, so a 150-instruction
+//! program doesn't scroll a slow terminal for seconds. With the `per_page`
+//! config key set, lists are paged by default and `--all` shows everything.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use serde::Serialize;
+use std::ops::Range;
+
+/// Page size when `--page` is given without `--per-page` or a configured size
+pub const DEFAULT_PER_PAGE: usize = 50;
+
+/// One page of a list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Page {
+    /// Page number, from 1
+    pub page: usize,
+
+    /// Items per page
+    pub per_page: usize,
+
+    /// Number of pages (at least 1, an empty list has one empty page)
+    pub pages: usize,
+
+    /// Items in the whole list
+    pub total: usize,
+}
+
+impl Page {
+    /// Page `page` of a list of `total` items, `per_page` at a time
+    pub fn new(page: usize, per_page: usize, total: usize) -> PeriscopeResult<Self> {
+        if page == 0 || per_page == 0 {
+            return Err(PeriscopeError::Usage(
+                "--page and --per-page must be at least 1".into(),
+            ));
+        }
+        let pages = total.div_ceil(per_page).max(1);
+        if page > pages {
+            return Err(PeriscopeError::Usage(format!(
+                "page {} is past the last page ({} of {} items, {} per page)",
+                page, pages, total, per_page
+            )));
+        }
+        Ok(Page {
+            page,
+            per_page,
+            pages,
+            total,
+        })
+    }
+
+    /// Indexes of the items on this page
+    pub fn range(&self) -> Range<usize> {
+        let start = (self.page - 1) * self.per_page;
+        start..(start + self.per_page).min(self.total)
+    }
+
+    /// The items on this page
+    pub fn slice<'a, T>(&self, items: &'a [T]) -> &'a [T] {
+        &items[self.range()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_ranges() {
+        let items: Vec<usize> = (0..7).collect();
+        let page = Page::new(2, 3, items.len()).unwrap();
+        assert_eq!(page.pages, 3);
+        assert_eq!(page.slice(&items), [3, 4, 5]);
+        assert_eq!(Page::new(3, 3, 7).unwrap().slice(&items), [6]);
+        assert_eq!(Page::new(1, 3, 0).unwrap().range(), 0..0);
+
+        assert!(Page::new(4, 3, 7).is_err());
+        assert!(Page::new(0, 3, 7).is_err());
+        assert!(Page::new(1, 0, 7).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! PDA catalogue
+//!
+//! Every PDA an IDL declares, across all instructions: the accounts' `pda`
+//! seed lists are collected, identical seed recipes grouped, and each
+//! recipe listed with the instruction accounts that use it. Recipes made of
+//! constants only are derived to their address, so `periscope pdas` doubles
+//! as a map of a program's singleton accounts.
+
+use crate::idl::{hex, Idl, IdlAccountItem, IdlSeed};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use std::str::FromStr;
+
+/// One PDA seed recipe and where it's used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PdaRecipe {
+    /// Seeds in order, see [`seed_label`]
+    pub seeds: Vec<String>,
+
+    /// Account names the recipe goes by, in order of first use
+    pub names: Vec<String>,
+
+    /// `instruction.account` of every use (nested groups as `group.account`)
+    pub uses: Vec<String>,
+
+    /// Address and bump, when every seed is a constant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<DerivedPda>,
+}
+
+/// Address of a constant-seeded PDA
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DerivedPda {
+    pub address: String,
+    pub bump: u8,
+}
+
+impl fmt::Display for DerivedPda {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (bump {})", self.address, self.bump)
+    }
+}
+
+/// Every distinct PDA recipe of `idl`, in order of first use (`raw_seeds`:
+/// see [`seed_label`])
+pub fn pda_recipes(idl: &Idl, raw_seeds: bool) -> Vec<PdaRecipe> {
+    let program_id = Pubkey::from_str(&idl.address).ok();
+    let mut recipes: Vec<PdaRecipe> = Vec::new();
+
+    for ix in &idl.instructions {
+        let mut pdas = Vec::new();
+        collect(&ix.accounts, "", &mut pdas);
+        for (name, seeds) in pdas {
+            let labels: Vec<String> = seeds
+                .iter()
+                .map(|seed| seed_label(seed, raw_seeds))
+                .collect();
+            let use_ = format!("{}.{}", ix.name, name);
+            let short = name.rsplit('.').next().unwrap_or(&name).to_string();
+            match recipes.iter_mut().find(|r| r.seeds == labels) {
+                Some(recipe) => {
+                    if !recipe.names.contains(&short) {
+                        recipe.names.push(short);
+                    }
+                    recipe.uses.push(use_);
+                }
+                None => recipes.push(PdaRecipe {
+                    address: program_id.and_then(|id| derive_constant(seeds, &id)),
+                    seeds: labels,
+                    names: vec![short],
+                    uses: vec![use_],
+                }),
+            }
+        }
+    }
+    recipes
+}
+
+/// `"vault"` for a printable UTF-8 constant, base58 for a 32-byte constant
+/// (a pubkey), `0x…` for other constants, `account:<path>` and `arg:<path>`
+/// for seeds taken from the instruction. With `raw`, constants are printed
+/// as the byte arrays the IDL stores.
+pub fn seed_label(seed: &IdlSeed, raw: bool) -> String {
+    match seed {
+        IdlSeed::Const { value } => match const_bytes(value) {
+            Some(bytes) if !raw => const_label(&bytes),
+            _ => value.to_string(),
+        },
+        IdlSeed::Account { path } => format!("account:{}", path),
+        IdlSeed::Arg { path } => format!("arg:{}", path),
+    }
+}
+
+fn const_label(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.is_empty() && text.chars().all(|c| !c.is_control()) => {
+            format!("\"{}\"", text)
+        }
+        _ => match Pubkey::try_from(bytes) {
+            Ok(key) => key.to_string(),
+            Err(_) => format!("0x{}", hex(bytes)),
+        },
+    }
+}
+
+/// PDA accounts of an instruction with their seeds, by dotted name
+fn collect<'a>(items: &'a [IdlAccountItem], prefix: &str, out: &mut Vec<(String, &'a [IdlSeed])>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => {
+                if let Some(pda) = &account.pda {
+                    out.push((format!("{}{}", prefix, account.name), &pda.seeds));
+                }
+            }
+            IdlAccountItem::Group(group) => {
+                collect(&group.accounts, &format!("{}{}.", prefix, group.name), out)
+            }
+        }
+    }
+}
+
+fn const_bytes(value: &serde_json::Value) -> Option<Vec<u8>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect()
+}
+
+fn derive_constant(seeds: &[IdlSeed], program_id: &Pubkey) -> Option<DerivedPda> {
+    let seeds = seeds
+        .iter()
+        .map(|seed| match seed {
+            IdlSeed::Const { value } => const_bytes(value),
+            _ => None,
+        })
+        .collect::<Option<Vec<Vec<u8>>>>()?;
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::try_find_program_address(&seeds, program_id).map(|(address, bump)| DerivedPda {
+        address: address.to_string(),
+        bump,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+
+    #[test]
+    fn test_recipes_grouped_and_derived() {
+        let idl = parse_idl_json(
+            r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "init", "discriminator": [1,0,0,0,0,0,0,0], "args": [], "accounts": [
+                    { "name": "config", "pda": { "seeds": [
+                        { "kind": "const", "value": [99, 111, 110, 102, 105, 103] }
+                    ] } },
+                    { "name": "owner", "signer": true }
+                ] },
+                { "name": "deposit", "discriminator": [2,0,0,0,0,0,0,0], "args": [], "accounts": [
+                    { "name": "settings", "pda": { "seeds": [
+                        { "kind": "const", "value": [99, 111, 110, 102, 105, 103] }
+                    ] } },
+                    { "name": "vault", "pda": { "seeds": [
+                        { "kind": "const", "value": [0, 255] },
+                        { "kind": "account", "path": "owner" }
+                    ] } }
+                ] }
+            ]
+        }"#,
+        )
+        .unwrap();
+
+        let recipes = pda_recipes(&idl, false);
+        assert_eq!(recipes.len(), 2);
+        assert_eq!(recipes[0].seeds, vec!["\"config\""]);
+        assert_eq!(recipes[0].names, vec!["config", "settings"]);
+        assert_eq!(recipes[0].uses, vec!["init.config", "deposit.settings"]);
+        let (address, bump) =
+            Pubkey::find_program_address(&[b"config"], &Pubkey::from_str(&idl.address).unwrap());
+        assert_eq!(
+            recipes[0].address,
+            Some(DerivedPda {
+                address: address.to_string(),
+                bump
+            })
+        );
+
+        assert_eq!(recipes[1].seeds, vec!["0x00ff", "account:owner"]);
+        assert_eq!(recipes[1].uses, vec!["deposit.vault"]);
+        assert_eq!(recipes[1].address, None);
+        assert_eq!(
+            pda_recipes(&idl, true)[1].seeds,
+            vec!["[0,255]", "account:owner"]
+        );
+    }
+
+    #[test]
+    fn test_const_labels() {
+        let key = Pubkey::new_unique();
+        assert_eq!(const_label(b"pool-v2"), "\"pool-v2\"");
+        assert_eq!(const_label(&key.to_bytes()), key.to_string());
+        assert_eq!(const_label(&[1, 2, 0xff]), "0x0102ff");
+        assert_eq!(const_label(&[0, 0]), "0x0000");
+    }
+}
@@ -0,0 +1,141 @@
+//! Concurrent decompression and parsing of many IDLs
+//!
+//! Decompressing and parsing an IDL is CPU-bound and independent of every
+//! other, so commands handling many programs (bundles, overlap checks, the
+//! whole cache) spread the work over rayon's global pool (one thread per
+//! core) instead of doing it one program after another. Results come back
+//! in input order. Without the `parallel` feature the same functions run
+//! serially.
+//!
+//! ```ignore
+//! let idls = fetch_idls(&client, &program_ids, DEFAULT_MAX_IDL_SIZE)?;
+//! for (program_id, idl) in program_ids.iter().zip(idls) {
+//!     registry.insert(*program_id, idl?);
+//! }
+//! ```
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{decompress_idl_data_with_limit, idl_account_payload, parse_idl_json, Idl};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "fetch-rpc")]
+use crate::idl::{fetch_accounts, get_idl_address};
+#[cfg(feature = "fetch-rpc")]
+use solana_client::rpc_client::RpcClient;
+#[cfg(feature = "fetch-rpc")]
+use solana_sdk::pubkey::Pubkey;
+
+/// Apply `f` to every item on rayon's pool, keeping the input order.
+/// Work is split and stolen as threads free up, so a few large IDLs don't
+/// leave the other threads idle.
+#[cfg(feature = "parallel")]
+pub fn map_concurrent<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    items.par_iter().map(&f).collect()
+}
+
+/// Apply `f` to every item in turn (built without the `parallel` feature)
+#[cfg(not(feature = "parallel"))]
+pub fn map_concurrent<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    items.iter().map(f).collect()
+}
+
+/// Parse IDL JSON documents concurrently
+pub fn parse_idls<S: AsRef<str> + Sync>(jsons: &[S]) -> Vec<PeriscopeResult<Idl>> {
+    map_concurrent(jsons, |json| parse_idl_json(json.as_ref()))
+}
+
+/// Decompress and parse the data of IDL accounts concurrently, each capped
+/// at `limit` bytes decompressed
+pub fn decode_idl_accounts<D: AsRef<[u8]> + Sync>(
+    accounts: &[D],
+    limit: usize,
+) -> Vec<PeriscopeResult<Idl>> {
+    map_concurrent(accounts, |data| {
+        let json = decompress_idl_data_with_limit(idl_account_payload(data.as_ref())?, limit)?;
+        let json = String::from_utf8(json)
+            .map_err(|_| PeriscopeError::DecompressionError("Invalid UTF-8".to_string()))?;
+        parse_idl_json(&json)
+    })
+}
+
+/// Fetch the on-chain IDLs of many programs: their IDL accounts in batched
+/// `getMultipleAccounts` requests, then decompressed and parsed
+/// concurrently (capped at `limit` bytes each). A program without an IDL gets
+/// `IdlNotFound`.
+#[cfg(feature = "fetch-rpc")]
+pub fn fetch_idls(
+    client: &RpcClient,
+    program_ids: &[Pubkey],
+    limit: usize,
+) -> PeriscopeResult<Vec<PeriscopeResult<Idl>>> {
+    let addresses = program_ids
+        .iter()
+        .map(get_idl_address)
+        .collect::<PeriscopeResult<Vec<_>>>()?;
+    let accounts = fetch_accounts(client, &addresses)?;
+    let found: Vec<&[u8]> = accounts
+        .iter()
+        .flatten()
+        .map(|account| account.data.as_slice())
+        .collect();
+    let mut idls = decode_idl_accounts(&found, limit).into_iter();
+
+    Ok(program_ids
+        .iter()
+        .zip(&accounts)
+        .map(|(program_id, account)| match account {
+            Some(_) => idls.next().expect("one result per account"),
+            None => Err(PeriscopeError::IdlNotFound(program_id.to_string())),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::DEFAULT_MAX_IDL_SIZE;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    fn idl_account(name: &str) -> Vec<u8> {
+        let json = format!(
+            r#"{{"address":"11111111111111111111111111111111","metadata":{{"name":"{}","version":"0.1.0","spec":"0.1.0"}},"instructions":[]}}"#,
+            name
+        );
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut data = vec![0u8; 40];
+        data.extend((compressed.len() as u32).to_le_bytes());
+        data.extend(compressed);
+        data
+    }
+
+    #[test]
+    fn test_decode_idl_accounts_keeps_order() {
+        let mut accounts: Vec<Vec<u8>> = (0..40).map(|i| idl_account(&format!("p{i}"))).collect();
+        accounts[7] = vec![1, 2, 3];
+
+        let idls = decode_idl_accounts(&accounts, DEFAULT_MAX_IDL_SIZE);
+        assert_eq!(idls.len(), 40);
+        assert!(idls[7].is_err());
+        for (i, idl) in idls.iter().enumerate().filter(|(i, _)| *i != 7) {
+            assert_eq!(idl.as_ref().unwrap().metadata.name, format!("p{i}"));
+        }
+
+        assert_eq!(map_concurrent(&[3, 1, 2], |n| n * 10), [30, 10, 20]);
+        assert!(parse_idls::<&str>(&[]).is_empty());
+    }
+}
@@ -0,0 +1,411 @@
+//! Pre-flight checks of an instruction's accounts
+//!
+//! `periscope preflight <PROGRAM_ID> <IX> --account user=<PUBKEY> ...` looks
+//! up every account the instruction would be sent with and checks it against
+//! what the IDL says about it, catching wiring bugs (a swapped account, the
+//! wrong PDA, a mint where a pool was expected) before a transaction fails
+//! on chain. Accounts that aren't given are taken from their fixed address
+//! or derived from their PDA seeds where possible.
+
+use crate::doctor::CheckStatus;
+use crate::encode::derive_pda;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{Idl, IdlAccount, IdlAccountItem, IdlInstruction};
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Length of an Anchor account discriminator
+const DISCRIMINATOR_LEN: usize = 8;
+
+/// One check of one account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountCheck {
+    /// Account name in the IDL
+    pub account: String,
+
+    /// Address checked (base58), when one is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+
+    /// What was checked: address, exists, owner, type, signer, writable, pda
+    pub check: &'static str,
+
+    /// Outcome
+    pub status: CheckStatus,
+
+    /// Reason for the outcome
+    pub detail: String,
+}
+
+/// Where the address of an account came from
+enum Resolved {
+    Given(Pubkey),
+    Fixed(Pubkey),
+    Derived(Pubkey),
+    Omitted,
+    Missing,
+}
+
+/// Check the accounts of `ix`
+///
+/// `given` holds the addresses passed on the command line, by account name.
+/// `fetch` looks up accounts in one batch, `None` for those that don't
+/// exist.
+pub fn preflight(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    given: &HashMap<String, Pubkey>,
+    fetch: impl FnOnce(&[Pubkey]) -> PeriscopeResult<Vec<Option<Account>>>,
+) -> PeriscopeResult<Vec<AccountCheck>> {
+    let program_id = Pubkey::from_str(&idl.address)
+        .map_err(|_| PeriscopeError::InvalidProgramId(format!("{} (IDL address)", idl.address)))?;
+
+    let mut accounts = Vec::new();
+    flatten(&ix.accounts, &mut accounts);
+    if let Some(unknown) = given
+        .keys()
+        .find(|name| !accounts.iter().any(|a| &a.name == *name))
+    {
+        return Err(PeriscopeError::Usage(format!(
+            "'{}' is not an account of {}",
+            unknown, ix.name
+        )));
+    }
+
+    let resolved: Vec<Resolved> = accounts
+        .iter()
+        .map(|account| resolve(idl, ix, account, given))
+        .collect();
+    let addresses: Vec<Pubkey> = resolved.iter().filter_map(Resolved::address).collect();
+    let mut fetched = fetch(&addresses)?.into_iter();
+
+    let mut checks = Vec::new();
+    for (account, resolved) in accounts.iter().zip(&resolved) {
+        let address = match resolved {
+            Resolved::Omitted => continue,
+            Resolved::Missing => {
+                let detail = if account.pda.is_some() {
+                    "not given, and its seeds aren't resolvable offline (pass --account NAME=PUBKEY)"
+                } else {
+                    "not given (pass --account NAME=PUBKEY)"
+                };
+                checks.push(AccountCheck::new(
+                    account,
+                    None,
+                    "address",
+                    CheckStatus::Fail,
+                    detail,
+                ));
+                continue;
+            }
+            Resolved::Given(address) | Resolved::Fixed(address) | Resolved::Derived(address) => {
+                *address
+            }
+        };
+        let fetched = fetched.next().flatten();
+        check_account(
+            idl,
+            ix,
+            &program_id,
+            account,
+            resolved,
+            &address,
+            fetched.as_ref(),
+            given,
+            &mut checks,
+        );
+    }
+    Ok(checks)
+}
+
+impl Resolved {
+    fn address(&self) -> Option<Pubkey> {
+        match self {
+            Resolved::Given(address) | Resolved::Fixed(address) | Resolved::Derived(address) => {
+                Some(*address)
+            }
+            Resolved::Omitted | Resolved::Missing => None,
+        }
+    }
+}
+
+impl AccountCheck {
+    fn new(
+        account: &IdlAccount,
+        address: Option<&Pubkey>,
+        check: &'static str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+    ) -> Self {
+        AccountCheck {
+            account: account.name.clone(),
+            address: address.map(Pubkey::to_string),
+            check,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+fn resolve(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    account: &IdlAccount,
+    given: &HashMap<String, Pubkey>,
+) -> Resolved {
+    if let Some(address) = given.get(&account.name) {
+        return Resolved::Given(*address);
+    }
+    if let Some(address) = account
+        .address
+        .as_deref()
+        .and_then(|a| Pubkey::from_str(a).ok())
+    {
+        return Resolved::Fixed(address);
+    }
+    if let Some(address) = account
+        .pda
+        .as_ref()
+        .and_then(|pda| derive_pda(idl, ix, pda, given, &Value::Null))
+    {
+        return Resolved::Derived(address);
+    }
+    if account.optional {
+        Resolved::Omitted
+    } else {
+        Resolved::Missing
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_account(
+    idl: &Idl,
+    ix: &IdlInstruction,
+    program_id: &Pubkey,
+    account: &IdlAccount,
+    resolved: &Resolved,
+    address: &Pubkey,
+    fetched: Option<&Account>,
+    given: &HashMap<String, Pubkey>,
+    checks: &mut Vec<AccountCheck>,
+) {
+    let mut push = |check, status, detail: String| {
+        checks.push(AccountCheck::new(
+            account,
+            Some(address),
+            check,
+            status,
+            detail,
+        ))
+    };
+
+    // A given address must be the one the IDL fixes or derives
+    if let Resolved::Given(address) = resolved {
+        if let Some(fixed) = account.address.as_deref() {
+            if fixed != address.to_string() {
+                push(
+                    "address",
+                    CheckStatus::Fail,
+                    format!("the IDL fixes this account to {}", fixed),
+                );
+            }
+        }
+        if let Some(pda) = &account.pda {
+            match derive_pda(idl, ix, pda, given, &Value::Null) {
+                Some(derived) if derived == *address => {
+                    push("pda", CheckStatus::Pass, "matches its seeds".to_string())
+                }
+                Some(derived) => push(
+                    "pda",
+                    CheckStatus::Fail,
+                    format!("its seeds derive {}", derived),
+                ),
+                None => push(
+                    "pda",
+                    CheckStatus::Warn,
+                    "seeds not resolvable offline (argument or account data)".to_string(),
+                ),
+            }
+        }
+    }
+
+    if account.signer && !address.is_on_curve() {
+        push(
+            "signer",
+            CheckStatus::Fail,
+            "must sign, but is off the curve (a PDA has no private key)".to_string(),
+        );
+    }
+
+    let Some(fetched) = fetched else {
+        // Writable accounts may be created by the instruction itself
+        let status = if account.writable {
+            CheckStatus::Warn
+        } else {
+            CheckStatus::Fail
+        };
+        push(
+            "exists",
+            status,
+            "not found (fine only if the instruction creates it)".to_string(),
+        );
+        return;
+    };
+    push(
+        "exists",
+        CheckStatus::Pass,
+        format!(
+            "{} lamports, {} bytes",
+            fetched.lamports,
+            fetched.data.len()
+        ),
+    );
+
+    if account.writable && fetched.executable {
+        push(
+            "writable",
+            CheckStatus::Fail,
+            "must be writable, but is an executable program".to_string(),
+        );
+    }
+
+    // Accounts named after an IDL account type hold that type, owned by the program
+    let expected = idl
+        .accounts
+        .iter()
+        .find(|def| same_name(&def.name, &account.name));
+    if fetched.owner != *program_id {
+        if expected.is_some() {
+            push(
+                "owner",
+                CheckStatus::Fail,
+                format!("owned by {}, expected {}", fetched.owner, program_id),
+            );
+        }
+        return;
+    }
+    push("owner", CheckStatus::Pass, program_id.to_string());
+
+    let actual = fetched
+        .data
+        .get(..DISCRIMINATOR_LEN)
+        .and_then(|discriminator| idl.find_account_by_discriminator(discriminator));
+    match (expected, actual) {
+        (Some(expected), Some(actual)) if expected.name != actual.name => push(
+            "type",
+            CheckStatus::Fail,
+            format!("holds a {}, expected {}", actual.name, expected.name),
+        ),
+        (_, Some(actual)) => push("type", CheckStatus::Pass, actual.name.clone()),
+        (Some(expected), None) => push(
+            "type",
+            CheckStatus::Fail,
+            format!("no {} discriminator", expected.name),
+        ),
+        (None, None) => push(
+            "type",
+            CheckStatus::Warn,
+            "no discriminator of this IDL".to_string(),
+        ),
+    }
+}
+
+/// `pool_state` and `PoolState` name the same thing
+fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| *c != '_')
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+    normalize(a) == normalize(b)
+}
+
+fn flatten<'a>(items: &'a [IdlAccountItem], out: &mut Vec<&'a IdlAccount>) {
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => out.push(account),
+            IdlAccountItem::Group(group) => flatten(&group.accounts, out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+
+    #[test]
+    fn test_preflight_catches_wiring_bugs() {
+        let idl = parse_idl_json(
+            r#"{
+            "address": "11111111111111111111111111111112",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "deposit", "discriminator": [2,0,0,0,0,0,0,0], "args": [], "accounts": [
+                    { "name": "pool", "writable": true },
+                    { "name": "config", "pda": { "seeds": [
+                        { "kind": "const", "value": [99, 111, 110, 102, 105, 103] }
+                    ] } },
+                    { "name": "user", "signer": true },
+                    { "name": "referrer", "optional": true }
+                ] }
+            ],
+            "accounts": [
+                { "name": "Pool", "discriminator": [1,1,1,1,1,1,1,1] },
+                { "name": "Config", "discriminator": [2,2,2,2,2,2,2,2] }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let program_id = Pubkey::from_str(&idl.address).unwrap();
+        let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let pool = Pubkey::new_unique();
+        let (user, _) = Pubkey::find_program_address(&[b"not a wallet"], &program_id);
+
+        let given = HashMap::from([("pool".to_string(), pool), ("user".to_string(), user)]);
+        let checks = preflight(&idl, &idl.instructions[0], &given, |addresses| {
+            assert_eq!(addresses, [pool, config, user]);
+            let account = |discriminator: u8| Account {
+                lamports: 1,
+                data: vec![discriminator; 16],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            };
+            // A Config where the Pool goes
+            Ok(vec![Some(account(2)), Some(account(2)), None])
+        })
+        .unwrap();
+
+        let failed: Vec<(&str, &str)> = checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .map(|c| (c.account.as_str(), c.check))
+            .collect();
+        assert_eq!(
+            failed,
+            [("pool", "type"), ("user", "signer"), ("user", "exists")]
+        );
+        assert!(checks
+            .iter()
+            .any(|c| c.account == "config" && c.check == "type" && c.status == CheckStatus::Pass));
+        assert!(checks.iter().all(|c| c.account != "referrer"));
+
+        let wrong = HashMap::from([("config".to_string(), pool)]);
+        let checks = preflight(&idl, &idl.instructions[0], &wrong, |addresses| {
+            Ok(vec![None; addresses.len()])
+        })
+        .unwrap();
+        assert!(checks
+            .iter()
+            .any(|c| c.account == "config" && c.check == "pda" && c.status == CheckStatus::Fail));
+
+        let unknown = HashMap::from([("vault".to_string(), pool)]);
+        assert!(preflight(&idl, &idl.instructions[0], &unknown, |_| Ok(vec![])).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! Quarantine of account data that failed to decode
+//!
+//! When an IDL lags behind a program upgrade, decoding the upgraded
+//! accounts fails. Instead of losing the data with the error, `decode-account`
+//! keeps the raw bytes and the error under
+//! ~/.config/periscope/quarantine/, one JSON file per payload.
+//! `periscope quarantine retry` decodes them again once the IDL has caught
+//! up, and drops those that now decode.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::hex;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::hash::hashv;
+use std::fs;
+use std::path::PathBuf;
+
+/// Quarantine directory name, in the config directory
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// A payload that failed to decode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    /// Content hash of the program and data, also the file name
+    pub id: String,
+
+    /// Program whose IDL failed to decode it (base58)
+    pub program_id: String,
+
+    /// Account the data was read from (base58)
+    pub address: String,
+
+    /// Account type it was decoded as (`--type`), when one was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_type: Option<String>,
+
+    /// Raw account data (base64)
+    pub data: String,
+
+    /// Error of the latest attempt
+    pub error: String,
+
+    /// Unix timestamp of the first failure
+    pub quarantined_at: u64,
+
+    /// Decoding attempts so far
+    pub attempts: u32,
+}
+
+impl QuarantineEntry {
+    /// Entry for `data` that failed with `error`
+    pub fn new(
+        program_id: &str,
+        address: &str,
+        account_type: Option<&str>,
+        data: &[u8],
+        error: &PeriscopeError,
+        now: u64,
+    ) -> Self {
+        let hash = hashv(&[program_id.as_bytes(), data]).to_bytes();
+        QuarantineEntry {
+            id: hex(&hash[..8]),
+            program_id: program_id.to_string(),
+            address: address.to_string(),
+            account_type: account_type.map(str::to_string),
+            data: base64::engine::general_purpose::STANDARD.encode(data),
+            error: error.to_string(),
+            quarantined_at: now,
+            attempts: 1,
+        }
+    }
+
+    /// The raw data
+    pub fn bytes(&self) -> PeriscopeResult<Vec<u8>> {
+        base64::engine::general_purpose::STANDARD
+            .decode(&self.data)
+            .map_err(|e| PeriscopeError::CacheError(format!("quarantine {}: {}", self.id, e)))
+    }
+}
+
+/// Result of decoding a quarantined payload again
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryOutcome {
+    pub id: String,
+    pub address: String,
+
+    /// Account type it decoded as
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+
+    /// Decoded fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+
+    /// Why it still fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Quarantined payloads on disk
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    dir: PathBuf,
+}
+
+impl Quarantine {
+    /// The quarantine in the config directory
+    pub fn open() -> PeriscopeResult<Self> {
+        Ok(Self::at(
+            crate::config::Config::dir_path()?.join(QUARANTINE_DIR),
+        ))
+    }
+
+    /// A quarantine rooted at `dir`
+    pub fn at(dir: PathBuf) -> Self {
+        Quarantine { dir }
+    }
+
+    /// Keep an entry; the same payload failing again counts as another
+    /// attempt of the existing entry
+    pub fn add(&self, mut entry: QuarantineEntry) -> PeriscopeResult<PathBuf> {
+        if let Some(existing) = self.get(&entry.id) {
+            entry.quarantined_at = existing.quarantined_at;
+            entry.attempts = existing.attempts + 1;
+        }
+        self.put(&entry)
+    }
+
+    /// Replace an entry as is
+    pub fn put(&self, entry: &QuarantineEntry) -> PeriscopeResult<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&entry.id);
+        fs::write(&path, serde_json::to_string_pretty(entry)?)?;
+        Ok(path)
+    }
+
+    /// The entry with this id
+    pub fn get(&self, id: &str) -> Option<QuarantineEntry> {
+        let contents = fs::read_to_string(self.path(id)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Drop an entry
+    pub fn remove(&self, id: &str) -> PeriscopeResult<()> {
+        match fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every entry, oldest first; unreadable files are skipped
+    pub fn entries(&self) -> PeriscopeResult<Vec<QuarantineEntry>> {
+        let files = match fs::read_dir(&self.dir) {
+            Ok(files) => files,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries: Vec<QuarantineEntry> = files
+            .flatten()
+            .filter_map(|file| fs::read_to_string(file.path()).ok())
+            .filter_map(|contents| serde_json::from_str(&contents).ok())
+            .collect();
+        entries.sort_by(|a, b| (a.quarantined_at, &a.id).cmp(&(b.quarantined_at, &b.id)));
+        Ok(entries)
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_round_trip() {
+        let dir = std::env::temp_dir().join(format!("periscope-quarantine-{}", std::process::id()));
+        let quarantine = Quarantine::at(dir.clone());
+        let error = PeriscopeError::UnknownDiscriminator("0102".into());
+        let entry = QuarantineEntry::new("prog", "acct", None, &[1, 2, 3], &error, 100);
+
+        quarantine.add(entry.clone()).unwrap();
+        quarantine
+            .add(QuarantineEntry::new(
+                "prog",
+                "acct",
+                None,
+                &[1, 2, 3],
+                &error,
+                200,
+            ))
+            .unwrap();
+        quarantine
+            .add(QuarantineEntry::new(
+                "prog",
+                "other",
+                None,
+                &[4],
+                &error,
+                300,
+            ))
+            .unwrap();
+
+        let entries = quarantine.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, entry.id);
+        assert_eq!(entries[0].attempts, 2);
+        assert_eq!(entries[0].quarantined_at, 100);
+        assert_eq!(entries[0].bytes().unwrap(), [1, 2, 3]);
+
+        quarantine.remove(&entry.id).unwrap();
+        assert_eq!(quarantine.entries().unwrap().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
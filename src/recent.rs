@@ -0,0 +1,154 @@
+//! Recently inspected programs
+//!
+//! Every successful on-chain fetch is recorded in
+//! ~/.config/periscope/recent.toml, so `periscope recent` can list them and
+//! commands run without a program ID default to the most recent one.
+
+use crate::config::Config;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Recent programs file name
+const RECENT_FILE: &str = "recent.toml";
+
+/// Number of programs remembered
+pub const RECENT_LIMIT: usize = 20;
+
+/// A recently inspected program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentProgram {
+    /// Program ID (base58)
+    pub program_id: String,
+
+    /// Program name from the IDL
+    pub name: String,
+
+    /// Cluster the IDL was fetched from (`mainnet-beta`, `devnet`, or the RPC host)
+    pub cluster: String,
+
+    /// Unix timestamp of the last fetch
+    pub last_used: u64,
+}
+
+/// Recently inspected programs, most recent first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentPrograms {
+    #[serde(default)]
+    pub programs: Vec<RecentProgram>,
+}
+
+impl RecentPrograms {
+    /// Get the recent programs file path (~/.config/periscope/recent.toml)
+    pub fn file_path() -> PeriscopeResult<PathBuf> {
+        Config::dir_path().map(|p| p.join(RECENT_FILE))
+    }
+
+    /// Load the list, empty if it was never saved
+    pub fn load() -> PeriscopeResult<Self> {
+        let path = Self::file_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path).map_err(PeriscopeError::IoError)?;
+        toml::from_str(&contents).map_err(|e| {
+            PeriscopeError::ConfigError(format!("Failed to parse {}: {}", RECENT_FILE, e))
+        })
+    }
+
+    /// Save the list, creating the config directory if needed
+    pub fn save(&self) -> PeriscopeResult<()> {
+        let path = Self::file_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(PeriscopeError::IoError)?;
+        }
+
+        let contents = toml::to_string_pretty(self).map_err(|e| {
+            PeriscopeError::ConfigError(format!("Failed to serialize {}: {}", RECENT_FILE, e))
+        })?;
+        fs::write(&path, contents).map_err(PeriscopeError::IoError)
+    }
+
+    /// Move a program to the front of the list (adding it if new)
+    pub fn touch(&mut self, program_id: &str, name: &str, rpc_url: &str) {
+        self.programs.retain(|p| p.program_id != program_id);
+        self.programs.insert(
+            0,
+            RecentProgram {
+                program_id: program_id.to_string(),
+                name: name.to_string(),
+                cluster: cluster_name(rpc_url),
+                last_used: now(),
+            },
+        );
+        self.programs.truncate(RECENT_LIMIT);
+    }
+
+    /// The most recently used program
+    pub fn latest(&self) -> Option<&RecentProgram> {
+        self.programs.first()
+    }
+
+    /// Forget all programs
+    pub fn clear(&mut self) {
+        self.programs.clear();
+    }
+}
+
+/// Short cluster name for an RPC URL
+pub fn cluster_name(rpc_url: &str) -> String {
+    let host = rpc_url
+        .split("://")
+        .nth(1)
+        .unwrap_or(rpc_url)
+        .split(['/', '?'])
+        .next()
+        .unwrap_or_default();
+
+    for cluster in ["mainnet-beta", "devnet", "testnet"] {
+        if host.contains(cluster) {
+            return cluster.to_string();
+        }
+    }
+    if host.starts_with("localhost") || host.starts_with("127.0.0.1") {
+        return "localnet".to_string();
+    }
+    host.to_string()
+}
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touch_moves_to_front() {
+        let mut recent = RecentPrograms::default();
+        recent.touch("A", "a", "https://api.devnet.solana.com");
+        recent.touch("B", "b", "http://localhost:8899");
+        recent.touch("A", "a", "https://api.mainnet-beta.solana.com");
+
+        let ids: Vec<&str> = recent
+            .programs
+            .iter()
+            .map(|p| p.program_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["A", "B"]);
+        assert_eq!(recent.latest().unwrap().cluster, "mainnet-beta");
+        assert_eq!(recent.programs[1].cluster, "localnet");
+        assert_eq!(
+            cluster_name("https://rpc.helius.xyz/?api-key=x"),
+            "rpc.helius.xyz"
+        );
+    }
+}
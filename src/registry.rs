@@ -0,0 +1,270 @@
+//! IDLs of many programs, by program ID
+//!
+//! Indexers decode instructions and accounts of dozens of programs. A
+//! [`ProgramRegistry`] holds one [`SharedIdl`] per program, remembers where
+//! each came from so [`ProgramRegistry::refresh`] can load it again after a
+//! program upgrade, and keeps pinned programs on the IDL they have:
+//!
+//! ```ignore
+//! let mut registry = ProgramRegistry::new();
+//! registry.load_file("./target/idl/amm.json")?;
+//! registry.fetch(&client, &jupiter)?;
+//! registry.pin(&jupiter);
+//!
+//! let idl = registry.get(&ix.program_id).ok_or(...)?;
+//! let decoded = decode_instruction(idl, &ix.data)?;
+//! ```
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{parse_idl_json, read_idl_json_from_file, Idl, SharedIdl};
+use solana_sdk::hash::{hashv, Hash};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[cfg(feature = "fetch-rpc")]
+use crate::idl::{fetch_on_chain_idl, DEFAULT_MAX_IDL_SIZE};
+#[cfg(feature = "fetch-rpc")]
+use solana_client::rpc_client::RpcClient;
+
+/// Where a registered IDL was loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlOrigin {
+    /// The program's IDL account
+    OnChain,
+    /// A JSON file, read again on refresh
+    File(PathBuf),
+    /// Handed to [`ProgramRegistry::insert`] or
+    /// [`ProgramRegistry::insert_json`]; never refreshed
+    Inline,
+}
+
+/// A program's entry in the registry
+#[derive(Debug, Clone)]
+pub struct RegisteredProgram {
+    /// The IDL, ready to hand to decoders
+    pub idl: SharedIdl,
+
+    /// Where it was loaded from
+    pub origin: IdlOrigin,
+
+    /// Slot an on-chain IDL was read at
+    pub slot: Option<u64>,
+
+    /// Whether refresh leaves this IDL as it is
+    pub pinned: bool,
+
+    /// Hash of the JSON it was loaded from, to tell versions apart; `None`
+    /// for an [`Idl`] handed to [`ProgramRegistry::insert`]
+    pub hash: Option<Hash>,
+}
+
+impl RegisteredProgram {
+    /// `metadata.version` of the IDL
+    pub fn version(&self) -> &str {
+        &self.idl.metadata.version
+    }
+}
+
+/// IDLs of many programs, looked up by program ID
+#[derive(Debug, Clone, Default)]
+pub struct ProgramRegistry {
+    programs: BTreeMap<Pubkey, RegisteredProgram>,
+}
+
+impl ProgramRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an IDL for `program_id`, replacing any IDL it had. A pinned
+    /// program stays pinned.
+    pub fn insert(&mut self, program_id: Pubkey, idl: Idl) -> &SharedIdl {
+        self.put(program_id, idl, IdlOrigin::Inline, None, None)
+    }
+
+    /// [`ProgramRegistry::insert`], from IDL JSON
+    pub fn insert_json(&mut self, program_id: Pubkey, json: &str) -> PeriscopeResult<&SharedIdl> {
+        self.insert_from(program_id, json, IdlOrigin::Inline, None)
+    }
+
+    /// Register an IDL file under the program ID in its `address`
+    pub fn load_file(&mut self, path: impl Into<PathBuf>) -> PeriscopeResult<Pubkey> {
+        let path = path.into();
+        let json = read_idl_json_from_file(&path.to_string_lossy())?;
+        let idl = parse_idl_json(&json)?;
+        let program_id = Pubkey::from_str(&idl.address)
+            .map_err(|_| PeriscopeError::InvalidProgramId(idl.address.clone()))?;
+        let hash = Some(hashv(&[json.as_bytes()]));
+        self.put(program_id, idl, IdlOrigin::File(path), None, hash);
+        Ok(program_id)
+    }
+
+    /// Fetch and register the IDL in a program's IDL account
+    #[cfg(feature = "fetch-rpc")]
+    pub fn fetch(
+        &mut self,
+        client: &RpcClient,
+        program_id: &Pubkey,
+    ) -> PeriscopeResult<&SharedIdl> {
+        let on_chain = fetch_on_chain_idl(client, program_id, DEFAULT_MAX_IDL_SIZE)?;
+        self.insert_from(
+            *program_id,
+            &on_chain.json,
+            IdlOrigin::OnChain,
+            Some(on_chain.slot),
+        )
+    }
+
+    /// Load every unpinned IDL again from where it came from (inline ones
+    /// are skipped). Returns, per program, whether its IDL changed; a
+    /// program that fails keeps its previous IDL.
+    #[cfg(feature = "fetch-rpc")]
+    pub fn refresh(&mut self, client: &RpcClient) -> Vec<(Pubkey, PeriscopeResult<bool>)> {
+        let stale: Vec<(Pubkey, IdlOrigin)> = self
+            .programs
+            .iter()
+            .filter(|(_, p)| !p.pinned && p.origin != IdlOrigin::Inline)
+            .map(|(id, p)| (*id, p.origin.clone()))
+            .collect();
+        stale
+            .into_iter()
+            .map(|(program_id, origin)| {
+                let before = self.programs[&program_id].hash;
+                let reloaded = match origin {
+                    IdlOrigin::File(path) => self.load_file(path).map(|_| ()),
+                    _ => self.fetch(client, &program_id).map(|_| ()),
+                };
+                let changed = reloaded.map(|()| self.programs[&program_id].hash != before);
+                (program_id, changed)
+            })
+            .collect()
+    }
+
+    /// The IDL of a program
+    pub fn get(&self, program_id: &Pubkey) -> Option<&SharedIdl> {
+        self.programs.get(program_id).map(|p| &p.idl)
+    }
+
+    /// A program's entry, with where its IDL came from
+    pub fn program(&self, program_id: &Pubkey) -> Option<&RegisteredProgram> {
+        self.programs.get(program_id)
+    }
+
+    /// Registered program IDs, in order
+    pub fn program_ids(&self) -> impl Iterator<Item = &Pubkey> {
+        self.programs.keys()
+    }
+
+    /// Drop a program
+    pub fn remove(&mut self, program_id: &Pubkey) -> Option<RegisteredProgram> {
+        self.programs.remove(program_id)
+    }
+
+    /// Keep a program on its current IDL version through refreshes; false
+    /// if it isn't registered
+    pub fn pin(&mut self, program_id: &Pubkey) -> bool {
+        self.set_pinned(program_id, true)
+    }
+
+    /// Let refresh update a pinned program again
+    pub fn unpin(&mut self, program_id: &Pubkey) -> bool {
+        self.set_pinned(program_id, false)
+    }
+
+    /// Number of registered programs
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Whether no program is registered
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    fn set_pinned(&mut self, program_id: &Pubkey, pinned: bool) -> bool {
+        match self.programs.get_mut(program_id) {
+            Some(program) => {
+                program.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert_from(
+        &mut self,
+        program_id: Pubkey,
+        json: &str,
+        origin: IdlOrigin,
+        slot: Option<u64>,
+    ) -> PeriscopeResult<&SharedIdl> {
+        let idl = parse_idl_json(json)?;
+        let hash = Some(hashv(&[json.as_bytes()]));
+        Ok(self.put(program_id, idl, origin, slot, hash))
+    }
+
+    fn put(
+        &mut self,
+        program_id: Pubkey,
+        idl: Idl,
+        origin: IdlOrigin,
+        slot: Option<u64>,
+        hash: Option<Hash>,
+    ) -> &SharedIdl {
+        let pinned = self.programs.get(&program_id).is_some_and(|p| p.pinned);
+        let program = RegisteredProgram {
+            idl: SharedIdl::new(idl),
+            origin,
+            slot,
+            pinned,
+            hash,
+        };
+        self.programs.insert(program_id, program);
+        &self.programs[&program_id].idl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMM: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [{ "name": "swap", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [], "args": [] }]
+    }"#;
+
+    #[test]
+    fn test_registry_lookup_and_pinning() {
+        let dir = std::env::temp_dir().join(format!("periscope-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("amm.json");
+        std::fs::write(&path, AMM).unwrap();
+
+        let mut registry = ProgramRegistry::new();
+        let amm = registry.load_file(&path).unwrap();
+        assert_eq!(amm, Pubkey::default());
+        assert_eq!(registry.get(&amm).unwrap().instructions[0].name, "swap");
+        assert_eq!(
+            registry.program(&amm).unwrap().origin,
+            IdlOrigin::File(path)
+        );
+        assert!(registry.pin(&amm));
+
+        let other = Pubkey::new_unique();
+        registry
+            .insert_json(other, &AMM.replace("0.1.0\", \"spec", "0.2.0\", \"spec"))
+            .unwrap();
+        assert_eq!(registry.program(&other).unwrap().version(), "0.2.0");
+        assert!(!registry.program(&other).unwrap().pinned);
+
+        registry.insert_json(amm, AMM).unwrap();
+        assert!(registry.program(&amm).unwrap().pinned);
+        assert_eq!(registry.len(), 2);
+        assert!(registry.get(&Pubkey::new_unique()).is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,440 @@
+//! Interactive shell over a single loaded IDL
+//!
+//! The IDL is fetched and parsed once, then queried with short commands
+//! (`ix swap`, `type Pool`, `search fee`, `decode <hex>`). Input has tab
+//! completion for commands and IDL names, and history persisted to
+//! `~/.config/periscope/repl_history`. When stdin is not a terminal, commands
+//! are read line by line so the shell can be scripted.
+
+use crate::config::Config;
+use crate::decode::{decode_account, decode_instruction};
+use crate::display::{
+    display_decoded_account, display_decoded_instruction, display_error, display_errors_list,
+    display_idl_overview, display_instruction_detail, display_instruction_not_found,
+    display_name_list, display_search_results, display_type_detail, group_label, print_header,
+};
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::groups::{group_by_prefix, name_prefix};
+use crate::i18n::{tr, trf, Msg};
+use crate::idl::{hex, Idl, DISCRIMINATOR_LEN};
+use crate::search::search_idl;
+use crate::theme::{paint, Element};
+use inquire::autocompletion::{Autocomplete, Replacement};
+use inquire::{CustomUserError, InquireError, Text};
+use std::fs;
+use std::io::{BufRead, IsTerminal};
+use std::path::PathBuf;
+
+/// History file name, in the config directory
+const HISTORY_FILE: &str = "repl_history";
+
+/// Maximum number of history entries kept on disk
+const HISTORY_LIMIT: usize = 1000;
+
+/// Maximum number of suggestions shown below the prompt
+const MAX_SUGGESTIONS: usize = 8;
+
+/// REPL commands: name, argument placeholder, help text
+const COMMANDS: &[(&str, &str, Msg)] = &[
+    ("info", "", Msg::HelpInfo),
+    ("ixs", "[prefix]", Msg::HelpInstructions),
+    ("groups", "", Msg::HelpGroups),
+    ("ix", "<name>", Msg::HelpInstruction),
+    ("types", "", Msg::HelpTypes),
+    ("type", "<name>", Msg::HelpType),
+    ("accounts", "", Msg::HelpAccounts),
+    ("events", "", Msg::HelpEvents),
+    ("errors", "", Msg::HelpErrors),
+    ("search", "<text>", Msg::HelpSearch),
+    ("decode", "<hex>", Msg::HelpDecode),
+    ("history", "", Msg::HelpHistory),
+    ("exit", "", Msg::HelpExit),
+];
+
+/// Whether the loop should keep reading commands
+enum Flow {
+    Continue,
+    Exit,
+}
+
+/// Interactive session over a loaded IDL
+pub struct Repl {
+    idl: Idl,
+    history: Vec<String>,
+}
+
+impl Repl {
+    /// Create a session, loading saved history
+    pub fn new(idl: Idl) -> Self {
+        Self {
+            idl,
+            history: load_history(),
+        }
+    }
+
+    /// Run until `exit`, Ctrl-C/Ctrl-D or end of input
+    pub fn run(&mut self) -> PeriscopeResult<()> {
+        let interactive = std::io::stdin().is_terminal();
+        if interactive {
+            println!(
+                "{}",
+                paint(
+                    &trf(
+                        Msg::ReplLoaded,
+                        &[&self.idl.metadata.name, &self.idl.metadata.version]
+                    ),
+                    Element::Muted
+                )
+            );
+            self.run_interactive()?;
+            save_history(&self.history);
+        } else {
+            for line in std::io::stdin().lock().lines() {
+                let line = line.map_err(PeriscopeError::IoError)?;
+                if let Flow::Exit = self.execute(&line) {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn run_interactive(&mut self) -> PeriscopeResult<()> {
+        let prompt = format!("{}>", self.idl.metadata.name);
+        loop {
+            let completer = Completer::new(&self.idl, &self.history);
+            let line = match Text::new(&prompt)
+                .with_autocomplete(completer)
+                .with_page_size(MAX_SUGGESTIONS)
+                .prompt()
+            {
+                Ok(line) => line,
+                Err(InquireError::OperationCanceled) => continue,
+                Err(InquireError::OperationInterrupted) => return Ok(()),
+                Err(e) => return Err(PeriscopeError::IoError(std::io::Error::other(e))),
+            };
+
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && self.history.last().map(String::as_str) != Some(trimmed) {
+                self.history.push(trimmed.to_string());
+            }
+            if let Flow::Exit = self.execute(trimmed) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Run a single command line
+    fn execute(&self, line: &str) -> Flow {
+        let line = line.trim();
+        let (command, arg) = match line.split_once(char::is_whitespace) {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (line, ""),
+        };
+
+        let idl = &self.idl;
+        match command {
+            "" => {}
+            "help" | "?" => display_help(),
+            "info" | "inspect" => display_idl_overview(idl, None, 0),
+            "ixs" | "instructions" => {
+                // `ixs admin` expands the `admin_*` group
+                let prefix = arg.trim_end_matches(['*', '_']);
+                let names: Vec<&str> = idl
+                    .instructions
+                    .iter()
+                    .map(|i| i.name.as_str())
+                    .filter(|name| prefix.is_empty() || name_prefix(name) == Some(prefix))
+                    .collect();
+                display_name_list(
+                    &trf(Msg::InstructionsHeader, &[&idl.metadata.name, &names.len()]),
+                    &names,
+                );
+            }
+            "groups" => {
+                let names: Vec<&str> = idl.instructions.iter().map(|i| i.name.as_str()).collect();
+                let groups = group_by_prefix(&names);
+                print_header(&trf(
+                    Msg::InstructionsHeader,
+                    &[&idl.metadata.name, &names.len()],
+                ));
+                for group in &groups {
+                    println!(
+                        "  {} {}",
+                        paint(&format!("{:<16}", group_label(group)), Element::Name),
+                        paint(&format!("({})", group.names.len()), Element::Muted)
+                    );
+                }
+                println!();
+            }
+            "ix" | "instruction" => {
+                if let Some(name) = required(command, arg) {
+                    match idl.find_instruction(name) {
+                        Some(ix) => display_instruction_detail(ix, false),
+                        None => {
+                            let available: Vec<&str> =
+                                idl.instructions.iter().map(|ix| ix.name.as_str()).collect();
+                            display_instruction_not_found(name, &available);
+                        }
+                    }
+                }
+            }
+            "types" => {
+                let names: Vec<&str> = idl.types.iter().map(|t| t.name.as_str()).collect();
+                display_name_list(&trf(Msg::TypesCount, &[&names.len()]), &names);
+            }
+            "type" => {
+                if let Some(name) = required(command, arg) {
+                    match idl.find_type_ignore_case(name) {
+                        Some(ty) => display_type_detail(ty),
+                        None => display_error(&trf(Msg::TypeNotFound, &[&name])),
+                    }
+                }
+            }
+            "accounts" => {
+                let names: Vec<&str> = idl.accounts.iter().map(|a| a.name.as_str()).collect();
+                display_name_list(&trf(Msg::AccountTypesCount, &[&names.len()]), &names);
+            }
+            "events" => {
+                let names: Vec<&str> = idl.events.iter().map(|e| e.name.as_str()).collect();
+                display_name_list(&trf(Msg::EventsCount, &[&names.len()]), &names);
+            }
+            "errors" => display_errors_list(&idl.metadata.name, &idl.errors, None),
+            "search" => {
+                if let Some(query) = required(command, arg) {
+                    display_search_results(query, &search_idl(idl, query));
+                }
+            }
+            "decode" => {
+                if let Some(hex) = required(command, arg) {
+                    self.decode(hex);
+                }
+            }
+            "history" => {
+                print_header(tr(Msg::ReplHistory));
+                let start = self.history.len().saturating_sub(20);
+                for (i, entry) in self.history.iter().enumerate().skip(start) {
+                    println!(
+                        "  {}  {}",
+                        paint(&format!("{:>4}", i + 1), Element::Label),
+                        entry
+                    );
+                }
+                println!();
+            }
+            "exit" | "quit" | "q" => return Flow::Exit,
+            other => display_error(&trf(Msg::ReplUnknownCommand, &[&other])),
+        }
+        Flow::Continue
+    }
+
+    /// Decode hex data as an instruction, falling back to an account
+    fn decode(&self, input: &str) {
+        let data = match parse_hex(input) {
+            Ok(data) => data,
+            Err(e) => return display_error(&trf(Msg::ReplInvalidHex, &[&e])),
+        };
+
+        match decode_instruction(&self.idl, &data) {
+            Ok(decoded) => return display_decoded_instruction(&decoded),
+            Err(PeriscopeError::UnknownDiscriminator(_)) => {}
+            Err(e) => return display_error(&e.to_string()),
+        }
+
+        match decode_account(&self.idl, &data) {
+            Ok(decoded) => display_decoded_account("-", &decoded),
+            Err(PeriscopeError::UnknownDiscriminator(_)) => {
+                let prefix = &data[..data.len().min(DISCRIMINATOR_LEN)];
+                display_error(&trf(Msg::ReplUndecodable, &[&hex(prefix)]));
+            }
+            Err(e) => display_error(&e.to_string()),
+        }
+    }
+}
+
+/// Return the argument, or print usage when it's missing
+fn required<'a>(command: &str, arg: &'a str) -> Option<&'a str> {
+    if arg.is_empty() {
+        let placeholder = COMMANDS
+            .iter()
+            .find(|(name, _, _)| *name == command)
+            .map_or("", |(_, placeholder, _)| placeholder);
+        display_error(&trf(
+            Msg::ReplUsage,
+            &[&format!("{} {}", command, placeholder)],
+        ));
+        None
+    } else {
+        Some(arg)
+    }
+}
+
+fn display_help() {
+    print_header(tr(Msg::ReplCommands));
+    for (name, placeholder, help) in COMMANDS {
+        println!(
+            "  {} {}  {}",
+            paint(&format!("{:<8}", name), Element::Name),
+            paint(&format!("{:<8}", placeholder), Element::Field),
+            paint(tr(*help), Element::Muted)
+        );
+    }
+    println!();
+}
+
+/// Parse hex data, allowing a `0x` prefix and whitespace between bytes
+fn parse_hex(input: &str) -> Result<Vec<u8>, String> {
+    let digits: String = input
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of hex digits".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("'{}' is not a hex byte", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+fn history_path() -> Option<PathBuf> {
+    Config::dir_path().ok().map(|p| p.join(HISTORY_FILE))
+}
+
+fn load_history() -> Vec<String> {
+    history_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|contents| contents.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Best effort: history is a convenience, failing to save it is not an error
+fn save_history(history: &[String]) {
+    let Some(path) = history_path() else {
+        return;
+    };
+    let start = history.len().saturating_sub(HISTORY_LIMIT);
+    let mut contents = history[start..].join("\n");
+    contents.push('\n');
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, contents);
+}
+
+/// Completes command names, IDL names after `ix`/`type`, instruction
+/// prefixes after `ixs`, and history entries
+#[derive(Clone)]
+struct Completer {
+    instructions: Vec<String>,
+    prefixes: Vec<String>,
+    types: Vec<String>,
+    history: Vec<String>,
+}
+
+impl Completer {
+    fn new(idl: &Idl, history: &[String]) -> Self {
+        let names: Vec<&str> = idl.instructions.iter().map(|i| i.name.as_str()).collect();
+        Self {
+            instructions: idl.instructions.iter().map(|i| i.name.clone()).collect(),
+            prefixes: group_by_prefix(&names)
+                .iter()
+                .filter_map(|g| g.prefix.map(str::to_string))
+                .collect(),
+            types: idl.types.iter().map(|t| t.name.clone()).collect(),
+            history: history.iter().rev().cloned().collect(),
+        }
+    }
+
+    fn candidates(&self, input: &str) -> Vec<String> {
+        let mut out: Vec<String> = match input.split_once(' ') {
+            None => COMMANDS
+                .iter()
+                .map(|(name, _, _)| name.to_string())
+                .filter(|name| name.starts_with(input) && !input.is_empty())
+                .collect(),
+            Some((command, partial)) => {
+                let names = match command {
+                    "ix" | "instruction" => &self.instructions,
+                    "ixs" | "instructions" => &self.prefixes,
+                    "type" => &self.types,
+                    _ => return self.history_matches(input),
+                };
+                let partial = partial.to_lowercase();
+                names
+                    .iter()
+                    .filter(|name| name.to_lowercase().starts_with(&partial))
+                    .map(|name| format!("{} {}", command, name))
+                    .collect()
+            }
+        };
+        for entry in self.history_matches(input) {
+            if !out.contains(&entry) {
+                out.push(entry);
+            }
+        }
+        out.truncate(MAX_SUGGESTIONS);
+        out
+    }
+
+    fn history_matches(&self, input: &str) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for entry in self.history.iter().filter(|e| e.starts_with(input)) {
+            if !out.contains(entry) {
+                out.push(entry.clone());
+            }
+            if out.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+        out
+    }
+}
+
+impl Autocomplete for Completer {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        Ok(self.candidates(input))
+    }
+
+    /// Tab accepts the highlighted suggestion, or extends the input to the
+    /// longest prefix shared by all suggestions
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        if highlighted.is_some() {
+            return Ok(highlighted);
+        }
+        let candidates = self.candidates(input);
+        let Some(first) = candidates.first() else {
+            return Ok(None);
+        };
+        let common = candidates.iter().skip(1).fold(first.len(), |len, c| {
+            first
+                .bytes()
+                .zip(c.bytes())
+                .take(len)
+                .take_while(|(a, b)| a == b)
+                .count()
+        });
+        Ok((common > input.len()).then(|| first[..common].to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("0x01ff").unwrap(), vec![0x01, 0xff]);
+        assert_eq!(parse_hex("01 02\n03").unwrap(), vec![1, 2, 3]);
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+}
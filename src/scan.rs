@@ -0,0 +1,298 @@
+//! Protocol-level statistics over all accounts of a type
+//!
+//! `periscope scan` fetches every account of one IDL account type with
+//! `getProgramAccounts` and summarizes them: how many there are, the
+//! lamports they hold, how their sizes are distributed, and min / max /
+//! mean of chosen numeric fields (`--stats amount,fee`), without exporting
+//! them to a notebook first. `--export` writes the decoded accounts
+//! themselves as a flat table.
+
+use crate::decode::decode_account_as;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::export::{Table, VecStrategy};
+use crate::i18n::{trf, Msg};
+use crate::idl::Idl;
+use crate::snapshot::{program_accounts, Fixture};
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+/// Most size buckets shown; more distinct sizes are grouped into ranges
+pub const MAX_SIZE_BUCKETS: usize = 10;
+
+/// Accounts whose data length falls in `min..=max`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeBucket {
+    pub min: usize,
+    pub max: usize,
+    pub count: usize,
+}
+
+/// Summary of one numeric field (as `f64`, so values past 2^53 are rounded)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldStats {
+    /// Field path (`config.fee` for nested structs)
+    pub field: String,
+
+    /// Accounts the field was read from
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// Summary of the accounts of one type
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanStats {
+    pub account_type: String,
+    pub count: usize,
+
+    /// Lamports held by all of them
+    pub lamports: u64,
+    pub sizes: Vec<SizeBucket>,
+    pub fields: Vec<FieldStats>,
+
+    /// Accounts that matched the discriminator but failed to decode
+    pub undecoded: usize,
+}
+
+/// Every account of `account_type` owned by `program_id`
+pub fn scan(
+    client: &RpcClient,
+    idl: &Idl,
+    program_id: &Pubkey,
+    account_type: &str,
+) -> PeriscopeResult<Vec<Fixture>> {
+    let account = idl
+        .accounts
+        .iter()
+        .find(|a| a.name == account_type)
+        .ok_or_else(|| PeriscopeError::NotFound(trf(Msg::AccountTypeNotFound, &[&account_type])))?;
+    program_accounts(client, program_id, &account.discriminator_bytes())
+}
+
+/// Summarize `accounts` of `account_type`, with stats of the numeric
+/// `fields`
+pub fn scan_stats(
+    idl: &Idl,
+    account_type: &str,
+    accounts: &[Fixture],
+    fields: &[String],
+) -> PeriscopeResult<ScanStats> {
+    let mut sizes = Vec::with_capacity(accounts.len());
+    let mut values: Vec<Vec<f64>> = vec![Vec::new(); fields.len()];
+    let mut undecoded = 0;
+    for fixture in accounts {
+        let data = fixture.data()?;
+        sizes.push(data.len());
+        if fields.is_empty() {
+            continue;
+        }
+        match decode_account_as(idl, account_type, &data) {
+            Ok(decoded) => {
+                for (field, values) in fields.iter().zip(&mut values) {
+                    values.extend(number_at(&decoded.value, field));
+                }
+            }
+            Err(_) => undecoded += 1,
+        }
+    }
+
+    let decoded = accounts.len() - undecoded;
+    let fields = fields
+        .iter()
+        .zip(values)
+        .map(|(field, values)| {
+            if values.is_empty() && decoded > 0 {
+                return Err(PeriscopeError::Usage(trf(
+                    Msg::ScanFieldNotNumeric,
+                    &[field, &account_type],
+                )));
+            }
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let mean = values.iter().sum::<f64>() / values.len().max(1) as f64;
+            Ok(FieldStats {
+                field: field.clone(),
+                count: values.len(),
+                min: if values.is_empty() { 0.0 } else { min },
+                max: if values.is_empty() { 0.0 } else { max },
+                mean,
+            })
+        })
+        .collect::<PeriscopeResult<Vec<_>>>()?;
+
+    Ok(ScanStats {
+        account_type: account_type.to_string(),
+        count: accounts.len(),
+        lamports: accounts.iter().map(|f| f.account.lamports).sum(),
+        sizes: size_histogram(&sizes),
+        fields,
+        undecoded,
+    })
+}
+
+/// The decoded `accounts` as a table: address and lamports, then the
+/// fields flattened with `strategy`. Accounts that fail to decode are left
+/// out.
+pub fn scan_table(
+    idl: &Idl,
+    account_type: &str,
+    accounts: &[Fixture],
+    strategy: VecStrategy,
+) -> PeriscopeResult<Table> {
+    let mut table = Table::with_columns(&["address", "lamports"]);
+    for fixture in accounts {
+        if let Ok(decoded) = decode_account_as(idl, account_type, &fixture.data()?) {
+            let leading = [fixture.pubkey.clone(), fixture.account.lamports.to_string()];
+            table.push(&leading, &decoded.value, strategy);
+        }
+    }
+    Ok(table)
+}
+
+/// Count of each size, or of equal ranges of sizes when there are more
+/// than [`MAX_SIZE_BUCKETS`] distinct ones
+pub fn size_histogram(sizes: &[usize]) -> Vec<SizeBucket> {
+    let mut distinct: Vec<usize> = sizes.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let (Some(&min), Some(&max)) = (distinct.first(), distinct.last()) else {
+        return Vec::new();
+    };
+
+    let mut buckets: Vec<SizeBucket> = if distinct.len() <= MAX_SIZE_BUCKETS {
+        distinct
+            .iter()
+            .map(|&size| SizeBucket {
+                min: size,
+                max: size,
+                count: 0,
+            })
+            .collect()
+    } else {
+        let width = (max - min) / MAX_SIZE_BUCKETS + 1;
+        (0..MAX_SIZE_BUCKETS)
+            .map(|i| SizeBucket {
+                min: min + i * width,
+                max: (min + (i + 1) * width - 1).min(max),
+                count: 0,
+            })
+            .filter(|b| b.min <= max)
+            .collect()
+    };
+    for size in sizes {
+        if let Some(bucket) = buckets.iter_mut().find(|b| (b.min..=b.max).contains(size)) {
+            bucket.count += 1;
+        }
+    }
+    buckets
+}
+
+/// The number at a dotted path of a decoded value; 128-bit integers are
+/// decoded as strings
+fn number_at(value: &Value, path: &str) -> Option<f64> {
+    let field = path
+        .split('.')
+        .try_fold(value, |value, key| value.get(key))?;
+    match field {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s
+            .parse::<i128>()
+            .map(|n| n as f64)
+            .or_else(|_| s.parse::<u128>().map(|n| n as f64))
+            .ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+    use crate::snapshot::FixtureAccount;
+    use base64::Engine;
+
+    fn fixture(lamports: u64, data: &[u8]) -> Fixture {
+        Fixture {
+            pubkey: Pubkey::new_unique().to_string(),
+            account: FixtureAccount {
+                lamports,
+                data: (
+                    base64::engine::general_purpose::STANDARD.encode(data),
+                    "base64".to_string(),
+                ),
+                owner: Pubkey::default().to_string(),
+                executable: false,
+                rent_epoch: 0,
+                space: data.len(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_scan_stats() {
+        let idl = parse_idl_json(
+            r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Pool", "discriminator": [1,1,1,1,1,1,1,1] }],
+            "types": [{ "name": "Pool", "type": { "kind": "struct", "fields": [
+                { "name": "fee", "type": "u16" },
+                { "name": "owner", "type": "pubkey" }
+            ] } }]
+        }"#,
+        )
+        .unwrap();
+        let pool = |fee: u16| {
+            let mut data = vec![1; 8];
+            data.extend(fee.to_le_bytes());
+            data.extend([7; 32]);
+            data
+        };
+        let accounts = [
+            fixture(10, &pool(30)),
+            fixture(20, &pool(10)),
+            fixture(5, &[1, 1, 1, 1, 1, 1, 1, 1, 9]),
+        ];
+
+        let stats = scan_stats(&idl, "Pool", &accounts, &["fee".to_string()]).unwrap();
+        assert_eq!((stats.count, stats.lamports, stats.undecoded), (3, 35, 1));
+        assert_eq!(
+            stats.sizes,
+            [
+                SizeBucket {
+                    min: 9,
+                    max: 9,
+                    count: 1
+                },
+                SizeBucket {
+                    min: 42,
+                    max: 42,
+                    count: 2
+                },
+            ]
+        );
+        assert_eq!(
+            (
+                stats.fields[0].min,
+                stats.fields[0].max,
+                stats.fields[0].mean
+            ),
+            (10.0, 30.0, 20.0)
+        );
+        assert!(scan_stats(&idl, "Pool", &accounts, &["owner".to_string()]).is_err());
+
+        let table = scan_table(&idl, "Pool", &accounts, VecStrategy::Join).unwrap();
+        assert_eq!(table.columns, ["address", "lamports", "fee", "owner"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[1][1..3], ["20", "10"]);
+
+        let sizes: Vec<usize> = (0..100).collect();
+        let histogram = size_histogram(&sizes);
+        assert_eq!(histogram.len(), MAX_SIZE_BUCKETS);
+        assert!(histogram.iter().all(|b| b.count == 10));
+    }
+}
@@ -0,0 +1,1054 @@
+//! Versioned JSON output schema
+//!
+//! Everything printed by `--output json` carries a `schema_version`. Within a
+//! version, fields are only ever added; renaming, removing or retyping a
+//! field bumps [`SCHEMA_VERSION`]. `periscope schema <command>` prints the
+//! JSON Schema of a command's output so consumers can validate and pin it.
+
+use serde_json::{json, Map, Value};
+
+/// Version of the JSON output format
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Commands with a documented JSON output (`error` is the stderr error report)
+pub const SCHEMA_COMMANDS: &[&str] = &[
+    "inspect",
+    "instructions",
+    "instruction",
+    "template",
+    "build-ix",
+    "send-ix",
+    "simulate",
+    "errors",
+    "decode-account",
+    "explain",
+    "account-diff",
+    "anchor-version",
+    "matrix",
+    "uses",
+    "lint",
+    "pdas",
+    "preflight",
+    "overlap",
+    "grep",
+    "bundle",
+    "sign",
+    "verify",
+    "xcheck",
+    "snapshot",
+    "scan",
+    "mock",
+    "whois",
+    "rust-names",
+    "codegen",
+    "compat",
+    "cache",
+    "quarantine",
+    "recent",
+    "doctor",
+    "error",
+];
+
+/// Add `schema_version` to a command result
+///
+/// Objects get the field at the top level; lists are wrapped in an object
+/// under `key`, since a bare array has nowhere to carry it.
+pub fn versioned(value: Value, key: &str) -> Value {
+    let mut object = match value {
+        Value::Object(object) => object,
+        other => {
+            let mut object = Map::new();
+            object.insert(key.to_string(), other);
+            object
+        }
+    };
+    object.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+    Value::Object(object)
+}
+
+/// JSON Schema of a command's JSON output, `None` for unknown commands
+pub fn output_schema(command: &str) -> Option<Value> {
+    let body = match command {
+        "inspect" => inspect(),
+        "instructions" => paged(
+            "instructions",
+            json!({
+                "type": "object",
+                "required": ["name", "discriminator"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "discriminator": { "$ref": "#/$defs/bytes" },
+                    "group": {
+                        "type": ["string", "null"],
+                        "description": "Name prefix with --group-by prefix (null: ungrouped)"
+                    }
+                }
+            }),
+        ),
+        "instruction" => json!({ "allOf": [{ "$ref": "#/$defs/instruction" }] }),
+        "template" => json!({
+            "required": ["instruction", "accounts", "args", "comments"],
+            "properties": {
+                "instruction": { "type": "string" },
+                "accounts": { "type": "object" },
+                "args": { "type": "object" },
+                "comments": { "type": "object", "additionalProperties": { "type": "string" } }
+            }
+        }),
+        "build-ix" => json!({
+            "required": ["program_id", "instruction", "accounts", "data"],
+            "properties": {
+                "program_id": { "type": "string" },
+                "instruction": { "type": "string" },
+                "accounts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "pubkey", "writable", "signer"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "pubkey": { "type": "string" },
+                            "writable": { "type": "boolean" },
+                            "signer": { "type": "boolean" }
+                        }
+                    }
+                },
+                "data": { "type": "string", "description": "Instruction data as hex" },
+                "encoded": {
+                    "type": "object",
+                    "description": "Present with --format",
+                    "required": ["format", "payer", "blockhash", "nonce_account", "data"],
+                    "properties": {
+                        "format": { "enum": ["squads", "message"] },
+                        "payer": { "type": "string" },
+                        "blockhash": { "type": "string" },
+                        "nonce_account": { "type": ["string", "null"] },
+                        "data": {
+                            "type": "string",
+                            "description": "base58 unsigned transaction (squads) or base64 message"
+                        }
+                    }
+                }
+            }
+        }),
+        "send-ix" => json!({
+            "required": ["signature", "logs", "events"],
+            "properties": {
+                "signature": { "type": "string" },
+                "logs": { "type": "array", "items": { "type": "string" } },
+                "events": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "value"],
+                        "properties": { "name": { "type": "string" }, "value": {} }
+                    }
+                }
+            }
+        }),
+        "simulate" => json!({
+            "description": "A single simulation; with --sweep, `arg` and `points` instead",
+            "properties": {
+                "error": { "type": ["string", "null"] },
+                "units_consumed": { "type": ["integer", "null"], "minimum": 0 },
+                "frames": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["program", "depth", "consumed", "own"],
+                        "properties": {
+                            "program": { "type": "string" },
+                            "depth": { "type": "integer", "minimum": 1 },
+                            "consumed": { "type": "integer", "minimum": 0 },
+                            "own": { "type": "integer", "minimum": 0 }
+                        }
+                    }
+                },
+                "events": { "type": "array", "items": { "type": "object" } },
+                "logs": { "type": "array", "items": { "type": "string" } },
+                "arg": { "type": "string" },
+                "points": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["value", "units_consumed", "error"],
+                        "properties": {
+                            "value": {},
+                            "units_consumed": { "type": ["integer", "null"] },
+                            "error": { "type": ["string", "null"] }
+                        }
+                    }
+                }
+            }
+        }),
+        "errors" => paged("errors", json!({ "$ref": "#/$defs/error" })),
+        "decode-account" => decode_account(),
+        "whois" => whois(),
+        "codegen" => json!({
+            "description": "`tests` gives the framework, `action` the instruction and descriptor",
+            "required": ["path", "code"],
+            "properties": {
+                "framework": { "enum": ["mocha", "litesvm"] },
+                "instruction": { "type": "string" },
+                "descriptor": {
+                    "type": "object",
+                    "description": "Solana Actions GET response (ActionGetResponse)"
+                },
+                "path": { "type": ["string", "null"] },
+                "code": { "type": "string", "description": "Generated code, or the descriptor with --descriptor" }
+            }
+        }),
+        "compat" => json!({
+            "required": ["old_version", "new_version", "instructions", "code"],
+            "properties": {
+                "old_version": { "type": "string" },
+                "new_version": { "type": "string" },
+                "instructions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["old", "new", "change", "args", "accounts"],
+                        "properties": {
+                            "old": { "type": ["string", "null"] },
+                            "new": { "type": ["string", "null"] },
+                            "change": { "$ref": "#/$defs/change" },
+                            "args": { "type": "array", "items": { "$ref": "#/$defs/item" } },
+                            "accounts": { "type": "array", "items": { "$ref": "#/$defs/item" } }
+                        }
+                    }
+                },
+                "code": { "type": ["string", "null"], "description": "Rust adapter, with --rust" }
+            },
+            "$defs": {
+                "change": {
+                    "enum": ["unchanged", "renamed", "retyped", "changed", "added", "removed"]
+                },
+                "item": {
+                    "type": "object",
+                    "required": ["old", "new", "change", "note"],
+                    "properties": {
+                        "old": { "type": ["string", "null"] },
+                        "new": { "type": ["string", "null"] },
+                        "change": { "$ref": "#/$defs/change" },
+                        "note": { "type": ["string", "null"] }
+                    }
+                }
+            }
+        }),
+        "explain" => list(
+            "messages",
+            json!({
+                "type": "object",
+                "required": ["version", "fee_payer", "signers", "recent_blockhash", "instructions"],
+                "properties": {
+                    "version": { "enum": ["legacy", "v0"] },
+                    "fee_payer": { "type": "string" },
+                    "signers": { "type": "array", "items": { "type": "string" } },
+                    "recent_blockhash": { "type": "string" },
+                    "instructions": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["program_id", "accounts", "data"],
+                            "properties": {
+                                "program_id": { "type": "string" },
+                                "program": { "type": "string", "description": "IDL or well-known program name" },
+                                "instruction": { "type": "string", "description": "Present when it decoded" },
+                                "args": { "type": "object" },
+                                "accounts": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "required": ["address", "signer", "writable"],
+                                        "properties": {
+                                            "name": { "type": "string" },
+                                            "address": { "type": "string", "description": "`<table>[index]` when the lookup table couldn't be read" },
+                                            "signer": { "type": "boolean" },
+                                            "writable": { "type": "boolean" }
+                                        }
+                                    }
+                                },
+                                "data": { "type": "string", "description": "Instruction data (hex)" },
+                                "error": { "type": "string", "description": "Why it wasn't decoded" }
+                            }
+                        }
+                    }
+                }
+            }),
+        ),
+        "account-diff" => json!({
+            "required": ["address", "type", "before", "after", "changes"],
+            "properties": {
+                "address": { "type": "string" },
+                "type": { "type": "string" },
+                "before": { "type": "string", "description": "Fixture file of the earlier state" },
+                "after": { "type": "string", "description": "Fixture file of the later state, or the live account" },
+                "changes": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["path", "before", "after"],
+                        "properties": {
+                            "path": { "type": "string" },
+                            "before": {},
+                            "after": {}
+                        }
+                    }
+                }
+            }
+        }),
+        "grep" => json!({
+            "required": ["pattern", "searched", "programs"],
+            "properties": {
+                "pattern": { "type": "string" },
+                "searched": { "type": "integer", "description": "Cached IDLs searched, of every cluster" },
+                "programs": {
+                    "type": "array",
+                    "description": "Cached programs with matches, by name then cluster",
+                    "items": {
+                        "type": "object",
+                        "required": ["program_id", "cluster", "name", "hits"],
+                        "properties": {
+                            "program_id": { "type": "string" },
+                            "cluster": { "type": "string" },
+                            "name": { "type": "string" },
+                            "hits": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["kind", "path", "detail"],
+                                    "properties": {
+                                        "kind": {
+                                            "enum": ["instruction", "account", "arg", "type", "field", "variant", "event", "error"]
+                                        },
+                                        "path": {
+                                            "type": "string",
+                                            "description": "Location, e.g. swap.amount_in or Pool.fees"
+                                        },
+                                        "detail": {
+                                            "type": "string",
+                                            "description": "Type or error message, may be empty"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+        "overlap" => json!({
+            "required": ["programs", "overlaps"],
+            "properties": {
+                "programs": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "address"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "address": { "type": "string" }
+                        }
+                    }
+                },
+                "overlaps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["kind", "key", "occurrences"],
+                        "properties": {
+                            "kind": {
+                                "enum": ["error-code", "error-name", "instruction", "account", "event"]
+                            },
+                            "key": {
+                                "type": "string",
+                                "description": "Error code, error name or discriminator (hex)"
+                            },
+                            "occurrences": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["program", "address", "name"],
+                                    "properties": {
+                                        "program": { "type": "string" },
+                                        "address": { "type": "string" },
+                                        "name": { "type": "string" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+        "cache" => json!({
+            "required": ["path", "entries"],
+            "properties": {
+                "path": { "type": "string", "description": "Archive file" },
+                "entries": { "type": "integer", "description": "Entries in the archive" },
+                "imported": {
+                    "type": "integer",
+                    "description": "Entries written by `cache import`; newer local entries are kept"
+                }
+            }
+        }),
+        "quarantine" => json!({
+            "description": "`list` gives entries, `retry` outcomes, `clear` removed",
+            "properties": {
+                "entries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "program_id", "address", "data", "error", "quarantined_at", "attempts"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "program_id": { "type": "string" },
+                            "address": { "type": "string" },
+                            "account_type": { "type": "string" },
+                            "data": { "type": "string", "description": "Raw account data (base64)" },
+                            "error": { "type": "string", "description": "Error of the latest attempt" },
+                            "quarantined_at": { "type": "integer" },
+                            "attempts": { "type": "integer" }
+                        }
+                    }
+                },
+                "outcomes": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "address"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "address": { "type": "string" },
+                            "account": { "type": "string", "description": "Present when it decoded" },
+                            "value": { "type": "object" },
+                            "error": { "type": "string", "description": "Present when it still fails" }
+                        }
+                    }
+                },
+                "removed": { "type": "integer" }
+            }
+        }),
+        "sign" => json!({
+            "required": ["format", "version", "algorithm", "signer", "program", "canonical_version", "canonical_hash", "signature", "signed_at"],
+            "description": "The signature file written",
+            "properties": {
+                "format": { "const": "periscope-idl-signature" },
+                "version": { "type": "integer" },
+                "algorithm": { "const": "ed25519" },
+                "signer": { "type": "string" },
+                "program": { "type": "string" },
+                "canonical_version": { "type": "integer", "description": "Version of the canonical form the hash was computed under" },
+                "canonical_hash": { "type": "string" },
+                "signature": { "type": "string", "description": "base58" },
+                "signed_at": { "type": "integer", "description": "Unix time" }
+            }
+        }),
+        "verify" => json!({
+            "required": ["valid", "signer", "program", "canonical_version", "canonical_hash", "signed_at"],
+            "description": "Only printed for a valid signature; an invalid one is a PSC-SIG-401 error",
+            "properties": {
+                "valid": { "const": true },
+                "signer": { "type": "string" },
+                "program": { "type": "string" },
+                "canonical_version": { "type": "integer" },
+                "canonical_hash": { "type": "string" },
+                "signed_at": { "type": "integer" }
+            }
+        }),
+        "bundle" => json!({
+            "required": ["path", "version", "created_at", "programs"],
+            "properties": {
+                "path": { "type": "string" },
+                "version": { "type": "integer", "description": "Bundle layout version" },
+                "created_at": { "type": "integer", "description": "Unix time" },
+                "programs": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["address", "name", "source"],
+                        "properties": {
+                            "address": { "type": "string" },
+                            "name": { "type": "string" },
+                            "source": {
+                                "type": "string",
+                                "description": "Program ID (on-chain), file or URL the IDL came from"
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+        "matrix" => json!({
+            "required": ["instructions", "accounts"],
+            "properties": {
+                "instructions": { "type": "array", "items": { "type": "string" } },
+                "accounts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "roles"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "roles": {
+                                "type": "object",
+                                "description": "Instruction name -> R, W, S or WS (? when optional)",
+                                "additionalProperties": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+        "uses" => paged(
+            "uses",
+            json!({
+                "type": "object",
+                "required": ["kind", "name", "field", "type"],
+                "properties": {
+                    "kind": { "enum": ["instruction", "account", "event", "type"] },
+                    "name": { "type": "string", "description": "Instruction or type name" },
+                    "field": {
+                        "type": "string",
+                        "description": "Argument or field (Variant.field / Variant.0 in enums)"
+                    },
+                    "type": { "type": "string", "description": "Type of the field as written" }
+                }
+            }),
+        ),
+        "lint" => list(
+            "findings",
+            json!({
+                "type": "object",
+                "required": ["rule", "name", "detail"],
+                "properties": {
+                    "rule": { "enum": ["dead-type"] },
+                    "name": { "type": "string", "description": "Definition the finding concerns" },
+                    "detail": { "type": "string" }
+                }
+            }),
+        ),
+        "pdas" => paged(
+            "pdas",
+            json!({
+                "type": "object",
+                "required": ["seeds", "names", "uses"],
+                "properties": {
+                    "seeds": {
+                        "type": "array",
+                        "description": "\"text\", base58 or 0x-hex constants (byte arrays with --raw-seeds), account:<path>, arg:<path>",
+                        "items": { "type": "string" }
+                    },
+                    "names": { "type": "array", "items": { "type": "string" } },
+                    "uses": {
+                        "type": "array",
+                        "description": "instruction.account of every use",
+                        "items": { "type": "string" }
+                    },
+                    "address": {
+                        "type": "object",
+                        "description": "Present when every seed is a constant",
+                        "required": ["address", "bump"],
+                        "properties": {
+                            "address": { "type": "string" },
+                            "bump": { "type": "integer" }
+                        }
+                    }
+                }
+            }),
+        ),
+        "preflight" => list(
+            "checks",
+            json!({
+                "type": "object",
+                "required": ["account", "check", "status", "detail"],
+                "properties": {
+                    "account": { "type": "string" },
+                    "address": { "type": "string" },
+                    "check": {
+                        "enum": ["address", "exists", "owner", "type", "signer", "writable", "pda"]
+                    },
+                    "status": { "enum": ["pass", "warn", "fail"] },
+                    "detail": { "type": "string" }
+                }
+            }),
+        ),
+        "anchor-version" => json!({
+            "required": ["range", "binary_version", "evidence", "features", "clients"],
+            "properties": {
+                "range": {
+                    "type": "object",
+                    "required": ["min", "below"],
+                    "properties": {
+                        "min": { "type": ["string", "null"] },
+                        "below": { "type": ["string", "null"] }
+                    }
+                },
+                "binary_version": { "type": ["string", "null"] },
+                "evidence": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["clue", "implies"],
+                        "properties": {
+                            "clue": { "type": "string" },
+                            "implies": { "type": "string" }
+                        }
+                    }
+                },
+                "features": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "used", "detail"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "used": { "type": "boolean" },
+                            "detail": { "type": ["string", "null"] }
+                        }
+                    }
+                },
+                "clients": { "type": "string" }
+            }
+        }),
+        "xcheck" => json!({
+            "required": ["clusters", "mismatches"],
+            "properties": {
+                "clusters": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["cluster", "version", "error"],
+                        "properties": {
+                            "cluster": { "type": "string" },
+                            "version": { "type": ["string", "null"] },
+                            "error": { "type": ["string", "null"] }
+                        }
+                    }
+                },
+                "mismatches": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["kind", "name", "present_on", "missing_on", "differs"],
+                        "properties": {
+                            "kind": { "enum": ["instruction", "account", "type", "event", "error"] },
+                            "name": { "type": "string" },
+                            "present_on": { "type": "array", "items": { "type": "string" } },
+                            "missing_on": { "type": "array", "items": { "type": "string" } },
+                            "differs": { "type": "boolean" }
+                        }
+                    }
+                }
+            }
+        }),
+        "scan" => json!({
+            "required": ["account_type", "count", "lamports", "sizes", "fields", "undecoded"],
+            "properties": {
+                "account_type": { "type": "string" },
+                "count": { "type": "integer" },
+                "lamports": { "type": "integer", "description": "Held by all scanned accounts" },
+                "sizes": {
+                    "type": "array",
+                    "description": "Accounts per data length, or per range of lengths",
+                    "items": {
+                        "type": "object",
+                        "required": ["min", "max", "count"],
+                        "properties": {
+                            "min": { "type": "integer" },
+                            "max": { "type": "integer" },
+                            "count": { "type": "integer" }
+                        }
+                    }
+                },
+                "fields": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["field", "count", "min", "max", "mean"],
+                        "properties": {
+                            "field": { "type": "string" },
+                            "count": { "type": "integer" },
+                            "min": { "type": "number" },
+                            "max": { "type": "number" },
+                            "mean": { "type": "number" }
+                        }
+                    }
+                },
+                "undecoded": { "type": "integer", "description": "Accounts left out of the field stats" }
+            }
+        }),
+        "snapshot" => json!({
+            "required": ["dir", "fixtures"],
+            "properties": {
+                "dir": { "type": "string" },
+                "fixtures": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["kind", "pubkey", "path"],
+                        "properties": {
+                            "kind": { "enum": ["program", "program-data", "idl", "account"] },
+                            "name": { "type": "string", "description": "Account type, for kind account" },
+                            "pubkey": { "type": "string" },
+                            "path": { "type": "string" }
+                        }
+                    }
+                }
+            }
+        }),
+        "mock" => json!({
+            "required": ["type", "seed", "value", "data"],
+            "properties": {
+                "type": { "type": "string" },
+                "seed": { "type": "integer" },
+                "value": {},
+                "data": { "type": "string", "description": "Borsh bytes of value (hex)" }
+            }
+        }),
+        "rust-names" => list(
+            "renames",
+            json!({
+                "type": "object",
+                "required": ["scope", "from", "to", "reason"],
+                "properties": {
+                    "scope": { "type": "string" },
+                    "from": { "type": "string" },
+                    "to": { "type": "string" },
+                    "reason": { "enum": ["keyword", "leading-digit", "invalid-chars", "duplicate"] }
+                }
+            }),
+        ),
+        "recent" => list(
+            "programs",
+            json!({
+                "type": "object",
+                "required": ["program_id", "name", "cluster", "last_used"],
+                "properties": {
+                    "program_id": { "type": "string" },
+                    "name": { "type": "string" },
+                    "cluster": { "type": "string" },
+                    "last_used": { "type": "integer", "description": "Unix timestamp" }
+                }
+            }),
+        ),
+        "doctor" => list(
+            "checks",
+            json!({
+                "type": "object",
+                "required": ["name", "status", "detail"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "status": { "enum": ["pass", "warn", "fail"] },
+                    "detail": { "type": "string" }
+                }
+            }),
+        ),
+        "error" => json!({
+            "type": "object",
+            "required": ["code", "message", "retryable"],
+            "properties": {
+                "code": { "type": "string", "pattern": "^PSC-[A-Z]+-[0-9X]{3}$" },
+                "message": { "type": "string" },
+                "hint": { "type": "string" },
+                "retryable": { "type": "boolean" }
+            }
+        }),
+        _ => return None,
+    };
+
+    let mut schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!(
+            "https://github.com/Mctursh/Periscope/schema/v{}/{}.json",
+            SCHEMA_VERSION, command
+        ),
+        "title": format!("periscope {} output", command),
+        "type": "object",
+        "required": ["schema_version"],
+        "properties": {
+            "schema_version": { "const": SCHEMA_VERSION }
+        },
+        "$defs": defs(),
+    });
+    merge(&mut schema, body);
+    Some(schema)
+}
+
+/// Object holding a list under `key`
+fn list(key: &str, item: Value) -> Value {
+    json!({
+        "required": [key],
+        "properties": { key: { "type": "array", "items": item } }
+    })
+}
+
+/// A list command's output that `--page` / the `per_page` config key can cut
+fn paged(key: &str, item: Value) -> Value {
+    let mut schema = list(key, item);
+    schema["properties"]["page"] = json!({
+        "type": "object",
+        "description": "Present when the list was paged",
+        "required": ["page", "per_page", "pages", "total"],
+        "properties": {
+            "page": { "type": "integer", "minimum": 1 },
+            "per_page": { "type": "integer", "minimum": 1 },
+            "pages": { "type": "integer", "minimum": 1 },
+            "total": { "type": "integer", "minimum": 0 }
+        }
+    });
+    schema
+}
+
+fn inspect() -> Value {
+    json!({
+        "required": ["address", "metadata", "instructions"],
+        "properties": {
+            "address": { "type": "string" },
+            "canonical_hash": {
+                "type": "string",
+                "description": "SHA-256 of the canonical form version and the IDL with sorted keys and no whitespace (hex)"
+            },
+            "cluster": {
+                "type": "string",
+                "description": "Cluster an on-chain IDL was read from (mainnet-beta, devnet, testnet, localnet or the RPC host)"
+            },
+            "slot": {
+                "type": ["integer", "null"],
+                "description": "Slot the on-chain IDL account was read at (null for cache entries predating slots)"
+            },
+            "fetched_at": {
+                "type": "integer",
+                "description": "Unix time the on-chain IDL was read (earlier than now when served from the cache)"
+            },
+            "changes_since_cached": {
+                "type": "object",
+                "description": "How a freshly fetched IDL differs from the cached copy it replaced (absent when nothing changed)",
+                "required": ["fetched_at", "kinds"],
+                "properties": {
+                    "slot": { "type": ["integer", "null"] },
+                    "fetched_at": { "type": "integer" },
+                    "kinds": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["kind", "added", "removed", "changed"],
+                            "properties": {
+                                "kind": { "enum": ["instruction", "account", "type", "event", "error"] },
+                                "added": { "type": "integer" },
+                                "removed": { "type": "integer" },
+                                "changed": { "type": "integer" }
+                            }
+                        }
+                    }
+                }
+            },
+            "metadata": {
+                "type": "object",
+                "required": ["name", "version", "spec"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "version": { "type": "string" },
+                    "spec": { "type": "string" },
+                    "description": { "type": ["string", "null"] }
+                }
+            },
+            "instructions": { "type": "array", "items": { "$ref": "#/$defs/instruction" } },
+            "accounts": { "type": "array", "items": { "$ref": "#/$defs/discriminated" } },
+            "types": { "type": "array", "items": { "$ref": "#/$defs/typeDef" } },
+            "events": { "type": "array", "items": { "$ref": "#/$defs/discriminated" } },
+            "errors": { "type": "array", "items": { "$ref": "#/$defs/error" } },
+            "warnings": { "type": "array", "items": { "type": "object" } }
+        }
+    })
+}
+
+fn decode_account() -> Value {
+    json!({
+        "required": ["address", "name", "layout", "value", "consumed", "data_len", "spans"],
+        "properties": {
+            "address": { "type": "string" },
+            "name": { "type": "string" },
+            "layout": { "enum": ["borsh", "zero-copy", "native"] },
+            "value": { "description": "Decoded fields; 128-bit and larger integers are strings" },
+            "consumed": { "type": "integer", "minimum": 0 },
+            "data_len": { "type": "integer", "minimum": 0 },
+            "spans": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["path", "ty", "start", "end"],
+                    "properties": {
+                        "path": { "type": "string" },
+                        "ty": { "type": "string" },
+                        "start": { "type": "integer", "minimum": 0 },
+                        "end": { "type": "integer", "minimum": 0 }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn whois() -> Value {
+    json!({
+        "required": ["address", "kind", "on_curve", "owner", "lamports", "data_len", "rent_exempt", "details", "explorers"],
+        "properties": {
+            "address": { "type": "string" },
+            "kind": { "type": "string" },
+            "on_curve": { "type": "boolean" },
+            "owner": { "type": ["string", "null"] },
+            "lamports": { "type": "integer", "minimum": 0 },
+            "data_len": { "type": "integer", "minimum": 0 },
+            "rent_exempt": { "type": ["boolean", "null"] },
+            "details": { "type": "object", "additionalProperties": { "type": "string" } },
+            "explorers": {
+                "type": "object",
+                "description": "Page of the address on each configured explorer, by explorer name",
+                "additionalProperties": { "type": "string" }
+            }
+        }
+    })
+}
+
+/// Definitions shared by several commands
+fn defs() -> Value {
+    json!({
+        "bytes": { "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } },
+        "type": {
+            "description": "Primitive name (\"u64\", \"pubkey\", ...) or a complex type",
+            "anyOf": [
+                { "type": "string" },
+                { "type": "object", "required": ["vec"], "properties": { "vec": { "$ref": "#/$defs/type" } } },
+                { "type": "object", "required": ["option"], "properties": { "option": { "$ref": "#/$defs/type" } } },
+                { "type": "object", "required": ["array"], "properties": { "array": { "type": "array", "prefixItems": [{ "$ref": "#/$defs/type" }, { "type": "integer" }] } } },
+                { "type": "object", "required": ["defined"], "properties": { "defined": { "type": "object", "required": ["name"], "properties": { "name": { "type": "string" } } } } }
+            ]
+        },
+        "field": {
+            "type": "object",
+            "required": ["name", "type"],
+            "properties": { "name": { "type": "string" }, "type": { "$ref": "#/$defs/type" } }
+        },
+        "accountItem": {
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "writable": { "type": "boolean" },
+                "signer": { "type": "boolean" },
+                "optional": { "type": "boolean" },
+                "address": { "type": ["string", "null"] },
+                "pda": { "type": ["object", "null"] },
+                "accounts": { "type": "array", "items": { "$ref": "#/$defs/accountItem" } }
+            }
+        },
+        "instruction": {
+            "type": "object",
+            "required": ["name", "discriminator", "accounts", "args"],
+            "properties": {
+                "name": { "type": "string" },
+                "discriminator": { "$ref": "#/$defs/bytes" },
+                "accounts": { "type": "array", "items": { "$ref": "#/$defs/accountItem" } },
+                "args": { "type": "array", "items": { "$ref": "#/$defs/field" } }
+            }
+        },
+        "discriminated": {
+            "type": "object",
+            "required": ["name", "discriminator"],
+            "properties": { "name": { "type": "string" }, "discriminator": { "$ref": "#/$defs/bytes" } }
+        },
+        "typeDef": {
+            "type": "object",
+            "required": ["name", "type"],
+            "properties": {
+                "name": { "type": "string" },
+                "generics": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["kind", "name"],
+                        "properties": {
+                            "kind": { "enum": ["type", "const"] },
+                            "name": { "type": "string" },
+                            "type": { "type": "string" }
+                        }
+                    }
+                },
+                "serialization": {},
+                "repr": {
+                    "type": "object",
+                    "required": ["kind"],
+                    "properties": {
+                        "kind": { "enum": ["rust", "c", "transparent"] },
+                        "packed": { "type": "boolean" },
+                        "align": { "type": "integer", "minimum": 1 }
+                    }
+                },
+                "type": {
+                    "type": "object",
+                    "required": ["kind"],
+                    "properties": { "kind": { "enum": ["struct", "enum"] } }
+                }
+            }
+        },
+        "error": {
+            "type": "object",
+            "required": ["code", "name"],
+            "properties": {
+                "code": { "type": "integer", "minimum": 0 },
+                "name": { "type": "string" },
+                "msg": { "type": ["string", "null"] }
+            }
+        }
+    })
+}
+
+/// Merge a command's `required`, `properties` and other keywords into the envelope
+fn merge(schema: &mut Value, body: Value) {
+    let Value::Object(body) = body else { return };
+    for (key, value) in body {
+        match (key.as_str(), &value) {
+            ("required", Value::Array(items)) => {
+                if let Some(Value::Array(required)) = schema.get_mut("required") {
+                    required.extend(items.iter().cloned());
+                }
+            }
+            ("properties", Value::Object(props)) => {
+                if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+                    properties.extend(props.clone());
+                }
+            }
+            _ => {
+                schema[key] = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_output() {
+        let list = versioned(json!([{ "code": 6000 }]), "errors");
+        assert_eq!(list["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(list["errors"][0]["code"], json!(6000));
+
+        let object = versioned(json!({ "name": "init" }), "instruction");
+        assert_eq!(object["name"], json!("init"));
+        assert!(object.get("instruction").is_none());
+    }
+
+    #[test]
+    fn test_every_command_has_schema() {
+        for command in SCHEMA_COMMANDS {
+            let schema = output_schema(command).unwrap();
+            let required = schema["required"].as_array().unwrap();
+            assert_eq!(required[0], json!("schema_version"), "{}", command);
+            assert!(schema["$id"].as_str().unwrap().ends_with(".json"));
+        }
+        assert!(output_schema("repl").is_none());
+    }
+}
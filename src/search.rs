@@ -0,0 +1,223 @@
+//! Free-text search over an IDL
+//!
+//! Matches a case-insensitive substring against every name in the IDL:
+//! instructions, their accounts and arguments, types and their fields or
+//! variants, events and errors (names and messages).
+
+use crate::idl::{format_type, Idl, IdlAccountItem, IdlEnumFields, IdlTypeDefTy};
+use std::fmt;
+
+/// What a search hit refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    Instruction,
+    Account,
+    Argument,
+    Type,
+    Field,
+    Variant,
+    Event,
+    Error,
+}
+
+impl fmt::Display for SearchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SearchKind::Instruction => "instruction",
+            SearchKind::Account => "account",
+            SearchKind::Argument => "arg",
+            SearchKind::Type => "type",
+            SearchKind::Field => "field",
+            SearchKind::Variant => "variant",
+            SearchKind::Event => "event",
+            SearchKind::Error => "error",
+        };
+        f.pad(s)
+    }
+}
+
+/// A single search match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// What matched
+    pub kind: SearchKind,
+
+    /// Location, e.g. `swap.amount_in` or `Pool.fees`
+    pub path: String,
+
+    /// Extra context (type, error message), may be empty
+    pub detail: String,
+}
+
+/// Hits in one program's IDL, when searching many (`periscope grep`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramHits {
+    /// Program ID (base58)
+    pub program_id: String,
+
+    /// Cluster the IDL was fetched from
+    pub cluster: String,
+
+    /// Program name, from the IDL metadata
+    pub name: String,
+
+    /// Matches, in IDL order
+    pub hits: Vec<SearchHit>,
+}
+
+/// Search every name in the IDL for `query` (case-insensitive substring).
+pub fn search_idl(idl: &Idl, query: &str) -> Vec<SearchHit> {
+    let query = query.to_lowercase();
+    search_idl_with(idl, |s| s.to_lowercase().contains(&query))
+}
+
+/// Search every name in the IDL with a custom matcher (a regex, an exact
+/// name); error messages are matched too.
+pub fn search_idl_with(idl: &Idl, matches: impl Fn(&str) -> bool) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+    let mut push = |kind, path: String, detail: String| {
+        hits.push(SearchHit { kind, path, detail });
+    };
+
+    for ix in &idl.instructions {
+        if matches(&ix.name) {
+            push(SearchKind::Instruction, ix.name.clone(), String::new());
+        }
+        for name in account_names(&ix.accounts) {
+            if matches(&name) {
+                push(
+                    SearchKind::Account,
+                    format!("{}.{}", ix.name, name),
+                    String::new(),
+                );
+            }
+        }
+        for arg in &ix.args {
+            if matches(&arg.name) {
+                push(
+                    SearchKind::Argument,
+                    format!("{}.{}", ix.name, arg.name),
+                    format_type(&arg.ty),
+                );
+            }
+        }
+    }
+
+    for ty in &idl.types {
+        if matches(&ty.name) {
+            push(SearchKind::Type, ty.name.clone(), String::new());
+        }
+        match &ty.ty {
+            IdlTypeDefTy::Struct { fields } => {
+                for field in fields {
+                    if matches(&field.name) {
+                        push(
+                            SearchKind::Field,
+                            format!("{}.{}", ty.name, field.name),
+                            format_type(&field.ty),
+                        );
+                    }
+                }
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                for variant in variants {
+                    if matches(&variant.name) {
+                        push(
+                            SearchKind::Variant,
+                            format!("{}::{}", ty.name, variant.name),
+                            String::new(),
+                        );
+                    }
+                    if let Some(IdlEnumFields::Named(fields)) = &variant.fields {
+                        for field in fields.iter().filter(|f| matches(&f.name)) {
+                            push(
+                                SearchKind::Field,
+                                format!("{}::{}.{}", ty.name, variant.name, field.name),
+                                format_type(&field.ty),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for event in &idl.events {
+        if matches(&event.name) {
+            push(SearchKind::Event, event.name.clone(), String::new());
+        }
+    }
+
+    for error in &idl.errors {
+        let msg = error.msg.as_deref().unwrap_or_default();
+        if matches(&error.name) || matches(msg) {
+            push(
+                SearchKind::Error,
+                format!("{} {}", error.code, error.name),
+                msg.to_string(),
+            );
+        }
+    }
+
+    hits
+}
+
+/// Flattened account names, groups as `group.account`
+fn account_names(items: &[IdlAccountItem]) -> Vec<String> {
+    let mut names = Vec::new();
+    for item in items {
+        match item {
+            IdlAccountItem::Single(account) => names.push(account.name.clone()),
+            IdlAccountItem::Group(group) => names.extend(
+                account_names(&group.accounts)
+                    .into_iter()
+                    .map(|name| format!("{}.{}", group.name, name)),
+            ),
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_search_idl() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "set_fee",
+                "accounts": [{ "name": "fee_authority", "signer": true }],
+                "args": [{ "name": "new_fee", "type": "u16" }]
+            }],
+            "types": [{
+                "name": "Pool",
+                "type": { "kind": "struct", "fields": [{ "name": "fee_bps", "type": "u16" }] }
+            }],
+            "errors": [{ "code": 6000, "name": "Slippage", "msg": "Fee too high" }]
+        }))
+        .unwrap();
+
+        let hits = search_idl(&idl, "FEE");
+        let kinds: Vec<SearchKind> = hits.iter().map(|h| h.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SearchKind::Instruction,
+                SearchKind::Account,
+                SearchKind::Argument,
+                SearchKind::Field,
+                SearchKind::Error,
+            ]
+        );
+        assert_eq!(hits[3].path, "Pool.fee_bps");
+        assert!(search_idl(&idl, "nothing").is_empty());
+
+        let exact = search_idl_with(&idl, |name| name == "set_fee");
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].kind, SearchKind::Instruction);
+    }
+}
@@ -0,0 +1,225 @@
+//! Sign, send and confirm built instructions
+//!
+//! `periscope send-ix` signs a [`BuiltInstruction`] with one keypair, which
+//! also pays the fee, and sends it with preflight simulation. Confirmation is
+//! polled at `confirmed` commitment. On success the program logs are fetched
+//! and Anchor events (`Program data:` lines) decoded with the IDL; on
+//! failure, custom program error codes are mapped to the IDL's error names.
+//!
+//! This is the only module that signs or broadcasts transactions, and it only
+//! exists with the `send` feature, which doesn't need `cli`. Builds that do
+//! include it can still refuse to send at runtime: every send takes
+//! [`SendOptions`], and those can't be built without saying whether sending
+//! is read-only (the CLI passes `--read-only` or the `read_only` config key).
+
+use crate::build_ix::{BuiltInstruction, MessageOptions};
+pub use crate::cluster::MAINNET_GENESIS_HASH;
+use crate::decode::DecodedEvent;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::i18n::{tr, trf, Msg};
+use crate::idl::Idl;
+pub use crate::simulate::{decode_log_events, describe_error};
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// How long to wait for confirmation (a blockhash expires after ~60-90s)
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Delay between signature status polls
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Spinner frames shown on stderr while waiting
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How [`send_instruction`] may send; built with [`SendOptions::new`], so
+/// there is no default that silently allows sending
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SendOptions {
+    /// Refuse to send: [`send_instruction`] fails with
+    /// [`PeriscopeError::ReadOnly`] before loading a blockhash or signing
+    pub read_only: bool,
+}
+
+impl SendOptions {
+    /// Options for a caller that has looked up whether it is read-only
+    pub fn new(read_only: bool) -> Self {
+        SendOptions { read_only }
+    }
+
+    /// Fail with [`PeriscopeError::ReadOnly`] when sending is refused, so
+    /// callers can stop before preparing a transaction
+    pub fn check(&self) -> PeriscopeResult<()> {
+        if self.read_only {
+            return Err(PeriscopeError::ReadOnly(tr(Msg::ReadOnlySend).to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// A confirmed transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct SendOutcome {
+    /// Transaction signature (base58)
+    pub signature: String,
+
+    /// Program log lines
+    pub logs: Vec<String>,
+
+    /// Events decoded from the logs
+    pub events: Vec<DecodedEvent>,
+}
+
+/// Whether the RPC endpoint serves mainnet-beta, by genesis hash
+pub fn is_mainnet(client: &RpcClient) -> PeriscopeResult<bool> {
+    Ok(client.get_genesis_hash()?.to_string() == MAINNET_GENESIS_HASH)
+}
+
+/// Sign `built` with `signer`, send it and wait for confirmation
+///
+/// `signer` pays the fee and must be the only signer the instruction needs.
+pub fn send_instruction(
+    client: &RpcClient,
+    idl: &Idl,
+    built: &BuiltInstruction,
+    signer: &Keypair,
+    options: &SendOptions,
+) -> PeriscopeResult<SendOutcome> {
+    options.check()?;
+    let payer = signer.pubkey();
+    if let Some(other) = built
+        .accounts
+        .iter()
+        .find(|a| a.signer && a.pubkey != payer.to_string())
+    {
+        return Err(PeriscopeError::Usage(trf(
+            Msg::SendExtraSigner,
+            &[&other.name, &other.pubkey],
+        )));
+    }
+
+    let blockhash = client.get_latest_blockhash()?;
+    let options = MessageOptions {
+        blockhash: Some(blockhash),
+        nonce: None,
+    };
+    let message = built.to_message(&payer, &options)?;
+    let transaction = Transaction::new(&[signer], message, blockhash);
+
+    let signature =
+        client
+            .send_transaction(&transaction)
+            .map_err(|e| match e.get_transaction_error() {
+                Some(err) => PeriscopeError::TransactionFailed(describe_error(idl, &err)),
+                None => e.into(),
+            })?;
+    wait_for_confirmation(client, idl, &signature)?;
+
+    let logs = fetch_logs(client, &signature).unwrap_or_default();
+    let events = decode_log_events(idl, &logs);
+    Ok(SendOutcome {
+        signature: signature.to_string(),
+        logs,
+        events,
+    })
+}
+
+/// Poll the signature status, with a spinner when stderr is a terminal
+fn wait_for_confirmation(
+    client: &RpcClient,
+    idl: &Idl,
+    signature: &Signature,
+) -> PeriscopeResult<()> {
+    let spinner = std::io::stderr().is_terminal();
+    let started = Instant::now();
+    let mut frame = 0;
+    let result = loop {
+        let status = client
+            .get_signature_status_with_commitment(signature, CommitmentConfig::confirmed())?;
+        match status {
+            Some(Ok(())) => break Ok(()),
+            Some(Err(err)) => {
+                break Err(PeriscopeError::TransactionFailed(describe_error(idl, &err)))
+            }
+            None if started.elapsed() >= CONFIRM_TIMEOUT => {
+                break Err(PeriscopeError::TransactionFailed(trf(
+                    Msg::SendNotConfirmed,
+                    &[&signature, &CONFIRM_TIMEOUT.as_secs()],
+                )))
+            }
+            None => {}
+        }
+        if spinner {
+            eprint!(
+                "\r{} {}",
+                SPINNER[frame % SPINNER.len()],
+                trf(Msg::SendConfirming, &[&signature])
+            );
+            let _ = std::io::stderr().flush();
+            frame += 1;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    if spinner && frame > 0 {
+        eprint!("\r\x1b[2K");
+    }
+    result
+}
+
+/// Log messages of a confirmed transaction
+fn fetch_logs(client: &RpcClient, signature: &Signature) -> PeriscopeResult<Vec<String>> {
+    let response: Value = client.send(
+        RpcRequest::GetTransaction,
+        json!([signature.to_string(), {
+            "encoding": "json",
+            "commitment": "confirmed",
+            "maxSupportedTransactionVersion": 0
+        }]),
+    )?;
+    Ok(response["meta"]["logMessages"]
+        .as_array()
+        .map(|lines| {
+            lines
+                .iter()
+                .filter_map(|l| l.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_refuses_before_rpc() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": []
+        }))
+        .unwrap();
+        let built = BuiltInstruction {
+            program_id: idl.address.clone(),
+            instruction: "noop".to_string(),
+            accounts: vec![],
+            data: String::new(),
+        };
+        // Nothing listens on port 1, so reaching the RPC would be a different error
+        let client = RpcClient::new("http://127.0.0.1:1".to_string());
+
+        let options = SendOptions::new(true);
+        assert!(matches!(
+            send_instruction(&client, &idl, &built, &Keypair::new(), &options),
+            Err(PeriscopeError::ReadOnly(_))
+        ));
+        assert!(SendOptions::new(false).check().is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+//! Detached ed25519 signatures of IDLs
+//!
+//! A protocol team signs an exported IDL with a keypair (the IDL authority
+//! or any published key), producing a small JSON signature file; consumers
+//! check it against the expected signer before trusting the IDL for
+//! codegen or decoding. The signature covers [`Idl::canonical_hash`], so
+//! reformatting the IDL file does not invalidate it. The version of the
+//! canonical form is recorded and signed too: a signature made under another
+//! version is reported as such, so the IDL can be signed again.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{Idl, CANONICAL_VERSION};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::str::FromStr;
+
+/// `format` field of every signature file
+pub const SIGNATURE_FORMAT: &str = "periscope-idl-signature";
+
+/// Version of the signature layout this crate writes and reads
+pub const SIGNATURE_VERSION: u32 = 2;
+
+/// A detached signature of an IDL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdlSignature {
+    /// Always [`SIGNATURE_FORMAT`]
+    pub format: String,
+
+    /// Layout version, see [`SIGNATURE_VERSION`]
+    pub version: u32,
+
+    /// Always `ed25519`
+    pub algorithm: String,
+
+    /// Signer public key (base58)
+    pub signer: String,
+
+    /// Program address of the signed IDL
+    pub program: String,
+
+    /// Version of the canonical form the hash was computed under, see
+    /// [`CANONICAL_VERSION`] (0 in signatures predating it)
+    #[serde(default)]
+    pub canonical_version: u32,
+
+    /// Canonical hash of the signed IDL (hex)
+    pub canonical_hash: String,
+
+    /// Signature of [`signed_message`] (base58)
+    pub signature: String,
+
+    /// Unix time of signing
+    pub signed_at: u64,
+}
+
+/// Bytes actually signed: a domain prefix, the canonical form version and
+/// the canonical hash, so an IDL signature can't be replayed as a
+/// transaction or any other message
+pub fn signed_message(canonical_version: u32, canonical_hash: &str) -> Vec<u8> {
+    format!(
+        "{}:v{}:c{}:{}",
+        SIGNATURE_FORMAT, SIGNATURE_VERSION, canonical_version, canonical_hash
+    )
+    .into_bytes()
+}
+
+/// Sign `idl` with `keypair`
+pub fn sign_idl(idl: &Idl, keypair: &Keypair, signed_at: u64) -> IdlSignature {
+    let canonical_hash = idl.canonical_hash();
+    let signature = keypair.sign_message(&signed_message(CANONICAL_VERSION, &canonical_hash));
+    IdlSignature {
+        format: SIGNATURE_FORMAT.to_string(),
+        version: SIGNATURE_VERSION,
+        algorithm: "ed25519".to_string(),
+        signer: keypair.pubkey().to_string(),
+        program: idl.address.clone(),
+        canonical_version: CANONICAL_VERSION,
+        canonical_hash,
+        signature: signature.to_string(),
+        signed_at,
+    }
+}
+
+impl IdlSignature {
+    /// Check that this signature is `signer`'s over exactly `idl`
+    pub fn verify(&self, idl: &Idl, signer: &Pubkey) -> PeriscopeResult<()> {
+        let invalid = PeriscopeError::SignatureInvalid;
+
+        if self.format != SIGNATURE_FORMAT
+            || self.version > SIGNATURE_VERSION
+            || self.algorithm != "ed25519"
+        {
+            return Err(invalid(format!(
+                "unsupported signature (format '{}', version {}, algorithm {})",
+                self.format, self.version, self.algorithm
+            )));
+        }
+        if self.canonical_version != CANONICAL_VERSION {
+            return Err(invalid(format!(
+                "signed under canonical form v{}, this version of Periscope uses v{}; sign the IDL again",
+                self.canonical_version, CANONICAL_VERSION
+            )));
+        }
+        if self.signer != signer.to_string() {
+            return Err(invalid(format!(
+                "signed by {}, expected {}",
+                self.signer, signer
+            )));
+        }
+        let actual = idl.canonical_hash();
+        if self.canonical_hash != actual {
+            return Err(invalid(format!(
+                "the IDL changed since it was signed (hash {}, signed {})",
+                actual, self.canonical_hash
+            )));
+        }
+        let signature = Signature::from_str(&self.signature)
+            .map_err(|_| invalid("malformed signature".to_string()))?;
+        if !signature.verify(signer.as_ref(), &signed_message(CANONICAL_VERSION, &actual)) {
+            return Err(invalid(
+                "the signature does not match the signer".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let json = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{ "name": "deposit", "accounts": [], "args": [] }]
+        }"#;
+        let idl = parse_idl_json(json).unwrap();
+        let keypair = Keypair::new();
+        let signature = sign_idl(&idl, &keypair, 1_700_000_000);
+
+        // Reformatting keeps the signature valid
+        let minified = parse_idl_json(&serde_json::to_string(&idl).unwrap()).unwrap();
+        assert!(signature.verify(&minified, &keypair.pubkey()).is_ok());
+
+        assert!(signature.verify(&idl, &Pubkey::new_unique()).is_err());
+        let changed = parse_idl_json(&json.replace("deposit", "withdraw")).unwrap();
+        assert!(signature.verify(&changed, &keypair.pubkey()).is_err());
+
+        let mut forged = signature.clone();
+        forged.canonical_hash = changed.canonical_hash();
+        assert!(forged.verify(&changed, &keypair.pubkey()).is_err());
+
+        // A signature from before the canonical form was versioned
+        let mut unversioned = signature.clone();
+        unversioned.canonical_version = 0;
+        let error = unversioned
+            .verify(&idl, &keypair.pubkey())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("sign the IDL again"), "{}", error);
+    }
+}
@@ -0,0 +1,311 @@
+//! Simulate built instructions and profile their compute units
+//!
+//! `periscope simulate` runs a [`BuiltInstruction`] through
+//! `simulateTransaction` without signatures (the RPC node replaces the
+//! blockhash), then breaks the compute units down per program invocation
+//! from the `invoke [n]` / `consumed N of M compute units` log lines.
+//! `--sweep arg=1,10,100` repeats the simulation for each value of one
+//! argument to show how the cost scales.
+
+use crate::build_ix::{BuiltInstruction, MessageOptions};
+use crate::decode::{decode_event, DecodedEvent};
+use crate::error::PeriscopeResult;
+use crate::idl::Idl;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+/// Prefix of the log lines carrying `emit!` event data
+const EVENT_LOG_PREFIX: &str = "Program data: ";
+
+/// Compute units used by one program invocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CuFrame {
+    /// Invoked program (base58)
+    pub program: String,
+
+    /// Invocation depth: 1 for the top-level instruction, 2+ for CPIs
+    pub depth: usize,
+
+    /// Units consumed, including those of nested invocations
+    pub consumed: u64,
+
+    /// Units consumed by this invocation alone
+    pub own: u64,
+}
+
+/// Result of simulating an instruction
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    /// Error, with custom program errors named from the IDL; `None` on success
+    pub error: Option<String>,
+
+    /// Compute units consumed by the whole transaction
+    pub units_consumed: Option<u64>,
+
+    /// Per-invocation breakdown, in the order invocations started
+    pub frames: Vec<CuFrame>,
+
+    /// Events decoded from the logs
+    pub events: Vec<DecodedEvent>,
+
+    /// Program log lines
+    pub logs: Vec<String>,
+}
+
+/// One run of a `--sweep`
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepPoint {
+    /// Argument value simulated
+    pub value: Value,
+
+    /// Compute units consumed
+    pub units_consumed: Option<u64>,
+
+    /// Error of the simulation, if it failed
+    pub error: Option<String>,
+}
+
+/// Simulate `built` with `payer` as fee payer, skipping signature checks
+pub fn simulate_instruction(
+    client: &RpcClient,
+    idl: &Idl,
+    built: &BuiltInstruction,
+    payer: &Pubkey,
+) -> PeriscopeResult<Simulation> {
+    let message = built.to_message(payer, &MessageOptions::default())?;
+    let transaction = Transaction::new_unsigned(message);
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(CommitmentConfig::confirmed()),
+        ..Default::default()
+    };
+    let result = client
+        .simulate_transaction_with_config(&transaction, config)?
+        .value;
+
+    let logs = result.logs.unwrap_or_default();
+    Ok(Simulation {
+        error: result.err.map(|err| describe_error(idl, &err)),
+        units_consumed: result.units_consumed,
+        frames: parse_compute_units(&logs),
+        events: decode_log_events(idl, &logs),
+        logs,
+    })
+}
+
+/// Per-invocation compute units from program logs
+///
+/// Invocations whose `consumed` line is missing (e.g. truncated logs or a
+/// failed CPI) are left out.
+pub fn parse_compute_units(logs: &[String]) -> Vec<CuFrame> {
+    // Open invocations: index into `frames` and units of finished children
+    let mut stack: Vec<(usize, u64)> = Vec::new();
+    let mut frames: Vec<Option<CuFrame>> = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let mut words = rest.split_whitespace();
+        let (Some(program), Some(verb)) = (words.next(), words.next()) else {
+            continue;
+        };
+        match verb {
+            "invoke" => {
+                let depth = words
+                    .next()
+                    .and_then(|d| d.trim_matches(['[', ']']).parse().ok())
+                    .unwrap_or(stack.len() + 1);
+                frames.push(Some(CuFrame {
+                    program: program.to_string(),
+                    depth,
+                    consumed: 0,
+                    own: 0,
+                }));
+                stack.push((frames.len() - 1, 0));
+            }
+            "consumed" => {
+                let Some(consumed) = words.next().and_then(|n| n.parse::<u64>().ok()) else {
+                    continue;
+                };
+                if let Some((index, children)) = stack.last() {
+                    if let Some(frame) = frames[*index].as_mut() {
+                        frame.consumed = consumed;
+                        frame.own = consumed.saturating_sub(*children);
+                    }
+                }
+            }
+            "success" | "failed:" => {
+                if let Some((index, _)) = stack.pop() {
+                    let consumed = frames[index].as_ref().map_or(0, |f| f.consumed);
+                    if consumed == 0 {
+                        frames[index] = None;
+                    }
+                    if let Some((_, children)) = stack.last_mut() {
+                        *children += consumed;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    frames.into_iter().flatten().collect()
+}
+
+/// Parse `--sweep name=v1,v2,...` into the argument path and its values
+///
+/// Values that parse as JSON (numbers, `true`, ...) are used as such, anything
+/// else as a string.
+pub fn parse_sweep(spec: &str) -> Option<(String, Vec<Value>)> {
+    let (path, values) = spec.split_once('=')?;
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    let values: Vec<Value> = values
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| serde_json::from_str(v).unwrap_or_else(|_| Value::String(v.to_string())))
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some((path.to_string(), values))
+}
+
+/// Set the argument at a dotted `path` (`params.amount`) of a filled-in template
+pub fn set_arg(filled: &mut Value, path: &str, value: Value) {
+    let mut target = &mut filled["args"];
+    for part in path.split('.') {
+        target = &mut target[part];
+    }
+    *target = value;
+}
+
+/// Decode the `Program data:` log lines that match an IDL event
+pub fn decode_log_events(idl: &Idl, logs: &[String]) -> Vec<DecodedEvent> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(EVENT_LOG_PREFIX))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter_map(|data| decode_event(idl, &data).ok())
+        .collect()
+}
+
+/// Describe a transaction error, naming custom program errors from the IDL
+pub fn describe_error(idl: &Idl, err: &TransactionError) -> String {
+    let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+        return err.to_string();
+    };
+    match idl.find_error(*code) {
+        Some(e) => match &e.msg {
+            Some(msg) => format!("instruction {}: {} ({}): {}", index, e.name, code, msg),
+            None => format!("instruction {}: {} ({})", index, e.name, code),
+        },
+        None => format!("instruction {}: custom program error {}", index, code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn test_idl() -> Idl {
+        serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "events": [{ "name": "Filled", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9] }],
+            "types": [{ "name": "Filled", "type": { "kind": "struct", "fields": [
+                { "name": "amount", "type": "u64" }
+            ] } }],
+            "errors": [{ "code": 6001, "name": "Slippage", "msg": "Slippage exceeded" }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_log_events() {
+        let idl = test_idl();
+        let mut data = vec![9u8; 8];
+        data.extend(42u64.to_le_bytes());
+        let logs = vec![
+            "Program log: Instruction: Fill".to_string(),
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode(&data)
+            ),
+            "Program data: AAAA".to_string(),
+        ];
+
+        let events = decode_log_events(&idl, &logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "Filled");
+        assert_eq!(events[0].value, json!({ "amount": 42 }));
+    }
+
+    #[test]
+    fn test_describe_error_maps_custom_codes() {
+        let idl = test_idl();
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(6001));
+        assert_eq!(
+            describe_error(&idl, &err),
+            "instruction 0: Slippage (6001): Slippage exceeded"
+        );
+        let err = TransactionError::InstructionError(1, InstructionError::Custom(7));
+        assert_eq!(
+            describe_error(&idl, &err),
+            "instruction 1: custom program error 7"
+        );
+    }
+
+    #[test]
+    fn test_parse_compute_units() {
+        let logs: Vec<String> = [
+            "Program Swap111 invoke [1]",
+            "Program log: Instruction: Swap",
+            "Program Token111 invoke [2]",
+            "Program Token111 consumed 4500 of 190000 compute units",
+            "Program Token111 success",
+            "Program Token111 invoke [2]",
+            "Program Token111 consumed 4400 of 180000 compute units",
+            "Program Token111 success",
+            "Program Swap111 consumed 30000 of 200000 compute units",
+            "Program Swap111 success",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let frames = parse_compute_units(&logs);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].program, "Swap111");
+        assert_eq!(frames[0].depth, 1);
+        assert_eq!(frames[0].consumed, 30000);
+        assert_eq!(frames[0].own, 30000 - 4500 - 4400);
+        assert_eq!(frames[2].depth, 2);
+        assert_eq!(frames[2].own, 4400);
+    }
+
+    #[test]
+    fn test_sweep_spec() {
+        let (path, values) = parse_sweep("params.amount=1, 10,100").unwrap();
+        assert_eq!(path, "params.amount");
+        assert_eq!(values, vec![json!(1), json!(10), json!(100)]);
+        assert!(parse_sweep("amount").is_none());
+        assert!(parse_sweep("amount=").is_none());
+
+        let mut filled = json!({ "args": { "params": { "amount": 0 } } });
+        set_arg(&mut filled, &path, json!(10));
+        assert_eq!(filled["args"]["params"]["amount"], json!(10));
+    }
+}
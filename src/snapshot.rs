@@ -0,0 +1,247 @@
+//! Account fixtures for a local validator
+//!
+//! `periscope snapshot` fetches every account of the selected IDL account
+//! types with `getProgramAccounts` (filtered on the discriminator), plus the
+//! program, its ProgramData and its IDL account, and writes each one as a
+//! `solana account --output json` file. `solana-test-validator --account-dir`
+//! loads such a directory as is.
+
+use crate::builtin_programs::BPF_LOADER_UPGRADEABLE_ID;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::i18n::{trf, Msg};
+use crate::idl::{fetch_account, get_idl_address, Idl};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// What a captured account is
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "kebab-case")]
+pub enum FixtureKind {
+    /// The executable program account
+    Program,
+    /// ProgramData of an upgradeable program (holds the binary)
+    ProgramData,
+    /// Anchor IDL account
+    Idl,
+    /// Account of an IDL account type
+    Account(String),
+}
+
+/// One account in `solana account --output json` form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Address (base58)
+    pub pubkey: String,
+
+    /// Account contents
+    pub account: FixtureAccount,
+}
+
+/// Account contents of a [`Fixture`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureAccount {
+    pub lamports: u64,
+
+    /// `[<base64>, "base64"]`
+    pub data: (String, String),
+
+    /// Owner program (base58)
+    pub owner: String,
+
+    pub executable: bool,
+
+    pub rent_epoch: u64,
+
+    /// Data length (older RPC nodes omit it)
+    #[serde(default)]
+    pub space: usize,
+}
+
+impl Fixture {
+    /// Fixture of a fetched account
+    pub fn new(address: &Pubkey, account: &Account) -> Self {
+        Fixture {
+            pubkey: address.to_string(),
+            account: FixtureAccount {
+                lamports: account.lamports,
+                data: (
+                    base64::engine::general_purpose::STANDARD.encode(&account.data),
+                    "base64".to_string(),
+                ),
+                owner: account.owner.to_string(),
+                executable: account.executable,
+                rent_epoch: account.rent_epoch,
+                space: account.data.len(),
+            },
+        }
+    }
+}
+
+/// Read a fixture file written by `snapshot` (or `solana account --output json`)
+pub fn read_fixture(path: &Path) -> PeriscopeResult<Fixture> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+impl Fixture {
+    /// Address of the account
+    pub fn address(&self) -> PeriscopeResult<Pubkey> {
+        Pubkey::from_str(&self.pubkey)
+            .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&self.pubkey])))
+    }
+
+    /// Owner program of the account
+    pub fn owner(&self) -> PeriscopeResult<Pubkey> {
+        Pubkey::from_str(&self.account.owner)
+            .map_err(|_| PeriscopeError::Usage(trf(Msg::InvalidAddress, &[&self.account.owner])))
+    }
+
+    /// Account data, decoded from base64
+    pub fn data(&self) -> PeriscopeResult<Vec<u8>> {
+        base64::engine::general_purpose::STANDARD
+            .decode(&self.account.data.0)
+            .map_err(|e| {
+                PeriscopeError::Usage(format!("{}: invalid base64 data: {}", self.pubkey, e))
+            })
+    }
+}
+
+/// Fetch the program accounts and all accounts of `account_types`
+///
+/// Types are matched by name against the IDL's `accounts`. The IDL and
+/// ProgramData accounts are skipped when the program has none.
+pub fn snapshot(
+    client: &RpcClient,
+    idl: &Idl,
+    program_id: &Pubkey,
+    account_types: &[String],
+) -> PeriscopeResult<Vec<(FixtureKind, Fixture)>> {
+    // Resolve every type before the first (possibly slow) scan
+    let mut filters = Vec::new();
+    for name in account_types {
+        let account = idl
+            .accounts
+            .iter()
+            .find(|a| a.name == *name)
+            .ok_or_else(|| PeriscopeError::NotFound(trf(Msg::AccountTypeNotFound, &[name])))?;
+        filters.push((name, account.discriminator_bytes()));
+    }
+
+    let mut fixtures = Vec::new();
+    let program = fetch_account(client, program_id)?;
+    fixtures.push((FixtureKind::Program, Fixture::new(program_id, &program)));
+
+    if program.owner == BPF_LOADER_UPGRADEABLE_ID {
+        let (program_data, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+        let account = fetch_account(client, &program_data)?;
+        fixtures.push((
+            FixtureKind::ProgramData,
+            Fixture::new(&program_data, &account),
+        ));
+    }
+
+    let idl_address = get_idl_address(program_id)?;
+    match fetch_account(client, &idl_address) {
+        Ok(account) => fixtures.push((FixtureKind::Idl, Fixture::new(&idl_address, &account))),
+        Err(PeriscopeError::AccountNotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+
+    for (name, discriminator) in filters {
+        for fixture in program_accounts(client, program_id, &discriminator)? {
+            fixtures.push((FixtureKind::Account(name.clone()), fixture));
+        }
+    }
+    Ok(fixtures)
+}
+
+/// Accounts of `program_id` whose data starts with `discriminator`
+pub fn program_accounts(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    discriminator: &[u8],
+) -> PeriscopeResult<Vec<Fixture>> {
+    let response: Value = client.send(
+        RpcRequest::GetProgramAccounts,
+        json!([program_id.to_string(), {
+            "encoding": "base64",
+            "commitment": "confirmed",
+            "filters": [{ "memcmp": {
+                "offset": 0,
+                "bytes": solana_sdk::bs58::encode(discriminator).into_string()
+            } }]
+        }]),
+    )?;
+    let mut fixtures: Vec<Fixture> = serde_json::from_value(response).map_err(|e| {
+        PeriscopeError::NetworkError(format!("unexpected getProgramAccounts response: {}", e))
+    })?;
+    for fixture in &mut fixtures {
+        if let Ok(data) = base64::engine::general_purpose::STANDARD.decode(&fixture.account.data.0)
+        {
+            fixture.account.space = data.len();
+        }
+    }
+    Ok(fixtures)
+}
+
+/// Write each fixture to `<dir>/<pubkey>.json`, returning the paths
+pub fn write_fixtures(
+    dir: &Path,
+    fixtures: &[(FixtureKind, Fixture)],
+) -> PeriscopeResult<Vec<String>> {
+    fs::create_dir_all(dir)?;
+    let mut paths = Vec::with_capacity(fixtures.len());
+    for (_, fixture) in fixtures {
+        let path = dir.join(format!("{}.json", fixture.pubkey));
+        fs::write(&path, serde_json::to_string_pretty(fixture)?)?;
+        paths.push(path.display().to_string());
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_matches_solana_account_json() {
+        let account = Account {
+            lamports: 1_000_000,
+            data: vec![1, 2, 3],
+            owner: BPF_LOADER_UPGRADEABLE_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let address = Pubkey::new_unique();
+        let fixture = Fixture::new(&address, &account);
+        assert_eq!(
+            serde_json::to_value(&fixture).unwrap(),
+            json!({
+                "pubkey": address.to_string(),
+                "account": {
+                    "lamports": 1_000_000,
+                    "data": ["AQID", "base64"],
+                    "owner": BPF_LOADER_UPGRADEABLE_ID.to_string(),
+                    "executable": false,
+                    "rentEpoch": u64::MAX,
+                    "space": 3
+                }
+            })
+        );
+
+        // getProgramAccounts entries have the same shape
+        let parsed: Fixture =
+            serde_json::from_value(serde_json::to_value(&fixture).unwrap()).expect("round trip");
+        assert_eq!(parsed.account.data.0, "AQID");
+    }
+}
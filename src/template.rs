@@ -0,0 +1,337 @@
+//! Fill-in templates for instruction arguments and accounts
+//!
+//! `periscope template` prints every account and argument of an instruction
+//! with a placeholder of the right shape and a comment describing it. Values
+//! use the same JSON representation as decoded data: 128-bit and larger
+//! integers are strings, unit enum variants are their name and other
+//! variants are `{ "Variant": fields }`.
+
+use crate::idl::{
+    format_type, Idl, IdlAccountItem, IdlEnumFields, IdlField, IdlInstruction, IdlType,
+    IdlTypeComplex, IdlTypeDefTy,
+};
+use serde_json::{json, Map, Value};
+use std::fmt::Write;
+
+/// Nesting depth past which defined types are left as `null`
+const MAX_DEPTH: usize = 16;
+
+/// A template entry: name, placeholder value and description
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateField {
+    /// Account or argument name
+    pub name: String,
+
+    /// Placeholder; `null` for `Option` arguments (None)
+    pub value: Value,
+
+    /// Type or account constraints
+    pub comment: String,
+}
+
+/// Template of an instruction's accounts and arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    /// Instruction name
+    pub instruction: String,
+
+    /// Accounts in instruction order; groups hold an object of their accounts
+    pub accounts: Vec<TemplateField>,
+
+    /// Arguments in instruction order
+    pub args: Vec<TemplateField>,
+}
+
+impl Template {
+    /// Build the template of `ix`
+    pub fn new(idl: &Idl, ix: &IdlInstruction) -> Self {
+        Template {
+            instruction: ix.name.clone(),
+            accounts: ix.accounts.iter().map(account_field).collect(),
+            args: ix
+                .args
+                .iter()
+                .map(|arg| TemplateField {
+                    name: arg.name.clone(),
+                    value: placeholder(idl, &arg.ty, 0),
+                    comment: describe(idl, &arg.ty),
+                })
+                .collect(),
+        }
+    }
+
+    /// JSON template; comments are keyed by `accounts.<name>` / `args.<name>`
+    pub fn to_json(&self) -> Value {
+        let mut comments = Map::new();
+        let mut section = |prefix: &str, fields: &[TemplateField]| {
+            let mut map = Map::new();
+            for field in fields {
+                comments.insert(format!("{}.{}", prefix, field.name), json!(field.comment));
+                map.insert(field.name.clone(), field.value.clone());
+            }
+            Value::Object(map)
+        };
+        let accounts = section("accounts", &self.accounts);
+        let args = section("args", &self.args);
+
+        json!({
+            "instruction": self.instruction,
+            "accounts": accounts,
+            "args": args,
+            "comments": comments,
+        })
+    }
+
+    /// Commented TOML template
+    ///
+    /// TOML has no null: `Option` arguments are written commented out, with
+    /// the placeholder of their inner type.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "instruction = {}", toml_string(&self.instruction));
+
+        for (title, fields) in [("accounts", &self.accounts), ("args", &self.args)] {
+            let _ = writeln!(out, "\n[{}]", title);
+            for field in fields {
+                let (prefix, value) = match &field.value {
+                    Value::Null => ("# ", String::from("...")),
+                    value => ("", toml_value(value)),
+                };
+                let _ = writeln!(
+                    out,
+                    "{}{} = {}  # {}",
+                    prefix,
+                    toml_key(&field.name),
+                    value,
+                    field.comment
+                );
+            }
+        }
+        out
+    }
+}
+
+fn account_field(item: &IdlAccountItem) -> TemplateField {
+    match item {
+        IdlAccountItem::Single(account) => {
+            let mut notes = Vec::new();
+            if account.writable {
+                notes.push("writable");
+            }
+            if account.signer {
+                notes.push("signer");
+            }
+            if account.optional {
+                notes.push("optional");
+            }
+            if account.pda.is_some() {
+                notes.push("PDA");
+            }
+            let value = match &account.address {
+                Some(address) => {
+                    notes.push("fixed address");
+                    json!(address)
+                }
+                None => json!(""),
+            };
+            let comment = if notes.is_empty() {
+                "pubkey".to_string()
+            } else {
+                format!("pubkey, {}", notes.join(", "))
+            };
+            TemplateField {
+                name: account.name.clone(),
+                value,
+                comment,
+            }
+        }
+        IdlAccountItem::Group(group) => {
+            let members: Map<String, Value> = group
+                .accounts
+                .iter()
+                .map(account_field)
+                .map(|f| (f.name, f.value))
+                .collect();
+            TemplateField {
+                name: group.name.clone(),
+                value: Value::Object(members),
+                comment: "account group".to_string(),
+            }
+        }
+    }
+}
+
+/// Placeholder value of the right shape for `ty`
+pub fn placeholder(idl: &Idl, ty: &IdlType, depth: usize) -> Value {
+    match ty {
+        IdlType::Primitive(name) => match name.as_str() {
+            "bool" => json!(false),
+            "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" => json!(0),
+            "f32" | "f64" => json!(0.0),
+            "u128" | "i128" | "u256" | "i256" => json!("0"),
+            "bytes" => json!([]),
+            _ => json!(""),
+        },
+        IdlType::Complex(IdlTypeComplex::Vec(_)) => json!([]),
+        IdlType::Complex(IdlTypeComplex::Option(_)) => Value::Null,
+        IdlType::Complex(IdlTypeComplex::Array(inner, len)) => {
+            let item = placeholder(idl, inner, depth + 1);
+            Value::Array(vec![item; len.value().unwrap_or(0)])
+        }
+        IdlType::Complex(IdlTypeComplex::Generic(_)) => Value::Null,
+        IdlType::Complex(IdlTypeComplex::Defined { name, generics }) => {
+            if depth >= MAX_DEPTH {
+                return Value::Null;
+            }
+            let type_def = idl.instantiate_type(name, generics).ok();
+            match type_def.as_ref().map(|t| &t.ty) {
+                Some(IdlTypeDefTy::Struct { fields }) => fields_placeholder(idl, fields, depth),
+                Some(IdlTypeDefTy::Enum { variants }) => match variants.first() {
+                    None => Value::Null,
+                    Some(variant) => match &variant.fields {
+                        None => json!(variant.name),
+                        Some(IdlEnumFields::Named(fields)) => {
+                            json!({ &variant.name: fields_placeholder(idl, fields, depth) })
+                        }
+                        Some(IdlEnumFields::Tuple(types)) => {
+                            let items: Vec<Value> = types
+                                .iter()
+                                .map(|t| placeholder(idl, t, depth + 1))
+                                .collect();
+                            json!({ &variant.name: items })
+                        }
+                    },
+                },
+                None => Value::Null,
+            }
+        }
+    }
+}
+
+fn fields_placeholder(idl: &Idl, fields: &[IdlField], depth: usize) -> Value {
+    Value::Object(
+        fields
+            .iter()
+            .map(|f| (f.name.clone(), placeholder(idl, &f.ty, depth + 1)))
+            .collect(),
+    )
+}
+
+/// Type description for a comment, listing the variants of enums
+fn describe(idl: &Idl, ty: &IdlType) -> String {
+    let mut text = format_type(ty);
+    match ty {
+        IdlType::Primitive(name) if name == "pubkey" || name == "publicKey" => {
+            text.push_str(" (base58)");
+        }
+        IdlType::Primitive(name) if matches!(name.as_str(), "u128" | "i128" | "u256" | "i256") => {
+            text.push_str(" (as a string)");
+        }
+        IdlType::Complex(IdlTypeComplex::Option(_)) => text.push_str(" (null for None)"),
+        IdlType::Complex(IdlTypeComplex::Defined { name, .. }) => {
+            if let Some(IdlTypeDefTy::Enum { variants }) = idl.find_type(name).map(|t| &t.ty) {
+                let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+                let _ = write!(text, ", one of: {}", names.join(", "));
+            }
+        }
+        _ => {}
+    }
+    text
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+fn toml_string(s: &str) -> String {
+    // JSON string escapes are valid TOML basic-string escapes
+    Value::String(s.to_string()).to_string()
+}
+
+/// Inline TOML for a placeholder; `null` members are left out
+fn toml_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::String(s) => toml_string(s),
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(toml_value)
+                .collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| format!("{} = {}", toml_key(k), toml_value(v)))
+                .collect();
+            if entries.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_template() {
+        let idl: Idl = serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "swap",
+                "accounts": [
+                    { "name": "user", "writable": true, "signer": true },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "limit", "type": { "option": "u128" } },
+                    { "name": "side", "type": { "defined": { "name": "Side" } } },
+                    { "name": "route", "type": { "array": ["u8", 2] } }
+                ]
+            }],
+            "types": [{ "name": "Side", "type": { "kind": "enum", "variants": [
+                { "name": "Bid" }, { "name": "Ask" }
+            ] } }]
+        }))
+        .unwrap();
+
+        let template = Template::new(&idl, &idl.instructions[0]);
+        let json = template.to_json();
+        assert_eq!(json["args"]["amount"], json!(0));
+        assert_eq!(json["args"]["limit"], Value::Null);
+        assert_eq!(json["args"]["side"], json!("Bid"));
+        assert_eq!(json["args"]["route"], json!([0, 0]));
+        assert_eq!(
+            json["accounts"]["system_program"],
+            json!("11111111111111111111111111111111")
+        );
+        assert_eq!(
+            json["comments"]["args.side"],
+            json!("Side, one of: Bid, Ask")
+        );
+
+        let toml = template.to_toml();
+        assert!(toml.contains("user = \"\"  # pubkey, writable, signer"));
+        assert!(toml.contains("# limit = ...  # Option<u128> (null for None)"));
+        let parsed: toml::Table = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed["args"]["route"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,175 @@
+//! Golden-file assertions for tests of downstream crates
+//!
+//! A service built against one version of a program's IDL can pin it:
+//!
+//! ```ignore
+//! #[test]
+//! fn amm_idl_is_pinned() {
+//!     let idl = fetch_idl_from_chain(&AMM, RPC_URL).unwrap();
+//!     periscope::assert_idl_matches!(idl, "fixtures/amm.json");
+//! }
+//! ```
+//!
+//! IDLs are compared in canonical form, so key order, whitespace and spelled
+//! out defaults don't count. When they differ the test fails with the
+//! changed fields, named by path (`instructions.swap.args.amount.type`).
+//! Running the tests with `PERISCOPE_UPDATE_GOLDEN=1` writes the IDL to the
+//! golden file instead, which is how the file is created in the first place.
+
+use crate::account_diff::{diff_values, FieldChange};
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::i18n::{trf, Msg};
+use crate::idl::{parse_idl_json, Idl};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Environment variable that turns assertions into golden-file updates
+pub const UPDATE_GOLDEN_ENV: &str = "PERISCOPE_UPDATE_GOLDEN";
+
+/// Assert that an IDL matches the golden JSON file at a path relative to
+/// the calling crate's root, listing the changed fields when it doesn't
+///
+/// With `PERISCOPE_UPDATE_GOLDEN=1` the file is (re)written instead.
+#[macro_export]
+macro_rules! assert_idl_matches {
+    ($idl:expr, $path:expr $(,)?) => {{
+        let path = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path);
+        match $crate::test_util::idl_drift(&$idl, &path) {
+            Ok(changes) if changes.is_empty() => {}
+            Ok(changes) => panic!("{}", $crate::test_util::drift_report(&path, &changes)),
+            Err(e) => panic!("{}", e),
+        }
+    }};
+}
+
+/// Fields of `idl` that differ from the golden file at `path`; empty when
+/// they match. In update mode the file is written and nothing differs.
+pub fn idl_drift(idl: &Idl, path: &Path) -> PeriscopeResult<Vec<FieldChange>> {
+    let actual = golden_value(idl);
+    if update_mode() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&actual)?;
+        std::fs::write(path, json + "\n")?;
+        return Ok(Vec::new());
+    }
+
+    let json = std::fs::read_to_string(path).map_err(|e| {
+        PeriscopeError::NotFound(trf(
+            Msg::GoldenUnreadable,
+            &[&path.display(), &e, &UPDATE_GOLDEN_ENV],
+        ))
+    })?;
+    let expected = golden_value(&parse_idl_json(&json)?);
+    Ok(diff_values(
+        &keyed_by_name(expected),
+        &keyed_by_name(actual),
+    ))
+}
+
+/// The failure message of [`assert_idl_matches!`]: one line per changed
+/// field, golden value first
+pub fn drift_report(path: &Path, changes: &[FieldChange]) -> String {
+    let mut report = trf(Msg::GoldenMismatch, &[&path.display(), &UPDATE_GOLDEN_ENV]);
+    for change in changes {
+        report.push_str(&format!(
+            "\n  {}: {} -> {}",
+            change.path,
+            shown(&change.before),
+            shown(&change.after)
+        ));
+    }
+    report
+}
+
+fn update_mode() -> bool {
+    std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// The IDL in canonical form, as written to golden files
+fn golden_value(idl: &Idl) -> Value {
+    serde_json::from_str(&idl.canonical_json()).expect("canonical JSON parses")
+}
+
+/// Arrays of named items (instructions, accounts, fields...) as objects
+/// keyed by name, so an inserted item shows up as one added field instead
+/// of every later index changing
+fn keyed_by_name(value: Value) -> Value {
+    match value {
+        Value::Object(map) => map
+            .into_iter()
+            .map(|(k, v)| (k, keyed_by_name(v)))
+            .collect::<Map<_, _>>()
+            .into(),
+        Value::Array(items) => {
+            let named = !items.is_empty()
+                && items
+                    .iter()
+                    .all(|item| item.get("name").is_some_and(Value::is_string));
+            let items = items.into_iter().map(keyed_by_name);
+            if named {
+                let map: Map<_, _> = items
+                    .map(|item| (item["name"].as_str().unwrap_or_default().to_string(), item))
+                    .collect();
+                Value::Object(map)
+            } else {
+                Value::Array(items.collect())
+            }
+        }
+        other => other,
+    }
+}
+
+fn shown(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMM: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [
+            { "name": "init", "discriminator": [0,0,0,0,0,0,0,0], "accounts": [], "args": [] },
+            { "name": "swap", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [],
+              "args": [{ "name": "amount", "type": "u64" }] }
+        ]
+    }"#;
+
+    #[test]
+    fn test_idl_drift_by_name() {
+        let dir = std::env::temp_dir().join(format!("periscope-golden-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("amm.json");
+        std::fs::write(&path, AMM).unwrap();
+
+        let idl = parse_idl_json(AMM).unwrap();
+        assert!(idl_drift(&idl, &path).unwrap().is_empty());
+        crate::assert_idl_matches!(idl, &path);
+
+        let drifted = AMM
+            .replace("\"u64\"", "\"u128\"")
+            .replace("{ \"name\": \"init\"", "{ \"name\": \"start\"");
+        let changes = idl_drift(&parse_idl_json(&drifted).unwrap(), &path).unwrap();
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "instructions.init",
+                "instructions.swap.args.amount.type",
+                "instructions.start"
+            ]
+        );
+        let report = drift_report(&path, &changes);
+        assert!(report.contains("instructions.swap.args.amount.type: \"u64\" -> \"u128\""));
+
+        assert!(idl_drift(&idl, &dir.join("missing.json")).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,382 @@
+//! Output color themes
+//!
+//! Every style decision in CLI output goes through [`paint`], which looks up
+//! the element in the active theme. Themes are picked (and individual
+//! elements overridden) in the `[theme]` table of config.toml.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: &[&str] = &["default", "solarized", "monochrome"];
+
+/// Active theme, set once at startup
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Styled element of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    /// Main headers (program name, command title)
+    Header,
+    /// Section headers
+    Subheader,
+    /// Horizontal rules under headers
+    Rule,
+    /// Field labels, column headings, list numbers
+    Label,
+    /// De-emphasized text ("(none)", addresses, messages)
+    Muted,
+    /// Instruction and account type names
+    Name,
+    /// Argument, account and field names
+    Field,
+    /// Types
+    Type,
+    /// Event names and counts
+    Event,
+    /// `signer` constraint
+    Signer,
+    /// `writable` constraint
+    Writable,
+    /// `optional` constraint
+    Optional,
+    /// Error codes and error labels
+    Error,
+    /// Warnings and notes
+    Warning,
+    /// Positive status
+    Success,
+}
+
+impl Element {
+    /// All elements, in config key order
+    pub const ALL: &'static [Element] = &[
+        Element::Header,
+        Element::Subheader,
+        Element::Rule,
+        Element::Label,
+        Element::Muted,
+        Element::Name,
+        Element::Field,
+        Element::Type,
+        Element::Event,
+        Element::Signer,
+        Element::Writable,
+        Element::Optional,
+        Element::Error,
+        Element::Warning,
+        Element::Success,
+    ];
+
+    /// Key of this element in the `[theme]` config table
+    pub fn key(&self) -> &'static str {
+        match self {
+            Element::Header => "header",
+            Element::Subheader => "subheader",
+            Element::Rule => "rule",
+            Element::Label => "label",
+            Element::Muted => "muted",
+            Element::Name => "name",
+            Element::Field => "field",
+            Element::Type => "type",
+            Element::Event => "event",
+            Element::Signer => "signer",
+            Element::Writable => "writable",
+            Element::Optional => "optional",
+            Element::Error => "error",
+            Element::Warning => "warning",
+            Element::Success => "success",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|e| e.key() == key)
+    }
+}
+
+/// Style of a single element
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    const fn color(color: Color) -> Self {
+        Self {
+            color: Some(color),
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    const fn dimmed() -> Self {
+        Self {
+            color: None,
+            bold: false,
+            dimmed: true,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    fn apply(&self, text: &str) -> ColoredString {
+        let mut s = match self.color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        if self.bold {
+            s = s.bold();
+        }
+        if self.dimmed {
+            s = s.dimmed();
+        }
+        if self.italic {
+            s = s.italic();
+        }
+        if self.underline {
+            s = s.underline();
+        }
+        s
+    }
+}
+
+/// Parses specs like `"bold cyan"`, `"dimmed"`, `"#268bd2"` or `"none"`
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::default();
+        for word in spec.split_whitespace() {
+            match word {
+                "none" | "plain" => {}
+                "bold" => style.bold = true,
+                "dimmed" | "dim" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                hex if hex.starts_with('#') && hex.len() == 7 => {
+                    let channel = |i: usize| {
+                        u8::from_str_radix(&hex[i..i + 2], 16)
+                            .map_err(|_| format!("invalid hex color '{}'", hex))
+                    };
+                    style.color = Some(Color::TrueColor {
+                        r: channel(1)?,
+                        g: channel(3)?,
+                        b: channel(5)?,
+                    });
+                }
+                color => {
+                    style.color = Some(
+                        Color::from_str(&color.replace('-', " "))
+                            .map_err(|_| format!("unknown color or style '{}'", color))?,
+                    );
+                }
+            }
+        }
+        Ok(style)
+    }
+}
+
+/// A complete set of element styles
+#[derive(Debug, Clone)]
+pub struct Theme {
+    styles: [Style; Element::ALL.len()],
+}
+
+impl Theme {
+    /// The default colorful theme
+    pub fn default_theme() -> Self {
+        let mut theme = Self::monochrome();
+        theme.set(Element::Header, Style::color(Color::Cyan).bold());
+        theme.set(Element::Subheader, Style::color(Color::White).bold());
+        theme.set(Element::Name, Style::color(Color::Green));
+        theme.set(Element::Field, Style::color(Color::Yellow));
+        theme.set(Element::Type, Style::color(Color::Blue));
+        theme.set(Element::Event, Style::color(Color::Magenta));
+        theme.set(Element::Signer, Style::color(Color::Green));
+        theme.set(Element::Writable, Style::color(Color::Magenta));
+        theme.set(Element::Error, Style::color(Color::Red));
+        theme.set(Element::Warning, Style::color(Color::Yellow));
+        theme.set(Element::Success, Style::color(Color::Green));
+        theme
+    }
+
+    /// Solarized palette (24-bit color)
+    pub fn solarized() -> Self {
+        let rgb = |r, g, b| Style::color(Color::TrueColor { r, g, b });
+        let base01 = rgb(0x58, 0x6e, 0x75);
+
+        let mut theme = Self::monochrome();
+        theme.set(Element::Header, rgb(0x26, 0x8b, 0xd2).bold());
+        theme.set(Element::Subheader, rgb(0x93, 0xa1, 0xa1).bold());
+        theme.set(Element::Rule, base01);
+        theme.set(Element::Label, base01);
+        theme.set(Element::Muted, base01);
+        theme.set(Element::Name, rgb(0x85, 0x99, 0x00));
+        theme.set(Element::Field, rgb(0xb5, 0x89, 0x00));
+        theme.set(Element::Type, rgb(0x2a, 0xa1, 0x98));
+        theme.set(Element::Event, rgb(0x6c, 0x71, 0xc4));
+        theme.set(Element::Signer, rgb(0x85, 0x99, 0x00));
+        theme.set(Element::Writable, rgb(0xd3, 0x36, 0x82));
+        theme.set(Element::Optional, base01);
+        theme.set(Element::Error, rgb(0xdc, 0x32, 0x2f));
+        theme.set(Element::Warning, rgb(0xcb, 0x4b, 0x16));
+        theme.set(Element::Success, rgb(0x85, 0x99, 0x00));
+        theme
+    }
+
+    /// No colors, only weight
+    pub fn monochrome() -> Self {
+        let mut theme = Self {
+            styles: [Style::default(); Element::ALL.len()],
+        };
+        theme.set(Element::Header, Style::default().bold());
+        theme.set(Element::Subheader, Style::default().bold());
+        theme.set(Element::Rule, Style::dimmed());
+        theme.set(Element::Label, Style::dimmed());
+        theme.set(Element::Muted, Style::dimmed());
+        theme.set(Element::Optional, Style::dimmed());
+        theme.set(Element::Error, Style::default().bold());
+        theme
+    }
+
+    /// Built-in theme by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default_theme()),
+            "solarized" => Some(Self::solarized()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Build the theme described by a config `[theme]` table
+    pub fn from_config(config: &ThemeConfig) -> PeriscopeResult<Self> {
+        let name = config.name.as_deref().unwrap_or("default");
+        let mut theme = Self::builtin(name).ok_or_else(|| {
+            PeriscopeError::ConfigError(format!(
+                "Unknown theme '{}' (available: {})",
+                name,
+                BUILTIN_THEMES.join(", ")
+            ))
+        })?;
+
+        for (key, spec) in &config.overrides {
+            let element = Element::from_key(key).ok_or_else(|| {
+                PeriscopeError::ConfigError(format!("Unknown theme element '{}'", key))
+            })?;
+            let style = spec.parse().map_err(|e| {
+                PeriscopeError::ConfigError(format!("Invalid style for theme.{}: {}", key, e))
+            })?;
+            theme.set(element, style);
+        }
+
+        Ok(theme)
+    }
+
+    /// Style of an element
+    pub fn style(&self, element: Element) -> Style {
+        self.styles[element as usize]
+    }
+
+    /// Replace the style of an element
+    pub fn set(&mut self, element: Element, style: Style) {
+        self.styles[element as usize] = style;
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::default_theme()
+    }
+}
+
+/// `[theme]` table in config.toml
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Built-in theme to start from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Per-element style overrides, e.g. `header = "bold magenta"`
+    #[serde(flatten)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl ThemeConfig {
+    /// True when nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.overrides.is_empty()
+    }
+}
+
+/// Install the theme used by all output. Only the first call has an effect.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The active theme
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// Style text as the given element of the active theme
+pub fn paint(text: &str, element: Element) -> ColoredString {
+    current().style(element).apply(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        let style: Style = "bold bright-blue".parse().unwrap();
+        assert!(style.bold);
+        assert_eq!(style.color, Some(Color::BrightBlue));
+
+        let style: Style = "#268bd2".parse().unwrap();
+        assert_eq!(
+            style.color,
+            Some(Color::TrueColor {
+                r: 0x26,
+                g: 0x8b,
+                b: 0xd2
+            })
+        );
+
+        assert!("sparkly".parse::<Style>().is_err());
+    }
+
+    #[test]
+    fn test_theme_from_config() {
+        let config: ThemeConfig = toml::from_str(
+            r#"
+            name = "monochrome"
+            header = "underline"
+            "#,
+        )
+        .unwrap();
+
+        let theme = Theme::from_config(&config).unwrap();
+        assert!(theme.style(Element::Header).underline);
+        assert_eq!(theme.style(Element::Name).color, None);
+
+        let bad: ThemeConfig = toml::from_str(r#"headr = "red""#).unwrap();
+        assert!(Theme::from_config(&bad).is_err());
+    }
+}
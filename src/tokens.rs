@@ -0,0 +1,327 @@
+//! Token mint names, symbols and decimals
+//!
+//! [`resolve_token`] looks a mint up in a small bundled list of well-known
+//! tokens, then in the token lists installed with [`set_token_lists`],
+//! without any I/O. [`fetch_token`] falls back to the chain: the Token-2022
+//! metadata extension of the mint, then the Metaplex Token Metadata account.
+
+use crate::builtin_programs::{decode_builtin_account, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::fetch_account;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::OnceLock;
+
+static TOKEN_LISTS: OnceLock<Vec<TokenInfo>> = OnceLock::new();
+
+/// Metaplex Token Metadata program
+pub const METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Well-known mainnet mints: address, symbol, name, decimals
+const TOKEN_LIST: &[(Pubkey, &str, &str, u8)] = &[
+    (
+        pubkey!("So11111111111111111111111111111111111111112"),
+        "SOL",
+        "Wrapped SOL",
+        9,
+    ),
+    (
+        pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+        "USDC",
+        "USD Coin",
+        6,
+    ),
+    (
+        pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB"),
+        "USDT",
+        "USDT",
+        6,
+    ),
+    (
+        pubkey!("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So"),
+        "mSOL",
+        "Marinade staked SOL",
+        9,
+    ),
+    (
+        pubkey!("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn"),
+        "JitoSOL",
+        "Jito Staked SOL",
+        9,
+    ),
+    (
+        pubkey!("bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1"),
+        "bSOL",
+        "BlazeStake Staked SOL",
+        9,
+    ),
+    (
+        pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263"),
+        "Bonk",
+        "Bonk",
+        5,
+    ),
+    (
+        pubkey!("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN"),
+        "JUP",
+        "Jupiter",
+        6,
+    ),
+    (
+        pubkey!("4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R"),
+        "RAY",
+        "Raydium",
+        6,
+    ),
+    (
+        pubkey!("HZ1JovNiVvGrGNiiYvEozEVgZ58xaU3RKwX8eACQBCt3"),
+        "PYTH",
+        "Pyth Network",
+        6,
+    ),
+];
+
+/// Where token information came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenSource {
+    /// Bundled token list
+    List,
+    /// A token list set with [`set_token_lists`] (`endpoints.token_lists`)
+    TokenList,
+    /// Token-2022 metadata extension of the mint
+    Token2022Metadata,
+    /// Metaplex Token Metadata account
+    Metaplex,
+    /// The mint alone (decimals only)
+    Mint,
+}
+
+/// What a mint is
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenInfo {
+    /// Mint address (base58)
+    pub mint: String,
+
+    pub symbol: Option<String>,
+
+    pub name: Option<String>,
+
+    pub decimals: Option<u8>,
+
+    pub source: TokenSource,
+}
+
+impl TokenInfo {
+    /// `SYMBOL (Name)`, or whichever of the two is known
+    pub fn label(&self) -> Option<String> {
+        match (&self.symbol, &self.name) {
+            (Some(symbol), Some(name)) if symbol != name => Some(format!("{} ({})", symbol, name)),
+            (Some(symbol), _) => Some(symbol.clone()),
+            (None, name) => name.clone(),
+        }
+    }
+}
+
+/// Look a mint up in the bundled token list, then in the installed token lists
+pub fn resolve_token(mint: &Pubkey) -> Option<TokenInfo> {
+    let bundled = TOKEN_LIST.iter().find(|(address, ..)| address == mint).map(
+        |(address, symbol, name, decimals)| TokenInfo {
+            mint: address.to_string(),
+            symbol: Some(symbol.to_string()),
+            name: Some(name.to_string()),
+            decimals: Some(*decimals),
+            source: TokenSource::List,
+        },
+    );
+    bundled.or_else(|| {
+        let mint = mint.to_string();
+        TOKEN_LISTS
+            .get()?
+            .iter()
+            .find(|token| token.mint == mint)
+            .cloned()
+    })
+}
+
+/// Also look mints up in these tokens for the rest of the process (the
+/// first call wins); earlier entries take precedence
+pub fn set_token_lists(tokens: Vec<TokenInfo>) {
+    let _ = TOKEN_LISTS.set(tokens);
+}
+
+/// Entry of a token list; other keys (`chainId`, `logoURI`, ...) are ignored
+#[derive(Deserialize)]
+struct ListedToken {
+    address: String,
+    #[serde(default)]
+    symbol: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    decimals: Option<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenListFile {
+    /// Solana token-list format
+    Wrapped { tokens: Vec<ListedToken> },
+    /// Jupiter format
+    Bare(Vec<ListedToken>),
+}
+
+/// Tokens of a token list: `{"tokens": [...]}` as in the Solana token-list
+/// format, or a bare array as Jupiter serves
+pub fn parse_token_list(json: &str) -> PeriscopeResult<Vec<TokenInfo>> {
+    let tokens = match serde_json::from_str(json)? {
+        TokenListFile::Wrapped { tokens } | TokenListFile::Bare(tokens) => tokens,
+    };
+    Ok(tokens
+        .into_iter()
+        .map(|token| TokenInfo {
+            mint: token.address,
+            symbol: token.symbol,
+            name: token.name,
+            decimals: token.decimals,
+            source: TokenSource::TokenList,
+        })
+        .collect())
+}
+
+/// Address of a mint's Metaplex metadata account
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Resolve a mint from the token lists, then from the chain
+///
+/// `None` when the address is not a token mint.
+pub fn fetch_token(client: &RpcClient, mint: &Pubkey) -> PeriscopeResult<Option<TokenInfo>> {
+    if let Some(token) = resolve_token(mint) {
+        return Ok(Some(token));
+    }
+
+    let account = match fetch_account(client, mint) {
+        Ok(account) => account,
+        Err(PeriscopeError::AccountNotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+        return Ok(None);
+    }
+    let Some(Ok(decoded)) = decode_builtin_account(&account.owner, &account.data) else {
+        return Ok(None);
+    };
+    if decoded.name != "Mint" {
+        return Ok(None);
+    }
+
+    let mut token = TokenInfo {
+        mint: mint.to_string(),
+        symbol: None,
+        name: None,
+        decimals: decoded.value["decimals"]
+            .as_u64()
+            .and_then(|d| u8::try_from(d).ok()),
+        source: TokenSource::Mint,
+    };
+
+    let extension = decoded.value["extensions"]
+        .as_array()
+        .and_then(|extensions| {
+            extensions
+                .iter()
+                .find_map(|e| e.get("TokenMetadata"))
+                .cloned()
+        });
+    if let Some(metadata) = extension {
+        token.symbol = metadata["symbol"].as_str().map(str::to_string);
+        token.name = metadata["name"].as_str().map(str::to_string);
+        token.source = TokenSource::Token2022Metadata;
+        return Ok(Some(token));
+    }
+
+    match fetch_account(client, &metadata_address(mint)) {
+        Ok(account) => {
+            if let Some((name, symbol)) = parse_metaplex_metadata(&account.data) {
+                token.name = Some(name).filter(|s| !s.is_empty());
+                token.symbol = Some(symbol).filter(|s| !s.is_empty());
+                token.source = TokenSource::Metaplex;
+            }
+        }
+        Err(PeriscopeError::AccountNotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    Ok(Some(token))
+}
+
+/// Name and symbol of a Metaplex metadata account
+///
+/// Layout: key (1), update authority (32), mint (32), then Borsh strings
+/// padded with NULs to a fixed length.
+pub fn parse_metaplex_metadata(data: &[u8]) -> Option<(String, String)> {
+    let mut offset = 65;
+    let mut read_string = || {
+        let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let s = std::str::from_utf8(data.get(offset + 4..offset + 4 + len)?).ok()?;
+        offset += 4 + len;
+        Some(s.trim_end_matches('\0').trim().to_string())
+    };
+    let name = read_string()?;
+    let symbol = read_string()?;
+    Some((name, symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_token() {
+        let usdc = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+        let token = resolve_token(&usdc).unwrap();
+        assert_eq!(token.symbol.as_deref(), Some("USDC"));
+        assert_eq!(token.decimals, Some(6));
+        assert_eq!(token.label().unwrap(), "USDC (USD Coin)");
+        assert!(resolve_token(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn test_parse_token_list() {
+        let wrapped = r#"{ "name": "Org", "tokens": [
+            { "chainId": 101, "address": "Mint1", "symbol": "ORG", "name": "Org Token", "decimals": 6 }
+        ] }"#;
+        let tokens = parse_token_list(wrapped).unwrap();
+        assert_eq!(tokens[0].mint, "Mint1");
+        assert_eq!(tokens[0].label().unwrap(), "ORG (Org Token)");
+        assert_eq!(tokens[0].source, TokenSource::TokenList);
+
+        let bare = r#"[{ "address": "Mint2", "symbol": "B", "decimals": 9, "logoURI": "x" }]"#;
+        assert_eq!(parse_token_list(bare).unwrap()[0].decimals, Some(9));
+        assert!(parse_token_list(r#"{ "mints": [] }"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_metaplex_metadata() {
+        let mut data = vec![4u8];
+        data.extend([0u8; 64]);
+        for (s, padded) in [("Test Token", 32), ("TST", 10)] {
+            data.extend((padded as u32).to_le_bytes());
+            let mut bytes = s.as_bytes().to_vec();
+            bytes.resize(padded, 0);
+            data.extend(bytes);
+        }
+        assert_eq!(
+            parse_metaplex_metadata(&data),
+            Some(("Test Token".to_string(), "TST".to_string()))
+        );
+        assert_eq!(parse_metaplex_metadata(&data[..40]), None);
+    }
+}
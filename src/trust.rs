@@ -0,0 +1,256 @@
+//! Trust policy for IDL sources
+//!
+//! A service that decodes with Periscope trusts whatever IDL it loads. The
+//! `[trust]` config table restricts that per source kind:
+//!
+//! ```toml
+//! [trust]
+//! # On-chain IDLs must be published by one of these IDL authorities
+//! idl_authorities = ["<PUBKEY>"]
+//! # IDL files (and bundle entries) need a `periscope sign` signature by one of these keys
+//! signers = ["<PUBKEY>"]
+//! # URL IDLs must have this canonical hash; with require_pinned_urls, unlisted URLs are rejected
+//! require_pinned_urls = true
+//! [trust.pinned_urls]
+//! "https://example.com/idl.json" = "<CANONICAL_HASH>"
+//! ```
+//!
+//! An empty policy accepts everything.
+
+use crate::bundle::parse_bundle_ref;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{parse_idl_json, CANONICAL_VERSION};
+use crate::signing::IdlSignature;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fs;
+use std::str::FromStr;
+
+/// `[trust]` table in config.toml
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustPolicy {
+    /// On-chain IDLs must have one of these IDL authorities
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub idl_authorities: Vec<String>,
+
+    /// IDL files and bundle entries must be signed by one of these keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signers: Vec<String>,
+
+    /// Canonical hash each URL's IDL must have, by URL
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pinned_urls: BTreeMap<String, String>,
+
+    /// Reject URLs missing from `pinned_urls`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_pinned_urls: bool,
+}
+
+impl TrustPolicy {
+    /// True when nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.idl_authorities.is_empty()
+            && self.signers.is_empty()
+            && self.pinned_urls.is_empty()
+            && !self.require_pinned_urls
+    }
+
+    /// Check an on-chain IDL by its authority (`None` when unknown)
+    pub fn check_on_chain(&self, program_id: &str, authority: Option<&str>) -> PeriscopeResult<()> {
+        if self.idl_authorities.is_empty() {
+            return Ok(());
+        }
+        match authority {
+            Some(authority) if self.idl_authorities.iter().any(|a| a == authority) => Ok(()),
+            Some(authority) => Err(violation(format!(
+                "the IDL of {} is published by {}, not a trusted IDL authority",
+                program_id, authority
+            ))),
+            None => Err(violation(format!(
+                "the IDL authority of {} is unknown",
+                program_id
+            ))),
+        }
+    }
+
+    /// Check a URL IDL against its pinned hash
+    pub fn check_url(&self, url: &str, json: &str) -> PeriscopeResult<()> {
+        let Some(pinned) = self.pinned_urls.get(url) else {
+            if self.require_pinned_urls {
+                return Err(violation(format!("{} has no pinned hash", url)));
+            }
+            return Ok(());
+        };
+        let actual = parse_idl_json(json)?.canonical_hash();
+        if !actual.eq_ignore_ascii_case(pinned) {
+            return Err(violation(format!(
+                "{} has hash {}, pinned {} (hashes are of canonical form v{}; pins from \
+                 another version of Periscope need updating)",
+                url, actual, pinned, CANONICAL_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check an IDL a registry served for `program_id`. Registries aren't
+    /// an on-chain or signed source, so while `idl_authorities` or `signers`
+    /// restrict those, only pinned registry URLs are accepted.
+    pub fn check_registry(&self, url: &str, json: &str, program_id: &str) -> PeriscopeResult<()> {
+        let restricted = !self.idl_authorities.is_empty() || !self.signers.is_empty();
+        if restricted && !self.pinned_urls.contains_key(url) {
+            return Err(violation(format!(
+                "{} is an unpinned registry URL and IDL sources are restricted",
+                url
+            )));
+        }
+        self.check_url(url, json)?;
+        let address = parse_idl_json(json)?.address;
+        if address != program_id {
+            return Err(violation(format!(
+                "{} serves the IDL of {}, not {}",
+                url, address, program_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check an IDL file or bundle entry (`bundle:FILE#PROGRAM_ID`) against
+    /// its signature file, see [`signature_path`]
+    pub fn check_file(&self, source: &str, json: &str) -> PeriscopeResult<()> {
+        if self.signers.is_empty() {
+            return Ok(());
+        }
+        let idl = parse_idl_json(json)?;
+        let path = signature_path(source, &idl.address);
+        let signature: IdlSignature = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .ok_or_else(|| violation(format!("{} has no signature ({})", source, path)))?;
+
+        let signer = self
+            .signers
+            .iter()
+            .find(|s| **s == signature.signer)
+            .and_then(|s| Pubkey::from_str(s).ok())
+            .ok_or_else(|| {
+                violation(format!(
+                    "{} is signed by {}, not a trusted signer",
+                    source, signature.signer
+                ))
+            })?;
+        signature
+            .verify(&idl, &signer)
+            .map_err(|e| violation(format!("{}: {}", source, e)))
+    }
+}
+
+/// Where the signature of an IDL file is expected: `<file>.sig.json`, or
+/// `<bundle>.<program_id>.sig.json` for a bundle entry
+pub fn signature_path(source: &str, address: &str) -> String {
+    match parse_bundle_ref(source) {
+        Some((bundle, program_id)) => {
+            format!("{}.{}.sig.json", bundle, program_id.unwrap_or(address))
+        }
+        None => format!("{}.sig.json", source),
+    }
+}
+
+fn violation(reason: String) -> PeriscopeError {
+    PeriscopeError::PolicyViolation(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_checks() {
+        let json = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": []
+        }"#;
+        let hash = parse_idl_json(json).unwrap().canonical_hash();
+        let authority = Pubkey::new_unique().to_string();
+        let url = "https://example.com/idl.json";
+
+        let policy: TrustPolicy = serde_json::from_value(serde_json::json!({
+            "idl_authorities": [authority],
+            "require_pinned_urls": true,
+            "pinned_urls": { url: hash }
+        }))
+        .unwrap();
+
+        assert!(policy.check_on_chain("p", Some(&authority)).is_ok());
+        assert!(policy.check_on_chain("p", Some("other")).is_err());
+        assert!(policy.check_on_chain("p", None).is_err());
+        assert!(policy.check_url(url, json).is_ok());
+        assert!(policy
+            .check_url(url, &json.replace("vault", "evil"))
+            .is_err());
+        assert!(policy
+            .check_url("https://example.com/other.json", json)
+            .is_err());
+        // Registries: pinned and for the right program only while on-chain
+        // IDLs are restricted
+        let program_id = "11111111111111111111111111111111";
+        assert!(policy.check_registry(url, json, program_id).is_ok());
+        assert!(policy.check_registry(url, json, "other").is_err());
+        let open = TrustPolicy::default();
+        assert!(open
+            .check_registry("https://example.com/other.json", json, program_id)
+            .is_ok());
+        let restricted = TrustPolicy {
+            signers: vec![authority.clone()],
+            ..Default::default()
+        };
+        assert!(restricted
+            .check_registry("https://example.com/other.json", json, program_id)
+            .is_err());
+        // No signers configured: files are accepted
+        assert!(policy.check_file("idl.json", json).is_ok());
+        assert!(TrustPolicy::default().is_empty());
+
+        assert_eq!(signature_path("idl.json", "x"), "idl.json.sig.json");
+        assert_eq!(signature_path("bundle:p.psc", "x"), "p.psc.x.sig.json");
+    }
+
+    #[test]
+    fn test_sign_and_verify_under_signers_policy() {
+        use crate::bundle::read_local_idl_json;
+        use crate::signing::sign_idl;
+        use solana_sdk::signature::{Keypair, Signer};
+
+        let json = r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": []
+        }"#;
+        let path =
+            std::env::temp_dir().join(format!("periscope-trust-{}.json", std::process::id()));
+        let source = path.to_string_lossy().into_owned();
+        fs::write(&path, json).unwrap();
+        let keypair = Keypair::new();
+        let policy = TrustPolicy {
+            signers: vec![keypair.pubkey().to_string()],
+            ..Default::default()
+        };
+
+        // Not signed yet: rejected when loaded, but sign can still read it
+        assert!(policy.check_file(&source, json).is_err());
+        let idl = parse_idl_json(&read_local_idl_json(&source).unwrap()).unwrap();
+        let signature = sign_idl(&idl, &keypair, 1_700_000_000);
+        let sig_path = signature_path(&source, &idl.address);
+        fs::write(&sig_path, serde_json::to_string(&signature).unwrap()).unwrap();
+
+        // And verify reads it the same way
+        let read = read_local_idl_json(&source);
+        let checked = policy.check_file(&source, json);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&sig_path);
+        let idl = parse_idl_json(&read.unwrap()).unwrap();
+        assert!(signature.verify(&idl, &keypair.pubkey()).is_ok());
+        assert!(checked.is_ok());
+    }
+}
@@ -0,0 +1,253 @@
+//! On-demand update checks and self-update
+//!
+//! Nothing here runs automatically: `periscope self-update --check` asks the
+//! GitHub releases API (falling back to crates.io) for the latest version,
+//! and `periscope self-update` replaces the running binary with the release
+//! asset for this platform after verifying its published SHA-256 checksum.
+//! No information beyond the HTTP request itself is sent.
+
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{hex, USER_AGENT};
+use serde::Deserialize;
+use solana_sdk::hash::hash;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Version of this build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Crate name on crates.io
+const CRATE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// GitHub repository (owner/name)
+const GITHUB_REPO: &str = "Mctursh/Periscope";
+
+/// Timeout for update requests
+const UPDATE_TIMEOUT_SECS: u64 = 30;
+
+/// Latest published release
+#[derive(Debug, Clone)]
+pub struct Release {
+    /// Version without the `v` prefix
+    pub version: String,
+
+    /// Downloadable binaries (empty for crates.io-only releases)
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// A file attached to a GitHub release
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    #[serde(rename = "browser_download_url")]
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct CratesIoResponse {
+    #[serde(rename = "crate")]
+    krate: CratesIoCrate,
+}
+
+#[derive(Deserialize)]
+struct CratesIoCrate {
+    max_stable_version: String,
+}
+
+impl Release {
+    /// Whether this release is newer than the running binary
+    pub fn is_newer(&self) -> bool {
+        is_newer(&self.version, CURRENT_VERSION)
+    }
+
+    /// Binary asset for the current platform, with its checksum file
+    pub fn platform_asset(&self) -> Option<(&ReleaseAsset, Option<&ReleaseAsset>)> {
+        let name = platform_asset_name();
+        let binary = self.assets.iter().find(|a| a.name == name)?;
+        let checksum = self
+            .assets
+            .iter()
+            .find(|a| a.name == format!("{}.sha256", name));
+        Some((binary, checksum))
+    }
+}
+
+/// Asset name of the binary for this platform, e.g. `periscope-x86_64-linux`
+pub fn platform_asset_name() -> String {
+    let ext = if cfg!(windows) { ".exe" } else { "" };
+    format!(
+        "periscope-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        ext
+    )
+}
+
+fn http_client() -> PeriscopeResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(UPDATE_TIMEOUT_SECS))
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| PeriscopeError::NetworkError(format!("Failed to create HTTP client: {}", e)))
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> PeriscopeResult<reqwest::Response> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| PeriscopeError::NetworkError(format!("HTTP request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(PeriscopeError::HttpError {
+            status: response.status().as_u16(),
+            url: url.to_string(),
+        });
+    }
+    Ok(response)
+}
+
+/// Look up the latest release: GitHub first, crates.io if GitHub has none
+pub async fn check_latest() -> PeriscopeResult<Release> {
+    let client = http_client()?;
+
+    let github_url = format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        GITHUB_REPO
+    );
+    if let Ok(response) = get(&client, &github_url).await {
+        if let Ok(release) = response.json::<GithubRelease>().await {
+            return Ok(Release {
+                version: release.tag_name.trim_start_matches('v').to_string(),
+                assets: release.assets,
+            });
+        }
+    }
+
+    let crates_url = format!("https://crates.io/api/v1/crates/{}", CRATE_NAME);
+    let response: CratesIoResponse =
+        get(&client, &crates_url).await?.json().await.map_err(|e| {
+            PeriscopeError::NetworkError(format!("Invalid crates.io response: {}", e))
+        })?;
+
+    Ok(Release {
+        version: response.krate.max_stable_version,
+        assets: Vec::new(),
+    })
+}
+
+/// Download `binary`, verify it against `checksum` and replace the file at `target`.
+pub async fn install_asset(
+    binary: &ReleaseAsset,
+    checksum: &ReleaseAsset,
+    target: &Path,
+) -> PeriscopeResult<()> {
+    let client = http_client()?;
+
+    let checksum_text = get(&client, &checksum.url)
+        .await?
+        .text()
+        .await
+        .map_err(|e| PeriscopeError::NetworkError(format!("Failed to read checksum: {}", e)))?;
+    let expected = parse_checksum(&checksum_text).ok_or_else(|| {
+        PeriscopeError::NetworkError(format!("{} is not a SHA-256 checksum", checksum.name))
+    })?;
+
+    let bytes = get(&client, &binary.url)
+        .await?
+        .bytes()
+        .await
+        .map_err(|e| PeriscopeError::NetworkError(format!("Failed to download binary: {}", e)))?;
+
+    let actual = sha256_hex(&bytes);
+    if actual != expected {
+        return Err(PeriscopeError::ChecksumMismatch {
+            file: binary.name.clone(),
+            expected,
+            actual,
+        });
+    }
+
+    replace_binary(target, &bytes)
+}
+
+/// Write the new binary next to the old one, then rename it over (atomic on Unix)
+fn replace_binary(target: &Path, bytes: &[u8]) -> PeriscopeResult<()> {
+    let staged = target.with_extension("new");
+    fs::write(&staged, bytes)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(target)
+            .map(|m| m.permissions().mode())
+            .unwrap_or(0o755);
+        fs::set_permissions(&staged, fs::Permissions::from_mode(mode))?;
+    }
+
+    // Windows can't overwrite a running executable, but it can rename it away
+    #[cfg(windows)]
+    {
+        let old = target.with_extension("old");
+        fs::rename(target, &old)?;
+        if let Err(e) = fs::rename(&staged, target) {
+            // Put the old binary back rather than leave no binary at all
+            let _ = fs::rename(&old, target);
+            let _ = fs::remove_file(&staged);
+            return Err(e.into());
+        }
+    }
+
+    #[cfg(not(windows))]
+    fs::rename(&staged, target)?;
+    Ok(())
+}
+
+/// First token of a `sha256sum`-style line, if it is 64 hex digits
+fn parse_checksum(text: &str) -> Option<String> {
+    let digest = text.split_whitespace().next()?.to_ascii_lowercase();
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())).then_some(digest)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&hash(bytes).to_bytes())
+}
+
+/// Compare dotted numeric versions; pre-release suffixes are ignored
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(candidate) > parse(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_and_checksum() {
+        assert!(is_newer("0.2.0", "0.1.9"));
+        assert!(is_newer("1.10.0", "1.9.3"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0-rc.1", "0.1.0"));
+
+        let digest = sha256_hex(b"periscope");
+        let line = format!("{}  periscope-x86_64-linux\n", digest.to_uppercase());
+        assert_eq!(parse_checksum(&line), Some(digest));
+        assert_eq!(parse_checksum("not-a-digest"), None);
+    }
+}
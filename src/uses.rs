@@ -0,0 +1,169 @@
+//! Where a type is used
+//!
+//! The reverse of the type dependency graph: every instruction argument and
+//! every field of an account, event or other type that mentions a given
+//! type, including inside `Vec`, `Option`, arrays and generic arguments.
+//! `periscope uses Fee` answers "what breaks if `Fee` changes".
+
+use crate::idl::{
+    format_type, Idl, IdlEnumFields, IdlGenericArg, IdlType, IdlTypeComplex, IdlTypeDef,
+    IdlTypeDefTy,
+};
+use serde::Serialize;
+
+/// One reference to a type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeUse {
+    /// What refers to it: instruction, account, event or type
+    pub kind: &'static str,
+
+    /// Name of the instruction or type
+    pub name: String,
+
+    /// Argument or field (`Variant.field`, `Variant.0` in enums)
+    pub field: String,
+
+    /// Type of the argument or field as written (`Vec<Fee>`)
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// Every use of the type `name` in `idl`: instruction arguments first, then
+/// fields of type definitions, each in IDL order
+pub fn type_uses(idl: &Idl, name: &str) -> Vec<TypeUse> {
+    let mut uses = Vec::new();
+    let mut push = |kind, owner: &str, field: String, ty: &IdlType| {
+        let mut names = Vec::new();
+        references(ty, &mut names);
+        if names.contains(&name) {
+            uses.push(TypeUse {
+                kind,
+                name: owner.to_string(),
+                field,
+                ty: format_type(ty),
+            });
+        }
+    };
+
+    for ix in &idl.instructions {
+        for arg in &ix.args {
+            push("instruction", &ix.name, arg.name.clone(), &arg.ty);
+        }
+    }
+    for def in &idl.types {
+        let kind = if idl.accounts.iter().any(|a| a.name == def.name) {
+            "account"
+        } else if idl.events.iter().any(|e| e.name == def.name) {
+            "event"
+        } else {
+            "type"
+        };
+        for (field, ty) in fields(def) {
+            push(kind, &def.name, field, ty);
+        }
+    }
+    uses
+}
+
+/// Names of the defined types `ty` mentions, generic arguments included
+pub fn references<'a>(ty: &'a IdlType, out: &mut Vec<&'a str>) {
+    let IdlType::Complex(complex) = ty else {
+        return;
+    };
+    match complex {
+        IdlTypeComplex::Vec(inner)
+        | IdlTypeComplex::Option(inner)
+        | IdlTypeComplex::Array(inner, _) => references(inner, out),
+        IdlTypeComplex::Defined { name, generics } => {
+            out.push(name);
+            for arg in generics {
+                if let IdlGenericArg::Type { ty } = arg {
+                    references(ty, out);
+                }
+            }
+        }
+        IdlTypeComplex::Generic(_) => {}
+    }
+}
+
+/// Fields of a type definition by name, enum variant fields as
+/// `Variant.field` / `Variant.0`
+pub fn fields(def: &IdlTypeDef) -> Vec<(String, &IdlType)> {
+    match &def.ty {
+        IdlTypeDefTy::Struct { fields } => fields.iter().map(|f| (f.name.clone(), &f.ty)).collect(),
+        IdlTypeDefTy::Enum { variants } => variants
+            .iter()
+            .flat_map(|variant| match &variant.fields {
+                None => Vec::new(),
+                Some(IdlEnumFields::Named(fields)) => fields
+                    .iter()
+                    .map(|f| (format!("{}.{}", variant.name, f.name), &f.ty))
+                    .collect(),
+                Some(IdlEnumFields::Tuple(types)) => types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| (format!("{}.{}", variant.name, i), ty))
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::parse_idl_json;
+
+    #[test]
+    fn test_type_uses() {
+        let idl = parse_idl_json(
+            r#"{
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [
+                { "name": "set_fees", "discriminator": [1,0,0,0,0,0,0,0], "accounts": [], "args": [
+                    { "name": "fees", "type": { "vec": { "defined": { "name": "Fee" } } } },
+                    { "name": "bump", "type": "u8" }
+                ] }
+            ],
+            "accounts": [{ "name": "Pool", "discriminator": [1,1,1,1,1,1,1,1] }],
+            "events": [{ "name": "FeeChanged", "discriminator": [2,2,2,2,2,2,2,2] }],
+            "types": [
+                { "name": "Fee", "type": { "kind": "struct", "fields": [{ "name": "bps", "type": "u16" }] } },
+                { "name": "Pool", "type": { "kind": "struct", "fields": [
+                    { "name": "fee", "type": { "option": { "defined": { "name": "Fee" } } } },
+                    { "name": "cap", "type": "u64" }
+                ] } },
+                { "name": "FeeChanged", "type": { "kind": "struct", "fields": [
+                    { "name": "new", "type": { "defined": {
+                        "name": "Wrapper",
+                        "generics": [{ "kind": "type", "type": { "defined": { "name": "Fee" } } }]
+                    } } }
+                ] } },
+                { "name": "Tier", "type": { "kind": "enum", "variants": [
+                    { "name": "Flat", "fields": [{ "defined": { "name": "Fee" } }] },
+                    { "name": "Free" }
+                ] } }
+            ]
+        }"#,
+        )
+        .unwrap();
+
+        let uses = type_uses(&idl, "Fee");
+        let found: Vec<(&str, &str, &str)> = uses
+            .iter()
+            .map(|u| (u.kind, u.name.as_str(), u.field.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("instruction", "set_fees", "fees"),
+                ("account", "Pool", "fee"),
+                ("event", "FeeChanged", "new"),
+                ("type", "Tier", "Flat.0"),
+            ]
+        );
+        assert_eq!(uses[1].ty, "Option<Fee>");
+        assert!(type_uses(&idl, "Tier").is_empty());
+    }
+}
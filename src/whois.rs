@@ -0,0 +1,274 @@
+//! Address classification: what is this pubkey?
+//!
+//! Looks at the account's existence, owner, executable flag and data to tell
+//! programs, program data, IDL accounts, token accounts, wallets and Anchor
+//! accounts apart.
+
+use crate::builtin_programs::{
+    builtin_program_name, decode_builtin_account, known_program_name, BPF_LOADER_UPGRADEABLE_ID,
+    SYSTEM_PROGRAM_ID,
+};
+use crate::decode::decode_account;
+use crate::error::{PeriscopeError, PeriscopeResult};
+use crate::idl::{fetch_account, fetch_on_chain_idl, get_idl_address, has_idl, parse_idl_json};
+use crate::tokens::fetch_token;
+use serde_json::{json, Map, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+/// Upgradeable loader account state tags (bincode u32)
+const LOADER_STATE_BUFFER: u32 = 1;
+const LOADER_STATE_PROGRAM: u32 = 2;
+const LOADER_STATE_PROGRAM_DATA: u32 = 3;
+
+/// What kind of address a pubkey is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressKind {
+    /// No account exists at this address
+    Unused,
+    /// Executable program
+    Program,
+    /// ProgramData account of an upgradeable program
+    ProgramData,
+    /// Upgradeable loader buffer (pending deploy)
+    ProgramBuffer,
+    /// Anchor IDL account of a program
+    IdlAccount { program: Pubkey },
+    /// System-owned account (wallet)
+    Wallet,
+    /// Account owned by a builtin program (token mint/account, stake, vote)
+    Builtin { account_type: String },
+    /// Account owned by another program, with its Anchor type when known
+    ProgramAccount { account_type: Option<String> },
+}
+
+impl fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressKind::Unused => write!(f, "Unused address (no account)"),
+            AddressKind::Program => write!(f, "Program"),
+            AddressKind::ProgramData => write!(f, "Program data"),
+            AddressKind::ProgramBuffer => write!(f, "Program buffer"),
+            AddressKind::IdlAccount { program } => write!(f, "Anchor IDL account of {}", program),
+            AddressKind::Wallet => write!(f, "System account (wallet)"),
+            AddressKind::Builtin { account_type } => write!(f, "{}", account_type),
+            AddressKind::ProgramAccount {
+                account_type: Some(name),
+            } => write!(f, "Anchor account ({})", name),
+            AddressKind::ProgramAccount { account_type: None } => {
+                write!(f, "Program-owned account")
+            }
+        }
+    }
+}
+
+/// Classification of an address
+#[derive(Debug, Clone)]
+pub struct AddressInfo {
+    /// The address classified
+    pub address: Pubkey,
+
+    /// What the address is
+    pub kind: AddressKind,
+
+    /// Whether the address is on the ed25519 curve (off-curve addresses are PDAs)
+    pub on_curve: bool,
+
+    /// Owner program, if the account exists
+    pub owner: Option<Pubkey>,
+
+    /// Balance in lamports
+    pub lamports: u64,
+
+    /// Account data size
+    pub data_len: usize,
+
+    /// Whether the balance covers rent exemption for the data size
+    pub rent_exempt: Option<bool>,
+
+    /// Kind-specific details (loader, upgrade authority, ...), in display order
+    pub details: Vec<(String, String)>,
+}
+
+impl AddressInfo {
+    /// JSON representation for `--output json`
+    pub fn to_json(&self) -> Value {
+        let details: Map<String, Value> = self
+            .details
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        json!({
+            "address": self.address.to_string(),
+            "kind": self.kind.to_string(),
+            "on_curve": self.on_curve,
+            "owner": self.owner.map(|o| o.to_string()),
+            "lamports": self.lamports,
+            "data_len": self.data_len,
+            "rent_exempt": self.rent_exempt,
+            "details": details,
+        })
+    }
+}
+
+/// Classify an address by fetching its account. The IDL of the owning
+/// program, used to name the account type, is capped at `limit` bytes
+/// decompressed.
+pub fn classify_address(
+    client: &RpcClient,
+    address: &Pubkey,
+    limit: usize,
+) -> PeriscopeResult<AddressInfo> {
+    let on_curve = address.is_on_curve();
+
+    let account = match fetch_account(client, address) {
+        Ok(account) => account,
+        Err(PeriscopeError::AccountNotFound(_)) => {
+            return Ok(AddressInfo {
+                address: *address,
+                kind: AddressKind::Unused,
+                on_curve,
+                owner: None,
+                lamports: 0,
+                data_len: 0,
+                rent_exempt: None,
+                details: Vec::new(),
+            });
+        }
+        Err(e) => return Err(e),
+    };
+
+    let rent_exempt = client
+        .get_minimum_balance_for_rent_exemption(account.data.len())
+        .ok()
+        .map(|min| account.lamports >= min);
+
+    let mut details = Vec::new();
+    let owner = account.owner;
+
+    let kind = if account.executable {
+        details.push((
+            "Loader".to_string(),
+            known_program_name(&owner).unwrap_or("unknown").to_string(),
+        ));
+        if let Some(name) = known_program_name(address) {
+            details.push(("Known as".to_string(), name.to_string()));
+        }
+        if owner == BPF_LOADER_UPGRADEABLE_ID {
+            if let Some(programdata) = read_loader_pubkey(&account.data, LOADER_STATE_PROGRAM) {
+                details.push(("Program data".to_string(), programdata.to_string()));
+                if let Ok(data_account) = fetch_account(client, &programdata) {
+                    details.extend(program_data_details(&data_account.data));
+                }
+            }
+        }
+        let has_idl = has_idl(client, address)?;
+        details.push((
+            "Anchor IDL".to_string(),
+            if has_idl {
+                get_idl_address(address)?.to_string()
+            } else {
+                "not published".to_string()
+            },
+        ));
+        AddressKind::Program
+    } else if owner == BPF_LOADER_UPGRADEABLE_ID {
+        match loader_state(&account.data) {
+            Some(LOADER_STATE_PROGRAM_DATA) => {
+                details.extend(program_data_details(&account.data));
+                AddressKind::ProgramData
+            }
+            Some(LOADER_STATE_BUFFER) => AddressKind::ProgramBuffer,
+            _ => AddressKind::ProgramAccount { account_type: None },
+        }
+    } else if owner == SYSTEM_PROGRAM_ID {
+        AddressKind::Wallet
+    } else if let Some(decoded) = decode_builtin_account(&owner, &account.data) {
+        let account_type = match decoded {
+            Ok(decoded) => {
+                let mint = match decoded.name.as_str() {
+                    "Mint" => Some(*address),
+                    "TokenAccount" => decoded.value["mint"].as_str().and_then(|m| m.parse().ok()),
+                    _ => None,
+                };
+                let token = mint.and_then(|mint| fetch_token(client, &mint).ok().flatten());
+                if let Some(label) = token.and_then(|t| t.label()) {
+                    details.push(("Token".to_string(), label));
+                }
+                format!(
+                    "{} {}",
+                    builtin_program_name(&owner).unwrap_or_default(),
+                    decoded.name
+                )
+            }
+            Err(_) => format!(
+                "{} account",
+                builtin_program_name(&owner).unwrap_or_default()
+            ),
+        };
+        AddressKind::Builtin { account_type }
+    } else if get_idl_address(&owner).ok().as_ref() == Some(address) {
+        AddressKind::IdlAccount { program: owner }
+    } else {
+        let account_type = fetch_on_chain_idl(client, &owner, limit)
+            .and_then(|fetched| parse_idl_json(&fetched.json))
+            .ok()
+            .and_then(|idl| decode_account(&idl, &account.data).ok())
+            .map(|decoded| decoded.name);
+        AddressKind::ProgramAccount { account_type }
+    };
+
+    Ok(AddressInfo {
+        address: *address,
+        kind,
+        on_curve,
+        owner: Some(owner),
+        lamports: account.lamports,
+        data_len: account.data.len(),
+        rent_exempt,
+        details,
+    })
+}
+
+fn loader_state(data: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+/// Pubkey stored right after the state tag, if the state matches
+fn read_loader_pubkey(data: &[u8], state: u32) -> Option<Pubkey> {
+    if loader_state(data)? != state {
+        return None;
+    }
+    let bytes: [u8; 32] = data.get(4..36)?.try_into().ok()?;
+    Some(Pubkey::new_from_array(bytes))
+}
+
+/// ProgramData header: tag, deploy slot, `Option<Pubkey>` upgrade authority
+fn program_data_details(data: &[u8]) -> Vec<(String, String)> {
+    let mut details = Vec::new();
+    if loader_state(data) != Some(LOADER_STATE_PROGRAM_DATA) {
+        return details;
+    }
+
+    if let Some(slot) = data.get(4..12).and_then(|b| b.try_into().ok()) {
+        details.push((
+            "Last deployed slot".to_string(),
+            u64::from_le_bytes(slot).to_string(),
+        ));
+    }
+
+    let authority = match data.get(12) {
+        Some(1) => data
+            .get(13..45)
+            .and_then(|b| <[u8; 32]>::try_from(b).ok())
+            .map(|b| Pubkey::new_from_array(b).to_string()),
+        _ => None,
+    };
+    details.push((
+        "Upgrade authority".to_string(),
+        authority.unwrap_or_else(|| "none (immutable)".to_string()),
+    ));
+
+    details
+}
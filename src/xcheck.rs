@@ -0,0 +1,259 @@
+//! Cross-cluster IDL consistency
+//!
+//! `periscope xcheck` fetches a program's IDL from several clusters and
+//! lists the instructions, accounts, types, events and errors that are
+//! missing on some of them or defined differently. Items are compared by
+//! name; "different" means their full IDL definitions don't match.
+
+use crate::config::resolve_cluster;
+use crate::error::PeriscopeResult;
+use crate::idl::{fetch_on_chain_idl, parse_idl_json, rpc_client, Idl};
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+
+/// IDL of one cluster, or why it couldn't be fetched
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterIdl {
+    /// Cluster name or RPC URL, as given
+    pub cluster: String,
+
+    /// IDL `metadata.version`
+    pub version: Option<String>,
+
+    /// Fetch error (no IDL, RPC failure); the cluster is left out of the comparison
+    pub error: Option<String>,
+
+    #[serde(skip)]
+    pub idl: Option<Idl>,
+}
+
+/// An item not defined identically on every cluster
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mismatch {
+    /// `instruction`, `account`, `type`, `event` or `error`
+    pub kind: &'static str,
+
+    /// Item name
+    pub name: String,
+
+    /// Clusters that define the item
+    pub present_on: Vec<String>,
+
+    /// Clusters that don't
+    pub missing_on: Vec<String>,
+
+    /// Whether the clusters that define it disagree on the definition
+    pub differs: bool,
+}
+
+/// Items of one kind added, removed and changed between two IDLs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KindChanges {
+    /// `instruction`, `account`, `type`, `event` or `error`
+    pub kind: &'static str,
+
+    /// Items only in the newer IDL
+    pub added: usize,
+
+    /// Items only in the older IDL
+    pub removed: usize,
+
+    /// Items in both, defined differently
+    pub changed: usize,
+}
+
+/// Fetch the IDL of `program_id` from each cluster, each capped at `limit`
+/// bytes decompressed
+///
+/// Unknown cluster names fail the whole check; fetch failures are recorded
+/// per cluster.
+pub fn fetch_clusters(
+    program_id: &Pubkey,
+    clusters: &[String],
+    limit: usize,
+) -> PeriscopeResult<Vec<ClusterIdl>> {
+    let urls = clusters
+        .iter()
+        .map(|c| resolve_cluster(c))
+        .collect::<PeriscopeResult<Vec<_>>>()?;
+
+    Ok(clusters
+        .iter()
+        .zip(urls)
+        .map(|(cluster, url)| {
+            let client = rpc_client(&url);
+            let fetched = fetch_on_chain_idl(&client, program_id, limit);
+            match fetched.and_then(|fetched| parse_idl_json(&fetched.json)) {
+                Ok(idl) => ClusterIdl {
+                    cluster: cluster.clone(),
+                    version: Some(idl.metadata.version.clone()),
+                    error: None,
+                    idl: Some(idl),
+                },
+                Err(e) => ClusterIdl {
+                    cluster: cluster.clone(),
+                    version: None,
+                    error: Some(e.to_string()),
+                    idl: None,
+                },
+            }
+        })
+        .collect())
+}
+
+/// Item kind, with its position in the output order
+type Kind = (usize, &'static str);
+
+/// Definitions of one item kind: name -> (cluster, definition)
+type ByName<'a> = BTreeMap<String, Vec<(&'a str, Value)>>;
+
+/// Items missing on some clusters or defined differently, by kind then name
+pub fn compare(idls: &[(&str, &Idl)]) -> Vec<Mismatch> {
+    let mut items: BTreeMap<Kind, ByName> = BTreeMap::new();
+    for (cluster, idl) in idls {
+        for (kind, name, definition) in definitions(idl) {
+            items
+                .entry(kind)
+                .or_default()
+                .entry(name)
+                .or_default()
+                .push((cluster, definition));
+        }
+    }
+
+    let mut mismatches = Vec::new();
+    for ((_, kind), by_name) in items {
+        for (name, defined) in by_name {
+            let present_on: Vec<String> = defined.iter().map(|(c, _)| c.to_string()).collect();
+            let missing_on: Vec<String> = idls
+                .iter()
+                .map(|(c, _)| c.to_string())
+                .filter(|c| !present_on.contains(c))
+                .collect();
+            let differs = defined.windows(2).any(|w| w[0].1 != w[1].1);
+            if !missing_on.is_empty() || differs {
+                mismatches.push(Mismatch {
+                    kind,
+                    name,
+                    present_on,
+                    missing_on,
+                    differs,
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+/// How `new` differs from `old`, per item kind in display order; kinds
+/// without changes are left out
+pub fn change_counts(old: &Idl, new: &Idl) -> Vec<KindChanges> {
+    let mut counts: Vec<KindChanges> = Vec::new();
+    for mismatch in compare(&[("old", old), ("new", new)]) {
+        if counts.last().map(|c| c.kind) != Some(mismatch.kind) {
+            counts.push(KindChanges {
+                kind: mismatch.kind,
+                added: 0,
+                removed: 0,
+                changed: 0,
+            });
+        }
+        let count = counts.last_mut().expect("pushed above");
+        if mismatch.missing_on.iter().any(|c| c == "old") {
+            count.added += 1;
+        } else if mismatch.missing_on.iter().any(|c| c == "new") {
+            count.removed += 1;
+        } else {
+            count.changed += 1;
+        }
+    }
+    counts
+}
+
+/// Every named item of an IDL with its definition, keyed for display order
+fn definitions(idl: &Idl) -> Vec<(Kind, String, Value)> {
+    fn json(item: &impl Serialize) -> Value {
+        serde_json::to_value(item).unwrap_or(Value::Null)
+    }
+    let mut out = Vec::new();
+    for ix in &idl.instructions {
+        out.push(((0, "instruction"), ix.name.clone(), json(ix)));
+    }
+    for account in &idl.accounts {
+        out.push(((1, "account"), account.name.clone(), json(account)));
+    }
+    for ty in &idl.types {
+        out.push(((2, "type"), ty.name.clone(), json(ty)));
+    }
+    for event in &idl.events {
+        out.push(((3, "event"), event.name.clone(), json(event)));
+    }
+    for error in &idl.errors {
+        out.push(((4, "error"), error.name.clone(), json(error)));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn idl(instructions: Value, errors: Value) -> Idl {
+        serde_json::from_value(json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": instructions,
+            "errors": errors
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compare_clusters() {
+        let swap = json!({ "name": "swap", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1], "accounts": [], "args": [] });
+        let mainnet = idl(
+            json!([swap]),
+            json!([{ "code": 6000, "name": "Slippage", "msg": "Slippage exceeded" }]),
+        );
+        let devnet = idl(
+            json!([swap, { "name": "swap_v2", "discriminator": [2, 2, 2, 2, 2, 2, 2, 2], "accounts": [], "args": [] }]),
+            json!([{ "code": 6000, "name": "Slippage", "msg": "Too much slippage" }]),
+        );
+
+        let mismatches = compare(&[("mainnet", &mainnet), ("devnet", &devnet)]);
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].kind, "instruction");
+        assert_eq!(mismatches[0].name, "swap_v2");
+        assert_eq!(mismatches[0].missing_on, ["mainnet"]);
+        assert!(!mismatches[0].differs);
+        assert_eq!(mismatches[1].kind, "error");
+        assert!(mismatches[1].differs);
+        assert!(mismatches[1].missing_on.is_empty());
+
+        assert!(compare(&[("mainnet", &mainnet), ("testnet", &mainnet)]).is_empty());
+
+        let counts = change_counts(&mainnet, &devnet);
+        assert_eq!(
+            counts,
+            [
+                KindChanges {
+                    kind: "instruction",
+                    added: 1,
+                    removed: 0,
+                    changed: 0
+                },
+                KindChanges {
+                    kind: "error",
+                    added: 0,
+                    removed: 0,
+                    changed: 1
+                },
+            ]
+        );
+        assert_eq!(change_counts(&devnet, &mainnet)[0].removed, 1);
+        assert!(change_counts(&mainnet, &mainnet).is_empty());
+    }
+}